@@ -0,0 +1,31 @@
+use ezno_ast_generator::{class_member, expr, interface_member, jsx, module, r#type};
+use parser::{extractor::ExtractedFunctions, ASTNode, ToStringSettingsAndData};
+
+fn main() {
+	let mut functions = ExtractedFunctions::default();
+
+	let item = "Post";
+	let posts = r#type!(Array<#item>);
+	let posts_type = posts.clone();
+
+	let fields = ["title", "content"].map(|name| expr!(registerField(#name)));
+	let my_module = module!(
+		import { registerField } from "./fields";
+		#..fields;
+		function getPosts(): #posts {
+			return [];
+		}
+	);
+
+	let method = class_member!(render(message: string) { return #item + message; });
+	let member = interface_member!(posts: #posts_type);
+
+	let children = ["first", "second"];
+	let list = jsx!(<ul>{#..children}</ul>);
+
+	let settings = ToStringSettingsAndData(Default::default(), functions);
+	println!("{}", my_module.to_string(&settings));
+	println!("{}", method.to_string(&settings));
+	println!("{}", member.to_string(&settings));
+	println!("{}", list.to_string(&settings));
+}
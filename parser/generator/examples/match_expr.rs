@@ -0,0 +1,46 @@
+use ezno_ast_generator::{expr, match_expr};
+use parser::{ASTNode, Expression};
+
+fn simplify(expression: &Expression) -> Option<Expression> {
+	match_expr!(expression,
+		"$value + 0" => Some(value.clone()),
+		"!$condition ? $truthy : $falsy" => {
+			let (condition, truthy, falsy) = (condition.clone(), truthy.clone(), falsy.clone());
+			Some(expr!(#condition ? #falsy : #truthy))
+		},
+		"$array.indexOf($item) !== -1" => {
+			let (array, item) = (array.clone(), item.clone());
+			Some(expr!(#array.includes(#item)))
+		},
+		_ => None
+	)
+}
+
+/// Works on mutable references as well
+fn swap_operands(expression: &mut Expression) {
+	match_expr!(expression,
+		"$lhs + $rhs" => std::mem::swap(lhs, rhs),
+		"$call($$$arguments)" => arguments.reverse(),
+		_ => {}
+	)
+}
+
+fn main() {
+	for source in
+		["x + 0", "!isEmpty ? first : rest", "items.indexOf(y) !== -1", "a + b", "f(1, 2)"]
+	{
+		let expression = Expression::from_string(
+			source.to_owned(),
+			Default::default(),
+			parser::SourceId::NULL,
+			None,
+			Vec::new(),
+		)
+		.unwrap()
+		.0;
+
+		let mut expression = simplify(&expression).unwrap_or(expression);
+		swap_operands(&mut expression);
+		println!("{source} => {}", expression.to_string(&Default::default()));
+	}
+}
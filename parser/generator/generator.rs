@@ -1,12 +1,17 @@
 use proc_macro::{token_stream, Delimiter, Spacing, TokenStream, TokenTree};
 use proc_macro2::Span;
-use quote::{format_ident, quote};
+use quote::{format_ident, quote, quote_spanned};
+
+mod match_expr;
 
 /// Used for generating parser::ASTNodes using proc macros
 ///
 /// Turns token stream into string.
 /// - Finds expressions and registers cursor locations
 /// - Parses structure from string and turns it into Rust tokens
+///
+/// `#name` interpolates any value which implements `IntoAST` for the node at that position.
+/// `#..items` splices each item of an iterator into a list (arguments, array items, statements etc)
 #[proc_macro]
 pub fn expr(item: TokenStream) -> TokenStream {
 	token_stream_to_ast_node::<parser::Expression>(item)
@@ -17,9 +22,56 @@ pub fn stmt(item: TokenStream) -> TokenStream {
 	token_stream_to_ast_node::<parser::StatementOrDeclaration>(item)
 }
 
+/// Generates a [parser::TypeReference]. Use as `r#type!(Array<#item>)`
+#[proc_macro]
+pub fn r#type(item: TokenStream) -> TokenStream {
+	token_stream_to_ast_node::<parser::TypeReference>(item)
+}
+
+#[proc_macro]
+pub fn module(item: TokenStream) -> TokenStream {
+	token_stream_to_ast_node::<parser::Module>(item)
+}
+
+/// Note: functions are inserted into a `functions: parser::extractor::ExtractedFunctions` binding
+/// which must be in scope
+#[proc_macro]
+pub fn class_member(item: TokenStream) -> TokenStream {
+	token_stream_to_ast_node::<parser::declarations::classes::ClassMember>(item)
+}
+
+#[proc_macro]
+pub fn interface_member(item: TokenStream) -> TokenStream {
+	token_stream_to_ast_node::<parser::types::interface::InterfaceMember>(item)
+}
+
+#[proc_macro]
+pub fn jsx(item: TokenStream) -> TokenStream {
+	token_stream_to_ast_node::<parser::ast::JSXElement>(item)
+}
+
+/// Matches an expression against patterns, ignoring positions and ids
+///
+/// ```ignore
+/// match_expr!(expression,
+///     "$a + 0" => Some(a),
+///     "!$condition ? $a : $b" => None,
+///     _ => None
+/// )
+/// ```
+///
+/// `$name` binds the sub-expression (or property name) as a reference. `$$$name` binds the remaining
+/// items of a list as a slice. Works on `&Expression` and `&mut Expression`
+#[proc_macro]
+pub fn match_expr(item: TokenStream) -> TokenStream {
+	match_expr::match_expr(item.into()).into()
+}
+
 struct InterpolationPoint {
 	position: usize,
 	expr_name: String,
+	/// For `#..items`
+	is_splice: bool,
 }
 
 fn token_stream_to_ast_node<T: parser::ASTNode + self_rust_tokenize::SelfRustTokenize>(
@@ -27,12 +79,17 @@ fn token_stream_to_ast_node<T: parser::ASTNode + self_rust_tokenize::SelfRustTok
 ) -> TokenStream {
 	let mut cursor_locations = Vec::new();
 	let mut string = String::new();
-	parse_token_stream(item.into_iter(), &mut string, &mut cursor_locations);
+	if let Err((span, reason)) =
+		parse_token_stream(item.into_iter(), &mut string, &mut cursor_locations)
+	{
+		let span = Span::from(span);
+		return quote_spanned!(span=> compile_error!(#reason)).into();
+	}
 
 	let cursors = cursor_locations
 		.iter()
 		.enumerate()
-		.map(|(idx, InterpolationPoint { position, expr_name: _ })| {
+		.map(|(idx, InterpolationPoint { position, .. })| {
 			(
 				*position,
 				parser::CursorId(idx.try_into().unwrap(), std::marker::PhantomData::default()),
@@ -48,23 +105,31 @@ fn token_stream_to_ast_node<T: parser::ASTNode + self_rust_tokenize::SelfRustTok
 		cursors,
 	);
 
-	let node = match parse_result {
-		Ok(parser::ParseOutput(node, _)) => node,
+	let (node, functions) = match parse_result {
+		Ok(parser::ParseOutput(node, state)) => (node, state.function_extractor),
 		Err(err) => {
 			let reason = err.reason;
 			return quote!(compile_error!(#reason)).into();
 		}
 	};
 
-	let node_as_tokens = self_rust_tokenize::SelfRustTokenize::to_tokens(&node);
+	let node_as_tokens = parser::extractor::tokenize_with_functions(&node, &functions);
 
-	let interpolation_tokens = cursor_locations.iter().enumerate().map(
-		|(idx, InterpolationPoint { position: _, expr_name })| {
+	let splices = cursor_locations
+		.iter()
+		.enumerate()
+		.filter(|(_, point)| point.is_splice)
+		.map(|(idx, _)| format!("_cursor_{idx}"))
+		.collect::<Vec<_>>();
+	let node_as_tokens =
+		if splices.is_empty() { node_as_tokens } else { splice_lists(node_as_tokens, &splices) };
+
+	let interpolation_tokens =
+		cursor_locations.iter().enumerate().map(|(idx, InterpolationPoint { expr_name, .. })| {
 			let ident = format_ident!("_cursor_{idx}");
 			let expr_ident = proc_macro2::Ident::new(expr_name, Span::call_site());
 			quote!(let #ident = #expr_ident)
-		},
-	);
+		});
 
 	let tokens = quote! {
 		{
@@ -80,11 +145,12 @@ fn token_stream_to_ast_node<T: parser::ASTNode + self_rust_tokenize::SelfRustTok
 	tokens.into()
 }
 
+/// Errors with the span of the unexpected token and a reason
 fn parse_token_stream(
 	mut token_iter: token_stream::IntoIter,
 	string: &mut String,
 	cursor_locations: &mut Vec<InterpolationPoint>,
-) {
+) -> Result<(), (proc_macro::Span, &'static str)> {
 	let mut last_was_ident = false;
 	while let Some(token_tree) = token_iter.next() {
 		let current_is_ident = matches!(token_tree, TokenTree::Ident(_));
@@ -99,7 +165,7 @@ fn parse_token_stream(
 					Delimiter::None => ("", ""),
 				};
 				string.push_str(start);
-				parse_token_stream(group.stream().into_iter(), string, cursor_locations);
+				parse_token_stream(group.stream().into_iter(), string, cursor_locations)?;
 				string.push_str(end);
 			}
 			TokenTree::Ident(ident) => {
@@ -111,12 +177,34 @@ fn parse_token_stream(
 			TokenTree::Punct(punctuation) => {
 				let chr = punctuation.as_char();
 				if chr == '#' {
-					if let Some(TokenTree::Ident(ident)) = token_iter.next() {
-						let expr_name = ident.to_string();
-						cursor_locations
-							.push(InterpolationPoint { position: string.len(), expr_name });
-					} else {
-						panic!("Expected ident")
+					let mut next = token_iter.next();
+					let is_splice =
+						matches!(&next, Some(TokenTree::Punct(punct)) if punct.as_char() == '.');
+					if is_splice {
+						match token_iter.next() {
+							Some(TokenTree::Punct(punct)) if punct.as_char() == '.' => {}
+							token => {
+								let span = token.map_or(punctuation.span(), |token| token.span());
+								return Err((span, "Expected '..' to splice items"));
+							}
+						}
+						next = token_iter.next();
+					}
+					match next {
+						Some(TokenTree::Ident(ident)) => {
+							let expr_name = ident.to_string();
+							cursor_locations.push(InterpolationPoint {
+								position: string.len(),
+								expr_name,
+								is_splice,
+							});
+							// The cursor is emitted by the lexer at this whitespace
+							string.push(' ');
+						}
+						token => {
+							let span = token.map_or(punctuation.span(), |token| token.span());
+							return Err((span, "Expected the name of a variable to interpolate"));
+						}
 					}
 				} else {
 					let spacing = matches!(punctuation.spacing(), Spacing::Alone);
@@ -124,7 +212,8 @@ fn parse_token_stream(
 						string.push(' ');
 					}
 					string.push(chr);
-					if spacing {
+					// A space after `>` would be the start of text in JSX
+					if spacing && chr != '>' {
 						string.push(' ');
 					}
 				}
@@ -136,4 +225,124 @@ fn parse_token_stream(
 
 		last_was_ident = current_is_ident;
 	}
+	Ok(())
+}
+
+/// Number of tokens in `::std::vec![..]`, which is how `SelfRustTokenize` prints a [Vec]
+const VEC_MACRO_LENGTH: usize = 8;
+
+/// Rewrites `::std::vec![..]` lists that contain a splice cursor into a block which extends the list
+/// with each item of the spliced iterator
+fn splice_lists(stream: proc_macro2::TokenStream, splices: &[String]) -> proc_macro2::TokenStream {
+	use proc_macro2::{Group, TokenTree};
+
+	let tokens = stream.into_iter().collect::<Vec<_>>();
+	let mut output: Vec<TokenTree> = Vec::new();
+	let mut idx = 0;
+	while idx < tokens.len() {
+		if let Some(group) = vec_macro_at(&tokens, idx) {
+			let items = split_on_commas(group.stream());
+			if items.iter().any(|item| find_splice(item.clone(), splices).is_some()) {
+				let statements = items.into_iter().map(|item| {
+					let splice = find_splice(item.clone(), splices);
+					let item = splice_lists(item, splices);
+					if let Some(splice) = splice {
+						quote!(__items.extend(::std::iter::IntoIterator::into_iter(#splice).map(|#splice| #item));)
+					} else {
+						quote!(__items.push(#item);)
+					}
+				});
+				let block = quote!({
+					let mut __items = ::std::vec::Vec::new();
+					#(#statements)*
+					__items
+				});
+				output.extend(block);
+			} else {
+				output.extend(tokens[idx..idx + VEC_MACRO_LENGTH - 1].iter().cloned());
+				let mut new_group =
+					Group::new(group.delimiter(), splice_lists(group.stream(), splices));
+				new_group.set_span(group.span());
+				output.push(TokenTree::Group(new_group));
+			}
+			idx += VEC_MACRO_LENGTH;
+			continue;
+		}
+
+		match &tokens[idx] {
+			TokenTree::Group(group) => {
+				let mut new_group =
+					Group::new(group.delimiter(), splice_lists(group.stream(), splices));
+				new_group.set_span(group.span());
+				output.push(TokenTree::Group(new_group));
+			}
+			token => output.push(token.clone()),
+		}
+		idx += 1;
+	}
+	output.into_iter().collect()
+}
+
+/// Returns the group of `::std::vec![..]` starting at `idx`
+fn vec_macro_at(tokens: &[proc_macro2::TokenTree], idx: usize) -> Option<&proc_macro2::Group> {
+	use proc_macro2::TokenTree;
+
+	let Some([colon1, colon2, std, colon3, colon4, vec, bang, TokenTree::Group(group)]) =
+		tokens.get(idx..idx + VEC_MACRO_LENGTH)
+	else {
+		return None;
+	};
+	let is_punct =
+		|token: &TokenTree, chr| matches!(token, TokenTree::Punct(punct) if punct.as_char() == chr);
+	let is_ident =
+		|token: &TokenTree, name: &str| matches!(token, TokenTree::Ident(ident) if ident == name);
+	let is_vec = [colon1, colon2, colon3, colon4].into_iter().all(|token| is_punct(token, ':'))
+		&& is_ident(std, "std")
+		&& is_ident(vec, "vec")
+		&& is_punct(bang, '!')
+		&& group.delimiter() == proc_macro2::Delimiter::Bracket;
+	is_vec.then_some(group)
+}
+
+fn split_on_commas(stream: proc_macro2::TokenStream) -> Vec<proc_macro2::TokenStream> {
+	let mut items = Vec::new();
+	let mut current = proc_macro2::TokenStream::new();
+	for token in stream {
+		if matches!(&token, proc_macro2::TokenTree::Punct(punct) if punct.as_char() == ',') {
+			items.push(std::mem::take(&mut current));
+		} else {
+			current.extend(std::iter::once(token));
+		}
+	}
+	if !current.is_empty() {
+		items.push(current);
+	}
+	items
+}
+
+/// Finds a splice cursor in an item, not including ones in nested lists
+fn find_splice(item: proc_macro2::TokenStream, splices: &[String]) -> Option<proc_macro2::Ident> {
+	use proc_macro2::TokenTree;
+
+	let tokens = item.into_iter().collect::<Vec<_>>();
+	let mut idx = 0;
+	while idx < tokens.len() {
+		if vec_macro_at(&tokens, idx).is_some() {
+			idx += VEC_MACRO_LENGTH;
+			continue;
+		}
+		match &tokens[idx] {
+			TokenTree::Ident(ident) if splices.iter().any(|splice| ident == splice) => {
+				return Some(ident.clone());
+			}
+			TokenTree::Group(group) => {
+				if let Some(ident) = find_splice(group.stream(), splices) {
+					return Some(ident);
+				}
+			}
+			_ => {}
+		}
+		idx += 1;
+	}
+	None
 }
@@ -0,0 +1,486 @@
+//! Implementation of `match_expr!`. Patterns are parsed using the same metavariable to cursor
+//! replacement as [parser::query] and then turned into nested `if let`s over the [Expression] structure.
+//! Positions and ids are never compared.
+
+use std::collections::HashMap;
+
+use parser::{
+	ast::{LHSOfAssignment, VariableOrPropertyAccess},
+	expressions::{MultipleExpression, SpreadExpression},
+	query::{source_with_cursors, Metavariable},
+	ASTNode, Expression, ParseOutput, PropertyReference, SourceId,
+};
+use proc_macro2::{Ident, Span, TokenStream, TokenTree};
+use quote::{format_ident, quote};
+use self_rust_tokenize::SelfRustTokenize;
+
+struct Arm {
+	pattern: Expression,
+	metavariables: Vec<Metavariable>,
+	body: TokenStream,
+}
+
+pub(crate) fn match_expr(item: TokenStream) -> TokenStream {
+	let (scrutinee, arms, default) = match parse_arms(item) {
+		Ok(parts) => parts,
+		Err(reason) => return quote!(compile_error!(#reason)),
+	};
+
+	let mut arm_tokens = Vec::new();
+	for Arm { pattern, metavariables, body } in arms {
+		let mut generator = MatcherGenerator {
+			metavariables: &metavariables,
+			bound: HashMap::new(),
+			steps: Vec::new(),
+			counter: 0,
+		};
+		let target = format_ident!("__target");
+		if let Err(reason) = generator.expression(&pattern, &target) {
+			return quote!(compile_error!(#reason));
+		}
+
+		let matched = generator.steps.into_iter().rev().fold(
+			quote!(break '__match_expr { #body };),
+			|inner, step| match step {
+				Step::Check(check) => quote!(#check { #inner }),
+				Step::Bind(binding) => quote!(#binding #inner),
+			},
+		);
+		arm_tokens.push(quote!({
+			let __target = parser::Reborrow::reborrow(&mut __node);
+			#matched
+		}));
+	}
+
+	let default = default.unwrap_or_default();
+	quote!({
+		#[allow(unused_imports)]
+		use parser::{ast::*, expressions::*, Reborrow, Unbox};
+		#[allow(unused_mut)]
+		let mut __node = #scrutinee;
+		'__match_expr: {
+			#(#arm_tokens)*
+			#default
+		}
+	})
+}
+
+/// Splits `node, "pattern" => body, ..., _ => default`
+#[allow(clippy::type_complexity)]
+fn parse_arms(item: TokenStream) -> Result<(TokenStream, Vec<Arm>, Option<TokenStream>), String> {
+	let mut parts = Vec::new();
+	let mut current = Vec::new();
+	for token in item {
+		if matches!(&token, TokenTree::Punct(punct) if punct.as_char() == ',') {
+			parts.push(std::mem::take(&mut current));
+		} else {
+			current.push(token);
+		}
+	}
+	parts.push(current);
+
+	let mut parts = parts.into_iter();
+	let scrutinee = parts.next().unwrap_or_default();
+	if scrutinee.is_empty() {
+		return Err("expected an expression to match on".to_owned());
+	}
+
+	let mut arms = Vec::new();
+	let mut default = None;
+	for part in parts.filter(|part| !part.is_empty()) {
+		let is_arrow = matches!(
+			(part.get(1), part.get(2)),
+			(Some(TokenTree::Punct(equals)), Some(TokenTree::Punct(greater)))
+				if equals.as_char() == '=' && greater.as_char() == '>'
+		);
+		if !is_arrow || part.len() < 4 {
+			return Err("expected arm of the form `\"pattern\" => body`".to_owned());
+		}
+		let body = part[3..].iter().cloned().collect::<TokenStream>();
+		match &part[0] {
+			TokenTree::Ident(ident) if ident == "_" => {
+				default = Some(body);
+			}
+			TokenTree::Literal(literal) => {
+				let pattern = string_literal_value(&literal.to_string())
+					.ok_or_else(|| "expected pattern to be a string literal".to_owned())?;
+				let (source, cursors, metavariables) = source_with_cursors(&pattern);
+				let ParseOutput(pattern, _) = Expression::from_string(
+					source,
+					Default::default(),
+					SourceId::NULL,
+					None,
+					cursors,
+				)
+				.map_err(|err| format!("invalid pattern {pattern:?}: {}", err.reason))?;
+				arms.push(Arm { pattern, metavariables, body });
+			}
+			_ => return Err("expected pattern to be a string literal".to_owned()),
+		}
+	}
+	Ok((scrutinee.into_iter().collect(), arms, default))
+}
+
+/// Returns the content of a (possibly raw) string literal
+fn string_literal_value(literal: &str) -> Option<String> {
+	if let Some(raw) = literal.strip_prefix('r') {
+		let hashes = raw.len() - raw.trim_start_matches('#').len();
+		let content = &raw[hashes..raw.len() - hashes];
+		return content.strip_prefix('"')?.strip_suffix('"').map(ToOwned::to_owned);
+	}
+
+	let content = literal.strip_prefix('"')?.strip_suffix('"')?;
+	let mut value = String::with_capacity(content.len());
+	let mut chars = content.chars();
+	while let Some(chr) = chars.next() {
+		if chr == '\\' {
+			match chars.next()? {
+				'n' => value.push('\n'),
+				't' => value.push('\t'),
+				'r' => value.push('\r'),
+				'0' => value.push('\0'),
+				chr => value.push(chr),
+			}
+		} else {
+			value.push(chr);
+		}
+	}
+	Some(value)
+}
+
+enum Step {
+	/// `if ...` or `if let ...`. Following steps go in its block
+	Check(TokenStream),
+	/// `let ...;`
+	Bind(TokenStream),
+}
+
+/// What a metavariable was bound to. Decides how repeated metavariables are compared
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+	Expression,
+	/// A [VariableOrPropertyAccess] on the left hand side of an assignment
+	Access,
+	Property,
+	Items,
+}
+
+struct MatcherGenerator<'a> {
+	metavariables: &'a [Metavariable],
+	bound: HashMap<String, (Ident, Kind)>,
+	steps: Vec<Step>,
+	counter: usize,
+}
+
+impl<'a> MatcherGenerator<'a> {
+	fn new_ident(&mut self) -> Ident {
+		self.counter += 1;
+		format_ident!("__field_{}", self.counter)
+	}
+
+	fn check(&mut self, check: TokenStream) {
+		self.steps.push(Step::Check(check));
+	}
+
+	/// Steps through a [Box] or [Vec]
+	fn unbox(&mut self, ident: &Ident) {
+		self.steps.push(Step::Bind(quote!(let #ident = Unbox::unbox(#ident);)));
+	}
+
+	fn expression(&mut self, expression: &Expression, target: &Ident) -> Result<(), String> {
+		match expression {
+			Expression::Cursor { cursor_id, .. } => {
+				self.capture(cursor_id.0, target, Kind::Expression)?;
+			}
+			Expression::NumberLiteral(value, ..) => {
+				let ident = self.new_ident();
+				let value = value.to_tokens();
+				self.check(quote!(if let Expression::NumberLiteral(#ident, ..) = #target));
+				self.check(quote!(if *#ident == #value));
+			}
+			Expression::StringLiteral(value, ..) => {
+				let ident = self.new_ident();
+				self.check(quote!(if let Expression::StringLiteral(#ident, ..) = #target));
+				self.check(quote!(if #ident.as_str() == #value));
+			}
+			Expression::BooleanLiteral(value, ..) => {
+				self.check(quote!(if let Expression::BooleanLiteral(#value, ..) = #target));
+			}
+			Expression::Null(..) => {
+				self.check(quote!(if let Expression::Null(..) = #target));
+			}
+			Expression::ThisReference(..) => {
+				self.check(quote!(if let Expression::ThisReference(..) = #target));
+			}
+			Expression::VariableReference(name, ..) => {
+				let ident = self.new_ident();
+				self.check(quote!(if let Expression::VariableReference(#ident, ..) = #target));
+				self.check(quote!(if #ident.as_str() == #name));
+			}
+			Expression::ArrayLiteral(items, ..) => {
+				let ident = self.new_ident();
+				self.check(quote!(if let Expression::ArrayLiteral(#ident, ..) = #target));
+				self.unbox(&ident);
+				self.items(items, &ident)?;
+			}
+			Expression::ParenthesizedExpression(inner, ..) => {
+				let ident = self.new_ident();
+				self.check(
+					quote!(if let Expression::ParenthesizedExpression(#ident, ..) = #target),
+				);
+				self.unbox(&ident);
+				self.multiple_expression(inner, &ident)?;
+			}
+			Expression::BinaryOperation { lhs, operator, rhs, .. } => {
+				let (lhs_ident, rhs_ident) = (self.new_ident(), self.new_ident());
+				let operator = operator.to_tokens();
+				self.check(quote!(if let Expression::BinaryOperation {
+					lhs: #lhs_ident, operator: #operator, rhs: #rhs_ident, ..
+				} = #target));
+				self.unbox(&lhs_ident);
+				self.unbox(&rhs_ident);
+				self.expression(lhs, &lhs_ident)?;
+				self.expression(rhs, &rhs_ident)?;
+			}
+			Expression::UnaryOperation { operator, operand, .. } => {
+				let ident = self.new_ident();
+				let operator = operator.to_tokens();
+				self.check(quote!(if let Expression::UnaryOperation {
+					operator: #operator, operand: #ident, ..
+				} = #target));
+				self.unbox(&ident);
+				self.expression(operand, &ident)?;
+			}
+			Expression::Assignment {
+				lhs: LHSOfAssignment::VariableOrPropertyAccess(lhs),
+				rhs,
+				..
+			} => {
+				let (lhs_ident, rhs_ident) = (self.new_ident(), self.new_ident());
+				self.check(quote!(if let Expression::Assignment {
+					lhs: LHSOfAssignment::VariableOrPropertyAccess(#lhs_ident), rhs: #rhs_ident, ..
+				} = #target));
+				self.unbox(&rhs_ident);
+				self.variable_or_property_access(lhs, &lhs_ident)?;
+				self.expression(rhs, &rhs_ident)?;
+			}
+			Expression::BinaryAssignmentOperation { lhs, operator, rhs, .. } => {
+				let (lhs_ident, rhs_ident) = (self.new_ident(), self.new_ident());
+				let operator = operator.to_tokens();
+				self.check(quote!(if let Expression::BinaryAssignmentOperation {
+					lhs: #lhs_ident, operator: #operator, rhs: #rhs_ident, ..
+				} = #target));
+				self.unbox(&rhs_ident);
+				self.variable_or_property_access(lhs, &lhs_ident)?;
+				self.expression(rhs, &rhs_ident)?;
+			}
+			Expression::PropertyAccess { parent, property, is_optional, .. } => {
+				let (parent_ident, property_ident) = (self.new_ident(), self.new_ident());
+				self.check(quote!(if let Expression::PropertyAccess {
+					parent: #parent_ident, property: #property_ident, is_optional: #is_optional, ..
+				} = #target));
+				self.unbox(&parent_ident);
+				self.expression(parent, &parent_ident)?;
+				self.property(property, &property_ident)?;
+			}
+			Expression::Index { indexee, indexer, .. } => {
+				let (indexee_ident, indexer_ident) = (self.new_ident(), self.new_ident());
+				self.check(quote!(if let Expression::Index {
+					indexee: #indexee_ident, indexer: #indexer_ident, ..
+				} = #target));
+				self.unbox(&indexee_ident);
+				self.unbox(&indexer_ident);
+				self.expression(indexee, &indexee_ident)?;
+				self.multiple_expression(indexer, &indexer_ident)?;
+			}
+			Expression::FunctionCall { function, arguments, .. } => {
+				let (function_ident, arguments_ident) = (self.new_ident(), self.new_ident());
+				self.check(quote!(if let Expression::FunctionCall {
+					function: #function_ident, arguments: #arguments_ident, ..
+				} = #target));
+				self.unbox(&function_ident);
+				self.unbox(&arguments_ident);
+				self.expression(function, &function_ident)?;
+				self.items(arguments, &arguments_ident)?;
+			}
+			Expression::ConstructorCall { constructor, arguments, .. } => {
+				let (constructor_ident, arguments_ident) = (self.new_ident(), self.new_ident());
+				if arguments.is_some() {
+					self.check(quote!(if let Expression::ConstructorCall {
+						constructor: #constructor_ident, arguments: Some(#arguments_ident), ..
+					} = #target));
+				} else {
+					self.check(quote!(if let Expression::ConstructorCall {
+						constructor: #constructor_ident, arguments: None, ..
+					} = #target));
+				}
+				self.unbox(&constructor_ident);
+				self.expression(constructor, &constructor_ident)?;
+				if let Some(arguments) = arguments {
+					self.unbox(&arguments_ident);
+					self.items(arguments, &arguments_ident)?;
+				}
+			}
+			Expression::TernaryExpression { condition, truthy_result, falsy_result, .. } => {
+				let idents = [self.new_ident(), self.new_ident(), self.new_ident()];
+				let [condition_ident, truthy_ident, falsy_ident] = &idents;
+				self.check(quote!(if let Expression::TernaryExpression {
+					condition: #condition_ident,
+					truthy_result: #truthy_ident,
+					falsy_result: #falsy_ident,
+					..
+				} = #target));
+				for ident in &idents {
+					self.unbox(ident);
+				}
+				self.expression(condition, condition_ident)?;
+				self.expression(truthy_result, truthy_ident)?;
+				self.expression(falsy_result, falsy_ident)?;
+			}
+			_ => return Err("unsupported expression in `match_expr!` pattern".to_owned()),
+		}
+		Ok(())
+	}
+
+	fn multiple_expression(
+		&mut self,
+		expression: &MultipleExpression,
+		target: &Ident,
+	) -> Result<(), String> {
+		if expression.lhs.is_some() {
+			return Err("comma expressions are not supported in `match_expr!` patterns".to_owned());
+		}
+		let ident = self.new_ident();
+		self.check(quote!(if let MultipleExpression { lhs: None, rhs: #ident } = #target));
+		self.expression(&expression.rhs, &ident)
+	}
+
+	fn variable_or_property_access(
+		&mut self,
+		access: &VariableOrPropertyAccess,
+		target: &Ident,
+	) -> Result<(), String> {
+		match access {
+			VariableOrPropertyAccess::Cursor { cursor_id, .. } => {
+				self.capture(cursor_id.0, target, Kind::Access)?;
+			}
+			VariableOrPropertyAccess::Variable(name, ..) => {
+				let ident = self.new_ident();
+				self.check(quote!(if let VariableOrPropertyAccess::Variable(#ident, ..) = #target));
+				self.check(quote!(if #ident.as_str() == #name));
+			}
+			VariableOrPropertyAccess::PropertyAccess { parent, property, .. } => {
+				let (parent_ident, property_ident) = (self.new_ident(), self.new_ident());
+				self.check(quote!(if let VariableOrPropertyAccess::PropertyAccess {
+					parent: #parent_ident, property: #property_ident, ..
+				} = #target));
+				self.unbox(&parent_ident);
+				self.expression(parent, &parent_ident)?;
+				self.property(property, &property_ident)?;
+			}
+			VariableOrPropertyAccess::Index { indexee, indexer, .. } => {
+				let (indexee_ident, indexer_ident) = (self.new_ident(), self.new_ident());
+				self.check(quote!(if let VariableOrPropertyAccess::Index {
+					indexee: #indexee_ident, indexer: #indexer_ident, ..
+				} = #target));
+				self.unbox(&indexee_ident);
+				self.unbox(&indexer_ident);
+				self.expression(indexee, &indexee_ident)?;
+				self.multiple_expression(indexer, &indexer_ident)?;
+			}
+		}
+		Ok(())
+	}
+
+	fn property(&mut self, property: &PropertyReference, target: &Ident) -> Result<(), String> {
+		let ident = self.new_ident();
+		self.check(quote!(if let PropertyReference::Standard(#ident) = #target));
+		match property {
+			PropertyReference::Standard(name) => {
+				self.check(quote!(if #ident.as_str() == #name));
+				Ok(())
+			}
+			PropertyReference::Cursor(cursor_id) => {
+				self.capture(cursor_id.0, &ident, Kind::Property)
+			}
+		}
+	}
+
+	/// For argument and array literal items. `target` is a slice
+	fn items(&mut self, items: &[SpreadExpression], target: &Ident) -> Result<(), String> {
+		let rest = match items.last() {
+			Some(SpreadExpression::NonSpread(Expression::Cursor { cursor_id, .. }))
+				if self.metavariables[cursor_id.0 as usize].variadic =>
+			{
+				Some(cursor_id.0)
+			}
+			_ => None,
+		};
+		let fixed = if rest.is_some() { &items[..items.len() - 1] } else { items };
+
+		let idents = fixed.iter().map(|_| self.new_ident()).collect::<Vec<_>>();
+		if let Some(rest) = rest {
+			let rest_ident = self.new_ident();
+			self.check(quote!(if let [#(#idents,)* #rest_ident @ ..] = #target));
+			self.capture(rest, &rest_ident, Kind::Items)?;
+		} else {
+			self.check(quote!(if let [#(#idents),*] = #target));
+		}
+
+		for (item, ident) in fixed.iter().zip(idents) {
+			let inner = self.new_ident();
+			match item {
+				SpreadExpression::NonSpread(expression) => {
+					self.check(quote!(if let SpreadExpression::NonSpread(#inner) = #ident));
+					self.expression(expression, &inner)?;
+				}
+				SpreadExpression::Spread(expression, _) => {
+					self.check(quote!(if let SpreadExpression::Spread(#inner, _) = #ident));
+					self.expression(expression, &inner)?;
+				}
+				SpreadExpression::Empty => {
+					self.check(quote!(if let SpreadExpression::Empty = #ident));
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Binds a metavariable. Repeated metavariables are compared against the first binding
+	fn capture(&mut self, cursor_id: u8, target: &Ident, kind: Kind) -> Result<(), String> {
+		let Metavariable { name, variadic } = &self.metavariables[cursor_id as usize];
+		if *variadic && kind != Kind::Items {
+			return Err(format!("`$$${name}` can only be used as the last item of a list"));
+		}
+		if name == "_" {
+			return Ok(());
+		}
+
+		match self.bound.get(name) {
+			Some((bound, bound_kind)) if *bound_kind == kind => {
+				let bound = bound.clone();
+				self.check(quote!(if *#bound == *#target));
+			}
+			Some((bound, Kind::Access)) if kind == Kind::Expression => {
+				let bound = bound.clone();
+				self.check(quote!(if Expression::from((*#bound).clone()) == *#target));
+			}
+			Some((bound, Kind::Expression)) if kind == Kind::Access => {
+				let bound = bound.clone();
+				self.check(quote!(if *#bound == Expression::from((*#target).clone())));
+			}
+			Some(_) => {
+				return Err(format!("`${name}` is used in incompatible positions"));
+			}
+			None => {
+				let ident = Ident::new(name, Span::call_site());
+				self.steps.push(Step::Bind(quote!(
+					#[allow(unused_variables)]
+					let #ident = #target;
+				)));
+				self.bound.insert(name.clone(), (ident, kind));
+			}
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,113 @@
+use ezno_ast_generator::{class_member, expr, interface_member, jsx, module, r#type, stmt};
+use parser::{
+	declarations::classes::ClassMember, extractor::ExtractedFunctions, ASTNode, Declaration,
+	Expression, ParseOutput, SourceId, StatementOrDeclaration, ToStringSettingsAndData,
+	TypeReference,
+};
+
+fn parse<T: ASTNode>(source: &str) -> T {
+	T::from_string(source.to_owned(), Default::default(), SourceId::NULL, None, Vec::new())
+		.unwrap()
+		.0
+}
+
+/// Nodes with functions are compared by their printed output, as the functions are held in
+/// different [ExtractedFunctions]
+fn parse_and_print<T: ASTNode>(source: &str) -> String {
+	let ParseOutput(node, state) =
+		T::from_string(source.to_owned(), Default::default(), SourceId::NULL, None, Vec::new())
+			.unwrap();
+	node.to_string(&ToStringSettingsAndData(Default::default(), state.function_extractor))
+}
+
+#[test]
+fn interpolation() {
+	let name = "x";
+	let argument = expr!(a + b);
+	let generated = expr!(call(#name, #argument));
+	assert_eq!(generated, parse::<Expression>(r#"call("x", a + b)"#));
+}
+
+#[test]
+fn splices() {
+	let items = ["b", "c"];
+	let arguments = expr!(call(1, #..items, 4));
+	assert_eq!(arguments, parse::<Expression>(r#"call(1, "b", "c", 4)"#));
+
+	let empty: [&str; 0] = [];
+	let array = expr!([#..empty]);
+	assert_eq!(array, parse::<Expression>("[]"));
+
+	let nested = expr!(outer([#..items], 5));
+	assert_eq!(nested, parse::<Expression>(r#"outer(["b", "c"], 5)"#));
+}
+
+#[test]
+fn statements_and_types() {
+	let value = expr!(1 + 2);
+	let generated = stmt!(const x = #value);
+	let StatementOrDeclaration::Declaration(Declaration::Variable(declaration)) = &generated else {
+		panic!("expected a variable declaration, found {generated:?}")
+	};
+	assert_eq!(declaration.to_string(&Default::default()), "const x = 1 + 2");
+
+	let item = "Post";
+	let generated = r#type!(Array<#item>);
+	assert_eq!(generated, parse::<TypeReference>("Array<Post>"));
+}
+
+#[test]
+fn modules_with_functions() {
+	let mut functions = ExtractedFunctions::default();
+
+	let calls = ["title", "content"].map(|name| expr!(registerField(#name)));
+	let generated = module!(
+		import { registerField } from "./fields";
+		#..calls;
+		function getPosts() {
+			return [];
+		}
+	);
+	assert_eq!(generated.items.len(), 4);
+
+	let settings = ToStringSettingsAndData(Default::default(), functions);
+	assert_eq!(
+		generated.to_string(&settings),
+		parse_and_print::<parser::Module>(
+			r#"import { registerField } from "./fields";
+			registerField("title");
+			registerField("content");
+			function getPosts() {
+				return [];
+			}"#
+		)
+	);
+}
+
+#[test]
+fn members_and_jsx() {
+	let mut functions = ExtractedFunctions::default();
+
+	let item = "Post";
+	let method = class_member!(render(message: string) { return #item + message; });
+	assert!(matches!(method, ClassMember::Function(None, _)));
+
+	let member = interface_member!(posts: Array<#item>);
+
+	let children = ["first", "second"];
+	let list = jsx!(<ul>{#..children}</ul>);
+
+	let settings = ToStringSettingsAndData(Default::default(), functions);
+	assert_eq!(
+		method.to_string(&settings),
+		parse_and_print::<ClassMember>(r#"render(message: string) { return "Post" + message; }"#)
+	);
+	assert_eq!(
+		member.to_string(&settings),
+		parse_and_print::<parser::types::interface::InterfaceMember>("posts: Array<Post>")
+	);
+	assert_eq!(
+		list.to_string(&settings),
+		parse_and_print::<parser::JSXElement>(r#"<ul>{"first"}{"second"}</ul>"#)
+	);
+}
@@ -0,0 +1,111 @@
+use ezno_ast_generator::{expr, match_expr};
+use parser::{ASTNode, Expression, SourceId};
+
+fn parse(source: &str) -> Expression {
+	Expression::from_string(source.to_owned(), Default::default(), SourceId::NULL, None, Vec::new())
+		.unwrap()
+		.0
+}
+
+fn print(expression: &Expression) -> String {
+	expression.to_string(&Default::default())
+}
+
+#[test]
+fn matches_and_binds_expressions() {
+	let classify = |source: &str| -> Option<(String, String)> {
+		let expression = parse(source);
+		match_expr!(&expression,
+			"$value + 0" => Some(("add zero".to_owned(), print(value))),
+			"$array.indexOf($item) !== -1" => Some((print(array), print(item))),
+			_ => None
+		)
+	};
+
+	assert_eq!(classify("x + 0"), Some(("add zero".to_owned(), "x".to_owned())));
+	assert_eq!(classify("f(a) + 0"), Some(("add zero".to_owned(), "f(a)".to_owned())));
+	assert_eq!(
+		classify("items.indexOf(y + 1) !== -1"),
+		Some(("items".to_owned(), "y + 1".to_owned()))
+	);
+
+	assert_eq!(classify("x + 1"), None);
+	assert_eq!(classify("0 + x"), None);
+	assert_eq!(classify("items.indexOf(y) === -1"), None);
+	assert_eq!(classify("items.lastIndexOf(y) !== -1"), None);
+}
+
+#[test]
+fn first_matching_arm() {
+	let expression = parse("a + 0");
+	let arm = match_expr!(&expression,
+		"$value + 1" => 1,
+		"$value + 0" => 2,
+		"$lhs + $rhs" => 3,
+		_ => 4
+	);
+	assert_eq!(arm, 2);
+
+	let expression = parse("a - 0");
+	let arm = match_expr!(&expression, "$lhs + $rhs" => 3, _ => 4);
+	assert_eq!(arm, 4);
+}
+
+#[test]
+fn repeated_metavariables() {
+	let is_self_comparison = |source: &str| {
+		let expression = parse(source);
+		match_expr!(&expression, "$a === $a" => true, _ => false)
+	};
+
+	assert!(is_self_comparison("x === x"));
+	assert!(is_self_comparison("a.b === a.b"));
+	assert!(!is_self_comparison("x === y"));
+	assert!(!is_self_comparison("a.b === a.c"));
+}
+
+#[test]
+fn properties_and_items() {
+	let expression = parse("console.log(1, 2, 3)");
+	let bound = match_expr!(&expression,
+		"$object.$method($first, $$$rest)" => {
+			let rest = rest.iter().map(|item| item.to_string(&Default::default()));
+			Some((print(object), method.clone(), print(first), rest.collect::<Vec<_>>()))
+		},
+		_ => None
+	);
+	let (object, method, first, rest) = bound.unwrap();
+	assert_eq!((object.as_str(), method.as_str(), first.as_str()), ("console", "log", "1"));
+	assert_eq!(rest, ["2", "3"]);
+
+	let expression = parse("console.log()");
+	let matched = match_expr!(&expression, "$object.$method($first, $$$rest)" => true, _ => false);
+	assert!(!matched);
+}
+
+#[test]
+fn mutable_references() {
+	let mut expression = parse("a + b");
+	match_expr!(&mut expression,
+		"$lhs + $rhs" => std::mem::swap(lhs, rhs),
+		_ => {}
+	);
+	assert_eq!(expression, parse("b + a"));
+
+	let mut expression = parse("f(1, 2, 3)");
+	match_expr!(&mut expression,
+		"$call($$$arguments)" => arguments.reverse(),
+		_ => {}
+	);
+	assert_eq!(expression, parse("f(3, 2, 1)"));
+
+	let mut expression = parse("!isEmpty ? first : rest");
+	let simplified = match_expr!(&mut expression,
+		"!$condition ? $truthy : $falsy" => {
+			let (condition, truthy, falsy) = (condition.clone(), truthy.clone(), falsy.clone());
+			Some(expr!(#condition ? #falsy : #truthy))
+		},
+		_ => None
+	);
+	assert_eq!(simplified, Some(parse("isEmpty ? rest : first")));
+}
@@ -11,8 +11,10 @@ use visitable_derive::Visitable;
 
 use super::{ASTNode, Span, TSXToken, TokenReader};
 use crate::{
-	expect_semi_colon, extractor::ExtractedFunctions, Declaration, ParseResult, ParseSettings,
-	Statement, VisitSettings, Visitable,
+	declarations::{export::Exportable, ExportDeclaration},
+	expect_semi_colon,
+	extractor::ExtractedFunctions,
+	Declaration, ParseResult, ParseSettings, Statement, VisitSettings, Visitable,
 };
 
 static BLOCK_ID_COUNTER: AtomicU16 = AtomicU16::new(0);
@@ -51,13 +53,28 @@ pub enum StatementOrDeclaration {
 }
 
 impl StatementOrDeclaration {
-	pub(crate) fn requires_semi_colon(&self) -> bool {
+	pub fn requires_semi_colon(&self) -> bool {
 		match self {
 			StatementOrDeclaration::Statement(stmt) => stmt.requires_semi_colon(),
-			StatementOrDeclaration::Declaration(dec) => matches!(
-				dec,
-				Declaration::Variable(..) | Declaration::Export(..) | Declaration::Import(..)
-			),
+			StatementOrDeclaration::Declaration(dec) => match dec {
+				Declaration::Variable(..)
+				| Declaration::Import(..)
+				| Declaration::TypeAlias(..)
+				| Declaration::DeclareVariable(..)
+				| Declaration::DeclareFunction(..) => true,
+				// Exported classes, functions, interfaces and enums end with a brace
+				Declaration::Export(export) => !matches!(
+					export.on,
+					ExportDeclaration::Variable {
+						exported: Exportable::Class(..)
+							| Exportable::Function(..)
+							| Exportable::Interface(..)
+							| Exportable::Enum(..),
+						..
+					}
+				),
+				_ => false,
+			},
 		}
 	}
 }
@@ -269,9 +286,16 @@ impl ASTNode for BlockOrSingleStatement {
 		state: &mut crate::ParsingState,
 		settings: &ParseSettings,
 	) -> ParseResult<Self> {
-		Statement::from_reader(reader, state, settings).map(|stmt| match stmt {
+		let stmt = Statement::from_reader(reader, state, settings)?;
+		Ok(match stmt {
 			Statement::Block(blk) => Self::Braced(blk),
-			stmt => Box::new(stmt).into(),
+			stmt => {
+				// e.g. `if (x) return;`
+				if stmt.requires_semi_colon() {
+					reader.conditional_next(|tok| *tok == TSXToken::SemiColon);
+				}
+				Box::new(stmt).into()
+			}
 		})
 	}
 
@@ -287,9 +311,10 @@ impl ASTNode for BlockOrSingleStatement {
 			}
 			BlockOrSingleStatement::SingleStatement(stmt) => {
 				if settings.0.pretty {
-					buf.push_new_line();
-					settings.0.add_gap(buf);
 					stmt.to_string_from_buffer(buf, settings, depth);
+					if stmt.requires_semi_colon() {
+						buf.push(';');
+					}
 				} else {
 					buf.push('{');
 					stmt.to_string_from_buffer(buf, settings, depth);
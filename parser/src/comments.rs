@@ -5,6 +5,12 @@ use crate::ParseSettings;
 use std::{borrow::Cow, mem};
 use tokenizer_lib::Token;
 
+/// Whether a comment is `/*#__PURE__*/` (or `/*@__PURE__*/`), which marks that the call or `new`
+/// expression after it can be removed if its result is unused
+pub fn is_purity_annotation(comment: &str) -> bool {
+	matches!(comment.trim(), "#__PURE__" | "@__PURE__")
+}
+
 #[derive(Debug, Clone, Eq)]
 pub enum WithComment<T> {
 	None(T),
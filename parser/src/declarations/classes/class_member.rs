@@ -5,15 +5,16 @@ use source_map::Span;
 use tokenizer_lib::{Token, TokenReader};
 
 use crate::{
-	extractor::{ExtractedFunction, GetFunction},
+	extractor::{ExtractedFunction, ExtractedFunctions, GetFunction},
 	functions::FunctionBased,
 	ASTNode, Block, ChainVariable, Expression, FunctionBase, GetSetGeneratorOrNone, Keyword,
 	ParseError, ParseErrors, ParseResult, ParseSettings, PropertyKey, TSXKeyword, TSXToken,
-	TypeReference, VariableId, VisitSettings, WithComment,
+	TypeReference, VisitSettings, Visitable, WithComment,
 };
 
 /// The variable id's of these is handled by their [PropertyKey]
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "self-rust-tokenize", derive(self_rust_tokenize::SelfRustTokenize))]
 pub enum ClassMember {
 	Constructor(ExtractedFunction<ClassConstructorBase>),
 	Function(Option<Keyword<tsx_keywords::Static>>, ExtractedFunction<ClassFunctionBase>),
@@ -31,7 +32,11 @@ pub struct ClassFunctionBase;
 pub type ClassFunction = FunctionBase<ClassFunctionBase>;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "self-rust-tokenize", derive(self_rust_tokenize::SelfRustTokenize))]
 pub struct ClassProperty {
+	/// Position of `accessor`, which makes the property an auto-accessor: a getter and setter for
+	/// a private field
+	pub accessor: Option<Span>,
 	pub key: WithComment<PropertyKey>,
 	pub type_reference: Option<TypeReference>,
 	pub value: Option<Box<Expression>>,
@@ -61,6 +66,10 @@ impl ASTNode for ClassMember {
 		let is_static = reader
 			.conditional_next(|tok| *tok == TSXToken::Keyword(TSXKeyword::Static))
 			.map(|Token(_, span)| Keyword::new(span));
+		// `accessor` is only a modifier when followed by a key, otherwise it is the name
+		let is_accessor = matches!(reader.peek(), Some(Token(TSXToken::IdentLiteral(name), _)) if name == "accessor")
+			&& reader.peek_n(1).is_some_and(|Token(next, _)| is_key_start(next));
+		let accessor = is_accessor.then(|| reader.next().unwrap().1);
 		let is_async = reader
 			.conditional_next(|tok| *tok == TSXToken::Keyword(TSXKeyword::Async))
 			.map(|Token(_, span)| Keyword::new(span));
@@ -69,6 +78,16 @@ impl ASTNode for ClassMember {
 		let key = WithComment::<PropertyKey>::from_reader(reader, state, settings)?;
 
 		match reader.peek().unwrap() {
+			Token(TSXToken::OpenParentheses, _) if accessor.is_some() => {
+				let Token(token, position) = reader.next().unwrap();
+				Err(ParseError::new(
+					ParseErrors::UnexpectedToken {
+						expected: vec![TSXToken::Assign, TSXToken::SemiColon],
+						found: token,
+					},
+					position,
+				))
+			}
 			Token(TSXToken::OpenParentheses, _) => {
 				let class_method = ClassFunction::from_reader_with_config(
 					reader,
@@ -87,7 +106,7 @@ impl ASTNode for ClassMember {
 					let Token(token, position) = reader.next().unwrap();
 					return Err(ParseError::new(
 						ParseErrors::UnexpectedToken {
-							expected: &[TSXToken::OpenParentheses],
+							expected: vec![TSXToken::OpenParentheses],
 							found: token,
 						},
 						position,
@@ -112,6 +131,7 @@ impl ASTNode for ClassMember {
 				Ok(Self::Property(
 					is_static,
 					ClassProperty {
+						accessor,
 						key,
 						type_reference: member_type,
 						value: member_expression.map(Box::new),
@@ -128,10 +148,13 @@ impl ASTNode for ClassMember {
 		depth: u8,
 	) {
 		match self {
-			Self::Property(is_static, ClassProperty { key, type_reference, value }) => {
+			Self::Property(is_static, ClassProperty { accessor, key, type_reference, value }) => {
 				if is_static.is_some() {
 					buf.push_str("static ");
 				}
+				if accessor.is_some() {
+					buf.push_str("accessor ");
+				}
 				key.to_string_from_buffer(buf, settings, depth);
 				if let (true, Some(type_reference)) = (settings.0.include_types, type_reference) {
 					buf.push_str(": ");
@@ -165,6 +188,19 @@ impl ASTNode for ClassMember {
 	}
 }
 
+/// Whether a token can be the start of a [PropertyKey]
+fn is_key_start(token: &TSXToken) -> bool {
+	matches!(
+		token,
+		TSXToken::IdentLiteral(_)
+			| TSXToken::Keyword(_)
+			| TSXToken::SingleQuotedStringLiteral(_)
+			| TSXToken::DoubleQuotedStringLiteral(_)
+			| TSXToken::NumberLiteral(_)
+			| TSXToken::OpenBracket
+	)
+}
+
 impl ClassMember {
 	// pub fn get_property_id(&self) -> Option<PropertyId> {
 	// 	match self {
@@ -175,103 +211,45 @@ impl ClassMember {
 	// }
 }
 
-#[allow(unused)]
-impl ClassMember {
-	pub fn visit<TData>(
+impl Visitable for ClassMember {
+	fn visit<TData>(
 		&self,
 		visitors: &mut (impl crate::VisitorReceiver<TData> + ?Sized),
 		data: &mut TData,
 		settings: &VisitSettings,
-		chain: &mut temporary_annex::Annex<crate::visiting::Chain>,
-		// TODO VariableId and TypeId
-		class_variable_id: VariableId,
+		functions: &mut ExtractedFunctions,
+		chain: &mut temporary_annex::Annex<crate::Chain>,
 	) {
 		match self {
-			ClassMember::Constructor(..) => {
-				todo!()
-				// decorators.visit(visitors, data, settings, chain);
-				// function.visit(
-				// 	visitors,
-				// 	data,
-				// 	settings,
-				// 	&mut chain.push_annex(ChainVariable::UnderClassMethod(
-				// 		function.body.1,
-				// 		class_variable_id,
-				// 	)),
-				// );
+			ClassMember::Constructor(constructor) => {
+				constructor.visit(visitors, data, settings, functions, chain)
 			}
-			ClassMember::Function(..) => {
-				todo!()
-				//  { decorators, function, key, .. }
-				// key.visit(visitors, data, chain, PropertyKeyLocation::Class(class_variable_id));
-				// decorators.visit(visitors, data, settings, chain);
-				// function.visit(
-				// 	visitors,
-				// 	data,
-				// 	settings,
-				// 	&mut chain.push_annex(ChainVariable::UnderClassMethod(
-				// 		function.body.1,
-				// 		class_variable_id,
-				// 	)),
-				// );
+			ClassMember::Function(_, function) => {
+				function.visit(visitors, data, settings, functions, chain)
 			}
-			ClassMember::Property(_, ClassProperty { value, key, .. }) => {
-				todo!()
-				// key.visit(visitors, data, chain, PropertyKeyLocation::Class(class_variable_id));
-				// value.visit(visitors, data, settings, chain);
+			ClassMember::Property(_, ClassProperty { value, .. }) => {
+				value.visit(visitors, data, settings, functions, chain)
 			}
 		}
 	}
 
-	pub fn visit_mut<TData>(
+	fn visit_mut<TData>(
 		&mut self,
 		visitors: &mut (impl crate::VisitorMutReceiver<TData> + ?Sized),
 		data: &mut TData,
 		settings: &VisitSettings,
-		chain: &mut temporary_annex::Annex<crate::visiting::Chain>,
-		// TODO remove
-		class_variable_id: VariableId,
+		functions: &mut ExtractedFunctions,
+		chain: &mut temporary_annex::Annex<crate::Chain>,
 	) {
 		match self {
-			ClassMember::Constructor(..) => {
-				todo!()
-				//  { decorators, function, .. }
-				// decorators
-				// 	.iter_mut()
-				// 	.for_each(|decorator| decorator.visit_mut(visitors, data, settings, chain));
-				// function.visit_mut(
-				// 	visitors,
-				// 	data,
-				// 	settings,
-				// 	&mut chain.push_annex(ChainVariable::UnderClassMethod(
-				// 		function.body.1,
-				// 		class_variable_id,
-				// 	)),
-				// );
+			ClassMember::Constructor(constructor) => {
+				constructor.visit_mut(visitors, data, settings, functions, chain)
 			}
-			ClassMember::Function(..) => {
-				todo!()
-				//  { decorators, function, key, .. }
-				// key.visit_mut(visitors, data, chain, PropertyKeyLocation::Class(class_variable_id));
-				// decorators
-				// 	.iter_mut()
-				// 	.for_each(|decorator| decorator.visit_mut(visitors, data, settings, chain));
-				// function.visit_mut(
-				// 	visitors,
-				// 	data,
-				// 	settings,
-				// 	&mut chain.push_annex(ChainVariable::UnderClassMethod(
-				// 		function.body.1,
-				// 		class_variable_id,
-				// 	)),
-				// );
+			ClassMember::Function(_, function) => {
+				function.visit_mut(visitors, data, settings, functions, chain)
 			}
-			ClassMember::Property(_, ClassProperty { value, key, .. }) => {
-				todo!()
-				// key.visit_mut(visitors, data, chain, PropertyKeyLocation::Class(class_variable_id));
-				// if let Some(value) = value {
-				// 	value.visit_mut(visitors, data, settings, chain);
-				// }
+			ClassMember::Property(_, ClassProperty { value, .. }) => {
+				value.visit_mut(visitors, data, settings, functions, chain)
 			}
 		}
 	}
@@ -29,14 +29,34 @@ pub struct ClassDeclaration<T: ExpressionOrStatementPosition> {
 }
 
 #[cfg(feature = "self-rust-tokenize")]
-impl<T: ExpressionOrStatementPosition> self_rust_tokenize::SelfRustTokenize
-	for ClassDeclaration<T>
+impl<T: ExpressionOrStatementPosition> self_rust_tokenize::SelfRustTokenize for ClassDeclaration<T>
+where
+	T::Name: self_rust_tokenize::SelfRustTokenize,
 {
 	fn append_to_token_stream(
 		&self,
-		_token_stream: &mut self_rust_tokenize::proc_macro2::TokenStream,
+		token_stream: &mut self_rust_tokenize::proc_macro2::TokenStream,
 	) {
-		todo!()
+		use self_rust_tokenize::quote;
+
+		let class_keyword = self.class_keyword.to_tokens();
+		let name = self.name.to_tokens();
+		let type_parameters = self.type_parameters.to_tokens();
+		let extends = self.extends.to_tokens();
+		let members = self.members.to_tokens();
+		let type_id = self.type_id.to_tokens();
+		let variable_id = self.variable_id.to_tokens();
+		let position = self.position.to_tokens();
+		token_stream.extend(quote!(ClassDeclaration {
+			class_keyword: #class_keyword,
+			name: #name,
+			type_parameters: #type_parameters,
+			extends: #extends,
+			members: #members,
+			type_id: #type_id,
+			variable_id: #variable_id,
+			position: #position,
+		}));
 	}
 }
 
@@ -114,8 +134,11 @@ impl<U: ExpressionOrStatementPosition> ClassDeclaration<U> {
 		settings: &crate::ToStringSettingsAndData,
 		depth: u8,
 	) {
-		buf.push_str("class ");
-		buf.push_str(U::as_option_str(&self.name).unwrap_or_default());
+		buf.push_str("class");
+		if let Some(name) = U::as_option_str(&self.name) {
+			buf.push(' ');
+			buf.push_str(name);
+		}
 		if let Some(type_parameters) = &self.type_parameters {
 			to_string_bracketed(type_parameters, ('<', '>'), buf, settings, depth);
 		}
@@ -137,6 +160,7 @@ impl<U: ExpressionOrStatementPosition> ClassDeclaration<U> {
 		}
 		if settings.0.pretty && !self.members.is_empty() {
 			buf.push_new_line();
+			settings.0.add_indent(depth, buf);
 		}
 		buf.push('}');
 	}
@@ -149,22 +173,24 @@ impl<U: ExpressionOrStatementPosition> ClassDeclaration<U> {
 impl<T: ExpressionOrStatementPosition> Visitable for ClassDeclaration<T> {
 	fn visit<TData>(
 		&self,
-		_visitors: &mut (impl crate::VisitorReceiver<TData> + ?Sized),
-		_data: &mut TData,
-		_settings: &VisitSettings,
+		visitors: &mut (impl crate::VisitorReceiver<TData> + ?Sized),
+		data: &mut TData,
+		settings: &VisitSettings,
 		// TODO could be &
-		_functions: &mut crate::extractor::ExtractedFunctions,
-		_chain: &mut temporary_annex::Annex<crate::Chain>,
+		functions: &mut crate::extractor::ExtractedFunctions,
+		chain: &mut temporary_annex::Annex<crate::Chain>,
 	) {
+		self.members.visit(visitors, data, settings, functions, chain);
 	}
 
 	fn visit_mut<TData>(
 		&mut self,
-		_visitors: &mut (impl crate::VisitorMutReceiver<TData> + ?Sized),
-		_data: &mut TData,
-		_settings: &VisitSettings,
-		_functions: &mut crate::extractor::ExtractedFunctions,
-		_chain: &mut temporary_annex::Annex<crate::Chain>,
+		visitors: &mut (impl crate::VisitorMutReceiver<TData> + ?Sized),
+		data: &mut TData,
+		settings: &VisitSettings,
+		functions: &mut crate::extractor::ExtractedFunctions,
+		chain: &mut temporary_annex::Annex<crate::Chain>,
 	) {
+		self.members.visit_mut(visitors, data, settings, functions, chain);
 	}
 }
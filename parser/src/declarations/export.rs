@@ -1,8 +1,9 @@
 use std::borrow::Cow;
 
 use crate::{
-	errors::parse_lexing_error, ASTNode, Expression, Keyword, ParseResult, ParseSettings, Span,
-	StatementPosition, TSXKeyword, TSXToken, Token,
+	errors::parse_lexing_error, statements::VarVariableStatement,
+	types::enum_declaration::EnumDeclaration, ASTNode, Expression, Keyword, ParseResult,
+	ParseSettings, Span, StatementPosition, TSXKeyword, TSXToken, Token,
 };
 
 use super::{
@@ -30,8 +31,10 @@ pub enum Exportable {
 	Class(ClassDeclaration<StatementPosition>),
 	Function(StatementFunction),
 	Variable(VariableDeclaration),
+	VarVariable(VarVariableStatement),
 	Interface(InterfaceDeclaration),
 	TypeAlias(TypeAlias),
+	Enum(EnumDeclaration),
 }
 
 impl ASTNode for ExportDeclaration {
@@ -56,6 +59,9 @@ impl ASTNode for ExportDeclaration {
 			let position = start.union(&expression.get_position());
 			Ok(ExportDeclaration::Default { expression: Box::new(expression), position })
 		} else {
+			// `export const enum ...`
+			let is_const_enum =
+				matches!(reader.peek_n(1), Some(Token(TSXToken::Keyword(TSXKeyword::Enum), _)));
 			match reader.peek().ok_or_else(parse_lexing_error)? {
 				Token(TSXToken::Keyword(TSXKeyword::Class), _) => {
 					let Token(_, pos) = reader.next().unwrap();
@@ -66,7 +72,8 @@ impl ASTNode for ExportDeclaration {
 					let position = start.union(&class_declaration.get_position());
 					Ok(Self::Variable { exported: Exportable::Class(class_declaration), position })
 				}
-				Token(TSXToken::Keyword(TSXKeyword::Function), _) => {
+				Token(TSXToken::Keyword(TSXKeyword::Function), _)
+				| Token(TSXToken::Keyword(TSXKeyword::Async), _) => {
 					let function_declaration =
 						StatementFunction::from_reader(reader, state, settings)?;
 					let position = start.union(&function_declaration.get_position());
@@ -75,6 +82,16 @@ impl ASTNode for ExportDeclaration {
 						position,
 					})
 				}
+				Token(TSXToken::Keyword(TSXKeyword::Const), _) if is_const_enum => {
+					let enum_declaration = EnumDeclaration::from_reader(reader, state, settings)?;
+					let position = start.union(&enum_declaration.get_position());
+					Ok(Self::Variable { exported: Exportable::Enum(enum_declaration), position })
+				}
+				Token(TSXToken::Keyword(TSXKeyword::Enum), _) => {
+					let enum_declaration = EnumDeclaration::from_reader(reader, state, settings)?;
+					let position = start.union(&enum_declaration.get_position());
+					Ok(Self::Variable { exported: Exportable::Enum(enum_declaration), position })
+				}
 				Token(
 					TSXToken::Keyword(TSXKeyword::Const) | TSXToken::Keyword(TSXKeyword::Let),
 					_,
@@ -87,6 +104,14 @@ impl ASTNode for ExportDeclaration {
 						position,
 					})
 				}
+				Token(TSXToken::Keyword(TSXKeyword::Var), _) => {
+					let var_statement = VarVariableStatement::from_reader(reader, state, settings)?;
+					let position = start.union(&var_statement.get_position());
+					Ok(Self::Variable {
+						exported: Exportable::VarVariable(var_statement),
+						position,
+					})
+				}
 				Token(TSXToken::Keyword(TSXKeyword::Interface), _) => {
 					let interface_declaration =
 						InterfaceDeclaration::from_reader(reader, state, settings)?;
@@ -130,9 +155,15 @@ impl ASTNode for ExportDeclaration {
 					Exportable::Variable(variable_dec_stmt) => {
 						variable_dec_stmt.to_string_from_buffer(buf, settings, depth);
 					}
+					Exportable::VarVariable(var_statement) => {
+						var_statement.to_string_from_buffer(buf, settings, depth);
+					}
 					Exportable::TypeAlias(type_alias) => {
 						type_alias.to_string_from_buffer(buf, settings, depth);
 					}
+					Exportable::Enum(enum_declaration) => {
+						enum_declaration.to_string_from_buffer(buf, settings, depth);
+					}
 				}
 			}
 			ExportDeclaration::Default { expression, position: _ } => {
@@ -173,22 +173,47 @@ impl ASTNode for ImportDeclaration {
 #[cfg_attr(feature = "self-rust-tokenize", derive(self_rust_tokenize::SelfRustTokenize))]
 pub enum ImportPart {
 	Name(VariableIdentifier),
-	NameWithAlias { name: String, alias: String, variable_id: VariableId, position: Span },
+	NameWithAlias {
+		name: String,
+		alias: String,
+		variable_id: VariableId,
+		position: Span,
+	},
+	/// `type X` in `import { type X } from ...`
+	TypeOnly(Box<ImportPart>, Span),
 }
 
 impl ASTNode for ImportPart {
 	fn get_position(&self) -> Cow<Span> {
 		match self {
 			ImportPart::Name(identifier) => identifier.get_position(),
-			ImportPart::NameWithAlias { position, .. } => Cow::Borrowed(position),
+			ImportPart::NameWithAlias { position, .. } | ImportPart::TypeOnly(_, position) => {
+				Cow::Borrowed(position)
+			}
 		}
 	}
 
 	fn from_reader(
 		reader: &mut impl TokenReader<TSXToken, Span>,
-		_state: &mut crate::ParsingState,
-		_settings: &ParseSettings,
+		state: &mut crate::ParsingState,
+		settings: &ParseSettings,
 	) -> ParseResult<Self> {
+		if let Some(Token(TSXToken::Keyword(TSXKeyword::Type), _)) = reader.peek() {
+			// `type` is also a valid name to import
+			let is_modifier = !matches!(
+				reader.peek_n(1),
+				Some(Token(
+					TSXToken::Comma | TSXToken::CloseBrace | TSXToken::Keyword(TSXKeyword::As),
+					_
+				))
+			);
+			if is_modifier {
+				let Token(_, start) = reader.next().unwrap();
+				let part = Self::from_reader(reader, state, settings)?;
+				let position = start.union(&part.get_position());
+				return Ok(Self::TypeOnly(Box::new(part), position));
+			}
+		}
 		let (name, pos) =
 			token_as_identifier(reader.next().ok_or_else(parse_lexing_error)?, "imported name")?;
 		if let Some(Token(TSXToken::Keyword(TSXKeyword::As), _)) = reader.peek() {
@@ -215,6 +240,10 @@ impl ASTNode for ImportPart {
 				buf.push_str(" as ");
 				buf.push_str(alias);
 			}
+			ImportPart::TypeOnly(part, _) => {
+				buf.push_str("type ");
+				part.to_string_from_buffer(buf, _settings, _depth);
+			}
 		}
 	}
 }
@@ -58,16 +58,28 @@ impl Declaration {
 	pub(crate) fn is_declaration_start(
 		reader: &mut impl tokenizer_lib::TokenReader<crate::TSXToken, source_map::Span>,
 	) -> bool {
+		// `import(...)` is a dynamic import expression rather than a declaration
+		if let Some(Token(TSXToken::Keyword(TSXKeyword::Import), _)) = reader.peek() {
+			return !matches!(reader.peek_n(1), Some(Token(TSXToken::OpenParentheses, _)));
+		}
+		// `namespace` is otherwise a valid name
+		if let Some(Token(TSXToken::Keyword(TSXKeyword::Namespace), _)) = reader.peek() {
+			return matches!(reader.peek_n(1), Some(Token(TSXToken::IdentLiteral(_), _)));
+		}
 		matches!(
 			reader.peek(),
 			Some(Token(
 				TSXToken::Keyword(
 					TSXKeyword::Let
-						| TSXKeyword::Const | TSXKeyword::Function
-						| TSXKeyword::Class | TSXKeyword::Enum
-						| TSXKeyword::Type | TSXKeyword::Declare
-						| TSXKeyword::Import | TSXKeyword::Export
-						| TSXKeyword::Async | TSXKeyword::Generator
+						| TSXKeyword::Const
+						| TSXKeyword::Function
+						| TSXKeyword::Class
+						| TSXKeyword::Enum | TSXKeyword::Type
+						| TSXKeyword::Declare
+						| TSXKeyword::Interface
+						| TSXKeyword::Export
+						| TSXKeyword::Async
+						| TSXKeyword::Generator
 				) | TSXToken::At,
 				_
 			))
@@ -105,7 +117,7 @@ impl crate::ASTNode for Declaration {
 		match reader.peek().ok_or_else(parse_lexing_error)?.0 {
 			// Const can be either variable declaration or const enum
 			TSXToken::Keyword(TSXKeyword::Const) => {
-				let after_const = reader.peek_n(2);
+				let after_const = reader.peek_n(1);
 				if let Some(Token(TSXToken::Keyword(TSXKeyword::Enum), _)) = after_const {
 					EnumDeclaration::from_reader(reader, state, settings)
 						.map(|on| Declaration::Enum(Decorated { decorators, on }))
@@ -122,6 +134,11 @@ impl crate::ASTNode for Declaration {
 				EnumDeclaration::from_reader(reader, state, settings)
 					.map(|on| Declaration::Enum(Decorated { decorators, on }))
 			}
+			// Namespaces are only supported in type definition files
+			TSXToken::Keyword(TSXKeyword::Namespace) => {
+				let Token(_, position) = reader.next().unwrap();
+				Err(ParseError::new(ParseErrors::NamespaceOutsideOfDefinitionFile, position))
+			}
 			TSXToken::Keyword(TSXKeyword::Generator) if settings.generator_keyword => {
 				let func = StatementFunction::from_reader(reader, state, settings)?;
 				if !decorators.is_empty() {
@@ -197,7 +214,7 @@ impl crate::ASTNode for Declaration {
 				let Token(token, position) = reader.next().unwrap();
 				return Err(ParseError::new(
 					ParseErrors::UnexpectedToken {
-						expected: &[
+						expected: vec![
 							TSXToken::Keyword(TSXKeyword::Let),
 							TSXToken::Keyword(TSXKeyword::Const),
 							TSXToken::Keyword(TSXKeyword::Function),
@@ -224,6 +241,7 @@ impl crate::ASTNode for Declaration {
 		settings: &crate::ToStringSettingsAndData,
 		depth: u8,
 	) {
+		crate::add_mapping(buf, &self.get_position());
 		match self {
 			Declaration::Function(f) => f.to_string_from_buffer(buf, settings, depth),
 			Declaration::ExtractedFunction(func) => {
@@ -170,22 +170,26 @@ pub enum VariableDeclaration {
 pub enum VariableDeclarationKeyword {
 	Const(Keyword<tsx_keywords::Const>),
 	Let(Keyword<tsx_keywords::Let>),
+	/// Only in `for (var x of y)` and `for (var x in y)`
+	Var(Keyword<tsx_keywords::Var>),
 }
 
 impl VariableDeclarationKeyword {
 	pub fn is_token_variable_keyword(token: &TSXToken) -> bool {
-		matches!(token, TSXToken::Keyword(TSXKeyword::Const | TSXKeyword::Let))
+		matches!(token, TSXToken::Keyword(TSXKeyword::Const | TSXKeyword::Let | TSXKeyword::Var))
 	}
 
 	pub(crate) fn from_reader(token: Token<TSXToken, Span>) -> ParseResult<Self> {
 		match token {
 			Token(TSXToken::Keyword(TSXKeyword::Const), pos) => Ok(Self::Const(Keyword::new(pos))),
 			Token(TSXToken::Keyword(TSXKeyword::Let), pos) => Ok(Self::Let(Keyword::new(pos))),
+			Token(TSXToken::Keyword(TSXKeyword::Var), pos) => Ok(Self::Var(Keyword::new(pos))),
 			Token(token, position) => Err(ParseError::new(
 				crate::ParseErrors::UnexpectedToken {
-					expected: &[
+					expected: vec![
 						TSXToken::Keyword(TSXKeyword::Const),
 						TSXToken::Keyword(TSXKeyword::Let),
+						TSXToken::Keyword(TSXKeyword::Var),
 					],
 					found: token,
 				},
@@ -198,6 +202,7 @@ impl VariableDeclarationKeyword {
 		match self {
 			VariableDeclarationKeyword::Const(_) => "const ",
 			VariableDeclarationKeyword::Let(_) => "let ",
+			VariableDeclarationKeyword::Var(_) => "var ",
 		}
 	}
 
@@ -205,6 +210,7 @@ impl VariableDeclarationKeyword {
 		match self {
 			VariableDeclarationKeyword::Const(kw) => kw.get_position(),
 			VariableDeclarationKeyword::Let(kw) => kw.get_position(),
+			VariableDeclarationKeyword::Var(kw) => kw.get_position(),
 		}
 	}
 }
@@ -248,6 +254,19 @@ impl ASTNode for VariableDeclaration {
 				}
 				VariableDeclaration::ConstDeclaration { keyword, declarations }
 			}
+			// `var` is a statement, see [crate::statements::VarVariableStatement]
+			VariableDeclarationKeyword::Var(keyword) => {
+				return Err(ParseError::new(
+					crate::ParseErrors::UnexpectedToken {
+						expected: vec![
+							TSXToken::Keyword(TSXKeyword::Const),
+							TSXToken::Keyword(TSXKeyword::Let),
+						],
+						found: TSXToken::Keyword(TSXKeyword::Var),
+					},
+					keyword.get_position().clone(),
+				))
+			}
 		})
 	}
 
@@ -6,22 +6,25 @@ use source_map::Span;
 use tokenizer_lib::Token;
 
 #[allow(missing_docs)]
-pub enum ParseErrors<'a> {
-	UnexpectedToken { expected: &'a [TSXToken], found: TSXToken },
+#[derive(Debug, PartialEq)]
+pub enum ParseErrors {
+	UnexpectedToken { expected: Vec<TSXToken>, found: TSXToken },
 	UnexpectedSymbol(derive_finite_automaton::InvalidCharacter),
-	ClosingTagDoesNotMatch { expected: &'a str, found: &'a str },
+	ClosingTagDoesNotMatch { expected: String, found: String },
 	ExpectedStringLiteral { found: TSXToken },
 	TypeArgumentsNotValidOnReference,
 	UnmatchedBrackets,
 	FunctionParameterOptionalAndDefaultValue,
 	NonOptionalFunctionParameterAfterOptionalFunctionParameter,
-	ExpectedIdent { found: TSXToken, at_location: &'a str },
+	ExpectedIdent { found: TSXToken, at_location: &'static str },
 	ParameterCannotHaveDefaultValueHere,
 	InvalidLHSAssignment,
 	LexingFailed,
+	NamespaceOutsideOfDefinitionFile,
 }
 
 #[allow(missing_docs)]
+#[derive(Debug, PartialEq)]
 pub enum LexingErrors {
 	SecondDecimalPoint,
 	NumberLiteralCannotHaveDecimalPoint,
@@ -84,12 +87,12 @@ impl Display for LexingErrors {
 	}
 }
 
-impl<'a> Display for ParseErrors<'a> {
+impl Display for ParseErrors {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		match self {
 			ParseErrors::UnexpectedToken { expected, found } => {
 				f.write_str("Expected ")?;
-				match expected {
+				match expected.as_slice() {
 					[] => unreachable!("no expected tokens given"),
 					[a] => f.write_fmt(format_args!("{a:?}")),
 					[a, b] => f.write_fmt(format_args!("{a:?} or {b:?}")),
@@ -132,6 +135,9 @@ impl<'a> Display for ParseErrors<'a> {
 				f.write_str("Function parameter cannot be have default value here")
 			}
 			ParseErrors::InvalidLHSAssignment => f.write_str("Invalid syntax on LHS of assignment"),
+			ParseErrors::NamespaceOutsideOfDefinitionFile => {
+				f.write_str("Namespaces are only supported in type definition files, use a module")
+			}
 			ParseErrors::LexingFailed => {
 				// unreachable!("This should never be written"),
 				f.write_str("Lexing issue")
@@ -145,7 +151,7 @@ impl From<Option<(TSXToken, Token<TSXToken, Span>)>> for ParseError {
 	fn from(opt: Option<(TSXToken, Token<TSXToken, Span>)>) -> Self {
 		if let Some((expected_type, Token(token, invalid_token_position))) = opt {
 			Self::new(
-				ParseErrors::UnexpectedToken { expected: &[expected_type], found: token },
+				ParseErrors::UnexpectedToken { expected: vec![expected_type], found: token },
 				invalid_token_position,
 			)
 		} else {
@@ -159,9 +165,105 @@ pub(crate) fn parse_lexing_error() -> ParseError {
 	ParseError::new(ParseErrors::LexingFailed, Span::NULL_SPAN)
 }
 
-pub trait ParserErrorReason: Display {}
+/// The reason for a [ParseError], for matching on
+#[derive(Debug, PartialEq)]
+pub enum ParseErrorKind {
+	Parse(ParseErrors),
+	Lexing(LexingErrors),
+}
+
+impl From<ParseErrors> for ParseErrorKind {
+	fn from(error: ParseErrors) -> Self {
+		ParseErrorKind::Parse(error)
+	}
+}
+
+impl From<LexingErrors> for ParseErrorKind {
+	fn from(error: LexingErrors) -> Self {
+		ParseErrorKind::Lexing(error)
+	}
+}
+
+impl Display for ParseErrorKind {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ParseErrorKind::Parse(error) => Display::fmt(error, f),
+			ParseErrorKind::Lexing(error) => Display::fmt(error, f),
+		}
+	}
+}
+
+impl ParseErrorKind {
+	/// A stable identifier for the kind of error. Parse errors are `E00xx` and lexing errors
+	/// are `E01xx`
+	pub fn code(&self) -> &'static str {
+		match self {
+			ParseErrorKind::Parse(error) => match error {
+				ParseErrors::UnexpectedToken { .. } => "E0001",
+				ParseErrors::UnexpectedSymbol(_) => "E0002",
+				ParseErrors::ClosingTagDoesNotMatch { .. } => "E0003",
+				ParseErrors::ExpectedStringLiteral { .. } => "E0004",
+				ParseErrors::TypeArgumentsNotValidOnReference => "E0005",
+				ParseErrors::UnmatchedBrackets => "E0006",
+				ParseErrors::FunctionParameterOptionalAndDefaultValue => "E0007",
+				ParseErrors::NonOptionalFunctionParameterAfterOptionalFunctionParameter => "E0008",
+				ParseErrors::ExpectedIdent { .. } => "E0009",
+				ParseErrors::ParameterCannotHaveDefaultValueHere => "E0010",
+				ParseErrors::InvalidLHSAssignment => "E0011",
+				ParseErrors::LexingFailed => "E0012",
+				ParseErrors::NamespaceOutsideOfDefinitionFile => "E0013",
+			},
+			ParseErrorKind::Lexing(error) => match error {
+				LexingErrors::SecondDecimalPoint => "E0101",
+				LexingErrors::NumberLiteralCannotHaveDecimalPoint => "E0102",
+				LexingErrors::NumberLiteralBaseSpecifierMustBeSecondCharacter => "E0103",
+				LexingErrors::NumberLiteralBaseSpecifierMustPrecededWithZero => "E0104",
+				LexingErrors::InvalidCharacterInJSXTag(_) => "E0105",
+				LexingErrors::UnbalancedJSXClosingTags => "E0106",
+				LexingErrors::ExpectedClosingAngleAtEndOfSelfClosingTag => "E0107",
+				LexingErrors::InvalidCharacterInAttributeKey(_) => "E0108",
+				LexingErrors::UnexpectedCharacter(_) => "E0109",
+				LexingErrors::EmptyAttributeName => "E0110",
+				LexingErrors::ExpectedJSXEndTag => "E0111",
+				LexingErrors::NewLineInStringLiteral => "E0112",
+				LexingErrors::ExpectedEndToMultilineComment => "E0113",
+				LexingErrors::ExpectedEndToStringLiteral => "E0114",
+				LexingErrors::ExpectedEndToRegexLiteral => "E0115",
+				LexingErrors::ExpectedEndToJSXLiteral => "E0116",
+				LexingErrors::ExpectedEndToTemplateLiteral => "E0117",
+			},
+		}
+	}
+
+	/// A suggestion for fixing the error
+	pub fn help(&self) -> Option<&'static str> {
+		match self {
+			ParseErrorKind::Parse(ParseErrors::FunctionParameterOptionalAndDefaultValue) => {
+				Some("parameters with a default value are already optional, remove the `?`")
+			}
+			ParseErrorKind::Parse(
+				ParseErrors::NonOptionalFunctionParameterAfterOptionalFunctionParameter,
+			) => Some("move optional parameters after the required parameters"),
+			ParseErrorKind::Parse(ParseErrors::InvalidLHSAssignment) => {
+				Some("only variables, properties and destructuring patterns can be assigned to")
+			}
+			ParseErrorKind::Parse(ParseErrors::TypeArgumentsNotValidOnReference) => {
+				Some("type arguments can only be passed when calling or constructing")
+			}
+			ParseErrorKind::Lexing(LexingErrors::NewLineInStringLiteral) => {
+				Some("use a template literal (`) for strings over multiple lines")
+			}
+			ParseErrorKind::Lexing(
+				LexingErrors::NumberLiteralBaseSpecifierMustPrecededWithZero,
+			) => Some("prefix the number with 0, for example `0x1F`"),
+			_ => None,
+		}
+	}
+}
+
+pub trait ParserErrorReason: Display + Into<ParseErrorKind> {}
 
-impl<'a> ParserErrorReason for ParseErrors<'a> {}
+impl ParserErrorReason for ParseErrors {}
 impl ParserErrorReason for LexingErrors {}
 
 /// A error for not parsing
@@ -169,11 +271,31 @@ impl ParserErrorReason for LexingErrors {}
 pub struct ParseError {
 	pub reason: String,
 	pub position: Span,
+	pub kind: ParseErrorKind,
+	/// Other positions relevant to the error, with a message
+	pub labels: Vec<(Span, String)>,
 }
 
 impl ParseError {
 	pub fn new(reason: impl ParserErrorReason, position: Span) -> Self {
-		Self { reason: reason.to_string(), position }
+		let kind = reason.into();
+		Self { reason: kind.to_string(), position, kind, labels: Vec::new() }
+	}
+
+	/// Adds a secondary label
+	pub fn with_label(mut self, position: Span, message: impl Into<String>) -> Self {
+		self.labels.push((position, message.into()));
+		self
+	}
+
+	/// See [ParseErrorKind::code]
+	pub fn code(&self) -> &'static str {
+		self.kind.code()
+	}
+
+	/// See [ParseErrorKind::help]
+	pub fn help(&self) -> Option<&'static str> {
+		self.kind.help()
 	}
 }
 
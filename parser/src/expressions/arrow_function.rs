@@ -70,6 +70,7 @@ impl FunctionBased for ArrowFunctionBase {
 							.into(),
 					),
 					type_reference: None,
+					property: None,
 				}];
 				Ok(FunctionParameters {
 					parameters,
@@ -131,6 +132,7 @@ impl ArrowFunction {
 				.into(),
 			),
 			type_reference: None,
+			property: None,
 		}];
 		reader.expect_next(TSXToken::Arrow)?;
 		let body = ExpressionOrBlock::from_reader(reader, state, settings)?;
@@ -186,7 +188,7 @@ impl ArrowFunction {
 
 /// For [ArrowFunction] and [crate::MatchArm] bodies
 #[derive(Debug, Clone, Eq, PartialEq, Visitable)]
-// #[cfg_attr(feature = "self-rust-tokenize", derive(self_rust_tokenize::SelfRustTokenize))]
+#[cfg_attr(feature = "self-rust-tokenize", derive(self_rust_tokenize::SelfRustTokenize))]
 pub enum ExpressionOrBlock {
 	Expression(Box<Expression>),
 	Block(Block),
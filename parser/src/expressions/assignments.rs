@@ -1,6 +1,6 @@
 use std::borrow::Cow;
 
-use crate::{PropertyReference, TSXToken};
+use crate::{CursorId, PropertyReference, TSXToken};
 use derive_partial_eq_extras::PartialEqExtras;
 use iterator_endiate::EndiateIteratorExt;
 use source_map::Span;
@@ -31,6 +31,14 @@ pub enum VariableOrPropertyAccess {
 		position: Span,
 		expression_id: ExpressionId,
 	},
+	/// For patterns and partial ASTs. Uses the expression cursor as it is converted from an expression
+	#[self_tokenize_field(cursor_id)]
+	Cursor {
+		#[visit_skip_field]
+		cursor_id: CursorId<Expression>,
+		position: Span,
+		expression_id: ExpressionId,
+	},
 }
 
 impl ASTNode for VariableOrPropertyAccess {
@@ -38,7 +46,8 @@ impl ASTNode for VariableOrPropertyAccess {
 		match self {
 			VariableOrPropertyAccess::Variable(_, position, _)
 			| VariableOrPropertyAccess::PropertyAccess { position, .. }
-			| VariableOrPropertyAccess::Index { position, .. } => Cow::Borrowed(position),
+			| VariableOrPropertyAccess::Index { position, .. }
+			| VariableOrPropertyAccess::Cursor { position, .. } => Cow::Borrowed(position),
 		}
 	}
 
@@ -75,6 +84,11 @@ impl ASTNode for VariableOrPropertyAccess {
 				indexer.to_string_from_buffer(buf, settings, depth);
 				buf.push(']');
 			}
+			VariableOrPropertyAccess::Cursor { .. } => {
+				if !settings.0.expect_cursors {
+					panic!("found cursor");
+				}
+			}
 		}
 	}
 }
@@ -102,6 +116,9 @@ impl TryFrom<Expression> for VariableOrPropertyAccess {
 			Expression::Index { expression_id, indexer, position, indexee } => {
 				Ok(Self::Index { indexer, position, indexee, expression_id })
 			}
+			Expression::Cursor { cursor_id, position, expression_id } => {
+				Ok(Self::Cursor { cursor_id, position, expression_id })
+			}
 			expression => Err(ParseError::new(
 				crate::ParseErrors::InvalidLHSAssignment,
 				expression.get_position().into_owned(),
@@ -131,6 +148,9 @@ impl From<VariableOrPropertyAccess> for Expression {
 				property,
 				is_optional: false,
 			},
+			VariableOrPropertyAccess::Cursor { cursor_id, position, expression_id } => {
+				Expression::Cursor { cursor_id, position, expression_id }
+			}
 		}
 	}
 }
@@ -138,7 +158,9 @@ impl From<VariableOrPropertyAccess> for Expression {
 impl VariableOrPropertyAccess {
 	pub fn get_parent(&self) -> Option<&Expression> {
 		match self {
-			VariableOrPropertyAccess::Variable(..) => None,
+			VariableOrPropertyAccess::Variable(..) | VariableOrPropertyAccess::Cursor { .. } => {
+				None
+			}
 			VariableOrPropertyAccess::PropertyAccess { parent, .. }
 			| VariableOrPropertyAccess::Index { indexee: parent, .. } => Some(parent),
 		}
@@ -146,7 +168,9 @@ impl VariableOrPropertyAccess {
 
 	pub fn get_parent_mut(&mut self) -> Option<&mut Expression> {
 		match self {
-			VariableOrPropertyAccess::Variable(..) => None,
+			VariableOrPropertyAccess::Variable(..) | VariableOrPropertyAccess::Cursor { .. } => {
+				None
+			}
 			VariableOrPropertyAccess::PropertyAccess { parent, .. }
 			| VariableOrPropertyAccess::Index { indexee: parent, .. } => Some(parent),
 		}
@@ -176,8 +200,8 @@ pub enum LHSOfAssignment {
 impl LHSOfAssignment {
 	pub fn get_position(&self) -> Cow<Span> {
 		match self {
-			LHSOfAssignment::ObjectDestructuring(_, _, _) => todo!(),
-			LHSOfAssignment::ArrayDestructuring(_, _, _) => todo!(),
+			LHSOfAssignment::ObjectDestructuring(_, position, _)
+			| LHSOfAssignment::ArrayDestructuring(_, position, _) => Cow::Borrowed(position),
 			LHSOfAssignment::VariableOrPropertyAccess(var_prop_access) => {
 				var_prop_access.get_position()
 			}
@@ -5,9 +5,9 @@ use crate::{
 	extractor::{ExtractedFunction, ExtractedFunctions, GetFunction},
 	functions::GeneralFunctionBase,
 	operators::{
-		AssociativityDirection, BinaryAssignmentOperator, UnaryPostfixAssignmentOperator,
-		UnaryPrefixAssignmentOperator, ASSIGNMENT_PRECEDENCE, AS_PRECEDENCE,
-		FUNCTION_CALL_PRECEDENCE, OPTIONAL_CHAINING_PRECEDENCE,
+		AssociativityDirection, BinaryAssignmentOperator, IncrementOrDecrement,
+		UnaryPostfixAssignmentOperator, UnaryPrefixAssignmentOperator, ASSIGNMENT_PRECEDENCE,
+		AS_PRECEDENCE, FUNCTION_CALL_PRECEDENCE, OPTIONAL_CHAINING_PRECEDENCE,
 	},
 	parse_bracketed, to_string_bracketed,
 	type_references::generic_arguments_from_reader_sub_open_angle,
@@ -309,13 +309,11 @@ impl Expression {
 		return_precedence: u8,
 	) -> ParseResult<Self> {
 		let first_expression = match reader.next().ok_or_else(parse_lexing_error)? {
-			Token(TSXToken::Cursor(cursor_id), position) => {
-				return Ok(Expression::Cursor {
-					cursor_id: cursor_id.into_cursor(),
-					position,
-					expression_id: ExpressionId::new(),
-				});
-			}
+			Token(TSXToken::Cursor(cursor_id), position) => Expression::Cursor {
+				cursor_id: cursor_id.into_cursor(),
+				position,
+				expression_id: ExpressionId::new(),
+			},
 			Token(TSXToken::SingleQuotedStringLiteral(expression), position) => {
 				Expression::StringLiteral(expression, Quoted::Single, position, ExpressionId::new())
 			}
@@ -375,7 +373,7 @@ impl Expression {
 					token => {
 						return Err(ParseError::new(
 							crate::ParseErrors::UnexpectedToken {
-								expected: &[
+								expected: vec![
 									TSXToken::Dot,
 									TSXToken::OpenParentheses,
 									TSXToken::OpenBracket,
@@ -400,16 +398,20 @@ impl Expression {
 			}
 			Token(TSXToken::OpenBracket, start_pos) => {
 				let mut bracket_depth = 1;
-				let after_bracket = reader.scan(|token, _| match token {
-					TSXToken::OpenBracket => {
-						bracket_depth += 1;
-						false
-					}
-					TSXToken::CloseBracket => {
-						bracket_depth -= 1;
-						bracket_depth == 0
+				let mut last_position = None;
+				let after_bracket = reader.scan(|token, position| {
+					last_position = Some(position.clone());
+					match token {
+						TSXToken::OpenBracket => {
+							bracket_depth += 1;
+							false
+						}
+						TSXToken::CloseBracket => {
+							bracket_depth -= 1;
+							bracket_depth == 0
+						}
+						_ => false,
 					}
-					_ => false,
 				});
 				if let Some(Token(token_type, _)) = after_bracket {
 					if let TSXToken::Assign = token_type {
@@ -441,21 +443,25 @@ impl Expression {
 						)
 					}
 				} else {
-					return Err(ParseError::new(crate::ParseErrors::UnmatchedBrackets, start_pos));
+					return Err(unmatched_brackets(start_pos, last_position));
 				}
 			}
 			Token(TSXToken::OpenBrace, start_pos) => {
 				let mut brace_depth = 1;
-				let after_brace = reader.scan(|token, _| match token {
-					TSXToken::OpenBrace => {
-						brace_depth += 1;
-						false
-					}
-					TSXToken::CloseBrace => {
-						brace_depth -= 1;
-						brace_depth == 0
+				let mut last_position = None;
+				let after_brace = reader.scan(|token, position| {
+					last_position = Some(position.clone());
+					match token {
+						TSXToken::OpenBrace => {
+							brace_depth += 1;
+							false
+						}
+						TSXToken::CloseBrace => {
+							brace_depth -= 1;
+							brace_depth == 0
+						}
+						_ => false,
 					}
-					_ => false,
 				});
 				if let Some(Token(token_type, _)) = after_brace {
 					if let TSXToken::Assign = token_type {
@@ -484,21 +490,25 @@ impl Expression {
 						Expression::ObjectLiteral(object_literal)
 					}
 				} else {
-					return Err(ParseError::new(crate::ParseErrors::UnmatchedBrackets, start_pos));
+					return Err(unmatched_brackets(start_pos, last_position));
 				}
 			}
 			Token(TSXToken::OpenParentheses, open_paren_span) => {
 				let mut parentheses_depth = 1;
-				let next = reader.scan(|token, _| match token {
-					TSXToken::OpenParentheses => {
-						parentheses_depth += 1;
-						false
-					}
-					TSXToken::CloseParentheses => {
-						parentheses_depth -= 1;
-						parentheses_depth == 0
+				let mut last_position = None;
+				let next = reader.scan(|token, position| {
+					last_position = Some(position.clone());
+					match token {
+						TSXToken::OpenParentheses => {
+							parentheses_depth += 1;
+							false
+						}
+						TSXToken::CloseParentheses => {
+							parentheses_depth -= 1;
+							parentheses_depth == 0
+						}
+						_ => false,
 					}
-					_ => false,
 				});
 				if let Some(Token(token_type, _)) = next {
 					if let TSXToken::Arrow = token_type {
@@ -522,10 +532,17 @@ impl Expression {
 						)
 					}
 				} else {
-					return Err(ParseError::new(
-						crate::ParseErrors::UnmatchedBrackets,
-						open_paren_span,
-					));
+					return Err(unmatched_brackets(open_paren_span, last_position));
+				}
+			}
+			Token(TSXToken::Keyword(TSXKeyword::Import), start_pos) => {
+				reader.expect_next(TSXToken::OpenParentheses)?;
+				let path = Expression::from_reader(reader, state, settings)?;
+				let end_pos = reader.expect_next(TSXToken::CloseParentheses)?;
+				Expression::DynamicImport {
+					path: Box::new(path),
+					position: start_pos.union(&end_pos),
+					expression_id: ExpressionId::new(),
 				}
 			}
 			Token(TSXToken::Keyword(TSXKeyword::New), new_pos) => {
@@ -554,7 +571,7 @@ impl Expression {
 						(None, position)
 					};
 					let (arguments, end_pos) = if reader
-						.conditional_next(|token| *token == TSXToken::OpenChevron)
+						.conditional_next(|token| *token == TSXToken::OpenParentheses)
 						.is_some()
 					{
 						parse_bracketed(reader, state, settings, None, TSXToken::CloseParentheses)
@@ -585,8 +602,14 @@ impl Expression {
 				{
 					return Ok(Expression::Comment(comment, start_pos));
 				}
+				// Annotations only apply to the call or `new` expression directly after them
+				let precedence = if crate::is_purity_annotation(&comment) {
+					CONSTRUCTOR_WITHOUT_PARENTHESIS_PRECEDENCE
+				} else {
+					return_precedence
+				};
 				let expression =
-					Self::from_reader_with_precedence(reader, state, settings, return_precedence)?;
+					Self::from_reader_with_precedence(reader, state, settings, precedence)?;
 				let position = start_pos.union(&expression.get_position());
 				Expression::PrefixComment(
 					comment,
@@ -610,8 +633,9 @@ impl Expression {
 				let header = FunctionHeader::VirginFunctionHeader {
 					async_keyword: None,
 					function_keyword: Keyword::new(span),
-					// TODO
-					generator_star_token_pos: None,
+					generator_star_token_pos: reader
+						.conditional_next(|tok| *tok == TSXToken::Multiply)
+						.map(|Token(_, pos)| pos),
 				};
 				let name = if let Some(Token(TSXToken::OpenParentheses, _)) = reader.peek() {
 					None
@@ -696,7 +720,7 @@ impl Expression {
 					Token(token, position) => {
 						return Err(ParseError::new(
 							crate::ParseErrors::UnexpectedToken {
-								expected: &[
+								expected: vec![
 									TSXToken::Keyword(TSXKeyword::Function),
 									TSXToken::OpenParentheses,
 								],
@@ -716,10 +740,38 @@ impl Expression {
 			)
 			.map(Expression::IsExpression)?,
 			token => {
-				if let Ok(unary_operator) = UnaryOperator::try_from(&token.0) {
+				if let Ok(mut unary_operator) = UnaryOperator::try_from(&token.0) {
+					if unary_operator == UnaryOperator::Yield
+						&& reader.conditional_next(|tok| *tok == TSXToken::Multiply).is_some()
+					{
+						unary_operator = UnaryOperator::DelegatedYield;
+					}
 					let op_precedence = unary_operator.precedence();
-					let operand =
-						Self::from_reader_with_precedence(reader, state, settings, op_precedence)?;
+					let operand = if unary_operator == UnaryOperator::Yield
+						&& matches!(
+							reader.peek(),
+							Some(Token(
+								TSXToken::CloseParentheses
+									| TSXToken::CloseBracket | TSXToken::CloseBrace
+									| TSXToken::Comma | TSXToken::SemiColon
+									| TSXToken::Colon | TSXToken::EOS,
+								_
+							)) | None
+						) {
+						// `yield` without an argument yields `undefined`
+						Expression::UnaryOperation {
+							operator: UnaryOperator::Void,
+							operand: Box::new(Expression::NumberLiteral(
+								crate::NumberStructure::Number(0f64),
+								token.1.clone(),
+								ExpressionId::new(),
+							)),
+							position: token.1.clone(),
+							id: ExpressionId::new(),
+						}
+					} else {
+						Self::from_reader_with_precedence(reader, state, settings, op_precedence)?
+					};
 					let position = token.1.union(&operand.get_position());
 					Expression::UnaryOperation {
 						operand: Box::new(operand),
@@ -997,12 +1049,13 @@ impl Expression {
 							return Ok(top);
 						}
 						let _ = reader.next().unwrap();
-						// Note precedence is already handled
+						// Operators with the same or lower precedence (except `**`) apply to this
+						// operation rather than the right hand side
 						let rhs = Self::from_reader_with_precedence(
 							reader,
 							state,
 							settings,
-							parent_precedence,
+							operator.precedence(),
 						)?;
 
 						top = Expression::BinaryOperation {
@@ -1103,6 +1156,39 @@ impl Expression {
         }
 	}
 
+	/// The sign the printed expression starts with. Used so that `a - -b` is not printed as `a--b`
+	fn leading_sign(&self) -> Option<char> {
+		match self {
+			Self::UnaryOperation { operator: UnaryOperator::Plus, .. }
+			| Self::UnaryPrefixAssignmentOperation {
+				operator:
+					UnaryPrefixAssignmentOperator::IncrementOrDecrement(IncrementOrDecrement::Increment),
+				..
+			} => Some('+'),
+			Self::UnaryOperation { operator: UnaryOperator::Negation, .. }
+			| Self::UnaryPrefixAssignmentOperation {
+				operator:
+					UnaryPrefixAssignmentOperator::IncrementOrDecrement(IncrementOrDecrement::Decrement),
+				..
+			} => Some('-'),
+			Self::NumberLiteral(number, ..) => number.to_string().starts_with('-').then_some('-'),
+			Self::BinaryOperation { lhs, .. } => lhs.leading_sign(),
+			_ => None,
+		}
+	}
+
+	/// Whether the printed expression starts and ends with punctuation. Used so that keyword
+	/// operators are only printed without spaces next to operands such as `"x"in a`
+	fn is_delimited(&self) -> bool {
+		matches!(
+			self,
+			Self::StringLiteral(..)
+				| Self::ArrayLiteral(..)
+				| Self::ObjectLiteral(..)
+				| Self::ParenthesizedExpression(..)
+		)
+	}
+
 	pub(crate) fn to_string_using_precedence<T: source_map::ToString>(
 		&self,
 		buf: &mut T,
@@ -1110,6 +1196,7 @@ impl Expression {
 		depth: u8,
 		_parent_precedence: u8,
 	) {
+		crate::add_mapping(buf, &self.get_position());
 		match self {
 			Self::Cursor { .. } => {
 				if !settings.0.expect_cursors {
@@ -1135,10 +1222,24 @@ impl Expression {
 			}
 			Self::BinaryOperation { lhs, operator, rhs, .. } => {
 				let op_precedence = operator.precedence();
+				let is_keyword =
+					matches!(operator, BinaryOperator::InstanceOf | BinaryOperator::In);
 				lhs.to_string_using_precedence(buf, settings, depth, op_precedence);
+				if is_keyword && !settings.0.pretty && !lhs.is_delimited() {
+					buf.push(' ');
+				}
 				settings.0.add_gap(buf);
 				buf.push_str(operator.to_str());
 				settings.0.add_gap(buf);
+				if is_keyword && !settings.0.pretty && !rhs.is_delimited() {
+					buf.push(' ');
+				}
+				if !settings.0.pretty
+					&& matches!(operator, BinaryOperator::Add | BinaryOperator::Subtract)
+					&& rhs.leading_sign() == operator.to_str().chars().next()
+				{
+					buf.push(' ');
+				}
 				rhs.to_string_using_precedence(buf, settings, depth, op_precedence);
 			}
 			Self::SpecialOperators(special, _, _) => match special {
@@ -1159,6 +1260,11 @@ impl Expression {
 			},
 			Self::UnaryOperation { operand, operator, .. } => {
 				buf.push_str(operator.to_str());
+				if matches!(operator, UnaryOperator::Plus | UnaryOperator::Negation)
+					&& operand.leading_sign() == operator.to_str().chars().next()
+				{
+					buf.push(' ');
+				}
 				operand.to_string_from_buffer(buf, settings, depth);
 			}
 			Self::Assignment { lhs, rhs, .. } => {
@@ -1235,11 +1341,9 @@ impl Expression {
 				if let (true, Some(type_arguments)) = (settings.0.include_types, type_arguments) {
 					to_string_bracketed(type_arguments, ('<', '>'), buf, settings, depth);
 				}
+				// The brackets are kept as `new a()` can be followed by a property access
 				if let Some(arguments) = arguments {
-					// Constructor calls can drop arguments if none
-					if !arguments.is_empty() {
-						to_string_bracketed(arguments, ('(', ')'), buf, settings, depth);
-					}
+					to_string_bracketed(arguments, ('(', ')'), buf, settings, depth);
 				}
 			}
 			Self::ArrayLiteral(values, _, _) => {
@@ -1277,7 +1381,21 @@ impl Expression {
 			}
 			Self::ClassExpression(class, _) => class.to_string_from_buffer(buf, settings, depth),
 			Self::PrefixComment(comment, expression, _, _) => {
-				if settings.0.should_add_comment() {
+				// Annotations are kept so that tools which read the output can use them, unless the
+				// call is no longer there (e.g. it is printed as its result)
+				let is_annotation = crate::is_purity_annotation(comment);
+				let is_call = match expression.get_non_parenthesized() {
+					call @ Self::FunctionCall { .. } => {
+						!matches!(call.is_iife(&settings.1), Some(ExpressionOrBlock::Expression(_)))
+					}
+					Self::ConstructorCall { .. } => true,
+					_ => false,
+				};
+				if is_annotation && is_call {
+					buf.push_str("/*");
+					buf.push_str(comment.as_str());
+					buf.push_str(if settings.0.pretty { "*/ " } else { "*/" });
+				} else if !is_annotation && settings.0.should_add_comment() {
 					buf.push_str("/*");
 					buf.push_str_contains_new_line(comment.as_str());
 					buf.push_str("*/ ");
@@ -1381,8 +1499,9 @@ impl ASTNode for MultipleExpression {
 		depth: u8,
 	) {
 		if let Some(ref lhs) = self.lhs {
-			buf.push(',');
 			lhs.to_string_from_buffer(buf, settings, depth);
+			buf.push(',');
+			settings.0.add_gap(buf);
 		}
 		self.rhs.to_string_from_buffer(buf, settings, depth);
 	}
@@ -1443,6 +1562,17 @@ fn is_generic_arguments(reader: &mut impl TokenReader<TSXToken, Span>) -> bool {
 	}
 }
 
+/// Reported at the last token, pointing back to the bracket which is not closed
+fn unmatched_brackets(open_position: Span, last_position: Option<Span>) -> ParseError {
+	match last_position {
+		Some(last_position) => {
+			ParseError::new(crate::ParseErrors::UnmatchedBrackets, last_position)
+				.with_label(open_position, "Unclosed bracket")
+		}
+		None => ParseError::new(crate::ParseErrors::UnmatchedBrackets, open_position),
+	}
+}
+
 /// Binary operations whose RHS are types rather than [Expression]s
 #[derive(PartialEqExtras, Debug, Clone, Visitable)]
 #[partial_eq_ignore_types(Span, ExpressionId)]
@@ -1795,4 +1925,26 @@ mod tests {
 			id: _
 		});
 	}
+
+	#[test]
+	fn binary_expression_precedence() {
+		assert_matches_ast!("a === b && c", BinaryOperation {
+			lhs: Deref @ BinaryOperation { operator: BinaryOperator::StrictEqual, .. },
+			operator: BinaryOperator::LogicalAnd,
+			rhs: Deref @ VariableReference(..),
+			id: _
+		});
+		assert_matches_ast!("a - b - c", BinaryOperation {
+			lhs: Deref @ BinaryOperation { operator: BinaryOperator::Subtract, .. },
+			operator: BinaryOperator::Subtract,
+			rhs: Deref @ VariableReference(..),
+			id: _
+		});
+		assert_matches_ast!("a ** b ** c", BinaryOperation {
+			lhs: Deref @ VariableReference(..),
+			operator: BinaryOperator::Exponent,
+			rhs: Deref @ BinaryOperation { operator: BinaryOperator::Exponent, .. },
+			id: _
+		});
+	}
 }
@@ -9,8 +9,9 @@ use crate::{
 	extractor::{ExtractedFunction, GetFunction},
 	functions::FunctionBased,
 	property_key::PropertyId,
-	ASTNode, Block, Expression, FunctionBase, GetSetGeneratorOrNone, ParseError, ParseErrors,
-	ParseResult, ParseSettings, PropertyKey, Span, TSXToken, Token, TokenReader, WithComment,
+	tsx_keywords, ASTNode, Block, Expression, FunctionBase, GetSetGeneratorOrNone, Keyword,
+	ParseError, ParseErrors, ParseResult, ParseSettings, PropertyKey, Span, TSXKeyword, TSXToken,
+	Token, TokenReader, WithComment,
 };
 
 #[derive(Debug, Clone, Eq, PartialEq, Visitable)]
@@ -43,9 +44,11 @@ impl crate::Visitable for ObjectLiteralMember {
 		chain: &mut temporary_annex::Annex<crate::Chain>,
 	) {
 		match self {
-			ObjectLiteralMember::SpreadExpression(_, _) => {}
+			ObjectLiteralMember::SpreadExpression(expression, _)
+			| ObjectLiteralMember::Property(_, expression, _) => {
+				expression.visit(visitors, data, settings, functions, chain)
+			}
 			ObjectLiteralMember::Shorthand(_, _, _, _) => {}
-			ObjectLiteralMember::Property(_, _, _) => {}
 			ObjectLiteralMember::Method(method) => {
 				method.visit(visitors, data, settings, functions, chain)
 			}
@@ -61,9 +64,11 @@ impl crate::Visitable for ObjectLiteralMember {
 		chain: &mut temporary_annex::Annex<crate::Chain>,
 	) {
 		match self {
-			ObjectLiteralMember::SpreadExpression(_, _) => {}
+			ObjectLiteralMember::SpreadExpression(expression, _)
+			| ObjectLiteralMember::Property(_, expression, _) => {
+				expression.visit_mut(visitors, data, settings, functions, chain)
+			}
 			ObjectLiteralMember::Shorthand(_, _, _, _) => {}
-			ObjectLiteralMember::Property(_, _, _) => {}
 			ObjectLiteralMember::Method(method) => {
 				method.visit_mut(visitors, data, settings, functions, chain)
 			}
@@ -76,12 +81,12 @@ pub struct ObjectLiteralMethodBase;
 pub type ObjectLiteralMethod = FunctionBase<ObjectLiteralMethodBase>;
 
 impl FunctionBased for ObjectLiteralMethodBase {
-	type Header = GetSetGeneratorOrNone;
+	type Header = (Option<Keyword<tsx_keywords::Async>>, GetSetGeneratorOrNone);
 	type Name = WithComment<PropertyKey>;
 	type Body = Block;
 
-	fn get_chain_variable(_this: &FunctionBase<Self>) -> crate::ChainVariable {
-		crate::ChainVariable::UnderObjectLiteralMethod
+	fn get_chain_variable(this: &FunctionBase<Self>) -> crate::ChainVariable {
+		crate::ChainVariable::UnderObjectLiteralMethod(this.body.1)
 	}
 
 	fn header_and_name_from_reader(
@@ -89,10 +94,12 @@ impl FunctionBased for ObjectLiteralMethodBase {
 		state: &mut crate::ParsingState,
 		settings: &ParseSettings,
 	) -> ParseResult<(Self::Header, Self::Name)> {
-		Ok((
-			GetSetGeneratorOrNone::from_reader(reader),
-			WithComment::<PropertyKey>::from_reader(reader, state, settings)?,
-		))
+		let async_keyword = reader
+			.conditional_next(|tok| matches!(tok, TSXToken::Keyword(TSXKeyword::Async)))
+			.map(|Token(_, span)| Keyword::new(span));
+		let header = GetSetGeneratorOrNone::from_reader(reader);
+		let name = WithComment::<PropertyKey>::from_reader(reader, state, settings)?;
+		Ok(((async_keyword, header), name))
 	}
 
 	fn header_and_name_to_string_from_buffer<T: source_map::ToString>(
@@ -102,12 +109,15 @@ impl FunctionBased for ObjectLiteralMethodBase {
 		settings: &crate::ToStringSettingsAndData,
 		depth: u8,
 	) {
-		header.to_string_from_buffer(buf);
+		if let Some(_header) = &header.0 {
+			buf.push_str("async ");
+		}
+		header.1.to_string_from_buffer(buf);
 		name.to_string_from_buffer(buf, settings, depth);
 	}
 
 	fn header_left(header: &Self::Header) -> Option<Cow<Span>> {
-		header.get_position()
+		header.0.as_ref().map(|async_kw| Cow::Borrowed(&async_kw.1)).xor(header.1.get_position())
 	}
 }
 
@@ -185,6 +195,26 @@ impl ASTNode for ObjectLiteralMember {
 		state: &mut crate::ParsingState,
 		settings: &ParseSettings,
 	) -> ParseResult<Self> {
+		if let Some(Token(TSXToken::Spread, _)) = reader.peek() {
+			let Token(_, spread_position) = reader.next().unwrap();
+			let expression = Expression::from_reader(reader, state, settings)?;
+			let position = spread_position.union(&expression.get_position());
+			return Ok(Self::SpreadExpression(expression, position));
+		}
+		// `async` is only a modifier if it is not the key itself
+		let is_async =
+			matches!(reader.peek(), Some(Token(TSXToken::Keyword(TSXKeyword::Async), _)))
+				&& !matches!(
+					reader.peek_n(1),
+					Some(Token(
+						TSXToken::OpenParentheses
+							| TSXToken::OpenChevron
+							| TSXToken::Colon | TSXToken::Comma
+							| TSXToken::CloseBrace,
+						_
+					))
+				);
+		let async_keyword = is_async.then(|| Keyword::new(reader.next().unwrap().1));
 		// TODO this probably needs with comment here:
 		let mut get_set_generator_or_none = GetSetGeneratorOrNone::from_reader(reader);
 		// Catch for named get or set :(
@@ -215,7 +245,7 @@ impl ASTNode for ObjectLiteralMember {
 						reader,
 						state,
 						settings,
-						get_set_generator_or_none,
+						(async_keyword, get_set_generator_or_none),
 						key,
 					)?;
 
@@ -223,11 +253,13 @@ impl ASTNode for ObjectLiteralMember {
 				Ok(Self::Method(extracted))
 			}
 			_ => {
-				if get_set_generator_or_none != GetSetGeneratorOrNone::None {
+				if async_keyword.is_some()
+					|| get_set_generator_or_none != GetSetGeneratorOrNone::None
+				{
 					let Token(token, position) = reader.next().unwrap();
 					return Err(ParseError::new(
 						ParseErrors::UnexpectedToken {
-							expected: &[TSXToken::OpenParentheses],
+							expected: vec![TSXToken::OpenParentheses],
 							found: token,
 						},
 						position,
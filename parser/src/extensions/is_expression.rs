@@ -13,6 +13,7 @@ use crate::{
 };
 
 #[derive(Debug, PartialEq, Eq, Clone, Visitable)]
+#[cfg_attr(feature = "self-rust-tokenize", derive(self_rust_tokenize::SelfRustTokenize))]
 pub struct IsExpression {
 	pub is: Keyword<Is>,
 	pub matcher: Box<MultipleExpression>,
@@ -21,16 +22,6 @@ pub struct IsExpression {
 	pub position: Span,
 }
 
-#[cfg(feature = "self-rust-tokenize")]
-impl self_rust_tokenize::SelfRustTokenize for IsExpression {
-	fn append_to_token_stream(
-		&self,
-		_token_stream: &mut self_rust_tokenize::proc_macro2::TokenStream,
-	) {
-		todo!()
-	}
-}
-
 impl ASTNode for IsExpression {
 	fn get_position(&self) -> Cow<source_map::Span> {
 		Cow::Borrowed(&self.position)
@@ -79,7 +70,7 @@ pub(crate) fn is_expression_from_reader_sub_is_keyword(
 	reader.expect_next(TSXToken::OpenBrace)?;
 	let mut branches = Vec::new();
 	loop {
-		let type_reference = TypeReference::from_reader(reader, state, settings)?;
+		let type_reference = TypeReference::from_reader_before_arrow(reader, state, settings)?;
 		reader.expect_next(TSXToken::Arrow)?;
 		let body = ExpressionOrBlock::from_reader(reader, state, settings)?;
 		let tokenizer_lib::Token(next, pos) = reader.next().ok_or_else(parse_lexing_error)?;
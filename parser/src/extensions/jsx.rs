@@ -115,6 +115,11 @@ fn parse_jsx_children(
 		let child = match token {
 			TSXToken::JSXContent(content) => JSXNode::TextNode(content, pos),
 			TSXToken::JSXExpressionStart => {
+				// Empty (or only contained a comment, which are not lexed) so has no effect
+				if let Some(Token(TSXToken::JSXExpressionEnd, _)) = reader.peek() {
+					reader.next();
+					continue;
+				}
 				let expression = Expression::from_reader(reader, state, settings)?;
 				let end_pos = reader.expect_next(TSXToken::JSXExpressionEnd)?;
 				JSXNode::InterpolatedExpression(Box::new(expression), pos.union(&end_pos))
@@ -122,6 +127,9 @@ fn parse_jsx_children(
 			TSXToken::JSXOpeningTagStart => {
 				JSXElement::from_reader_sub_start(reader, state, settings, pos)?.into()
 			}
+			TSXToken::JSXFragmentStart => {
+				JSXNode::Fragment(JSXFragment::from_reader_sub_start(reader, state, settings, pos)?)
+			}
 			TSXToken::JSXContentLineBreak => JSXNode::LineBreak,
 			_token => {
 				// unreachable!("Error in JSX lexing {:?}", token);
@@ -183,13 +191,13 @@ impl JSXRoot {
 	}
 }
 
-// TODO Fragment
 #[derive(Debug, Clone, PartialEq, Eq, Visitable)]
 #[cfg_attr(feature = "self-rust-tokenize", derive(self_rust_tokenize::SelfRustTokenize))]
 pub enum JSXNode {
 	TextNode(String, Span),
 	InterpolatedExpression(Box<Expression>, Span),
 	Element(JSXElement),
+	Fragment(JSXFragment),
 	LineBreak,
 }
 
@@ -200,6 +208,7 @@ impl ASTNode for JSXNode {
 				Cow::Borrowed(pos)
 			}
 			JSXNode::Element(element) => element.get_position(),
+			JSXNode::Fragment(fragment) => fragment.get_position(),
 			JSXNode::LineBreak => todo!(),
 		}
 	}
@@ -220,6 +229,9 @@ impl ASTNode for JSXNode {
 	) {
 		match self {
 			JSXNode::Element(element) => element.to_string_from_buffer(buf, settings, depth + 1),
+			JSXNode::Fragment(fragment) => {
+				fragment.to_string_from_buffer(buf, settings, depth + 1);
+			}
 			JSXNode::InterpolatedExpression(expression, _) => {
 				if !settings.0.should_add_comment()
 					&& matches!(&**expression, Expression::Comment(..))
@@ -371,11 +383,14 @@ impl ASTNode for JSXAttribute {
 				buf.push_str(key.as_str());
 			}
 			JSXAttribute::Spread(expr, _) => {
-				buf.push_str("...");
+				buf.push_str("{...");
 				expr.to_string_from_buffer(buf, settings, depth);
+				buf.push('}');
 			}
 			JSXAttribute::Shorthand(expr) => {
+				buf.push('{');
 				expr.to_string_from_buffer(buf, settings, depth);
+				buf.push('}');
 			}
 		}
 	}
@@ -388,11 +403,12 @@ impl JSXElement {
 		settings: &ParseSettings,
 		mut start_position: Span,
 	) -> ParseResult<Self> {
-		let tag_name = if let Some(Token(TSXToken::JSXTagName(tag_name), _)) = reader.next() {
-			tag_name
-		} else {
-			return Err(parse_lexing_error());
-		};
+		let (tag_name, tag_name_position) =
+			if let Some(Token(TSXToken::JSXTagName(tag_name), position)) = reader.next() {
+				(tag_name, position)
+			} else {
+				return Err(parse_lexing_error());
+			};
 		let mut attributes = Vec::new();
 		// TODO spread attributes
 		// Kind of weird / not clear conditions for breaking out of while loop
@@ -415,10 +431,11 @@ impl JSXElement {
 					let attribute = if let Some(Token(TSXToken::Spread, _)) = reader.peek() {
 						let Token(_, spread_pos) = reader.next().unwrap();
 						let expr = Expression::from_reader(reader, state, settings)?;
-						reader.expect_next(TSXToken::CloseBrace)?;
+						reader.expect_next(TSXToken::JSXExpressionEnd)?;
 						JSXAttribute::Spread(expr, spread_pos)
 					} else {
 						let expr = Expression::from_reader(reader, state, settings)?;
+						reader.expect_next(TSXToken::JSXExpressionEnd)?;
 						JSXAttribute::Shorthand(expr)
 					};
 					attributes.push(attribute);
@@ -456,11 +473,12 @@ impl JSXElement {
 				if closing_tag_name != tag_name {
 					return Err(ParseError::new(
 						crate::ParseErrors::ClosingTagDoesNotMatch {
-							expected: &tag_name,
-							found: &closing_tag_name,
+							expected: tag_name,
+							found: closing_tag_name,
 						},
 						position,
-					));
+					)
+					.with_label(tag_name_position, "Opening tag"));
 				}
 				position
 			} else {
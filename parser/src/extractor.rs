@@ -55,6 +55,22 @@ macro_rules! make_extracted_functions {
 				}
 			}
 		)*
+
+		#[cfg(feature = "self-rust-tokenize")]
+		impl ExtractedFunctions {
+			/// Tokens of the function with the id, whatever its kind. Ids are unique across kinds
+			fn function_to_tokens(
+				&self,
+				id: u16,
+			) -> Option<self_rust_tokenize::proc_macro2::TokenStream> {
+				$(
+					if let Some(function) = self.$ty.get(&FunctionId::from_id(id)) {
+						return Some(self_rust_tokenize::SelfRustTokenize::to_tokens(function));
+					}
+				)*
+				None
+			}
+		}
 	}
 }
 
@@ -71,13 +87,75 @@ make_extracted_functions! {
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct ExtractedFunction<T: FunctionBased>(pub FunctionId<T>, pub source_map::Span);
 
+/// Prefix of the placeholder identifier an [ExtractedFunction] is tokenized as, followed by its id
+#[cfg(feature = "self-rust-tokenize")]
+const EXTRACTED_FUNCTION_PLACEHOLDER: &str = "__extracted_function_";
+
+/// Tokenizes a node which may contain [ExtractedFunction]s. Functions are looked up in `functions`.
+///
+/// The generated tokens insert the functions into a `functions` binding of [ExtractedFunctions]
+/// which must be in scope where the tokens are expanded
+#[cfg(feature = "self-rust-tokenize")]
+pub fn tokenize_with_functions(
+	node: &impl self_rust_tokenize::SelfRustTokenize,
+	functions: &ExtractedFunctions,
+) -> self_rust_tokenize::proc_macro2::TokenStream {
+	insert_functions(node.to_tokens(), functions)
+}
+
+/// Replaces placeholders of [ExtractedFunction]s with the tokens of the function they refer to
+#[cfg(feature = "self-rust-tokenize")]
+fn insert_functions(
+	tokens: self_rust_tokenize::proc_macro2::TokenStream,
+	functions: &ExtractedFunctions,
+) -> self_rust_tokenize::proc_macro2::TokenStream {
+	use self_rust_tokenize::proc_macro2::{Group, TokenTree};
+
+	tokens
+		.into_iter()
+		.map(|token| match token {
+			TokenTree::Group(group) => {
+				let mut new_group =
+					Group::new(group.delimiter(), insert_functions(group.stream(), functions));
+				new_group.set_span(group.span());
+				TokenTree::Group(new_group)
+			}
+			TokenTree::Ident(ident) => {
+				let name = ident.to_string();
+				let Some(id) = name.strip_prefix(EXTRACTED_FUNCTION_PLACEHOLDER) else {
+					return TokenTree::Ident(ident);
+				};
+				let function = id
+					.parse()
+					.ok()
+					.and_then(|id| functions.function_to_tokens(id))
+					.expect("missing extracted function");
+				let function = insert_functions(function, functions);
+				let block = self_rust_tokenize::quote!({
+					use parser::extractor::GetFunction;
+					functions.new_extracted_function(#function)
+				});
+				block.into_iter().next().unwrap()
+			}
+			token => token,
+		})
+		.collect()
+}
+
+/// Tokenizes as a placeholder, which [tokenize_with_functions] replaces with the function
 #[cfg(feature = "self-rust-tokenize")]
-impl<T: FunctionBased> self_rust_tokenize::SelfRustTokenize for ExtractedFunction<T> {
+impl<T: FunctionBased + 'static> self_rust_tokenize::SelfRustTokenize for ExtractedFunction<T> {
 	fn append_to_token_stream(
 		&self,
-		_token_stream: &mut self_rust_tokenize::proc_macro2::TokenStream,
+		token_stream: &mut self_rust_tokenize::proc_macro2::TokenStream,
 	) {
-		todo!("extracted function to tokens")
+		let placeholder = self_rust_tokenize::proc_macro2::Ident::new(
+			&format!("{EXTRACTED_FUNCTION_PLACEHOLDER}{}", self.0.get_id()),
+			self_rust_tokenize::proc_macro2::Span::call_site(),
+		);
+		token_stream.extend(std::iter::once(self_rust_tokenize::proc_macro2::TokenTree::Ident(
+			placeholder,
+		)));
 	}
 }
 
@@ -129,12 +129,34 @@ pub struct FunctionBase<T: FunctionBased> {
 }
 
 #[cfg(feature = "self-rust-tokenize")]
-impl<T: FunctionBased> self_rust_tokenize::SelfRustTokenize for FunctionBase<T> {
+impl<T: FunctionBased> self_rust_tokenize::SelfRustTokenize for FunctionBase<T>
+where
+	T::Header: self_rust_tokenize::SelfRustTokenize,
+	T::Name: self_rust_tokenize::SelfRustTokenize,
+	T::Body: self_rust_tokenize::SelfRustTokenize,
+{
 	fn append_to_token_stream(
 		&self,
-		_token_stream: &mut self_rust_tokenize::proc_macro2::TokenStream,
+		token_stream: &mut self_rust_tokenize::proc_macro2::TokenStream,
 	) {
-		todo!("fb to tokens")
+		use self_rust_tokenize::quote;
+
+		let header = self.header.to_tokens();
+		let name = self.name.to_tokens();
+		let type_parameters = self.type_parameters.to_tokens();
+		let parameters = self.parameters.to_tokens();
+		let return_type = self.return_type.to_tokens();
+		let body = self.body.to_tokens();
+		// A new id is generated as ids are not stable between the macro and the built binary
+		token_stream.extend(quote!(parser::FunctionBase {
+			function_id: parser::FunctionId::new(),
+			header: #header,
+			name: #name,
+			type_parameters: #type_parameters,
+			parameters: #parameters,
+			return_type: #return_type,
+			body: #body,
+		}));
 	}
 }
 
@@ -236,9 +258,10 @@ where
 		functions: &mut ExtractedFunctions,
 		chain: &mut temporary_annex::Annex<crate::Chain>,
 	) {
-		self.parameters.visit(visitors, data, settings, functions, chain);
+		let mut chain = chain.push_annex(T::get_chain_variable(self));
+		self.parameters.visit(visitors, data, settings, functions, &mut chain);
 		if settings.visit_function_bodies {
-			self.body.visit(visitors, data, settings, functions, chain);
+			self.body.visit(visitors, data, settings, functions, &mut chain);
 		}
 	}
 
@@ -250,9 +273,10 @@ where
 		functions: &mut ExtractedFunctions,
 		chain: &mut temporary_annex::Annex<crate::Chain>,
 	) {
-		self.parameters.visit_mut(visitors, data, settings, functions, chain);
+		let mut chain = chain.push_annex(T::get_chain_variable(self));
+		self.parameters.visit_mut(visitors, data, settings, functions, &mut chain);
 		if settings.visit_function_bodies {
-			self.body.visit_mut(visitors, data, settings, functions, chain);
+			self.body.visit_mut(visitors, data, settings, functions, &mut chain);
 		}
 	}
 }
@@ -267,9 +291,8 @@ impl<T: ExpressionOrStatementPosition> FunctionBased for GeneralFunctionBase<T>
 	type Header = FunctionHeader;
 	type Name = T::Name;
 
-	fn get_chain_variable(_this: &FunctionBase<Self>) -> crate::ChainVariable {
-		todo!()
-		// crate::ChainVariable::UnderExpressionFunctionBlock(self.base.body.1, self.expression_id)
+	fn get_chain_variable(this: &FunctionBase<Self>) -> crate::ChainVariable {
+		crate::ChainVariable::UnderGeneralFunction(this.body.1)
 	}
 
 	fn header_and_name_from_reader(
@@ -301,6 +324,7 @@ impl<T: ExpressionOrStatementPosition> FunctionBased for GeneralFunctionBase<T>
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "self-rust-tokenize", derive(self_rust_tokenize::SelfRustTokenize))]
 pub enum FunctionHeader {
 	VirginFunctionHeader {
 		async_keyword: Option<Keyword<tsx_keywords::Async>>,
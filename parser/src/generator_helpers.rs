@@ -1,5 +1,8 @@
 use crate::expressions::ExpressionId;
-use crate::{ASTNode, Expression, PropertyReference, Statement, VariableId, VariableIdentifier};
+use crate::{
+	ASTNode, Expression, PropertyReference, Statement, TypeReference, VariableId,
+	VariableIdentifier,
+};
 
 use source_map::Span;
 
@@ -45,6 +48,12 @@ impl<'a> IntoAST<VariableIdentifier> for &'a str {
 	}
 }
 
+impl IntoAST<TypeReference> for &str {
+	fn into_ast(self) -> TypeReference {
+		TypeReference::Name(self.to_owned(), Span::NULL_SPAN)
+	}
+}
+
 impl IntoAST<Expression> for usize {
 	fn into_ast(self) -> Expression {
 		Expression::NumberLiteral(
@@ -70,3 +79,76 @@ impl IntoAST<Statement> for Expression {
 		Statement::Expression(self.into())
 	}
 }
+
+/// Used by the generator's `match_expr!` so that matching works on both shared and mutable
+/// references. Shared references keep their original lifetime
+#[doc(hidden)]
+pub trait Reborrow {
+	type Output<'b>
+	where
+		Self: 'b;
+
+	fn reborrow(&mut self) -> Self::Output<'_>;
+}
+
+impl<'a, T: ?Sized> Reborrow for &'a T {
+	type Output<'b>
+		= &'a T
+	where
+		Self: 'b;
+
+	fn reborrow(&mut self) -> &'a T {
+		self
+	}
+}
+
+impl<T: ?Sized> Reborrow for &mut T {
+	type Output<'b>
+		= &'b mut T
+	where
+		Self: 'b;
+
+	fn reborrow(&mut self) -> &mut T {
+		self
+	}
+}
+
+/// Used by the generator's `match_expr!` to step through [Box]es and [Vec]s of fields
+#[doc(hidden)]
+pub trait Unbox {
+	type Output;
+
+	fn unbox(self) -> Self::Output;
+}
+
+impl<'a, T> Unbox for &'a Box<T> {
+	type Output = &'a T;
+
+	fn unbox(self) -> &'a T {
+		self
+	}
+}
+
+impl<'a, T> Unbox for &'a mut Box<T> {
+	type Output = &'a mut T;
+
+	fn unbox(self) -> &'a mut T {
+		self
+	}
+}
+
+impl<'a, T> Unbox for &'a Vec<T> {
+	type Output = &'a [T];
+
+	fn unbox(self) -> &'a [T] {
+		self
+	}
+}
+
+impl<'a, T> Unbox for &'a mut Vec<T> {
+	type Output = &'a mut [T];
+
+	fn unbox(self) -> &'a mut [T] {
+		self
+	}
+}
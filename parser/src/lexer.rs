@@ -4,8 +4,8 @@
 
 use super::{ParseError, SourceId, Span, TSXToken};
 use crate::{
-	cursor::EmptyCursorId, errors::LexingErrors, html_tag_contains_literal_content,
-	html_tag_is_self_closing,
+	comments::is_purity_annotation, cursor::EmptyCursorId, errors::LexingErrors,
+	html_tag_contains_literal_content, html_tag_is_self_closing,
 };
 use tokenizer_lib::{Token, TokenSender};
 
@@ -409,10 +409,10 @@ pub fn lex_source(
 			}
 			LexingState::MultiLineComment { ref mut last_char_was_star } => match chr {
 				'/' if *last_char_was_star => {
-					if settings.include_comments {
-						push_token!(TSXToken::MultiLineComment(
-							script[(start + 2)..(idx - 1)].to_owned()
-						));
+					let comment = &script[(start + 2)..(idx - 1)];
+					// Annotations are kept as they change what the code can be transformed to
+					if settings.include_comments || is_purity_annotation(comment) {
+						push_token!(TSXToken::MultiLineComment(comment.to_owned()));
 					}
 					set_state!(LexingState::None);
 					continue;
@@ -479,7 +479,7 @@ pub fn lex_source(
 					continue;
 				}
 				'`' if !*escaped => {
-					if idx > start + 1 {
+					if idx > start {
 						push_token!(
 							EXCLUDING_LAST_CHAR,
 							TSXToken::TemplateLiteralChunk(script[start..idx].to_owned())
@@ -539,8 +539,9 @@ pub fn lex_source(
 							*tag_depth += 1;
 							continue;
 						}
-						// Tag name characters:
-						'A'..='Z' | 'a'..='z' | '0'..='9' | ' ' => {
+						// Tag name characters (including `.` for `<ui.Button>`). Whitespace ends the
+						// name and starts the attributes
+						'A'..='Z' | 'a'..='z' | '0'..='9' | '_' | '$' | '.' | ':' => {
 							// Add the opening tag here as know it is not closing
 							if !*lexed_start {
 								match direction {
@@ -572,6 +573,9 @@ pub fn lex_source(
 						'/' if start + 1 == idx => {
 							*direction = JSXTagNameDirection::Closing;
 						}
+						// Whitespace before the name or (in closing tags) before the `>` is skipped
+						chr if chr.is_whitespace()
+							&& (!*lexed_start || *direction == JSXTagNameDirection::Closing) => {}
 						// Comment
 						'!' if start + 1 == idx => {
 							*jsx_state = JSXLexingState::Comment(JSXCommentState::None);
@@ -809,7 +813,12 @@ pub fn lex_source(
 						match chr {
 							'<' => {
 								let content_slice = &script[start..idx];
-								if !content_slice.trim().is_empty() {
+								// Whitespace between tags and expressions on the same line is
+								// content (`{a} {b}`)
+								if !content_slice.trim().is_empty()
+									|| (!content_slice.is_empty()
+										&& !script[..start].ends_with('\n'))
+								{
 									push_token!(
 										EXCLUDING_LAST_CHAR,
 										TSXToken::JSXContent(content_slice.to_owned())
@@ -823,7 +832,12 @@ pub fn lex_source(
 							}
 							'{' => {
 								let content_slice = &script[start..idx];
-								if !content_slice.trim().is_empty() {
+								// Whitespace between tags and expressions on the same line is
+								// content (`{a} {b}`)
+								if !content_slice.trim().is_empty()
+									|| (!content_slice.is_empty()
+										&& !script[..start].ends_with('\n'))
+								{
 									push_token!(
 										EXCLUDING_LAST_CHAR,
 										TSXToken::JSXContent(content_slice.to_owned())
@@ -836,7 +850,13 @@ pub fn lex_source(
 								continue;
 							}
 							'\n' => {
-								let source = script[start..idx].trim();
+								let source = &script[start..idx];
+								// Leading whitespace is content unless it starts the line
+								let source = if script[..start].ends_with('\n') {
+									source.trim()
+								} else {
+									source.trim_end()
+								};
 								if !source.is_empty() {
 									push_token!(
 										EXCLUDING_LAST_CHAR,
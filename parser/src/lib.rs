@@ -12,9 +12,12 @@ pub mod extractor;
 pub mod functions;
 mod generator_helpers;
 mod modules;
+pub mod node;
 pub mod operators;
 pub mod parameters;
 mod property_key;
+pub mod query;
+pub mod rewrite;
 pub mod statements;
 mod tokens;
 pub mod types;
@@ -27,11 +30,11 @@ pub mod lexer;
 pub use block::{
 	Block, BlockId, BlockLike, BlockLikeMut, BlockOrSingleStatement, StatementOrDeclaration,
 };
-pub use comments::WithComment;
+pub use comments::{is_purity_annotation, WithComment};
 pub use cursor::{CursorId, EmptyCursorId};
 pub use declarations::Declaration;
 use declarations::StatementFunctionBase;
-pub use errors::{ParseError, ParseErrors, ParseResult};
+pub use errors::{LexingErrors, ParseError, ParseErrorKind, ParseErrors, ParseResult};
 pub use expressions::{Expression, PropertyReference};
 pub use extensions::{
 	decorators::{Decorated, Decorator},
@@ -41,10 +44,11 @@ pub use extensions::{
 use extractor::ExtractedFunctions;
 pub use extractor::UniversalFunctionId;
 pub use functions::{FunctionBase, FunctionBased, FunctionHeader, FunctionId};
-pub use generator_helpers::IntoAST;
+pub use generator_helpers::{IntoAST, Reborrow, Unbox};
 use iterator_endiate::EndiateIteratorExt;
 pub use lexer::{lex_source, LexSettings};
 pub use modules::{FromFileError, Module, TypeDefinitionModule, TypeDefinitionModuleDeclaration};
+pub use node::NodeRef;
 pub use parameters::{
 	FunctionParameters, OptionalOrWithDefaultValueParameter, Parameter, SpreadParameter,
 };
@@ -117,6 +121,7 @@ impl Default for ParseSettings {
 pub struct ToStringSettingsAndData(pub ToStringSettings, pub ExtractedFunctions);
 
 /// Settings for serializing ASTNodes
+#[derive(Clone)]
 pub struct ToStringSettings {
 	/// Does not include whitespace minification
 	pub pretty: bool,
@@ -273,6 +278,30 @@ pub trait ASTNode: Sized + Clone + PartialEq + std::fmt::Debug + Sync + Send + '
 	}
 }
 
+/// Tokens of a whole source. Unlike [ASTNode::from_string] (which lexes and parses in parallel),
+/// lexing is done before parsing so that each can be measured
+pub struct LexedSource(tokenizer_lib::BufferedTokenQueue<TSXToken, Span>);
+
+impl LexedSource {
+	pub fn new(source: &str, settings: &ParseSettings, source_id: SourceId) -> ParseResult<Self> {
+		let lex_settings = lexer::LexSettings {
+			include_comments: false,
+			lex_jsx: settings.jsx,
+			..Default::default()
+		};
+		let mut queue = tokenizer_lib::BufferedTokenQueue::new();
+		lexer::lex_source(source, &mut queue, &lex_settings, Some(source_id), None, Vec::new())?;
+		Ok(Self(queue))
+	}
+
+	pub fn parse<T: ASTNode>(mut self, settings: &ParseSettings) -> ParseResult<ParseOutput<T>> {
+		let mut state = ParsingState::default();
+		let ast = T::from_reader(&mut self.0, &mut state, settings)?;
+		self.0.expect_next(TSXToken::EOS)?;
+		Ok(ParseOutput(ast, state))
+	}
+}
+
 #[derive(Default, Debug)]
 pub struct ParsingState {
 	pub function_extractor: extractor::ExtractedFunctions,
@@ -481,6 +510,7 @@ impl NumberStructure {
 }
 
 #[derive(Eq, PartialEq, Clone, Debug, Default)]
+#[cfg_attr(feature = "self-rust-tokenize", derive(self_rust_tokenize::SelfRustTokenize))]
 pub enum GetSetGeneratorOrNone {
 	Get(Keyword<tsx_keywords::Get>),
 	Set(Keyword<tsx_keywords::Set>),
@@ -641,7 +671,7 @@ pub(crate) fn parse_bracketed<T: ASTNode>(
 			Token(token, position) => {
 				return Err(ParseError::new(
 					crate::ParseErrors::UnexpectedToken {
-						expected: &[end, TSXToken::Comma],
+						expected: vec![end, TSXToken::Comma],
 						found: token,
 					},
 					position,
@@ -670,6 +700,13 @@ pub(crate) fn to_string_bracketed<T: source_map::ToString, U: ASTNode>(
 	buf.push(brackets.1);
 }
 
+/// Records where the following output came from. Generated nodes (which have a null span) are skipped
+pub(crate) fn add_mapping<T: source_map::ToString>(buf: &mut T, position: &Span) {
+	if !position.is_null() {
+		buf.add_mapping(position);
+	}
+}
+
 /// Part of [ASI](https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Lexical_grammar#automatic_semicolon_insertion)
 pub(crate) fn expect_semi_colon(reader: &mut impl TokenReader<TSXToken, Span>) -> ParseResult<()> {
 	if let Some(Token(TSXToken::CloseBrace, _)) = reader.peek() {
@@ -682,8 +719,16 @@ pub(crate) fn expect_semi_colon(reader: &mut impl TokenReader<TSXToken, Span>) -
 /// Re-exports or generator and general use
 pub mod ast {
 	pub use crate::{
-		declarations::*, expressions::*, extensions::jsx::*, statements::*, Keyword,
-		NumberStructure, StatementOrDeclaration, VariableField, VariableId, VariableIdentifier,
+		declarations::{classes::*, export::*, *},
+		expressions::*,
+		extensions::{decorators::*, is_expression::*, jsx::*},
+		functions::{FunctionBase, FunctionHeader, FunctionId},
+		operators::*,
+		parameters::*,
+		statements::*,
+		types::{interface::*, type_references::*},
+		Block, BlockId, GetSetGeneratorOrNone, Keyword, Module, NumberStructure, PropertyId,
+		PropertyKey, StatementOrDeclaration, TypeId, VariableField, VariableId, VariableIdentifier,
 		WithComment,
 	};
 
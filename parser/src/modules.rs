@@ -32,6 +32,7 @@ pub enum FromFileError {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "self-rust-tokenize", derive(self_rust_tokenize::SelfRustTokenize))]
 pub struct Module {
 	pub items: Vec<StatementOrDeclaration>,
 	pub block_id: BlockId,
@@ -346,7 +347,7 @@ impl ASTNode for TypeDefinitionModuleDeclaration {
 				let Token(token, position) = reader.next().unwrap();
 				Err(ParseError::new(
 					crate::ParseErrors::UnexpectedToken {
-						expected: &[
+						expected: vec![
 							TSXToken::Keyword(TSXKeyword::Declare),
 							TSXToken::Keyword(TSXKeyword::Interface),
 							TSXToken::Keyword(TSXKeyword::Type),
@@ -420,7 +421,7 @@ pub(crate) fn parse_declare_item(
 			let Token(token, position) = reader.next().unwrap();
 			Err(ParseError::new(
 				crate::ParseErrors::UnexpectedToken {
-					expected: &[
+					expected: vec![
 						TSXToken::Keyword(TSXKeyword::Var),
 						TSXToken::Keyword(TSXKeyword::Class),
 						TSXToken::Keyword(TSXKeyword::Type),
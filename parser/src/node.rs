@@ -0,0 +1,974 @@
+//! A borrowed, untyped view of the AST for generic walking.
+//!
+//! [NodeRef] covers every node that has a position in source. Wrapper structures which do not have a
+//! position of their own ([crate::WithComment], [crate::Decorated], [StatementOrDeclaration],
+//! [MultipleExpression], [BlockOrSingleStatement], [SpreadExpression] etc) are flattened so that their
+//! contents become children of the parent node. Type annotations are currently leaves.
+
+use std::borrow::Cow;
+
+use crate::{
+	declarations::{
+		classes::{ClassConstructorBase, ClassFunctionBase, ClassMember, ClassProperty},
+		export::Exportable,
+		ClassDeclaration, ExportDeclaration, ImportPart, InterfaceDeclaration, StatementFunction,
+		StatementFunctionBase, TypeAlias, VariableDeclaration, VariableDeclarationItem,
+	},
+	expressions::{
+		arrow_function::ArrowFunctionBase,
+		assignments::{LHSOfAssignment, VariableOrPropertyAccess},
+		object_literal::{ObjectLiteralMember, ObjectLiteralMethodBase},
+		ExpressionFunctionBase, ExpressionOrBlock, MultipleExpression, SpecialOperators,
+		SpreadExpression, SuperReference, TemplateLiteralPart,
+	},
+	extractor::{ExtractedFunction, ExtractedFunctions, GetFunction},
+	statements::{
+		ConditionalElseStatement, ForLoopCondition, ForLoopStatementInitializer, SwitchBranch,
+		UnconditionalElseStatement,
+	},
+	types::enum_declaration::EnumMember,
+	ASTNode, ArrayDestructuringField, Block, BlockOrSingleStatement, Declaration, Decorator,
+	Expression, ExpressionOrStatementPosition, FunctionBase, FunctionBased, FunctionParameters,
+	JSXAttribute, JSXElement, JSXElementChildren, JSXNode, JSXRoot, Module,
+	ObjectDestructuringField, OptionalOrWithDefaultValueParameter, Parameter, PropertyKey,
+	SpreadParameter, Statement, StatementOrDeclaration, TypeReference, VariableField,
+	VariableFieldInSourceCode, VariableIdentifier,
+};
+use source_map::Span;
+
+/// A reference to any node in the AST
+#[derive(Debug, Clone, Copy)]
+pub enum NodeRef<'a> {
+	Module(&'a Module),
+	Block(&'a Block),
+	Statement(&'a Statement),
+	Declaration(&'a Declaration),
+	Expression(&'a Expression),
+	/// For function declarations that are not extracted (e.g. under `export`)
+	StatementFunction(&'a StatementFunction),
+	/// For class declarations that are not under [Declaration] (e.g. under `export`)
+	ClassDeclaration(&'a ClassDeclaration<crate::StatementPosition>),
+	ClassMember(&'a ClassMember),
+	ObjectLiteralMember(&'a ObjectLiteralMember),
+	PropertyKey(&'a PropertyKey),
+	Decorator(&'a Decorator),
+	Parameter(&'a Parameter),
+	OptionalParameter(&'a OptionalOrWithDefaultValueParameter),
+	SpreadParameter(&'a SpreadParameter),
+	/// Names of optional and spread parameters, which are not [VariableField]s
+	VariableIdentifier(&'a VariableIdentifier),
+	VariableDeclaration(&'a VariableDeclaration),
+	VariableDeclarationItem(&'a VariableDeclarationItem<Expression>),
+	/// Items of `let` and `var` declarations which may not have a value
+	OptionalVariableDeclarationItem(&'a VariableDeclarationItem<Option<Expression>>),
+	VariableField(&'a VariableField<VariableFieldInSourceCode>),
+	ObjectDestructuringField(&'a ObjectDestructuringField<VariableFieldInSourceCode>),
+	ArrayDestructuringField(&'a ArrayDestructuringField<VariableFieldInSourceCode>),
+	/// Target of a modifying assignment (`x += 1`, `a.b++` etc)
+	VariableOrPropertyAccess(&'a VariableOrPropertyAccess),
+	ConditionalElseStatement(&'a ConditionalElseStatement),
+	UnconditionalElseStatement(&'a UnconditionalElseStatement),
+	ImportPart(&'a ImportPart),
+	EnumMember(&'a EnumMember),
+	InterfaceDeclaration(&'a InterfaceDeclaration),
+	TypeAlias(&'a TypeAlias),
+	TypeReference(&'a TypeReference),
+	JSXElement(&'a JSXElement),
+	/// Text and interpolated expressions inside of JSX. Elements are [NodeRef::JSXElement]
+	JSXNode(&'a JSXNode),
+	JSXAttribute(&'a JSXAttribute),
+}
+
+macro_rules! node_ref_from {
+	($($variant:ident => $ty:ty),* $(,)?) => {
+		$(
+			impl<'a> From<&'a $ty> for NodeRef<'a> {
+				fn from(value: &'a $ty) -> Self {
+					Self::$variant(value)
+				}
+			}
+		)*
+	};
+}
+
+node_ref_from! {
+	Module => Module,
+	Block => Block,
+	Statement => Statement,
+	Declaration => Declaration,
+	Expression => Expression,
+	ClassMember => ClassMember,
+	ObjectLiteralMember => ObjectLiteralMember,
+	PropertyKey => PropertyKey,
+	Decorator => Decorator,
+	Parameter => Parameter,
+	VariableDeclaration => VariableDeclaration,
+	VariableField => VariableField<VariableFieldInSourceCode>,
+	VariableIdentifier => VariableIdentifier,
+	VariableOrPropertyAccess => VariableOrPropertyAccess,
+	EnumMember => EnumMember,
+	TypeReference => TypeReference,
+	JSXElement => JSXElement,
+	JSXAttribute => JSXAttribute,
+}
+
+impl<'a> From<&'a StatementOrDeclaration> for NodeRef<'a> {
+	fn from(value: &'a StatementOrDeclaration) -> Self {
+		match value {
+			StatementOrDeclaration::Statement(statement) => Self::Statement(statement),
+			StatementOrDeclaration::Declaration(declaration) => Self::Declaration(declaration),
+		}
+	}
+}
+
+impl<'a> NodeRef<'a> {
+	/// Name of the node. For enum structures this is the name of the variant
+	/// (e.g. `FunctionCall` rather than `Expression`)
+	pub fn kind(&self) -> &'static str {
+		match self {
+			NodeRef::Module(_) => "Module",
+			NodeRef::Block(_) => "Block",
+			NodeRef::Statement(statement) => match statement {
+				Statement::Expression(_) => "ExpressionStatement",
+				Statement::Block(_) => "Block",
+				Statement::Debugger(_) => "Debugger",
+				Statement::IfStatement(_) => "IfStatement",
+				Statement::ForLoopStatement(_) => "ForLoopStatement",
+				Statement::SwitchStatement(_) => "SwitchStatement",
+				Statement::WhileStatement(_) => "WhileStatement",
+				Statement::DoWhileStatement(_) => "DoWhileStatement",
+				Statement::TryCatchStatement(_) => "TryCatchStatement",
+				Statement::Return(..) => "Return",
+				Statement::Continue(..) => "Continue",
+				Statement::Break(..) => "Break",
+				Statement::Throw(..) => "Throw",
+				Statement::Comment(..) | Statement::MultiLineComment(..) => "Comment",
+				Statement::Labelled { .. } => "Labelled",
+				Statement::VarVariable(_) => "VarVariable",
+				Statement::Cursor(..) => "Cursor",
+			},
+			NodeRef::Declaration(declaration) => match declaration {
+				Declaration::Variable(_) => "VariableDeclaration",
+				Declaration::Function(_) | Declaration::ExtractedFunction(_) => {
+					"FunctionDeclaration"
+				}
+				Declaration::Class(_) => "ClassDeclaration",
+				Declaration::Enum(_) => "EnumDeclaration",
+				Declaration::Interface(_) | Declaration::DeclareInterface(_) => {
+					"InterfaceDeclaration"
+				}
+				Declaration::TypeAlias(_) => "TypeAlias",
+				Declaration::DeclareVariable(_) => "DeclareVariable",
+				Declaration::DeclareFunction(_) => "DeclareFunction",
+				Declaration::Import(_) => "ImportDeclaration",
+				Declaration::Export(_) => "ExportDeclaration",
+			},
+			NodeRef::Expression(expression) => expression_kind(expression),
+			NodeRef::StatementFunction(_) => "FunctionDeclaration",
+			NodeRef::ClassDeclaration(_) => "ClassDeclaration",
+			NodeRef::ClassMember(member) => match member {
+				ClassMember::Constructor(_) => "ClassConstructor",
+				ClassMember::Function(..) => "ClassMethod",
+				ClassMember::Property(..) => "ClassProperty",
+			},
+			NodeRef::ObjectLiteralMember(member) => match member {
+				ObjectLiteralMember::SpreadExpression(..) => "SpreadProperty",
+				ObjectLiteralMember::Shorthand(..) => "ShorthandProperty",
+				ObjectLiteralMember::Property(..) => "Property",
+				ObjectLiteralMember::Method(_) => "Method",
+			},
+			NodeRef::PropertyKey(_) => "PropertyKey",
+			NodeRef::Decorator(_) => "Decorator",
+			NodeRef::Parameter(_) => "Parameter",
+			NodeRef::OptionalParameter(_) => "OptionalParameter",
+			NodeRef::SpreadParameter(_) => "SpreadParameter",
+			NodeRef::VariableIdentifier(_) => "VariableIdentifier",
+			NodeRef::VariableDeclaration(_) => "VariableDeclaration",
+			NodeRef::VariableDeclarationItem(_) | NodeRef::OptionalVariableDeclarationItem(_) => {
+				"VariableDeclarationItem"
+			}
+			NodeRef::VariableField(_) => "VariableField",
+			NodeRef::ObjectDestructuringField(_) => "ObjectDestructuringField",
+			NodeRef::ArrayDestructuringField(_) => "ArrayDestructuringField",
+			NodeRef::VariableOrPropertyAccess(access) => match access {
+				VariableOrPropertyAccess::Variable(..) => "VariableReference",
+				VariableOrPropertyAccess::PropertyAccess { .. } => "PropertyAccess",
+				VariableOrPropertyAccess::Index { .. } => "Index",
+				VariableOrPropertyAccess::Cursor { .. } => "Cursor",
+			},
+			NodeRef::ConditionalElseStatement(_) => "ConditionalElseStatement",
+			NodeRef::UnconditionalElseStatement(_) => "UnconditionalElseStatement",
+			NodeRef::ImportPart(_) => "ImportPart",
+			NodeRef::EnumMember(_) => "EnumMember",
+			NodeRef::InterfaceDeclaration(_) => "InterfaceDeclaration",
+			NodeRef::TypeAlias(_) => "TypeAlias",
+			NodeRef::TypeReference(_) => "TypeReference",
+			NodeRef::JSXElement(_) => "JSXElement",
+			NodeRef::JSXNode(node) => match node {
+				JSXNode::TextNode(..) => "JSXText",
+				JSXNode::InterpolatedExpression(..) => "JSXInterpolatedExpression",
+				JSXNode::Element(_) => "JSXElement",
+				JSXNode::Fragment(_) => "JSXFragment",
+				JSXNode::LineBreak => "JSXLineBreak",
+			},
+			NodeRef::JSXAttribute(_) => "JSXAttribute",
+		}
+	}
+
+	pub fn get_position(&self) -> Cow<'a, Span> {
+		match *self {
+			NodeRef::Module(module) => {
+				if module.items.is_empty() {
+					Cow::Owned(Span { start: 0, end: 0, source_id: module.source_id })
+				} else {
+					module.get_position()
+				}
+			}
+			NodeRef::Block(block) => block.get_position(),
+			NodeRef::Statement(statement) => statement.get_position(),
+			NodeRef::Declaration(declaration) => declaration.get_position(),
+			NodeRef::Expression(expression) => expression.get_position(),
+			NodeRef::StatementFunction(function) => function.get_position(),
+			NodeRef::ClassDeclaration(class) => class.get_position(),
+			NodeRef::ClassMember(member) => match member {
+				ClassMember::Constructor(function) => function.get_position(),
+				ClassMember::Function(is_static, function) => match is_static {
+					Some(keyword) => Cow::Owned(keyword.1.union(&function.1)),
+					None => function.get_position(),
+				},
+				ClassMember::Property(
+					is_static,
+					ClassProperty { accessor, key, type_reference, value },
+				) => {
+					let start = match (is_static, accessor) {
+						(Some(keyword), _) => Cow::Borrowed(&keyword.1),
+						(None, Some(accessor)) => Cow::Borrowed(accessor),
+						(None, None) => key.get_position(),
+					};
+					if let Some(value) = value {
+						Cow::Owned(start.union(&value.get_position()))
+					} else if let Some(type_reference) = type_reference {
+						Cow::Owned(start.union(&type_reference.get_position()))
+					} else {
+						Cow::Owned(start.union(&key.get_position()))
+					}
+				}
+			},
+			NodeRef::ObjectLiteralMember(member) => match member {
+				ObjectLiteralMember::Method(function) => function.get_position(),
+				member => member.get_position(),
+			},
+			NodeRef::PropertyKey(key) => ASTNode::get_position(key),
+			NodeRef::Decorator(decorator) => decorator.get_position(),
+			NodeRef::Parameter(parameter) => parameter.get_position(),
+			NodeRef::OptionalParameter(parameter) => match parameter {
+				OptionalOrWithDefaultValueParameter::Optional { name, type_reference, .. } => {
+					match type_reference {
+						Some(type_reference) => {
+							Cow::Owned(name.get_position().union(&type_reference.get_position()))
+						}
+						None => name.get_position(),
+					}
+				}
+				OptionalOrWithDefaultValueParameter::WithDefaultValue { name, value, .. } => {
+					Cow::Owned(name.get_position().union(&value.get_position()))
+				}
+			},
+			NodeRef::SpreadParameter(parameter) => match parameter.type_reference {
+				Some(ref type_reference) => {
+					Cow::Owned(parameter.name.get_position().union(&type_reference.get_position()))
+				}
+				None => parameter.name.get_position(),
+			},
+			NodeRef::VariableIdentifier(identifier) => identifier.get_position(),
+			NodeRef::VariableDeclaration(declaration) => declaration.get_position(),
+			NodeRef::VariableDeclarationItem(item) => item.get_position(),
+			NodeRef::OptionalVariableDeclarationItem(item) => item.get_position(),
+			NodeRef::VariableField(field) => field.get_position(),
+			NodeRef::ObjectDestructuringField(field) => field.get_position(),
+			NodeRef::ArrayDestructuringField(field) => field.get_position(),
+			NodeRef::VariableOrPropertyAccess(access) => access.get_position(),
+			NodeRef::ConditionalElseStatement(statement) => statement.get_position(),
+			NodeRef::UnconditionalElseStatement(statement) => statement.get_position(),
+			NodeRef::ImportPart(part) => part.get_position(),
+			NodeRef::EnumMember(member) => member.get_position(),
+			NodeRef::InterfaceDeclaration(interface) => interface.get_position(),
+			NodeRef::TypeAlias(alias) => alias.get_position(),
+			NodeRef::TypeReference(reference) => reference.get_position(),
+			NodeRef::JSXElement(element) => element.get_position(),
+			NodeRef::JSXNode(node) => node.get_position(),
+			NodeRef::JSXAttribute(attribute) => attribute.get_position(),
+		}
+	}
+
+	/// Direct children of the node, in source order.
+	///
+	/// Requires `functions` as function bodies are held out of the tree in [ExtractedFunctions]
+	pub fn children(&self, functions: &'a ExtractedFunctions) -> Vec<NodeRef<'a>> {
+		let mut children = Vec::new();
+		let out = &mut children;
+		match *self {
+			NodeRef::Module(module) => out.extend(module.items.iter().map(NodeRef::from)),
+			NodeRef::Block(block) => out.extend(block.0.iter().map(NodeRef::from)),
+			NodeRef::Statement(statement) => statement_children(statement, out),
+			NodeRef::Declaration(declaration) => declaration_children(declaration, functions, out),
+			NodeRef::Expression(expression) => expression_children(expression, functions, out),
+			NodeRef::StatementFunction(function) => function_children(function, out),
+			NodeRef::ClassDeclaration(class) => class_children(class, out),
+			NodeRef::ClassMember(member) => match member {
+				ClassMember::Constructor(function) => {
+					extracted_function_children::<ClassConstructorBase>(function, functions, out)
+				}
+				ClassMember::Function(_, function) => {
+					extracted_function_children::<ClassFunctionBase>(function, functions, out)
+				}
+				ClassMember::Property(_, ClassProperty { key, type_reference, value, .. }) => {
+					out.push(NodeRef::PropertyKey(key.get_ast()));
+					out.extend(type_reference.as_ref().map(NodeRef::TypeReference));
+					out.extend(value.as_deref().map(NodeRef::Expression));
+				}
+			},
+			NodeRef::ObjectLiteralMember(member) => match member {
+				ObjectLiteralMember::SpreadExpression(expression, _) => {
+					out.push(NodeRef::Expression(expression))
+				}
+				ObjectLiteralMember::Shorthand(..) => {}
+				ObjectLiteralMember::Property(key, value, _) => {
+					out.push(NodeRef::PropertyKey(key.get_ast()));
+					out.push(NodeRef::Expression(value));
+				}
+				ObjectLiteralMember::Method(function) => {
+					extracted_function_children::<ObjectLiteralMethodBase>(function, functions, out)
+				}
+			},
+			NodeRef::PropertyKey(key) => {
+				if let PropertyKey::Computed(expression, _, _) = key {
+					out.push(NodeRef::Expression(expression));
+				}
+			}
+			NodeRef::Decorator(decorator) => {
+				if let Some(arguments) = &decorator.arguments {
+					out.extend(arguments.iter().map(NodeRef::Expression));
+				}
+			}
+			NodeRef::Parameter(parameter) => {
+				out.push(NodeRef::VariableField(parameter.name.get_ast()));
+				out.extend(parameter.type_reference.as_ref().map(NodeRef::TypeReference));
+			}
+			NodeRef::OptionalParameter(parameter) => match parameter {
+				OptionalOrWithDefaultValueParameter::Optional { name, type_reference, .. } => {
+					out.push(NodeRef::VariableIdentifier(name));
+					out.extend(type_reference.as_ref().map(NodeRef::TypeReference));
+				}
+				OptionalOrWithDefaultValueParameter::WithDefaultValue {
+					name,
+					type_reference,
+					value,
+					..
+				} => {
+					out.push(NodeRef::VariableField(name.get_ast()));
+					out.extend(type_reference.as_ref().map(NodeRef::TypeReference));
+					out.push(NodeRef::Expression(value));
+				}
+			},
+			NodeRef::SpreadParameter(parameter) => {
+				out.push(NodeRef::VariableIdentifier(&parameter.name));
+				out.extend(parameter.type_reference.as_ref().map(NodeRef::TypeReference));
+			}
+			NodeRef::VariableDeclaration(declaration) => {
+				variable_declaration_children(declaration, out)
+			}
+			NodeRef::VariableDeclarationItem(item) => {
+				out.push(NodeRef::VariableField(item.name.get_ast()));
+				out.extend(item.type_reference.as_ref().map(NodeRef::TypeReference));
+				out.push(NodeRef::Expression(&item.expression));
+			}
+			NodeRef::OptionalVariableDeclarationItem(item) => {
+				out.push(NodeRef::VariableField(item.name.get_ast()));
+				out.extend(item.type_reference.as_ref().map(NodeRef::TypeReference));
+				out.extend(item.expression.as_ref().map(NodeRef::Expression));
+			}
+			NodeRef::VariableField(field) => match field {
+				VariableField::Name(_) => {}
+				VariableField::Array(fields, _) => out.extend(
+					fields
+						.iter()
+						.filter(|field| !matches!(field, ArrayDestructuringField::None))
+						.map(NodeRef::ArrayDestructuringField),
+				),
+				VariableField::Object(fields, _) => out.extend(
+					fields.iter().map(|field| NodeRef::ObjectDestructuringField(field.get_ast())),
+				),
+			},
+			NodeRef::ObjectDestructuringField(field) => match field {
+				ObjectDestructuringField::Spread(..) => {}
+				ObjectDestructuringField::Name(_, default_value) => {
+					out.extend(default_value.as_ref().map(NodeRef::Expression));
+				}
+				ObjectDestructuringField::Map { from, variable_name, default_value, .. } => {
+					out.push(NodeRef::PropertyKey(from));
+					out.push(NodeRef::VariableField(variable_name.get_ast()));
+					out.extend(default_value.as_ref().map(NodeRef::Expression));
+				}
+			},
+			NodeRef::ArrayDestructuringField(field) => match field {
+				ArrayDestructuringField::Spread(..) | ArrayDestructuringField::None => {}
+				ArrayDestructuringField::Name(name, default_value) => {
+					out.push(NodeRef::VariableField(name.get_ast()));
+					out.extend(default_value.as_ref().map(NodeRef::Expression));
+				}
+			},
+			NodeRef::VariableOrPropertyAccess(access) => match access {
+				VariableOrPropertyAccess::Variable(..)
+				| VariableOrPropertyAccess::Cursor { .. } => {}
+				VariableOrPropertyAccess::PropertyAccess { parent, .. } => {
+					out.push(NodeRef::Expression(parent));
+				}
+				VariableOrPropertyAccess::Index { indexee, indexer, .. } => {
+					out.push(NodeRef::Expression(indexee));
+					multiple_expression_children(indexer, out);
+				}
+			},
+			NodeRef::ConditionalElseStatement(statement) => {
+				multiple_expression_children(&statement.condition, out);
+				block_or_single_statement_child(&statement.inner, out);
+			}
+			NodeRef::UnconditionalElseStatement(statement) => {
+				block_or_single_statement_child(&statement.inner, out);
+			}
+			NodeRef::EnumMember(EnumMember::Variant { value, .. }) => {
+				out.extend(value.as_ref().map(NodeRef::Expression));
+			}
+			NodeRef::JSXElement(element) => jsx_element_children(element, out),
+			NodeRef::JSXNode(node) => match node {
+				JSXNode::InterpolatedExpression(expression, _) => {
+					out.push(NodeRef::Expression(expression));
+				}
+				JSXNode::Fragment(fragment) => jsx_nodes_children(&fragment.children, out),
+				JSXNode::TextNode(..) | JSXNode::Element(_) | JSXNode::LineBreak => {}
+			},
+			NodeRef::JSXAttribute(attribute) => match attribute {
+				JSXAttribute::Static(..) | JSXAttribute::BooleanAttribute(..) => {}
+				JSXAttribute::Dynamic(_, expression, _) => {
+					out.push(NodeRef::Expression(expression))
+				}
+				JSXAttribute::Spread(expression, _) | JSXAttribute::Shorthand(expression) => {
+					out.push(NodeRef::Expression(expression))
+				}
+			},
+			NodeRef::ImportPart(_)
+			| NodeRef::VariableIdentifier(_)
+			| NodeRef::InterfaceDeclaration(_)
+			| NodeRef::TypeAlias(_)
+			| NodeRef::TypeReference(_) => {}
+		}
+		children
+	}
+
+	/// All nodes under this node (not including itself) in pre-order
+	pub fn descendants(&self, functions: &'a ExtractedFunctions) -> Vec<NodeRef<'a>> {
+		let mut nodes = Vec::new();
+		self.walk(functions, &mut |node, depth| {
+			if depth > 0 {
+				nodes.push(node);
+			}
+		});
+		nodes
+	}
+
+	/// Calls `cb` with each node (starting with this node) and its depth relative to this node, in pre-order
+	pub fn walk(&self, functions: &'a ExtractedFunctions, cb: &mut impl FnMut(NodeRef<'a>, usize)) {
+		fn walk_inner<'a>(
+			node: NodeRef<'a>,
+			functions: &'a ExtractedFunctions,
+			depth: usize,
+			cb: &mut impl FnMut(NodeRef<'a>, usize),
+		) {
+			cb(node, depth);
+			for child in node.children(functions) {
+				walk_inner(child, functions, depth + 1, cb);
+			}
+		}
+		walk_inner(*self, functions, 0, cb)
+	}
+}
+
+fn expression_kind(expression: &Expression) -> &'static str {
+	match expression {
+		Expression::NumberLiteral(..) => "NumberLiteral",
+		Expression::StringLiteral(..) => "StringLiteral",
+		Expression::BooleanLiteral(..) => "BooleanLiteral",
+		Expression::RegexLiteral { .. } => "RegexLiteral",
+		Expression::ArrayLiteral(..) => "ArrayLiteral",
+		Expression::ObjectLiteral(_) => "ObjectLiteral",
+		Expression::TemplateLiteral(_) => "TemplateLiteral",
+		Expression::ParenthesizedExpression(..) => "ParenthesizedExpression",
+		Expression::BinaryOperation { .. } => "BinaryOperation",
+		Expression::SpecialOperators(operator, ..) => match operator {
+			SpecialOperators::AsExpression { .. } => "AsExpression",
+			SpecialOperators::SatisfiesExpression { .. } => "SatisfiesExpression",
+			#[cfg(feature = "extras")]
+			SpecialOperators::IsExpression { .. } => "IsTypeExpression",
+		},
+		Expression::UnaryOperation { .. } => "UnaryOperation",
+		Expression::Assignment { .. } => "Assignment",
+		Expression::BinaryAssignmentOperation { .. } => "BinaryAssignmentOperation",
+		Expression::UnaryPrefixAssignmentOperation { .. } => "UnaryPrefixAssignmentOperation",
+		Expression::UnaryPostfixAssignmentOperation { .. } => "UnaryPostfixAssignmentOperation",
+		Expression::VariableReference(..) => "VariableReference",
+		Expression::ThisReference(..) => "ThisReference",
+		Expression::SuperExpression(..) => "SuperExpression",
+		Expression::NewTarget(..) => "NewTarget",
+		Expression::DynamicImport { .. } => "DynamicImport",
+		Expression::PropertyAccess { .. } => "PropertyAccess",
+		Expression::Index { .. } => "Index",
+		Expression::FunctionCall { .. } => "FunctionCall",
+		Expression::ConstructorCall { .. } => "ConstructorCall",
+		Expression::TernaryExpression { .. } => "TernaryExpression",
+		Expression::ArrowFunction(_) | Expression::ExtractedArrowFunction(_) => "ArrowFunction",
+		Expression::ExpressionFunction(_) | Expression::ExtractedExpressionFunction(_) => {
+			"ExpressionFunction"
+		}
+		Expression::ClassExpression(..) => "ClassExpression",
+		Expression::Null(..) => "Null",
+		Expression::PrefixComment(..) | Expression::PostfixComment(..) => "CommentedExpression",
+		Expression::Comment(..) => "Comment",
+		Expression::JSXRoot(JSXRoot::Element(_)) => "JSXElement",
+		Expression::JSXRoot(JSXRoot::Fragment(_)) => "JSXFragment",
+		Expression::IsExpression(_) => "IsExpression",
+		Expression::Cursor { .. } => "Cursor",
+	}
+}
+
+fn statement_children<'a>(statement: &'a Statement, out: &mut Vec<NodeRef<'a>>) {
+	match statement {
+		Statement::Expression(expression) => multiple_expression_children(expression, out),
+		Statement::Block(block) => out.extend(block.0.iter().map(NodeRef::from)),
+		Statement::IfStatement(if_statement) => {
+			multiple_expression_children(&if_statement.condition, out);
+			block_or_single_statement_child(&if_statement.inner, out);
+			out.extend(if_statement.else_conditions.iter().map(NodeRef::ConditionalElseStatement));
+			out.extend(
+				if_statement.trailing_else.as_ref().map(NodeRef::UnconditionalElseStatement),
+			);
+		}
+		Statement::ForLoopStatement(for_statement) => {
+			match &for_statement.condition {
+				ForLoopCondition::ForOf { variable, of: subject, .. }
+				| ForLoopCondition::ForIn { variable, r#in: subject, .. } => {
+					out.push(NodeRef::VariableField(variable.get_ast()));
+					out.push(NodeRef::Expression(subject));
+				}
+				ForLoopCondition::Statements { initializer, condition, afterthought } => {
+					match initializer {
+						Some(ForLoopStatementInitializer::Statement(declaration)) => {
+							out.push(NodeRef::VariableDeclaration(declaration))
+						}
+						Some(ForLoopStatementInitializer::VarStatement(statement)) => out.extend(
+							statement
+								.declarations
+								.iter()
+								.map(NodeRef::OptionalVariableDeclarationItem),
+						),
+						Some(ForLoopStatementInitializer::Expression(expression)) => {
+							out.push(NodeRef::Expression(expression))
+						}
+						None => {}
+					}
+					out.extend(condition.as_ref().map(NodeRef::Expression));
+					out.extend(afterthought.as_ref().map(NodeRef::Expression));
+				}
+			}
+			block_or_single_statement_child(&for_statement.inner, out);
+		}
+		Statement::SwitchStatement(switch_statement) => {
+			out.push(NodeRef::Expression(&switch_statement.case));
+			for branch in switch_statement.branches.iter() {
+				let statements = match branch {
+					SwitchBranch::Default(statements) => statements,
+					SwitchBranch::Case(case, statements) => {
+						out.push(NodeRef::Expression(case));
+						statements
+					}
+				};
+				out.extend(statements.iter().map(NodeRef::Statement));
+			}
+		}
+		Statement::WhileStatement(while_statement) => {
+			out.push(NodeRef::Expression(&while_statement.condition));
+			block_or_single_statement_child(&while_statement.inner, out);
+		}
+		Statement::DoWhileStatement(do_while_statement) => {
+			block_or_single_statement_child(&do_while_statement.inner, out);
+			out.push(NodeRef::Expression(&do_while_statement.condition));
+		}
+		Statement::TryCatchStatement(try_catch_statement) => {
+			out.push(NodeRef::Block(&try_catch_statement.try_inner));
+			out.extend(
+				try_catch_statement
+					.exception_var
+					.as_ref()
+					.map(|exception_var| NodeRef::VariableField(exception_var.get_ast())),
+			);
+			out.extend(try_catch_statement.catch_inner.as_ref().map(NodeRef::Block));
+			out.extend(try_catch_statement.finally_inner.as_ref().map(NodeRef::Block));
+		}
+		Statement::Return(_, expression) => {
+			if let Some(expression) = expression {
+				multiple_expression_children(expression, out);
+			}
+		}
+		Statement::Throw(_, expression) => out.push(NodeRef::Expression(expression)),
+		Statement::Labelled { statement, .. } => out.push(NodeRef::Statement(statement)),
+		Statement::VarVariable(statement) => {
+			out.extend(statement.declarations.iter().map(NodeRef::OptionalVariableDeclarationItem))
+		}
+		Statement::Debugger(_)
+		| Statement::Continue(..)
+		| Statement::Break(..)
+		| Statement::Comment(..)
+		| Statement::MultiLineComment(..)
+		| Statement::Cursor(..) => {}
+	}
+}
+
+fn declaration_children<'a>(
+	declaration: &'a Declaration,
+	functions: &'a ExtractedFunctions,
+	out: &mut Vec<NodeRef<'a>>,
+) {
+	match declaration {
+		Declaration::Variable(declaration) => variable_declaration_children(declaration, out),
+		Declaration::Function(function) => {
+			out.extend(function.decorators.iter().map(NodeRef::Decorator));
+			function_children(&function.on, out);
+		}
+		Declaration::ExtractedFunction(function) => {
+			extracted_function_children::<StatementFunctionBase>(function, functions, out)
+		}
+		Declaration::Class(class) => {
+			out.extend(class.decorators.iter().map(NodeRef::Decorator));
+			class_children(&class.on, out);
+		}
+		Declaration::Enum(enum_declaration) => {
+			out.extend(enum_declaration.decorators.iter().map(NodeRef::Decorator));
+			out.extend(enum_declaration.on.members.iter().map(NodeRef::EnumMember));
+		}
+		Declaration::Interface(interface) => {
+			out.extend(interface.decorators.iter().map(NodeRef::Decorator));
+		}
+		Declaration::DeclareVariable(declare) => {
+			out.extend(declare.decorators.iter().map(NodeRef::Decorator));
+			out.push(NodeRef::TypeReference(&declare.type_restriction));
+		}
+		Declaration::DeclareFunction(declare) => {
+			out.extend(declare.decorators.iter().map(NodeRef::Decorator));
+			out.extend(declare.return_type.as_ref().map(NodeRef::TypeReference));
+		}
+		Declaration::Import(import) => {
+			if let Some(imports) = &import.imports {
+				out.extend(imports.iter().map(NodeRef::ImportPart));
+			}
+		}
+		Declaration::Export(export) => {
+			out.extend(export.decorators.iter().map(NodeRef::Decorator));
+			match &export.on {
+				ExportDeclaration::Variable { exported, .. } => match exported {
+					Exportable::Class(class) => out.push(NodeRef::ClassDeclaration(class)),
+					Exportable::Function(function) => {
+						out.push(NodeRef::StatementFunction(function))
+					}
+					Exportable::Variable(declaration) => {
+						out.push(NodeRef::VariableDeclaration(declaration))
+					}
+					Exportable::VarVariable(statement) => out.extend(
+						statement.declarations.iter().map(NodeRef::OptionalVariableDeclarationItem),
+					),
+					Exportable::Interface(interface) => {
+						out.push(NodeRef::InterfaceDeclaration(interface))
+					}
+					Exportable::TypeAlias(alias) => out.push(NodeRef::TypeAlias(alias)),
+					Exportable::Enum(enum_declaration) => {
+						out.extend(enum_declaration.members.iter().map(NodeRef::EnumMember))
+					}
+				},
+				ExportDeclaration::Default { expression, .. } => {
+					out.push(NodeRef::Expression(expression))
+				}
+			}
+		}
+		Declaration::TypeAlias(_) | Declaration::DeclareInterface(_) => {}
+	}
+}
+
+fn expression_children<'a>(
+	expression: &'a Expression,
+	functions: &'a ExtractedFunctions,
+	out: &mut Vec<NodeRef<'a>>,
+) {
+	match expression {
+		Expression::ArrayLiteral(items, _, _) => spread_expression_children(items, out),
+		Expression::ObjectLiteral(object) => {
+			out.extend(object.members.iter().map(NodeRef::ObjectLiteralMember))
+		}
+		Expression::TemplateLiteral(template) => {
+			out.extend(template.tag.as_deref().map(NodeRef::Expression));
+			for part in template.parts.iter() {
+				if let TemplateLiteralPart::Dynamic(expression) = part {
+					out.push(NodeRef::Expression(expression));
+				}
+			}
+		}
+		Expression::ParenthesizedExpression(inner, _, _) => {
+			multiple_expression_children(inner, out)
+		}
+		Expression::BinaryOperation { lhs, rhs, .. } => {
+			out.push(NodeRef::Expression(lhs));
+			out.push(NodeRef::Expression(rhs));
+		}
+		Expression::SpecialOperators(operator, _, _) => match operator {
+			SpecialOperators::AsExpression { value, type_annotation, .. }
+			| SpecialOperators::SatisfiesExpression { value, type_annotation, .. } => {
+				out.push(NodeRef::Expression(value));
+				out.push(NodeRef::TypeReference(type_annotation));
+			}
+			#[cfg(feature = "extras")]
+			SpecialOperators::IsExpression { value, type_annotation, .. } => {
+				out.push(NodeRef::Expression(value));
+				out.push(NodeRef::TypeReference(type_annotation));
+			}
+		},
+		Expression::UnaryOperation { operand, .. } => out.push(NodeRef::Expression(operand)),
+		Expression::Assignment { lhs, rhs, .. } => {
+			match lhs {
+				LHSOfAssignment::ObjectDestructuring(fields, _, _) => out.extend(
+					fields.iter().map(|field| NodeRef::ObjectDestructuringField(field.get_ast())),
+				),
+				LHSOfAssignment::ArrayDestructuring(fields, _, _) => out.extend(
+					fields
+						.iter()
+						.filter(|field| !matches!(field, ArrayDestructuringField::None))
+						.map(NodeRef::ArrayDestructuringField),
+				),
+				LHSOfAssignment::VariableOrPropertyAccess(access) => {
+					out.push(NodeRef::VariableOrPropertyAccess(access))
+				}
+			}
+			out.push(NodeRef::Expression(rhs));
+		}
+		Expression::BinaryAssignmentOperation { lhs, rhs, .. } => {
+			out.push(NodeRef::VariableOrPropertyAccess(lhs));
+			out.push(NodeRef::Expression(rhs));
+		}
+		Expression::UnaryPrefixAssignmentOperation { operand, .. }
+		| Expression::UnaryPostfixAssignmentOperation { operand, .. } => {
+			out.push(NodeRef::VariableOrPropertyAccess(operand))
+		}
+		Expression::SuperExpression(reference, _, _) => match reference {
+			SuperReference::Call { arguments } => spread_expression_children(arguments, out),
+			SuperReference::PropertyAccess { .. } => {}
+			SuperReference::Index { indexer } => out.push(NodeRef::Expression(indexer)),
+		},
+		Expression::DynamicImport { path, .. } => out.push(NodeRef::Expression(path)),
+		Expression::PropertyAccess { parent, .. } => out.push(NodeRef::Expression(parent)),
+		Expression::Index { indexee, indexer, .. } => {
+			out.push(NodeRef::Expression(indexee));
+			multiple_expression_children(indexer, out);
+		}
+		Expression::FunctionCall { function, type_arguments, arguments, .. } => {
+			out.push(NodeRef::Expression(function));
+			if let Some(type_arguments) = type_arguments {
+				out.extend(type_arguments.iter().map(NodeRef::TypeReference));
+			}
+			spread_expression_children(arguments, out);
+		}
+		Expression::ConstructorCall { constructor, type_arguments, arguments, .. } => {
+			out.push(NodeRef::Expression(constructor));
+			if let Some(type_arguments) = type_arguments {
+				out.extend(type_arguments.iter().map(NodeRef::TypeReference));
+			}
+			if let Some(arguments) = arguments {
+				spread_expression_children(arguments, out);
+			}
+		}
+		Expression::TernaryExpression { condition, truthy_result, falsy_result, .. } => {
+			out.push(NodeRef::Expression(condition));
+			out.push(NodeRef::Expression(truthy_result));
+			out.push(NodeRef::Expression(falsy_result));
+		}
+		Expression::ArrowFunction(function) => function_children(function, out),
+		Expression::ExpressionFunction(function) => function_children(function, out),
+		Expression::ExtractedArrowFunction(function) => {
+			extracted_function_children::<ArrowFunctionBase>(function, functions, out)
+		}
+		Expression::ExtractedExpressionFunction(function) => {
+			extracted_function_children::<ExpressionFunctionBase>(function, functions, out)
+		}
+		Expression::ClassExpression(class, _) => class_children(class, out),
+		Expression::PrefixComment(_, expression, _, _)
+		| Expression::PostfixComment(expression, _, _, _) => out.push(NodeRef::Expression(expression)),
+		Expression::JSXRoot(JSXRoot::Element(element)) => jsx_element_children(element, out),
+		Expression::JSXRoot(JSXRoot::Fragment(fragment)) => {
+			jsx_nodes_children(&fragment.children, out)
+		}
+		Expression::IsExpression(is_expression) => {
+			multiple_expression_children(&is_expression.matcher, out);
+			for (type_reference, body) in is_expression.branches.iter() {
+				out.push(NodeRef::TypeReference(type_reference));
+				body.push_body_node(out);
+			}
+		}
+		Expression::NumberLiteral(..)
+		| Expression::StringLiteral(..)
+		| Expression::BooleanLiteral(..)
+		| Expression::RegexLiteral { .. }
+		| Expression::VariableReference(..)
+		| Expression::ThisReference(..)
+		| Expression::NewTarget(..)
+		| Expression::Null(..)
+		| Expression::Comment(..)
+		| Expression::Cursor { .. } => {}
+	}
+}
+
+/// Flattens `a, b, c` into its expressions
+fn multiple_expression_children<'a>(
+	expression: &'a MultipleExpression,
+	out: &mut Vec<NodeRef<'a>>,
+) {
+	if let Some(lhs) = &expression.lhs {
+		multiple_expression_children(lhs, out);
+	}
+	out.push(NodeRef::Expression(&expression.rhs));
+}
+
+fn spread_expression_children<'a>(items: &'a [SpreadExpression], out: &mut Vec<NodeRef<'a>>) {
+	for item in items {
+		match item {
+			SpreadExpression::Spread(expression, _) | SpreadExpression::NonSpread(expression) => {
+				out.push(NodeRef::Expression(expression))
+			}
+			SpreadExpression::Empty => {}
+		}
+	}
+}
+
+fn block_or_single_statement_child<'a>(
+	inner: &'a BlockOrSingleStatement,
+	out: &mut Vec<NodeRef<'a>>,
+) {
+	out.push(match inner {
+		BlockOrSingleStatement::Braced(block) => NodeRef::Block(block),
+		BlockOrSingleStatement::SingleStatement(statement) => NodeRef::Statement(statement),
+	})
+}
+
+fn variable_declaration_children<'a>(
+	declaration: &'a VariableDeclaration,
+	out: &mut Vec<NodeRef<'a>>,
+) {
+	match declaration {
+		VariableDeclaration::ConstDeclaration { declarations, .. } => {
+			out.extend(declarations.iter().map(NodeRef::VariableDeclarationItem))
+		}
+		VariableDeclaration::LetDeclaration { declarations, .. } => {
+			out.extend(declarations.iter().map(NodeRef::OptionalVariableDeclarationItem))
+		}
+	}
+}
+
+fn class_children<'a, T: ExpressionOrStatementPosition>(
+	class: &'a ClassDeclaration<T>,
+	out: &mut Vec<NodeRef<'a>>,
+) {
+	out.extend(class.extends.as_ref().map(NodeRef::TypeReference));
+	for member in class.members.iter() {
+		out.extend(member.decorators.iter().map(NodeRef::Decorator));
+		out.push(NodeRef::ClassMember(&member.on));
+	}
+}
+
+fn jsx_element_children<'a>(element: &'a JSXElement, out: &mut Vec<NodeRef<'a>>) {
+	out.extend(element.attributes.iter().map(NodeRef::JSXAttribute));
+	if let JSXElementChildren::Children(children) = &element.children {
+		jsx_nodes_children(children, out);
+	}
+}
+
+fn jsx_nodes_children<'a>(nodes: &'a [JSXNode], out: &mut Vec<NodeRef<'a>>) {
+	for node in nodes {
+		match node {
+			JSXNode::Element(element) => out.push(NodeRef::JSXElement(element)),
+			JSXNode::LineBreak => {}
+			node => out.push(NodeRef::JSXNode(node)),
+		}
+	}
+}
+
+/// Function bodies are either [Block]s or (for arrow functions) [ExpressionOrBlock]
+trait FunctionBodyNode {
+	fn push_body_node<'a>(&'a self, out: &mut Vec<NodeRef<'a>>);
+}
+
+impl FunctionBodyNode for Block {
+	fn push_body_node<'a>(&'a self, out: &mut Vec<NodeRef<'a>>) {
+		out.push(NodeRef::Block(self));
+	}
+}
+
+impl FunctionBodyNode for ExpressionOrBlock {
+	fn push_body_node<'a>(&'a self, out: &mut Vec<NodeRef<'a>>) {
+		out.push(match self {
+			ExpressionOrBlock::Expression(expression) => NodeRef::Expression(expression),
+			ExpressionOrBlock::Block(block) => NodeRef::Block(block),
+		});
+	}
+}
+
+/// Names of class and object methods can be computed
+trait FunctionNameNode {
+	fn push_name_node<'a>(&'a self, _out: &mut Vec<NodeRef<'a>>) {}
+}
+
+impl FunctionNameNode for () {}
+impl FunctionNameNode for crate::VariableIdentifier {}
+impl FunctionNameNode for Option<crate::VariableIdentifier> {}
+
+impl FunctionNameNode for crate::WithComment<PropertyKey> {
+	fn push_name_node<'a>(&'a self, out: &mut Vec<NodeRef<'a>>) {
+		out.push(NodeRef::PropertyKey(self.get_ast()));
+	}
+}
+
+fn function_children<'a, T: FunctionBased>(
+	function: &'a FunctionBase<T>,
+	out: &mut Vec<NodeRef<'a>>,
+) where
+	T::Name: FunctionNameNode,
+	T::Body: FunctionBodyNode,
+{
+	function.name.push_name_node(out);
+	parameters_children(&function.parameters, out);
+	out.extend(function.return_type.as_ref().map(NodeRef::TypeReference));
+	function.body.push_body_node(out);
+}
+
+fn extracted_function_children<'a, T: FunctionBased + 'static>(
+	function: &ExtractedFunction<T>,
+	functions: &'a ExtractedFunctions,
+	out: &mut Vec<NodeRef<'a>>,
+) where
+	ExtractedFunctions: GetFunction<T>,
+	T::Name: FunctionNameNode,
+	T::Body: FunctionBodyNode,
+{
+	if let Some(function) = GetFunction::<T>::get_function_ref(functions, function.0) {
+		function_children(function, out);
+	}
+}
+
+fn parameters_children<'a>(parameters: &'a FunctionParameters, out: &mut Vec<NodeRef<'a>>) {
+	out.extend(parameters.parameters.iter().map(NodeRef::Parameter));
+	out.extend(parameters.optional_parameters.iter().map(NodeRef::OptionalParameter));
+	out.extend(parameters.rest_parameter.as_deref().map(NodeRef::SpreadParameter));
+}
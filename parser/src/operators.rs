@@ -116,7 +116,7 @@ impl Operator for BinaryOperator {
 			BinaryOperator::LessThan => "<",
 			BinaryOperator::GreaterThan => ">",
 			BinaryOperator::LessThanEqual => "<=",
-			BinaryOperator::GreaterThanEqual => ">",
+			BinaryOperator::GreaterThanEqual => ">=",
 			BinaryOperator::Equal => "==",
 			BinaryOperator::StrictEqual => "===",
 			BinaryOperator::NotEqual => "!=",
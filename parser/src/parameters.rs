@@ -8,15 +8,93 @@ use tokenizer_lib::{Token, TokenReader};
 use visitable_derive::Visitable;
 
 use crate::{
-	errors::parse_lexing_error, tokens::token_as_identifier, ASTNode, Expression, ParseError,
-	ParseResult, TypeReference, VariableField, VariableFieldInSourceCode, VariableId,
-	VariableIdentifier, WithComment,
+	errors::parse_lexing_error, tokens::token_as_identifier, types::Visibility, ASTNode,
+	Expression, ParseError, ParseResult, TSXKeyword, TypeReference, VariableField,
+	VariableFieldInSourceCode, VariableId, VariableIdentifier, WithComment,
 };
 
 #[derive(Debug, Clone, Eq, PartialEq, Visitable)]
+#[cfg_attr(feature = "self-rust-tokenize", derive(self_rust_tokenize::SelfRustTokenize))]
 pub struct Parameter {
 	pub name: WithComment<VariableField<VariableFieldInSourceCode>>,
 	pub type_reference: Option<TypeReference>,
+	#[visit_skip_field]
+	pub property: Option<ParameterProperty>,
+}
+
+/// Modifiers on a constructor parameter which also declare (and assign) a property of the same
+/// name. [See](https://www.typescriptlang.org/docs/handbook/2/classes.html#parameter-properties)
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "self-rust-tokenize", derive(self_rust_tokenize::SelfRustTokenize))]
+pub struct ParameterProperty {
+	pub visibility: Option<Visibility>,
+	pub readonly: bool,
+	pub position: Span,
+}
+
+impl ParameterProperty {
+	pub(crate) fn from_reader(reader: &mut impl TokenReader<TSXToken, Span>) -> Option<Self> {
+		let mut property: Option<Self> = None;
+		while let Some(Token(
+			TSXToken::Keyword(
+				TSXKeyword::Public
+				| TSXKeyword::Private
+				| TSXKeyword::Protected
+				| TSXKeyword::Readonly,
+			),
+			_,
+		)) = reader.peek()
+		{
+			// Modifiers are also valid parameter names
+			if let Some(Token(
+				TSXToken::Comma
+				| TSXToken::CloseParentheses
+				| TSXToken::Colon
+				| TSXToken::Assign
+				| TSXToken::QuestionMark
+				| TSXToken::OptionalMember,
+				_,
+			)) = reader.peek_n(1)
+			{
+				break;
+			}
+			let Token(token, position) = reader.next().unwrap();
+			let property = property.get_or_insert_with(|| Self {
+				visibility: None,
+				readonly: false,
+				position: position.clone(),
+			});
+			property.position = property.position.union(&position);
+			match token {
+				TSXToken::Keyword(TSXKeyword::Public) => {
+					property.visibility = Some(Visibility::Public);
+				}
+				TSXToken::Keyword(TSXKeyword::Private) => {
+					property.visibility = Some(Visibility::Private);
+				}
+				TSXToken::Keyword(TSXKeyword::Protected) => {
+					property.visibility = Some(Visibility::Protected);
+				}
+				_ => property.readonly = true,
+			}
+		}
+		property
+	}
+
+	fn to_string_from_buffer<T: source_map::ToString>(
+		property: &Option<Self>,
+		buf: &mut T,
+		settings: &crate::ToStringSettingsAndData,
+	) {
+		if let (true, Some(property)) = (settings.0.include_types, property) {
+			if let Some(visibility) = property.visibility {
+				buf.push_str(visibility.as_str());
+			}
+			if property.readonly {
+				buf.push_str("readonly ");
+			}
+		}
+	}
 }
 
 // TODO not sure whether parameter should implement ASTNode
@@ -32,20 +110,26 @@ impl Parameter {
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Visitable)]
+#[cfg_attr(feature = "self-rust-tokenize", derive(self_rust_tokenize::SelfRustTokenize))]
 pub enum OptionalOrWithDefaultValueParameter {
 	Optional {
 		// WithComment<VariableField<VariableFieldInSourceCode>>
 		name: VariableIdentifier,
 		type_reference: Option<TypeReference>,
+		#[visit_skip_field]
+		property: Option<ParameterProperty>,
 	},
 	WithDefaultValue {
 		name: WithComment<VariableField<VariableFieldInSourceCode>>,
 		type_reference: Option<TypeReference>,
 		value: Box<Expression>,
+		#[visit_skip_field]
+		property: Option<ParameterProperty>,
 	},
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Visitable)]
+#[cfg_attr(feature = "self-rust-tokenize", derive(self_rust_tokenize::SelfRustTokenize))]
 pub struct SpreadParameter {
 	pub name: VariableIdentifier,
 	pub type_reference: Option<TypeReference>,
@@ -54,6 +138,7 @@ pub struct SpreadParameter {
 /// TODO need to something special to not enable `OptionalFunctionParameter::WithValue` in interfaces and other
 /// type structure
 #[derive(Debug, Clone, PartialEqExtras, Visitable)]
+#[cfg_attr(feature = "self-rust-tokenize", derive(self_rust_tokenize::SelfRustTokenize))]
 pub struct FunctionParameters {
 	pub parameters: Vec<Parameter>,
 	pub optional_parameters: Vec<OptionalOrWithDefaultValueParameter>,
@@ -86,8 +171,9 @@ impl ASTNode for FunctionParameters {
 	) {
 		let FunctionParameters { parameters, optional_parameters, rest_parameter, .. } = self;
 		buf.push('(');
-		for (at_end, Parameter { name, type_reference, .. }) in parameters.iter().endiate() {
+		for (at_end, Parameter { name, type_reference, property }) in parameters.iter().endiate() {
 			// decorators_to_string_from_buffer(decorators, buf, settings, depth);
+			ParameterProperty::to_string_from_buffer(property, buf, settings);
 			name.to_string_from_buffer(buf, settings, depth);
 			if let (true, Some(ref type_reference)) = (settings.0.include_types, type_reference) {
 				buf.push_str(": ");
@@ -100,9 +186,16 @@ impl ASTNode for FunctionParameters {
 		}
 		for (at_end, parameter) in optional_parameters.iter().endiate() {
 			match parameter {
-				OptionalOrWithDefaultValueParameter::Optional { name, type_reference, .. } => {
+				OptionalOrWithDefaultValueParameter::Optional {
+					name,
+					type_reference,
+					property,
+				} => {
+					ParameterProperty::to_string_from_buffer(property, buf, settings);
 					buf.push_str(name.as_str());
-					buf.push('?');
+					if settings.0.include_types {
+						buf.push('?');
+					}
 					if let (true, Some(type_reference)) = (settings.0.include_types, type_reference)
 					{
 						buf.push_str(": ");
@@ -113,8 +206,9 @@ impl ASTNode for FunctionParameters {
 					name,
 					type_reference,
 					value,
-					..
+					property,
 				} => {
+					ParameterProperty::to_string_from_buffer(property, buf, settings);
 					name.to_string_from_buffer(buf, settings, depth);
 					if let (true, Some(type_reference)) = (settings.0.include_types, type_reference)
 					{
@@ -133,7 +227,9 @@ impl ASTNode for FunctionParameters {
 		if let Some(rest_parameter) = rest_parameter {
 			buf.push_str("...");
 			buf.push_str(rest_parameter.name.as_str());
-			if let Some(ref type_reference) = rest_parameter.type_reference {
+			if let (true, Some(ref type_reference)) =
+				(settings.0.include_types, &rest_parameter.type_reference)
+			{
 				buf.push_str(": ");
 				type_reference.to_string_from_buffer(buf, settings, depth);
 			}
@@ -179,6 +275,7 @@ impl FunctionParameters {
 				}));
 				break;
 			} else {
+				let property = ParameterProperty::from_reader(reader);
 				let name = WithComment::<VariableField<VariableFieldInSourceCode>>::from_reader(
 					reader, state, settings,
 				)?;
@@ -223,6 +320,7 @@ impl FunctionParameters {
 								name,
 								type_reference,
 								value: Box::new(value),
+								property,
 							},
 						);
 					}
@@ -241,6 +339,7 @@ impl FunctionParameters {
 						optional_parameters.push(OptionalOrWithDefaultValueParameter::Optional {
 							name,
 							type_reference,
+							property,
 						});
 					}
 					(false, None) => {
@@ -250,7 +349,7 @@ impl FunctionParameters {
 								name.get_position().into_owned()
                             ));
 						}
-						parameters.push(Parameter { name, type_reference });
+						parameters.push(Parameter { name, type_reference, property });
 					}
 				}
 			}
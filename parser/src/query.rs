@@ -0,0 +1,898 @@
+//! Structural search over the AST.
+//!
+//! Patterns are written as source with metavariables, e.g. `console.log($message)`. Metavariables are
+//! turned into cursors (the same mechanism used by the generator crate's `#name` interpolation) before
+//! parsing.
+//! - `$name` matches any expression (or statement, property name, variable name). If the same name
+//!   is used twice, both positions must be syntactically equal
+//! - `$_` matches anything without capturing
+//! - `$$$name` as the last item of an argument or array list matches the remaining items
+
+use std::{
+	borrow::Cow,
+	collections::{hash_map::Entry, HashMap},
+};
+
+use crate::{
+	cursor::EmptyCursorId,
+	declarations::VariableDeclarationItem,
+	expressions::{
+		arrow_function::ArrowFunctionBase,
+		assignments::{LHSOfAssignment, VariableOrPropertyAccess},
+		object_literal::ObjectLiteralMember,
+		ExpressionFunctionBase, ExpressionOrBlock, MultipleExpression, SpreadExpression,
+		TemplateLiteralPart,
+	},
+	extractor::{ExtractedFunctions, GetFunction},
+	ASTNode, Block, BlockOrSingleStatement, Declaration, Expression, FunctionParameters, NodeRef,
+	ParseOutput, ParseResult, ParseSettings, PropertyKey, PropertyReference, SourceId, Span,
+	Statement, StatementOrDeclaration, VariableField, VariableFieldInSourceCode,
+	VariableIdentifier,
+};
+
+/// A name in a pattern which captures a node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Metavariable {
+	pub name: String,
+	/// Whether it was written as `$$$name`
+	pub variadic: bool,
+}
+
+/// Replaces metavariables in `source` with cursors, returning the new source, the cursor positions and
+/// the metavariables (indexed by cursor id)
+pub fn source_with_cursors(
+	source: &str,
+) -> (String, Vec<(usize, EmptyCursorId)>, Vec<Metavariable>) {
+	let mut output = String::with_capacity(source.len());
+	let mut cursors = Vec::new();
+	let mut metavariables: Vec<Metavariable> = Vec::new();
+
+	let is_identifier_char = |chr: char| chr.is_alphanumeric() || chr == '_';
+
+	let mut in_string: Option<char> = None;
+	let mut last = None::<char>;
+	let mut chars = source.char_indices().peekable();
+	while let Some((idx, chr)) = chars.next() {
+		if let Some(quote) = in_string {
+			if chr == quote && last != Some('\\') {
+				in_string = None;
+			}
+		} else if matches!(chr, '"' | '\'' | '`') {
+			in_string = Some(chr);
+		} else if chr == '$' && !last.is_some_and(is_identifier_char) {
+			let rest = &source[idx..];
+			let (variadic, name_start) =
+				if rest.starts_with("$$$") { (true, idx + 3) } else { (false, idx + 1) };
+			let name_length = source[name_start..]
+				.char_indices()
+				.take_while(|(_, chr)| is_identifier_char(*chr))
+				.last()
+				.map_or(0, |(idx, chr)| idx + chr.len_utf8());
+			let name = &source[name_start..(name_start + name_length)];
+			let starts_with_digit = name.chars().next().is_none_or(|chr| chr.is_ascii_digit());
+			if !starts_with_digit {
+				// Repeated names get their own cursor and are compared when capturing
+				let id = u8::try_from(metavariables.len()).expect("too many metavariables");
+				metavariables.push(Metavariable { name: name.to_owned(), variadic });
+				cursors.push((output.len(), EmptyCursorId::new(id)));
+				// A space means the lexer is in a neutral state when it reaches the cursor
+				output.push(' ');
+				while matches!(chars.peek(), Some((next, _)) if *next < name_start + name_length) {
+					chars.next();
+				}
+				last = Some(' ');
+				continue;
+			}
+		}
+		output.push(chr);
+		last = Some(chr);
+	}
+
+	(output, cursors, metavariables)
+}
+
+#[derive(Debug)]
+enum PatternRoot {
+	Expression(Box<Expression>),
+	StatementOrDeclaration(Box<StatementOrDeclaration>),
+}
+
+/// A parsed pattern
+#[derive(Debug)]
+pub struct Pattern {
+	root: PatternRoot,
+	metavariables: Vec<Metavariable>,
+	functions: ExtractedFunctions,
+}
+
+/// What a metavariable matched against
+#[derive(Debug, Clone, Copy)]
+pub enum Capture<'a> {
+	Node(NodeRef<'a>),
+	/// For metavariables in property position (`x.$property`)
+	Property(&'a str),
+	/// For variadic metavariables (`$$$items`)
+	Items(&'a [SpreadExpression]),
+}
+
+impl<'a> Capture<'a> {
+	/// Whether the capture is syntactically equal to `other`. Used for repeated metavariables
+	fn syntactically_equal(&self, other: &Capture<'a>) -> bool {
+		match (self, other) {
+			(Capture::Node(left), Capture::Node(right)) => match (left, right) {
+				(NodeRef::Expression(left), NodeRef::Expression(right)) => left == right,
+				(NodeRef::Statement(left), NodeRef::Statement(right)) => left == right,
+				(
+					NodeRef::VariableOrPropertyAccess(left),
+					NodeRef::VariableOrPropertyAccess(right),
+				) => left == right,
+				(NodeRef::VariableOrPropertyAccess(access), NodeRef::Expression(expression))
+				| (NodeRef::Expression(expression), NodeRef::VariableOrPropertyAccess(access)) => {
+					&Expression::from((*access).clone()) == *expression
+				}
+				(NodeRef::VariableField(left), NodeRef::VariableField(right)) => {
+					variable_field_name(left).is_some()
+						&& variable_field_name(left) == variable_field_name(right)
+				}
+				(NodeRef::VariableIdentifier(left), NodeRef::VariableIdentifier(right)) => {
+					!left.as_str().is_empty() && left.as_str() == right.as_str()
+				}
+				_ => false,
+			},
+			(Capture::Property(left), Capture::Property(right)) => left == right,
+			(Capture::Items(left), Capture::Items(right)) => left == right,
+			_ => false,
+		}
+	}
+}
+
+/// A node that matched a pattern
+#[derive(Debug, Clone)]
+pub struct Match<'a> {
+	pub node: NodeRef<'a>,
+	pub captures: HashMap<String, Capture<'a>>,
+}
+
+impl<'a> Match<'a> {
+	pub fn get_position(&self) -> Cow<'a, Span> {
+		self.node.get_position()
+	}
+}
+
+impl Pattern {
+	/// Parses a pattern. Patterns are parsed as a statement or declaration first, with expression statements
+	/// becoming expression patterns. If that fails they are parsed as an expression (for object literals etc)
+	pub fn from_string(pattern: &str, settings: ParseSettings) -> ParseResult<Self> {
+		let (source, cursors, metavariables) = source_with_cursors(pattern);
+		let item = StatementOrDeclaration::from_string(
+			source.clone(),
+			settings.clone(),
+			SourceId::NULL,
+			None,
+			cursors.clone(),
+		);
+		let (root, functions) = match item {
+			Ok(ParseOutput(
+				StatementOrDeclaration::Statement(Statement::Expression(MultipleExpression {
+					lhs: None,
+					rhs,
+				})),
+				state,
+			)) => (PatternRoot::Expression(Box::new(rhs)), state.function_extractor),
+			Ok(ParseOutput(item, state)) => {
+				(PatternRoot::StatementOrDeclaration(Box::new(item)), state.function_extractor)
+			}
+			Err(item_error) => {
+				match Expression::from_string(source, settings, SourceId::NULL, None, cursors) {
+					Ok(ParseOutput(expression, state)) => {
+						(PatternRoot::Expression(Box::new(expression)), state.function_extractor)
+					}
+					Err(_) => return Err(item_error),
+				}
+			}
+		};
+		Ok(Self { root, metavariables, functions })
+	}
+
+	pub fn metavariables(&self) -> &[Metavariable] {
+		&self.metavariables
+	}
+
+	/// Whether the pattern is for expressions (rather than statements or declarations)
+	pub fn is_expression_pattern(&self) -> bool {
+		matches!(self.root, PatternRoot::Expression(_))
+	}
+
+	/// Tests `node` against the pattern, returning the captures if it matches
+	pub fn matches<'a>(
+		&self,
+		node: NodeRef<'a>,
+		functions: &'a ExtractedFunctions,
+	) -> Option<HashMap<String, Capture<'a>>> {
+		let mut matcher = Matcher {
+			metavariables: &self.metavariables,
+			pattern_functions: &self.functions,
+			functions,
+			captures: HashMap::new(),
+		};
+		let matched = match (&self.root, node) {
+			(PatternRoot::Expression(pattern), NodeRef::Expression(expression)) => {
+				matcher.expression(pattern, expression)
+			}
+			(PatternRoot::StatementOrDeclaration(pattern), NodeRef::Statement(statement)) => {
+				match &**pattern {
+					StatementOrDeclaration::Statement(pattern) => {
+						matcher.statement(pattern, statement)
+					}
+					StatementOrDeclaration::Declaration(_) => false,
+				}
+			}
+			(PatternRoot::StatementOrDeclaration(pattern), NodeRef::Declaration(declaration)) => {
+				match &**pattern {
+					StatementOrDeclaration::Declaration(pattern) => {
+						matcher.declaration(pattern, declaration)
+					}
+					StatementOrDeclaration::Statement(Statement::Cursor(cursor_id, _)) => {
+						matcher.capture(cursor_id.0, Capture::Node(node))
+					}
+					StatementOrDeclaration::Statement(_) => false,
+				}
+			}
+			_ => false,
+		};
+		matched.then_some(matcher.captures)
+	}
+
+	/// Finds all nodes under (and including) `root` which match the pattern, in pre-order
+	pub fn find_all<'a>(
+		&self,
+		root: NodeRef<'a>,
+		functions: &'a ExtractedFunctions,
+	) -> Vec<Match<'a>> {
+		let mut matches = Vec::new();
+		root.walk(functions, &mut |node, _| {
+			if let Some(captures) = self.matches(node, functions) {
+				matches.push(Match { node, captures });
+			}
+		});
+		matches
+	}
+}
+
+fn variable_field_name(field: &VariableField<VariableFieldInSourceCode>) -> Option<&str> {
+	match field {
+		VariableField::Name(VariableIdentifier::Standard(name, ..)) => Some(name),
+		_ => None,
+	}
+}
+
+/// Holds state for a single attempt at matching. `'p` is the lifetime of the pattern, `'a` the
+/// lifetime of the AST being searched
+struct Matcher<'p, 'a> {
+	metavariables: &'p [Metavariable],
+	pattern_functions: &'p ExtractedFunctions,
+	functions: &'a ExtractedFunctions,
+	captures: HashMap<String, Capture<'a>>,
+}
+
+impl<'p, 'a> Matcher<'p, 'a> {
+	fn capture(&mut self, cursor_id: u8, capture: Capture<'a>) -> bool {
+		let Metavariable { name, .. } = &self.metavariables[cursor_id as usize];
+		if name == "_" {
+			return true;
+		}
+		match self.captures.entry(name.clone()) {
+			Entry::Occupied(existing) => existing.get().syntactically_equal(&capture),
+			Entry::Vacant(entry) => {
+				entry.insert(capture);
+				true
+			}
+		}
+	}
+
+	fn is_variadic(&self, expression: &Expression) -> bool {
+		matches!(expression, Expression::Cursor { cursor_id, .. } if self.metavariables[cursor_id.0 as usize].variadic)
+	}
+
+	fn expression(&mut self, pattern: &'p Expression, target: &'a Expression) -> bool {
+		if let Expression::Cursor { cursor_id, .. } = pattern {
+			return self.capture(cursor_id.0, Capture::Node(NodeRef::Expression(target)));
+		}
+
+		// Comments on the searched AST are skipped over
+		if let Expression::PrefixComment(_, inner, ..) | Expression::PostfixComment(inner, ..) =
+			target
+		{
+			if !matches!(pattern, Expression::PrefixComment(..) | Expression::PostfixComment(..)) {
+				return self.expression(pattern, inner);
+			}
+		}
+
+		match (pattern, target) {
+			(
+				Expression::ArrayLiteral(pattern_items, ..),
+				Expression::ArrayLiteral(target_items, ..),
+			) => self.spread_expressions(pattern_items, target_items),
+			(Expression::ObjectLiteral(pattern), Expression::ObjectLiteral(target)) => {
+				pattern.members.len() == target.members.len()
+					&& pattern
+						.members
+						.iter()
+						.zip(target.members.iter())
+						.all(|(pattern, target)| self.object_literal_member(pattern, target))
+			}
+			(Expression::TemplateLiteral(pattern), Expression::TemplateLiteral(target)) => {
+				let tags_match = match (&pattern.tag, &target.tag) {
+					(Some(pattern), Some(target)) => self.expression(pattern, target),
+					(None, None) => true,
+					_ => false,
+				};
+				tags_match
+					&& pattern.parts.len() == target.parts.len()
+					&& pattern.parts.iter().zip(target.parts.iter()).all(|parts| match parts {
+						(
+							TemplateLiteralPart::Static(pattern),
+							TemplateLiteralPart::Static(target),
+						) => pattern == target,
+						(
+							TemplateLiteralPart::Dynamic(pattern),
+							TemplateLiteralPart::Dynamic(target),
+						) => self.expression(pattern, target),
+						_ => false,
+					})
+			}
+			(
+				Expression::ParenthesizedExpression(pattern, ..),
+				Expression::ParenthesizedExpression(target, ..),
+			) => self.multiple_expression(pattern, target),
+			(
+				Expression::BinaryOperation { lhs, operator, rhs, .. },
+				Expression::BinaryOperation {
+					lhs: target_lhs,
+					operator: target_operator,
+					rhs: target_rhs,
+					..
+				},
+			) => {
+				operator == target_operator
+					&& self.expression(lhs, target_lhs)
+					&& self.expression(rhs, target_rhs)
+			}
+			(
+				Expression::UnaryOperation { operator, operand, .. },
+				Expression::UnaryOperation {
+					operator: target_operator,
+					operand: target_operand,
+					..
+				},
+			) => operator == target_operator && self.expression(operand, target_operand),
+			(
+				Expression::Assignment { lhs, rhs, .. },
+				Expression::Assignment { lhs: target_lhs, rhs: target_rhs, .. },
+			) => {
+				let lhs_matches = match (lhs, target_lhs) {
+					(
+						LHSOfAssignment::VariableOrPropertyAccess(pattern),
+						LHSOfAssignment::VariableOrPropertyAccess(target),
+					) => self.variable_or_property_access(pattern, target),
+					(pattern, target) => pattern == target,
+				};
+				lhs_matches && self.expression(rhs, target_rhs)
+			}
+			(
+				Expression::BinaryAssignmentOperation { lhs, operator, rhs, .. },
+				Expression::BinaryAssignmentOperation {
+					lhs: target_lhs,
+					operator: target_operator,
+					rhs: target_rhs,
+					..
+				},
+			) => {
+				operator == target_operator
+					&& self.variable_or_property_access(lhs, target_lhs)
+					&& self.expression(rhs, target_rhs)
+			}
+			(
+				Expression::UnaryPrefixAssignmentOperation { operator, operand, .. },
+				Expression::UnaryPrefixAssignmentOperation {
+					operator: target_operator,
+					operand: target_operand,
+					..
+				},
+			) => {
+				operator == target_operator
+					&& self.variable_or_property_access(operand, target_operand)
+			}
+			(
+				Expression::UnaryPostfixAssignmentOperation { operator, operand, .. },
+				Expression::UnaryPostfixAssignmentOperation {
+					operator: target_operator,
+					operand: target_operand,
+					..
+				},
+			) => {
+				operator == target_operator
+					&& self.variable_or_property_access(operand, target_operand)
+			}
+			(
+				Expression::DynamicImport { path, .. },
+				Expression::DynamicImport { path: target_path, .. },
+			) => self.expression(path, target_path),
+			(
+				Expression::PropertyAccess { parent, property, is_optional, .. },
+				Expression::PropertyAccess {
+					parent: target_parent,
+					property: target_property,
+					is_optional: target_is_optional,
+					..
+				},
+			) => {
+				is_optional == target_is_optional
+					&& self.expression(parent, target_parent)
+					&& self.property_reference(property, target_property)
+			}
+			(
+				Expression::Index { indexee, indexer, .. },
+				Expression::Index { indexee: target_indexee, indexer: target_indexer, .. },
+			) => {
+				self.expression(indexee, target_indexee)
+					&& self.multiple_expression(indexer, target_indexer)
+			}
+			(
+				Expression::FunctionCall { function, type_arguments, arguments, .. },
+				Expression::FunctionCall {
+					function: target_function,
+					type_arguments: target_type_arguments,
+					arguments: target_arguments,
+					..
+				},
+			) => {
+				// Type arguments are only checked if the pattern has them
+				(type_arguments.is_none() || type_arguments == target_type_arguments)
+					&& self.expression(function, target_function)
+					&& self.spread_expressions(arguments, target_arguments)
+			}
+			(
+				Expression::ConstructorCall { constructor, type_arguments, arguments, .. },
+				Expression::ConstructorCall {
+					constructor: target_constructor,
+					type_arguments: target_type_arguments,
+					arguments: target_arguments,
+					..
+				},
+			) => {
+				let arguments_match = match (arguments, target_arguments) {
+					(Some(arguments), Some(target_arguments)) => {
+						self.spread_expressions(arguments, target_arguments)
+					}
+					(None, None) => true,
+					(Some(arguments), None) => {
+						arguments.is_empty()
+							|| matches!(arguments.as_slice(), [SpreadExpression::NonSpread(argument)] if self.is_variadic(argument))
+					}
+					(None, Some(target_arguments)) => target_arguments.is_empty(),
+				};
+				(type_arguments.is_none() || type_arguments == target_type_arguments)
+					&& self.expression(constructor, target_constructor)
+					&& arguments_match
+			}
+			(
+				Expression::TernaryExpression { condition, truthy_result, falsy_result, .. },
+				Expression::TernaryExpression {
+					condition: target_condition,
+					truthy_result: target_truthy_result,
+					falsy_result: target_falsy_result,
+					..
+				},
+			) => {
+				self.expression(condition, target_condition)
+					&& self.expression(truthy_result, target_truthy_result)
+					&& self.expression(falsy_result, target_falsy_result)
+			}
+			(
+				Expression::ExtractedArrowFunction(pattern),
+				Expression::ExtractedArrowFunction(target),
+			) => {
+				let pattern = GetFunction::<ArrowFunctionBase>::get_function_ref(
+					self.pattern_functions,
+					pattern.0,
+				);
+				let target =
+					GetFunction::<ArrowFunctionBase>::get_function_ref(self.functions, target.0);
+				match (pattern, target) {
+					(Some(pattern), Some(target)) => {
+						pattern.header.is_some() == target.header.is_some()
+							&& self.parameters(&pattern.parameters, &target.parameters)
+							&& self.expression_or_block(&pattern.body, &target.body)
+					}
+					_ => false,
+				}
+			}
+			(
+				Expression::ExtractedExpressionFunction(pattern),
+				Expression::ExtractedExpressionFunction(target),
+			) => {
+				let pattern = GetFunction::<ExpressionFunctionBase>::get_function_ref(
+					self.pattern_functions,
+					pattern.0,
+				);
+				let target = GetFunction::<ExpressionFunctionBase>::get_function_ref(
+					self.functions,
+					target.0,
+				);
+				match (pattern, target) {
+					(Some(pattern), Some(target)) => {
+						pattern.header.is_async() == target.header.is_async()
+							&& pattern.header.is_generator() == target.header.is_generator()
+							&& pattern.name.as_ref().map(VariableIdentifier::as_str)
+								== target.name.as_ref().map(VariableIdentifier::as_str)
+							&& self.parameters(&pattern.parameters, &target.parameters)
+							&& self.block(&pattern.body, &target.body)
+					}
+					_ => false,
+				}
+			}
+			(pattern, target) => pattern == target,
+		}
+	}
+
+	fn multiple_expression(
+		&mut self,
+		pattern: &'p MultipleExpression,
+		target: &'a MultipleExpression,
+	) -> bool {
+		let lhs_matches = match (&pattern.lhs, &target.lhs) {
+			(Some(pattern), Some(target)) => self.multiple_expression(pattern, target),
+			(None, None) => true,
+			_ => false,
+		};
+		lhs_matches && self.expression(&pattern.rhs, &target.rhs)
+	}
+
+	fn spread_expressions(
+		&mut self,
+		pattern: &'p [SpreadExpression],
+		target: &'a [SpreadExpression],
+	) -> bool {
+		for (idx, pattern_item) in pattern.iter().enumerate() {
+			if let SpreadExpression::NonSpread(expression @ Expression::Cursor { cursor_id, .. }) =
+				pattern_item
+			{
+				if idx + 1 == pattern.len() && self.is_variadic(expression) {
+					let rest = target.get(idx..).unwrap_or_default();
+					return self.capture(cursor_id.0, Capture::Items(rest));
+				}
+			}
+			let matched = match (pattern_item, target.get(idx)) {
+				(
+					SpreadExpression::Spread(pattern, _),
+					Some(SpreadExpression::Spread(target, _)),
+				)
+				| (
+					SpreadExpression::NonSpread(pattern),
+					Some(SpreadExpression::NonSpread(target)),
+				) => self.expression(pattern, target),
+				(SpreadExpression::Empty, Some(SpreadExpression::Empty)) => true,
+				_ => false,
+			};
+			if !matched {
+				return false;
+			}
+		}
+		pattern.len() == target.len()
+	}
+
+	fn property_reference(
+		&mut self,
+		pattern: &'p PropertyReference,
+		target: &'a PropertyReference,
+	) -> bool {
+		match (pattern, target) {
+			(PropertyReference::Cursor(cursor_id), PropertyReference::Standard(name)) => {
+				self.capture(cursor_id.0, Capture::Property(name))
+			}
+			(pattern, target) => pattern == target,
+		}
+	}
+
+	fn property_key(&mut self, pattern: &'p PropertyKey, target: &'a PropertyKey) -> bool {
+		match (pattern, target) {
+			(PropertyKey::Ident(pattern, ..), PropertyKey::Ident(target, ..))
+			| (PropertyKey::StringLiteral(pattern, ..), PropertyKey::StringLiteral(target, ..)) => {
+				pattern == target
+			}
+			(PropertyKey::NumberLiteral(pattern, ..), PropertyKey::NumberLiteral(target, ..)) => {
+				pattern == target
+			}
+			(PropertyKey::Computed(pattern, ..), PropertyKey::Computed(target, ..)) => {
+				self.expression(pattern, target)
+			}
+			_ => false,
+		}
+	}
+
+	fn object_literal_member(
+		&mut self,
+		pattern: &'p ObjectLiteralMember,
+		target: &'a ObjectLiteralMember,
+	) -> bool {
+		match (pattern, target) {
+			(
+				ObjectLiteralMember::SpreadExpression(pattern, _),
+				ObjectLiteralMember::SpreadExpression(target, _),
+			) => self.expression(pattern, target),
+			(
+				ObjectLiteralMember::Shorthand(pattern, ..),
+				ObjectLiteralMember::Shorthand(target, ..),
+			) => pattern == target,
+			(
+				ObjectLiteralMember::Property(pattern_key, pattern_value, _),
+				ObjectLiteralMember::Property(target_key, target_value, _),
+			) => {
+				self.property_key(pattern_key.get_ast(), target_key.get_ast())
+					&& self.expression(pattern_value, target_value)
+			}
+			_ => false,
+		}
+	}
+
+	fn variable_or_property_access(
+		&mut self,
+		pattern: &'p VariableOrPropertyAccess,
+		target: &'a VariableOrPropertyAccess,
+	) -> bool {
+		match (pattern, target) {
+			(VariableOrPropertyAccess::Cursor { cursor_id, .. }, target) => {
+				self.capture(cursor_id.0, Capture::Node(NodeRef::VariableOrPropertyAccess(target)))
+			}
+			(
+				VariableOrPropertyAccess::PropertyAccess { parent, property, .. },
+				VariableOrPropertyAccess::PropertyAccess {
+					parent: target_parent,
+					property: target_property,
+					..
+				},
+			) => {
+				self.expression(parent, target_parent)
+					&& self.property_reference(property, target_property)
+			}
+			(
+				VariableOrPropertyAccess::Index { indexee, indexer, .. },
+				VariableOrPropertyAccess::Index {
+					indexee: target_indexee,
+					indexer: target_indexer,
+					..
+				},
+			) => {
+				self.expression(indexee, target_indexee)
+					&& self.multiple_expression(indexer, target_indexer)
+			}
+			(pattern, target) => pattern == target,
+		}
+	}
+
+	fn variable_field(
+		&mut self,
+		pattern: &'p VariableField<VariableFieldInSourceCode>,
+		target: &'a VariableField<VariableFieldInSourceCode>,
+	) -> bool {
+		match pattern {
+			VariableField::Name(VariableIdentifier::Cursor(cursor_id)) => {
+				self.capture(cursor_id.0, Capture::Node(NodeRef::VariableField(target)))
+			}
+			VariableField::Name(VariableIdentifier::Standard(name, ..)) => {
+				variable_field_name(target) == Some(name)
+			}
+			// TODO destructuring patterns
+			_ => false,
+		}
+	}
+
+	fn parameters(
+		&mut self,
+		pattern: &'p FunctionParameters,
+		target: &'a FunctionParameters,
+	) -> bool {
+		pattern.optional_parameters.is_empty()
+			&& target.optional_parameters.is_empty()
+			&& pattern.rest_parameter.is_none()
+			&& target.rest_parameter.is_none()
+			&& pattern.parameters.len() == target.parameters.len()
+			&& pattern.parameters.iter().zip(target.parameters.iter()).all(|(pattern, target)| {
+				self.variable_field(pattern.name.get_ast(), target.name.get_ast())
+			})
+	}
+
+	fn expression_or_block(
+		&mut self,
+		pattern: &'p ExpressionOrBlock,
+		target: &'a ExpressionOrBlock,
+	) -> bool {
+		match (pattern, target) {
+			(ExpressionOrBlock::Expression(pattern), ExpressionOrBlock::Expression(target)) => {
+				self.expression(pattern, target)
+			}
+			(ExpressionOrBlock::Block(pattern), ExpressionOrBlock::Block(target)) => {
+				self.block(pattern, target)
+			}
+			_ => false,
+		}
+	}
+
+	fn block(&mut self, pattern: &'p Block, target: &'a Block) -> bool {
+		pattern.0.len() == target.0.len()
+			&& pattern
+				.0
+				.iter()
+				.zip(target.0.iter())
+				.all(|(pattern, target)| self.statement_or_declaration(pattern, target))
+	}
+
+	fn block_or_single_statement(
+		&mut self,
+		pattern: &'p BlockOrSingleStatement,
+		target: &'a BlockOrSingleStatement,
+	) -> bool {
+		match (pattern, target) {
+			(BlockOrSingleStatement::Braced(pattern), BlockOrSingleStatement::Braced(target)) => {
+				self.block(pattern, target)
+			}
+			(
+				BlockOrSingleStatement::SingleStatement(pattern),
+				BlockOrSingleStatement::SingleStatement(target),
+			) => self.statement(pattern, target),
+			// Allows `if ($condition) $body` to match blocks
+			(
+				BlockOrSingleStatement::SingleStatement(pattern),
+				BlockOrSingleStatement::Braced(_),
+			) => {
+				if let Statement::Cursor(cursor_id, _) = &**pattern {
+					let node = match target {
+						BlockOrSingleStatement::Braced(block) => NodeRef::Block(block),
+						BlockOrSingleStatement::SingleStatement(statement) => {
+							NodeRef::Statement(statement)
+						}
+					};
+					self.capture(cursor_id.0, Capture::Node(node))
+				} else {
+					false
+				}
+			}
+			_ => false,
+		}
+	}
+
+	fn statement_or_declaration(
+		&mut self,
+		pattern: &'p StatementOrDeclaration,
+		target: &'a StatementOrDeclaration,
+	) -> bool {
+		match (pattern, target) {
+			(
+				StatementOrDeclaration::Statement(pattern),
+				StatementOrDeclaration::Statement(target),
+			) => self.statement(pattern, target),
+			(
+				StatementOrDeclaration::Declaration(pattern),
+				StatementOrDeclaration::Declaration(target),
+			) => self.declaration(pattern, target),
+			(
+				StatementOrDeclaration::Statement(Statement::Cursor(cursor_id, _)),
+				StatementOrDeclaration::Declaration(target),
+			) => self.capture(cursor_id.0, Capture::Node(NodeRef::Declaration(target))),
+			_ => false,
+		}
+	}
+
+	fn statement(&mut self, pattern: &'p Statement, target: &'a Statement) -> bool {
+		match (pattern, target) {
+			(Statement::Cursor(cursor_id, _), target) => {
+				self.capture(cursor_id.0, Capture::Node(NodeRef::Statement(target)))
+			}
+			(Statement::Expression(pattern), Statement::Expression(target)) => {
+				self.multiple_expression(pattern, target)
+			}
+			(Statement::Block(pattern), Statement::Block(target)) => self.block(pattern, target),
+			(Statement::IfStatement(pattern), Statement::IfStatement(target)) => {
+				let trailing_else_matches = match (&pattern.trailing_else, &target.trailing_else) {
+					(Some(pattern), Some(target)) => {
+						self.block_or_single_statement(&pattern.inner, &target.inner)
+					}
+					(None, None) => true,
+					_ => false,
+				};
+				self.multiple_expression(&pattern.condition, &target.condition)
+					&& self.block_or_single_statement(&pattern.inner, &target.inner)
+					&& pattern.else_conditions.len() == target.else_conditions.len()
+					&& pattern.else_conditions.iter().zip(target.else_conditions.iter()).all(
+						|(pattern, target)| {
+							self.multiple_expression(&pattern.condition, &target.condition)
+								&& self.block_or_single_statement(&pattern.inner, &target.inner)
+						},
+					) && trailing_else_matches
+			}
+			(Statement::WhileStatement(pattern), Statement::WhileStatement(target)) => {
+				self.expression(&pattern.condition, &target.condition)
+					&& self.block_or_single_statement(&pattern.inner, &target.inner)
+			}
+			(Statement::DoWhileStatement(pattern), Statement::DoWhileStatement(target)) => {
+				self.expression(&pattern.condition, &target.condition)
+					&& self.block_or_single_statement(&pattern.inner, &target.inner)
+			}
+			(Statement::Return(_, pattern), Statement::Return(_, target)) => {
+				match (pattern, target) {
+					(Some(pattern), Some(target)) => self.multiple_expression(pattern, target),
+					(None, None) => true,
+					_ => false,
+				}
+			}
+			(Statement::Throw(_, pattern), Statement::Throw(_, target)) => {
+				self.expression(pattern, target)
+			}
+			(
+				Statement::Labelled { name, statement, .. },
+				Statement::Labelled { name: target_name, statement: target_statement, .. },
+			) => name == target_name && self.statement(statement, target_statement),
+			(Statement::VarVariable(pattern), Statement::VarVariable(target)) => {
+				self.optional_declaration_items(&pattern.declarations, &target.declarations)
+			}
+			(pattern, target) => pattern == target,
+		}
+	}
+
+	fn declaration(&mut self, pattern: &'p Declaration, target: &'a Declaration) -> bool {
+		use crate::declarations::VariableDeclaration;
+		match (pattern, target) {
+			(
+				Declaration::Variable(VariableDeclaration::ConstDeclaration {
+					declarations, ..
+				}),
+				Declaration::Variable(VariableDeclaration::ConstDeclaration {
+					declarations: target_declarations,
+					..
+				}),
+			) => {
+				declarations.len() == target_declarations.len()
+					&& declarations.iter().zip(target_declarations.iter()).all(
+						|(pattern, target)| {
+							self.declaration_item_head(pattern, target)
+								&& self.expression(&pattern.expression, &target.expression)
+						},
+					)
+			}
+			(
+				Declaration::Variable(VariableDeclaration::LetDeclaration { declarations, .. }),
+				Declaration::Variable(VariableDeclaration::LetDeclaration {
+					declarations: target_declarations,
+					..
+				}),
+			) => self.optional_declaration_items(declarations, target_declarations),
+			(pattern, target) => pattern == target,
+		}
+	}
+
+	fn declaration_item_head<T: crate::declarations::variable::DeclarationExpression>(
+		&mut self,
+		pattern: &'p VariableDeclarationItem<T>,
+		target: &'a VariableDeclarationItem<T>,
+	) -> bool {
+		(pattern.type_reference.is_none() || pattern.type_reference == target.type_reference)
+			&& self.variable_field(pattern.name.get_ast(), target.name.get_ast())
+	}
+
+	fn optional_declaration_items(
+		&mut self,
+		pattern: &'p [VariableDeclarationItem<Option<Expression>>],
+		target: &'a [VariableDeclarationItem<Option<Expression>>],
+	) -> bool {
+		pattern.len() == target.len()
+			&& pattern.iter().zip(target.iter()).all(|(pattern, target)| {
+				self.declaration_item_head(pattern, target)
+					&& match (&pattern.expression, &target.expression) {
+						(Some(pattern), Some(target)) => self.expression(pattern, target),
+						(None, None) => true,
+						_ => false,
+					}
+			})
+	}
+}
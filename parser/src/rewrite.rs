@@ -0,0 +1,414 @@
+//! Structural search and replace using [crate::query] patterns.
+//!
+//! Rules are written as `pattern => replacement`, e.g. `$a.indexOf($b) !== -1 => $a.includes($b)`.
+//! [Rewriter] is a [VisitorMut] which replaces matching expressions in place and records an [Edit] for each
+//! replacement. [apply_edits] reprints only the replaced expressions, leaving the rest of the source as it was
+
+use std::{borrow::Cow, collections::HashMap, fmt};
+
+use temporary_annex::Annex;
+
+use crate::{
+	expressions::{
+		assignments::VariableOrPropertyAccess, ExpressionId, MultipleExpression, SpreadExpression,
+	},
+	extractor::ExtractedFunctions,
+	operators::{Operator, TERNARY_PRECEDENCE},
+	query::{source_with_cursors, Capture, Metavariable, Pattern},
+	ASTNode, Chain, Expression, NodeRef, ParseError, ParseOutput, ParseSettings, PropertyReference,
+	SourceId, Span, ToStringSettingsAndData, VisitSettings, Visitable, VisitorMut,
+	VisitorMutReceiver,
+};
+
+/// Replacement expressions are parsed at this offset so that the positions of template nodes never
+/// overlap with positions in the source being rewritten
+const TEMPLATE_OFFSET: usize = (u32::MAX / 2) as usize;
+
+#[derive(Debug)]
+pub enum RewriteRuleError {
+	/// No `=>` which splits a valid pattern and replacement
+	MissingArrow,
+	Pattern(ParseError),
+	Replacement(ParseError),
+	/// Only expressions can currently be rewritten
+	NotAnExpression,
+	/// Metavariable in the replacement which is not in the pattern
+	UnknownMetavariable(String),
+	/// Replacements cannot introduce functions as they would need to be added to the [ExtractedFunctions]
+	FunctionInReplacement,
+}
+
+impl fmt::Display for RewriteRuleError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			RewriteRuleError::MissingArrow => {
+				f.write_str("Expected rule of the form 'pattern => replacement'")
+			}
+			RewriteRuleError::Pattern(err) => write!(f, "Invalid pattern: {}", err.reason),
+			RewriteRuleError::Replacement(err) => {
+				write!(f, "Invalid replacement: {}", err.reason)
+			}
+			RewriteRuleError::NotAnExpression => {
+				f.write_str("Pattern and replacement must be expressions")
+			}
+			RewriteRuleError::UnknownMetavariable(name) => {
+				write!(f, "${name} is used in the replacement but not in the pattern")
+			}
+			RewriteRuleError::FunctionInReplacement => {
+				f.write_str("Replacements cannot contain function expressions")
+			}
+		}
+	}
+}
+
+/// A `pattern => replacement` rule
+#[derive(Debug)]
+pub struct RewriteRule {
+	pattern: Pattern,
+	replacement: Expression,
+	replacement_metavariables: Vec<Metavariable>,
+}
+
+impl RewriteRule {
+	pub fn from_string(rule: &str, settings: ParseSettings) -> Result<Self, RewriteRuleError> {
+		let mut last_error = RewriteRuleError::MissingArrow;
+		// Patterns may contain arrow functions, so try each `=>` until one splits the rule
+		for (idx, _) in rule.match_indices("=>") {
+			let (pattern, replacement) = (&rule[..idx], &rule[(idx + 2)..]);
+			if pattern.trim().is_empty() || replacement.trim().is_empty() {
+				continue;
+			}
+			match Self::from_parts(pattern, replacement, settings.clone()) {
+				Ok(rule) => return Ok(rule),
+				Err(err) => {
+					last_error = err;
+				}
+			}
+		}
+		Err(last_error)
+	}
+
+	pub fn from_parts(
+		pattern: &str,
+		replacement: &str,
+		settings: ParseSettings,
+	) -> Result<Self, RewriteRuleError> {
+		let pattern =
+			Pattern::from_string(pattern, settings.clone()).map_err(RewriteRuleError::Pattern)?;
+		if !pattern.is_expression_pattern() {
+			return Err(RewriteRuleError::NotAnExpression);
+		}
+
+		let (source, cursors, replacement_metavariables) = source_with_cursors(replacement);
+		let ParseOutput(replacement, state) = Expression::from_string(
+			source,
+			settings,
+			SourceId::NULL,
+			Some(TEMPLATE_OFFSET),
+			cursors,
+		)
+		.map_err(RewriteRuleError::Replacement)?;
+
+		if !state.function_extractor.is_empty() {
+			return Err(RewriteRuleError::FunctionInReplacement);
+		}
+		if let Some(unknown) = replacement_metavariables.iter().find(|metavariable| {
+			metavariable.name == "_"
+				|| !pattern.metavariables().iter().any(|other| other.name == metavariable.name)
+		}) {
+			return Err(RewriteRuleError::UnknownMetavariable(unknown.name.clone()));
+		}
+
+		Ok(Self { pattern, replacement, replacement_metavariables })
+	}
+
+	pub fn pattern(&self) -> &Pattern {
+		&self.pattern
+	}
+
+	/// Builds the replacement for a match. Returns [None] if a capture cannot be used in its position in
+	/// the replacement
+	pub fn instantiate(
+		&self,
+		captures: &HashMap<String, Capture>,
+		functions: &mut ExtractedFunctions,
+	) -> Option<Expression> {
+		let substitutions = substitutions(captures)?;
+		self.instantiate_with(substitutions, functions)
+	}
+
+	fn instantiate_with(
+		&self,
+		substitutions: HashMap<String, Substitution>,
+		functions: &mut ExtractedFunctions,
+	) -> Option<Expression> {
+		let mut substitutor = Substitutor {
+			metavariables: &self.replacement_metavariables,
+			substitutions,
+			valid: true,
+		};
+		let mut replacement = self.replacement.clone();
+		let mut chain = Chain::new();
+		Visitable::visit_mut(
+			&mut replacement,
+			&mut substitutor,
+			&mut (),
+			&VisitSettings::default(),
+			functions,
+			&mut Annex::new(&mut chain),
+		);
+		substitutor.valid.then_some(replacement)
+	}
+}
+
+/// Owned version of [Capture], so that the matched AST can be modified
+enum Substitution {
+	Expression(Box<Expression>),
+	Property(String),
+	Items(Vec<SpreadExpression>),
+}
+
+fn substitutions(captures: &HashMap<String, Capture>) -> Option<HashMap<String, Substitution>> {
+	captures
+		.iter()
+		.map(|(name, capture)| {
+			let substitution = match capture {
+				Capture::Node(NodeRef::Expression(expression)) => {
+					Substitution::Expression(Box::new((*expression).clone()))
+				}
+				Capture::Node(NodeRef::VariableOrPropertyAccess(access)) => {
+					Substitution::Expression(Box::new(
+						VariableOrPropertyAccess::clone(access).into(),
+					))
+				}
+				Capture::Node(_) => return None,
+				Capture::Property(property) => Substitution::Property((*property).to_owned()),
+				Capture::Items(items) => Substitution::Items(items.to_vec()),
+			};
+			Some((name.clone(), substitution))
+		})
+		.collect()
+}
+
+/// Replaces cursors in a replacement with captures
+struct Substitutor<'a> {
+	metavariables: &'a [Metavariable],
+	substitutions: HashMap<String, Substitution>,
+	/// Set to false if a capture cannot be used in its position
+	valid: bool,
+}
+
+impl<'a> Substitutor<'a> {
+	fn get(&self, cursor_id: u8) -> Option<&Substitution> {
+		self.substitutions.get(&self.metavariables[cursor_id as usize].name)
+	}
+
+	/// The printer does not add parentheses, so captures which bind less tightly than their position in
+	/// the replacement are wrapped
+	fn parenthesize(&self, expression: &mut Expression, min_precedence: u8) {
+		if let Expression::Cursor { cursor_id, .. } = expression {
+			if let Some(Substitution::Expression(substitution)) = self.get(cursor_id.0) {
+				if substitution.get_precedence() < min_precedence {
+					*expression = Expression::ParenthesizedExpression(
+						Box::new(MultipleExpression { lhs: None, rhs: (**substitution).clone() }),
+						substitution.get_position().into_owned(),
+						ExpressionId::new(),
+					);
+				}
+			}
+		}
+	}
+
+	fn splice(&mut self, items: &mut Vec<SpreadExpression>) {
+		let variadic = items.iter().position(|item| {
+			matches!(item, SpreadExpression::NonSpread(Expression::Cursor { cursor_id, .. }) if self.metavariables[cursor_id.0 as usize].variadic)
+		});
+		if let Some(idx) = variadic {
+			let SpreadExpression::NonSpread(Expression::Cursor { cursor_id, .. }) = &items[idx]
+			else {
+				unreachable!()
+			};
+			match self.get(cursor_id.0) {
+				Some(Substitution::Items(substitution)) => {
+					let substitution = substitution.clone();
+					items.splice(idx..=idx, substitution);
+				}
+				_ => self.valid = false,
+			}
+		}
+	}
+}
+
+impl<'a> VisitorMutReceiver<()> for Substitutor<'a> {
+	fn visit_expression_mut(
+		&mut self,
+		expression: &mut Expression,
+		_data: &mut (),
+		_functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		let precedence = expression.get_precedence();
+		match expression {
+			Expression::Cursor { cursor_id, .. } => match self.get(cursor_id.0) {
+				Some(Substitution::Expression(substitution)) => {
+					*expression = (**substitution).clone();
+				}
+				Some(Substitution::Property(property)) => {
+					*expression = Expression::VariableReference(
+						property.clone(),
+						Span::NULL_SPAN,
+						ExpressionId::new(),
+					);
+				}
+				Some(Substitution::Items(_)) | None => self.valid = false,
+			},
+			Expression::BinaryOperation { lhs, operator, rhs, .. } => {
+				self.parenthesize(lhs, operator.precedence());
+				self.parenthesize(rhs, operator.precedence() + 1);
+			}
+			Expression::UnaryOperation { operator, operand, .. } => {
+				self.parenthesize(operand, operator.precedence());
+			}
+			Expression::TernaryExpression { condition, .. } => {
+				self.parenthesize(condition, TERNARY_PRECEDENCE + 1);
+			}
+			Expression::Index { indexee, .. } => {
+				self.parenthesize(indexee, precedence);
+			}
+			Expression::PropertyAccess { parent, property, .. } => {
+				self.parenthesize(parent, precedence);
+				if let PropertyReference::Cursor(cursor_id) = property {
+					let name = match self.get(cursor_id.0) {
+						Some(Substitution::Property(name)) => Some(name),
+						Some(Substitution::Expression(expression)) => match &**expression {
+							Expression::VariableReference(name, ..) => Some(name),
+							_ => None,
+						},
+						_ => None,
+					};
+					match name {
+						Some(name) => *property = PropertyReference::Standard(name.clone()),
+						None => self.valid = false,
+					}
+				}
+			}
+			Expression::FunctionCall { function: callee, arguments, .. }
+			| Expression::ConstructorCall {
+				constructor: callee, arguments: Some(arguments), ..
+			} => {
+				self.parenthesize(callee, precedence);
+				self.splice(arguments);
+			}
+			Expression::ArrayLiteral(items, ..) => {
+				self.splice(items);
+			}
+			_ => {}
+		}
+	}
+}
+
+/// A replaced expression
+#[derive(Debug, Clone)]
+pub struct Edit {
+	/// Position of the expression that was replaced
+	pub position: Span,
+	/// Precedence of the expression that was replaced, used to decide whether the replacement needs
+	/// parentheses
+	pub precedence: u8,
+	pub replacement: Expression,
+}
+
+/// Applies rules to expressions. Data is the list of replacements made. Matches are replaced outermost
+/// first and expressions inside a replacement are not rewritten again
+#[derive(Debug, Default)]
+pub struct Rewriter {
+	pub rules: Vec<RewriteRule>,
+}
+
+impl Rewriter {
+	pub fn new(rules: Vec<RewriteRule>) -> Self {
+		Self { rules }
+	}
+}
+
+impl VisitorMut<Expression, Vec<Edit>> for Rewriter {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		data: &mut Vec<Edit>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		let position = item.get_position().into_owned();
+		let in_replacement = position.start as usize >= TEMPLATE_OFFSET
+			|| data.iter().any(|edit| {
+				edit.position.start <= position.start && position.end <= edit.position.end
+			});
+		if in_replacement {
+			return;
+		}
+
+		for rule in self.rules.iter() {
+			let Some(captures) = rule.pattern.matches(NodeRef::Expression(item), functions) else {
+				continue;
+			};
+			// Captures are cloned here so that `item` can be replaced
+			let replacement = substitutions(&captures)
+				.and_then(|substitutions| rule.instantiate_with(substitutions, functions));
+
+			if let Some(replacement) = replacement {
+				data.push(Edit {
+					position,
+					precedence: item.get_precedence(),
+					replacement: replacement.clone(),
+				});
+				*item = replacement;
+				return;
+			}
+		}
+	}
+}
+
+/// Prints the replacement of an edit, with parentheses if required
+pub fn edit_to_string(edit: &Edit, settings: &ToStringSettingsAndData) -> String {
+	let mut buf = String::new();
+	let needs_parentheses = edit.replacement.get_precedence() < edit.precedence;
+	if needs_parentheses {
+		buf.push('(');
+	}
+	edit.replacement.to_string_from_buffer(&mut buf, settings, 0);
+	if needs_parentheses {
+		buf.push(')');
+	}
+	buf
+}
+
+/// Replaces the ranges of `source` covered by edits with the printed replacement. Edits for a different
+/// source or which overlap a previous edit are skipped
+pub fn apply_edits<'a>(
+	source: &'a str,
+	source_id: SourceId,
+	edits: &[Edit],
+	settings: &ToStringSettingsAndData,
+) -> Cow<'a, str> {
+	let mut edits =
+		edits.iter().filter(|edit| edit.position.source_id == source_id).collect::<Vec<_>>();
+	if edits.is_empty() {
+		return Cow::Borrowed(source);
+	}
+	edits.sort_by_key(|edit| edit.position.start);
+
+	let mut output = String::with_capacity(source.len());
+	let mut last = 0;
+	for edit in edits {
+		let (start, end) = (edit.position.start as usize, edit.position.end as usize);
+		if start < last {
+			continue;
+		}
+		output.push_str(&source[last..start]);
+		output.push_str(&edit_to_string(edit, settings));
+		last = end;
+	}
+	output.push_str(&source[last..]);
+	Cow::Owned(output)
+}
@@ -3,11 +3,14 @@ use std::borrow::Cow;
 use crate::{
 	block::BlockOrSingleStatement,
 	declarations::variable::{VariableDeclaration, VariableDeclarationKeyword},
-	ParseSettings, TSXKeyword, VariableField, VariableFieldInSourceCode, WithComment,
+	ParseError, ParseErrors, ParseSettings, TSXKeyword, VariableField, VariableFieldInSourceCode,
+	WithComment,
 };
 use visitable_derive::Visitable;
 
-use super::{ASTNode, Expression, ParseResult, Span, TSXToken, Token, TokenReader};
+use super::{
+	ASTNode, Expression, ParseResult, Span, TSXToken, Token, TokenReader, VarVariableStatement,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Visitable)]
 #[cfg_attr(feature = "self-rust-tokenize", derive(self_rust_tokenize::SelfRustTokenize))]
@@ -41,6 +44,10 @@ impl ASTNode for ForLoopStatement {
 		depth: u8,
 	) {
 		buf.push_str("for");
+		if let ForLoopCondition::ForOf { is_await: true, .. } = self.condition {
+			// A space is needed even when minifying
+			buf.push_str(" await");
+		}
 		settings.0.add_gap(buf);
 		self.condition.to_string_from_buffer(buf, settings, depth);
 		settings.0.add_gap(buf);
@@ -52,6 +59,7 @@ impl ASTNode for ForLoopStatement {
 #[cfg_attr(feature = "self-rust-tokenize", derive(self_rust_tokenize::SelfRustTokenize))]
 pub enum ForLoopStatementInitializer {
 	Statement(VariableDeclaration),
+	VarStatement(VarVariableStatement),
 	Expression(Expression),
 }
 
@@ -63,6 +71,8 @@ pub enum ForLoopCondition {
 		variable: WithComment<VariableField<VariableFieldInSourceCode>>,
 		// TODO box...?
 		of: Expression,
+		/// `for await (...)`
+		is_await: bool,
 	},
 	ForIn {
 		keyword: Option<VariableDeclarationKeyword>,
@@ -80,7 +90,7 @@ pub enum ForLoopCondition {
 impl ASTNode for ForLoopCondition {
 	fn get_position(&self) -> Cow<Span> {
 		match self {
-			ForLoopCondition::ForOf { keyword, variable, of: rhs }
+			ForLoopCondition::ForOf { keyword, variable, of: rhs, .. }
 			| ForLoopCondition::ForIn { keyword, variable, r#in: rhs } => Cow::Owned(
 				keyword
 					.as_ref()
@@ -93,6 +103,7 @@ impl ASTNode for ForLoopCondition {
 				let initializer_position = match initializer.as_ref().expect("TODO what about None")
 				{
 					ForLoopStatementInitializer::Statement(stmt) => stmt.get_position(),
+					ForLoopStatementInitializer::VarStatement(stmt) => stmt.get_position(),
 					ForLoopStatementInitializer::Expression(expr) => expr.get_position(),
 				};
 				Cow::Owned(
@@ -109,6 +120,10 @@ impl ASTNode for ForLoopCondition {
 		state: &mut crate::ParsingState,
 		settings: &ParseSettings,
 	) -> ParseResult<Self> {
+		let await_position = reader
+			.conditional_next(|tok| *tok == TSXToken::Keyword(TSXKeyword::Await))
+			.map(|Token(_, position)| position);
+		let is_await = await_position.is_some();
 		reader.expect_next(TSXToken::OpenParentheses)?;
 		// Figure out if after variable declaration there exists a "=", "in" or a "of"
 		let mut destructuring_depth = 0;
@@ -143,7 +158,7 @@ impl ASTNode for ForLoopCondition {
 					WithComment::<VariableField<_>>::from_reader(reader, state, settings)?;
 				reader.expect_next(TSXToken::Keyword(TSXKeyword::Of))?;
 				let of = Expression::from_reader(reader, state, settings)?;
-				Self::ForOf { variable, keyword, of }
+				Self::ForOf { variable, keyword, of, is_await }
 			}
 			Some(TSXToken::Keyword(TSXKeyword::In)) => {
 				let keyword = if let Some(token) =
@@ -162,8 +177,11 @@ impl ASTNode for ForLoopCondition {
 			}
 			_ => {
 				let peek = reader.peek();
-				let initializer = if let Some(Token(
-					TSXToken::Keyword(TSXKeyword::Const | TSXKeyword::Let | TSXKeyword::Var),
+				let initializer = if let Some(Token(TSXToken::Keyword(TSXKeyword::Var), _)) = peek {
+					let statement = VarVariableStatement::from_reader(reader, state, settings)?;
+					Some(ForLoopStatementInitializer::VarStatement(statement))
+				} else if let Some(Token(
+					TSXToken::Keyword(TSXKeyword::Const | TSXKeyword::Let),
 					_,
 				)) = peek
 				{
@@ -191,6 +209,15 @@ impl ASTNode for ForLoopCondition {
 				Self::Statements { initializer, condition, afterthought }
 			}
 		};
+		if let (Some(position), false) = (await_position, matches!(condition, Self::ForOf { .. })) {
+			return Err(ParseError::new(
+				ParseErrors::UnexpectedToken {
+					expected: vec![TSXToken::OpenParentheses],
+					found: TSXToken::Keyword(TSXKeyword::Await),
+				},
+				position,
+			));
+		}
 		reader.expect_next(TSXToken::CloseParentheses)?;
 		Ok(condition)
 	}
@@ -203,7 +230,7 @@ impl ASTNode for ForLoopCondition {
 	) {
 		buf.push('(');
 		match self {
-			Self::ForOf { keyword, variable, of } => {
+			Self::ForOf { keyword, variable, of, .. } => {
 				if let Some(keyword) = keyword {
 					buf.push_str(keyword.as_str());
 				}
@@ -227,6 +254,9 @@ impl ASTNode for ForLoopCondition {
 						ForLoopStatementInitializer::Statement(stmt) => {
 							stmt.to_string_from_buffer(buf, settings, depth)
 						}
+						ForLoopStatementInitializer::VarStatement(stmt) => {
+							stmt.to_string_from_buffer(buf, settings, depth)
+						}
 						ForLoopStatementInitializer::Expression(expr) => {
 							expr.to_string_from_buffer(buf, settings, depth);
 						}
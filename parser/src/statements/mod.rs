@@ -6,7 +6,6 @@ mod while_statement;
 
 use crate::{
 	declarations::variable::{declarations_to_string, VariableDeclarationItem},
-	expect_semi_colon,
 	tokens::token_as_identifier,
 	tsx_keywords,
 };
@@ -22,6 +21,7 @@ use crate::errors::parse_lexing_error;
 pub use for_statement::{ForLoopCondition, ForLoopStatement, ForLoopStatementInitializer};
 pub use if_statement::*;
 pub use switch_statement::{SwitchBranch, SwitchStatement};
+pub use try_catch_statement::TryCatchStatement;
 use visitable_derive::Visitable;
 pub use while_statement::{DoWhileStatement, WhileStatement};
 
@@ -43,6 +43,7 @@ pub enum Statement {
 	SwitchStatement(SwitchStatement),
 	WhileStatement(WhileStatement),
 	DoWhileStatement(DoWhileStatement),
+	TryCatchStatement(TryCatchStatement),
 	// Control flow
 	Return(Keyword<tsx_keywords::Return>, Option<MultipleExpression>),
 	// TODO maybe an actual label struct:
@@ -91,6 +92,7 @@ impl ASTNode for Statement {
 			Statement::SwitchStatement(ss) => ss.get_position(),
 			Statement::WhileStatement(ws) => ws.get_position(),
 			Statement::DoWhileStatement(dws) => dws.get_position(),
+			Statement::TryCatchStatement(tcs) => tcs.get_position(),
 			Statement::Block(blk) => blk.get_position(),
 		}
 	}
@@ -120,7 +122,6 @@ impl ASTNode for Statement {
 			}
 			TSXToken::Keyword(TSXKeyword::Var) => {
 				let stmt = VarVariableStatement::from_reader(reader, state, settings)?;
-				expect_semi_colon(reader)?;
 				Ok(Statement::VarVariable(stmt))
 			}
 			TSXToken::Keyword(TSXKeyword::Throw) => {
@@ -144,7 +145,7 @@ impl ASTNode for Statement {
 				DoWhileStatement::from_reader(reader, state, settings).map(Into::into)
 			}
 			TSXToken::Keyword(TSXKeyword::Try) => {
-				todo!()
+				TryCatchStatement::from_reader(reader, state, settings).map(Into::into)
 			}
 			TSXToken::OpenBrace => {
 				Block::from_reader(reader, state, settings).map(Statement::Block)
@@ -222,6 +223,7 @@ impl ASTNode for Statement {
 		settings: &crate::ToStringSettingsAndData,
 		depth: u8,
 	) {
+		crate::add_mapping(buf, &self.get_position());
 		match self {
 			Statement::Cursor(..) => {
 				if !settings.0.expect_cursors {
@@ -240,6 +242,7 @@ impl ASTNode for Statement {
 			Statement::SwitchStatement(ss) => ss.to_string_from_buffer(buf, settings, depth),
 			Statement::WhileStatement(ws) => ws.to_string_from_buffer(buf, settings, depth),
 			Statement::DoWhileStatement(dws) => dws.to_string_from_buffer(buf, settings, depth),
+			Statement::TryCatchStatement(tcs) => tcs.to_string_from_buffer(buf, settings, depth),
 			Statement::Comment(comment, _) => {
 				if settings.0.should_add_comment() {
 					buf.push_str("//");
@@ -300,21 +303,23 @@ impl Statement {
 		matches!(
 			self,
 			Statement::VarVariable(_)
+				| Statement::Debugger(_)
 				| Statement::Expression(_)
 				| Statement::DoWhileStatement(_)
 				| Statement::Continue(..)
 				| Statement::Break(..)
 				| Statement::Return(..)
 				| Statement::Throw(..)
+				| Statement::Cursor(..)
 		)
 	}
 }
 
-#[derive(Debug, PartialEq, Clone, Visitable)]
+#[derive(Debug, PartialEq, Eq, Clone, Visitable)]
 #[cfg_attr(feature = "self-rust-tokenize", derive(self_rust_tokenize::SelfRustTokenize))]
 pub struct VarVariableStatement {
-	keyword: Keyword<tsx_keywords::Var>,
-	declarations: Vec<VariableDeclarationItem<Option<Expression>>>,
+	pub keyword: Keyword<tsx_keywords::Var>,
+	pub declarations: Vec<VariableDeclarationItem<Option<Expression>>>,
 }
 
 impl ASTNode for VarVariableStatement {
@@ -351,6 +356,5 @@ impl ASTNode for VarVariableStatement {
 	) {
 		buf.push_str("var ");
 		declarations_to_string(&self.declarations, buf, settings, depth);
-		buf.push(';');
 	}
 }
@@ -1 +1,106 @@
+use std::borrow::Cow;
 
+use source_map::Span;
+use tokenizer_lib::Token;
+use visitable_derive::Visitable;
+
+use crate::{
+	ASTNode, Block, ParseError, ParseErrors, TSXKeyword, TSXToken, VariableField,
+	VariableFieldInSourceCode, WithComment,
+};
+
+/// `try { ... } catch (e) { ... } finally { ... }`. At least one of `catch` or `finally` is present
+#[derive(Debug, PartialEq, Eq, Clone, Visitable)]
+#[cfg_attr(feature = "self-rust-tokenize", derive(self_rust_tokenize::SelfRustTokenize))]
+pub struct TryCatchStatement {
+	pub try_inner: Block,
+	/// `None` for `catch { ... }`
+	pub exception_var: Option<WithComment<VariableField<VariableFieldInSourceCode>>>,
+	pub catch_inner: Option<Block>,
+	pub finally_inner: Option<Block>,
+	pub position: Span,
+}
+
+impl ASTNode for TryCatchStatement {
+	fn get_position(&self) -> Cow<'_, Span> {
+		Cow::Borrowed(&self.position)
+	}
+
+	fn from_reader(
+		reader: &mut impl tokenizer_lib::TokenReader<TSXToken, Span>,
+		state: &mut crate::ParsingState,
+		settings: &crate::ParseSettings,
+	) -> Result<Self, ParseError> {
+		let start_span = reader.expect_next(TSXToken::Keyword(TSXKeyword::Try))?;
+		let try_inner = Block::from_reader(reader, state, settings)?;
+		let mut position = start_span.union(&try_inner.get_position());
+
+		let (mut exception_var, mut catch_inner) = (None, None);
+		if let Some(Token(TSXToken::Keyword(TSXKeyword::Catch), _)) = reader.peek() {
+			reader.next();
+			if let Some(Token(TSXToken::OpenParentheses, _)) = reader.peek() {
+				reader.next();
+				exception_var =
+					Some(WithComment::<VariableField<_>>::from_reader(reader, state, settings)?);
+				reader.expect_next(TSXToken::CloseParentheses)?;
+			}
+			let block = Block::from_reader(reader, state, settings)?;
+			position = position.union(&block.get_position());
+			catch_inner = Some(block);
+		}
+
+		let mut finally_inner = None;
+		if let Some(Token(TSXToken::Keyword(TSXKeyword::Finally), _)) = reader.peek() {
+			reader.next();
+			let block = Block::from_reader(reader, state, settings)?;
+			position = position.union(&block.get_position());
+			finally_inner = Some(block);
+		}
+
+		if catch_inner.is_none() && finally_inner.is_none() {
+			let Token(token, position) =
+				reader.next().ok_or_else(crate::errors::parse_lexing_error)?;
+			return Err(ParseError::new(
+				ParseErrors::UnexpectedToken {
+					expected: vec![
+						TSXToken::Keyword(TSXKeyword::Catch),
+						TSXToken::Keyword(TSXKeyword::Finally),
+					],
+					found: token,
+				},
+				position,
+			));
+		}
+
+		Ok(Self { try_inner, exception_var, catch_inner, finally_inner, position })
+	}
+
+	fn to_string_from_buffer<T: source_map::ToString>(
+		&self,
+		buf: &mut T,
+		settings: &crate::ToStringSettingsAndData,
+		depth: u8,
+	) {
+		buf.push_str("try");
+		settings.0.add_gap(buf);
+		self.try_inner.to_string_from_buffer(buf, settings, depth + 1);
+		if let Some(catch_inner) = &self.catch_inner {
+			settings.0.add_gap(buf);
+			buf.push_str("catch");
+			settings.0.add_gap(buf);
+			if let Some(exception_var) = &self.exception_var {
+				buf.push('(');
+				exception_var.to_string_from_buffer(buf, settings, depth);
+				buf.push(')');
+				settings.0.add_gap(buf);
+			}
+			catch_inner.to_string_from_buffer(buf, settings, depth + 1);
+		}
+		if let Some(finally_inner) = &self.finally_inner {
+			settings.0.add_gap(buf);
+			buf.push_str("finally");
+			settings.0.add_gap(buf);
+			finally_inner.to_string_from_buffer(buf, settings, depth + 1);
+		}
+	}
+}
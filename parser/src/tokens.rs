@@ -62,6 +62,7 @@ use crate::ParseError;
     "?" => TSXToken::QuestionMark,
     "?:" => TSXToken::OptionalMember,
     "-?:" => TSXToken::NonOptionalMember,
+    "?." => TSXToken::OptionalChain,
     "??" => TSXToken::NullishCoalescing,
     "??=" => TSXToken::NullishCoalescingAssign,
     "!" => TSXToken::LogicalNot,
@@ -258,7 +259,7 @@ impl TSXToken {
 /// cannot convert token to string
 pub(crate) fn token_as_identifier(
 	token: Token<TSXToken, Span>,
-	at_location: &str,
+	at_location: &'static str,
 ) -> Result<(String, Span), ParseError> {
 	let Token(token_type, position) = token;
 	let name = match token_type {
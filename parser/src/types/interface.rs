@@ -344,7 +344,7 @@ impl ASTNode for InterfaceMember {
 										Token(token, position) => {
 											return Err(ParseError::new(
 												ParseErrors::UnexpectedToken {
-													expected: &[
+													expected: vec![
 														TSXToken::Colon,
 														TSXToken::OptionalMember,
 														TSXToken::NonOptionalMember,
@@ -373,7 +373,7 @@ impl ASTNode for InterfaceMember {
 								Token(token, position) => {
 									return Err(ParseError::new(
 										crate::ParseErrors::UnexpectedToken {
-											expected: &[
+											expected: vec![
 												TSXToken::Colon,
 												TSXToken::Keyword(TSXKeyword::In),
 											],
@@ -607,7 +607,7 @@ impl ASTNode for InterfaceMember {
 			}
 			Token(token, position) => Err(ParseError::new(
 				crate::ParseErrors::UnexpectedToken {
-					expected: &[
+					expected: vec![
 						TSXToken::OpenParentheses,
 						TSXToken::QuestionMark,
 						TSXToken::Colon,
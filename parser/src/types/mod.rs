@@ -46,20 +46,21 @@ impl self_rust_tokenize::SelfRustTokenize for TypeId {
 	}
 }
 
-// [See](https://www.typescriptlang.org/docs/handbook/2/classes.html#member-visibility)
-// #[derive(Debug, Clone, PartialEq, Eq)]
-// pub enum Visibility {
-// 	Private,
-// 	Public,
-// 	Protected,
-// }
+/// [See](https://www.typescriptlang.org/docs/handbook/2/classes.html#member-visibility)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "self-rust-tokenize", derive(self_rust_tokenize::SelfRustTokenize))]
+pub enum Visibility {
+	Private,
+	Public,
+	Protected,
+}
 
-// impl Visibility {
-// 	pub fn as_str(&self) -> &'static str {
-// 		match self {
-// 			Visibility::Private => "private ",
-// 			Visibility::Public => "public ",
-// 			Visibility::Protected => "protected ",
-// 		}
-// 	}
-// }
+impl Visibility {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Visibility::Private => "private ",
+			Visibility::Public => "public ",
+			Visibility::Protected => "protected ",
+		}
+	}
+}
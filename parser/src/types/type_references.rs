@@ -371,6 +371,32 @@ impl TypeReference {
 		state: &mut crate::ParsingState,
 		settings: &ParseSettings,
 		return_on_union_or_intersection: bool,
+	) -> ParseResult<Self> {
+		Self::from_reader_with_options(
+			reader,
+			state,
+			settings,
+			return_on_union_or_intersection,
+			true,
+		)
+	}
+
+	/// For the branches of `is` expressions (`number => ...`), where `=>` does not start an
+	/// implicit function literal
+	pub(crate) fn from_reader_before_arrow(
+		reader: &mut impl TokenReader<TSXToken, Span>,
+		state: &mut crate::ParsingState,
+		settings: &ParseSettings,
+	) -> ParseResult<Self> {
+		Self::from_reader_with_options(reader, state, settings, false, false)
+	}
+
+	fn from_reader_with_options(
+		reader: &mut impl TokenReader<TSXToken, Span>,
+		state: &mut crate::ParsingState,
+		settings: &ParseSettings,
+		return_on_union_or_intersection: bool,
+		implicit_function_literals: bool,
 	) -> ParseResult<Self> {
 		while let Some(Token(TSXToken::Comment(_) | TSXToken::MultiLineComment(_), _)) =
 			reader.peek()
@@ -386,6 +412,7 @@ impl TypeReference {
 			}
 			Token(TSXToken::SingleQuotedStringLiteral(content), pos)
 			| Token(TSXToken::DoubleQuotedStringLiteral(content), pos) => Self::StringLiteral(content, pos),
+			Token(TSXToken::Cursor(cursor_id), pos) => Self::Cursor(cursor_id.into_cursor(), pos),
 			Token(TSXToken::At, pos) => {
 				let decorator =
 					Decorator::from_reader_sub_at_symbol(reader, state, settings, pos.clone())?;
@@ -658,8 +685,13 @@ impl TypeReference {
 				let mut union_members = vec![reference];
 				while let Some(Token(TSXToken::BitwiseOr, _)) = reader.peek() {
 					reader.next();
-					union_members
-						.push(Self::from_reader_with_config(reader, state, settings, true)?);
+					union_members.push(Self::from_reader_with_options(
+						reader,
+						state,
+						settings,
+						true,
+						implicit_function_literals,
+					)?);
 				}
 				Ok(Self::Union(union_members))
 			}
@@ -670,12 +702,17 @@ impl TypeReference {
 				let mut intersection_members = vec![reference];
 				while let Some(Token(TSXToken::BitwiseAnd, _)) = reader.peek() {
 					reader.next();
-					intersection_members
-						.push(Self::from_reader_with_config(reader, state, settings, true)?);
+					intersection_members.push(Self::from_reader_with_options(
+						reader,
+						state,
+						settings,
+						true,
+						implicit_function_literals,
+					)?);
 				}
 				Ok(Self::Intersection(intersection_members))
 			}
-			Some(Token(TSXToken::Arrow, _)) => {
+			Some(Token(TSXToken::Arrow, _)) if implicit_function_literals => {
 				reader.next();
 				let return_type = Self::from_reader_with_config(reader, state, settings, true)?;
 				let position = reference.get_position().into_owned();
@@ -751,7 +788,7 @@ pub(crate) fn generic_arguments_from_reader_sub_open_angle(
 			Token(token, position) => {
 				return Err(ParseError::new(
 					crate::ParseErrors::UnexpectedToken {
-						expected: &[TSXToken::CloseChevron, TSXToken::Comma],
+						expected: vec![TSXToken::CloseChevron, TSXToken::Comma],
 						found: token,
 					},
 					position,
@@ -873,7 +910,7 @@ impl TypeReferenceFunctionParameters {
 					Token(token, position) => {
 						return Err(ParseError::new(
 							crate::ParseErrors::UnexpectedToken {
-								expected: &[TSXToken::Colon, TSXToken::OptionalMember],
+								expected: vec![TSXToken::Colon, TSXToken::OptionalMember],
 								found: token,
 							},
 							position,
@@ -421,7 +421,10 @@ impl<U: VariableFieldTypes> ASTNode for ObjectDestructuringField<U> {
 				} else {
 					let Token(token, pos) = reader.next().unwrap();
 					Err(ParseError::new(
-						ParseErrors::UnexpectedToken { expected: &[TSXToken::Colon], found: token },
+						ParseErrors::UnexpectedToken {
+							expected: vec![TSXToken::Colon],
+							found: token,
+						},
 						pos,
 					))
 				}
@@ -576,7 +579,7 @@ impl Visitable for WithComment<VariableField<VariableFieldInSourceCode>> {
 						chain,
 					);
 					match field {
-						ArrayDestructuringField::Spread(_, _id) => todo!(),
+						ArrayDestructuringField::Spread(_, _id) => {}
 						ArrayDestructuringField::None => {}
 						ArrayDestructuringField::Name(variable_field, expression) => {
 							variable_field.visit(visitors, data, settings, functions, chain);
@@ -636,7 +639,7 @@ impl Visitable for WithComment<VariableField<VariableFieldInSourceCode>> {
 						chain,
 					);
 					match field {
-						ArrayDestructuringField::Spread(_, _id) => todo!(),
+						ArrayDestructuringField::Spread(_, _id) => {}
 						ArrayDestructuringField::None => {}
 						ArrayDestructuringField::Name(variable_field, default_value) => {
 							variable_field.visit_mut(visitors, data, settings, functions, chain);
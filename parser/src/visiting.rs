@@ -307,8 +307,10 @@ mod structures {
 		UnderFunction(BlockId, VariableId),
 		UnderClassMethod(BlockId),
 		UnderClassConstructor(BlockId),
-		UnderObjectLiteralMethod,
+		UnderObjectLiteralMethod(BlockId),
 		UnderExpressionFunctionBlock(BlockId, ExpressionId),
+		/// Expression and statement functions
+		UnderGeneralFunction(BlockId),
 		UnderArrowFunction(Option<BlockId>),
 		UnderRhsOfOperation(ExpressionId),
 		UnderMatchArm(BlockId),
@@ -325,11 +327,12 @@ mod structures {
 				| ChainVariable::UnderClassConstructor(block_id)
 				| ChainVariable::UnderModule(block_id, _)
 				| ChainVariable::UnderArrowFunction(Some(block_id))
+				| ChainVariable::UnderObjectLiteralMethod(block_id)
+				| ChainVariable::UnderGeneralFunction(block_id)
 				| ChainVariable::UnderExpressionFunctionBlock(block_id, _) => *block_id,
 				ChainVariable::UnderMatchArm(block_id) => *block_id,
 				ChainVariable::UnderArrowFunction(None)
 				| ChainVariable::SingleStatementOrExpression => panic!(),
-				ChainVariable::UnderObjectLiteralMethod => todo!(),
 				ChainVariable::UnderRhsOfOperation(_) => todo!(),
 			}
 		}
@@ -402,6 +405,43 @@ mod structures {
 			self.0.last().unwrap().get_block_id()
 		}
 
+		/// The body of the innermost function being visited (or the module if not in a function).
+		/// Arrow functions with a block body are only included if `include_arrow_functions`, as
+		/// they share `this` and `arguments` with the function they are in
+		pub fn function_block_id(&self, include_arrow_functions: bool) -> Option<BlockId> {
+			self.0.iter().rev().find_map(|chain_variable| match chain_variable {
+				ChainVariable::UnderFunction(block_id, _)
+				| ChainVariable::UnderClassMethod(block_id)
+				| ChainVariable::UnderClassConstructor(block_id)
+				| ChainVariable::UnderObjectLiteralMethod(block_id)
+				| ChainVariable::UnderGeneralFunction(block_id)
+				| ChainVariable::UnderExpressionFunctionBlock(block_id, _)
+				| ChainVariable::UnderModule(block_id, _) => Some(*block_id),
+				ChainVariable::UnderArrowFunction(Some(block_id)) if include_arrow_functions => {
+					Some(*block_id)
+				}
+				_ => None,
+			})
+		}
+
+		/// Whether the innermost function being visited is an arrow function
+		pub fn in_arrow_function(&self) -> bool {
+			self.0
+				.iter()
+				.rev()
+				.find_map(|chain_variable| match chain_variable {
+					ChainVariable::UnderArrowFunction(_) => Some(true),
+					ChainVariable::UnderFunction(..)
+					| ChainVariable::UnderClassMethod(_)
+					| ChainVariable::UnderClassConstructor(_)
+					| ChainVariable::UnderObjectLiteralMethod(_)
+					| ChainVariable::UnderGeneralFunction(_)
+					| ChainVariable::UnderExpressionFunctionBlock(..) => Some(false),
+					_ => None,
+				})
+				.unwrap_or(false)
+		}
+
 		pub fn get_module(&self) -> SourceId {
 			if let ChainVariable::UnderModule(_, source_id) = self.0.first().unwrap() {
 				*source_id
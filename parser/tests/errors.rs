@@ -0,0 +1,46 @@
+use ezno_parser::{ASTNode, Module, ParseError, ParseErrorKind, ParseErrors, SourceId};
+
+fn parse_error(input: &str) -> ParseError {
+	let settings = Default::default();
+	Module::from_string(input.to_owned(), settings, SourceId::NULL, None, Vec::new()).unwrap_err()
+}
+
+#[test]
+fn closing_tag_label() {
+	let error = parse_error("const x = <div>hi</span>;");
+
+	assert_eq!(
+		error.kind,
+		ParseErrorKind::Parse(ParseErrors::ClosingTagDoesNotMatch {
+			expected: "div".to_owned(),
+			found: "span".to_owned()
+		})
+	);
+	assert_eq!(error.code(), "E0003");
+	assert_eq!(error.labels.len(), 1);
+	assert_eq!(error.labels[0].1, "Opening tag");
+}
+
+#[test]
+fn unclosed_bracket_label() {
+	let error = parse_error("const a = [1, 2;");
+
+	assert_eq!(error.kind, ParseErrorKind::Parse(ParseErrors::UnmatchedBrackets));
+	let (position, message) = &error.labels[0];
+	assert_eq!((position.start, position.end), (10, 11));
+	assert_eq!(message, "Unclosed bracket");
+}
+
+#[test]
+fn help() {
+	let error = parse_error("function x(a?: number = 2) {}");
+
+	assert_eq!(
+		error.kind,
+		ParseErrorKind::Parse(ParseErrors::FunctionParameterOptionalAndDefaultValue)
+	);
+	assert_eq!(
+		error.help(),
+		Some("parameters with a default value are already optional, remove the `?`")
+	);
+}
@@ -0,0 +1,88 @@
+use ezno_parser::{ASTNode, Module, NodeRef, ParseOutput, SourceId};
+
+#[test]
+fn walking_nodes() {
+	let input = r#"
+        const x = 2;
+        function y(a) {
+            if (a) {
+                console.log(a + x)
+            }
+        }
+        "#;
+
+	let ParseOutput(module, state) =
+		Module::from_string(input.to_owned(), Default::default(), SourceId::NULL, None, Vec::new())
+			.unwrap();
+
+	let mut kinds = Vec::new();
+	NodeRef::Module(&module).walk(&state.function_extractor, &mut |node, depth| {
+		kinds.push(format!("{}{}", " ".repeat(depth), node.kind()));
+	});
+
+	let expected = [
+		"Module",
+		" VariableDeclaration",
+		"  VariableDeclarationItem",
+		"   VariableField",
+		"   NumberLiteral",
+		" FunctionDeclaration",
+		"  Parameter",
+		"   VariableField",
+		"  Block",
+		"   IfStatement",
+		"    VariableReference",
+		"    Block",
+		"     ExpressionStatement",
+		"      FunctionCall",
+		"       PropertyAccess",
+		"        VariableReference",
+		"       BinaryOperation",
+		"        VariableReference",
+		"        VariableReference",
+	];
+	assert_eq!(kinds, expected);
+}
+
+#[test]
+fn parameter_names() {
+	let input = "function y(a, b?, c = 1, ...d) {}";
+
+	let ParseOutput(module, state) =
+		Module::from_string(input.to_owned(), Default::default(), SourceId::NULL, None, Vec::new())
+			.unwrap();
+
+	let names: Vec<_> = NodeRef::Module(&module)
+		.descendants(&state.function_extractor)
+		.into_iter()
+		.filter(|node| matches!(node.kind(), "VariableField" | "VariableIdentifier"))
+		.map(|node| {
+			let position = node.get_position();
+			&input[position.start as usize..position.end as usize]
+		})
+		.collect();
+	assert_eq!(names, ["a", "b", "c", "d"]);
+}
+
+#[test]
+fn node_positions() {
+	let input = "const x = call(1, 2);";
+
+	let ParseOutput(module, state) =
+		Module::from_string(input.to_owned(), Default::default(), SourceId::NULL, None, Vec::new())
+			.unwrap();
+
+	let call = NodeRef::Module(&module)
+		.descendants(&state.function_extractor)
+		.into_iter()
+		.find(|node| node.kind() == "FunctionCall")
+		.unwrap();
+
+	let position = call.get_position();
+	assert_eq!(&input[position.start as usize..position.end as usize], "call(1, 2)");
+	assert_eq!(call.children(&state.function_extractor).len(), 3);
+	assert_eq!(
+		module.get_position().into_owned(),
+		NodeRef::Module(&module).get_position().into_owned()
+	);
+}
@@ -0,0 +1,97 @@
+use ezno_parser::{
+	query::{Capture, Pattern},
+	ASTNode, Module, NodeRef, ParseOutput, SourceId,
+};
+
+fn find_matches(pattern: &str, input: &str) -> Vec<(String, Vec<(String, String)>)> {
+	let pattern = Pattern::from_string(pattern, Default::default()).unwrap();
+	let ParseOutput(module, state) =
+		Module::from_string(input.to_owned(), Default::default(), SourceId::NULL, None, Vec::new())
+			.unwrap();
+
+	let slice = |node: NodeRef| {
+		let position = node.get_position();
+		input[position.start as usize..position.end as usize].to_owned()
+	};
+
+	pattern
+		.find_all(NodeRef::Module(&module), &state.function_extractor)
+		.into_iter()
+		.map(|found| {
+			let mut captures = found
+				.captures
+				.iter()
+				.map(|(name, capture)| {
+					let value = match capture {
+						Capture::Node(node) => slice(*node),
+						Capture::Property(property) => property.to_string(),
+						Capture::Items(items) => items.len().to_string(),
+					};
+					(name.clone(), value)
+				})
+				.collect::<Vec<_>>();
+			captures.sort();
+			(slice(found.node), captures)
+		})
+		.collect()
+}
+
+#[test]
+fn expression_patterns() {
+	let input = r#"
+        const a = items.indexOf(x) !== -1;
+        const b = list.indexOf(y + 1) !== -1;
+        const c = list.indexOf(y) === -1;
+        "#;
+
+	let matches = find_matches("$a.indexOf($b) !== -1", input);
+	assert_eq!(
+		matches,
+		[
+			(
+				"items.indexOf(x) !== -1".to_owned(),
+				vec![("a".to_owned(), "items".to_owned()), ("b".to_owned(), "x".to_owned())]
+			),
+			(
+				"list.indexOf(y + 1) !== -1".to_owned(),
+				vec![("a".to_owned(), "list".to_owned()), ("b".to_owned(), "y + 1".to_owned())]
+			),
+		]
+	);
+}
+
+#[test]
+fn repeated_and_variadic_metavariables() {
+	let input = r#"
+        x = x + 1;
+        x = y + 1;
+        console.log(1, 2, 3);
+        console.warn();
+        "#;
+
+	let matches = find_matches("$a = $a + 1", input);
+	assert_eq!(matches.len(), 1);
+	assert_eq!(matches[0].0, "x = x + 1");
+
+	let matches = find_matches("console.$method($$$args)", input);
+	assert_eq!(
+		matches.iter().map(|(_, captures)| captures.clone()).collect::<Vec<_>>(),
+		[
+			vec![("args".to_owned(), "3".to_owned()), ("method".to_owned(), "log".to_owned())],
+			vec![("args".to_owned(), "0".to_owned()), ("method".to_owned(), "warn".to_owned())],
+		]
+	);
+}
+
+#[test]
+fn statement_patterns() {
+	let input = r#"
+        if (debug) { console.log(1) }
+        if (other) { return }
+        "#;
+
+	let matches = find_matches("if ($condition) $body", input);
+	assert_eq!(matches.len(), 2);
+	assert_eq!(matches[0].1[0], ("body".to_owned(), "{ console.log(1) }".to_owned()));
+	assert_eq!(matches[1].1[0], ("body".to_owned(), "{ return }".to_owned()));
+}
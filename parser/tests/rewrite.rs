@@ -0,0 +1,71 @@
+use ezno_parser::{
+	rewrite::{apply_edits, RewriteRule, Rewriter},
+	source_map::MapFileStore,
+	ASTNode, Module, ParseOutput, SourceId, ToStringSettingsAndData, VisitSettings, VisitorsMut,
+};
+
+fn rewrite(rules: &[&str], input: &str) -> String {
+	let rules = rules
+		.iter()
+		.map(|rule| RewriteRule::from_string(rule, Default::default()).unwrap())
+		.collect();
+
+	let mut fs = MapFileStore::default();
+	let source_id = SourceId::new(&mut fs, "input.js".into(), input.to_owned());
+	let ParseOutput(mut module, state) =
+		Module::from_string(input.to_owned(), Default::default(), source_id, None, Vec::new())
+			.unwrap();
+	let mut functions = state.function_extractor;
+
+	let mut visitors = VisitorsMut {
+		expression_visitors_mut: vec![Box::new(Rewriter::new(rules))],
+		..Default::default()
+	};
+	let mut edits = Vec::new();
+	module.visit_mut(&mut visitors, &mut edits, &mut functions, &VisitSettings::default());
+
+	let settings = ToStringSettingsAndData(Default::default(), functions);
+	apply_edits(input, source_id, &edits, &settings).into_owned()
+}
+
+#[test]
+fn rewrite_keeps_surrounding_source() {
+	let input = r#"
+        // Check membership
+        if (items.indexOf(x)   !== -1) {
+            found(  x  );
+        }
+        const y = [list.indexOf(a + 1) !== -1];
+        "#;
+
+	let output = rewrite(&["$a.indexOf($b) !== -1 => $a.includes($b)"], input);
+
+	let expected = r#"
+        // Check membership
+        if (items.includes(x)) {
+            found(  x  );
+        }
+        const y = [list.includes(a + 1)];
+        "#;
+	assert_eq!(output, expected);
+}
+
+#[test]
+fn rewrite_properties_and_variadic() {
+	let input = "console.log(a, b, c);\nconsole.warn();\n";
+	let output = rewrite(&["console.$method($$$args) => logger.$method(\"app\", $$$args)"], input);
+	assert_eq!(output, "logger.log(\"app\", a, b, c);\nlogger.warn(\"app\");\n");
+}
+
+#[test]
+fn rewrite_adds_parentheses() {
+	let input = "const x = double(a + b) * 3;\n";
+	let output = rewrite(&["double($a) => $a * 2"], input);
+	assert_eq!(output, "const x = ((a + b) * 2) * 3;\n");
+}
+
+#[test]
+fn invalid_rules() {
+	assert!(RewriteRule::from_string("$a + 1", Default::default()).is_err());
+	assert!(RewriteRule::from_string("$a + 1 => $b", Default::default()).is_err());
+}
@@ -1,4 +1,6 @@
-use ezno_parser::{ASTNode, Module, ParseOutput, SourceId, ToStringSettingsAndData};
+use ezno_parser::{
+	ASTNode, Expression, Module, ParseOutput, SourceId, ToStringSettings, ToStringSettingsAndData,
+};
 
 #[test]
 fn statements() {
@@ -33,3 +35,167 @@ for (let i = 0; i < 4; i++) {
 
 	assert_eq!(output, input);
 }
+
+#[test]
+fn dynamic_imports() {
+	let input = r#"
+import("./y.js");
+if (lazy) {
+    const module = await import("./z.js")
+}"#
+	.trim_start();
+
+	let ParseOutput(module, state) =
+		Module::from_string(input.to_owned(), Default::default(), SourceId::NULL, None, Vec::new())
+			.unwrap();
+
+	let output =
+		module.to_string(&ToStringSettingsAndData(Default::default(), state.function_extractor));
+
+	assert_eq!(output, input);
+}
+
+#[test]
+fn for_await() {
+	let input = "for await (const x of y) {\n    console.log(x)\n}";
+
+	let ParseOutput(module, state) =
+		Module::from_string(input.to_owned(), Default::default(), SourceId::NULL, None, Vec::new())
+			.unwrap();
+	let settings = ToStringSettingsAndData(Default::default(), state.function_extractor);
+	assert_eq!(module.to_string(&settings), input);
+
+	let settings = ToStringSettingsAndData(ToStringSettings::minified(), settings.1);
+	let minified = module.to_string(&settings);
+	assert_eq!(minified, "for await(const x of y){console.log(x)}");
+
+	// And the minified output parses back to the same statement
+	let ParseOutput(module, state) =
+		Module::from_string(minified.clone(), Default::default(), SourceId::NULL, None, Vec::new())
+			.unwrap();
+	let settings = ToStringSettingsAndData(ToStringSettings::minified(), state.function_extractor);
+	assert_eq!(module.to_string(&settings), minified);
+}
+
+#[test]
+fn var_declarations() {
+	// In the heads of loops and exported
+	let input = r#"
+export var a = 1, b;
+for (var i = 0, j = 4; i < j; i++) {
+    console.log(i)
+}
+for (var key in object) {
+    console.log(key)
+}
+for (var [a, b] of pairs) {
+    console.log(a, b)
+}"#
+	.trim_start();
+
+	let ParseOutput(module, state) =
+		Module::from_string(input.to_owned(), Default::default(), SourceId::NULL, None, Vec::new())
+			.unwrap();
+	let settings = ToStringSettingsAndData(Default::default(), state.function_extractor);
+	assert_eq!(module.to_string(&settings), input);
+}
+
+#[test]
+fn purity_annotations() {
+	let input = r#"
+const a = /*#__PURE__*/ create(1) + 2;
+function make() {
+    return /* @__PURE__ */ new Thing()
+}"#
+	.trim_start();
+
+	let ParseOutput(module, state) =
+		Module::from_string(input.to_owned(), Default::default(), SourceId::NULL, None, Vec::new())
+			.unwrap();
+	let settings = ToStringSettingsAndData(Default::default(), state.function_extractor);
+	assert_eq!(module.to_string(&settings), input);
+
+	// Kept without comments, and only the call is annotated
+	let settings = ToStringSettingsAndData(ToStringSettings::minified(), settings.1);
+	assert_eq!(
+		module.to_string(&settings),
+		"const a=/*#__PURE__*/create(1)+2;function make(){return /* @__PURE__ */new Thing()}"
+	);
+}
+
+#[test]
+fn is_expression_branches() {
+	// `=>` after a branch type does not start a function type
+	let input = r#"
+function kind(x) {
+    return is (x) {number => "n", string | boolean => "s", Date => 1}
+}"#
+	.trim_start();
+
+	let ParseOutput(module, state) =
+		Module::from_string(input.to_owned(), Default::default(), SourceId::NULL, None, Vec::new())
+			.unwrap();
+	let settings = ToStringSettingsAndData(Default::default(), state.function_extractor);
+	assert_eq!(module.to_string(&settings), input);
+}
+
+#[test]
+fn auto_accessors() {
+	// `accessor` is also a valid name for a field
+	let input = r#"
+class Counter {
+    accessor count = 0
+    static accessor "limit"
+    accessor [key] = 1
+    accessor = 2
+}"#
+	.trim_start();
+
+	let ParseOutput(module, state) =
+		Module::from_string(input.to_owned(), Default::default(), SourceId::NULL, None, Vec::new())
+			.unwrap();
+	let settings = ToStringSettingsAndData(Default::default(), state.function_extractor);
+	assert_eq!(module.to_string(&settings), input);
+
+	let method = "class Counter {\n    accessor count() {}\n}";
+	assert!(Module::from_string(
+		method.to_owned(),
+		Default::default(),
+		SourceId::NULL,
+		None,
+		Vec::new()
+	)
+	.is_err());
+}
+
+#[test]
+fn constructor_calls() {
+	let input = r#"
+function create(items) {
+    const year = new Date(2020, 1).getFullYear();
+    const value = new (factory())(...items);
+    return new Map
+}"#
+	.trim_start();
+
+	let ParseOutput(module, state) =
+		Module::from_string(input.to_owned(), Default::default(), SourceId::NULL, None, Vec::new())
+			.unwrap();
+	let settings = ToStringSettingsAndData(Default::default(), state.function_extractor);
+	assert_eq!(module.to_string(&settings), input);
+
+	// The arguments belong to the constructor call, rather than a call of its result
+	let expression = Expression::from_string(
+		"new Date(2020, 1)".to_owned(),
+		Default::default(),
+		SourceId::NULL,
+		None,
+		Vec::new(),
+	)
+	.unwrap()
+	.0;
+	assert!(
+		matches!(&expression, Expression::ConstructorCall { arguments: Some(arguments), .. } if arguments.len() == 2),
+		"{expression:?}"
+	);
+}
@@ -0,0 +1,1841 @@
+//! Concatenates the modules reachable from an entry point into a single output.
+//!
+//! Modules are placed one after another in the order they are evaluated (dependencies first).
+//! `import`s between them are removed and the imported names are replaced with the (possibly
+//! renamed) bindings of the module they come from. Top level names which collide across modules
+//! are renamed with a `$N` suffix. Modules which are only referenced through `import()` are given
+//! a namespace object.
+
+use std::{
+	cell::RefCell,
+	collections::{HashMap, HashSet},
+	path::{Path, PathBuf},
+	rc::Rc,
+	str::FromStr,
+};
+
+use parser::{
+	declarations::{
+		classes::{ClassConstructorBase, ClassFunctionBase, ClassMember},
+		export::Exportable,
+		variable::VariableDeclarationKeyword,
+		ClassDeclaration, ExportDeclaration, ImportPart, StatementFunctionBase,
+		VariableDeclaration, VariableDeclarationItem,
+	},
+	expressions::{
+		arrow_function::ArrowFunctionBase,
+		assignments::{LHSOfAssignment, VariableOrPropertyAccess},
+		object_literal::{ObjectLiteralMember, ObjectLiteralMethodBase},
+		ExpressionFunctionBase, ExpressionId,
+	},
+	extractor::{ExtractedFunctions, GetFunction},
+	source_map::{MapFileStore, StringWithSourceMap, ToString as _},
+	statements::{ForLoopCondition, ForLoopStatement, ForLoopStatementInitializer},
+	ASTNode, ArrayDestructuringField, BlockLikeMut, Chain, Declaration, Decorated, Expression,
+	ExpressionOrStatementPosition, FunctionBased, FunctionId, FunctionParameters, JSXElement,
+	Keyword, NodeRef, ObjectDestructuringField, OptionalOrWithDefaultValueParameter, ParseOutput,
+	ParseSettings, PropertyId, PropertyKey, SourceId, Span, Statement, StatementOrDeclaration,
+	ToStringSettingsAndData, TypeReference, VariableField, VariableFieldInSourceCode, VariableId,
+	VariableIdentifier, WithComment,
+};
+
+use crate::{
+	enums::ImportedEnums,
+	error_handling::{DiagnosticCode, ErrorWarningInfo, TempDiagnostic},
+	jsx::{self, JSXRuntime, RuntimeImports},
+	pipeline::{File, Pipeline},
+	resolver::{module_has_side_effects, Resolver},
+	source_maps::source_map_to_json,
+	statistics::{time_phase, ModuleStatistics},
+	temp::{self, BuildOutput, Output, ParsedModule, Transform},
+	tree_shaking::is_removable,
+};
+
+/// Module format of a bundle
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BundleFormat {
+	/// Imports of packages are kept and the exports of the entry point are exported
+	#[default]
+	Esm,
+	/// Wrapped in a function which is called immediately. Cannot import packages
+	Iife,
+}
+
+impl FromStr for BundleFormat {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"esm" => Ok(BundleFormat::Esm),
+			"iife" => Ok(BundleFormat::Iife),
+			s => Err(format!("Unknown bundle format '{s}', expected 'esm' or 'iife'")),
+		}
+	}
+}
+
+pub struct BundleSettings {
+	pub format: BundleFormat,
+	/// Variable the exports of the entry point are assigned to for [BundleFormat::Iife]
+	pub global_name: Option<String>,
+	/// Remove declarations which are not used. See [crate::tree_shaking]
+	pub tree_shaking: bool,
+}
+
+/// Bundles the entry point and every module in the project reachable from it through `import`s and
+/// `import()`s into one output. Packages are left as imports. `read_from_path` returns the content of other modules (or `None`
+/// if they do not exist).
+///
+/// If `output_path` is a directory the bundle is written to a file named after the entry point in
+/// it
+pub fn bundle(
+	entry_path: PathBuf,
+	entry_content: String,
+	resolver: &Resolver,
+	read_from_path: &impl Fn(&Path) -> Option<String>,
+	output_path: PathBuf,
+	pipeline: &mut Pipeline,
+	BundleSettings { format, global_name, tree_shaking }: BundleSettings,
+) -> (MapFileStore, Result<BuildOutput, Vec<TempDiagnostic>>) {
+	let output_path = if temp::is_directory(&output_path) {
+		let stem = entry_path.file_stem().unwrap_or_default();
+		output_path.join(stem).with_extension("js")
+	} else {
+		output_path
+	};
+	// Phases which are not for a single module
+	let mut bundle_statistics =
+		pipeline.settings.statistics.then(|| ModuleStatistics::new(output_path.clone(), 0));
+
+	let mut fs = MapFileStore::default();
+	let mut diagnostics = Vec::new();
+	let parsed_modules = temp::load_modules(
+		entry_path.clone(),
+		entry_content,
+		resolver,
+		read_from_path,
+		&pipeline.settings,
+		&mut fs,
+		&mut diagnostics,
+	);
+
+	if has_errors(&diagnostics) {
+		return (fs, Err(diagnostics));
+	}
+
+	// Before exports are removed from modules
+	let exported_enums = if pipeline.settings.transforms.contains(&Transform::InlineEnums) {
+		parsed_modules.iter().map(|module| crate::enums::exported(&module.module)).collect()
+	} else {
+		Vec::new()
+	};
+	let indexes = parsed_modules
+		.iter()
+		.enumerate()
+		.map(|(index, module)| (module.path.clone(), index))
+		.collect::<HashMap<_, _>>();
+	let mut modules = parsed_modules
+		.into_iter()
+		.map(|parsed| BundleModule::new(parsed, &indexes))
+		.collect::<Vec<_>>();
+
+	let (order, linker) = time_phase(&mut bundle_statistics, "linking", || {
+		let order = evaluation_order(&modules);
+		let linker = Linker::new(&modules, &order, &mut diagnostics);
+		(order, linker)
+	});
+
+	if format == BundleFormat::Iife {
+		for module in modules.iter() {
+			let externals = module.imports.iter().filter_map(|(_, import)| match &import.source {
+				ImportSource::External(specifier) => Some((specifier, &import.position)),
+				ImportSource::Internal(_) => None,
+			});
+			let side_effect_imports = module
+				.side_effect_imports
+				.iter()
+				.map(|(specifier, position)| (specifier, position));
+			for (specifier, position) in externals.chain(side_effect_imports) {
+				diagnostics.push(TempDiagnostic {
+					label: format!(
+						"Cannot import '{specifier}' in an IIFE bundle, use the 'esm' format instead"
+					),
+					position: position.clone(),
+					kind: ErrorWarningInfo::Error,
+					code: DiagnosticCode::UnsupportedBundleImport,
+					secondary_labels: Vec::new(),
+					notes: Vec::new(),
+				});
+			}
+		}
+	}
+
+	if has_errors(&diagnostics) {
+		return (fs, Err(diagnostics));
+	}
+
+	if tree_shaking {
+		time_phase(&mut bundle_statistics, "tree shaking", || {
+			let side_effects = modules
+				.iter()
+				.map(|module| module_has_side_effects(&module.parsed.path, read_from_path))
+				.collect::<Vec<_>>();
+			linker.tree_shake(&mut modules, &side_effects);
+		});
+	}
+
+	let renames =
+		(0..modules.len()).map(|index| linker.renames(&modules, index)).collect::<Vec<_>>();
+	let namespaces = (0..modules.len())
+		.filter_map(|index| linker.namespace(&modules, index))
+		.collect::<Vec<_>>();
+	let mut hoisted_imports = linker.hoisted_imports(&modules);
+
+	// So that the helpers of the automatic JSX runtime are only imported once
+	let runtime_imports = Rc::new(RefCell::new(RuntimeImports::default()));
+	for (module, renames) in modules.iter_mut().zip(renames) {
+		let imported_enums = module
+			.imports
+			.iter()
+			.filter_map(|(local, import)| match import.source {
+				ImportSource::Internal(index) => {
+					let members = exported_enums.get(index)?.get(&import.name)?;
+					Some((local.clone(), (import.name.clone(), members.clone())))
+				}
+				ImportSource::External(_) => None,
+			})
+			.collect::<ImportedEnums>();
+		let ParsedModule { path, module, functions, statistics, .. } = &mut module.parsed;
+		pipeline.before_file(&mut File { path, module, functions, diagnostics: &mut diagnostics });
+		for transform in pipeline.settings.transforms.iter() {
+			// After renaming, so that local names do not shadow the new names
+			if let Transform::MangleNames = transform {
+				continue;
+			}
+			let (name, mut passes) = pipeline.passes(transform, path);
+			match transform {
+				Transform::LowerJSX => {
+					passes =
+						vec![jsx::visitors(&pipeline.settings.jsx, Some(runtime_imports.clone()))];
+				}
+				Transform::InlineEnums => {
+					passes = vec![crate::enums::visitors(imported_enums.clone())];
+				}
+				_ => {}
+			}
+			time_phase(statistics, name, || {
+				for visitors in passes {
+					temp::run_pass(module, visitors, &mut diagnostics, functions);
+				}
+			});
+		}
+		let renamer = Renamer(Rc::new(RefCell::new(renames)));
+		let mut visitors_mut = parser::VisitorsMut {
+			expression_visitors_mut: vec![Box::new(renamer.clone())],
+			statement_visitors_mut: vec![Box::new(renamer.clone())],
+			jsx_element_visitors_mut: vec![Box::new(renamer.clone())],
+			block_visitors_mut: vec![Box::new(renamer)],
+			..Default::default()
+		};
+		time_phase(statistics, "renaming", || {
+			module.visit_mut(
+				&mut visitors_mut,
+				&mut diagnostics,
+				functions,
+				&parser::VisitSettings::default(),
+			);
+		});
+		if pipeline.settings.transforms.contains(&Transform::MangleNames) {
+			let (name, passes) = pipeline.passes(&Transform::MangleNames, path);
+			time_phase(statistics, name, || {
+				for visitors in passes {
+					temp::run_pass(module, visitors, &mut diagnostics, functions);
+				}
+			});
+		}
+		pipeline.after_file(&mut File { path, module, functions, diagnostics: &mut diagnostics });
+	}
+
+	let runtime_imports = runtime_imports.borrow();
+	if let (JSXRuntime::Automatic { import_source }, Some(position)) =
+		(&pipeline.settings.jsx, &runtime_imports.position)
+	{
+		if format == BundleFormat::Iife {
+			diagnostics.push(TempDiagnostic {
+				label: format!(
+					"Cannot import the '{import_source}' JSX runtime in an IIFE bundle, use the \
+					 'esm' format or the classic runtime instead"
+				),
+				position: position.clone(),
+				kind: ErrorWarningInfo::Error,
+				code: DiagnosticCode::UnsupportedBundleImport,
+				secondary_labels: Vec::new(),
+				notes: Vec::new(),
+			});
+			return (fs, Err(diagnostics));
+		}
+		let imports = runtime_imports.to_imports(import_source).into_iter();
+		hoisted_imports.extend(imports.map(|import| format!("{import};")));
+	}
+
+	let output_path = output_path.into_os_string().into_string().expect("Invalid path");
+
+	let mut buf = StringWithSourceMap::new();
+	let depth = match format {
+		BundleFormat::Esm => 0,
+		BundleFormat::Iife => 1,
+	};
+	let output_settings = &pipeline.settings.output_settings;
+	let pretty = output_settings.pretty;
+	let indent =
+		if pretty { output_settings.indent_with.repeat(depth as usize) } else { String::new() };
+	let new_line = |buf: &mut StringWithSourceMap| {
+		if pretty {
+			buf.push_new_line();
+		}
+	};
+
+	if format == BundleFormat::Iife {
+		if let Some(global_name) = &global_name {
+			buf.push_str(&format!("var {global_name} = "));
+		}
+		buf.push_str("(function () {");
+		new_line(&mut buf);
+	}
+
+	for import in hoisted_imports {
+		buf.push_str(&import);
+		new_line(&mut buf);
+	}
+
+	for namespace in namespaces {
+		buf.push_str(&indent);
+		buf.push_str(&namespace);
+		new_line(&mut buf);
+	}
+
+	let mut modules = modules.into_iter().map(|module| Some(module.parsed)).collect::<Vec<_>>();
+	let mut statistics = Vec::new();
+	for index in order.iter().copied() {
+		let Some(ParsedModule {
+			path, module, functions, statistics: mut module_statistics, ..
+		}) = modules[index].take()
+		else {
+			continue;
+		};
+		if !module.items.is_empty() {
+			time_phase(&mut module_statistics, "printing", || {
+				if pretty && output_settings.include_comments {
+					buf.push_str(&indent);
+					buf.push_str(&format!("// {}", path.display().to_string().replace('\\', "/")));
+					buf.push_new_line();
+				}
+				let settings = ToStringSettingsAndData(output_settings.clone(), functions);
+				module.to_string_from_buffer(&mut buf, &settings, depth);
+				if module.items.last().is_some_and(StatementOrDeclaration::requires_semi_colon) {
+					buf.push(';');
+				}
+				new_line(&mut buf);
+			});
+		}
+		statistics.extend(module_statistics);
+	}
+
+	let entry_exports = linker.entry_exports();
+	match format {
+		BundleFormat::Esm => {
+			if !entry_exports.is_empty() {
+				let exports = entry_exports
+					.iter()
+					.map(|(exported, name)| {
+						if exported == name {
+							name.clone()
+						} else {
+							format!("{name} as {exported}")
+						}
+					})
+					.collect::<Vec<_>>();
+				buf.push_str(&format!("export {{ {} }};", exports.join(", ")));
+				new_line(&mut buf);
+			}
+		}
+		BundleFormat::Iife => {
+			if global_name.is_some() {
+				buf.push_str(&indent);
+				buf.push_str(&format!("return {};", namespace_object(&entry_exports)));
+				new_line(&mut buf);
+			}
+			buf.push_str("})();");
+			new_line(&mut buf);
+		}
+	}
+
+	let (content, source_map, source_map_json) =
+		time_phase(&mut bundle_statistics, "source map", || {
+			let (content, source_map) = buf.build(&fs);
+			let source_map_json = source_map_to_json(&source_map, &fs, Path::new(&output_path));
+			(content, source_map, source_map_json)
+		});
+	if let Some(mut bundle_statistics) = bundle_statistics {
+		bundle_statistics.size = content.len();
+		statistics.push(bundle_statistics);
+	}
+	let output =
+		Output { output_path, content, mappings: source_map.mappings, source_map: source_map_json };
+
+	(
+		fs,
+		Ok(BuildOutput { outputs: vec![output], temp_warnings_and_infos: diagnostics, statistics }),
+	)
+}
+
+fn has_errors(diagnostics: &[TempDiagnostic]) -> bool {
+	diagnostics.iter().any(|diagnostic| matches!(diagnostic.kind, ErrorWarningInfo::Error))
+}
+
+/// A module with its `import` and `export` declarations taken out
+struct BundleModule {
+	parsed: ParsedModule,
+	/// Local names which are imported, in order of the imports
+	imports: Vec<(String, Import)>,
+	/// Exported name and the local name it refers to
+	exports: Vec<(String, String)>,
+	/// Exported interfaces and type aliases, which do not exist in the output
+	type_exports: HashSet<String>,
+	/// Modules in the bundle this module imports statically, in order of the imports
+	dependencies: Vec<usize>,
+	/// Packages imported only for their side effects
+	side_effect_imports: Vec<(String, Span)>,
+	scope: ModuleScope,
+}
+
+#[derive(Clone)]
+enum ImportSource {
+	Internal(usize),
+	External(String),
+}
+
+struct Import {
+	source: ImportSource,
+	/// The exported name, `default` for default imports
+	name: String,
+	position: Span,
+}
+
+impl BundleModule {
+	fn new(mut parsed: ParsedModule, indexes: &HashMap<PathBuf, usize>) -> Self {
+		let mut imports = Vec::new();
+		let mut exports = Vec::new();
+		let mut type_exports = HashSet::new();
+		let mut dependencies = Vec::new();
+		let mut side_effect_imports = Vec::new();
+
+		let stem = parsed.path.file_stem().unwrap_or_default().to_string_lossy();
+		let default_name = format!("{}_default", identifier_from(&stem));
+
+		let items = std::mem::take(&mut parsed.module.items);
+		for item in items {
+			let declaration = match item {
+				StatementOrDeclaration::Declaration(Declaration::Import(import)) => {
+					if import.only_type {
+						continue;
+					}
+					let source = match parsed.resolved.get(&import.from) {
+						Some(path) => {
+							let index = indexes[path];
+							dependencies.push(index);
+							ImportSource::Internal(index)
+						}
+						None => {
+							if import.default_import.is_none() && import.imports.is_none() {
+								side_effect_imports
+									.push((import.from.clone(), import.position.clone()));
+							}
+							ImportSource::External(import.from.clone())
+						}
+					};
+					if let Some(default_import) = import.default_import {
+						imports.push((
+							default_import,
+							Import {
+								source: source.clone(),
+								name: "default".to_owned(),
+								position: import.position.clone(),
+							},
+						));
+					}
+					for part in import.imports.into_iter().flatten() {
+						let (local, name, position) = match part {
+							ImportPart::Name(VariableIdentifier::Standard(name, _, position)) => {
+								(name.clone(), name, position)
+							}
+							ImportPart::NameWithAlias { name, alias, position, .. } => {
+								(alias, name, position)
+							}
+							ImportPart::Name(VariableIdentifier::Cursor(_))
+							| ImportPart::TypeOnly(..) => continue,
+						};
+						imports.push((local, Import { source: source.clone(), name, position }));
+					}
+					continue;
+				}
+				StatementOrDeclaration::Declaration(Declaration::Export(Decorated {
+					decorators,
+					on: ExportDeclaration::Variable { exported, .. },
+				})) => match exported {
+					Exportable::Variable(declaration) => {
+						let mut names = Vec::new();
+						variable_declaration_identifiers(&declaration, &mut names);
+						exports.extend(names.into_iter().map(|(name, _)| (name.clone(), name)));
+						Declaration::Variable(declaration)
+					}
+					Exportable::VarVariable(statement) => {
+						let mut names = Vec::new();
+						for declaration in statement.declarations.iter() {
+							variable_field_identifiers(declaration.name.get_ast(), &mut names);
+						}
+						exports.extend(names.into_iter().map(|(name, _)| (name.clone(), name)));
+						let statement =
+							StatementOrDeclaration::Statement(Statement::VarVariable(statement));
+						parsed.module.items.push(statement);
+						continue;
+					}
+					Exportable::Function(function) => {
+						if let VariableIdentifier::Standard(name, ..) = &function.name {
+							exports.push((name.clone(), name.clone()));
+						}
+						Declaration::Function(Decorated { decorators, on: function })
+					}
+					Exportable::Class(class) => {
+						if let VariableIdentifier::Standard(name, ..) = &class.name {
+							exports.push((name.clone(), name.clone()));
+						}
+						Declaration::Class(Decorated { decorators, on: class })
+					}
+					Exportable::Interface(interface) => {
+						type_exports.insert(interface.name.clone());
+						Declaration::Interface(Decorated { decorators, on: interface })
+					}
+					Exportable::TypeAlias(alias) => {
+						type_exports.insert(alias.type_name.name.clone());
+						Declaration::TypeAlias(alias)
+					}
+					Exportable::Enum(mut enum_declaration) => {
+						exports
+							.push((enum_declaration.name.clone(), enum_declaration.name.clone()));
+						// Importers reference the members through the object rather than inlining them
+						enum_declaration.is_constant = false;
+						Declaration::Enum(Decorated { decorators, on: enum_declaration })
+					}
+				},
+				StatementOrDeclaration::Declaration(Declaration::Export(Decorated {
+					on: ExportDeclaration::Default { expression, position },
+					..
+				})) => {
+					let name = default_export_name(&expression, &parsed.functions)
+						.unwrap_or_else(|| default_name.clone());
+					exports.push(("default".to_owned(), name.clone()));
+					Declaration::Variable(VariableDeclaration::ConstDeclaration {
+						keyword: Keyword::new(Span::NULL_SPAN),
+						declarations: vec![VariableDeclarationItem {
+							name: WithComment::None(VariableField::Name(
+								VariableIdentifier::Standard(name, VariableId::new(), position),
+							)),
+							type_reference: None,
+							expression: *expression,
+						}],
+					})
+				}
+				item => {
+					parsed.module.items.push(item);
+					continue;
+				}
+			};
+			parsed.module.items.push(StatementOrDeclaration::Declaration(declaration));
+		}
+
+		let scope = ModuleScope::new(
+			&parsed.module.items,
+			imports.iter().map(|(local, _)| local.clone()),
+			&parsed.functions,
+		);
+
+		Self { parsed, imports, exports, type_exports, dependencies, side_effect_imports, scope }
+	}
+
+	fn import(&self, local: &str) -> Option<&Import> {
+		self.imports.iter().find_map(|(name, import)| (name == local).then_some(import))
+	}
+
+	fn export(&self, exported: &str) -> Option<&str> {
+		self.exports.iter().find_map(|(name, local)| (name == exported).then_some(local.as_str()))
+	}
+}
+
+/// The name of a function or class used as the default export
+fn default_export_name(expression: &Expression, functions: &ExtractedFunctions) -> Option<String> {
+	let name = match expression {
+		Expression::ExpressionFunction(function) => function.name.as_ref(),
+		Expression::ExtractedExpressionFunction(function) => {
+			GetFunction::<ExpressionFunctionBase>::get_function_ref(functions, function.0)?
+				.name
+				.as_ref()
+		}
+		Expression::ClassExpression(class, _) => class.name.as_ref(),
+		_ => None,
+	};
+	match name {
+		Some(VariableIdentifier::Standard(name, ..)) => Some(name.clone()),
+		_ => None,
+	}
+}
+
+/// Replaces characters which cannot be in an identifier
+fn identifier_from(name: &str) -> String {
+	let mut identifier = name
+		.chars()
+		.map(|chr| if chr.is_alphanumeric() || chr == '_' || chr == '$' { chr } else { '_' })
+		.collect::<String>();
+	if identifier.is_empty() || identifier.starts_with(|chr: char| chr.is_ascii_digit()) {
+		identifier.insert(0, '_');
+	}
+	identifier
+}
+
+/// Modules in the order they are evaluated. Dependencies come before the module which imports them.
+/// Imports which form a cycle are skipped, so the module reached first in a cycle runs last.
+/// Modules only reachable through `import()` are evaluated after the entry point
+fn evaluation_order(modules: &[BundleModule]) -> Vec<usize> {
+	fn visit(index: usize, modules: &[BundleModule], visited: &mut [bool], order: &mut Vec<usize>) {
+		if visited[index] {
+			return;
+		}
+		visited[index] = true;
+		for dependency in modules[index].dependencies.iter().copied() {
+			visit(dependency, modules, visited, order);
+		}
+		order.push(index);
+	}
+
+	let mut visited = vec![false; modules.len()];
+	let mut order = Vec::with_capacity(modules.len());
+	for index in 0..modules.len() {
+		visit(index, modules, &mut visited, &mut order);
+	}
+	order
+}
+
+/// A value in the bundle which needs a unique name
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum Binding {
+	/// A top level name of a module
+	Local(usize, String),
+	/// The namespace object for a module which is imported with `import()`
+	Namespace(usize),
+	/// Specifier and exported name of an import of a package
+	External(String, String),
+}
+
+/// Resolves imports to bindings and gives every binding a name in the bundle
+struct Linker {
+	names: HashMap<Binding, String>,
+	/// Externals in the order they are first imported
+	externals: Vec<Binding>,
+	/// Modules which are imported with `import()`
+	namespaced: HashSet<usize>,
+	entry_exports: Vec<(String, Binding)>,
+}
+
+impl Linker {
+	fn new(
+		modules: &[BundleModule],
+		order: &[usize],
+		diagnostics: &mut Vec<TempDiagnostic>,
+	) -> Self {
+		// Names from outside the bundle (globals) must not be shadowed
+		let mut used = modules
+			.iter()
+			.flat_map(|module| module.scope.free.iter().cloned())
+			.collect::<HashSet<_>>();
+		let all_nested = modules
+			.iter()
+			.flat_map(|module| module.scope.nested.iter().cloned())
+			.collect::<HashSet<_>>();
+
+		let mut names = HashMap::new();
+		let mut externals = Vec::new();
+		let mut name = |binding: Binding, base: &str, names: &mut HashMap<Binding, String>| {
+			if names.contains_key(&binding) {
+				return;
+			}
+			let name = if used.contains(base) {
+				fresh_name(base, &used, &all_nested)
+			} else {
+				base.to_owned()
+			};
+			used.insert(name.clone());
+			names.insert(binding, name);
+		};
+
+		for index in order.iter().copied() {
+			for (local, import) in modules[index].imports.iter() {
+				if let ImportSource::External(specifier) = &import.source {
+					let binding = Binding::External(specifier.clone(), import.name.clone());
+					if !names.contains_key(&binding) {
+						externals.push(binding.clone());
+					}
+					name(binding, local, &mut names);
+				}
+			}
+		}
+
+		let mut namespaced = HashSet::new();
+		for module in modules.iter() {
+			for specifier in module.scope.dynamic_imports.iter() {
+				if let Some(path) = module.parsed.resolved.get(specifier) {
+					if let Some(index) =
+						modules.iter().position(|module| module.parsed.path == *path)
+					{
+						namespaced.insert(index);
+					}
+				}
+			}
+		}
+
+		for index in order.iter().copied() {
+			let module = &modules[index];
+			for binding in module.scope.bindings.iter() {
+				name(Binding::Local(index, binding.clone()), binding, &mut names);
+			}
+			if namespaced.contains(&index) {
+				let stem = module.parsed.path.file_stem().unwrap_or_default().to_string_lossy();
+				let base = format!("{}_namespace", identifier_from(&stem));
+				name(Binding::Namespace(index), &base, &mut names);
+			}
+		}
+
+		let mut linker = Self { names, externals, namespaced, entry_exports: Vec::new() };
+
+		// Report imports of names which are not exported
+		for module in modules.iter() {
+			for (_, import) in module.imports.iter() {
+				if let ImportSource::Internal(target) = import.source {
+					let target = &modules[target];
+					if target.export(&import.name).is_none()
+						&& !target.type_exports.contains(&import.name)
+					{
+						let label = if import.name == "default" {
+							format!("'{}' has no default export", target.parsed.path.display())
+						} else {
+							format!(
+								"'{}' does not export '{}'",
+								target.parsed.path.display(),
+								import.name
+							)
+						};
+						diagnostics.push(TempDiagnostic {
+							label,
+							position: import.position.clone(),
+							kind: ErrorWarningInfo::Error,
+							code: DiagnosticCode::MissingExport,
+							secondary_labels: Vec::new(),
+							notes: Vec::new(),
+						});
+					}
+				}
+			}
+		}
+
+		// A binding may have a different name than the one it was imported as, which can then be
+		// captured by a name declared in a function etc of the importing module. Those are renamed
+		// to names which are not declared anywhere
+		let mut captured = Vec::new();
+		for (index, module) in modules.iter().enumerate() {
+			for (name, _) in module.scope.occurrences.iter() {
+				if let Some(binding) = linker.resolve(modules, index, name) {
+					let final_name = &linker.names[&binding];
+					if final_name != name && module.scope.nested.contains(final_name) {
+						captured.push(binding);
+					}
+				}
+			}
+			for specifier in module.scope.dynamic_imports.iter() {
+				if let Some(binding) = linker.namespace_binding(modules, module, specifier) {
+					if module.scope.nested.contains(&linker.names[&binding]) {
+						captured.push(binding);
+					}
+				}
+			}
+		}
+		for binding in captured {
+			let base = linker.names[&binding].clone();
+			let name = fresh_name(&base, &used, &all_nested);
+			used.insert(name.clone());
+			linker.names.insert(binding, name);
+		}
+
+		let entry = &modules[0];
+		linker.entry_exports = entry
+			.exports
+			.iter()
+			.filter_map(|(exported, local)| {
+				Some((exported.clone(), linker.resolve(modules, 0, local)?))
+			})
+			.collect();
+
+		linker
+	}
+
+	/// The binding a top level name in a module refers to
+	fn resolve(&self, modules: &[BundleModule], index: usize, name: &str) -> Option<Binding> {
+		let mut index = index;
+		let mut name = name.to_owned();
+		// Follow imports until a declaration. Limited by the number of modules to not loop forever
+		for _ in 0..=modules.len() {
+			let module = &modules[index];
+			let Some(import) = module.import(&name) else {
+				return Some(Binding::Local(index, name));
+			};
+			match &import.source {
+				ImportSource::External(specifier) => {
+					return Some(Binding::External(specifier.clone(), import.name.clone()))
+				}
+				ImportSource::Internal(target) => {
+					name = modules[*target].export(&import.name)?.to_owned();
+					index = *target;
+				}
+			}
+		}
+		None
+	}
+
+	fn namespace_binding(
+		&self,
+		modules: &[BundleModule],
+		module: &BundleModule,
+		specifier: &str,
+	) -> Option<Binding> {
+		let path = module.parsed.resolved.get(specifier)?;
+		let index = modules.iter().position(|module| module.parsed.path == *path)?;
+		Some(Binding::Namespace(index))
+	}
+
+	/// What identifiers in a module are renamed to
+	fn renames(&self, modules: &[BundleModule], index: usize) -> Renames {
+		let module = &modules[index];
+		let names = module
+			.scope
+			.occurrences
+			.iter()
+			.filter_map(|(name, position)| {
+				let new_name = &self.names[&self.resolve(modules, index, name)?];
+				(new_name != name).then(|| ((position.start, name.clone()), new_name.clone()))
+			})
+			.collect();
+		let namespaces = module
+			.scope
+			.dynamic_imports
+			.iter()
+			.filter_map(|specifier| {
+				let binding = self.namespace_binding(modules, module, specifier)?;
+				Some((specifier.clone(), self.names[&binding].clone()))
+			})
+			.collect();
+		Renames { names, namespaces }
+	}
+
+	/// `import` declarations for packages, grouped by specifier
+	fn hoisted_imports(&self, modules: &[BundleModule]) -> Vec<String> {
+		let mut specifiers = Vec::<&str>::new();
+		for binding in self.externals.iter() {
+			if let Binding::External(specifier, _) = binding {
+				if !specifiers.contains(&specifier.as_str()) {
+					specifiers.push(specifier);
+				}
+			}
+		}
+		for (specifier, _) in modules.iter().flat_map(|module| module.side_effect_imports.iter()) {
+			if !specifiers.contains(&specifier.as_str()) {
+				specifiers.push(specifier);
+			}
+		}
+
+		specifiers
+			.into_iter()
+			.map(|specifier| {
+				let mut default = None;
+				let mut named = Vec::new();
+				for binding in self.externals.iter() {
+					let Binding::External(from, name) = binding else { continue };
+					if from != specifier {
+						continue;
+					}
+					let local = &self.names[binding];
+					if name == "default" {
+						default = Some(local.clone());
+					} else if name == local {
+						named.push(local.clone());
+					} else {
+						named.push(format!("{name} as {local}"));
+					}
+				}
+				let mut parts = Vec::new();
+				parts.extend(default);
+				if !named.is_empty() {
+					parts.push(format!("{{ {} }}", named.join(", ")));
+				}
+				if parts.is_empty() {
+					format!("import \"{specifier}\";")
+				} else {
+					format!("import {} from \"{specifier}\";", parts.join(", "))
+				}
+			})
+			.collect()
+	}
+
+	/// Declaration of the namespace object for a module imported with `import()`
+	fn namespace(&self, modules: &[BundleModule], index: usize) -> Option<String> {
+		if !self.namespaced.contains(&index) {
+			return None;
+		}
+		let exports = modules[index]
+			.exports
+			.iter()
+			.filter_map(|(exported, local)| {
+				let binding = self.resolve(modules, index, local)?;
+				Some((exported.clone(), self.names[&binding].clone()))
+			})
+			.collect::<Vec<_>>();
+		Some(format!(
+			"const {} = Object.freeze({});",
+			self.names[&Binding::Namespace(index)],
+			namespace_object(&exports)
+		))
+	}
+
+	/// Removes top level items which can be removed (see [is_removable]) and declare names which
+	/// are not used by the exports of the entry point, namespace objects or items which are kept.
+	/// Modules without `side_effects` are removed completely if none of their names are used
+	fn tree_shake(&self, modules: &mut [BundleModule], side_effects: &[bool]) {
+		let mut declared = HashMap::<Binding, Vec<(usize, usize)>>::new();
+		let mut items = Vec::new();
+		for (index, module) in modules.iter().enumerate() {
+			let ParsedModule { module: parsed, functions, .. } = &module.parsed;
+			let imports = module.imports.iter().map(|(local, _)| local.clone()).collect::<Vec<_>>();
+			let top_level = module
+				.scope
+				.bindings
+				.iter()
+				.chain(&imports)
+				.map(String::as_str)
+				.collect::<HashSet<_>>();
+			let is_bound = |name: &str| top_level.contains(name);
+
+			let mut module_items = Vec::new();
+			for (item_index, item) in parsed.items.iter().enumerate() {
+				let mut declarations = Vec::new();
+				lexical_declarations(std::slice::from_ref(item), functions, &mut declarations);
+				var_declarations(NodeRef::from(item), functions, &mut declarations);
+				for (name, _) in declarations {
+					declared
+						.entry(Binding::Local(index, name))
+						.or_default()
+						.push((index, item_index));
+				}
+
+				let scope = ModuleScope::new(
+					std::slice::from_ref(item),
+					imports.iter().cloned(),
+					functions,
+				);
+				let mut names = scope
+					.occurrences
+					.iter()
+					.map(|(name, _)| name.as_str())
+					.chain(scope.free.iter().map(String::as_str))
+					.collect::<HashSet<_>>();
+				// Elements reference the factory of the classic JSX runtime
+				if scope.jsx {
+					names.insert("React");
+				}
+				let references = names
+					.into_iter()
+					.filter(|name| is_bound(name))
+					.filter_map(|name| self.resolve(modules, index, name))
+					.collect::<Vec<_>>();
+				module_items.push((references, is_removable(item, functions, &is_bound)));
+			}
+			items.push(module_items);
+		}
+
+		// The entry point, modules imported with `import()` and modules with side effects are run
+		let mut queue = (0..modules.len())
+			.filter(|index| *index == 0 || side_effects[*index] || self.namespaced.contains(index))
+			.map(Used::Module)
+			.collect::<Vec<_>>();
+		queue.extend(self.entry_exports.iter().map(|(_, binding)| Used::Binding(binding.clone())));
+		for index in self.namespaced.iter().copied() {
+			let exports = modules[index].exports.iter();
+			queue.extend(
+				exports
+					.filter_map(|(_, local)| self.resolve(modules, index, local))
+					.map(Used::Binding),
+			);
+		}
+
+		let mut run = vec![false; modules.len()];
+		let mut kept = items.iter().map(|items| vec![false; items.len()]).collect::<Vec<_>>();
+		while let Some(used) = queue.pop() {
+			match used {
+				Used::Module(index) => {
+					if !std::mem::replace(&mut run[index], true) {
+						let items = items[index].iter().enumerate();
+						queue.extend(
+							items
+								.filter(|(_, (_, removable))| !removable)
+								.map(|(item, _)| Used::Item(index, item)),
+						);
+					}
+				}
+				Used::Binding(binding) => {
+					let items = declared.get(&binding).into_iter().flatten();
+					queue.extend(items.map(|(index, item)| Used::Item(*index, *item)));
+				}
+				Used::Item(index, item) => {
+					if !std::mem::replace(&mut kept[index][item], true) {
+						queue.push(Used::Module(index));
+						let (references, _) = &items[index][item];
+						queue.extend(references.iter().cloned().map(Used::Binding));
+					}
+				}
+			}
+		}
+
+		for (module, kept) in modules.iter_mut().zip(kept) {
+			let mut kept = kept.into_iter();
+			module.parsed.module.items.retain(|_| kept.next().unwrap());
+		}
+	}
+
+	/// Exported name and the name of the binding in the bundle
+	fn entry_exports(&self) -> Vec<(String, String)> {
+		self.entry_exports
+			.iter()
+			.map(|(exported, binding)| (exported.clone(), self.names[binding].clone()))
+			.collect()
+	}
+}
+
+/// Something found to be used by [Linker::tree_shake]
+enum Used {
+	Module(usize),
+	Binding(Binding),
+	/// Module and index of the item in it
+	Item(usize, usize),
+}
+
+/// `name$N` for the lowest `N` which is not used
+fn fresh_name(base: &str, used: &HashSet<String>, avoid: &HashSet<String>) -> String {
+	(1..)
+		.map(|n| format!("{base}${n}"))
+		.find(|name| !used.contains(name) && !avoid.contains(name))
+		.unwrap()
+}
+
+/// An object with getters for each export, so that it reflects later assignments
+fn namespace_object(exports: &[(String, String)]) -> String {
+	let mut object = String::from("{ __proto__: null");
+	for (exported, name) in exports {
+		object.push_str(&format!(", get {exported}() {{ return {name}; }}"));
+	}
+	object.push_str(" }");
+	object
+}
+
+/// How names are used in a module
+#[derive(Default)]
+pub(crate) struct ModuleScope {
+	/// Names declared at the top level, in order of declaration (excluding imports)
+	bindings: Vec<String>,
+	/// Declarations of and references to top level names (including imports)
+	pub(crate) occurrences: Vec<(String, Span)>,
+	/// Names which are referenced but not declared (globals)
+	free: HashSet<String>,
+	/// Names declared in scopes other than the top level
+	nested: HashSet<String>,
+	/// Specifiers of `import()`s
+	dynamic_imports: Vec<String>,
+	/// Whether there are JSX elements, which reference the JSX factory (e.g. `React`)
+	pub(crate) jsx: bool,
+}
+
+impl ModuleScope {
+	pub(crate) fn new(
+		items: &[StatementOrDeclaration],
+		imports: impl Iterator<Item = String>,
+		functions: &ExtractedFunctions,
+	) -> Self {
+		let mut declarations = Vec::new();
+		lexical_declarations(items, functions, &mut declarations);
+		for item in items.iter() {
+			var_declarations(NodeRef::from(item), functions, &mut declarations);
+		}
+
+		let mut top_level = imports.collect::<HashSet<_>>();
+		let mut bindings = Vec::new();
+		for (name, _) in declarations.iter() {
+			if top_level.insert(name.clone()) {
+				bindings.push(name.clone());
+			}
+		}
+
+		let mut analysis = ScopeAnalysis {
+			functions,
+			scopes: vec![top_level],
+			scope: ModuleScope { bindings, occurrences: declarations, ..Default::default() },
+		};
+		for item in items.iter() {
+			analysis.walk(NodeRef::from(item));
+		}
+		analysis.scope
+	}
+
+	/// Every name declared or referenced in the module
+	pub(crate) fn names(&self) -> HashSet<String> {
+		let occurrences = self.occurrences.iter().map(|(name, _)| name);
+		occurrences.chain(&self.bindings).chain(&self.free).chain(&self.nested).cloned().collect()
+	}
+}
+
+/// Walks a module keeping track of the names declared in each scope
+struct ScopeAnalysis<'a> {
+	functions: &'a ExtractedFunctions,
+	/// Names declared in each scope, the first being the top level of the module
+	scopes: Vec<HashSet<String>>,
+	scope: ModuleScope,
+}
+
+impl<'a> ScopeAnalysis<'a> {
+	fn reference(&mut self, name: &str, position: &Span) {
+		if self.scopes[1..].iter().any(|scope| scope.contains(name)) {
+			return;
+		}
+		if self.scopes[0].contains(name) {
+			self.scope.occurrences.push((name.to_owned(), position.clone()));
+		} else {
+			self.scope.free.insert(name.to_owned());
+		}
+	}
+
+	fn reference_extends(&mut self, extends: &Option<TypeReference>) {
+		if let Some(TypeReference::Name(name, position)) = extends {
+			self.reference(name, position);
+		}
+	}
+
+	/// Names assigned to by a destructuring assignment
+	fn reference_all(&mut self, identifiers: Vec<(String, Span)>) {
+		for (name, position) in identifiers {
+			self.reference(&name, &position);
+		}
+	}
+
+	fn walk(&mut self, node: NodeRef<'a>) {
+		let children = node.children(self.functions);
+
+		if let NodeRef::JSXElement(_) = node {
+			self.scope.jsx = true;
+		}
+
+		match node {
+			NodeRef::Expression(Expression::VariableReference(name, position, _))
+			| NodeRef::VariableOrPropertyAccess(VariableOrPropertyAccess::Variable(
+				name,
+				position,
+				_,
+			))
+			| NodeRef::ObjectLiteralMember(ObjectLiteralMember::Shorthand(name, position, ..)) => {
+				self.reference(name, position)
+			}
+			NodeRef::Expression(Expression::Assignment { lhs, .. }) => {
+				let mut identifiers = Vec::new();
+				match lhs {
+					LHSOfAssignment::ObjectDestructuring(fields, ..) => {
+						object_fields_identifiers(fields, &mut identifiers)
+					}
+					LHSOfAssignment::ArrayDestructuring(fields, ..) => {
+						array_fields_identifiers(fields, &mut identifiers)
+					}
+					LHSOfAssignment::VariableOrPropertyAccess(_) => {}
+				}
+				self.reference_all(identifiers);
+			}
+			NodeRef::Expression(Expression::DynamicImport { path, .. }) => {
+				if let Expression::StringLiteral(specifier, ..) = path.as_ref() {
+					self.scope.dynamic_imports.push(specifier.clone());
+				}
+			}
+			NodeRef::Expression(Expression::ClassExpression(class, _)) => {
+				self.reference_extends(&class.extends)
+			}
+			NodeRef::Declaration(Declaration::Class(class)) => {
+				self.reference_extends(&class.on.extends)
+			}
+			NodeRef::ClassDeclaration(class) => self.reference_extends(&class.extends),
+			NodeRef::JSXElement(element) if is_component_name(&element.tag_name) => {
+				self.reference(&element.tag_name, &element.position)
+			}
+			NodeRef::Statement(Statement::ForLoopStatement(for_statement)) => {
+				if let ForLoopCondition::ForOf { keyword: None, variable, .. }
+				| ForLoopCondition::ForIn { keyword: None, variable, .. } = &for_statement.condition
+				{
+					let mut identifiers = Vec::new();
+					variable_field_identifiers(variable.get_ast(), &mut identifiers);
+					self.reference_all(identifiers);
+				}
+			}
+			_ => {}
+		}
+
+		let scope = match node {
+			NodeRef::Block(block) => Some(lexical_names(&block.0, self.functions)),
+			NodeRef::Statement(Statement::Block(block)) => {
+				Some(lexical_names(&block.0, self.functions))
+			}
+			NodeRef::Statement(Statement::ForLoopStatement(for_statement)) => {
+				let mut declarations = Vec::new();
+				match &for_statement.condition {
+					ForLoopCondition::ForOf { keyword: Some(keyword), variable, .. }
+					| ForLoopCondition::ForIn { keyword: Some(keyword), variable, .. }
+						if !matches!(keyword, VariableDeclarationKeyword::Var(_)) =>
+					{
+						variable_field_identifiers(variable.get_ast(), &mut declarations)
+					}
+					ForLoopCondition::Statements {
+						initializer: Some(ForLoopStatementInitializer::Statement(declaration)),
+						..
+					} => variable_declaration_identifiers(declaration, &mut declarations),
+					_ => {}
+				}
+				Some(declarations.into_iter().map(|(name, _)| name).collect())
+			}
+			node if is_function(node) => {
+				let mut declarations = Vec::new();
+				for child in children.iter().copied() {
+					match child {
+						NodeRef::Parameter(parameter) => {
+							variable_field_identifiers(parameter.name.get_ast(), &mut declarations)
+						}
+						NodeRef::OptionalParameter(
+							OptionalOrWithDefaultValueParameter::Optional { name, .. },
+						)
+						| NodeRef::SpreadParameter(parser::SpreadParameter { name, .. }) => {
+							identifier(name, &mut declarations)
+						}
+						NodeRef::OptionalParameter(
+							OptionalOrWithDefaultValueParameter::WithDefaultValue { name, .. },
+						) => variable_field_identifiers(name.get_ast(), &mut declarations),
+						child => var_declarations(child, self.functions, &mut declarations),
+					}
+				}
+				if let Some(name) = function_expression_name(node, self.functions) {
+					identifier(name, &mut declarations);
+				}
+				Some(declarations.into_iter().map(|(name, _)| name).collect())
+			}
+			_ => None,
+		};
+
+		let has_scope = scope.is_some();
+		if let Some(scope) = scope {
+			self.scope.nested.extend(scope.iter().cloned());
+			self.scopes.push(scope);
+		}
+		for child in children {
+			self.walk(child);
+		}
+		if has_scope {
+			self.scopes.pop();
+		}
+	}
+}
+
+/// JSX tags starting with a capital letter refer to variables
+pub(crate) fn is_component_name(tag_name: &str) -> bool {
+	tag_name.starts_with(|chr: char| chr.is_uppercase())
+		&& tag_name.chars().all(|chr| chr.is_alphanumeric() || chr == '_' || chr == '$')
+}
+
+pub(crate) fn is_function(node: NodeRef) -> bool {
+	matches!(
+		node,
+		NodeRef::Expression(
+			Expression::ArrowFunction(_)
+				| Expression::ExpressionFunction(_)
+				| Expression::ExtractedArrowFunction(_)
+				| Expression::ExtractedExpressionFunction(_)
+		) | NodeRef::Declaration(Declaration::Function(_) | Declaration::ExtractedFunction(_))
+			| NodeRef::StatementFunction(_)
+			| NodeRef::ClassMember(ClassMember::Constructor(_) | ClassMember::Function(..))
+			| NodeRef::ObjectLiteralMember(ObjectLiteralMember::Method(_))
+	)
+}
+
+/// The name of a function expression, which is only in scope inside of it
+pub(crate) fn function_expression_name<'a>(
+	node: NodeRef<'a>,
+	functions: &'a ExtractedFunctions,
+) -> Option<&'a VariableIdentifier> {
+	match node {
+		NodeRef::Expression(Expression::ExpressionFunction(function)) => function.name.as_ref(),
+		NodeRef::Expression(Expression::ExtractedExpressionFunction(function)) => {
+			GetFunction::<ExpressionFunctionBase>::get_function_ref(functions, function.0)?
+				.name
+				.as_ref()
+		}
+		_ => None,
+	}
+}
+
+pub(crate) fn identifier(identifier: &VariableIdentifier, out: &mut Vec<(String, Span)>) {
+	if let VariableIdentifier::Standard(name, _, position) = identifier {
+		out.push((name.clone(), position.clone()));
+	}
+}
+
+/// Names (and their position) declared by a variable field
+pub(crate) fn variable_field_identifiers(
+	field: &VariableField<VariableFieldInSourceCode>,
+	out: &mut Vec<(String, Span)>,
+) {
+	match field {
+		VariableField::Name(name) => identifier(name, out),
+		VariableField::Array(fields, _) => array_fields_identifiers(fields, out),
+		VariableField::Object(fields, _) => object_fields_identifiers(fields, out),
+	}
+}
+
+pub(crate) fn array_fields_identifiers(
+	fields: &[ArrayDestructuringField<VariableFieldInSourceCode>],
+	out: &mut Vec<(String, Span)>,
+) {
+	for field in fields.iter() {
+		match field {
+			ArrayDestructuringField::Spread(_, name) => identifier(name, out),
+			ArrayDestructuringField::Name(field, _) => {
+				variable_field_identifiers(field.get_ast(), out)
+			}
+			ArrayDestructuringField::None => {}
+		}
+	}
+}
+
+pub(crate) fn object_fields_identifiers(
+	fields: &[WithComment<ObjectDestructuringField<VariableFieldInSourceCode>>],
+	out: &mut Vec<(String, Span)>,
+) {
+	for field in fields.iter() {
+		match field.get_ast() {
+			ObjectDestructuringField::Spread(_, name) | ObjectDestructuringField::Name(name, _) => {
+				identifier(name, out)
+			}
+			ObjectDestructuringField::Map { variable_name, .. } => {
+				variable_field_identifiers(variable_name.get_ast(), out)
+			}
+		}
+	}
+}
+
+pub(crate) fn variable_declaration_identifiers(
+	declaration: &VariableDeclaration,
+	out: &mut Vec<(String, Span)>,
+) {
+	match declaration {
+		VariableDeclaration::ConstDeclaration { declarations, .. } => {
+			for declaration in declarations.iter() {
+				variable_field_identifiers(declaration.name.get_ast(), out);
+			}
+		}
+		VariableDeclaration::LetDeclaration { declarations, .. } => {
+			for declaration in declarations.iter() {
+				variable_field_identifiers(declaration.name.get_ast(), out);
+			}
+		}
+	}
+}
+
+/// Names declared with `let`, `const`, `function` and `class` directly in a block
+pub(crate) fn lexical_declarations(
+	items: &[StatementOrDeclaration],
+	functions: &ExtractedFunctions,
+	out: &mut Vec<(String, Span)>,
+) {
+	for item in items.iter() {
+		let StatementOrDeclaration::Declaration(declaration) = item else { continue };
+		match declaration {
+			Declaration::Variable(declaration) => {
+				variable_declaration_identifiers(declaration, out)
+			}
+			Declaration::Function(function) => identifier(&function.on.name, out),
+			Declaration::ExtractedFunction(function) => {
+				if let Some(function) =
+					GetFunction::<StatementFunctionBase>::get_function_ref(functions, function.0)
+				{
+					identifier(&function.name, out);
+				}
+			}
+			Declaration::Class(class) => identifier(&class.on.name, out),
+			Declaration::Enum(enum_declaration) => {
+				out.push((enum_declaration.on.name.clone(), enum_declaration.on.position.clone()))
+			}
+			_ => {}
+		}
+	}
+}
+
+fn lexical_names(
+	items: &[StatementOrDeclaration],
+	functions: &ExtractedFunctions,
+) -> HashSet<String> {
+	let mut declarations = Vec::new();
+	lexical_declarations(items, functions, &mut declarations);
+	declarations.into_iter().map(|(name, _)| name).collect()
+}
+
+/// Names declared with `var` under a node, not including those in nested functions
+pub(crate) fn var_declarations<'a>(
+	node: NodeRef<'a>,
+	functions: &'a ExtractedFunctions,
+	out: &mut Vec<(String, Span)>,
+) {
+	match node {
+		NodeRef::Statement(Statement::VarVariable(statement))
+		| NodeRef::Declaration(Declaration::Export(Decorated {
+			on: ExportDeclaration::Variable { exported: Exportable::VarVariable(statement), .. },
+			..
+		}))
+		| NodeRef::Statement(Statement::ForLoopStatement(ForLoopStatement {
+			condition:
+				ForLoopCondition::Statements {
+					initializer: Some(ForLoopStatementInitializer::VarStatement(statement)),
+					..
+				},
+			..
+		})) => {
+			for declaration in statement.declarations.iter() {
+				variable_field_identifiers(declaration.name.get_ast(), out);
+			}
+			for child in node.children(functions) {
+				var_declarations(child, functions, out);
+			}
+		}
+		NodeRef::Statement(Statement::ForLoopStatement(ForLoopStatement {
+			condition:
+				ForLoopCondition::ForOf {
+					keyword: Some(VariableDeclarationKeyword::Var(_)),
+					variable,
+					..
+				}
+				| ForLoopCondition::ForIn {
+					keyword: Some(VariableDeclarationKeyword::Var(_)),
+					variable,
+					..
+				},
+			..
+		})) => {
+			variable_field_identifiers(variable.get_ast(), out);
+			for child in node.children(functions) {
+				var_declarations(child, functions, out);
+			}
+		}
+		node if is_function(node) => {}
+		node => {
+			for child in node.children(functions) {
+				var_declarations(child, functions, out);
+			}
+		}
+	}
+}
+
+/// Identifiers to rename in a module. Also used by [crate::minify::mangle]
+#[derive(Default)]
+pub(crate) struct Renames {
+	/// Start and name of the identifier to its new name. Names are included as lowered code can
+	/// reuse positions
+	pub(crate) names: HashMap<(u32, String), String>,
+	/// Specifiers of `import()`s of modules in the bundle and the namespace which replaces them
+	pub(crate) namespaces: HashMap<String, String>,
+}
+
+impl Renames {
+	fn get(&self, name: &str, position: &Span) -> Option<&String> {
+		if position.is_null() {
+			None
+		} else {
+			self.names.get(&(position.start, name.to_owned()))
+		}
+	}
+
+	fn rename(&self, name: &mut String, position: &Span) {
+		if let Some(new_name) = self.get(name, position) {
+			*name = new_name.clone();
+		}
+	}
+
+	fn rename_identifier(&self, identifier: &mut VariableIdentifier) {
+		if let VariableIdentifier::Standard(name, _, position) = identifier {
+			self.rename(name, position);
+		}
+	}
+
+	fn rename_access(&self, access: &mut VariableOrPropertyAccess) {
+		if let VariableOrPropertyAccess::Variable(name, position, _) = access {
+			self.rename(name, position);
+		}
+	}
+
+	fn rename_extends(&self, extends: &mut Option<TypeReference>) {
+		if let Some(TypeReference::Name(name, position)) = extends {
+			self.rename(name, position);
+		}
+	}
+
+	fn rename_variable_field(&self, field: &mut VariableField<VariableFieldInSourceCode>) {
+		match field {
+			VariableField::Name(name) => self.rename_identifier(name),
+			VariableField::Array(fields, _) => {
+				for field in fields.iter_mut() {
+					match field {
+						ArrayDestructuringField::Spread(_, name) => self.rename_identifier(name),
+						ArrayDestructuringField::Name(field, _) => {
+							self.rename_variable_field(field.get_ast_mut())
+						}
+						ArrayDestructuringField::None => {}
+					}
+				}
+			}
+			VariableField::Object(fields, _) => {
+				for field in fields.iter_mut() {
+					self.rename_object_field(field.get_ast_mut());
+				}
+			}
+		}
+	}
+
+	fn rename_object_field(&self, field: &mut ObjectDestructuringField<VariableFieldInSourceCode>) {
+		match field {
+			ObjectDestructuringField::Spread(_, name) => self.rename_identifier(name),
+			ObjectDestructuringField::Name(VariableIdentifier::Standard(name, _, position), _) => {
+				let Some(new_name) = self.get(name, position).cloned() else { return };
+				// `{ x }` becomes `{ x: x$1 }`
+				let placeholder = ObjectDestructuringField::Spread(
+					Span::NULL_SPAN,
+					VariableIdentifier::Standard(String::new(), VariableId::new(), Span::NULL_SPAN),
+				);
+				if let ObjectDestructuringField::Name(
+					VariableIdentifier::Standard(name, variable_id, position),
+					default_value,
+				) = std::mem::replace(field, placeholder)
+				{
+					*field = ObjectDestructuringField::Map {
+						from: PropertyKey::Ident(name, PropertyId::new(), position.clone()),
+						variable_name: WithComment::None(VariableField::Name(
+							VariableIdentifier::Standard(new_name, variable_id, position.clone()),
+						)),
+						default_value,
+						position,
+					};
+				}
+			}
+			ObjectDestructuringField::Name(VariableIdentifier::Cursor(_), _) => {}
+			ObjectDestructuringField::Map { variable_name, .. } => {
+				self.rename_variable_field(variable_name.get_ast_mut())
+			}
+		}
+	}
+
+	fn rename_variable_declaration(&self, declaration: &mut VariableDeclaration) {
+		match declaration {
+			VariableDeclaration::ConstDeclaration { declarations, .. } => {
+				for declaration in declarations.iter_mut() {
+					self.rename_variable_field(declaration.name.get_ast_mut());
+				}
+			}
+			VariableDeclaration::LetDeclaration { declarations, .. } => {
+				for declaration in declarations.iter_mut() {
+					self.rename_variable_field(declaration.name.get_ast_mut());
+				}
+			}
+		}
+	}
+
+	fn rename_parameters(&self, parameters: &mut FunctionParameters) {
+		for parameter in parameters.parameters.iter_mut() {
+			self.rename_variable_field(parameter.name.get_ast_mut());
+		}
+		for parameter in parameters.optional_parameters.iter_mut() {
+			match parameter {
+				OptionalOrWithDefaultValueParameter::Optional { name, .. } => {
+					self.rename_identifier(name)
+				}
+				OptionalOrWithDefaultValueParameter::WithDefaultValue { name, .. } => {
+					self.rename_variable_field(name.get_ast_mut())
+				}
+			}
+		}
+		if let Some(rest_parameter) = &mut parameters.rest_parameter {
+			self.rename_identifier(&mut rest_parameter.name);
+		}
+	}
+
+	/// Renames the parameters of an extracted function and (with `rename_name`) its name
+	fn rename_extracted<T: FunctionBased + 'static>(
+		&self,
+		id: FunctionId<T>,
+		functions: &mut ExtractedFunctions,
+		rename_name: impl FnOnce(&Self, &mut T::Name),
+	) where
+		ExtractedFunctions: GetFunction<T>,
+	{
+		let mut function = functions.get_function(id);
+		rename_name(self, &mut function.name);
+		self.rename_parameters(&mut function.parameters);
+		functions.insert_function(function);
+	}
+
+	/// The name of a class is renamed separately as it depends on the position
+	fn rename_class<T: ExpressionOrStatementPosition>(
+		&self,
+		class: &mut ClassDeclaration<T>,
+		functions: &mut ExtractedFunctions,
+	) {
+		self.rename_extends(&mut class.extends);
+		for member in class.members.iter() {
+			match &member.on {
+				ClassMember::Constructor(constructor) => self
+					.rename_extracted::<ClassConstructorBase>(constructor.0, functions, |_, _| {}),
+				ClassMember::Function(_, function) => {
+					self.rename_extracted::<ClassFunctionBase>(function.0, functions, |_, _| {})
+				}
+				ClassMember::Property(..) => {}
+			}
+		}
+	}
+}
+
+/// Applies [Renames] to a module
+#[derive(Clone)]
+pub(crate) struct Renamer(pub(crate) Rc<RefCell<Renames>>);
+
+impl parser::VisitorMut<Expression, Vec<TempDiagnostic>> for Renamer {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		_data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		let renames = &self.0.borrow();
+		match item {
+			Expression::VariableReference(name, position, _) => renames.rename(name, position),
+			Expression::Assignment { lhs, .. } => match lhs {
+				LHSOfAssignment::VariableOrPropertyAccess(access) => renames.rename_access(access),
+				LHSOfAssignment::ObjectDestructuring(fields, ..) => {
+					for field in fields.iter_mut() {
+						renames.rename_object_field(field.get_ast_mut());
+					}
+				}
+				LHSOfAssignment::ArrayDestructuring(fields, ..) => {
+					for field in fields.iter_mut() {
+						match field {
+							ArrayDestructuringField::Spread(_, name) => {
+								renames.rename_identifier(name)
+							}
+							ArrayDestructuringField::Name(field, _) => {
+								renames.rename_variable_field(field.get_ast_mut())
+							}
+							ArrayDestructuringField::None => {}
+						}
+					}
+				}
+			},
+			Expression::BinaryAssignmentOperation { lhs: operand, .. }
+			| Expression::UnaryPrefixAssignmentOperation { operand, .. }
+			| Expression::UnaryPostfixAssignmentOperation { operand, .. } => renames.rename_access(operand),
+			Expression::ObjectLiteral(object) => {
+				for member in object.members.iter_mut() {
+					if let ObjectLiteralMember::Method(method) = member {
+						renames.rename_extracted::<ObjectLiteralMethodBase>(
+							method.0,
+							functions,
+							|_, _| {},
+						);
+						continue;
+					}
+					let ObjectLiteralMember::Shorthand(name, position, ..) = member else {
+						continue;
+					};
+					let Some(new_name) = renames.get(name, position) else { continue };
+					// `{ x }` becomes `{ x: x$1 }`
+					*member = ObjectLiteralMember::Property(
+						WithComment::None(PropertyKey::Ident(
+							name.clone(),
+							PropertyId::new(),
+							position.clone(),
+						)),
+						Expression::VariableReference(
+							new_name.clone(),
+							position.clone(),
+							ExpressionId::new(),
+						),
+						position.clone(),
+					);
+				}
+			}
+			Expression::ClassExpression(class, _) => {
+				if let Some(name) = &mut class.name {
+					renames.rename_identifier(name);
+				}
+				renames.rename_class(class, functions);
+			}
+			Expression::ExpressionFunction(function) => {
+				if let Some(name) = &mut function.name {
+					renames.rename_identifier(name);
+				}
+				renames.rename_parameters(&mut function.parameters);
+			}
+			Expression::ExtractedExpressionFunction(function) => renames
+				.rename_extracted::<ExpressionFunctionBase>(
+					function.0,
+					functions,
+					|renames, name| {
+						if let Some(name) = name {
+							renames.rename_identifier(name);
+						}
+					},
+				),
+			Expression::ArrowFunction(function) => {
+				renames.rename_parameters(&mut function.parameters)
+			}
+			Expression::ExtractedArrowFunction(function) => {
+				renames.rename_extracted::<ArrowFunctionBase>(function.0, functions, |_, _| {})
+			}
+			Expression::DynamicImport { path, .. } => {
+				let Expression::StringLiteral(specifier, ..) = path.as_ref() else { return };
+				let Some(namespace) = renames.namespaces.get(specifier) else { return };
+				// The module is already in the bundle so only its namespace is needed
+				let ParseOutput(expression, _) = Expression::from_string(
+					format!("Promise.resolve({namespace})"),
+					ParseSettings::default(),
+					SourceId::NULL,
+					None,
+					Vec::new(),
+				)
+				.expect("Invalid namespace expression");
+				*item = expression;
+			}
+			_ => {}
+		}
+	}
+}
+
+impl parser::VisitorMut<Statement, Vec<TempDiagnostic>> for Renamer {
+	fn visit_mut(
+		&mut self,
+		item: &mut Statement,
+		_data: &mut Vec<TempDiagnostic>,
+		_functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		let renames = &self.0.borrow();
+		match item {
+			Statement::VarVariable(statement) => {
+				for declaration in statement.declarations.iter_mut() {
+					renames.rename_variable_field(declaration.name.get_ast_mut());
+				}
+			}
+			Statement::ForLoopStatement(for_statement) => match &mut for_statement.condition {
+				ForLoopCondition::ForOf { variable, .. }
+				| ForLoopCondition::ForIn { variable, .. } => {
+					renames.rename_variable_field(variable.get_ast_mut())
+				}
+				ForLoopCondition::Statements {
+					initializer: Some(ForLoopStatementInitializer::Statement(declaration)),
+					..
+				} => renames.rename_variable_declaration(declaration),
+				ForLoopCondition::Statements {
+					initializer: Some(ForLoopStatementInitializer::VarStatement(statement)),
+					..
+				} => {
+					for declaration in statement.declarations.iter_mut() {
+						renames.rename_variable_field(declaration.name.get_ast_mut());
+					}
+				}
+				ForLoopCondition::Statements { .. } => {}
+			},
+			Statement::TryCatchStatement(try_catch_statement) => {
+				if let Some(exception_var) = &mut try_catch_statement.exception_var {
+					renames.rename_variable_field(exception_var.get_ast_mut());
+				}
+			}
+			_ => {}
+		}
+	}
+}
+
+impl parser::VisitorMut<JSXElement, Vec<TempDiagnostic>> for Renamer {
+	fn visit_mut(
+		&mut self,
+		item: &mut JSXElement,
+		_data: &mut Vec<TempDiagnostic>,
+		_functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		self.0.borrow().rename(&mut item.tag_name, &item.position);
+	}
+}
+
+impl<'a> parser::VisitorMut<BlockLikeMut<'a>, Vec<TempDiagnostic>> for Renamer {
+	fn visit_mut(
+		&mut self,
+		item: &mut BlockLikeMut<'a>,
+		_data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		let renames = &self.0.borrow();
+		for item in item.items.iter_mut() {
+			let StatementOrDeclaration::Declaration(declaration) = item else { continue };
+			match declaration {
+				Declaration::Variable(declaration) => {
+					renames.rename_variable_declaration(declaration)
+				}
+				Declaration::Function(function) => {
+					renames.rename_identifier(&mut function.on.name);
+					renames.rename_parameters(&mut function.on.parameters);
+				}
+				Declaration::ExtractedFunction(function) => renames
+					.rename_extracted::<StatementFunctionBase>(
+						function.0,
+						functions,
+						Renames::rename_identifier,
+					),
+				Declaration::Class(class) => {
+					renames.rename_identifier(&mut class.on.name);
+					renames.rename_class(&mut class.on, functions);
+				}
+				// Exported names are kept, but not the names inside of them
+				Declaration::Export(Decorated {
+					on: ExportDeclaration::Variable { exported, .. },
+					..
+				}) => match exported {
+					Exportable::Function(function) => {
+						renames.rename_parameters(&mut function.parameters)
+					}
+					Exportable::Class(class) => renames.rename_class(class, functions),
+					_ => {}
+				},
+				_ => {}
+			}
+		}
+	}
+}
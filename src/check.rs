@@ -0,0 +1,704 @@
+//! `ezno check`. Parses files in parallel, reporting syntax errors, early errors (which the parser
+//! accepts but the specification rejects) and the warnings of any enabled lints
+
+use std::{
+	collections::HashMap,
+	fs,
+	panic::AssertUnwindSafe,
+	path::{Path, PathBuf},
+	sync::atomic::{AtomicUsize, Ordering},
+	time::Instant,
+};
+
+use argh::FromArgs;
+use parser::{
+	declarations::{
+		export::Exportable, variable::VariableDeclarationKeyword, ExportDeclaration,
+		VariableDeclaration,
+	},
+	extractor::ExtractedFunctions,
+	operators::BinaryOperator,
+	source_map::MapFileStore,
+	statements::{
+		ForLoopCondition, ForLoopStatement, ForLoopStatementInitializer, IfStatement, SwitchBranch,
+	},
+	ASTNode, BlockLike, BlockOrSingleStatement, Chain, ChainVariable, Declaration, Expression,
+	Module, ParseOutput, ParseSettings, SourceId, Span, Statement, StatementOrDeclaration,
+	VariableField, VariableIdentifier,
+};
+
+use crate::{
+	config::Configuration,
+	error_handling::{
+		emit_diagnostics, DiagnosticCode, DiagnosticsFormat, ErrorWarningInfo, TempDiagnostic,
+	},
+	search::collect_source_files,
+	utilities::{glob_matches, print_to_cli},
+};
+
+/// Check files for syntax errors, early errors and lint warnings
+#[derive(FromArgs, Debug)]
+#[argh(subcommand, name = "check")]
+pub(crate) struct CheckArguments {
+	/// files, directories or globs (e.g. `packages/**/*.ts`) to check. Defaults to the files of
+	/// the configuration
+	#[argh(positional)]
+	paths: Vec<String>,
+	/// lints to run (can be repeated), e.g. `--lint no-debugger`
+	#[argh(option)]
+	lint: Vec<Lint>,
+	/// number of files parsed at once. Defaults to the number of cores
+	#[argh(option)]
+	jobs: Option<usize>,
+	/// how diagnostics are printed: text, json, sarif or github
+	#[argh(option, default = "DiagnosticsFormat::default()")]
+	diagnostics_format: DiagnosticsFormat,
+}
+
+impl CheckArguments {
+	/// Returns `Err` if any file has errors
+	pub(crate) fn run(&self) -> Result<(), ()> {
+		let now = Instant::now();
+		let cwd = std::env::current_dir().unwrap();
+		let read_from_path = |path: &Path| fs::read_to_string(path).ok();
+		let configuration = match Configuration::find(&cwd, &read_from_path) {
+			Ok(configuration) => configuration,
+			Err(err) => {
+				print_to_cli(format_args!("{err}"));
+				return Err(());
+			}
+		};
+
+		let files = if !self.paths.is_empty() {
+			let mut files = Vec::new();
+			for path in self.paths.iter() {
+				expand_path_or_glob(path, &mut files);
+			}
+			files
+		} else if let Some(configuration) = &configuration {
+			configuration.files()
+		} else {
+			let mut files = Vec::new();
+			expand_path_or_glob(".", &mut files);
+			files
+		};
+		let mut seen = std::collections::HashSet::new();
+		let files = files
+			.into_iter()
+			.map(|path| path.strip_prefix(&cwd).map(Path::to_owned).unwrap_or(path))
+			.filter(|path| seen.insert(path.clone()))
+			.collect::<Vec<PathBuf>>();
+
+		if files.is_empty() {
+			print_to_cli(format_args!("No files to check"));
+			return Err(());
+		}
+
+		let lints = match (&configuration, self.lint.is_empty()) {
+			(Some(Configuration { lints: Some(lints), .. }), true) => lints.clone(),
+			_ => self.lint.clone(),
+		};
+		let parse_settings =
+			configuration.as_ref().map(Configuration::parse_settings).unwrap_or_default();
+
+		let mut fs = MapFileStore::default();
+		let mut paths = Vec::new();
+		let mut sources = Vec::new();
+		let mut unreadable = Vec::new();
+		for path in files.iter() {
+			match fs::read_to_string(path) {
+				Ok(content) => {
+					let source_id = SourceId::new(&mut fs, path.clone(), content.clone());
+					paths.push(path);
+					sources.push((source_id, content));
+				}
+				Err(err) => unreadable.push((path, err)),
+			}
+		}
+
+		let jobs = self.jobs.unwrap_or_else(|| {
+			std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+		});
+		let results = check_sources(&sources, jobs, &parse_settings, &lints);
+
+		let mut errors = unreadable.len();
+		let mut warnings = 0;
+		let mut summary = Vec::new();
+		for (path, diagnostics) in paths.into_iter().zip(results.iter()) {
+			let file_errors = diagnostics
+				.iter()
+				.filter(|diagnostic| matches!(diagnostic.kind, ErrorWarningInfo::Error))
+				.count();
+			let file_warnings = diagnostics
+				.iter()
+				.filter(|diagnostic| matches!(diagnostic.kind, ErrorWarningInfo::Warning))
+				.count();
+			errors += file_errors;
+			warnings += file_warnings;
+			summary.push((path, file_errors, file_warnings));
+		}
+
+		emit_diagnostics(&fs, results.into_iter().flatten().collect(), self.diagnostics_format);
+
+		if let DiagnosticsFormat::Text = self.diagnostics_format {
+			for (path, file_errors, file_warnings) in summary {
+				let path = path.display();
+				if file_errors == 0 && file_warnings == 0 {
+					print_to_cli(format_args!("ok     {path}"));
+				} else {
+					print_to_cli(format_args!(
+						"{}  {path}: {}, {}",
+						if file_errors > 0 { "error" } else { "warn " },
+						plural(file_errors, "error"),
+						plural(file_warnings, "warning")
+					));
+				}
+			}
+			for (path, err) in unreadable.iter() {
+				print_to_cli(format_args!("error  {}: could not read ({err})", path.display()));
+			}
+			print_to_cli(format_args!(
+				"Checked {} in {:?}: {}, {}",
+				plural(files.len(), "file"),
+				now.elapsed(),
+				plural(errors, "error"),
+				plural(warnings, "warning")
+			));
+		}
+
+		if errors > 0 {
+			Err(())
+		} else {
+			Ok(())
+		}
+	}
+}
+
+fn plural(count: usize, name: &str) -> String {
+	format!("{count} {name}{}", if count == 1 { "" } else { "s" })
+}
+
+/// Paths without glob characters are files or directories, otherwise source files under the
+/// directory before the first glob character are matched against the glob
+fn expand_path_or_glob(pattern: &str, files: &mut Vec<PathBuf>) {
+	let pattern = pattern.strip_prefix("./").unwrap_or(pattern);
+	let (base, glob) = match pattern.find(['*', '?', '{']) {
+		Some(glob_start) => match pattern[..glob_start].rfind('/') {
+			Some(index) => (&pattern[..index], Some(pattern)),
+			None => (".", Some(pattern)),
+		},
+		None => (pattern, None),
+	};
+	let mut sources = Vec::new();
+	collect_source_files(Path::new(base), &mut sources);
+	for source in sources {
+		// Without the `./` from searching the current directory
+		let source = source.strip_prefix(".").map(Path::to_owned).unwrap_or(source);
+		if glob.is_none_or(|glob| glob_matches(glob, &source.to_string_lossy())) {
+			files.push(source);
+		}
+	}
+}
+
+/// Diagnostics for each source, in the same order. Sources are taken from a shared queue by
+/// `jobs` threads
+fn check_sources(
+	sources: &[(SourceId, String)],
+	jobs: usize,
+	parse_settings: &ParseSettings,
+	lints: &[Lint],
+) -> Vec<Vec<TempDiagnostic>> {
+	// A panic (from a bug in the parser or a lint) is reported for the source, rather than
+	// stopping the check of every other source
+	let check = |(source_id, content): &(SourceId, String)| {
+		std::panic::catch_unwind(AssertUnwindSafe(|| {
+			check_source(content.clone(), *source_id, parse_settings, lints)
+		}))
+		.unwrap_or_else(|payload| {
+			let message = payload
+				.downcast_ref::<String>()
+				.map(String::as_str)
+				.or_else(|| payload.downcast_ref::<&str>().copied())
+				.unwrap_or("unknown error");
+			vec![error(
+				format!("Could not check this file: {message}"),
+				Span { start: 0, end: 0, source_id: *source_id },
+				DiagnosticCode::InternalError,
+			)]
+		})
+	};
+	if jobs <= 1 || sources.len() <= 1 || cfg!(target_family = "wasm") {
+		return sources.iter().map(check).collect();
+	}
+
+	let next = AtomicUsize::new(0);
+	let mut results = sources.iter().map(|_| Vec::new()).collect::<Vec<_>>();
+	std::thread::scope(|scope| {
+		let workers = (0..jobs.min(sources.len()))
+			.map(|_| {
+				// Visiting is recursive so use the same stack size as the main thread
+				std::thread::Builder::new()
+					.stack_size(8 * 1024 * 1024)
+					.spawn_scoped(scope, || {
+						let mut checked = Vec::new();
+						loop {
+							let index = next.fetch_add(1, Ordering::Relaxed);
+							let Some(source) = sources.get(index) else {
+								break checked;
+							};
+							checked.push((index, check(source)));
+						}
+					})
+					.expect("could not spawn thread")
+			})
+			.collect::<Vec<_>>();
+		for worker in workers {
+			for (index, diagnostics) in worker.join().unwrap() {
+				results[index] = diagnostics;
+			}
+		}
+	});
+	results
+}
+
+fn check_source(
+	content: String,
+	source_id: SourceId,
+	parse_settings: &ParseSettings,
+	lints: &[Lint],
+) -> Vec<TempDiagnostic> {
+	let ParseOutput(module, mut state) =
+		match Module::from_string(content, parse_settings.clone(), source_id, None, Vec::new()) {
+			Ok(output) => output,
+			Err(err) => {
+				return vec![TempDiagnostic::from_parse_error(err, ErrorWarningInfo::Error)]
+			}
+		};
+
+	let mut diagnostics = Vec::new();
+	let mut visitors = parser::Visitors {
+		block_visitors: vec![Box::new(CheckControlFlow), Box::new(DuplicateDeclarations)],
+		..Default::default()
+	};
+	for lint in lints {
+		lint.add_visitors(&mut visitors);
+	}
+	module.visit(
+		&mut visitors,
+		&mut diagnostics,
+		&mut state.function_extractor,
+		&parser::VisitSettings::default(),
+	);
+	diagnostics.sort_by_key(|diagnostic| diagnostic.position.start);
+	diagnostics
+}
+
+fn error(label: String, position: Span, code: DiagnosticCode) -> TempDiagnostic {
+	TempDiagnostic {
+		label,
+		position,
+		kind: ErrorWarningInfo::Error,
+		code,
+		secondary_labels: Vec::new(),
+		notes: Vec::new(),
+	}
+}
+
+/// Runs [check_control_flow] on the module and on the body of each function. Loops and labels do
+/// not continue into functions, so each starts without them
+struct CheckControlFlow;
+
+impl<'a> parser::Visitor<BlockLike<'a>, Vec<TempDiagnostic>> for CheckControlFlow {
+	fn visit(
+		&mut self,
+		block: &BlockLike<'a>,
+		data: &mut Vec<TempDiagnostic>,
+		_functions: &mut ExtractedFunctions,
+		chain: &Chain,
+	) {
+		if chain.function_block_id(true) != Some(block.block_id) {
+			return;
+		}
+		let in_function = !matches!(chain.get_chain().last(), Some(ChainVariable::UnderModule(..)));
+		let flow = ControlFlow { in_function, ..Default::default() };
+		check_block(block.items, flow, &mut Vec::new(), data);
+	}
+}
+
+#[derive(Clone, Copy, Default)]
+struct ControlFlow {
+	in_function: bool,
+	in_loop: bool,
+	in_switch: bool,
+}
+
+/// Finds `return`, `break` and `continue` statements which are not allowed where they are. Only
+/// walks statements, so function bodies (which are expressions or declarations) are not entered
+fn check_control_flow<'a>(
+	statement: &'a Statement,
+	flow: ControlFlow,
+	labels: &mut Vec<&'a str>,
+	diagnostics: &mut Vec<TempDiagnostic>,
+) {
+	let in_loop = ControlFlow { in_loop: true, ..flow };
+	match statement {
+		Statement::Return(keyword, _) if !flow.in_function => diagnostics.push(error(
+			"Return statement is only valid in a function".to_owned(),
+			keyword.1.clone(),
+			DiagnosticCode::ReturnOutsideFunction,
+		)),
+		Statement::Break(label, position) | Statement::Continue(label, position) => {
+			let is_break = matches!(statement, Statement::Break(..));
+			let keyword = if is_break { "Break" } else { "Continue" };
+			match label {
+				Some(label) if !labels.contains(&label.as_str()) => diagnostics.push(error(
+					format!("Label '{label}' is not defined"),
+					position.clone(),
+					DiagnosticCode::UnknownLabel,
+				)),
+				Some(_) => {}
+				None if is_break && !(flow.in_loop || flow.in_switch) => diagnostics.push(error(
+					format!("{keyword} statement is only valid in a loop or switch"),
+					position.clone(),
+					DiagnosticCode::InvalidBreakOrContinue,
+				)),
+				None if !is_break && !flow.in_loop => diagnostics.push(error(
+					format!("{keyword} statement is only valid in a loop"),
+					position.clone(),
+					DiagnosticCode::InvalidBreakOrContinue,
+				)),
+				None => {}
+			}
+		}
+		Statement::Labelled { name, statement, .. } => {
+			labels.push(name);
+			check_control_flow(statement, flow, labels, diagnostics);
+			labels.pop();
+		}
+		Statement::Block(block) => check_block(&block.0, flow, labels, diagnostics),
+		Statement::IfStatement(IfStatement { inner, else_conditions, trailing_else, .. }) => {
+			check_inner(inner, flow, labels, diagnostics);
+			for condition in else_conditions {
+				check_inner(&condition.inner, flow, labels, diagnostics);
+			}
+			if let Some(trailing_else) = trailing_else {
+				check_inner(&trailing_else.inner, flow, labels, diagnostics);
+			}
+		}
+		Statement::ForLoopStatement(ForLoopStatement { inner, .. }) => {
+			check_inner(inner, in_loop, labels, diagnostics);
+		}
+		Statement::WhileStatement(statement) => {
+			check_inner(&statement.inner, in_loop, labels, diagnostics)
+		}
+		Statement::DoWhileStatement(statement) => {
+			check_inner(&statement.inner, in_loop, labels, diagnostics)
+		}
+		Statement::TryCatchStatement(statement) => {
+			check_block(&statement.try_inner.0, flow, labels, diagnostics);
+			for block in statement.catch_inner.iter().chain(statement.finally_inner.iter()) {
+				check_block(&block.0, flow, labels, diagnostics);
+			}
+		}
+		Statement::SwitchStatement(statement) => {
+			let in_switch = ControlFlow { in_switch: true, ..flow };
+			for branch in statement.branches.iter() {
+				let (SwitchBranch::Default(statements) | SwitchBranch::Case(_, statements)) =
+					branch;
+				for statement in statements {
+					check_control_flow(statement, in_switch, labels, diagnostics);
+				}
+			}
+		}
+		_ => {}
+	}
+}
+
+fn check_inner<'a>(
+	inner: &'a BlockOrSingleStatement,
+	flow: ControlFlow,
+	labels: &mut Vec<&'a str>,
+	diagnostics: &mut Vec<TempDiagnostic>,
+) {
+	match inner {
+		BlockOrSingleStatement::Braced(block) => check_block(&block.0, flow, labels, diagnostics),
+		BlockOrSingleStatement::SingleStatement(statement) => {
+			check_control_flow(statement, flow, labels, diagnostics);
+		}
+	}
+}
+
+fn check_block<'a>(
+	items: &'a [StatementOrDeclaration],
+	flow: ControlFlow,
+	labels: &mut Vec<&'a str>,
+	diagnostics: &mut Vec<TempDiagnostic>,
+) {
+	for item in items {
+		if let StatementOrDeclaration::Statement(statement) = item {
+			check_control_flow(statement, flow, labels, diagnostics);
+		}
+	}
+}
+
+/// `let`, `const`, `class` and `function` declarations with the same name in one block
+struct DuplicateDeclarations;
+
+impl<'a> parser::Visitor<BlockLike<'a>, Vec<TempDiagnostic>> for DuplicateDeclarations {
+	fn visit(
+		&mut self,
+		block: &BlockLike<'a>,
+		data: &mut Vec<TempDiagnostic>,
+		_functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		let mut declared = HashMap::<&str, &Span>::new();
+		let mut names = Vec::new();
+		for item in block.items.iter() {
+			let StatementOrDeclaration::Declaration(declaration) = item else {
+				continue;
+			};
+			match declaration {
+				Declaration::Variable(variable) => variable_names(variable, &mut names),
+				Declaration::Function(function) => identifier_name(&function.on.name, &mut names),
+				Declaration::Class(class) => identifier_name(&class.on.name, &mut names),
+				Declaration::Export(export) => {
+					if let ExportDeclaration::Variable { exported, .. } = &export.on {
+						match exported {
+							Exportable::Variable(variable) => variable_names(variable, &mut names),
+							Exportable::Function(function) => {
+								identifier_name(&function.name, &mut names);
+							}
+							Exportable::Class(class) => identifier_name(&class.name, &mut names),
+							// `var` can be declared again
+							Exportable::VarVariable(_)
+							| Exportable::Interface(_)
+							| Exportable::TypeAlias(_)
+							| Exportable::Enum(_) => {}
+						}
+					}
+				}
+				_ => {}
+			}
+			for (name, position) in names.drain(..) {
+				if let Some(first) = declared.get(name) {
+					let mut diagnostic = error(
+						format!("'{name}' has already been declared"),
+						position.clone(),
+						DiagnosticCode::DuplicateDeclaration,
+					);
+					diagnostic
+						.secondary_labels
+						.push(((*first).clone(), "First declared here".to_owned()));
+					data.push(diagnostic);
+				} else {
+					declared.insert(name, position);
+				}
+			}
+		}
+	}
+}
+
+fn identifier_name<'a>(identifier: &'a VariableIdentifier, names: &mut Vec<(&'a str, &'a Span)>) {
+	if let VariableIdentifier::Standard(name, _, position) = identifier {
+		names.push((name, position));
+	}
+}
+
+fn variable_names<'a>(declaration: &'a VariableDeclaration, names: &mut Vec<(&'a str, &'a Span)>) {
+	let fields = match declaration {
+		VariableDeclaration::ConstDeclaration { declarations, .. } => {
+			declarations.iter().map(|item| item.name.get_ast()).collect::<Vec<_>>()
+		}
+		VariableDeclaration::LetDeclaration { declarations, .. } => {
+			declarations.iter().map(|item| item.name.get_ast()).collect()
+		}
+	};
+	for field in fields {
+		field_names(field, names);
+	}
+}
+
+fn field_names<'a, T: parser::VariableFieldTypes>(
+	field: &'a VariableField<T>,
+	names: &mut Vec<(&'a str, &'a Span)>,
+) {
+	match field {
+		VariableField::Name(identifier) => identifier_name(identifier, names),
+		VariableField::Array(items, _) => {
+			for item in items {
+				match item {
+					parser::ArrayDestructuringField::Spread(_, identifier) => {
+						identifier_name(identifier, names);
+					}
+					parser::ArrayDestructuringField::Name(field, _) => {
+						field_names(field.get_ast(), names);
+					}
+					parser::ArrayDestructuringField::None => {}
+				}
+			}
+		}
+		VariableField::Object(items, _) => {
+			for item in items {
+				match item.get_ast() {
+					parser::ObjectDestructuringField::Spread(_, identifier)
+					| parser::ObjectDestructuringField::Name(identifier, _) => {
+						identifier_name(identifier, names);
+					}
+					parser::ObjectDestructuringField::Map { variable_name, .. } => {
+						field_names(variable_name.get_ast(), names);
+					}
+				}
+			}
+		}
+	}
+}
+
+/// Lints which can be selected in the configuration or with `--lint`. Each warns about uses of
+/// what it is named after
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(target_family = "wasm", derive(serde::Serialize))]
+pub enum Lint {
+	/// `debugger` statements
+	Debugger,
+	/// `var` declarations
+	Var,
+	/// `==` and `!=`
+	LooseEquality,
+}
+
+impl Lint {
+	pub const ALL: &'static [Lint] = &[Lint::Debugger, Lint::Var, Lint::LooseEquality];
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Lint::Debugger => "no-debugger",
+			Lint::Var => "no-var",
+			Lint::LooseEquality => "no-loose-equality",
+		}
+	}
+
+	pub fn code(self) -> &'static str {
+		match self {
+			Lint::Debugger => "L0001",
+			Lint::Var => "L0002",
+			Lint::LooseEquality => "L0003",
+		}
+	}
+
+	pub fn description(self) -> &'static str {
+		match self {
+			Lint::Debugger => "Debugger statement",
+			Lint::Var => "Var declaration",
+			Lint::LooseEquality => "Loose equality comparison",
+		}
+	}
+
+	fn add_visitors(self, visitors: &mut parser::Visitors<Vec<TempDiagnostic>>) {
+		match self {
+			Lint::Debugger | Lint::Var => {
+				visitors.statement_visitors.push(Box::new(StatementLint(self)));
+			}
+			Lint::LooseEquality => visitors.expression_visitors.push(Box::new(NoLooseEquality)),
+		}
+	}
+}
+
+impl std::str::FromStr for Lint {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Lint::ALL.iter().copied().find(|lint| lint.name() == s).ok_or_else(|| {
+			let names = Lint::ALL.iter().map(|lint| lint.name());
+			format!("Unknown lint '{s}', expected one of {}", names.collect::<Vec<_>>().join(", "))
+		})
+	}
+}
+
+fn warning(lint: Lint, label: &str, position: Span, help: &str) -> TempDiagnostic {
+	TempDiagnostic {
+		label: label.to_owned(),
+		position,
+		kind: ErrorWarningInfo::Warning,
+		code: DiagnosticCode::Lint(lint),
+		secondary_labels: Vec::new(),
+		notes: vec![format!("help: {help}")],
+	}
+}
+
+struct StatementLint(Lint);
+
+impl parser::Visitor<Statement, Vec<TempDiagnostic>> for StatementLint {
+	fn visit(
+		&mut self,
+		item: &Statement,
+		data: &mut Vec<TempDiagnostic>,
+		_functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		match (self.0, item) {
+			(Lint::Debugger, Statement::Debugger(position)) => data.push(warning(
+				self.0,
+				"Unexpected debugger statement",
+				position.clone(),
+				"remove it before committing",
+			)),
+			(Lint::Var, Statement::VarVariable(variable)) => data.push(warning(
+				self.0,
+				"Unexpected var declaration",
+				variable.get_position().into_owned(),
+				"use `let` or `const`, which are block scoped",
+			)),
+			(Lint::Var, Statement::ForLoopStatement(for_statement)) => {
+				let position = match &for_statement.condition {
+					ForLoopCondition::Statements {
+						initializer: Some(ForLoopStatementInitializer::VarStatement(variable)),
+						..
+					} => variable.get_position().into_owned(),
+					ForLoopCondition::ForOf {
+						keyword: Some(VariableDeclarationKeyword::Var(keyword)),
+						..
+					}
+					| ForLoopCondition::ForIn {
+						keyword: Some(VariableDeclarationKeyword::Var(keyword)),
+						..
+					} => keyword.get_position().clone(),
+					_ => return,
+				};
+				data.push(warning(
+					self.0,
+					"Unexpected var declaration",
+					position,
+					"use `let` or `const`, which are block scoped",
+				))
+			}
+			_ => {}
+		}
+	}
+}
+
+struct NoLooseEquality;
+
+impl parser::Visitor<Expression, Vec<TempDiagnostic>> for NoLooseEquality {
+	fn visit(
+		&mut self,
+		item: &Expression,
+		data: &mut Vec<TempDiagnostic>,
+		_functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		if let Expression::BinaryOperation {
+			operator: operator @ (BinaryOperator::Equal | BinaryOperator::NotEqual),
+			..
+		} = item
+		{
+			let strict = if let BinaryOperator::Equal = operator { "===" } else { "!==" };
+			data.push(warning(
+				Lint::LooseEquality,
+				"Unexpected loose equality comparison",
+				item.get_position().into_owned(),
+				&format!("use `{strict}`, which does not convert its operands"),
+			));
+		}
+	}
+}
@@ -7,9 +7,22 @@ use std::{
 	time::Instant,
 };
 
-use crate::{error_handling::emit_ezno_diagnostic, temp::Output, utilities};
+use crate::{
+	bundle::{BundleFormat, BundleSettings},
+	config::{Configuration, SourceMaps, CONFIGURATION_FILE_NAME},
+	downlevel::{decorators::DecoratorSemantics, Target},
+	error_handling::{emit_diagnostics, DiagnosticsFormat, TempDiagnostic},
+	jsx::JSXRuntime,
+	pipeline::Pipeline,
+	resolver::{ResolveError, Resolver},
+	source_maps,
+	statistics::{BuildStatistics, TimingsFormat},
+	temp::{BuildSettings, IncrementalBuild, Output, Transform},
+	utilities,
+};
 use crate::{temp::BuildOutput, utilities::print_to_cli};
 use argh::FromArgs;
+use parser::source_map::MapFileStore;
 // use checker::{
 // 	BuildOutput, Plugin, Project, TypeCheckSettings, TypeCheckingVisitorGenerators,
 // 	TypeDefinitionModulePath,
@@ -28,7 +41,9 @@ enum CompilerSubCommand {
 	Info(Info),
 	Build(BuildArguments),
 	ASTExplorer(crate::ast_explorer::ExplorerArguments),
-	// Check(CheckArguments),
+	Search(crate::search::SearchArguments),
+	Rewrite(crate::rewrite::RewriteArguments),
+	Check(crate::check::CheckArguments),
 	// Run(RunArguments),
 	// Repl(repl::ReplArguments),
 	// #[cfg(debug_assertions)]
@@ -56,28 +71,80 @@ struct Info {}
 #[derive(FromArgs, PartialEq, Debug)]
 #[argh(subcommand, name = "build")]
 struct BuildArguments {
-	/// path to input file
-	#[argh(positional)]
-	input: PathBuf,
-	/// path to output
+	/// path to input file followed by path to output. Default to 'entry' and 'outDir' in the
+	/// configuration
 	#[argh(positional)]
-	output: Option<PathBuf>,
-	/// whether to minify build output
+	paths: Vec<PathBuf>,
+	/// minify build output: print without whitespace, rename local variables, fold constants and
+	/// remove unreachable code
 	#[argh(switch, short = 'm')]
 	minify: bool,
 	/// whether to include comments in the output
 	#[argh(switch)]
 	no_comments: bool,
-	/// build source maps
+	/// build source maps (included as a base64 comment when there is no output path)
 	#[argh(switch)]
 	source_maps: bool,
+	/// include source maps in the output as a base64 comment
+	#[argh(switch)]
+	inline_source_maps: bool,
+	/// bundle the modules reachable from the input into a single output
+	#[argh(switch)]
+	bundle: bool,
+	/// format of the bundle, 'esm' (default) or 'iife'
+	#[argh(option)]
+	format: Option<BundleFormat>,
+	/// variable the exports of an 'iife' bundle are assigned to
+	#[argh(option)]
+	global_name: Option<String>,
+	/// keep declarations which are not used in bundles
+	#[argh(switch)]
+	no_tree_shaking: bool,
+	/// transform to run on each module, replacing the transforms in the configuration. Can be
+	/// repeated. Passes registered with the pipeline are selected by name too
+	#[argh(option)]
+	transform: Vec<Transform>,
+	/// version of ECMAScript to lower syntax to, 'es5', 'es2015', 'es2017' or 'es2020'
+	#[argh(option)]
+	target: Option<Target>,
+	/// lower JSX with the 'classic' (React.createElement) or 'automatic' (jsx-runtime) runtime
+	#[argh(option)]
+	jsx: Option<JSXRuntime>,
+	/// function called for elements by the classic JSX runtime. Defaults to React.createElement
+	#[argh(option)]
+	jsx_pragma: Option<String>,
+	/// type of fragments in the classic JSX runtime. Defaults to React.Fragment
+	#[argh(option)]
+	jsx_pragma_frag: Option<String>,
+	/// package the automatic JSX runtime is imported from. Defaults to react
+	#[argh(option)]
+	jsx_import_source: Option<String>,
+	/// lower decorators with the '2023-05' (TC39) or 'legacy' (TypeScript experimentalDecorators)
+	/// semantics
+	#[argh(option)]
+	decorators: Option<DecoratorSemantics>,
+	/// add type metadata to legacy decorators, like TypeScript's emitDecoratorMetadata
+	#[argh(switch)]
+	decorator_metadata: bool,
+	/// how to print diagnostics, 'text' (default), 'json', 'sarif' or 'github'
+	#[argh(option, default = "DiagnosticsFormat::default()")]
+	diagnostics_format: DiagnosticsFormat,
 	#[cfg(not(target_family = "wasm"))]
-	/// whether to display compile times
+	/// whether to display how long each phase took for each module
 	#[argh(switch)]
 	timings: bool,
-	// /// whether to re-build on file changes
-	// #[argh(switch)]
-	// watch: bool,
+	#[cfg(not(target_family = "wasm"))]
+	/// how to print timings, 'table' (default), 'json' or 'chrome-trace'. Implies --timings
+	#[argh(option)]
+	timings_format: Option<TimingsFormat>,
+	#[cfg(not(target_family = "wasm"))]
+	/// write timings to a file rather than printing them. Implies --timings
+	#[argh(option)]
+	timings_output: Option<PathBuf>,
+	#[cfg(not(target_family = "wasm"))]
+	/// whether to re-build on file changes
+	#[argh(switch)]
+	watch: bool,
 }
 
 // /// Check project
@@ -124,6 +191,11 @@ fn file_system_resolver(path: &Path) -> Option<(String, Vec<(usize, parser::Empt
 
 #[cfg_attr(target_family = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
 pub fn run_cli() {
+	run_cli_with_pipeline(|pipeline| pipeline)
+}
+
+/// Runs the CLI, where `configure` can register passes and hooks on the [Pipeline] of builds
+pub fn run_cli_with_pipeline(configure: impl Fn(Pipeline) -> Pipeline) {
 	let get_cli_args = crate::utilities::get_cli_args();
 	let arguments: Vec<&str> = get_cli_args.iter().map(AsRef::as_ref).collect();
 
@@ -145,58 +217,63 @@ pub fn run_cli() {
 			utilities::print_info();
 		}
 		CompilerSubCommand::Build(build_config) => {
-			let _output = build(build_config);
+			if build(build_config, &configure).is_err() {
+				#[cfg(not(target_family = "wasm"))]
+				std::process::exit(1);
+			}
 		}
 		CompilerSubCommand::ASTExplorer(mut repl) => repl.run(),
-		// CompilerSubCommand::Run(run_arguments) => {
-		// 	let build_arguments = BuildArguments {
-		// 		input: run_arguments.input,
-		// 		output: Some(run_arguments.output.clone()),
-		// 		minify: true,
-		// 		no_comments: true,
-		// 		source_maps: false,
-		// 		watch: false,
-		// 		timings: false,
-		// 	};
-		// 	let output = build(build_arguments);
-
-		// 	if output.is_ok() {
-		// 		Command::new("deno")
-		// 			.args(["run", "--allow-all", run_arguments.output.to_str().unwrap()])
-		// 			.spawn()
-		// 			.unwrap()
-		// 			.wait()
-		// 			.unwrap();
-		// 	}
-		// }
-		// CompilerSubCommand::Check(check_arguments) => {
-		// 	let CheckArguments { input, watch } = check_arguments;
-		// 	check(input, watch)
-		// }
-		// #[cfg(debug_assertions)]
-		// CompilerSubCommand::Pack(Pack { input, output }) => {
-		// 	let file = checker::definition_file_to_buffer(
-		// 		&file_system_resolver,
-		// 		&env::current_dir().unwrap(),
-		// 		&input,
-		// 	)
-		// 	.unwrap();
-
-		// 	std::fs::write(&output, &file).unwrap();
-		// 	// println!("Wrote binary context out to {}", output.display());
-
-		// 	let _root_ctx = checker::root_context_from_bytes(file);
-		// 	println!("Registered {} types", _root_ctx.types.len());
-		// }
-		// CompilerSubCommand::Repl(argument) => repl::run_deno_repl(argument),
+		CompilerSubCommand::Search(search) => search.run(),
+		CompilerSubCommand::Rewrite(rewrite) => rewrite.run(),
+		CompilerSubCommand::Check(check) => {
+			if check.run().is_err() {
+				#[cfg(not(target_family = "wasm"))]
+				std::process::exit(1);
+			}
+		} // CompilerSubCommand::Run(run_arguments) => {
+		  // 	let build_arguments = BuildArguments {
+		  // 		input: run_arguments.input,
+		  // 		output: Some(run_arguments.output.clone()),
+		  // 		minify: true,
+		  // 		no_comments: true,
+		  // 		source_maps: false,
+		  // 		watch: false,
+		  // 		timings: false,
+		  // 	};
+		  // 	let output = build(build_arguments);
+
+		  // 	if output.is_ok() {
+		  // 		Command::new("deno")
+		  // 			.args(["run", "--allow-all", run_arguments.output.to_str().unwrap()])
+		  // 			.spawn()
+		  // 			.unwrap()
+		  // 			.wait()
+		  // 			.unwrap();
+		  // 	}
+		  // }
+		  // CompilerSubCommand::Check(check_arguments) => {
+		  // 	let CheckArguments { input, watch } = check_arguments;
+		  // 	check(input, watch)
+		  // }
+		  // #[cfg(debug_assertions)]
+		  // CompilerSubCommand::Pack(Pack { input, output }) => {
+		  // 	let file = checker::definition_file_to_buffer(
+		  // 		&file_system_resolver,
+		  // 		&env::current_dir().unwrap(),
+		  // 		&input,
+		  // 	)
+		  // 	.unwrap();
+
+		  // 	std::fs::write(&output, &file).unwrap();
+		  // 	// println!("Wrote binary context out to {}", output.display());
+
+		  // 	let _root_ctx = checker::root_context_from_bytes(file);
+		  // 	println!("Registered {} types", _root_ctx.types.len());
+		  // }
+		  // CompilerSubCommand::Repl(argument) => repl::run_deno_repl(argument),
 	}
 }
 
-/// TODO + deserialize
-struct _Settings {
-	current_working_directory: Option<PathBuf>,
-}
-
 // fn check(entry_point: PathBuf, watch: bool) {
 // 	let cwd = env::current_dir().unwrap();
 
@@ -256,71 +333,304 @@ struct _Settings {
 // 	}
 // }
 
-// // <TData> data: TData
-// fn watch_command(cwd: PathBuf, mut callback: impl FnMut(PathBuf)) {
-// 	use std::time::Duration;
-// 	let (tx, rx) = std::sync::mpsc::channel();
-
-// 	let debounce_time = Duration::from_millis(500);
-// 	let mut watcher = notify::watcher(tx, debounce_time).unwrap();
-
-// 	// Add a path to be watched. All files and directories at that path and
-// 	// below will be monitored for changes.
-// 	notify::Watcher::watch(&mut watcher, cwd.as_path(), notify::RecursiveMode::Recursive).unwrap();
-
-// 	println!("Watching for changes on '{}'", cwd.display());
-// 	loop {
-// 		match rx.recv() {
-// 			Ok(event) => match event {
-// 				notify::DebouncedEvent::NoticeWrite(_)
-// 				| notify::DebouncedEvent::NoticeRemove(_)
-// 				| notify::DebouncedEvent::Rescan
-// 				| notify::DebouncedEvent::Chmod(_) => {}
-// 				notify::DebouncedEvent::Create(path)
-// 				| notify::DebouncedEvent::Write(path)
-// 				| notify::DebouncedEvent::Remove(path)
-// 				| notify::DebouncedEvent::Rename(path, _) => {
-// 					println!("'{}' changed, checking project again", path.display());
-// 					callback(path);
-// 					println!("Watching for changes on '{}'", cwd.display());
-// 				}
-// 				notify::DebouncedEvent::Error(_, _) => {
-// 					eprintln!("Error");
-// 					break;
-// 				}
-// 			},
-// 			Err(e) => {
-// 				eprintln!("watch error: {:?}", e);
-// 				break;
-// 			}
-// 		}
-// 	}
-// }
+/// A change to a file in a watched directory
+#[cfg(not(target_family = "wasm"))]
+enum FileChange {
+	Modified(PathBuf),
+	/// Created, removed or renamed. This can change how imports resolve
+	Moved(PathBuf),
+}
+
+/// Calls `callback` with changes under `cwd`. Changes which happen close together (for example
+/// saving several files at once) are passed together
+#[cfg(not(target_family = "wasm"))]
+fn watch_command(cwd: PathBuf, mut callback: impl FnMut(Vec<FileChange>)) {
+	use std::time::Duration;
+	let (tx, rx) = std::sync::mpsc::channel();
+
+	let debounce_time = Duration::from_millis(100);
+	let mut watcher = match notify::watcher(tx, debounce_time) {
+		Ok(watcher) => watcher,
+		Err(err) => {
+			eprintln!("Could not start watching: {err}");
+			return;
+		}
+	};
+
+	// Add a path to be watched. All files and directories at that path and
+	// below will be monitored for changes.
+	if let Err(err) =
+		notify::Watcher::watch(&mut watcher, cwd.as_path(), notify::RecursiveMode::Recursive)
+	{
+		eprintln!("Could not watch '{}': {err}", cwd.display());
+		return;
+	}
+
+	print_to_cli(format_args!("Watching for changes on '{}'", cwd.display()));
+	loop {
+		let mut events = match rx.recv() {
+			Ok(event) => vec![event],
+			Err(e) => {
+				eprintln!("watch error: {:?}", e);
+				break;
+			}
+		};
+		// Wait for the rest of the events in this batch
+		while let Ok(event) = rx.recv_timeout(debounce_time) {
+			events.push(event);
+		}
+
+		let mut changes = Vec::new();
+		for event in events {
+			match event {
+				notify::DebouncedEvent::NoticeWrite(_)
+				| notify::DebouncedEvent::NoticeRemove(_)
+				| notify::DebouncedEvent::Chmod(_) => {}
+				notify::DebouncedEvent::Write(path) => changes.push(FileChange::Modified(path)),
+				notify::DebouncedEvent::Create(path) | notify::DebouncedEvent::Remove(path) => {
+					changes.push(FileChange::Moved(path))
+				}
+				notify::DebouncedEvent::Rename(from, to) => {
+					changes.push(FileChange::Moved(from));
+					changes.push(FileChange::Moved(to));
+				}
+				notify::DebouncedEvent::Rescan => changes.push(FileChange::Moved(cwd.clone())),
+				notify::DebouncedEvent::Error(err, path) => {
+					eprintln!("watch error: {err}");
+					if let Some(path) = path {
+						changes.push(FileChange::Moved(path));
+					}
+				}
+			}
+		}
+		if !changes.is_empty() {
+			callback(changes);
+		}
+	}
+}
+
+/// Whether a file could affect a build: a module or a configuration file that changes how
+/// modules resolve
+#[cfg(not(target_family = "wasm"))]
+fn affects_build(path: &Path) -> bool {
+	let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+	matches!(name, "package.json" | "tsconfig.json")
+		|| crate::resolver::DEFAULT_EXTENSIONS
+			.iter()
+			.any(|extension| name.strip_suffix(extension).is_some_and(|stem| stem.ends_with('.')))
+}
 
 // TODO needs settings information structure
-fn build(build_arguments: BuildArguments) -> Result<(), ()> {
+fn build(
+	build_arguments: BuildArguments,
+	configure: &impl Fn(Pipeline) -> Pipeline,
+) -> Result<(), ()> {
 	// let _cwd = env::current_dir().unwrap();
 	let BuildArguments {
 		minify,
 		no_comments,
-		input: entry_path,
-		output: output_path,
-		source_maps: _,
+		paths,
+		source_maps,
+		inline_source_maps,
+		bundle,
+		format,
+		global_name,
+		no_tree_shaking,
+		transform,
+		target,
+		jsx,
+		jsx_pragma,
+		jsx_pragma_frag,
+		jsx_import_source,
+		decorators,
+		decorator_metadata,
+		diagnostics_format,
 		#[cfg(not(target_family = "wasm"))]
 		timings,
-		// watch,
+		#[cfg(not(target_family = "wasm"))]
+		timings_format,
+		#[cfg(not(target_family = "wasm"))]
+		timings_output,
+		#[cfg(not(target_family = "wasm"))]
+		watch,
 	} = build_arguments;
+	#[cfg(not(target_family = "wasm"))]
+	let timings = timings || timings_format.is_some() || timings_output.is_some();
+	#[cfg(target_family = "wasm")]
+	let timings = false;
+
+	let mut paths = paths.into_iter();
+	let (input, output) = (paths.next(), paths.next());
+	if paths.next().is_some() {
+		print_to_cli(format_args!("Expected an input path and an output path"));
+		return Err(());
+	}
 
-	// if watch {
-	// 	unimplemented!("Watch mode for build");
-	// }
+	let read_from_path = |path: &Path| utilities::read_fs_path_to_string(path).ok();
+	let cwd = env::current_dir().unwrap_or_default();
+	let configuration = match Configuration::find(&cwd, &read_from_path) {
+		Ok(configuration) => configuration.unwrap_or_default(),
+		Err(err) => {
+			print_to_cli(format_args!("{err}"));
+			return Err(());
+		}
+	};
+	// Paths in the configuration are absolute
+	let relative = |path: &Path| path.strip_prefix(&cwd).unwrap_or(path).to_owned();
+
+	let output_path = output.or_else(|| configuration.out_dir.as_deref().map(relative));
+	let source_map = if inline_source_maps {
+		SourceMapOutput::Inline
+	} else if source_maps {
+		SourceMapOutput::File
+	} else {
+		match configuration.source_maps.flatten() {
+			Some(SourceMaps::File) => SourceMapOutput::File,
+			Some(SourceMaps::Inline) => SourceMapOutput::Inline,
+			None => SourceMapOutput::None,
+		}
+	};
+	let source_map = match source_map {
+		// Without an output path there is nowhere to write the file
+		SourceMapOutput::File if output_path.is_none() => SourceMapOutput::Inline,
+		source_map => source_map,
+	};
 
 	// TODO debug_types temp
 	// let type_check_settings = TypeCheckSettings { ..Default::default() };
-	let _output_settings = parser::ToStringSettings {
-		include_comments: !no_comments,
-		pretty: !minify,
-		..Default::default()
+	let minify = minify || configuration.minify.unwrap_or(false);
+	let default_output_settings = parser::ToStringSettings::default();
+	let output_settings = if minify {
+		parser::ToStringSettings {
+			include_types: configuration.include_types.unwrap_or(false),
+			..parser::ToStringSettings::minified()
+		}
+	} else {
+		parser::ToStringSettings {
+			include_comments: !no_comments && configuration.comments.unwrap_or(true),
+			include_types: configuration.include_types.unwrap_or(false),
+			indent_with: configuration
+				.indent
+				.clone()
+				.unwrap_or(default_output_settings.indent_with),
+			..default_output_settings
+		}
+	};
+	let mut transforms = if transform.is_empty() {
+		configuration.transforms.clone().unwrap_or_else(|| BuildSettings::default().transforms)
+	} else {
+		transform
+	};
+	// Otherwise TypeScript only syntax would be printed as is
+	if !output_settings.include_types && !transforms.contains(&Transform::EraseTypes) {
+		transforms.insert(0, Transform::EraseTypes);
+	}
+	// Before erasure, which lowers the enums it reads
+	if let Some(index) =
+		transforms.iter().position(|transform| *transform == Transform::InlineEnums)
+	{
+		transforms.remove(index);
+		transforms.insert(0, Transform::InlineEnums);
+	}
+	let mut jsx_runtime = jsx.or_else(|| configuration.jsx_runtime.clone());
+	if jsx_pragma.is_some() || jsx_pragma_frag.is_some() || jsx_import_source.is_some() {
+		let runtime = jsx_runtime.unwrap_or_default();
+		match runtime.with_options(jsx_pragma, jsx_pragma_frag, jsx_import_source) {
+			Ok(runtime) => jsx_runtime = Some(runtime),
+			Err(err) => {
+				print_to_cli(format_args!("{err}"));
+				return Err(());
+			}
+		}
+	}
+	let mut decorators = decorators.or(configuration.decorator_semantics);
+	if decorator_metadata {
+		match decorators.unwrap_or_default().with_metadata(true) {
+			Ok(semantics) => decorators = Some(semantics),
+			Err(err) => {
+				print_to_cli(format_args!("{err}"));
+				return Err(());
+			}
+		}
+	}
+	// Before erasure, which removes the type annotations metadata is created from
+	if decorators.is_some() && !transforms.contains(&Transform::LowerDecorators) {
+		transforms.insert(0, Transform::LowerDecorators);
+	}
+	// Choosing a runtime implies lowering
+	if jsx_runtime.is_some() && !transforms.contains(&Transform::LowerJSX) {
+		transforms.push(Transform::LowerJSX);
+	}
+	if configuration.extras == Some(true) && !transforms.contains(&Transform::LowerExtras) {
+		transforms.push(Transform::LowerExtras);
+	}
+	// After JSX, which is lowered to calls and object literals (which may then be lowered)
+	if let Some(target) = target.or(configuration.target) {
+		for transform in target.transforms() {
+			if !transforms.contains(&transform) {
+				transforms.push(transform);
+			}
+		}
+	}
+	// Last, so that lowered code is minified too
+	if minify {
+		for transform in crate::minify::TRANSFORMS {
+			if !transforms.contains(transform) {
+				transforms.push(transform.clone());
+			}
+		}
+	}
+	let settings = BuildSettings {
+		parse_settings: configuration.parse_settings(),
+		output_settings,
+		transforms,
+		jsx: jsx_runtime.unwrap_or_default(),
+		decorators: decorators.unwrap_or_default(),
+		statistics: timings,
+	};
+	let bundle = bundle || configuration.bundle.unwrap_or(false);
+	let format = format.or(configuration.format).unwrap_or_default();
+	let global_name = global_name.or_else(|| configuration.global_name.clone());
+	let tree_shaking = !no_tree_shaking && configuration.tree_shaking.unwrap_or(true);
+
+	// Files are found each build so that new files are included in watch mode
+	let entry_points = || match &input {
+		Some(input) => vec![input.clone()],
+		None => configuration.files().iter().map(|path| relative(path)).collect(),
+	};
+	let mut pipeline = configure(Pipeline::new(settings));
+	if let Err(err) = pipeline.validate() {
+		print_to_cli(format_args!("{err}"));
+		return Err(());
+	}
+	let mut incremental = IncrementalBuild::new(output_path.clone().unwrap_or_default());
+	let mut run_build = |incremental: &mut IncrementalBuild| {
+		let entry_points = entry_points();
+		if entry_points.is_empty() {
+			print_to_cli(format_args!(
+				"No entry point, pass one or add 'entry' to {CONFIGURATION_FILE_NAME}"
+			));
+			return None;
+		}
+		if bundle && entry_points.len() > 1 {
+			print_to_cli(format_args!("Bundles can only have one entry point"));
+			return None;
+		}
+		let (entries, resolver) = read_entries(entry_points, &read_from_path).ok()?;
+		Some(if bundle {
+			let (entry_path, entry_content) = entries.into_iter().next().unwrap();
+			crate::bundle::bundle(
+				entry_path,
+				entry_content,
+				&resolver,
+				&read_from_path,
+				output_path.clone().unwrap_or_default(),
+				&mut pipeline,
+				BundleSettings { format, global_name: global_name.clone(), tree_shaking },
+			)
+		} else {
+			incremental.rebuild(&mut pipeline, entries, &resolver, &read_from_path)
+		})
 	};
 
 	// let test_plugin = Plugin {
@@ -373,57 +683,216 @@ fn build(build_arguments: BuildArguments) -> Result<(), ()> {
 	// 	}
 	// };
 
+	#[cfg(not(target_family = "wasm"))]
+	if watch {
+		let mut build_changes = |incremental: &mut IncrementalBuild| {
+			let now = Instant::now();
+			let Some((fs, mut result)) = run_build(incremental) else {
+				return;
+			};
+			if timings {
+				report_timings(now, &mut result, timings_format, timings_output.as_deref());
+			}
+			let rebuilt = result.as_ref().map_or(0, |output| output.outputs.len());
+			if emit_build_output(
+				&fs,
+				result,
+				output_path.as_deref(),
+				source_map,
+				diagnostics_format,
+			)
+			.is_ok()
+			{
+				let elapsed = now.elapsed();
+				if bundle {
+					print_to_cli(format_args!("Bundled in {elapsed:?}"));
+				} else {
+					let total = incremental.module_count();
+					print_to_cli(format_args!(
+						"Rebuilt {rebuilt} of {total} modules in {elapsed:?}"
+					));
+				}
+			}
+		};
+
+		build_changes(&mut incremental);
+
+		// Outputs are ignored so that writing them does not trigger another build
+		let output_directory = output_path.as_deref().map(crate::temp::absolute);
+		watch_command(cwd.clone(), |changes| {
+			let mut affected = false;
+			for change in changes {
+				let (FileChange::Modified(path) | FileChange::Moved(path)) = &change;
+				if output_directory.as_ref().is_some_and(|output| path.starts_with(output)) {
+					continue;
+				}
+				match change {
+					FileChange::Modified(path) if affects_build(&path) => {
+						if bundle || path.extension().is_some_and(|extension| extension == "json") {
+							incremental.mark_all_changed();
+							affected = true;
+						} else {
+							affected |= incremental.mark_changed(&path);
+						}
+					}
+					// Directories do not have extensions
+					FileChange::Moved(path)
+						if affects_build(&path) || path.extension().is_none() =>
+					{
+						incremental.mark_all_changed();
+						affected = true;
+					}
+					FileChange::Modified(_) | FileChange::Moved(_) => {}
+				}
+			}
+			if affected {
+				build_changes(&mut incremental);
+			}
+		});
+		return Ok(());
+	}
+
 	#[cfg(not(target_family = "wasm"))]
 	let now = Instant::now();
-	// let result = project.build(entry_point, output_settings);
-
-	let (fs, result) = crate::temp::build(
-		utilities::read_fs_path_to_string(entry_path.clone()).expect("Could not read path"),
-		// TODO
-		entry_path.display().to_string(),
-		output_path
-			.map(|path| path.into_os_string().into_string().expect("Invalid path"))
-			.unwrap_or_default(),
-	);
-
-	// TODO this should be integrated with project for a breakdown of
-	// each stage etc.
+
+	#[cfg_attr(target_family = "wasm", allow(unused_mut))]
+	let (fs, mut result) = run_build(&mut incremental).ok_or(())?;
+
 	#[cfg(not(target_family = "wasm"))]
 	if timings {
-		let elapsed = now.elapsed();
-		eprintln!("Project built in {:?}", elapsed);
+		report_timings(now, &mut result, timings_format, timings_output.as_deref());
 	}
 
-	match result {
-		Ok(BuildOutput { outputs, temp_warnings_and_infos }) => {
-			for error in temp_warnings_and_infos {
-				emit_ezno_diagnostic(&fs, error).unwrap();
+	emit_build_output(&fs, result, output_path.as_deref(), source_map, diagnostics_format)
+}
+
+/// Prints the statistics of a build which started at `start` (or writes them to `output`)
+#[cfg(not(target_family = "wasm"))]
+fn report_timings(
+	start: Instant,
+	result: &mut Result<BuildOutput, Vec<TempDiagnostic>>,
+	format: Option<TimingsFormat>,
+	output: Option<&Path>,
+) {
+	let modules = result.as_mut().map(|output| std::mem::take(&mut output.statistics));
+	let statistics = BuildStatistics::new(start, modules.unwrap_or_default());
+	let formatted = statistics.format(format.unwrap_or_default());
+	match output {
+		Some(output) => {
+			if let Err(err) = fs::write(output, formatted) {
+				print_to_cli(format_args!("Could not write timings: {err}"));
+			}
+		}
+		None => eprintln!("{formatted}"),
+	}
+}
+
+/// Reads the entry points and creates a resolver for the project the first is in
+fn read_entries(
+	entry_points: Vec<PathBuf>,
+	read_from_path: &impl Fn(&Path) -> Option<String>,
+) -> Result<(Vec<(PathBuf, String)>, Resolver), ()> {
+	let mut entries = Vec::new();
+	for entry_path in entry_points {
+		match utilities::read_fs_path_to_string(&entry_path) {
+			Ok(content) => entries.push((entry_path, content)),
+			Err(err) => {
+				print_to_cli(format_args!("Could not read {}: {err}", entry_path.display()));
+				return Err(());
 			}
-			// print_error_warning_info_handler(error_warning_info_handler);
-			// if let Some(output) = output_path {
-			// 	// if output.len() == 1 {
-			// 	// 	fs::write(output, output.into_values().next().unwrap()).unwrap();
-			// 	// } else {
-			// 	// 	for (filename, content) in output {
-			// 	// 		let final_path = output.join(filename);
-			// 	// 		fs::write(final_path, content).unwrap();
-			// 	// 	}
-			// 	// }
-			// } else {
-			// }
-
-			// TODO path
-			for Output { content, .. } in outputs {
-				print_to_cli(format_args!("{content}"));
+		}
+	}
+	match project_resolver(&entries[0].0, read_from_path) {
+		Ok(resolver) => Ok((entries, resolver)),
+		Err(err) => {
+			print_to_cli(format_args!("{err}"));
+			Err(())
+		}
+	}
+}
+
+/// Prints diagnostics and writes the outputs to `output_path` (or prints them if there is no
+/// output path)
+fn emit_build_output(
+	fs: &MapFileStore,
+	result: Result<BuildOutput, Vec<TempDiagnostic>>,
+	output_path: Option<&Path>,
+	source_map: SourceMapOutput,
+	diagnostics_format: DiagnosticsFormat,
+) -> Result<(), ()> {
+	match result {
+		Ok(BuildOutput { outputs, temp_warnings_and_infos, .. }) => {
+			emit_diagnostics(fs, temp_warnings_and_infos, diagnostics_format);
+
+			let print_paths = outputs.len() > 1;
+			for output in outputs {
+				if output_path.is_some() {
+					if let Err(err) = write_output(output, source_map) {
+						print_to_cli(format_args!("Could not write output: {err}"));
+						return Err(());
+					}
+				} else {
+					if print_paths {
+						print_to_cli(format_args!("// {}", output.output_path));
+					}
+					print_to_cli(format_args!("{}", with_source_map_comment(output, source_map)));
+				}
 			}
 			Ok(())
 		}
 		Err(errors) => {
-			for error in errors {
-				emit_ezno_diagnostic(&fs, error).unwrap();
-			}
-			// print_error_warning_info_handler(error_handler);
+			emit_diagnostics(fs, errors, diagnostics_format);
 			Err(())
 		}
 	}
 }
+
+/// Uses the path mappings from the closest `tsconfig.json` to the entry point
+fn project_resolver(
+	entry_path: &Path,
+	read_from_path: &impl Fn(&Path) -> Option<String>,
+) -> Result<Resolver, ResolveError> {
+	let directory = entry_path.parent().unwrap_or(Path::new(""));
+	match Resolver::find_ts_config(directory, read_from_path) {
+		Some(ts_config) => Resolver::default().with_ts_config(&ts_config, read_from_path),
+		None => Ok(Resolver::default()),
+	}
+}
+
+#[derive(Clone, Copy)]
+enum SourceMapOutput {
+	None,
+	/// Written to a `.map` file next to the output
+	File,
+	/// Appended to the output as a base64 data URL
+	Inline,
+}
+
+fn with_source_map_comment(output: Output, source_map: SourceMapOutput) -> String {
+	let Output { mut content, source_map: source_map_json, output_path, .. } = output;
+	let url = match source_map {
+		SourceMapOutput::None => return content,
+		SourceMapOutput::File => {
+			let name = Path::new(&output_path).file_name().unwrap_or_default().to_string_lossy();
+			format!("{name}.map")
+		}
+		SourceMapOutput::Inline => source_maps::inline_source_map_url(&source_map_json),
+	};
+	content.push('\n');
+	content.push_str(&source_maps::source_mapping_url_comment(&url));
+	content
+}
+
+/// Writes output (and possibly source map) to the output path, creating directories as needed
+fn write_output(output: Output, source_map: SourceMapOutput) -> std::io::Result<()> {
+	let output_path = PathBuf::from(&output.output_path);
+	if let Some(parent) = output_path.parent() {
+		fs::create_dir_all(parent)?;
+	}
+	if let SourceMapOutput::File = source_map {
+		let mut map_path = output_path.clone().into_os_string();
+		map_path.push(".map");
+		fs::write(map_path, &output.source_map)?;
+	}
+	fs::write(&output_path, with_source_map_comment(output, source_map))
+}
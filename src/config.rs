@@ -0,0 +1,322 @@
+//! Project configuration from an `ezno.json` file, found by walking up from the working directory.
+//!
+//! ```json
+//! {
+//!     "entry": "src/index.ts",
+//!     "include": ["src/**/*.{ts,tsx}"],
+//!     "exclude": ["**/*.test.ts"],
+//!     "outDir": "dist",
+//!     "parse": { "jsx": true, "decorators": true, "extras": false },
+//!     "output": { "minify": false, "comments": true, "sourceMaps": "inline" },
+//!     "transforms": ["invert-ternary-branches", ["my-pass", { "option": true }]],
+//!     "target": "es2015",
+//!     "jsx": { "runtime": "automatic", "importSource": "preact" },
+//!     "decorators": { "version": "legacy", "metadata": true },
+//!     "lints": ["no-debugger", "no-var"],
+//!     "bundle": { "format": "iife", "globalName": "Library", "treeShaking": true }
+//! }
+//! ```
+//!
+//! Values are optional so that command line flags can override them
+
+use std::{
+	fmt,
+	path::{Path, PathBuf},
+};
+
+use parser::ParseSettings;
+
+use crate::{
+	bundle::BundleFormat,
+	check::Lint,
+	downlevel::{decorators::DecoratorSemantics, Target},
+	json::{self, JSONValue},
+	jsx::JSXRuntime,
+	search::collect_source_files,
+	temp::Transform,
+	utilities::glob_matches,
+};
+
+pub const CONFIGURATION_FILE_NAME: &str = "ezno.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceMaps {
+	/// Written to a `.map` file next to the output
+	File,
+	/// Appended to the output as a base64 data URL
+	Inline,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Configuration {
+	/// Directory containing the configuration file. Paths in the configuration are relative to it
+	pub directory: PathBuf,
+	pub entry_points: Vec<PathBuf>,
+	/// Globs of files which are built as entry points (in addition to `entry_points`)
+	pub include: Vec<String>,
+	/// Globs of files which are not built as entry points
+	pub exclude: Vec<String>,
+	pub out_dir: Option<PathBuf>,
+
+	pub jsx: Option<bool>,
+	pub decorators: Option<bool>,
+	/// Generator function headers, server and module blocks. Setting this enables
+	/// [Transform::LowerExtras]
+	pub extras: Option<bool>,
+
+	pub minify: Option<bool>,
+	pub comments: Option<bool>,
+	pub include_types: Option<bool>,
+	pub indent: Option<String>,
+	/// `Some(None)` disables source maps
+	pub source_maps: Option<Option<SourceMaps>>,
+
+	pub transforms: Option<Vec<Transform>>,
+	/// Adds the transforms of [Target::transforms] after the others
+	pub target: Option<Target>,
+	/// Setting this enables [Transform::LowerJSX]
+	pub jsx_runtime: Option<JSXRuntime>,
+	/// Setting this enables [Transform::LowerDecorators]
+	pub decorator_semantics: Option<DecoratorSemantics>,
+	/// Lints run by `ezno check`
+	pub lints: Option<Vec<Lint>>,
+
+	pub bundle: Option<bool>,
+	pub format: Option<BundleFormat>,
+	pub global_name: Option<String>,
+	pub tree_shaking: Option<bool>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ConfigurationError {
+	pub path: PathBuf,
+	pub reason: String,
+}
+
+impl fmt::Display for ConfigurationError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "Invalid configuration '{}': {}", self.path.display(), self.reason)
+	}
+}
+
+impl Configuration {
+	/// Finds and reads the closest `ezno.json` in `directory` or its ancestors
+	pub fn find(
+		directory: &Path,
+		read_from_path: &impl Fn(&Path) -> Option<String>,
+	) -> Result<Option<Self>, ConfigurationError> {
+		for directory in directory.ancestors() {
+			let path = directory.join(CONFIGURATION_FILE_NAME);
+			if let Some(source) = read_from_path(&path) {
+				return Self::from_json(&source, directory.to_owned())
+					.map(Some)
+					.map_err(|reason| ConfigurationError { path, reason });
+			}
+		}
+		Ok(None)
+	}
+
+	pub fn from_json(source: &str, directory: PathBuf) -> Result<Self, String> {
+		let value = json::parse(source).map_err(|(reason, offset)| {
+			let before = &source[..offset];
+			let line = before.matches('\n').count() + 1;
+			let column = before.len() - before.rfind('\n').map_or(0, |index| index + 1) + 1;
+			format!("{reason} at {line}:{column}")
+		})?;
+		let JSONValue::Object(entries) = value else {
+			return Err("Expected an object".to_owned());
+		};
+
+		let mut configuration = Configuration { directory, ..Default::default() };
+		for (key, value) in &entries {
+			match key.as_str() {
+				"entry" => {
+					configuration.entry_points = strings(key, value)?
+						.into_iter()
+						.map(|entry| configuration.directory.join(entry))
+						.collect();
+				}
+				"include" => configuration.include = strings(key, value)?,
+				"exclude" => configuration.exclude = strings(key, value)?,
+				"outDir" => {
+					configuration.out_dir = Some(configuration.directory.join(string(key, value)?));
+				}
+				"parse" => {
+					for (key, value) in object(key, value)? {
+						match key.as_str() {
+							"jsx" => configuration.jsx = Some(boolean(key, value)?),
+							"decorators" => configuration.decorators = Some(boolean(key, value)?),
+							"extras" => configuration.extras = Some(boolean(key, value)?),
+							key => return Err(format!("Unknown parse option '{key}'")),
+						}
+					}
+				}
+				"output" => {
+					for (key, value) in object(key, value)? {
+						match key.as_str() {
+							"minify" => configuration.minify = Some(boolean(key, value)?),
+							"comments" => configuration.comments = Some(boolean(key, value)?),
+							"includeTypes" => {
+								configuration.include_types = Some(boolean(key, value)?);
+							}
+							"indent" => configuration.indent = Some(string(key, value)?),
+							"sourceMaps" => {
+								configuration.source_maps =
+									Some(match value {
+										JSONValue::Boolean(true) => Some(SourceMaps::File),
+										JSONValue::Boolean(false) => None,
+										JSONValue::String(value) if value == "inline" => {
+											Some(SourceMaps::Inline)
+										}
+										_ => return Err(
+											"Expected 'sourceMaps' to be a boolean or \"inline\""
+												.to_owned(),
+										),
+									});
+							}
+							key => return Err(format!("Unknown output option '{key}'")),
+						}
+					}
+				}
+				"transforms" => {
+					let JSONValue::Array(items) = value else {
+						return Err("Expected 'transforms' to be an array".to_owned());
+					};
+					configuration.transforms =
+						Some(items.iter().map(transform).collect::<Result<_, _>>()?);
+				}
+				"target" => configuration.target = Some(string(key, value)?.parse()?),
+				"jsx" => {
+					let mut runtime = JSXRuntime::default();
+					let (mut pragma, mut pragma_frag, mut import_source) = (None, None, None);
+					for (key, value) in object(key, value)? {
+						match key.as_str() {
+							"runtime" => runtime = string(key, value)?.parse()?,
+							"pragma" => pragma = Some(string(key, value)?),
+							"pragmaFrag" => pragma_frag = Some(string(key, value)?),
+							"importSource" => import_source = Some(string(key, value)?),
+							key => return Err(format!("Unknown jsx option '{key}'")),
+						}
+					}
+					configuration.jsx_runtime =
+						Some(runtime.with_options(pragma, pragma_frag, import_source)?);
+				}
+				"decorators" => {
+					let (mut semantics, mut metadata) = (DecoratorSemantics::default(), false);
+					for (key, value) in object(key, value)? {
+						match key.as_str() {
+							"version" => semantics = string(key, value)?.parse()?,
+							"metadata" => metadata = boolean(key, value)?,
+							key => return Err(format!("Unknown decorators option '{key}'")),
+						}
+					}
+					configuration.decorator_semantics = Some(semantics.with_metadata(metadata)?);
+				}
+				"lints" => {
+					configuration.lints = Some(
+						strings(key, value)?
+							.iter()
+							.map(|name| name.parse())
+							.collect::<Result<_, _>>()?,
+					);
+				}
+				"bundle" => match value {
+					JSONValue::Boolean(bundle) => configuration.bundle = Some(*bundle),
+					value => {
+						configuration.bundle = Some(true);
+						for (key, value) in object(key, value)? {
+							match key.as_str() {
+								"format" => {
+									configuration.format = Some(string(key, value)?.parse()?);
+								}
+								"globalName" => {
+									configuration.global_name = Some(string(key, value)?);
+								}
+								"treeShaking" => {
+									configuration.tree_shaking = Some(boolean(key, value)?);
+								}
+								key => return Err(format!("Unknown bundle option '{key}'")),
+							}
+						}
+					}
+				},
+				key => return Err(format!("Unknown option '{key}'")),
+			}
+		}
+		Ok(configuration)
+	}
+
+	pub fn parse_settings(&self) -> ParseSettings {
+		let default = ParseSettings::default();
+		ParseSettings {
+			jsx: self.jsx.unwrap_or(default.jsx),
+			decorators: self.decorators.unwrap_or(default.decorators),
+			generator_keyword: self.extras.unwrap_or(default.generator_keyword),
+			server_blocks: self.extras.unwrap_or(default.server_blocks),
+			module_blocks: self.extras.unwrap_or(default.module_blocks),
+			..default
+		}
+	}
+
+	/// Entry points followed by source files under [Configuration::directory] which match
+	/// `include` and not `exclude`
+	pub fn files(&self) -> Vec<PathBuf> {
+		let mut files = self.entry_points.clone();
+		if self.include.is_empty() {
+			return files;
+		}
+		let mut sources = Vec::new();
+		collect_source_files(&self.directory, &mut sources);
+		for source in sources {
+			let relative = source.strip_prefix(&self.directory).unwrap_or(&source);
+			let relative = relative.to_string_lossy();
+			let included = self.include.iter().any(|glob| glob_matches(glob, &relative));
+			let excluded = self.exclude.iter().any(|glob| glob_matches(glob, &relative));
+			if included && !excluded && !files.contains(&source) {
+				files.push(source);
+			}
+		}
+		files
+	}
+}
+
+fn string(key: &str, value: &JSONValue) -> Result<String, String> {
+	value.as_str().map(ToOwned::to_owned).ok_or_else(|| format!("Expected '{key}' to be a string"))
+}
+
+/// A string or an array of strings
+fn strings(key: &str, value: &JSONValue) -> Result<Vec<String>, String> {
+	match value {
+		JSONValue::String(value) => Ok(vec![value.clone()]),
+		JSONValue::Array(items) => items.iter().map(|item| string(key, item)).collect(),
+		_ => Err(format!("Expected '{key}' to be a string or an array of strings")),
+	}
+}
+
+/// A name, or a name and options for a registered pass (`["name", { ... }]`)
+fn transform(value: &JSONValue) -> Result<Transform, String> {
+	match value {
+		JSONValue::String(name) => name.parse(),
+		JSONValue::Array(items) => match items.as_slice() {
+			[JSONValue::String(name), options] => {
+				name.parse::<Transform>()?.with_options(options.clone())
+			}
+			_ => Err("Expected a transform with options to be a name and a value".to_owned()),
+		},
+		_ => Err("Expected a transform to be a name or a name and options".to_owned()),
+	}
+}
+
+fn boolean(key: &str, value: &JSONValue) -> Result<bool, String> {
+	match value {
+		JSONValue::Boolean(value) => Ok(*value),
+		_ => Err(format!("Expected '{key}' to be a boolean")),
+	}
+}
+
+fn object<'a>(key: &str, value: &'a JSONValue) -> Result<&'a [(String, JSONValue)], String> {
+	match value {
+		JSONValue::Object(entries) => Ok(entries),
+		_ => Err(format!("Expected '{key}' to be an object")),
+	}
+}
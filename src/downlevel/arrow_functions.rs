@@ -0,0 +1,125 @@
+//! `(x) => x` to `function (x) { return x; }`. `this` and `arguments` in arrow functions refer to
+//! those of the function they are in, so they are captured in variables there (`_this`,
+//! `_arguments`) before arrow functions become functions in a second pass
+
+use parser::{
+	expressions::{
+		arrow_function::{ArrowFunctionBase, ExpressionOrBlock},
+		ExpressionFunctionBase, MultipleExpression,
+	},
+	extractor::{ExtractedFunctions, GetFunction},
+	ASTNode, Block, BlockId, Chain, Expression, FunctionBase, FunctionHeader, FunctionId, Keyword,
+	Statement, StatementOrDeclaration, VisitorsMut,
+};
+
+use super::{parenthesize, reference, take, warning, SharedTemporaries};
+use crate::error_handling::TempDiagnostic;
+
+/// Passes for [crate::temp::Transform::LowerArrowFunctions]
+pub(crate) fn passes() -> Vec<VisitorsMut<Vec<TempDiagnostic>>> {
+	let mut passes = super::passes(|temporaries| VisitorsMut {
+		expression_visitors_mut: vec![Box::new(CaptureThisAndArguments(temporaries.clone()))],
+		..Default::default()
+	});
+	passes[1].expression_visitors_mut.push(Box::new(ArrowToFunctionExpressions));
+	passes[1].statement_visitors_mut.push(Box::new(ArrowToFunctionExpressions));
+	passes
+}
+
+struct CaptureThisAndArguments(SharedTemporaries);
+
+impl parser::VisitorMut<Expression, Vec<TempDiagnostic>> for CaptureThisAndArguments {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		data: &mut Vec<TempDiagnostic>,
+		_functions: &mut ExtractedFunctions,
+		chain: &Chain,
+	) {
+		if !chain.in_arrow_function() {
+			return;
+		}
+		match item {
+			Expression::ThisReference(position, _) => {
+				let name = self.0.borrow_mut().capture(chain, "this", position);
+				*item = reference(&name, position);
+			}
+			Expression::VariableReference(name, position, _) if name == "arguments" => {
+				let name = self.0.borrow_mut().capture(chain, "arguments", position);
+				*item = reference(&name, position);
+			}
+			Expression::SuperExpression(_, position, _) | Expression::NewTarget(position, _) => {
+				data.push(warning(
+					"'super' and 'new.target' in arrow functions are not lowered".to_owned(),
+					position.clone(),
+				));
+			}
+			_ => {}
+		}
+	}
+}
+
+struct ArrowToFunctionExpressions;
+
+impl parser::VisitorMut<Expression, Vec<TempDiagnostic>> for ArrowToFunctionExpressions {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		_data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		let Expression::ExtractedArrowFunction(arrow_function) = item else { return };
+		let position = arrow_function.1.clone();
+		let FunctionBase { header, type_parameters, parameters, return_type, body, .. } =
+			GetFunction::<ArrowFunctionBase>::get_function(functions, arrow_function.0);
+		let body = match body {
+			ExpressionOrBlock::Block(block) => block,
+			ExpressionOrBlock::Expression(expression) => {
+				let position = expression.get_position().into_owned();
+				let statement =
+					Statement::Return(Keyword::new(position.clone()), Some((*expression).into()));
+				Block(vec![StatementOrDeclaration::Statement(statement)], BlockId::new(), position)
+			}
+		};
+		let function = FunctionBase::<ExpressionFunctionBase> {
+			function_id: FunctionId::new(),
+			header: FunctionHeader::VirginFunctionHeader {
+				async_keyword: header,
+				function_keyword: Keyword::new(position),
+				generator_star_token_pos: None,
+			},
+			name: None,
+			type_parameters,
+			parameters,
+			return_type,
+			body,
+		};
+		*item = Expression::ExtractedExpressionFunction(functions.new_extracted_function(function));
+	}
+}
+
+/// A statement cannot start with `function` unless it is a declaration, so function expressions
+/// from arrow functions at the start of a statement are parenthesized
+impl parser::VisitorMut<Statement, Vec<TempDiagnostic>> for ArrowToFunctionExpressions {
+	fn visit_mut(
+		&mut self,
+		item: &mut Statement,
+		_data: &mut Vec<TempDiagnostic>,
+		_functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		let Statement::Expression(expression) = item else { return };
+		let mut first = expression;
+		while first.lhs.is_some() {
+			first = first.lhs.as_mut().unwrap();
+		}
+		if let MultipleExpression { lhs: None, rhs: rhs @ Expression::ExtractedArrowFunction(_) } =
+			first
+		{
+			let position = rhs.get_position().into_owned();
+			let arrow_function = take(rhs, &position);
+			*rhs = parenthesize(arrow_function, &position);
+		}
+	}
+}
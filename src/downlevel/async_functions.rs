@@ -0,0 +1,211 @@
+//! `async function f() { await x; }` to
+//! `function f() { return __awaiter(this, void 0, function* () { yield x; }); }`. `__awaiter`
+//! runs the generator, resuming it with the result of each promise it yields. `arguments` are
+//! passed to the generator if it references them
+//!
+//! Generators are lowered separately (see [super::generators]) so for ES5 the generator becomes
+//! a state machine. Async functions which use `super` are kept (with a warning), as `super` is not
+//! available in the generator
+
+use std::{cell::RefCell, collections::HashSet, rc::Rc};
+
+use parser::{
+	expressions::{ExpressionFunctionBase, ExpressionId, MultipleExpression},
+	extractor::{ExtractedFunctions, GetFunction},
+	operators::UnaryOperator,
+	statements::ForLoopCondition,
+	ASTNode, Block, BlockId, BlockLikeMut, Chain, Expression, FunctionBase, FunctionHeader,
+	FunctionId, FunctionParameters, Keyword, Span, Statement, StatementOrDeclaration, VisitorsMut,
+};
+
+use super::{
+	call, is_directive, lower_declared_functions, lower_expression_functions, parenthesize,
+	reference, references_arguments, take, uses_super, void_zero, warning, LowerFunction,
+	LowerableFunction, SharedTemporaries,
+};
+use crate::error_handling::TempDiagnostic;
+
+/// Passes for [crate::temp::Transform::LowerAsyncFunctions]
+pub(crate) fn passes() -> Vec<VisitorsMut<Vec<TempDiagnostic>>> {
+	super::passes(|temporaries| {
+		let shared = Rc::new(RefCell::new(AsyncFunctions {
+			temporaries: temporaries.clone(),
+			bodies: HashSet::new(),
+		}));
+		VisitorsMut {
+			block_visitors_mut: vec![Box::new(LowerAsyncFunctions(shared.clone()))],
+			statement_visitors_mut: vec![Box::new(LowerAsyncFunctions(shared.clone()))],
+			expression_visitors_mut: vec![Box::new(LowerAsyncFunctions(shared))],
+			..Default::default()
+		}
+	})
+}
+
+const AWAITER: &str = "function (thisArg, args, body) {
+	return new Promise(function (resolve, reject) {
+		var iterator = body.apply(thisArg, args || []);
+		function fulfilled(value) { try { step(iterator.next(value)); } catch (error) { reject(error); } }
+		function rejected(error) { try { step(iterator[\"throw\"](error)); } catch (error) { reject(error); } }
+		function step(result) {
+			if (result.done) resolve(result.value);
+			else Promise.resolve(result.value).then(fulfilled, rejected);
+		}
+		step(iterator.next());
+	});
+}";
+
+struct AsyncFunctions {
+	temporaries: SharedTemporaries,
+	/// Bodies of the generators which replaced async functions, where `await` becomes `yield`
+	bodies: HashSet<BlockId>,
+}
+
+impl AsyncFunctions {
+	fn in_lowered_body(&self, chain: &Chain) -> bool {
+		chain.function_block_id(true).is_some_and(|block| self.bodies.contains(&block))
+	}
+}
+
+impl LowerFunction for AsyncFunctions {
+	fn lower<T: LowerableFunction>(
+		&mut self,
+		function: &mut FunctionBase<T>,
+		functions: &mut ExtractedFunctions,
+		data: &mut Vec<TempDiagnostic>,
+	) {
+		if !T::is_async(function) || T::is_generator(function) {
+			return;
+		}
+		if uses_super(T::body_ref(function), functions) {
+			data.push(warning(
+				"Async functions which use 'super' are not lowered".to_owned(),
+				function.get_position().into_owned(),
+			));
+			return;
+		}
+		let arguments = references_arguments(T::body_ref(function), functions);
+		T::remove_async(function);
+		let Block(mut items, _, position) = T::take_body(function);
+		let body = items.split_off(items.iter().take_while(|item| is_directive(item)).count());
+		let (generator, block) = generator_expression(body, &position, functions);
+		self.bodies.insert(block);
+
+		let awaiter = self.temporaries.borrow_mut().helper("__awaiter", AWAITER);
+		let arguments =
+			if arguments { reference("arguments", &position) } else { void_zero(&position) };
+		let this = Expression::ThisReference(position.clone(), ExpressionId::new());
+		let value =
+			call(reference(&awaiter, &position), vec![this, arguments, generator], &position);
+		T::set_body(function, items, value);
+	}
+}
+
+/// `function* () { ... }` and the [BlockId] of its body
+pub(crate) fn generator_expression(
+	items: Vec<StatementOrDeclaration>,
+	position: &Span,
+	functions: &mut ExtractedFunctions,
+) -> (Expression, BlockId) {
+	let block = BlockId::new();
+	let function = FunctionBase::<ExpressionFunctionBase> {
+		function_id: FunctionId::new(),
+		header: FunctionHeader::VirginFunctionHeader {
+			async_keyword: None,
+			function_keyword: Keyword::new(position.clone()),
+			generator_star_token_pos: Some(position.clone()),
+		},
+		name: None,
+		type_parameters: None,
+		parameters: FunctionParameters {
+			parameters: Vec::new(),
+			optional_parameters: Vec::new(),
+			rest_parameter: None,
+			position: position.clone(),
+		},
+		return_type: None,
+		body: Block(items, block, position.clone()),
+	};
+	(Expression::ExtractedExpressionFunction(functions.new_extracted_function(function)), block)
+}
+
+struct LowerAsyncFunctions(Rc<RefCell<AsyncFunctions>>);
+
+impl<'a> parser::VisitorMut<BlockLikeMut<'a>, Vec<TempDiagnostic>> for LowerAsyncFunctions {
+	fn visit_mut(
+		&mut self,
+		item: &mut BlockLikeMut<'a>,
+		data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		lower_declared_functions(item.items, functions, data, &mut *self.0.borrow_mut());
+	}
+}
+
+/// `await x;` and `return await x;` to `yield x` without parentheses
+impl parser::VisitorMut<Statement, Vec<TempDiagnostic>> for LowerAsyncFunctions {
+	fn visit_mut(
+		&mut self,
+		item: &mut Statement,
+		data: &mut Vec<TempDiagnostic>,
+		_functions: &mut ExtractedFunctions,
+		chain: &Chain,
+	) {
+		if !self.0.borrow().in_lowered_body(chain) {
+			return;
+		}
+		match item {
+			Statement::Expression(MultipleExpression {
+				lhs: None,
+				rhs: Expression::UnaryOperation { operator: operator @ UnaryOperator::Await, .. },
+			})
+			| Statement::Return(
+				_,
+				Some(MultipleExpression {
+					lhs: None,
+					rhs:
+						Expression::UnaryOperation { operator: operator @ UnaryOperator::Await, .. },
+				}),
+			) => *operator = UnaryOperator::Yield,
+			Statement::ForLoopStatement(statement)
+				if matches!(
+					statement.condition,
+					ForLoopCondition::ForOf { is_await: true, .. }
+				) =>
+			{
+				data.push(warning(
+					"'for await' is only lowered by 'lower-async-iteration'".to_owned(),
+					statement.position.clone(),
+				));
+			}
+			_ => {}
+		}
+	}
+}
+
+impl parser::VisitorMut<Expression, Vec<TempDiagnostic>> for LowerAsyncFunctions {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		chain: &Chain,
+	) {
+		let mut state = self.0.borrow_mut();
+		lower_expression_functions(item, functions, data, &mut *state);
+		if !state.in_lowered_body(chain) {
+			return;
+		}
+		if let Expression::UnaryOperation {
+			operator: operator @ UnaryOperator::Await,
+			position,
+			..
+		} = item
+		{
+			*operator = UnaryOperator::Yield;
+			let position = position.clone();
+			let yielded = take(item, &position);
+			*item = parenthesize(yielded, &position);
+		}
+	}
+}
@@ -0,0 +1,367 @@
+//! Async generators and `for await`. `async function* g() { ... }` to
+//! `function g() { return __asyncGenerator(this, void 0, function* () { ... }); }`, where the
+//! generator yields `[0, x]` for `await x`, `[1, x]` for `yield x` and `[2, iterator]` for
+//! `yield* x`. `__asyncGenerator` queues calls to `next`, `throw` and `return` and settles each
+//! one when the generator yields (or returns)
+//!
+//! ```js
+//! for await (const x of y) { ... }
+//! ```
+//! becomes a loop over `__asyncValues(y)` (which adapts sync iterables)
+//! ```js
+//! { _a = __asyncValues(y); try { while (true) { _b = await _a.next(); if (_b.done) break; const x = _b.value; ... } } finally { if (_b && !_b.done && _a.return) await _a.return(); } }
+//! ```
+//! `await` is kept in async functions, which are lowered by [super::async_functions]
+
+use std::{cell::RefCell, collections::HashSet, rc::Rc};
+
+use parser::{
+	expressions::{ExpressionId, SpreadExpression},
+	extractor::ExtractedFunctions,
+	operators::{BinaryOperator, UnaryOperator},
+	statements::{
+		ForLoopCondition, ForLoopStatement, IfStatement, TryCatchStatement, WhileStatement,
+	},
+	ASTNode, Block, BlockId, BlockLikeMut, BlockOrSingleStatement, Chain, Expression, FunctionBase,
+	Span, Statement, StatementOrDeclaration, VisitorsMut,
+};
+
+use super::{
+	assign, call, is_directive, loop_variable, lower_declared_functions,
+	lower_expression_functions, member, parenthesize, reference, references_arguments, shadows,
+	take, uses_super, void_zero, warning, LowerFunction, LowerableFunction, SharedTemporaries,
+};
+use crate::error_handling::TempDiagnostic;
+
+/// Passes for [crate::temp::Transform::LowerAsyncIteration]
+pub(crate) fn passes() -> Vec<VisitorsMut<Vec<TempDiagnostic>>> {
+	super::passes(|temporaries| {
+		let shared = Rc::new(RefCell::new(AsyncIteration {
+			temporaries: temporaries.clone(),
+			bodies: HashSet::new(),
+			lowered: HashSet::new(),
+		}));
+		VisitorsMut {
+			block_visitors_mut: vec![Box::new(LowerAsyncIteration(shared.clone()))],
+			statement_visitors_mut: vec![Box::new(LowerAsyncIteration(shared.clone()))],
+			expression_visitors_mut: vec![Box::new(LowerAsyncIteration(shared))],
+			..Default::default()
+		}
+	})
+}
+
+const ASYNC_GENERATOR: &str = "function (thisArg, args, body) {
+	var iterator = body.apply(thisArg, args || []), queue = [], delegate, result = {};
+	result.next = verb(\"next\");
+	result[\"throw\"] = verb(\"throw\");
+	result[\"return\"] = verb(\"return\");
+	if (typeof Symbol === \"function\" && Symbol.asyncIterator) result[Symbol.asyncIterator] = function () { return this; };
+	return result;
+	function verb(name) {
+		return function (value) {
+			return new Promise(function (resolve, reject) {
+				if (queue.push([name, value, resolve, reject]) === 1) resume(name, value);
+			});
+		};
+	}
+	function resume(name, value) {
+		var step;
+		if (delegate) {
+			var method = delegate[name];
+			if (!method) {
+				var inner = delegate;
+				delegate = void 0;
+				if (name === \"return\") return resume(name, value);
+				if (inner[\"return\"]) inner[\"return\"]();
+				return resume(\"throw\", new TypeError(\"The iterator does not provide a 'throw' method\"));
+			}
+			try { step = method.call(delegate, value); } catch (error) { delegate = void 0; return resume(\"throw\", error); }
+			Promise.resolve(step).then(function (step) {
+				if (!step.done) return settle(0, { value: step.value, done: false });
+				delegate = void 0;
+				resume(name === \"return\" ? name : \"next\", step.value);
+			}, function (error) { delegate = void 0; resume(\"throw\", error); });
+			return;
+		}
+		try { step = iterator[name](value); } catch (error) { return settle(1, error); }
+		if (step.done) {
+			Promise.resolve(step.value).then(function (value) { settle(0, { value: value, done: true }); }, function (error) { settle(1, error); });
+			return;
+		}
+		var kind = step.value[0], operand = step.value[1];
+		if (kind === 2) {
+			delegate = operand;
+			return resume(\"next\", void 0);
+		}
+		Promise.resolve(operand).then(function (value) {
+			if (kind === 1) settle(0, { value: value, done: false });
+			else resume(\"next\", value);
+		}, function (error) { resume(\"throw\", error); });
+	}
+	function settle(rejected, value) {
+		var request = queue.shift();
+		(rejected ? request[3] : request[2])(value);
+		if (queue.length) resume(queue[0][0], queue[0][1]);
+	}
+}";
+
+const ASYNC_VALUES: &str = "function (iterable) {
+	var hasSymbol = typeof Symbol === \"function\", method = hasSymbol && Symbol.asyncIterator && iterable[Symbol.asyncIterator];
+	if (method) return method.call(iterable);
+	method = hasSymbol && iterable[Symbol.iterator];
+	if (!method) throw new TypeError(\"Object is not async iterable\");
+	var iterator = method.call(iterable), result = { next: verb(\"next\") };
+	if (iterator[\"throw\"]) result[\"throw\"] = verb(\"throw\");
+	if (iterator[\"return\"]) result[\"return\"] = verb(\"return\");
+	return result;
+	function verb(name) {
+		return function (value) {
+			return new Promise(function (resolve) {
+				var step = iterator[name](value);
+				resolve(Promise.resolve(step.value).then(function (value) { return { value: value, done: step.done }; }));
+			});
+		};
+	}
+}";
+
+struct AsyncIteration {
+	temporaries: SharedTemporaries,
+	/// Bodies of the generators which replaced async generators
+	bodies: HashSet<BlockId>,
+	/// `yield`s added for `await`, which are already lowered
+	lowered: HashSet<ExpressionId>,
+}
+
+impl LowerFunction for AsyncIteration {
+	fn lower<T: LowerableFunction>(
+		&mut self,
+		function: &mut FunctionBase<T>,
+		functions: &mut ExtractedFunctions,
+		data: &mut Vec<TempDiagnostic>,
+	) {
+		if !T::is_async(function) || !T::is_generator(function) {
+			return;
+		}
+		if uses_super(T::body_ref(function), functions) {
+			data.push(warning(
+				"Async generators which use 'super' are not lowered".to_owned(),
+				function.get_position().into_owned(),
+			));
+			return;
+		}
+		let arguments = references_arguments(T::body_ref(function), functions);
+		T::remove_async(function);
+		T::remove_generator(function);
+		let Block(mut items, _, position) = T::take_body(function);
+		let body = items.split_off(items.iter().take_while(|item| is_directive(item)).count());
+		let (generator, block) =
+			super::async_functions::generator_expression(body, &position, functions);
+		self.bodies.insert(block);
+
+		let helper = self.temporaries.borrow_mut().helper("__asyncGenerator", ASYNC_GENERATOR);
+		let arguments =
+			if arguments { reference("arguments", &position) } else { void_zero(&position) };
+		let this = Expression::ThisReference(position.clone(), ExpressionId::new());
+		let value =
+			call(reference(&helper, &position), vec![this, arguments, generator], &position);
+		T::set_body(function, items, value);
+	}
+}
+
+struct LowerAsyncIteration(Rc<RefCell<AsyncIteration>>);
+
+impl<'a> parser::VisitorMut<BlockLikeMut<'a>, Vec<TempDiagnostic>> for LowerAsyncIteration {
+	fn visit_mut(
+		&mut self,
+		item: &mut BlockLikeMut<'a>,
+		data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		lower_declared_functions(item.items, functions, data, &mut *self.0.borrow_mut());
+	}
+}
+
+/// `for await` (with any labels on it) to a loop calling `next`
+impl parser::VisitorMut<Statement, Vec<TempDiagnostic>> for LowerAsyncIteration {
+	fn visit_mut(
+		&mut self,
+		item: &mut Statement,
+		_data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		chain: &Chain,
+	) {
+		let mut labels = Vec::new();
+		let mut statement = &*item;
+		while let Statement::Labelled { name, statement: inner, .. } = statement {
+			labels.push(name.clone());
+			statement = inner;
+		}
+		let Statement::ForLoopStatement(ForLoopStatement {
+			condition: ForLoopCondition::ForOf { is_await: true, .. },
+			position,
+			..
+		}) = statement
+		else {
+			return;
+		};
+		let position = position.clone();
+		let mut statement = std::mem::replace(item, Statement::Debugger(position.clone()));
+		while let Statement::Labelled { statement: inner, .. } = statement {
+			statement = *inner;
+		}
+		let Statement::ForLoopStatement(ForLoopStatement {
+			condition: ForLoopCondition::ForOf { keyword, variable, of, .. },
+			inner,
+			..
+		}) = statement
+		else {
+			unreachable!()
+		};
+
+		let temporaries = self.0.borrow().temporaries.clone();
+		let mut temporaries = temporaries.borrow_mut();
+		let values = temporaries.helper("__asyncValues", ASYNC_VALUES);
+		let iterator = temporaries.temporary(chain);
+		let result = temporaries.temporary(chain);
+		drop(temporaries);
+		let iterator_member =
+			|property| member(reference(&iterator, &position), property, &position);
+		let result_member = |property| member(reference(&result, &position), property, &position);
+		let await_call = |function| Expression::UnaryOperation {
+			operator: UnaryOperator::Await,
+			operand: Box::new(call(function, Vec::new(), &position)),
+			position: position.clone(),
+			id: ExpressionId::new(),
+		};
+
+		// `_b = await _a.next(); if (_b.done) break; const x = _b.value;`
+		let next = assign(&result, await_call(iterator_member("next")), &position);
+		let done = if_statement(result_member("done"), Statement::Break(None, position.clone()));
+		let shadowed = shadows(&inner, variable.get_ast(), functions);
+		let variable = loop_variable(keyword, variable, result_member("value"), &position);
+		let mut items = vec![expression_statement(next), done, variable];
+		match inner {
+			BlockOrSingleStatement::Braced(block) if shadowed => {
+				items.push(StatementOrDeclaration::Statement(Statement::Block(block)))
+			}
+			BlockOrSingleStatement::Braced(block) => items.extend(block.0),
+			BlockOrSingleStatement::SingleStatement(statement) => {
+				items.push(StatementOrDeclaration::Statement(*statement))
+			}
+		}
+		let mut loop_statement = Statement::WhileStatement(WhileStatement {
+			condition: Expression::BooleanLiteral(true, position.clone(), ExpressionId::new()),
+			inner: BlockOrSingleStatement::Braced(Block(items, BlockId::new(), position.clone())),
+			position: position.clone(),
+		});
+		for name in labels.into_iter().rev() {
+			loop_statement = Statement::Labelled {
+				position: position.clone(),
+				name,
+				statement: Box::new(loop_statement),
+			};
+		}
+
+		// `if (_b && !_b.done && _a.return) await _a.return();`
+		let not_done = Expression::UnaryOperation {
+			operator: UnaryOperator::LogicalNot,
+			operand: Box::new(result_member("done")),
+			position: position.clone(),
+			id: ExpressionId::new(),
+		};
+		let condition = [not_done, iterator_member("return")].into_iter().fold(
+			reference(&result, &position),
+			|lhs, rhs| Expression::BinaryOperation {
+				lhs: Box::new(lhs),
+				operator: BinaryOperator::LogicalAnd,
+				rhs: Box::new(rhs),
+				id: ExpressionId::new(),
+			},
+		);
+		let close = await_call(iterator_member("return"));
+		let close = if_statement(condition, Statement::Expression(close.into()));
+
+		let start =
+			assign(&iterator, call(reference(&values, &position), vec![of], &position), &position);
+		let try_statement = Statement::TryCatchStatement(TryCatchStatement {
+			try_inner: block(vec![StatementOrDeclaration::Statement(loop_statement)], &position),
+			exception_var: None,
+			catch_inner: None,
+			finally_inner: Some(block(vec![close], &position)),
+			position: position.clone(),
+		});
+		let items =
+			vec![expression_statement(start), StatementOrDeclaration::Statement(try_statement)];
+		*item = Statement::Block(block(items, &position));
+	}
+}
+
+impl parser::VisitorMut<Expression, Vec<TempDiagnostic>> for LowerAsyncIteration {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		chain: &Chain,
+	) {
+		let mut state = self.0.borrow_mut();
+		lower_expression_functions(item, functions, data, &mut *state);
+		let in_lowered_body =
+			chain.function_block_id(true).is_some_and(|block| state.bodies.contains(&block));
+		if !in_lowered_body {
+			return;
+		}
+		let Expression::UnaryOperation { operator, operand, position, id } = item else { return };
+		let position = position.clone();
+		let kind = match operator {
+			UnaryOperator::Await => 0,
+			UnaryOperator::Yield if state.lowered.remove(id) => return,
+			UnaryOperator::Yield => 1,
+			UnaryOperator::DelegatedYield => 2,
+			_ => return,
+		};
+		let mut value = take(operand, &position);
+		if kind == 2 {
+			let values = state.temporaries.borrow_mut().helper("__asyncValues", ASYNC_VALUES);
+			value = call(reference(&values, &position), vec![value], &position);
+		}
+		let number = Expression::NumberLiteral(
+			parser::NumberStructure::Number(f64::from(kind)),
+			position.clone(),
+			ExpressionId::new(),
+		);
+		**operand = Expression::ArrayLiteral(
+			vec![SpreadExpression::NonSpread(number), SpreadExpression::NonSpread(value)],
+			position.clone(),
+			ExpressionId::new(),
+		);
+		*operator = UnaryOperator::Yield;
+		if kind == 0 {
+			// `await` binds tighter than `yield`
+			state.lowered.insert(*id);
+			let yielded = take(item, &position);
+			*item = parenthesize(yielded, &position);
+		}
+	}
+}
+
+fn expression_statement(expression: Expression) -> StatementOrDeclaration {
+	StatementOrDeclaration::Statement(Statement::Expression(expression.into()))
+}
+
+fn block(items: Vec<StatementOrDeclaration>, position: &Span) -> Block {
+	Block(items, BlockId::new(), position.clone())
+}
+
+/// `if (condition) statement`
+fn if_statement(condition: Expression, statement: Statement) -> StatementOrDeclaration {
+	let position = condition.get_position().into_owned();
+	StatementOrDeclaration::Statement(Statement::IfStatement(IfStatement {
+		condition: condition.into(),
+		inner: BlockOrSingleStatement::SingleStatement(Box::new(statement)),
+		else_conditions: Vec::new(),
+		trailing_else: None,
+		position,
+	}))
+}
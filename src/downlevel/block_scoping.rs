@@ -0,0 +1,1147 @@
+//! `let` and `const` to `var`. Variables are moved to the function they are in, so a variable
+//! which would clash with another of the same name in the function (or shadow one it references)
+//! is renamed, `{ let x = 1 } let x = 2` to `{ var x_1 = 1 } var x = 2`. `let`s without a value
+//! in loops are given `void 0`, so that they do not keep the value of the last iteration.
+//!
+//! Variables in loops which are captured by functions have a binding for each iteration, so the
+//! body of the loop is moved to a function which is called for each iteration, with the variables
+//! of the loop as parameters.
+//!
+//! ```js
+//! var _loop = function (i) { if (i === 2) return "break"; callbacks.push(function () { return i }) };
+//! for (var i = 0; i < 3; i++) { var _state = _loop(i); if (_state === "break") break; }
+//! ```
+//!
+//! `break`, `continue` and `return` in the body are returned to the loop, `var`s in it are
+//! declared before the loop and loop variables assigned in it are copied back. Bodies using
+//! `arguments`, `super`, `yield` or `await` are kept (with a warning), as are variables which
+//! cannot be found again by position. The temporal dead zone is not checked
+
+use std::{
+	cell::RefCell,
+	collections::{HashMap, HashSet},
+	rc::Rc,
+};
+
+use parser::{
+	declarations::{
+		export::Exportable, variable::VariableDeclarationKeyword, ExportDeclaration,
+		VariableDeclaration, VariableDeclarationItem,
+	},
+	expressions::{
+		assignments::{LHSOfAssignment, VariableOrPropertyAccess},
+		object_literal::{ObjectLiteral, ObjectLiteralMember},
+		ExpressionFunctionBase, ExpressionId, MultipleExpression,
+	},
+	extractor::{ExtractedFunctions, GetFunction},
+	operators::{BinaryOperator, UnaryOperator},
+	statements::{
+		DoWhileStatement, ForLoopCondition, ForLoopStatement, ForLoopStatementInitializer,
+		IfStatement, SwitchBranch, TryCatchStatement, VarVariableStatement, WhileStatement,
+	},
+	ASTNode, Block, BlockId, BlockLikeMut, BlockOrSingleStatement, Chain, Declaration, Decorated,
+	Expression, FunctionBase, FunctionId, FunctionParameters, Keyword, NodeRef, Parameter,
+	PropertyId, PropertyKey, Quoted, Span, Statement, StatementOrDeclaration, VariableField,
+	VariableFieldInSourceCode, VariableId, VariableIdentifier, VisitorsMut, WithComment,
+};
+
+use super::{
+	any_in_function, assign, assign_to, call, declaration_item, function_header, member, reference,
+	void_zero, warning, SharedTemporaries,
+};
+use crate::{
+	bundle::{
+		array_fields_identifiers, object_fields_identifiers, variable_declaration_identifiers,
+		variable_field_identifiers, Renamer, Renames,
+	},
+	error_handling::TempDiagnostic,
+	minify::scope::{Binding, Scopes},
+};
+
+/// Passes for [crate::temp::Transform::LowerBlockScoping]
+pub(crate) fn passes() -> Vec<VisitorsMut<Vec<TempDiagnostic>>> {
+	let lowered = Rc::new(RefCell::new(Lowered::default()));
+	let renamer = Renamer(Rc::new(RefCell::new(Renames::default())));
+	let captured = Captured::default();
+	let mut shared = None;
+	let mut passes = super::passes(|temporaries| {
+		shared = Some(temporaries.clone());
+		let loops = LoopFunctions { temporaries: temporaries.clone(), captured: captured.clone() };
+		VisitorsMut {
+			block_visitors_mut: vec![Box::new(loops.clone())],
+			statement_visitors_mut: vec![Box::new(loops)],
+			..Default::default()
+		}
+	});
+	// Variables are analysed after loop bodies are moved to functions
+	passes.push(VisitorsMut {
+		block_visitors_mut: vec![Box::new(Analyse {
+			temporaries: shared.unwrap(),
+			captured,
+			lowered: lowered.clone(),
+			renames: renamer.0.clone(),
+			is_module: true,
+		})],
+		..Default::default()
+	});
+	// Declarations are found by their original name, so they are lowered before being renamed
+	let convert = LowerDeclarations(lowered);
+	passes.push(VisitorsMut {
+		expression_visitors_mut: vec![Box::new(renamer.clone())],
+		statement_visitors_mut: vec![Box::new(convert.clone()), Box::new(renamer.clone())],
+		jsx_element_visitors_mut: vec![Box::new(renamer.clone())],
+		block_visitors_mut: vec![Box::new(convert), Box::new(renamer)],
+		..Default::default()
+	});
+	passes
+}
+
+/// The variables which become `var`s, with whether they need a value when declared without one
+#[derive(Default)]
+struct Lowered {
+	/// By the start and name of their declaring identifiers
+	declarations: HashMap<(u32, String), bool>,
+	/// Variables added by lowering without a position, by name
+	generated: HashMap<String, bool>,
+}
+
+impl Lowered {
+	fn get(&self, name: &str, position: &Span) -> Option<bool> {
+		if position.is_null() {
+			self.generated.get(name).copied()
+		} else {
+			self.declarations.get(&(position.start, name.to_owned())).copied()
+		}
+	}
+
+	fn lowers(&self, identifiers: &[(String, Span)]) -> bool {
+		identifiers.iter().all(|(name, position)| self.get(name, position).is_some())
+	}
+}
+
+/// Analyses the module, which is the first block visited
+struct Analyse {
+	temporaries: SharedTemporaries,
+	captured: Captured,
+	lowered: Rc<RefCell<Lowered>>,
+	renames: Rc<RefCell<Renames>>,
+	is_module: bool,
+}
+
+impl<'a> parser::VisitorMut<BlockLikeMut<'a>, Vec<TempDiagnostic>> for Analyse {
+	fn visit_mut(
+		&mut self,
+		item: &mut BlockLikeMut<'a>,
+		data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		if !std::mem::take(&mut self.is_module) {
+			return;
+		}
+		let scopes = Scopes::new(item.items, functions);
+		let used = &mut self.temporaries.borrow_mut().used;
+		let captured = self.captured.borrow();
+		let captured = captured.as_ref().expect("captured variables not found");
+		let (lowered, renames) = analyse(&scopes, captured, used, data);
+		*self.lowered.borrow_mut() = lowered;
+		self.renames.borrow_mut().names = renames;
+	}
+}
+
+/// Chooses which variables are lowered and the names of those which clash
+fn analyse(
+	scopes: &Scopes,
+	captured: &HashSet<(u32, String)>,
+	used: &mut HashSet<String>,
+	data: &mut Vec<TempDiagnostic>,
+) -> (Lowered, HashMap<(u32, String), String>) {
+	let Scopes { scopes, bindings } = scopes;
+
+	// Only `let` and `const` declarations are lowered, so other bindings sharing their identifiers
+	// only stop them being renamed
+	let ambiguous_declarations = ambiguous(bindings, |binding| {
+		if binding.lexical {
+			binding.declarations.iter().collect()
+		} else {
+			Vec::new()
+		}
+	});
+	let ambiguous = ambiguous(bindings, |binding| binding.occurrences().collect());
+
+	let mut lowers = bindings
+		.iter()
+		.zip(&ambiguous_declarations)
+		.map(|(binding, ambiguous)| {
+			// Functions for loop bodies are called in the iteration, so only variables captured
+			// in the source (and which are still in loops) are kept
+			let captured_in_loop = scopes[binding.scope].in_loop
+				&& binding.captured
+				&& binding
+					.declarations
+					.iter()
+					.any(|position| captured.contains(&(position.start, binding.name.clone())));
+			if binding.lexical && captured_in_loop {
+				let position = binding.declarations.first().filter(|position| !position.is_null());
+				if let Some(position) = position {
+					data.push(warning(
+						"Block scoped variables captured in loops are not lowered".to_owned(),
+						position.clone(),
+					));
+				}
+			}
+			binding.lexical && !captured_in_loop && !ambiguous
+		})
+		.collect::<Vec<_>>();
+
+	// The function (or module) scope each scope's variables are moved to
+	let targets = scopes
+		.iter()
+		.enumerate()
+		.map(|(index, _)| {
+			let mut target = index;
+			while !scopes[target].function {
+				target = scopes[target].parent.expect("no function scope");
+			}
+			target
+		})
+		.collect::<Vec<_>>();
+
+	// Names declared in each function scope, which moved variables cannot reuse
+	let mut claimed = scopes
+		.iter()
+		.map(|scope| scope.bindings.iter().map(|binding| bindings[*binding].name.clone()).collect())
+		.collect::<Vec<HashSet<String>>>();
+	let mut names = bindings.iter().map(|binding| binding.name.clone()).collect::<Vec<_>>();
+	for (index, binding) in bindings.iter().enumerate() {
+		let target = targets[binding.scope];
+		if !lowers[index] || target == binding.scope {
+			continue;
+		}
+		// Variables in the body of a function already shadow those outside of it
+		let mut shadows = false;
+		let mut scope = scopes[binding.scope].parent.filter(|parent| *parent != target);
+		while let Some(index) = scope {
+			let declared = scopes[index].bindings.iter();
+			shadows |=
+				declared.map(|other| &bindings[*other].name).any(|name| *name == binding.name);
+			scope = scopes[index].parent;
+		}
+		let clashes = shadows
+			|| claimed[target].contains(&binding.name)
+			|| scopes[target].free.contains(&binding.name);
+		if clashes {
+			let renamable = !scopes[binding.scope].has_eval
+				&& !ambiguous[index]
+				&& !binding.occurrences().any(Span::is_null);
+			if !renamable {
+				lowers[index] = false;
+				continue;
+			}
+			names[index] = (1..)
+				.map(|n| format!("{}_{n}", binding.name))
+				.find(|name| !used.contains(name))
+				.unwrap();
+			used.insert(names[index].clone());
+		}
+		claimed[target].insert(names[index].clone());
+	}
+
+	let mut lowered = Lowered::default();
+	let mut kept = HashSet::new();
+	let mut renames = HashMap::new();
+	for ((binding, lowers), name) in bindings.iter().zip(lowers).zip(names) {
+		if !lowers {
+			if binding.declarations.iter().any(Span::is_null) {
+				kept.insert(binding.name.clone());
+			}
+			continue;
+		}
+		let needs_value = scopes[binding.scope].in_loop;
+		for position in binding.declarations.iter() {
+			if position.is_null() {
+				lowered.generated.insert(binding.name.clone(), needs_value);
+			} else {
+				lowered.declarations.insert((position.start, binding.name.clone()), needs_value);
+			}
+		}
+		if binding.name != name {
+			for position in binding.occurrences() {
+				renames.insert((position.start, binding.name.clone()), name.clone());
+			}
+		}
+	}
+	lowered.generated.retain(|name, _| !kept.contains(name));
+	(lowered, renames)
+}
+
+/// Bindings with an identifier of the same name and position as another binding, which cannot be
+/// told apart. Only the identifiers from `identifiers` are compared
+fn ambiguous<'a>(
+	bindings: &'a [Binding],
+	identifiers: impl Fn(&'a Binding) -> Vec<&'a Span>,
+) -> Vec<bool> {
+	let mut seen = HashMap::new();
+	let mut ambiguous = vec![false; bindings.len()];
+	for (index, binding) in bindings.iter().enumerate() {
+		for position in identifiers(binding).into_iter().filter(|position| !position.is_null()) {
+			if let Some(other) = seen.insert((position.start, binding.name.as_str()), index) {
+				if other != index {
+					ambiguous[index] = true;
+					ambiguous[other] = true;
+				}
+			}
+		}
+	}
+	ambiguous
+}
+
+/// Replaces the `let` and `const` declarations of lowered variables with `var`
+#[derive(Clone)]
+struct LowerDeclarations(Rc<RefCell<Lowered>>);
+
+impl LowerDeclarations {
+	fn lowers(&self, declaration: &VariableDeclaration) -> bool {
+		let mut identifiers = Vec::new();
+		variable_declaration_identifiers(declaration, &mut identifiers);
+		self.0.borrow().lowers(&identifiers)
+	}
+
+	fn lower(&self, declaration: VariableDeclaration) -> VarVariableStatement {
+		let lowered = self.0.borrow();
+		let (position, declarations) = match declaration {
+			VariableDeclaration::ConstDeclaration { keyword, declarations } => {
+				let declarations = declarations.into_iter().map(|item| VariableDeclarationItem {
+					name: item.name,
+					type_reference: item.type_reference,
+					expression: Some(item.expression),
+				});
+				(keyword.1, declarations.collect())
+			}
+			VariableDeclaration::LetDeclaration { keyword, mut declarations } => {
+				for item in declarations.iter_mut().filter(|item| item.expression.is_none()) {
+					let VariableField::Name(VariableIdentifier::Standard(name, _, position)) =
+						item.name.get_ast()
+					else {
+						continue;
+					};
+					if lowered.get(name, position) == Some(true) {
+						item.expression = Some(void_zero(position));
+					}
+				}
+				(keyword.1, declarations)
+			}
+		};
+		VarVariableStatement { keyword: Keyword::new(position), declarations }
+	}
+
+	fn lowers_field(&self, field: &VariableField<VariableFieldInSourceCode>) -> bool {
+		let mut identifiers = Vec::new();
+		variable_field_identifiers(field, &mut identifiers);
+		self.0.borrow().lowers(&identifiers)
+	}
+}
+
+impl<'a> parser::VisitorMut<BlockLikeMut<'a>, Vec<TempDiagnostic>> for LowerDeclarations {
+	fn visit_mut(
+		&mut self,
+		item: &mut BlockLikeMut<'a>,
+		_data: &mut Vec<TempDiagnostic>,
+		_functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		for item in item.items.iter_mut() {
+			let StatementOrDeclaration::Declaration(declaration) = item else { continue };
+			match declaration {
+				Declaration::Variable(declaration) if self.lowers(declaration) => {
+					let declaration = declaration.clone();
+					*item = StatementOrDeclaration::Statement(Statement::VarVariable(
+						self.lower(declaration),
+					));
+				}
+				Declaration::Export(Decorated {
+					on: ExportDeclaration::Variable { exported, .. },
+					..
+				}) => {
+					let Exportable::Variable(declaration) = exported else { continue };
+					if self.lowers(declaration) {
+						let declaration = declaration.clone();
+						*exported = Exportable::VarVariable(self.lower(declaration));
+					}
+				}
+				_ => {}
+			}
+		}
+	}
+}
+
+impl parser::VisitorMut<Statement, Vec<TempDiagnostic>> for LowerDeclarations {
+	fn visit_mut(
+		&mut self,
+		item: &mut Statement,
+		_data: &mut Vec<TempDiagnostic>,
+		_functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		let Statement::ForLoopStatement(for_statement) = item else { return };
+		match &mut for_statement.condition {
+			ForLoopCondition::ForOf { keyword: Some(keyword), variable, .. }
+			| ForLoopCondition::ForIn { keyword: Some(keyword), variable, .. } => {
+				let position = match keyword {
+					VariableDeclarationKeyword::Const(keyword) => keyword.1.clone(),
+					VariableDeclarationKeyword::Let(keyword) => keyword.1.clone(),
+					VariableDeclarationKeyword::Var(_) => return,
+				};
+				if self.lowers_field(variable.get_ast()) {
+					*keyword = VariableDeclarationKeyword::Var(Keyword::new(position));
+				}
+			}
+			ForLoopCondition::Statements { initializer, .. } => {
+				let Some(ForLoopStatementInitializer::Statement(declaration)) = initializer else {
+					return;
+				};
+				if self.lowers(declaration) {
+					let declaration = declaration.clone();
+					*initializer =
+						Some(ForLoopStatementInitializer::VarStatement(self.lower(declaration)));
+				}
+			}
+			_ => {}
+		}
+	}
+}
+
+/// Moves the bodies of loops with `let` and `const` variables captured by functions to functions,
+/// so that each iteration has its own variables
+#[derive(Clone)]
+struct LoopFunctions {
+	temporaries: SharedTemporaries,
+	captured: Captured,
+}
+
+/// Declarations (by start and name) of variables captured in loops, found before loop bodies are
+/// moved to functions
+type Captured = Rc<RefCell<Option<HashSet<(u32, String)>>>>;
+
+impl<'a> parser::VisitorMut<BlockLikeMut<'a>, Vec<TempDiagnostic>> for LoopFunctions {
+	fn visit_mut(
+		&mut self,
+		item: &mut BlockLikeMut<'a>,
+		_data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		if self.captured.borrow().is_none() {
+			let Scopes { scopes, bindings } = Scopes::new(item.items, functions);
+			let captured = bindings
+				.iter()
+				.filter(|binding| {
+					binding.lexical && binding.captured && scopes[binding.scope].in_loop
+				})
+				.flat_map(|binding| {
+					let declarations = binding.declarations.iter();
+					let declarations = declarations.filter(|position| !position.is_null());
+					declarations.map(|position| (position.start, binding.name.clone()))
+				});
+			*self.captured.borrow_mut() = Some(captured.collect());
+		}
+		let mut index = 0;
+		while index < item.items.len() {
+			if let StatementOrDeclaration::Statement(statement) = &mut item.items[index] {
+				if let Some(declaration) = self.lower_loop(statement, functions) {
+					item.items.insert(index, StatementOrDeclaration::Statement(declaration));
+					index += 1;
+				}
+			}
+			index += 1;
+		}
+	}
+}
+
+impl parser::VisitorMut<Statement, Vec<TempDiagnostic>> for LoopFunctions {
+	fn visit_mut(
+		&mut self,
+		item: &mut Statement,
+		_data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		// Loops which are not in a block are put in one, which the function is declared in
+		let inners = match item {
+			Statement::IfStatement(statement) => {
+				let mut inners = vec![&mut statement.inner];
+				inners.extend(statement.else_conditions.iter_mut().map(|branch| &mut branch.inner));
+				inners.extend(statement.trailing_else.iter_mut().map(|branch| &mut branch.inner));
+				inners
+			}
+			Statement::ForLoopStatement(ForLoopStatement { inner, .. })
+			| Statement::WhileStatement(WhileStatement { inner, .. })
+			| Statement::DoWhileStatement(DoWhileStatement { inner, .. }) => vec![inner],
+			Statement::SwitchStatement(statement) => {
+				for branch in statement.branches.iter_mut() {
+					let (SwitchBranch::Default(statements) | SwitchBranch::Case(_, statements)) =
+						branch;
+					let mut index = 0;
+					while index < statements.len() {
+						if let Some(declaration) =
+							self.lower_loop(&mut statements[index], functions)
+						{
+							statements.insert(index, declaration);
+							index += 1;
+						}
+						index += 1;
+					}
+				}
+				return;
+			}
+			_ => return,
+		};
+		for inner in inners {
+			if let BlockOrSingleStatement::SingleStatement(statement) = inner {
+				if self.needs_function(labelled_loop(statement).1) {
+					let position = statement.get_position().into_owned();
+					let statement = std::mem::replace(&mut **statement, empty_statement(&position));
+					let items = vec![StatementOrDeclaration::Statement(statement)];
+					*inner = BlockOrSingleStatement::Braced(Block(items, BlockId::new(), position));
+				}
+			}
+		}
+	}
+}
+
+impl LoopFunctions {
+	/// Whether a loop declares a variable which is captured in it, not counting variables of loops
+	/// in it
+	fn needs_function(&self, statement: &Statement) -> bool {
+		let mut declarations = Vec::new();
+		let inner = match statement {
+			Statement::ForLoopStatement(ForLoopStatement { condition, inner, .. }) => {
+				loop_variables(condition, &mut declarations);
+				inner
+			}
+			Statement::WhileStatement(WhileStatement { inner, .. })
+			| Statement::DoWhileStatement(DoWhileStatement { inner, .. }) => inner,
+			_ => return false,
+		};
+		match inner {
+			BlockOrSingleStatement::Braced(block) => {
+				block_declarations(&block.0, &mut declarations)
+			}
+			BlockOrSingleStatement::SingleStatement(statement) => {
+				statement_declarations(statement, &mut declarations)
+			}
+		}
+		let captured = self.captured.borrow();
+		let Some(captured) = captured.as_ref() else { return false };
+		declarations.into_iter().any(|(name, position)| captured.contains(&(position.start, name)))
+	}
+
+	/// Moves the body of a (labelled) loop to a function if it needs one, returning the declaration
+	/// of the function to put before the loop
+	fn lower_loop(
+		&self,
+		statement: &mut Statement,
+		functions: &mut ExtractedFunctions,
+	) -> Option<Statement> {
+		let (labels, statement) = labelled_loop(statement);
+		if !self.needs_function(statement) {
+			return None;
+		}
+		let position = statement.get_position().into_owned();
+		let (variables, copied, inner) = match statement {
+			Statement::ForLoopStatement(ForLoopStatement { condition, inner, .. }) => {
+				let mut variables = Vec::new();
+				loop_variables(condition, &mut variables);
+				// Assignments to the variables of the next iteration are kept
+				let copied = match condition {
+					ForLoopCondition::Statements {
+						initializer:
+							Some(ForLoopStatementInitializer::Statement(
+								VariableDeclaration::LetDeclaration { .. },
+							)),
+						..
+					} => variables
+						.iter()
+						.filter(|(name, _)| assigns(inner_node(inner), name, functions))
+						.cloned()
+						.collect(),
+					_ => Vec::new(),
+				};
+				(variables, copied, inner)
+			}
+			Statement::WhileStatement(WhileStatement { inner, .. })
+			| Statement::DoWhileStatement(DoWhileStatement { inner, .. }) => (Vec::new(), Vec::new(), inner),
+			_ => unreachable!(),
+		};
+		let movable = !any_in_function(inner_node(inner), functions, true, &|node| {
+			matches!(
+				node,
+				NodeRef::Expression(
+					Expression::SuperExpression(..)
+						| Expression::NewTarget(..)
+						| Expression::UnaryOperation {
+							operator: UnaryOperator::Yield
+								| UnaryOperator::DelegatedYield
+								| UnaryOperator::Await,
+							..
+						}
+				) | NodeRef::Statement(Statement::ForLoopStatement(ForLoopStatement {
+					condition: ForLoopCondition::ForOf { is_await: true, .. },
+					..
+				}))
+			) || matches!(node, NodeRef::Expression(Expression::VariableReference(name, ..)) if name == "arguments")
+		});
+		if !movable {
+			return None;
+		}
+		let uses_this = any_in_function(inner_node(inner), functions, true, &|node| {
+			matches!(node, NodeRef::Expression(Expression::ThisReference(..)))
+		});
+
+		let mut items = match inner.clone() {
+			BlockOrSingleStatement::Braced(block) => block.0,
+			BlockOrSingleStatement::SingleStatement(statement) => {
+				vec![StatementOrDeclaration::Statement(*statement)]
+			}
+		};
+		let mut exits = Exits { labels, ..Default::default() };
+		exits.items(&mut items).ok()?;
+
+		let mut temporaries = self.temporaries.borrow_mut();
+		let function_name = temporaries.unique("_loop");
+		let copies = copied
+			.into_iter()
+			.map(|(name, declaration)| {
+				let copy = temporaries.unique(&format!("_{name}"));
+				(name, declaration, copy)
+			})
+			.collect::<Vec<_>>();
+
+		// `function (i) { ... }`, with `try { ... } finally { _i = i }` for copied variables
+		if !copies.is_empty() {
+			let assignments = copies.iter().map(|(name, _, copy)| {
+				let value = reference(name, &Span::NULL_SPAN);
+				expression_statement(assign(copy, value, &position))
+			});
+			let finally = Block(assignments.collect(), BlockId::new(), position.clone());
+			let statement = Statement::TryCatchStatement(TryCatchStatement {
+				try_inner: Block(items, BlockId::new(), position.clone()),
+				exception_var: None,
+				catch_inner: None,
+				finally_inner: Some(finally),
+				position: position.clone(),
+			});
+			items = vec![StatementOrDeclaration::Statement(statement)];
+		}
+		let parameters = variables.iter().map(|(name, _)| Parameter {
+			name: WithComment::None(VariableField::Name(VariableIdentifier::Standard(
+				name.clone(),
+				VariableId::new(),
+				Span::NULL_SPAN,
+			))),
+			type_reference: None,
+			property: None,
+		});
+		let function = FunctionBase::<ExpressionFunctionBase> {
+			function_id: FunctionId::new(),
+			header: function_header(None, None, &position),
+			name: None,
+			type_parameters: None,
+			parameters: FunctionParameters {
+				parameters: parameters.collect(),
+				optional_parameters: Vec::new(),
+				rest_parameter: None,
+				position: position.clone(),
+			},
+			return_type: None,
+			body: Block(items, BlockId::new(), position.clone()),
+		};
+		let function =
+			Expression::ExtractedExpressionFunction(functions.new_extracted_function(function));
+
+		// `var a, _i, _loop = function (i) { ... }`
+		let mut declarations = exits
+			.hoisted
+			.iter()
+			.map(|(name, declaration)| declaration_item(name.clone(), declaration.clone(), None))
+			.collect::<Vec<_>>();
+		declarations.extend(
+			copies
+				.iter()
+				.map(|(_, _, copy)| declaration_item(copy.clone(), position.clone(), None)),
+		);
+		declarations.push(declaration_item(
+			function_name.clone(),
+			position.clone(),
+			Some(function),
+		));
+		let declaration = Statement::VarVariable(VarVariableStatement {
+			keyword: Keyword::new(position.clone()),
+			declarations,
+		});
+
+		// `_loop(i)` or `_loop.call(this, i)`, where the arguments are the variables of the loop
+		let arguments = variables.iter().map(|(name, declaration)| reference(name, declaration));
+		let call_loop = if uses_this {
+			let this = Expression::ThisReference(position.clone(), ExpressionId::new());
+			let function = member(reference(&function_name, &position), "call", &position);
+			call(function, std::iter::once(this).chain(arguments).collect(), &position)
+		} else {
+			call(reference(&function_name, &position), arguments.collect(), &position)
+		};
+		let mut body = Vec::new();
+		let state = if exits.returns || exits.breaks || !exits.jumps.is_empty() {
+			let state = temporaries.unique("_state");
+			let declarations =
+				vec![declaration_item(state.clone(), position.clone(), Some(call_loop))];
+			let keyword = Keyword::new(position.clone());
+			let statement = Statement::VarVariable(VarVariableStatement { keyword, declarations });
+			body.push(StatementOrDeclaration::Statement(statement));
+			Some(state)
+		} else {
+			body.push(expression_statement(call_loop));
+			None
+		};
+		for (name, declaration, copy) in copies.iter() {
+			// Copied with the position of the declaration, so that it is renamed with it
+			let target = VariableOrPropertyAccess::Variable(
+				name.clone(),
+				declaration.clone(),
+				ExpressionId::new(),
+			);
+			body.push(expression_statement(assign_to(target, reference(copy, &position))));
+		}
+		if let Some(state) = state {
+			if exits.returns {
+				// `if (typeof _state === "object") return _state.value`
+				let type_of = Expression::UnaryOperation {
+					operator: UnaryOperator::TypeOf,
+					operand: Box::new(reference(&state, &position)),
+					position: position.clone(),
+					id: ExpressionId::new(),
+				};
+				let value = member(reference(&state, &position), "value", &position);
+				let statement =
+					Statement::Return(Keyword::new(position.clone()), Some(value.into()));
+				body.push(if_equals(type_of, "object", statement, &position));
+			}
+			if exits.breaks {
+				let statement = Statement::Break(None, position.clone());
+				body.push(if_equals(reference(&state, &position), "break", statement, &position));
+			}
+			for (is_continue, label) in exits.jumps {
+				let (kind, statement) = if is_continue {
+					("continue", Statement::Continue(Some(label.clone()), position.clone()))
+				} else {
+					("break", Statement::Break(Some(label.clone()), position.clone()))
+				};
+				let value = format!("{kind}-{label}");
+				body.push(if_equals(reference(&state, &position), &value, statement, &position));
+			}
+		}
+		*inner = BlockOrSingleStatement::Braced(Block(body, BlockId::new(), position));
+		Some(declaration)
+	}
+}
+
+/// `break`, `continue` and `return` in the body of a loop which is moved to a function, and the
+/// `var`s declared in it
+#[derive(Default)]
+struct Exits {
+	/// Labels of the loop
+	labels: Vec<String>,
+	/// Labels of statements in the body
+	inner_labels: Vec<String>,
+	/// Loops in the body around the statement being rewritten
+	loops: usize,
+	/// Loops and `switch` statements in the body around the statement being rewritten
+	breakables: usize,
+	returns: bool,
+	breaks: bool,
+	/// `continue` (`true`) and `break` with labels outside of the loop
+	jumps: Vec<(bool, String)>,
+	/// Variables declared with `var`, by name and position
+	hoisted: Vec<(String, Span)>,
+}
+
+impl Exits {
+	fn items(&mut self, items: &mut Vec<StatementOrDeclaration>) -> Result<(), ()> {
+		let mut index = 0;
+		while index < items.len() {
+			if let StatementOrDeclaration::Statement(statement) = &mut items[index] {
+				if let Statement::VarVariable(declaration) = statement {
+					let declaration = declaration.clone();
+					match self.hoist(declaration)? {
+						Some(assignments) => *statement = assignments,
+						None => {
+							items.remove(index);
+							continue;
+						}
+					}
+				} else {
+					self.statement(statement)?;
+				}
+			}
+			index += 1;
+		}
+		Ok(())
+	}
+
+	fn statements(&mut self, statements: &mut Vec<Statement>) -> Result<(), ()> {
+		let mut index = 0;
+		while index < statements.len() {
+			if let Statement::VarVariable(declaration) = &statements[index] {
+				match self.hoist(declaration.clone())? {
+					Some(assignments) => statements[index] = assignments,
+					None => {
+						statements.remove(index);
+						continue;
+					}
+				}
+			} else {
+				self.statement(&mut statements[index])?;
+			}
+			index += 1;
+		}
+		Ok(())
+	}
+
+	fn inner(&mut self, inner: &mut BlockOrSingleStatement) -> Result<(), ()> {
+		match inner {
+			BlockOrSingleStatement::Braced(block) => self.items(&mut block.0),
+			BlockOrSingleStatement::SingleStatement(statement) => self.statement(statement),
+		}
+	}
+
+	fn statement(&mut self, statement: &mut Statement) -> Result<(), ()> {
+		let position = statement.get_position().into_owned();
+		match statement {
+			Statement::Block(block) => self.items(&mut block.0)?,
+			Statement::IfStatement(statement) => {
+				self.inner(&mut statement.inner)?;
+				for branch in statement.else_conditions.iter_mut() {
+					self.inner(&mut branch.inner)?;
+				}
+				if let Some(branch) = &mut statement.trailing_else {
+					self.inner(&mut branch.inner)?;
+				}
+			}
+			Statement::TryCatchStatement(statement) => {
+				self.items(&mut statement.try_inner.0)?;
+				if let Some(block) = &mut statement.catch_inner {
+					self.items(&mut block.0)?;
+				}
+				if let Some(block) = &mut statement.finally_inner {
+					self.items(&mut block.0)?;
+				}
+			}
+			Statement::SwitchStatement(statement) => {
+				self.breakables += 1;
+				for branch in statement.branches.iter_mut() {
+					let (SwitchBranch::Default(statements) | SwitchBranch::Case(_, statements)) =
+						branch;
+					self.statements(statements)?;
+				}
+				self.breakables -= 1;
+			}
+			Statement::ForLoopStatement(ForLoopStatement { condition, inner, .. }) => {
+				match condition {
+					ForLoopCondition::Statements { initializer, .. } => {
+						if let Some(ForLoopStatementInitializer::VarStatement(declaration)) =
+							initializer
+						{
+							*initializer = match self.hoist(declaration.clone())? {
+								Some(Statement::Expression(assignments)) => {
+									let assignments = single(assignments, &position);
+									Some(ForLoopStatementInitializer::Expression(assignments))
+								}
+								_ => None,
+							};
+						}
+					}
+					ForLoopCondition::ForOf { keyword, variable, .. }
+					| ForLoopCondition::ForIn { keyword, variable, .. } => {
+						if let Some(VariableDeclarationKeyword::Var(_)) = keyword {
+							let VariableField::Name(VariableIdentifier::Standard(
+								name,
+								_,
+								position,
+							)) = variable.get_ast()
+							else {
+								return Err(());
+							};
+							self.hoisted.push((name.clone(), position.clone()));
+							*keyword = None;
+						}
+					}
+				}
+				self.in_loop(inner)?;
+			}
+			Statement::WhileStatement(WhileStatement { inner, .. })
+			| Statement::DoWhileStatement(DoWhileStatement { inner, .. }) => self.in_loop(inner)?,
+			Statement::Labelled { name, statement, .. } => {
+				self.inner_labels.push(name.clone());
+				self.statement(statement)?;
+				self.inner_labels.pop();
+			}
+			Statement::VarVariable(declaration) => {
+				*statement =
+					self.hoist(declaration.clone())?.unwrap_or_else(|| empty_statement(&position));
+			}
+			Statement::Return(_, value) => {
+				// `return { value: ... }`
+				self.returns = true;
+				let value = match value.take() {
+					Some(value) => single(value, &position),
+					None => void_zero(&position),
+				};
+				let key =
+					PropertyKey::Ident("value".to_owned(), PropertyId::new(), position.clone());
+				let object = Expression::ObjectLiteral(ObjectLiteral {
+					members: vec![ObjectLiteralMember::Property(
+						WithComment::None(key),
+						value,
+						position.clone(),
+					)],
+					position: position.clone(),
+					expression_id: ExpressionId::new(),
+				});
+				*statement = Statement::Return(Keyword::new(position.clone()), Some(object.into()));
+			}
+			Statement::Continue(label, _) => {
+				let exits = match label {
+					None => self.loops == 0,
+					Some(label) => !self.inner_labels.contains(label),
+				};
+				if exits {
+					let state = match label.take() {
+						Some(label) if !self.labels.contains(&label) => {
+							Some(self.jump(true, label))
+						}
+						_ => None,
+					};
+					*statement = self.exit(state, &position);
+				}
+			}
+			Statement::Break(label, _) => {
+				let exits = match label {
+					None => self.breakables == 0,
+					Some(label) => !self.inner_labels.contains(label),
+				};
+				if exits {
+					let state = match label.take() {
+						Some(label) if !self.labels.contains(&label) => self.jump(false, label),
+						_ => {
+							self.breaks = true;
+							"break".to_owned()
+						}
+					};
+					*statement = self.exit(Some(state), &position);
+				}
+			}
+			_ => {}
+		}
+		Ok(())
+	}
+
+	fn in_loop(&mut self, inner: &mut BlockOrSingleStatement) -> Result<(), ()> {
+		self.loops += 1;
+		self.breakables += 1;
+		let result = self.inner(inner);
+		self.loops -= 1;
+		self.breakables -= 1;
+		result
+	}
+
+	/// The state for a `break` or `continue` to a label outside of the loop
+	fn jump(&mut self, is_continue: bool, label: String) -> String {
+		let state = format!("{}-{label}", if is_continue { "continue" } else { "break" });
+		if !self.jumps.iter().any(|jump| jump.0 == is_continue && jump.1 == label) {
+			self.jumps.push((is_continue, label));
+		}
+		state
+	}
+
+	/// `return` or `return "break"` etc
+	fn exit(&self, state: Option<String>, position: &Span) -> Statement {
+		let value = state.map(|state| {
+			Expression::StringLiteral(state, Quoted::Double, position.clone(), ExpressionId::new())
+				.into()
+		});
+		Statement::Return(Keyword::new(position.clone()), value)
+	}
+
+	/// The assignments of a `var` declaration, which is declared before the loop
+	fn hoist(&mut self, declaration: VarVariableStatement) -> Result<Option<Statement>, ()> {
+		let mut assignments = Vec::new();
+		for item in declaration.declarations {
+			let VariableField::Name(VariableIdentifier::Standard(name, _, position)) =
+				item.name.get_ast()
+			else {
+				return Err(());
+			};
+			self.hoisted.push((name.clone(), position.clone()));
+			if let Some(value) = item.expression {
+				let target = VariableOrPropertyAccess::Variable(
+					name.clone(),
+					position.clone(),
+					ExpressionId::new(),
+				);
+				assignments.push(assign_to(target, value));
+			}
+		}
+		Ok((!assignments.is_empty()).then(|| Statement::Expression(super::sequence(assignments))))
+	}
+}
+
+/// The labels around a statement and the statement they label
+fn labelled_loop(mut statement: &mut Statement) -> (Vec<String>, &mut Statement) {
+	let mut labels = Vec::new();
+	while let Statement::Labelled { name, statement: labelled, .. } = statement {
+		labels.push(name.clone());
+		statement = labelled;
+	}
+	(labels, statement)
+}
+
+/// Variables declared with `let` or `const` by a `for` loop
+fn loop_variables(condition: &ForLoopCondition, out: &mut Vec<(String, Span)>) {
+	match condition {
+		ForLoopCondition::ForOf { keyword: Some(keyword), variable, .. }
+		| ForLoopCondition::ForIn { keyword: Some(keyword), variable, .. }
+			if !matches!(keyword, VariableDeclarationKeyword::Var(_)) =>
+		{
+			variable_field_identifiers(variable.get_ast(), out)
+		}
+		ForLoopCondition::Statements {
+			initializer: Some(ForLoopStatementInitializer::Statement(declaration)),
+			..
+		} => variable_declaration_identifiers(declaration, out),
+		_ => {}
+	}
+}
+
+/// `let` and `const` declarations in a block, not including those in loops or functions in it
+fn block_declarations(items: &[StatementOrDeclaration], out: &mut Vec<(String, Span)>) {
+	for item in items {
+		match item {
+			StatementOrDeclaration::Declaration(Declaration::Variable(declaration)) => {
+				variable_declaration_identifiers(declaration, out)
+			}
+			StatementOrDeclaration::Statement(statement) => statement_declarations(statement, out),
+			_ => {}
+		}
+	}
+}
+
+fn statement_declarations(statement: &Statement, out: &mut Vec<(String, Span)>) {
+	let inner = |inner: &BlockOrSingleStatement, out: &mut Vec<(String, Span)>| match inner {
+		BlockOrSingleStatement::Braced(block) => block_declarations(&block.0, out),
+		BlockOrSingleStatement::SingleStatement(statement) => {
+			statement_declarations(statement, out)
+		}
+	};
+	match statement {
+		Statement::Block(block) => block_declarations(&block.0, out),
+		Statement::IfStatement(statement) => {
+			inner(&statement.inner, out);
+			for branch in statement.else_conditions.iter() {
+				inner(&branch.inner, out);
+			}
+			if let Some(branch) = &statement.trailing_else {
+				inner(&branch.inner, out);
+			}
+		}
+		Statement::TryCatchStatement(statement) => {
+			let blocks = [Some(&statement.try_inner), statement.catch_inner.as_ref()];
+			let blocks = blocks.into_iter().chain([statement.finally_inner.as_ref()]);
+			for block in blocks.flatten() {
+				block_declarations(&block.0, out);
+			}
+		}
+		Statement::Labelled { statement, .. } => statement_declarations(statement, out),
+		_ => {}
+	}
+}
+
+fn inner_node(inner: &BlockOrSingleStatement) -> NodeRef<'_> {
+	match inner {
+		BlockOrSingleStatement::Braced(block) => NodeRef::Block(block),
+		BlockOrSingleStatement::SingleStatement(statement) => NodeRef::Statement(statement),
+	}
+}
+
+/// Whether a variable (of the name) is assigned to under a node, including in functions
+fn assigns(node: NodeRef, name: &str, functions: &ExtractedFunctions) -> bool {
+	let mut assigned = Vec::new();
+	match node {
+		NodeRef::VariableOrPropertyAccess(VariableOrPropertyAccess::Variable(variable, ..)) => {
+			return variable == name
+		}
+		NodeRef::Expression(Expression::Assignment {
+			lhs: LHSOfAssignment::ObjectDestructuring(fields, ..),
+			..
+		}) => object_fields_identifiers(fields, &mut assigned),
+		NodeRef::Expression(Expression::Assignment {
+			lhs: LHSOfAssignment::ArrayDestructuring(fields, ..),
+			..
+		}) => array_fields_identifiers(fields, &mut assigned),
+		NodeRef::Statement(Statement::ForLoopStatement(ForLoopStatement {
+			condition:
+				ForLoopCondition::ForOf { keyword: None, variable, .. }
+				| ForLoopCondition::ForIn { keyword: None, variable, .. },
+			..
+		})) => variable_field_identifiers(variable.get_ast(), &mut assigned),
+		_ => {}
+	}
+	assigned.iter().any(|(assigned, _)| assigned == name)
+		|| node.children(functions).into_iter().any(|child| assigns(child, name, functions))
+}
+
+/// A sequence as one expression, which is parenthesized if it has more than one
+fn single(expression: MultipleExpression, position: &Span) -> Expression {
+	match expression {
+		MultipleExpression { lhs: None, rhs } => rhs,
+		expression => Expression::ParenthesizedExpression(
+			Box::new(expression),
+			position.clone(),
+			ExpressionId::new(),
+		),
+	}
+}
+
+fn expression_statement(expression: Expression) -> StatementOrDeclaration {
+	StatementOrDeclaration::Statement(Statement::Expression(expression.into()))
+}
+
+fn empty_statement(position: &Span) -> Statement {
+	Statement::Block(Block(Vec::new(), BlockId::new(), position.clone()))
+}
+
+/// `if (value === "...") statement`
+fn if_equals(
+	value: Expression,
+	string: &str,
+	statement: Statement,
+	position: &Span,
+) -> StatementOrDeclaration {
+	let string = Expression::StringLiteral(
+		string.to_owned(),
+		Quoted::Double,
+		position.clone(),
+		ExpressionId::new(),
+	);
+	let condition = Expression::BinaryOperation {
+		lhs: Box::new(value),
+		operator: BinaryOperator::StrictEqual,
+		rhs: Box::new(string),
+		id: ExpressionId::new(),
+	};
+	StatementOrDeclaration::Statement(Statement::IfStatement(IfStatement {
+		condition: condition.into(),
+		inner: BlockOrSingleStatement::SingleStatement(Box::new(statement)),
+		else_conditions: Vec::new(),
+		trailing_else: None,
+		position: position.clone(),
+	}))
+}
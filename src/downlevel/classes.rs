@@ -0,0 +1,670 @@
+//! Classes to constructor functions with methods on their prototype, in the same form as other
+//! compilers targeting ES5
+//!
+//! ```js
+//! var B = (function (_super) {
+//!     function B(x) { _super.call(this, x); this.y = 1; }
+//!     __extends(B, _super);
+//!     B.prototype.m = function () { return _super.prototype.m.call(this); };
+//!     return B;
+//! })(A);
+//! ```
+//!
+//! `__extends` sets the prototype of the class to the class being extended, so that static members
+//! are inherited, with `__proto__` or (where that is not supported) by copying the static members.
+//! Static fields which use `this` are evaluated in a function called with the class as `this`.
+//!
+//! `super` is rewritten in a first pass, while the methods it is in are still known. Classes with
+//! decorators are kept (with a warning)
+
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use parser::{
+	declarations::{
+		classes::{ClassConstructorBase, ClassFunctionBase, ClassMember, ClassProperty},
+		export::Exportable,
+		ClassDeclaration, ExportDeclaration, StatementFunctionBase, VariableDeclaration,
+	},
+	expressions::{
+		object_literal::{ObjectLiteral, ObjectLiteralMember},
+		ExpressionFunctionBase, ExpressionId, MultipleExpression, SpreadExpression, SuperReference,
+	},
+	extractor::{ExtractedFunctions, GetFunction},
+	statements::VarVariableStatement,
+	ASTNode, Block, BlockId, BlockLikeMut, Chain, Declaration, Decorated, Expression,
+	ExpressionOrStatementPosition, FunctionBase, FunctionId, FunctionParameters,
+	GetSetGeneratorOrNone, Keyword, NodeRef, Parameter, PropertyId, PropertyKey, PropertyReference,
+	Span, Statement, StatementOrDeclaration, TypeReference, VariableField, VariableId,
+	VariableIdentifier, VisitorsMut, WithComment,
+};
+
+use super::{
+	any_in_function, assign_to, call, declaration_item, function_expression, function_header,
+	is_directive, key_expression, member, parenthesize, property, reference, take, warning,
+	SharedTemporaries,
+};
+use crate::error_handling::TempDiagnostic;
+
+/// Passes for [crate::temp::Transform::LowerClasses]
+pub(crate) fn passes() -> Vec<VisitorsMut<Vec<TempDiagnostic>>> {
+	let mut state = None;
+	let mut passes = super::passes(|temporaries| {
+		let shared = Rc::new(RefCell::new(Classes {
+			temporaries: temporaries.clone(),
+			super_name: None,
+			extends: None,
+			methods: HashMap::new(),
+		}));
+		state = Some(shared.clone());
+		VisitorsMut {
+			block_visitors_mut: vec![Box::new(RewriteSuper(shared.clone()))],
+			expression_visitors_mut: vec![Box::new(RewriteSuper(shared))],
+			..Default::default()
+		}
+	});
+	let state = state.unwrap();
+	passes[1].block_visitors_mut.push(Box::new(LowerClasses(state.clone())));
+	passes[1].expression_visitors_mut.push(Box::new(LowerClasses(state)));
+	passes
+}
+
+struct Classes {
+	temporaries: SharedTemporaries,
+	/// Name of the parameter holding the class being extended. The same name is used for every
+	/// class, as each parameter is only referenced in its own class
+	super_name: Option<String>,
+	/// Name of the `__extends` helper, if a class extends another
+	extends: Option<String>,
+	/// Bodies of the constructors and methods of classes which extend another, and whether the
+	/// method is static
+	methods: HashMap<BlockId, bool>,
+}
+
+impl Classes {
+	fn super_name(&mut self) -> String {
+		let temporaries = &self.temporaries;
+		self.super_name.get_or_insert_with(|| temporaries.borrow_mut().unique("_super")).clone()
+	}
+
+	fn record<T: ExpressionOrStatementPosition>(
+		&mut self,
+		class: &ClassDeclaration<T>,
+		functions: &ExtractedFunctions,
+	) {
+		if class.extends.is_none() {
+			return;
+		}
+		// Helpers are declared after this pass, before the classes are lowered
+		let temporaries = &self.temporaries;
+		self.extends.get_or_insert_with(|| temporaries.borrow_mut().helper("__extends", EXTENDS));
+		for member in class.members.iter() {
+			let (block, is_static) = match &member.on {
+				ClassMember::Constructor(constructor) => {
+					let constructor = GetFunction::<ClassConstructorBase>::get_function_ref(
+						functions,
+						constructor.0,
+					);
+					(constructor.map(|constructor| constructor.body.1), false)
+				}
+				ClassMember::Function(is_static, function) => {
+					let function =
+						GetFunction::<ClassFunctionBase>::get_function_ref(functions, function.0);
+					(function.map(|function| function.body.1), is_static.is_some())
+				}
+				ClassMember::Property(..) => continue,
+			};
+			if let Some(block) = block {
+				self.methods.insert(block, is_static);
+			}
+		}
+	}
+}
+
+/// `super(...)` to `_super.call(this, ...)`, `super.a` to `_super.prototype.a` (or `_super.a` in
+/// static methods) and `super.a(...)` to `_super.prototype.a.call(this, ...)`
+struct RewriteSuper(Rc<RefCell<Classes>>);
+
+impl<'a> parser::VisitorMut<BlockLikeMut<'a>, Vec<TempDiagnostic>> for RewriteSuper {
+	fn visit_mut(
+		&mut self,
+		item: &mut BlockLikeMut<'a>,
+		_data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		let mut classes = self.0.borrow_mut();
+		for item in item.items.iter() {
+			match item {
+				StatementOrDeclaration::Declaration(Declaration::Class(Decorated {
+					on: class,
+					..
+				}))
+				| StatementOrDeclaration::Declaration(Declaration::Export(Decorated {
+					on: ExportDeclaration::Variable { exported: Exportable::Class(class), .. },
+					..
+				})) => classes.record(class, functions),
+				_ => {}
+			}
+		}
+	}
+}
+
+impl parser::VisitorMut<Expression, Vec<TempDiagnostic>> for RewriteSuper {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		_data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		chain: &Chain,
+	) {
+		let mut classes = self.0.borrow_mut();
+		if let Expression::ClassExpression(class, _) = item {
+			classes.record(class, functions);
+			return;
+		}
+		let Some(is_static) =
+			chain.function_block_id(false).and_then(|block| classes.methods.get(&block).copied())
+		else {
+			return;
+		};
+		match item {
+			Expression::FunctionCall { function, arguments, position, .. }
+				if matches!(
+					function.as_ref(),
+					Expression::SuperExpression(
+						SuperReference::PropertyAccess { .. } | SuperReference::Index { .. },
+						..
+					)
+				) =>
+			{
+				let Expression::SuperExpression(reference, ..) = take(function, position) else {
+					unreachable!()
+				};
+				let method = super_member(reference, &classes.super_name(), is_static, position);
+				**function = member(method, "call", position);
+				let this = Expression::ThisReference(position.clone(), ExpressionId::new());
+				arguments.insert(0, SpreadExpression::NonSpread(this));
+			}
+			Expression::SuperExpression(SuperReference::Call { arguments }, position, _) => {
+				let function = member(reference(&classes.super_name(), position), "call", position);
+				let this = Expression::ThisReference(position.clone(), ExpressionId::new());
+				let mut arguments = std::mem::take(arguments);
+				arguments.insert(0, SpreadExpression::NonSpread(this));
+				*item = Expression::FunctionCall {
+					function: Box::new(function),
+					type_arguments: None,
+					arguments,
+					position: position.clone(),
+					expression_id: ExpressionId::new(),
+				};
+			}
+			Expression::SuperExpression(_, position, _) => {
+				let position = position.clone();
+				let Expression::SuperExpression(reference, ..) = take(item, &position) else {
+					unreachable!()
+				};
+				*item = super_member(reference, &classes.super_name(), is_static, &position);
+			}
+			_ => {}
+		}
+	}
+}
+
+/// `_super.prototype.a` or `_super.prototype[a]`
+fn super_member(
+	super_reference: SuperReference,
+	super_name: &str,
+	is_static: bool,
+	position: &Span,
+) -> Expression {
+	let mut object = reference(super_name, position);
+	if !is_static {
+		object = member(object, "prototype", position);
+	}
+	match super_reference {
+		SuperReference::PropertyAccess { property } => member(object, &property, position),
+		SuperReference::Index { indexer } => Expression::Index {
+			indexee: Box::new(object),
+			indexer: Box::new(MultipleExpression::from(*indexer)),
+			position: position.clone(),
+			expression_id: ExpressionId::new(),
+		},
+		SuperReference::Call { .. } => unreachable!("super call as member"),
+	}
+}
+
+struct LowerClasses(Rc<RefCell<Classes>>);
+
+impl<'a> parser::VisitorMut<BlockLikeMut<'a>, Vec<TempDiagnostic>> for LowerClasses {
+	fn visit_mut(
+		&mut self,
+		item: &mut BlockLikeMut<'a>,
+		data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		let classes = &mut self.0.borrow_mut();
+		for statement in std::mem::take(item.items) {
+			match statement {
+				StatementOrDeclaration::Declaration(Declaration::Class(Decorated {
+					decorators,
+					on: class,
+				})) if decorators.is_empty() => {
+					match lower_class(class, classes, data, functions) {
+						Ok((name, position, value)) => {
+							// `var B = (function () { ... })()`
+							let statement = VarVariableStatement {
+								keyword: Keyword::new(position.clone()),
+								declarations: vec![declaration_item(name, position, Some(value))],
+							};
+							item.items.push(Statement::VarVariable(statement).into_item());
+						}
+						Err(class) => {
+							let class = Decorated { decorators: Vec::new(), on: *class };
+							item.items.push(StatementOrDeclaration::Declaration(
+								Declaration::Class(class),
+							));
+						}
+					}
+				}
+				StatementOrDeclaration::Declaration(Declaration::Export(Decorated {
+					decorators,
+					on: ExportDeclaration::Variable { exported: Exportable::Class(class), position },
+				})) if decorators.is_empty() => {
+					let exported = match lower_class(class, classes, data, functions) {
+						// `var` cannot be exported
+						Ok((name, class_position, value)) => {
+							Exportable::Variable(VariableDeclaration::LetDeclaration {
+								keyword: Keyword::new(class_position.clone()),
+								declarations: vec![declaration_item(
+									name,
+									class_position,
+									Some(value),
+								)],
+							})
+						}
+						Err(class) => Exportable::Class(*class),
+					};
+					item.items.push(StatementOrDeclaration::Declaration(Declaration::Export(
+						Decorated {
+							decorators: Vec::new(),
+							on: ExportDeclaration::Variable { exported, position },
+						},
+					)));
+				}
+				StatementOrDeclaration::Declaration(Declaration::Class(Decorated {
+					on: ClassDeclaration { ref position, .. },
+					..
+				}))
+				| StatementOrDeclaration::Declaration(Declaration::Export(Decorated {
+					on:
+						ExportDeclaration::Variable {
+							exported: Exportable::Class(ClassDeclaration { ref position, .. }),
+							..
+						},
+					..
+				})) => {
+					data.push(decorators_warning(position.clone()));
+					item.items.push(statement);
+				}
+				statement => item.items.push(statement),
+			}
+		}
+	}
+}
+
+impl parser::VisitorMut<Expression, Vec<TempDiagnostic>> for LowerClasses {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		let Expression::ClassExpression(class, _) = item else { return };
+		let position = class.position.clone();
+		let Expression::ClassExpression(class, id) = take(item, &position) else { unreachable!() };
+		*item = match lower_class(class, &mut self.0.borrow_mut(), data, functions) {
+			Ok((_, _, value)) => value,
+			Err(class) => Expression::ClassExpression(*class, id),
+		};
+	}
+}
+
+trait IntoItem {
+	fn into_item(self) -> StatementOrDeclaration;
+}
+
+impl IntoItem for Statement {
+	fn into_item(self) -> StatementOrDeclaration {
+		StatementOrDeclaration::Statement(self)
+	}
+}
+
+impl IntoItem for Expression {
+	fn into_item(self) -> StatementOrDeclaration {
+		StatementOrDeclaration::Statement(Statement::Expression(self.into()))
+	}
+}
+
+/// Sets up the prototype chain of a class and the class it extends, `Object.setPrototypeOf` is
+/// not in ES5
+const EXTENDS: &str = r#"function (child, parent) {
+	var setPrototypeOf = Object.setPrototypeOf || ({ __proto__: [] } instanceof Array && function (object, prototype) { object.__proto__ = prototype; }) || function (object, prototype) {
+		for (var key in prototype) if (Object.prototype.hasOwnProperty.call(prototype, key)) object[key] = prototype[key];
+	};
+	setPrototypeOf(child, parent);
+	child.prototype = Object.create(parent.prototype, { constructor: { value: child, writable: true, configurable: true } });
+}"#;
+
+/// A getter and (or) setter pair
+struct Accessor {
+	is_static: bool,
+	key: Expression,
+	/// For finding the other of the pair. `None` for computed keys
+	name: Option<String>,
+	get: Option<Expression>,
+	set: Option<Expression>,
+}
+
+/// Returns the name of the class and the function expression (called immediately) which creates
+/// it, or the class back if it cannot be lowered
+fn lower_class<T: ExpressionOrStatementPosition>(
+	class: ClassDeclaration<T>,
+	classes: &mut Classes,
+	data: &mut Vec<TempDiagnostic>,
+	functions: &mut ExtractedFunctions,
+) -> Result<(String, Span, Expression), Box<ClassDeclaration<T>>> {
+	let position = class.position.clone();
+	if class.members.iter().any(|member| !member.decorators.is_empty()) {
+		data.push(decorators_warning(position));
+		return Err(Box::new(class));
+	}
+	let is_auto_accessor = |member: &Decorated<ClassMember>| {
+		matches!(member.on, ClassMember::Property(_, ClassProperty { accessor: Some(_), .. }))
+	};
+	if class.members.iter().any(is_auto_accessor) {
+		// Auto-accessors are lowered with decorators, so these are in classes without them
+		let label = "Classes with auto-accessors are only lowered when they have decorators";
+		data.push(warning(label.to_owned(), position));
+		return Err(Box::new(class));
+	}
+	let extends = match &class.extends {
+		None => None,
+		Some(
+			TypeReference::Name(name, position)
+			| TypeReference::NameWithGenericArguments(name, _, position),
+		) => Some(reference(name, position)),
+		Some(TypeReference::NamespacedName(namespace, name, position)) => {
+			Some(member(reference(namespace, position), name, position))
+		}
+		Some(_) => {
+			data.push(warning("Class extends an unsupported expression".to_owned(), position));
+			return Err(Box::new(class));
+		}
+	};
+	let name = match T::as_option_str(&class.name) {
+		Some(name) => name.to_owned(),
+		None => classes.temporaries.borrow_mut().unique("_class"),
+	};
+	let super_name = extends.as_ref().map(|_| classes.super_name());
+	let class_reference = || reference(&name, &position);
+	let prototype = || member(class_reference(), "prototype", &position);
+
+	let mut constructor = None;
+	let mut methods = Vec::new();
+	let mut accessors = Vec::<Accessor>::new();
+	let mut instance_fields = Vec::new();
+	let mut static_fields = Vec::new();
+	for member in class.members {
+		match member.on {
+			ClassMember::Constructor(function) => {
+				constructor =
+					Some(GetFunction::<ClassConstructorBase>::get_function(functions, function.0));
+			}
+			ClassMember::Function(is_static, function) => {
+				let is_static = is_static.is_some();
+				let function =
+					GetFunction::<ClassFunctionBase>::get_function(functions, function.0);
+				let key = function.name.get_ast().clone();
+				let (async_keyword, kind) = function.header.clone();
+				let generator = match &kind {
+					GetSetGeneratorOrNone::Generator(keyword) => Some(keyword.1.clone()),
+					GetSetGeneratorOrNone::GeneratorStar(position) => Some(position.clone()),
+					_ => None,
+				};
+				let value =
+					function_expression(function, async_keyword, generator, &position, functions);
+				if let GetSetGeneratorOrNone::Get(_) | GetSetGeneratorOrNone::Set(_) = kind {
+					let name = key_name(&key);
+					let existing = accessors.iter_mut().find(|accessor| {
+						accessor.is_static == is_static && name.is_some() && accessor.name == name
+					});
+					let accessor = match existing {
+						Some(accessor) => accessor,
+						None => {
+							accessors.push(Accessor {
+								is_static,
+								key: key_expression(key),
+								name,
+								get: None,
+								set: None,
+							});
+							accessors.last_mut().unwrap()
+						}
+					};
+					if let GetSetGeneratorOrNone::Get(_) = kind {
+						accessor.get = Some(value);
+					} else {
+						accessor.set = Some(value);
+					}
+				} else {
+					let object = if is_static { class_reference() } else { prototype() };
+					methods.push(assign_to(property(object, key), value).into_item());
+				}
+			}
+			ClassMember::Property(is_static, ClassProperty { key, value, .. }) => {
+				let value = value.map_or_else(|| super::void_zero(&position), |value| *value);
+				if is_static.is_some() {
+					let value = if uses_this(&value, functions) {
+						// `(function () { return ...; }).call(B)`
+						call_with_this(value, class_reference(), &position, functions)
+					} else {
+						value
+					};
+					let assignment =
+						assign_to(property(class_reference(), key.unwrap_ast()), value);
+					static_fields.push(assignment.into_item());
+				} else {
+					let this = Expression::ThisReference(position.clone(), ExpressionId::new());
+					instance_fields
+						.push(assign_to(property(this, key.unwrap_ast()), value).into_item());
+				}
+			}
+		}
+	}
+
+	// `function B(...) { ... }`
+	let (parameters, mut body) = match constructor {
+		Some(constructor) => (constructor.parameters, constructor.body),
+		None => {
+			let parameters = FunctionParameters {
+				parameters: Vec::new(),
+				optional_parameters: Vec::new(),
+				rest_parameter: None,
+				position: position.clone(),
+			};
+			(parameters, Block(Vec::new(), BlockId::new(), position.clone()))
+		}
+	};
+	if let (Some(super_name), true) = (&super_name, body.0.is_empty()) {
+		// Default constructors pass all arguments, `_super.apply(this, arguments)`
+		let apply = member(reference(super_name, &position), "apply", &position);
+		let this = Expression::ThisReference(position.clone(), ExpressionId::new());
+		body.0.push(
+			call(apply, vec![this, reference("arguments", &position)], &position).into_item(),
+		);
+	}
+	let index = match super_name.as_deref() {
+		Some(super_name) => {
+			body.0.iter().position(|item| is_super_call(item, super_name)).map(|index| index + 1)
+		}
+		None => None,
+	}
+	.unwrap_or_else(|| body.0.iter().take_while(|item| is_directive(item)).count());
+	body.0.splice(index..index, instance_fields);
+	let constructor = FunctionBase::<StatementFunctionBase> {
+		function_id: FunctionId::new(),
+		header: function_header(None, None, &position),
+		name: VariableIdentifier::Standard(name.clone(), VariableId::new(), position.clone()),
+		type_parameters: class.type_parameters,
+		parameters,
+		return_type: None,
+		body,
+	};
+	let mut statements = vec![StatementOrDeclaration::Declaration(Declaration::ExtractedFunction(
+		functions.new_extracted_function(constructor),
+	))];
+
+	if let Some(super_name) = &super_name {
+		// `__extends(B, _super)`
+		let extends = classes.extends.as_deref().expect("classes which extend are recorded");
+		let arguments = vec![class_reference(), reference(super_name, &position)];
+		statements.push(call(reference(extends, &position), arguments, &position).into_item());
+	}
+	statements.extend(methods);
+	for Accessor { is_static, key, get, set, .. } in accessors {
+		// `Object.defineProperty(B.prototype, "a", { get: ..., set: ..., configurable: true })`
+		let mut descriptor = Vec::new();
+		descriptor.extend(get.map(|get| ("get", get)));
+		descriptor.extend(set.map(|set| ("set", set)));
+		descriptor.push(("configurable", boolean(true, &position)));
+		let object_argument = if is_static { class_reference() } else { prototype() };
+		let define_property = member(reference("Object", &position), "defineProperty", &position);
+		let arguments = vec![object_argument, key, object(descriptor)];
+		statements.push(call(define_property, arguments, &position).into_item());
+	}
+	statements.extend(static_fields);
+	statements.push(
+		Statement::Return(Keyword::new(position.clone()), Some(class_reference().into()))
+			.into_item(),
+	);
+
+	let parameters = FunctionParameters {
+		parameters: super_name
+			.iter()
+			.map(|super_name| Parameter {
+				name: WithComment::None(VariableField::Name(VariableIdentifier::Standard(
+					super_name.clone(),
+					VariableId::new(),
+					position.clone(),
+				))),
+				type_reference: None,
+				property: None,
+			})
+			.collect(),
+		optional_parameters: Vec::new(),
+		rest_parameter: None,
+		position: position.clone(),
+	};
+	let function = FunctionBase::<ExpressionFunctionBase> {
+		function_id: FunctionId::new(),
+		header: function_header(None, None, &position),
+		name: None,
+		type_parameters: None,
+		parameters,
+		return_type: None,
+		body: Block(statements, BlockId::new(), position.clone()),
+	};
+	let function =
+		Expression::ExtractedExpressionFunction(functions.new_extracted_function(function));
+	let value = call(parenthesize(function, &position), extends.into_iter().collect(), &position);
+	Ok((name, position, value))
+}
+
+/// `_super.call(this, ...)` from [RewriteSuper] or `_super.apply(this, arguments)` in a default
+/// constructor
+fn is_super_call(item: &StatementOrDeclaration, super_name: &str) -> bool {
+	let StatementOrDeclaration::Statement(Statement::Expression(MultipleExpression {
+		lhs: None,
+		rhs: Expression::FunctionCall { function, .. },
+	})) = item
+	else {
+		return false;
+	};
+	matches!(
+		function.as_ref(),
+		Expression::PropertyAccess { parent, property: PropertyReference::Standard(call), .. }
+			if matches!(call.as_str(), "call" | "apply")
+				&& matches!(parent.as_ref(), Expression::VariableReference(name, ..) if name == super_name)
+	)
+}
+
+/// Whether `this` is used in a static field value (including in arrow functions), where it is the
+/// class
+fn uses_this(value: &Expression, functions: &ExtractedFunctions) -> bool {
+	any_in_function(NodeRef::Expression(value), functions, true, &|node| {
+		matches!(node, NodeRef::Expression(Expression::ThisReference(..)))
+	})
+}
+
+/// `(function () { return value; }).call(this)`
+fn call_with_this(
+	value: Expression,
+	this: Expression,
+	position: &Span,
+	functions: &mut ExtractedFunctions,
+) -> Expression {
+	let body =
+		vec![Statement::Return(Keyword::new(position.clone()), Some(value.into())).into_item()];
+	let function = FunctionBase::<ExpressionFunctionBase> {
+		function_id: FunctionId::new(),
+		header: function_header(None, None, position),
+		name: None,
+		type_parameters: None,
+		parameters: FunctionParameters {
+			parameters: Vec::new(),
+			optional_parameters: Vec::new(),
+			rest_parameter: None,
+			position: position.clone(),
+		},
+		return_type: None,
+		body: Block(body, BlockId::new(), position.clone()),
+	};
+	let function =
+		Expression::ExtractedExpressionFunction(functions.new_extracted_function(function));
+	call(member(parenthesize(function, position), "call", position), vec![this], position)
+}
+
+/// For pairing getters and setters
+fn key_name(key: &PropertyKey) -> Option<String> {
+	match key {
+		PropertyKey::Ident(name, ..) | PropertyKey::StringLiteral(name, ..) => Some(name.clone()),
+		PropertyKey::NumberLiteral(number, ..) => Some(number.to_string()),
+		PropertyKey::Computed(..) => None,
+	}
+}
+
+fn object(properties: Vec<(&str, Expression)>) -> Expression {
+	let members = properties
+		.into_iter()
+		.map(|(key, value)| {
+			let position = value.get_position().into_owned();
+			let key = PropertyKey::Ident(key.to_owned(), PropertyId::new(), position.clone());
+			ObjectLiteralMember::Property(WithComment::None(key), value, position)
+		})
+		.collect();
+	Expression::ObjectLiteral(ObjectLiteral {
+		members,
+		position: Span::NULL_SPAN,
+		expression_id: ExpressionId::new(),
+	})
+}
+
+fn boolean(value: bool, position: &Span) -> Expression {
+	Expression::BooleanLiteral(value, position.clone(), ExpressionId::new())
+}
+
+fn decorators_warning(position: Span) -> TempDiagnostic {
+	warning("Classes with decorators are not lowered".to_owned(), position)
+}
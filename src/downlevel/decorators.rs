@@ -0,0 +1,1327 @@
+//! Decorators on classes and their members to function calls, with the semantics of the 2023-05
+//! TC39 proposal or of TypeScript's `experimentalDecorators` (see [DecoratorSemantics]).
+//!
+//! With the standard semantics each decorated class is created in an arrow function (called
+//! immediately) which evaluates the decorators, applies them with `__esDecorate` and runs the
+//! initializers they add with `__runInitializers`:
+//!
+//! ```js
+//! let C = (() => {
+//!     var _metadata = ..., _classDecorators = [logged], _classExtraInitializers = [], _classDescriptor, _m_decorators = [bound];
+//!     var C = class { m() {} };
+//!     __esDecorate(C, null, _m_decorators, { kind: "method", name: "m", ... }, null, _instanceExtraInitializers);
+//!     __esDecorate(null, _classDescriptor = { value: C }, _classDecorators, { kind: "class", ... }, null, _classExtraInitializers);
+//!     C = _classDescriptor.value;
+//!     ...
+//!     return C;
+//! })();
+//! ```
+//!
+//! Other compilers apply member decorators in a static block, so that they are applied before
+//! static fields are initialized. The parser does not support static blocks, so they are applied
+//! in the initializer of the first static field instead. Instance initializers run in the
+//! initializer of the first instance field, or at the start of the constructor.
+//!
+//! Auto-accessors (`accessor x = 1`) in decorated classes become a field and a getter and setter
+//! for it. The parser does not support private names, so the field has a symbol key:
+//! `[_a] = 1; get x() { return this[_a]; } set x(value) { this[_a] = value; }` with
+//! `var _a = Symbol()` before the class.
+//!
+//! With the legacy semantics decorators are applied after the class by `__decorate`, which passes
+//! the prototype (or class), key and property descriptor. Class decorators reassign the class
+//! (`let C = class C { ... }; C = __decorate([logged], C);`). With metadata, the types of
+//! members are added as `design:type`, `design:paramtypes` and `design:returntype` metadata,
+//! from the type annotations (so this runs before types are erased)
+
+use std::{
+	cell::RefCell,
+	collections::{HashMap, HashSet},
+	rc::Rc,
+};
+
+use parser::{
+	declarations::{
+		classes::{ClassConstructorBase, ClassFunctionBase, ClassMember, ClassProperty},
+		export::Exportable,
+		ClassDeclaration, ExportDeclaration, ImportPart, VariableDeclaration,
+	},
+	expressions::{
+		arrow_function::{ArrowFunctionBase, ExpressionOrBlock},
+		assignments::{LHSOfAssignment, VariableOrPropertyAccess},
+		ExpressionId, SpreadExpression,
+	},
+	extractor::{ExtractedFunctions, GetFunction},
+	statements::VarVariableStatement,
+	types::enum_declaration::EnumMember,
+	ASTNode, Block, BlockId, BlockLikeMut, Chain, Declaration, Decorated, Decorator, Expression,
+	ExpressionOrStatementPosition, ExpressionPosition, FunctionBase, FunctionId,
+	FunctionParameters, GenericTypeConstraint, GetSetGeneratorOrNone, Keyword,
+	OptionalOrWithDefaultValueParameter, ParseOutput, ParseSettings, PropertyId, PropertyKey,
+	Quoted, SourceId, Span, Statement, StatementOrDeclaration, StatementPosition, TypeReference,
+	VariableId, VariableIdentifier, VisitorsMut, WithComment,
+};
+
+use super::{
+	assign, call, declaration_item, is_directive, is_super_call, member, reference, sequence, take,
+	void_zero, SharedTemporaries, Temporaries,
+};
+use crate::error_handling::{DiagnosticCode, ErrorWarningInfo, TempDiagnostic};
+
+/// How decorators are lowered by [crate::temp::Transform::LowerDecorators]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DecoratorSemantics {
+	/// The 2023-05 TC39 proposal. Decorators are called with the value being decorated and a
+	/// context object, and can replace the value and add initializers
+	#[default]
+	Standard,
+	/// TypeScript's `experimentalDecorators`. Decorators are called with the prototype (or
+	/// class), key and property descriptor. `metadata` adds type metadata like
+	/// `emitDecoratorMetadata`
+	Legacy { metadata: bool },
+}
+
+impl DecoratorSemantics {
+	pub fn with_metadata(self, metadata: bool) -> Result<Self, String> {
+		match self {
+			DecoratorSemantics::Standard if metadata => {
+				Err("Metadata only applies to legacy decorators".to_owned())
+			}
+			DecoratorSemantics::Standard => Ok(self),
+			DecoratorSemantics::Legacy { .. } => Ok(DecoratorSemantics::Legacy { metadata }),
+		}
+	}
+}
+
+impl std::str::FromStr for DecoratorSemantics {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"2023-05" => Ok(Self::Standard),
+			"legacy" => Ok(Self::Legacy { metadata: false }),
+			_ => Err(format!("Unknown decorators version '{s}', expected '2023-05' or 'legacy'")),
+		}
+	}
+}
+
+const ES_DECORATE: &str = "function (constructor, descriptorIn, decorators, contextIn, initializers, extraInitializers) {
+	function accept(f) {
+		if (f !== void 0 && typeof f !== \"function\") throw new TypeError(\"Function expected\");
+		return f;
+	}
+	var kind = contextIn.kind, key = kind === \"getter\" ? \"get\" : kind === \"setter\" ? \"set\" : \"value\";
+	var target = !descriptorIn && constructor ? (contextIn[\"static\"] ? constructor : constructor.prototype) : null;
+	var descriptor = descriptorIn || (target ? Object.getOwnPropertyDescriptor(target, contextIn.name) : {});
+	var done = false, i, property;
+	for (i = decorators.length - 1; i >= 0; i--) {
+		var context = {};
+		for (property in contextIn) context[property] = property === \"access\" ? {} : contextIn[property];
+		for (property in contextIn.access) context.access[property] = contextIn.access[property];
+		context.addInitializer = function (f) {
+			if (done) throw new TypeError(\"Cannot add initializers after decoration has completed\");
+			extraInitializers.push(accept(f || null));
+		};
+		var decorator = decorators[i];
+		var result = decorator(kind === \"accessor\" ? { get: descriptor.get, set: descriptor.set } : descriptor[key], context);
+		if (kind === \"accessor\") {
+			if (result === void 0) continue;
+			if (result === null || typeof result !== \"object\") throw new TypeError(\"Object expected\");
+			if (accept(result.get)) descriptor.get = result.get;
+			if (accept(result.set)) descriptor.set = result.set;
+			if (accept(result.init)) initializers.unshift(result.init);
+		} else if (accept(result)) {
+			if (kind === \"field\") initializers.unshift(result);
+			else descriptor[key] = result;
+		}
+	}
+	if (target) Object.defineProperty(target, contextIn.name, descriptor);
+	done = true;
+}";
+
+const RUN_INITIALIZERS: &str = "function (thisArg, initializers, value) {
+	var useValue = arguments.length > 2, i;
+	for (i = 0; i < initializers.length; i++) {
+		value = useValue ? initializers[i].call(thisArg, value) : initializers[i].call(thisArg);
+	}
+	return useValue ? value : void 0;
+}";
+
+const DECORATE: &str = "function (decorators, target, key, descriptor) {
+	var count = arguments.length, i;
+	var result = count < 3 ? target : descriptor === null ? (descriptor = Object.getOwnPropertyDescriptor(target, key)) : descriptor;
+	if (typeof Reflect === \"object\" && typeof Reflect.decorate === \"function\") {
+		result = Reflect.decorate(decorators, target, key, descriptor);
+	} else {
+		for (i = decorators.length - 1; i >= 0; i--) {
+			var decorator = decorators[i];
+			if (decorator) result = (count < 3 ? decorator(result) : count > 3 ? decorator(target, key, result) : decorator(target, key)) || result;
+		}
+	}
+	if (count > 3 && result) Object.defineProperty(target, key, result);
+	return result;
+}";
+
+const METADATA: &str = "function (key, value) {
+	if (typeof Reflect === \"object\" && typeof Reflect.metadata === \"function\") return Reflect.metadata(key, value);
+}";
+
+/// Passes for [crate::temp::Transform::LowerDecorators]
+pub(crate) fn passes(semantics: DecoratorSemantics) -> Vec<VisitorsMut<Vec<TempDiagnostic>>> {
+	super::passes(|temporaries| {
+		let shared = Rc::new(RefCell::new(Decorators {
+			temporaries: temporaries.clone(),
+			semantics,
+			types: Types::default(),
+			is_module: true,
+		}));
+		VisitorsMut {
+			block_visitors_mut: vec![Box::new(LowerDecorators(shared.clone()))],
+			expression_visitors_mut: vec![Box::new(LowerDecorators(shared))],
+			..Default::default()
+		}
+	})
+}
+
+struct Decorators {
+	temporaries: SharedTemporaries,
+	semantics: DecoratorSemantics,
+	/// From the module, the first block visited
+	types: Types,
+	is_module: bool,
+}
+
+/// Names in the module which metadata cannot reference as values
+#[derive(Default)]
+struct Types {
+	/// Interfaces, type aliases and type only imports
+	names: HashSet<String>,
+	/// The constructor of the values of each enum, `Number` or `String`
+	enums: HashMap<String, &'static str>,
+}
+
+impl Types {
+	fn new(items: &[StatementOrDeclaration]) -> Self {
+		let mut types = Types::default();
+		for item in items {
+			let StatementOrDeclaration::Declaration(declaration) = item else { continue };
+			match declaration {
+				Declaration::Interface(Decorated { on: interface, .. })
+				| Declaration::Export(Decorated {
+					on:
+						ExportDeclaration::Variable {
+							exported: Exportable::Interface(interface), ..
+						},
+					..
+				}) => {
+					types.names.insert(interface.name.clone());
+				}
+				Declaration::TypeAlias(alias)
+				| Declaration::Export(Decorated {
+					on: ExportDeclaration::Variable { exported: Exportable::TypeAlias(alias), .. },
+					..
+				}) => {
+					types.names.insert(alias.type_name.name.clone());
+				}
+				Declaration::Enum(Decorated { on: enum_declaration, .. })
+				| Declaration::Export(Decorated {
+					on:
+						ExportDeclaration::Variable {
+							exported: Exportable::Enum(enum_declaration), ..
+						},
+					..
+				}) => {
+					let strings = enum_declaration
+						.members
+						.iter()
+						.filter(|EnumMember::Variant { value, .. }| {
+							matches!(value, Some(Expression::StringLiteral(..)))
+						})
+						.count();
+					let constructor = match strings {
+						0 => "Number",
+						strings if strings == enum_declaration.members.len() => "String",
+						_ => "Object",
+					};
+					types.enums.insert(enum_declaration.name.clone(), constructor);
+				}
+				Declaration::Import(import) => {
+					let parts = import.imports.iter().flatten();
+					if import.only_type {
+						types.names.extend(import.default_import.clone());
+						types.names.extend(parts.filter_map(imported_name));
+					} else {
+						types.names.extend(parts.filter_map(|part| match part {
+							ImportPart::TypeOnly(part, _) => imported_name(part),
+							_ => None,
+						}));
+					}
+				}
+				_ => {}
+			}
+		}
+		types
+	}
+
+	/// The value TypeScript's `emitDecoratorMetadata` uses for a type annotation, as source
+	fn serialize(&self, reference: Option<&TypeReference>, type_parameters: &[&str]) -> String {
+		let Some(reference) = reference else { return "Object".to_owned() };
+		match reference {
+			TypeReference::Name(name, _) | TypeReference::NameWithGenericArguments(name, ..) => {
+				match name.as_str() {
+					"number" => "Number",
+					"string" => "String",
+					"boolean" => "Boolean",
+					"bigint" => "BigInt",
+					"symbol" => "Symbol",
+					"void" | "undefined" | "null" | "never" => "void 0",
+					"any" | "unknown" | "object" => "Object",
+					name if type_parameters.contains(&name) || self.names.contains(name) => {
+						"Object"
+					}
+					name => self.enums.get(name).copied().unwrap_or(name),
+				}
+				.to_owned()
+			}
+			TypeReference::NamespacedName(namespace, name, _) => format!("{namespace}.{name}"),
+			TypeReference::Union(members) => {
+				let mut serialized = members
+					.iter()
+					.filter(|member| {
+						!matches!(member, TypeReference::Name(name, _) if matches!(name.as_str(), "null" | "undefined" | "never"))
+					})
+					.map(|member| self.serialize(Some(member), type_parameters))
+					.collect::<Vec<_>>();
+				serialized.dedup();
+				match serialized.len() {
+					0 => "void 0".to_owned(),
+					1 => serialized.remove(0),
+					_ => "Object".to_owned(),
+				}
+			}
+			TypeReference::StringLiteral(..) | TypeReference::TemplateLiteral(..) => {
+				"String".to_owned()
+			}
+			TypeReference::NumberLiteral(..) => "Number".to_owned(),
+			TypeReference::BooleanLiteral(..) => "Boolean".to_owned(),
+			TypeReference::ArrayLiteral(..) | TypeReference::TupleLiteral(..) => "Array".to_owned(),
+			TypeReference::FunctionLiteral { .. } | TypeReference::ConstructorLiteral { .. } => {
+				"Function".to_owned()
+			}
+			TypeReference::Readonly(inner, _)
+			| TypeReference::ParenthesizedReference(inner, _)
+			| TypeReference::Decorated(_, inner, _) => self.serialize(Some(inner), type_parameters),
+			_ => "Object".to_owned(),
+		}
+	}
+
+	/// `[Number, String]` for the parameters of a function
+	fn serialize_parameters(
+		&self,
+		parameters: &FunctionParameters,
+		type_parameters: &[&str],
+	) -> String {
+		let types = parameters
+			.parameters
+			.iter()
+			.map(|parameter| parameter.type_reference.as_ref())
+			.chain(parameters.optional_parameters.iter().map(|parameter| match parameter {
+				OptionalOrWithDefaultValueParameter::Optional { type_reference, .. }
+				| OptionalOrWithDefaultValueParameter::WithDefaultValue {
+					type_reference, ..
+				} => type_reference.as_ref(),
+			}))
+			.chain(parameters.rest_parameter.iter().map(|rest| rest.type_reference.as_ref()))
+			.map(|reference| self.serialize(reference, type_parameters))
+			.collect::<Vec<_>>();
+		format!("[{}]", types.join(", "))
+	}
+}
+
+fn imported_name(part: &ImportPart) -> Option<String> {
+	match part {
+		ImportPart::Name(VariableIdentifier::Standard(name, ..))
+		| ImportPart::NameWithAlias { alias: name, .. } => Some(name.clone()),
+		ImportPart::TypeOnly(part, _) => imported_name(part),
+		ImportPart::Name(VariableIdentifier::Cursor(_)) => None,
+	}
+}
+
+fn type_parameter_names(type_parameters: &Option<Vec<GenericTypeConstraint>>) -> Vec<&str> {
+	type_parameters.iter().flatten().map(GenericTypeConstraint::name).collect()
+}
+
+/// Whether a class or its members have decorators
+fn is_decorated<T: ExpressionOrStatementPosition>(
+	decorators: &[Decorator],
+	class: &ClassDeclaration<T>,
+) -> bool {
+	!decorators.is_empty() || class.members.iter().any(|member| !member.decorators.is_empty())
+}
+
+struct LowerDecorators(Rc<RefCell<Decorators>>);
+
+impl<'a> parser::VisitorMut<BlockLikeMut<'a>, Vec<TempDiagnostic>> for LowerDecorators {
+	fn visit_mut(
+		&mut self,
+		item: &mut BlockLikeMut<'a>,
+		data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		chain: &Chain,
+	) {
+		let decorators = &mut *self.0.borrow_mut();
+		if std::mem::take(&mut decorators.is_module) {
+			decorators.types = Types::new(item.items);
+		}
+		for statement in std::mem::take(item.items) {
+			match statement {
+				StatementOrDeclaration::Declaration(Declaration::Class(Decorated {
+					decorators: class_decorators,
+					on: class,
+				})) if is_decorated(&class_decorators, &class) => decorators.declaration(
+					class,
+					class_decorators,
+					false,
+					item.items,
+					data,
+					functions,
+					chain,
+				),
+				// Decorators before `export` are on the class
+				StatementOrDeclaration::Declaration(Declaration::Export(Decorated {
+					decorators: class_decorators,
+					on: ExportDeclaration::Variable { exported: Exportable::Class(class), .. },
+				})) if is_decorated(&class_decorators, &class) => decorators.declaration(
+					class,
+					class_decorators,
+					true,
+					item.items,
+					data,
+					functions,
+					chain,
+				),
+				StatementOrDeclaration::Declaration(Declaration::Export(Decorated {
+					decorators: class_decorators,
+					on: ExportDeclaration::Default { expression, position },
+				})) if !class_decorators.is_empty()
+					&& matches!(*expression, Expression::ClassExpression(..)) =>
+				{
+					let Expression::ClassExpression(class, _) = *expression else { unreachable!() };
+					decorators.default_export(
+						class,
+						class_decorators,
+						position,
+						item.items,
+						data,
+						functions,
+						chain,
+					);
+				}
+				StatementOrDeclaration::Declaration(
+					Declaration::Function(Decorated { ref decorators, .. })
+					| Declaration::Enum(Decorated { ref decorators, .. })
+					| Declaration::Interface(Decorated { ref decorators, .. })
+					| Declaration::Export(Decorated { ref decorators, .. }),
+				) if !decorators.is_empty() => {
+					for decorator in decorators {
+						data.push(invalid_decorator(decorator.position.clone()));
+					}
+					item.items.push(statement);
+				}
+				statement => item.items.push(statement),
+			}
+		}
+	}
+}
+
+impl parser::VisitorMut<Expression, Vec<TempDiagnostic>> for LowerDecorators {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		chain: &Chain,
+	) {
+		let Expression::ClassExpression(class, _) = item else { return };
+		if !is_decorated(&[], class) {
+			return;
+		}
+		let position = class.position.clone();
+		let Expression::ClassExpression(mut class, id) = take(item, &position) else {
+			unreachable!()
+		};
+		let decorators = &mut *self.0.borrow_mut();
+		let temporaries = &mut decorators.temporaries.borrow_mut();
+		*item = match decorators.semantics {
+			DecoratorSemantics::Standard => {
+				standard(class, Vec::new(), temporaries, data, functions)
+			}
+			DecoratorSemantics::Legacy { metadata } => {
+				// `(_a = class { ... }, __decorate(...), _a)`
+				let variable = temporaries.temporary(chain);
+				let mut expressions = legacy(
+					&mut class,
+					Vec::new(),
+					&variable,
+					metadata.then_some(&decorators.types),
+					temporaries,
+					data,
+					functions,
+					chain,
+				);
+				let class = Expression::ClassExpression(class, id);
+				expressions.insert(0, assign(&variable, class, &position));
+				expressions.push(reference(&variable, &position));
+				Expression::ParenthesizedExpression(
+					Box::new(sequence(expressions)),
+					position,
+					ExpressionId::new(),
+				)
+			}
+		};
+	}
+}
+
+impl Decorators {
+	/// Lowers a class declaration, adding the statements to `out`
+	#[allow(clippy::too_many_arguments)]
+	fn declaration(
+		&mut self,
+		mut class: ClassDeclaration<StatementPosition>,
+		class_decorators: Vec<Decorator>,
+		exported: bool,
+		out: &mut Vec<StatementOrDeclaration>,
+		data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		chain: &Chain,
+	) {
+		let temporaries = &mut self.temporaries.borrow_mut();
+		let name = StatementPosition::as_option_str(&class.name).unwrap_or_default().to_owned();
+		let position = class.position.clone();
+		let (value, after) = match self.semantics {
+			DecoratorSemantics::Standard => {
+				(standard(class, class_decorators, temporaries, data, functions), Vec::new())
+			}
+			DecoratorSemantics::Legacy { metadata } => {
+				let has_class_decorators = !class_decorators.is_empty();
+				let after = legacy(
+					&mut class,
+					class_decorators,
+					&name,
+					metadata.then_some(&self.types),
+					temporaries,
+					data,
+					functions,
+					chain,
+				);
+				if !has_class_decorators {
+					// Kept as a declaration as it is not reassigned
+					let declaration = if exported {
+						let exported = Exportable::Class(class);
+						Declaration::Export(Decorated::new(ExportDeclaration::Variable {
+							exported,
+							position,
+						}))
+					} else {
+						Declaration::Class(Decorated::new(class))
+					};
+					out.push(StatementOrDeclaration::Declaration(declaration));
+					out.extend(after.into_iter().map(expression_statement));
+					return;
+				}
+				(class_expression(class, Some(name.clone())), after)
+			}
+		};
+		let declaration = let_declaration(name, value, &position);
+		let declaration = if exported {
+			Declaration::Export(Decorated::new(ExportDeclaration::Variable {
+				exported: Exportable::Variable(declaration),
+				position,
+			}))
+		} else {
+			Declaration::Variable(declaration)
+		};
+		out.push(StatementOrDeclaration::Declaration(declaration));
+		out.extend(after.into_iter().map(expression_statement));
+	}
+
+	/// `@decorator export default class { ... }` to `let _default = ...; export default _default`
+	#[allow(clippy::too_many_arguments)]
+	fn default_export(
+		&mut self,
+		mut class: ClassDeclaration<ExpressionPosition>,
+		class_decorators: Vec<Decorator>,
+		position: Span,
+		out: &mut Vec<StatementOrDeclaration>,
+		data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		chain: &Chain,
+	) {
+		let temporaries = &mut self.temporaries.borrow_mut();
+		let name = match ExpressionPosition::as_option_str(&class.name) {
+			Some(name) => name.to_owned(),
+			None => temporaries.unique("_default"),
+		};
+		let (value, after) = match self.semantics {
+			DecoratorSemantics::Standard => {
+				(standard(class, class_decorators, temporaries, data, functions), Vec::new())
+			}
+			DecoratorSemantics::Legacy { metadata } => {
+				let after = legacy(
+					&mut class,
+					class_decorators,
+					&name,
+					metadata.then_some(&self.types),
+					temporaries,
+					data,
+					functions,
+					chain,
+				);
+				let class_name = ExpressionPosition::as_option_str(&class.name).map(str::to_owned);
+				(class_expression(class, class_name), after)
+			}
+		};
+		let declaration = let_declaration(name.clone(), value, &position);
+		out.push(StatementOrDeclaration::Declaration(Declaration::Variable(declaration)));
+		out.extend(after.into_iter().map(expression_statement));
+		let export = ExportDeclaration::Default {
+			expression: Box::new(reference(&name, &position)),
+			position,
+		};
+		out.push(StatementOrDeclaration::Declaration(Declaration::Export(Decorated::new(export))));
+	}
+}
+
+/// Lowers decorators with the standard semantics, returning the expression which creates the
+/// class
+fn standard<T: ExpressionOrStatementPosition>(
+	mut class: ClassDeclaration<T>,
+	class_decorators: Vec<Decorator>,
+	temporaries: &mut Temporaries,
+	data: &mut Vec<TempDiagnostic>,
+	functions: &mut ExtractedFunctions,
+) -> Expression {
+	let position = class.position.clone();
+	let es_decorate = temporaries.helper("__esDecorate", ES_DECORATE);
+	let run_initializers = temporaries.helper("__runInitializers", RUN_INITIALIZERS);
+	let name = T::as_option_str(&class.name).map(str::to_owned);
+	let variable = match &name {
+		Some(name) => name.clone(),
+		None => temporaries.unique("_class"),
+	};
+	// Declared (in order of evaluation) before the class
+	let mut variables = Vec::<(String, Option<Expression>)>::new();
+
+	// Metadata is shared by the decorators of the class and inherited from the class it extends
+	let metadata = temporaries.unique("_metadata");
+	let parent = match &class.extends {
+		Some(TypeReference::Name(name, _) | TypeReference::NameWithGenericArguments(name, ..)) => {
+			format!("{name}[Symbol.metadata] || null")
+		}
+		Some(TypeReference::NamespacedName(namespace, name, _)) => {
+			format!("{namespace}.{name}[Symbol.metadata] || null")
+		}
+		_ => "null".to_owned(),
+	};
+	let value = format!(
+		"typeof Symbol === \"function\" && Symbol.metadata ? Object.create({parent}) : void 0"
+	);
+	variables.push((metadata.clone(), Some(parse(value, functions))));
+
+	let class_decorators = (!class_decorators.is_empty()).then(|| {
+		let decorators = temporaries.unique("_classDecorators");
+		variables.push((decorators.clone(), Some(decorator_array(class_decorators, &position))));
+		let extra_initializers = temporaries.unique("_classExtraInitializers");
+		variables.push((extra_initializers.clone(), Some(parse("[]".to_owned(), functions))));
+		let descriptor = temporaries.unique("_classDescriptor");
+		variables.push((descriptor.clone(), None));
+		(decorators, extra_initializers, descriptor)
+	});
+
+	// Decorators of members are applied when the first static field is initialized (where `this`
+	// is the class) or after the class
+	let first_static_field = class
+		.members
+		.iter()
+		.position(|member| matches!(member.on, ClassMember::Property(Some(_), _)));
+	let target = if first_static_field.is_some() { "this" } else { variable.as_str() };
+	let mut static_initializers = None;
+	let mut instance_initializers = None;
+	let mut first_instance_field = None;
+	// Methods and accessors are decorated before fields and static members before instance
+	// members, each in order
+	let mut decorations = Vec::<(u8, String)>::new();
+	for (index, member) in class.members.iter_mut().enumerate() {
+		if let ClassMember::Property(None, _) = member.on {
+			first_instance_field.get_or_insert(index);
+		}
+		let decorators = std::mem::take(&mut member.decorators);
+		if decorators.is_empty() {
+			continue;
+		}
+		let decorators_position = decorators[0].position.clone();
+		let (is_static, kind, key) = match &mut member.on {
+			ClassMember::Constructor(_) => {
+				data.extend(
+					decorators
+						.iter()
+						.map(|decorator| invalid_decorator(decorator.position.clone())),
+				);
+				continue;
+			}
+			ClassMember::Function(is_static, function) => {
+				if GetFunction::<ClassFunctionBase>::get_function_ref(functions, function.0)
+					.is_none()
+				{
+					continue;
+				}
+				let mut function =
+					GetFunction::<ClassFunctionBase>::get_function(functions, function.0);
+				let kind = match function.header.1 {
+					GetSetGeneratorOrNone::Get(_) => "getter",
+					GetSetGeneratorOrNone::Set(_) => "setter",
+					_ => "method",
+				};
+				let key = standard_key(function.name.get_ast_mut(), temporaries, &mut variables);
+				functions.insert_function(function);
+				(is_static.is_some(), kind, key)
+			}
+			ClassMember::Property(is_static, ClassProperty { accessor, key, .. }) => (
+				is_static.is_some(),
+				if accessor.is_some() { "accessor" } else { "field" },
+				standard_key(key.get_ast_mut(), temporaries, &mut variables),
+			),
+		};
+
+		let base = match &key {
+			Key::Name(name) => format!("_{name}"),
+			Key::Variable(_) => "_member".to_owned(),
+		};
+		let decorators_variable = temporaries.unique(&format!("{base}_decorators"));
+		variables.push((
+			decorators_variable.clone(),
+			Some(decorator_array(decorators, &decorators_position)),
+		));
+		let extra_initializers = if is_static {
+			static_initializers
+				.get_or_insert_with(|| temporaries.unique("_staticExtraInitializers"))
+		} else {
+			instance_initializers
+				.get_or_insert_with(|| temporaries.unique("_instanceExtraInitializers"))
+		}
+		.clone();
+
+		let (name, access) = (key.string(), key.access("obj"));
+		let mut accessors = vec![format!("has: obj => {name} in obj")];
+		if kind != "setter" {
+			accessors.push(format!("get: obj => {access}"));
+		}
+		if matches!(kind, "setter" | "field" | "accessor") {
+			accessors.push(format!("set: (obj, value) => {{ {access} = value; }}"));
+		}
+		let context = format!(
+			"{{ kind: \"{kind}\", name: {name}, static: {is_static}, private: false, access: {{ {} }}, metadata: {metadata} }}",
+			accessors.join(", ")
+		);
+		let (decoration, group) = if let ClassMember::Property(_, property) = &mut member.on {
+			// Initializers returned by the decorators are run on the value
+			let initializers = temporaries.unique(&format!("{base}_initializers"));
+			variables.push((initializers.clone(), Some(parse("[]".to_owned(), functions))));
+			let value = property.value.take().map_or_else(|| void_zero(&position), |value| *value);
+			let this = Expression::ThisReference(position.clone(), ExpressionId::new());
+			let arguments = vec![this, reference(&initializers, &position), value];
+			property.value =
+				Some(Box::new(call(reference(&run_initializers, &position), arguments, &position)));
+			// Auto-accessors are on the class, so are decorated with the methods
+			let (decorated, group) = if kind == "accessor" { (target, 0) } else { ("null", 2) };
+			let decoration = format!(
+				"{es_decorate}({decorated}, null, {decorators_variable}, {context}, {initializers}, {extra_initializers})"
+			);
+			(decoration, if is_static { group } else { group + 1 })
+		} else {
+			let decoration = format!(
+				"{es_decorate}({target}, null, {decorators_variable}, {context}, null, {extra_initializers})"
+			);
+			(decoration, if is_static { 0 } else { 1 })
+		};
+		decorations.push((group, decoration));
+	}
+	decorations.sort_by_key(|(group, _)| *group);
+	let mut decorations = decorations
+		.into_iter()
+		.map(|(_, decoration)| parse(decoration, functions))
+		.collect::<Vec<_>>();
+	if let Some(static_initializers) = &static_initializers {
+		let run = format!("{run_initializers}({target}, {static_initializers})");
+		decorations.push(parse(run, functions));
+	}
+
+	if let Some(instance_initializers) = &instance_initializers {
+		let run = format!("{run_initializers}(this, {instance_initializers})");
+		match first_instance_field {
+			Some(index) => {
+				let ClassMember::Property(_, property) = &mut class.members[index].on else {
+					unreachable!()
+				};
+				let value =
+					property.value.take().map_or_else(|| void_zero(&position), |value| *value);
+				property.value = Some(Box::new(parenthesized_sequence(
+					vec![parse(run, functions), value],
+					&position,
+				)));
+			}
+			None => run_in_constructor(&mut class, run, functions),
+		}
+	}
+	let mut after = Vec::new();
+	match first_static_field {
+		Some(index) if !decorations.is_empty() => {
+			let ClassMember::Property(_, property) = &mut class.members[index].on else {
+				unreachable!()
+			};
+			let value = property.value.take().map_or_else(|| void_zero(&position), |value| *value);
+			decorations.push(value);
+			property.value = Some(Box::new(parenthesized_sequence(decorations, &position)));
+		}
+		_ => after.extend(decorations),
+	}
+
+	// The class is anonymous when it has decorators, so that references to it in its body are to
+	// the decorated class
+	let class_name = if class_decorators.is_some() { None } else { name.clone() };
+	if let Some((decorators, extra_initializers, descriptor)) = &class_decorators {
+		let name = name.as_deref().unwrap_or("default");
+		after.push(parse(
+			format!(
+				"{es_decorate}(null, {descriptor} = {{ value: {variable} }}, {decorators}, {{ kind: \"class\", name: \"{name}\", metadata: {metadata} }}, null, {extra_initializers})"
+			),
+			functions,
+		));
+		after.push(parse(format!("{variable} = {descriptor}.value"), functions));
+	}
+	after.push(parse(
+		format!(
+			"{metadata} && Object.defineProperty({variable}, Symbol.metadata, {{ enumerable: true, configurable: true, writable: true, value: {metadata} }})"
+		),
+		functions,
+	));
+	if let Some((_, extra_initializers, _)) = &class_decorators {
+		after.push(parse(
+			format!("{run_initializers}({variable}, {extra_initializers})"),
+			functions,
+		));
+	}
+	let mut declare = |base: &str, value| {
+		let name = temporaries.unique(base);
+		variables.push((name.clone(), value));
+		name
+	};
+	lower_auto_accessors(&mut class, &mut declare, functions);
+	variables
+		.extend(static_initializers.map(|name| (name, Some(parse("[]".to_owned(), functions)))));
+	variables
+		.extend(instance_initializers.map(|name| (name, Some(parse("[]".to_owned(), functions)))));
+
+	let mut items = vec![var_statement(variables, &position)];
+	let class = class_expression(class, class_name);
+	items.push(var_statement(vec![(variable.clone(), Some(class))], &position));
+	items.extend(after.into_iter().map(expression_statement));
+	items.push(StatementOrDeclaration::Statement(Statement::Return(
+		Keyword::new(position.clone()),
+		Some(reference(&variable, &position).into()),
+	)));
+
+	// `(() => { ... })()`
+	let function = FunctionBase::<ArrowFunctionBase> {
+		function_id: FunctionId::new(),
+		header: None,
+		name: (),
+		type_parameters: None,
+		parameters: FunctionParameters {
+			parameters: Vec::new(),
+			optional_parameters: Vec::new(),
+			rest_parameter: None,
+			position: position.clone(),
+		},
+		return_type: None,
+		body: ExpressionOrBlock::Block(Block(items, BlockId::new(), position.clone())),
+	};
+	let function = Expression::ExtractedArrowFunction(functions.new_extracted_function(function));
+	call(super::parenthesize(function, &position), Vec::new(), &position)
+}
+
+/// How generated code refers to the key of a decorated member
+enum Key {
+	/// An identifier, `obj.name`
+	Name(String),
+	/// A variable holding the key, `obj[_a]`
+	Variable(String),
+}
+
+impl Key {
+	fn string(&self) -> String {
+		match self {
+			Key::Name(name) => format!("\"{name}\""),
+			Key::Variable(variable) => variable.clone(),
+		}
+	}
+
+	fn access(&self, object: &str) -> String {
+		match self {
+			Key::Name(name) => format!("{object}.{name}"),
+			Key::Variable(variable) => format!("{object}[{variable}]"),
+		}
+	}
+}
+
+/// Keys which are not identifiers are stored in variables. Computed keys are stored when the
+/// class is created (`[_a = key]`)
+fn standard_key(
+	key: &mut PropertyKey,
+	temporaries: &mut Temporaries,
+	variables: &mut Vec<(String, Option<Expression>)>,
+) -> Key {
+	let value = match key {
+		PropertyKey::Ident(name, ..) => return Key::Name(name.clone()),
+		PropertyKey::StringLiteral(name, _, position) => Some(Expression::StringLiteral(
+			name.clone(),
+			Quoted::Double,
+			position.clone(),
+			ExpressionId::new(),
+		)),
+		// Names are strings, `String(1)`
+		PropertyKey::NumberLiteral(number, _, position) => {
+			let number = Expression::NumberLiteral(*number, position.clone(), ExpressionId::new());
+			Some(call(reference("String", position), vec![number], position))
+		}
+		PropertyKey::Computed(..) => None,
+	};
+	let variable = temporaries.name();
+	if let PropertyKey::Computed(expression, _, position) = key {
+		let value = take(expression, position);
+		**expression = assign(&variable, value, position);
+	}
+	variables.push((variable.clone(), value));
+	Key::Variable(variable)
+}
+
+/// Lowers decorators with the legacy semantics, returning the calls which decorate the class
+/// (which is in `variable`), to run after it is created. With `types`, metadata is added
+#[allow(clippy::too_many_arguments)]
+fn legacy<T: ExpressionOrStatementPosition>(
+	class: &mut ClassDeclaration<T>,
+	class_decorators: Vec<Decorator>,
+	variable: &str,
+	types: Option<&Types>,
+	temporaries: &mut Temporaries,
+	data: &mut Vec<TempDiagnostic>,
+	functions: &mut ExtractedFunctions,
+	chain: &Chain,
+) -> Vec<Expression> {
+	let position = class.position.clone();
+	let decorate = temporaries.helper("__decorate", DECORATE);
+	let metadata = types.map(|types| (types, temporaries.helper("__metadata", METADATA)));
+	// `__metadata("design:type", Number)`
+	let metadata_call =
+		|helper: &str, key: &str, value: String, functions: &mut ExtractedFunctions| {
+			let key = Expression::StringLiteral(
+				key.to_owned(),
+				Quoted::Double,
+				position.clone(),
+				ExpressionId::new(),
+			);
+			call(reference(helper, &position), vec![key, parse(value, functions)], &position)
+		};
+	// Decorators of auto-accessors are on the getter, which has the type of the field
+	let mut declare = |_: &str, value| match value {
+		Some(value) => temporaries.temporary_with_value(chain, value),
+		None => temporaries.temporary(chain),
+	};
+	lower_auto_accessors(class, &mut declare, functions);
+	let class_type_parameters = type_parameter_names(&class.type_parameters);
+
+	// Instance members are decorated before static members
+	let mut decorations = Vec::<(bool, Expression)>::new();
+	let mut constructor = None;
+	for class_member in class.members.iter_mut() {
+		if let ClassMember::Constructor(function) = &class_member.on {
+			constructor = Some(function.0);
+		}
+		let decorators = std::mem::take(&mut class_member.decorators);
+		if decorators.is_empty() {
+			continue;
+		}
+		let decorators_position = decorators[0].position.clone();
+		let mut decorators = decorators.into_iter().map(decorator_expression).collect::<Vec<_>>();
+		let (is_static, key, descriptor) = match &mut class_member.on {
+			ClassMember::Constructor(_) => {
+				data.push(invalid_decorator(decorators_position));
+				continue;
+			}
+			ClassMember::Function(is_static, function) => {
+				if GetFunction::<ClassFunctionBase>::get_function_ref(functions, function.0)
+					.is_none()
+				{
+					continue;
+				}
+				let mut function =
+					GetFunction::<ClassFunctionBase>::get_function(functions, function.0);
+				let key = legacy_key(function.name.get_ast_mut(), temporaries, chain);
+				if let Some((types, helper)) = &metadata {
+					let mut type_parameters = class_type_parameters.clone();
+					type_parameters.extend(type_parameter_names(&function.type_parameters));
+					let parameters =
+						types.serialize_parameters(&function.parameters, &type_parameters);
+					match function.header.1 {
+						GetSetGeneratorOrNone::Get(_) => {
+							let value =
+								types.serialize(function.return_type.as_ref(), &type_parameters);
+							decorators.push(metadata_call(helper, "design:type", value, functions));
+							decorators.push(metadata_call(
+								helper,
+								"design:paramtypes",
+								parameters,
+								functions,
+							));
+						}
+						GetSetGeneratorOrNone::Set(_) => {
+							let value = function
+								.parameters
+								.parameters
+								.first()
+								.and_then(|parameter| parameter.type_reference.as_ref());
+							let value = types.serialize(value, &type_parameters);
+							decorators.push(metadata_call(helper, "design:type", value, functions));
+							decorators.push(metadata_call(
+								helper,
+								"design:paramtypes",
+								parameters,
+								functions,
+							));
+						}
+						_ => {
+							let return_type = match &function.return_type {
+								Some(return_type) => {
+									types.serialize(Some(return_type), &type_parameters)
+								}
+								None if function.header.0.is_some() => "Promise".to_owned(),
+								None => "void 0".to_owned(),
+							};
+							decorators.push(metadata_call(
+								helper,
+								"design:type",
+								"Function".to_owned(),
+								functions,
+							));
+							decorators.push(metadata_call(
+								helper,
+								"design:paramtypes",
+								parameters,
+								functions,
+							));
+							decorators.push(metadata_call(
+								helper,
+								"design:returntype",
+								return_type,
+								functions,
+							));
+						}
+					}
+				}
+				functions.insert_function(function);
+				(is_static.is_some(), key, Expression::Null(position.clone(), ExpressionId::new()))
+			}
+			ClassMember::Property(is_static, property) => {
+				let key = legacy_key(property.key.get_ast_mut(), temporaries, chain);
+				if let Some((types, helper)) = &metadata {
+					let value =
+						types.serialize(property.type_reference.as_ref(), &class_type_parameters);
+					decorators.push(metadata_call(helper, "design:type", value, functions));
+				}
+				(is_static.is_some(), key, void_zero(&position))
+			}
+		};
+		let mut target = reference(variable, &position);
+		if !is_static {
+			target = member(target, "prototype", &position);
+		}
+		let arguments = vec![array(decorators, &decorators_position), target, key, descriptor];
+		decorations.push((is_static, call(reference(&decorate, &position), arguments, &position)));
+	}
+	decorations.sort_by_key(|(is_static, _)| *is_static);
+	let mut expressions =
+		decorations.into_iter().map(|(_, decoration)| decoration).collect::<Vec<_>>();
+
+	if !class_decorators.is_empty() {
+		// `C = __decorate([...], C)`
+		let decorators_position = class_decorators[0].position.clone();
+		let mut decorators =
+			class_decorators.into_iter().map(decorator_expression).collect::<Vec<_>>();
+		if let (Some((types, helper)), Some(constructor)) = (&metadata, constructor) {
+			if let Some(constructor) =
+				GetFunction::<ClassConstructorBase>::get_function_ref(functions, constructor)
+			{
+				let parameters =
+					types.serialize_parameters(&constructor.parameters, &class_type_parameters);
+				decorators.push(metadata_call(helper, "design:paramtypes", parameters, functions));
+			}
+		}
+		let arguments =
+			vec![array(decorators, &decorators_position), reference(variable, &position)];
+		let decorated = call(reference(&decorate, &position), arguments, &position);
+		expressions.push(assign(variable, decorated, &position));
+	}
+	expressions
+}
+
+/// The key of a member as an argument. Computed keys are stored when the class is created
+/// (`[_a = key]`)
+fn legacy_key(key: &mut PropertyKey, temporaries: &mut Temporaries, chain: &Chain) -> Expression {
+	match key {
+		PropertyKey::Ident(name, _, position) | PropertyKey::StringLiteral(name, _, position) => {
+			Expression::StringLiteral(
+				name.clone(),
+				Quoted::Double,
+				position.clone(),
+				ExpressionId::new(),
+			)
+		}
+		PropertyKey::NumberLiteral(number, _, position) => {
+			Expression::NumberLiteral(*number, position.clone(), ExpressionId::new())
+		}
+		PropertyKey::Computed(expression, _, position) => {
+			let variable = temporaries.temporary(chain);
+			let value = take(expression, position);
+			**expression = assign(&variable, value, position);
+			reference(&variable, position)
+		}
+	}
+}
+
+/// Auto-accessors to a field with a symbol key and a getter and setter for it, which have the
+/// decorators. `declare` creates a variable (from a base name and with a value) declared before
+/// the class
+fn lower_auto_accessors<T: ExpressionOrStatementPosition>(
+	class: &mut ClassDeclaration<T>,
+	declare: &mut dyn FnMut(&str, Option<Expression>) -> String,
+	functions: &mut ExtractedFunctions,
+) {
+	let is_auto_accessor = |member: &Decorated<ClassMember>| {
+		matches!(member.on, ClassMember::Property(_, ClassProperty { accessor: Some(_), .. }))
+	};
+	if !class.members.iter().any(is_auto_accessor) {
+		return;
+	}
+	for member in std::mem::take(&mut class.members) {
+		let Decorated { decorators, on: ClassMember::Property(is_static, mut property) } = member
+		else {
+			class.members.push(member);
+			continue;
+		};
+		if property.accessor.take().is_none() {
+			class
+				.members
+				.push(Decorated { decorators, on: ClassMember::Property(is_static, property) });
+			continue;
+		}
+		let position = property.key.get_position().into_owned();
+		let (getter_key, setter_key) = match property.key.get_ast().clone() {
+			// Computed keys are evaluated once, `get [_b = key]() { ... } set [_b](value) { ... }`.
+			// Keys of decorated members are already stored in a variable
+			PropertyKey::Computed(mut expression, id, position) => {
+				let variable = match assigned_variable(&expression) {
+					Some(variable) => variable,
+					None => {
+						let variable = declare("_key", None);
+						expression = Box::new(assign(&variable, *expression, &position));
+						variable
+					}
+				};
+				let reference = Box::new(reference(&variable, &position));
+				let setter_key =
+					PropertyKey::Computed(reference, PropertyId::new(), position.clone());
+				(PropertyKey::Computed(expression, id, position), setter_key)
+			}
+			key => (key.clone(), key),
+		};
+
+		let base = match &getter_key {
+			PropertyKey::Ident(name, ..) => format!("_{name}_storage"),
+			_ => "_storage".to_owned(),
+		};
+		let symbol = call(reference("Symbol", &position), Vec::new(), &position);
+		let storage = declare(&base, Some(symbol));
+		let key = Box::new(reference(&storage, &position));
+		property.key = WithComment::None(PropertyKey::Computed(key, PropertyId::new(), position));
+		let type_reference = property.type_reference.clone();
+		class.members.push(Decorated {
+			decorators: Vec::new(),
+			on: ClassMember::Property(is_static.clone(), property),
+		});
+
+		let source = format!(
+			"class {{ get _() {{ return this[{storage}]; }} set _(value) {{ this[{storage}] = value; }} }}"
+		);
+		let Expression::ClassExpression(parsed, _) = parse(source, functions) else {
+			unreachable!("accessors are not a class")
+		};
+		let accessors = [(getter_key, decorators), (setter_key, Vec::new())];
+		for (member, (key, decorators)) in parsed.members.into_iter().zip(accessors) {
+			let ClassMember::Function(_, function) = member.on else { unreachable!() };
+			let mut function =
+				GetFunction::<ClassFunctionBase>::get_function(functions, function.0);
+			function.name = WithComment::None(key);
+			if let GetSetGeneratorOrNone::Get(_) = function.header.1 {
+				function.return_type = type_reference.clone();
+			}
+			let on = ClassMember::Function(
+				is_static.clone(),
+				functions.new_extracted_function(function),
+			);
+			class.members.push(Decorated { decorators, on });
+		}
+	}
+}
+
+/// `name` in `name = value`
+fn assigned_variable(expression: &Expression) -> Option<String> {
+	match expression {
+		Expression::Assignment {
+			lhs:
+				LHSOfAssignment::VariableOrPropertyAccess(VariableOrPropertyAccess::Variable(name, ..)),
+			..
+		} => Some(name.clone()),
+		_ => None,
+	}
+}
+
+/// Runs `source` at the start of construction, after `super(...)` in the constructor. Adds a
+/// constructor if there is not one
+fn run_in_constructor<T: ExpressionOrStatementPosition>(
+	class: &mut ClassDeclaration<T>,
+	source: String,
+	functions: &mut ExtractedFunctions,
+) {
+	let constructor = class.members.iter().find_map(|member| match &member.on {
+		ClassMember::Constructor(constructor) => Some(constructor.0),
+		_ => None,
+	});
+	match constructor {
+		Some(id)
+			if GetFunction::<ClassConstructorBase>::get_function_ref(functions, id).is_some() =>
+		{
+			let mut constructor = GetFunction::<ClassConstructorBase>::get_function(functions, id);
+			let items = &mut constructor.body.0;
+			let index = match items.iter().position(is_super_call) {
+				Some(index) => index + 1,
+				None => items.iter().take_while(|item| is_directive(item)).count(),
+			};
+			items.insert(index, expression_statement(parse(source, functions)));
+			functions.insert_function(constructor);
+		}
+		Some(_) => {}
+		None => {
+			let source = if class.extends.is_some() {
+				format!("class _ extends Object {{ constructor(...args) {{ super(...args); {source}; }} }}")
+			} else {
+				format!("class {{ constructor() {{ {source}; }} }}")
+			};
+			let Expression::ClassExpression(mut parsed, _) = parse(source, functions) else {
+				unreachable!("constructor is not a class")
+			};
+			class.members.insert(0, parsed.members.remove(0));
+		}
+	}
+}
+
+/// Parses generated code, which only references names created by the transform
+fn parse(source: String, functions: &mut ExtractedFunctions) -> Expression {
+	let ParseOutput(expression, state) =
+		Expression::from_string(source, ParseSettings::default(), SourceId::NULL, None, Vec::new())
+			.expect("Invalid generated code");
+	functions.merge(state.function_extractor);
+	expression
+}
+
+/// `@a` to `a` and `@a(b)` to `a(b)`
+fn decorator_expression(decorator: Decorator) -> Expression {
+	let function = reference(&decorator.name, &decorator.position);
+	match decorator.arguments {
+		Some(arguments) => call(function, arguments, &decorator.position),
+		None => function,
+	}
+}
+
+fn decorator_array(decorators: Vec<Decorator>, position: &Span) -> Expression {
+	array(decorators.into_iter().map(decorator_expression).collect(), position)
+}
+
+fn array(elements: Vec<Expression>, position: &Span) -> Expression {
+	Expression::ArrayLiteral(
+		elements.into_iter().map(SpreadExpression::NonSpread).collect(),
+		position.clone(),
+		ExpressionId::new(),
+	)
+}
+
+fn parenthesized_sequence(expressions: Vec<Expression>, position: &Span) -> Expression {
+	Expression::ParenthesizedExpression(
+		Box::new(sequence(expressions)),
+		position.clone(),
+		ExpressionId::new(),
+	)
+}
+
+fn class_expression<T: ExpressionOrStatementPosition>(
+	class: ClassDeclaration<T>,
+	name: Option<String>,
+) -> Expression {
+	let position = class.position;
+	let class = ClassDeclaration::<ExpressionPosition> {
+		class_keyword: class.class_keyword,
+		name: name
+			.map(|name| VariableIdentifier::Standard(name, VariableId::new(), position.clone())),
+		type_parameters: class.type_parameters,
+		extends: class.extends,
+		members: class.members,
+		type_id: class.type_id,
+		variable_id: class.variable_id,
+		position,
+	};
+	Expression::ClassExpression(class, ExpressionId::new())
+}
+
+fn let_declaration(name: String, value: Expression, position: &Span) -> VariableDeclaration {
+	VariableDeclaration::LetDeclaration {
+		keyword: Keyword::new(position.clone()),
+		declarations: vec![declaration_item(name, position.clone(), Some(value))],
+	}
+}
+
+fn var_statement(
+	variables: Vec<(String, Option<Expression>)>,
+	position: &Span,
+) -> StatementOrDeclaration {
+	StatementOrDeclaration::Statement(Statement::VarVariable(VarVariableStatement {
+		keyword: Keyword::new(position.clone()),
+		declarations: variables
+			.into_iter()
+			.map(|(name, value)| declaration_item(name, position.clone(), value))
+			.collect(),
+	}))
+}
+
+fn expression_statement(expression: Expression) -> StatementOrDeclaration {
+	StatementOrDeclaration::Statement(Statement::Expression(expression.into()))
+}
+
+fn invalid_decorator(position: Span) -> TempDiagnostic {
+	TempDiagnostic {
+		label: "Decorators can only be applied to classes, methods, accessors and fields"
+			.to_owned(),
+		position,
+		kind: ErrorWarningInfo::Warning,
+		code: DiagnosticCode::InvalidDecorator,
+		secondary_labels: Vec::new(),
+		notes: Vec::new(),
+	}
+}
@@ -0,0 +1,730 @@
+//! Destructuring to temporaries, `const { a, b: [c] = d } = e` to
+//! `const a = e.a, _a = e.b, _b = _a === void 0 ? d : _a, c = _b[0]`. Destructured parameters
+//! become a temporary which is destructured at the start of the body, assignments become comma
+//! expressions and `for (const { a } of b)` destructures at the start of the loop body.
+//!
+//! Array patterns index the value, so (as with loose mode in other compilers) they support arrays
+//! and strings but not other iterables. Object rest copies the object with `Object.assign` and
+//! then deletes the other keys
+
+use parser::{
+	declarations::{
+		classes::{ClassConstructorBase, ClassFunctionBase, ClassMember},
+		export::Exportable,
+		variable::{DeclarationExpression, VariableDeclarationKeyword},
+		ClassDeclaration, ExportDeclaration, StatementFunctionBase, VariableDeclaration,
+		VariableDeclarationItem,
+	},
+	expressions::{
+		arrow_function::{ArrowFunctionBase, ExpressionOrBlock},
+		assignments::LHSOfAssignment,
+		object_literal::{ObjectLiteral, ObjectLiteralMember, ObjectLiteralMethodBase},
+		ExpressionFunctionBase, ExpressionId, MultipleExpression,
+	},
+	extractor::{ExtractedFunctions, GetFunction},
+	operators::{BinaryOperator, UnaryOperator},
+	statements::{
+		ForLoopCondition, ForLoopStatement, ForLoopStatementInitializer, VarVariableStatement,
+	},
+	ASTNode, ArrayDestructuringField, Block, BlockId, BlockLikeMut, BlockOrSingleStatement, Chain,
+	Declaration, Decorated, Expression, ExpressionOrStatementPosition, FunctionBase, FunctionBased,
+	FunctionId, FunctionParameters, Keyword, NumberStructure, ObjectDestructuringField,
+	OptionalOrWithDefaultValueParameter, PropertyKey, Quoted, Span, Statement,
+	StatementOrDeclaration, VariableField, VariableFieldInSourceCode, VariableId,
+	VariableIdentifier, VisitorsMut, WithComment,
+};
+
+use super::{
+	assign, call, declaration_item, is_directive, is_simple, member, parenthesize, reference,
+	sequence, take, void_zero, SharedTemporaries, Temporaries,
+};
+use crate::error_handling::TempDiagnostic;
+
+type Pattern = VariableField<VariableFieldInSourceCode>;
+
+/// Passes for [crate::temp::Transform::LowerDestructuring]
+pub(crate) fn passes() -> Vec<VisitorsMut<Vec<TempDiagnostic>>> {
+	super::passes(|temporaries| VisitorsMut {
+		block_visitors_mut: vec![Box::new(LowerDestructuring(temporaries.clone()))],
+		statement_visitors_mut: vec![Box::new(LowerDestructuring(temporaries.clone()))],
+		expression_visitors_mut: vec![Box::new(LowerDestructuring(temporaries.clone()))],
+		..Default::default()
+	})
+}
+
+struct LowerDestructuring(SharedTemporaries);
+
+/// Declarations (and functions and classes, for their parameters)
+impl<'a> parser::VisitorMut<BlockLikeMut<'a>, Vec<TempDiagnostic>> for LowerDestructuring {
+	fn visit_mut(
+		&mut self,
+		item: &mut BlockLikeMut<'a>,
+		_data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		let temporaries = &mut self.0.borrow_mut();
+		for statement in std::mem::take(item.items) {
+			match statement {
+				StatementOrDeclaration::Declaration(Declaration::Variable(mut declaration)) => {
+					lower_declaration(&mut declaration, temporaries);
+					let declaration = Declaration::Variable(declaration);
+					item.items.push(StatementOrDeclaration::Declaration(declaration));
+				}
+				StatementOrDeclaration::Statement(Statement::VarVariable(statement)) => {
+					let VarVariableStatement { keyword, declarations } = statement;
+					let declarations = lower_items(declarations, temporaries, Some)
+						.into_iter()
+						.map(|(_, item)| item)
+						.collect();
+					let statement = VarVariableStatement { keyword, declarations };
+					let statement = Statement::VarVariable(statement);
+					item.items.push(StatementOrDeclaration::Statement(statement));
+				}
+				StatementOrDeclaration::Declaration(Declaration::Export(Decorated {
+					decorators,
+					on:
+						ExportDeclaration::Variable {
+							exported: Exportable::Variable(declaration),
+							position,
+						},
+				})) => {
+					// Temporaries are declared separately so that they are not exported
+					for (is_temporary, declaration) in
+						lower_exported_declaration(declaration, temporaries)
+					{
+						let lowered = if is_temporary {
+							Declaration::Variable(declaration)
+						} else {
+							Declaration::Export(Decorated {
+								decorators: decorators.clone(),
+								on: ExportDeclaration::Variable {
+									exported: Exportable::Variable(declaration),
+									position: position.clone(),
+								},
+							})
+						};
+						item.items.push(StatementOrDeclaration::Declaration(lowered));
+					}
+				}
+				mut statement => {
+					match &mut statement {
+						StatementOrDeclaration::Declaration(Declaration::ExtractedFunction(
+							function,
+						)) => lower_extracted::<StatementFunctionBase>(
+							function.0,
+							functions,
+							temporaries,
+						),
+						StatementOrDeclaration::Declaration(Declaration::Function(Decorated {
+							on: function,
+							..
+						}))
+						| StatementOrDeclaration::Declaration(Declaration::Export(Decorated {
+							on:
+								ExportDeclaration::Variable {
+									exported: Exportable::Function(function),
+									..
+								},
+							..
+						})) => lower_function(function, temporaries),
+						StatementOrDeclaration::Declaration(Declaration::Class(Decorated {
+							on: class,
+							..
+						}))
+						| StatementOrDeclaration::Declaration(Declaration::Export(Decorated {
+							on:
+								ExportDeclaration::Variable {
+									exported: Exportable::Class(class), ..
+								},
+							..
+						})) => lower_class(class, functions, temporaries),
+						_ => {}
+					}
+					item.items.push(statement);
+				}
+			}
+		}
+	}
+}
+
+/// `for` loops
+impl parser::VisitorMut<Statement, Vec<TempDiagnostic>> for LowerDestructuring {
+	fn visit_mut(
+		&mut self,
+		item: &mut Statement,
+		_data: &mut Vec<TempDiagnostic>,
+		_functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		let Statement::ForLoopStatement(ForLoopStatement { condition, inner, .. }) = item else {
+			return;
+		};
+		let temporaries = &mut self.0.borrow_mut();
+		match condition {
+			ForLoopCondition::ForOf { keyword: Some(keyword), variable, .. }
+			| ForLoopCondition::ForIn { keyword: Some(keyword), variable, .. } => {
+				if let VariableField::Name(_) = variable.get_ast() {
+					return;
+				}
+				let position = variable.get_position().into_owned();
+				let name = temporaries.name();
+				let pattern = std::mem::replace(
+					variable,
+					WithComment::None(VariableField::Name(VariableIdentifier::Standard(
+						name.clone(),
+						VariableId::new(),
+						position.clone(),
+					))),
+				);
+				let item = VariableDeclarationItem {
+					name: pattern,
+					type_reference: None,
+					expression: reference(&name, &position),
+				};
+				let declaration = match keyword {
+					VariableDeclarationKeyword::Const(keyword) => {
+						let mut declaration = VariableDeclaration::ConstDeclaration {
+							keyword: keyword.clone(),
+							declarations: vec![item],
+						};
+						lower_declaration(&mut declaration, temporaries);
+						StatementOrDeclaration::Declaration(Declaration::Variable(declaration))
+					}
+					VariableDeclarationKeyword::Let(keyword) => {
+						let mut declaration = VariableDeclaration::LetDeclaration {
+							keyword: keyword.clone(),
+							declarations: vec![optional(item)],
+						};
+						lower_declaration(&mut declaration, temporaries);
+						StatementOrDeclaration::Declaration(Declaration::Variable(declaration))
+					}
+					VariableDeclarationKeyword::Var(keyword) => {
+						let declarations = lower_items(vec![optional(item)], temporaries, Some)
+							.into_iter()
+							.map(|(_, item)| item)
+							.collect();
+						let statement =
+							VarVariableStatement { keyword: keyword.clone(), declarations };
+						StatementOrDeclaration::Statement(Statement::VarVariable(statement))
+					}
+				};
+				match inner {
+					BlockOrSingleStatement::Braced(block) => block.0.insert(0, declaration),
+					BlockOrSingleStatement::SingleStatement(statement) => {
+						let position = statement.get_position().into_owned();
+						let statement = std::mem::replace(
+							statement.as_mut(),
+							Statement::Debugger(position.clone()),
+						);
+						*inner = BlockOrSingleStatement::Braced(Block(
+							vec![declaration, StatementOrDeclaration::Statement(statement)],
+							BlockId::new(),
+							position,
+						));
+					}
+				}
+			}
+			ForLoopCondition::Statements {
+				initializer: Some(ForLoopStatementInitializer::Statement(declaration)),
+				..
+			} => lower_declaration(declaration, temporaries),
+			ForLoopCondition::Statements {
+				initializer:
+					Some(ForLoopStatementInitializer::VarStatement(VarVariableStatement {
+						declarations,
+						..
+					})),
+				..
+			} => {
+				let items = lower_items(std::mem::take(declarations), temporaries, Some);
+				*declarations = items.into_iter().map(|(_, item)| item).collect();
+			}
+			_ => {}
+		}
+	}
+}
+
+/// Assignments and functions (and classes and object literals, for the parameters of their
+/// methods)
+impl parser::VisitorMut<Expression, Vec<TempDiagnostic>> for LowerDestructuring {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		_data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		chain: &Chain,
+	) {
+		let temporaries = &mut self.0.borrow_mut();
+		match item {
+			Expression::Assignment {
+				lhs:
+					LHSOfAssignment::ObjectDestructuring(..) | LHSOfAssignment::ArrayDestructuring(..),
+				..
+			} => {
+				let position = item.get_position().into_owned();
+				let Expression::Assignment { lhs, rhs, .. } = take(item, &position) else {
+					unreachable!()
+				};
+				let pattern = match lhs {
+					LHSOfAssignment::ObjectDestructuring(fields, position, _) => {
+						VariableField::Object(fields, position)
+					}
+					LHSOfAssignment::ArrayDestructuring(fields, position, _) => {
+						VariableField::Array(fields, position)
+					}
+					LHSOfAssignment::VariableOrPropertyAccess(_) => unreachable!(),
+				};
+				// The value of the assignment is the value being destructured
+				let name = temporaries.temporary(chain);
+				let mut bindings =
+					Bindings { temporaries, chain: Some(chain), bindings: Vec::new() };
+				bindings.destructure(pattern, reference(&name, &position));
+				let assignments = bindings
+					.bindings
+					.into_iter()
+					.map(|binding| assign(&binding.name, binding.value, &binding.position));
+				let expressions = std::iter::once(assign(&name, *rhs, &position))
+					.chain(assignments)
+					.chain(std::iter::once(reference(&name, &position)));
+				*item = Expression::ParenthesizedExpression(
+					Box::new(sequence(expressions)),
+					position,
+					ExpressionId::new(),
+				);
+			}
+			Expression::ExtractedExpressionFunction(function) => {
+				lower_extracted::<ExpressionFunctionBase>(function.0, functions, temporaries)
+			}
+			Expression::ExtractedArrowFunction(function) => {
+				let mut function =
+					GetFunction::<ArrowFunctionBase>::get_function(functions, function.0);
+				if let Some(declaration) = lower_parameters(&mut function.parameters, temporaries) {
+					match &mut function.body {
+						ExpressionOrBlock::Block(block) => block.0.insert(0, declaration),
+						ExpressionOrBlock::Expression(expression) => {
+							let position = expression.get_position().into_owned();
+							let expression = take(expression, &position);
+							let statement = Statement::Return(
+								Keyword::new(position.clone()),
+								Some(expression.into()),
+							);
+							function.body = ExpressionOrBlock::Block(Block(
+								vec![declaration, StatementOrDeclaration::Statement(statement)],
+								BlockId::new(),
+								position,
+							));
+						}
+					}
+				}
+				functions.insert_function(function);
+			}
+			Expression::ClassExpression(class, _) => lower_class(class, functions, temporaries),
+			Expression::ObjectLiteral(ObjectLiteral { members, .. }) => {
+				for member in members.iter() {
+					if let ObjectLiteralMember::Method(method) = member {
+						lower_extracted::<ObjectLiteralMethodBase>(
+							method.0,
+							functions,
+							temporaries,
+						);
+					}
+				}
+			}
+			_ => {}
+		}
+	}
+}
+
+/// A name bound by destructuring and its value
+struct Binding {
+	name: String,
+	position: Span,
+	value: Expression,
+	is_temporary: bool,
+}
+
+/// Collects the names bound by patterns, in order
+struct Bindings<'a> {
+	temporaries: &'a mut Temporaries,
+	/// For assignments temporaries are declared in the function, for declarations they are
+	/// declared with the other bindings
+	chain: Option<&'a Chain>,
+	bindings: Vec<Binding>,
+}
+
+impl Bindings<'_> {
+	fn destructure(&mut self, pattern: Pattern, value: Expression) {
+		match pattern {
+			VariableField::Name(VariableIdentifier::Standard(name, _, position)) => {
+				self.bindings.push(Binding { name, position, value, is_temporary: false })
+			}
+			VariableField::Name(VariableIdentifier::Cursor(_)) => {}
+			VariableField::Object(fields, position) => {
+				let object = self.store(value, &position);
+				// Keys of the other properties, for a rest property
+				let has_rest = fields
+					.iter()
+					.any(|field| matches!(field.get_ast(), ObjectDestructuringField::Spread(..)));
+				let mut keys = Vec::new();
+				for field in fields {
+					match field.unwrap_ast() {
+						ObjectDestructuringField::Name(
+							VariableIdentifier::Standard(name, _, position),
+							default_value,
+						) => {
+							keys.push(string(&name, &position));
+							let value = member(object.clone(), &name, &position);
+							let value = self.with_default(value, default_value, &position);
+							self.bindings.push(Binding {
+								name,
+								position,
+								value,
+								is_temporary: false,
+							});
+						}
+						ObjectDestructuringField::Name(VariableIdentifier::Cursor(_), _) => {}
+						ObjectDestructuringField::Map {
+							from,
+							variable_name,
+							default_value,
+							position,
+						} => {
+							let value = match from {
+								PropertyKey::Ident(name, _, position) => {
+									keys.push(string(&name, &position));
+									member(object.clone(), &name, &position)
+								}
+								PropertyKey::StringLiteral(key, _, position) => {
+									keys.push(string(&key, &position));
+									index(object.clone(), string(&key, &position), &position)
+								}
+								PropertyKey::NumberLiteral(key, _, position) => {
+									let key = Expression::NumberLiteral(
+										key,
+										position.clone(),
+										ExpressionId::new(),
+									);
+									keys.push(key.clone());
+									index(object.clone(), key, &position)
+								}
+								PropertyKey::Computed(key, _, position) => {
+									// The key is also needed to remove it from the rest
+									let key =
+										if has_rest { self.store(*key, &position) } else { *key };
+									keys.push(key.clone());
+									index(object.clone(), key, &position)
+								}
+							};
+							let value = self.with_default(value, default_value, &position);
+							self.destructure(variable_name.unwrap_ast(), value);
+						}
+						ObjectDestructuringField::Spread(
+							position,
+							VariableIdentifier::Standard(name, ..),
+						) => {
+							let assign =
+								member(reference("Object", &position), "assign", &position);
+							let empty = Expression::ObjectLiteral(ObjectLiteral {
+								members: Vec::new(),
+								position: position.clone(),
+								expression_id: ExpressionId::new(),
+							});
+							let copy = call(assign, vec![empty, object.clone()], &position);
+							let value = if keys.is_empty() {
+								copy
+							} else {
+								// `(delete _a.x, delete _a.y, _a)`
+								let copy = self.store(copy, &position);
+								let deletes =
+									keys.drain(..).map(|key| Expression::UnaryOperation {
+										operator: UnaryOperator::Delete,
+										operand: Box::new(index(copy.clone(), key, &position)),
+										position: position.clone(),
+										id: ExpressionId::new(),
+									});
+								let expressions = deletes
+									.collect::<Vec<_>>()
+									.into_iter()
+									.chain(std::iter::once(copy));
+								Expression::ParenthesizedExpression(
+									Box::new(sequence(expressions)),
+									position.clone(),
+									ExpressionId::new(),
+								)
+							};
+							self.bindings.push(Binding {
+								name,
+								position,
+								value,
+								is_temporary: false,
+							});
+						}
+						ObjectDestructuringField::Spread(_, VariableIdentifier::Cursor(_)) => {}
+					}
+				}
+			}
+			VariableField::Array(fields, position) => {
+				let array = self.store(value, &position);
+				for (i, field) in fields.into_iter().enumerate() {
+					let key = Expression::NumberLiteral(
+						NumberStructure::Number(i as f64),
+						position.clone(),
+						ExpressionId::new(),
+					);
+					match field {
+						ArrayDestructuringField::Name(pattern, default_value) => {
+							let value = index(array.clone(), key, &position);
+							let value = self.with_default(value, default_value, &position);
+							self.destructure(pattern.unwrap_ast(), value);
+						}
+						ArrayDestructuringField::Spread(
+							position,
+							VariableIdentifier::Standard(name, ..),
+						) => {
+							let slice = member(array.clone(), "slice", &position);
+							let value = call(slice, vec![key], &position);
+							self.bindings.push(Binding {
+								name,
+								position,
+								value,
+								is_temporary: false,
+							});
+						}
+						ArrayDestructuringField::Spread(_, VariableIdentifier::Cursor(_))
+						| ArrayDestructuringField::None => {}
+					}
+				}
+			}
+		}
+	}
+
+	/// Stores a value in a temporary (unless it is simple), returning the expression to use it
+	fn store(&mut self, value: Expression, position: &Span) -> Expression {
+		if is_simple(&value) {
+			return value;
+		}
+		let name = match self.chain {
+			Some(chain) => self.temporaries.temporary(chain),
+			None => self.temporaries.name(),
+		};
+		self.bindings.push(Binding {
+			name: name.clone(),
+			position: position.clone(),
+			value,
+			is_temporary: true,
+		});
+		reference(&name, position)
+	}
+
+	/// `_a === void 0 ? default_value : _a`
+	fn with_default(
+		&mut self,
+		value: Expression,
+		default_value: Option<Expression>,
+		position: &Span,
+	) -> Expression {
+		let Some(default_value) = default_value else { return value };
+		let value = self.store(value, position);
+		let condition = Expression::TernaryExpression {
+			condition: Box::new(Expression::BinaryOperation {
+				lhs: Box::new(value.clone()),
+				operator: BinaryOperator::StrictEqual,
+				rhs: Box::new(void_zero(position)),
+				id: ExpressionId::new(),
+			}),
+			truthy_result: Box::new(default_value),
+			falsy_result: Box::new(value),
+			id: ExpressionId::new(),
+		};
+		parenthesize(condition, position)
+	}
+}
+
+/// Items with patterns become an item for each bound name (and temporary). Returns whether each
+/// item is a temporary
+fn lower_items<T>(
+	items: Vec<VariableDeclarationItem<T>>,
+	temporaries: &mut Temporaries,
+	from_value: fn(Expression) -> T,
+) -> Vec<(bool, VariableDeclarationItem<T>)>
+where
+	T: DeclarationExpression + Into<Option<Expression>>,
+{
+	let mut lowered = Vec::new();
+	for item in items {
+		if let VariableField::Name(_) = item.name.get_ast() {
+			lowered.push((false, item));
+			continue;
+		}
+		let position = item.name.get_position().into_owned();
+		// Patterns without values are a syntax error
+		let value = item.expression.into().unwrap_or_else(|| void_zero(&position));
+		let mut bindings = Bindings { temporaries, chain: None, bindings: Vec::new() };
+		bindings.destructure(item.name.unwrap_ast(), value);
+		lowered.extend(bindings.bindings.into_iter().map(|binding| {
+			let item = declaration_item(binding.name, binding.position, from_value(binding.value));
+			(binding.is_temporary, item)
+		}));
+	}
+	lowered
+}
+
+/// An item of a `let` or `var` declaration
+fn optional(
+	item: VariableDeclarationItem<Expression>,
+) -> VariableDeclarationItem<Option<Expression>> {
+	let VariableDeclarationItem { name, type_reference, expression } = item;
+	VariableDeclarationItem { name, type_reference, expression: Some(expression) }
+}
+
+fn lower_declaration(declaration: &mut VariableDeclaration, temporaries: &mut Temporaries) {
+	match declaration {
+		VariableDeclaration::ConstDeclaration { declarations, .. } => {
+			let items = lower_items(std::mem::take(declarations), temporaries, |value| value);
+			*declarations = items.into_iter().map(|(_, item)| item).collect();
+		}
+		VariableDeclaration::LetDeclaration { declarations, .. } => {
+			let items = lower_items(std::mem::take(declarations), temporaries, Some);
+			*declarations = items.into_iter().map(|(_, item)| item).collect();
+		}
+	}
+}
+
+/// Splits a declaration into runs of temporaries and bound names
+fn lower_exported_declaration(
+	declaration: VariableDeclaration,
+	temporaries: &mut Temporaries,
+) -> Vec<(bool, VariableDeclaration)> {
+	fn runs<T: DeclarationExpression>(
+		items: Vec<(bool, VariableDeclarationItem<T>)>,
+		declaration: impl Fn(Vec<VariableDeclarationItem<T>>) -> VariableDeclaration,
+	) -> Vec<(bool, VariableDeclaration)> {
+		let mut runs: Vec<(bool, Vec<VariableDeclarationItem<T>>)> = Vec::new();
+		for (is_temporary, item) in items {
+			match runs.last_mut() {
+				Some((last, run)) if *last == is_temporary => run.push(item),
+				_ => runs.push((is_temporary, vec![item])),
+			}
+		}
+		runs.into_iter().map(|(is_temporary, run)| (is_temporary, declaration(run))).collect()
+	}
+
+	match declaration {
+		VariableDeclaration::ConstDeclaration { keyword, declarations } => {
+			runs(lower_items(declarations, temporaries, |value| value), |declarations| {
+				VariableDeclaration::ConstDeclaration { keyword: keyword.clone(), declarations }
+			})
+		}
+		VariableDeclaration::LetDeclaration { keyword, declarations } => {
+			runs(lower_items(declarations, temporaries, Some), |declarations| {
+				VariableDeclaration::LetDeclaration { keyword: keyword.clone(), declarations }
+			})
+		}
+	}
+}
+
+/// Replaces patterns in parameters with temporaries, returning a declaration which destructures
+/// them for the start of the body
+fn lower_parameters(
+	parameters: &mut FunctionParameters,
+	temporaries: &mut Temporaries,
+) -> Option<StatementOrDeclaration> {
+	let mut lowered = Vec::new();
+	let mut replace = |name: &mut WithComment<Pattern>| {
+		if let VariableField::Name(_) = name.get_ast() {
+			return;
+		}
+		let position = name.get_position().into_owned();
+		let temporary = temporaries.name();
+		let pattern = std::mem::replace(
+			name,
+			WithComment::None(VariableField::Name(VariableIdentifier::Standard(
+				temporary.clone(),
+				VariableId::new(),
+				position.clone(),
+			))),
+		);
+		lowered.push(VariableDeclarationItem {
+			name: pattern,
+			type_reference: None,
+			expression: Some(reference(&temporary, &position)),
+		});
+	};
+	for parameter in parameters.parameters.iter_mut() {
+		replace(&mut parameter.name);
+	}
+	for parameter in parameters.optional_parameters.iter_mut() {
+		if let OptionalOrWithDefaultValueParameter::WithDefaultValue { name, .. } = parameter {
+			replace(name);
+		}
+	}
+	if lowered.is_empty() {
+		return None;
+	}
+	let declarations =
+		lower_items(lowered, temporaries, Some).into_iter().map(|(_, item)| item).collect();
+	let statement =
+		VarVariableStatement { keyword: Keyword::new(parameters.position.clone()), declarations };
+	Some(StatementOrDeclaration::Statement(Statement::VarVariable(statement)))
+}
+
+fn lower_function<T: FunctionBased<Body = Block>>(
+	function: &mut FunctionBase<T>,
+	temporaries: &mut Temporaries,
+) {
+	if let Some(declaration) = lower_parameters(&mut function.parameters, temporaries) {
+		let body = &mut function.body.0;
+		let index = body.iter().take_while(|item| is_directive(item)).count();
+		body.insert(index, declaration);
+	}
+}
+
+fn lower_extracted<T: FunctionBased<Body = Block> + 'static>(
+	id: FunctionId<T>,
+	functions: &mut ExtractedFunctions,
+	temporaries: &mut Temporaries,
+) where
+	ExtractedFunctions: GetFunction<T>,
+{
+	let mut function = functions.get_function(id);
+	lower_function(&mut function, temporaries);
+	functions.insert_function(function);
+}
+
+fn lower_class<T: ExpressionOrStatementPosition>(
+	class: &ClassDeclaration<T>,
+	functions: &mut ExtractedFunctions,
+	temporaries: &mut Temporaries,
+) {
+	for member in class.members.iter() {
+		match &member.on {
+			ClassMember::Constructor(constructor) => {
+				lower_extracted::<ClassConstructorBase>(constructor.0, functions, temporaries)
+			}
+			ClassMember::Function(_, function) => {
+				lower_extracted::<ClassFunctionBase>(function.0, functions, temporaries)
+			}
+			ClassMember::Property(..) => {}
+		}
+	}
+}
+
+/// `object[key]`
+fn index(object: Expression, key: Expression, position: &Span) -> Expression {
+	Expression::Index {
+		indexee: Box::new(object),
+		indexer: Box::new(MultipleExpression::from(key)),
+		position: position.clone(),
+		expression_id: ExpressionId::new(),
+	}
+}
+
+fn string(value: &str, position: &Span) -> Expression {
+	Expression::StringLiteral(
+		value.to_owned(),
+		Quoted::Double,
+		position.clone(),
+		ExpressionId::new(),
+	)
+}
@@ -0,0 +1,64 @@
+//! `a ** b` to `Math.pow(a, b)` and `a **= b` to `a = Math.pow(a, b)`. The object and key of
+//! property targets are stored in temporaries so that they are only evaluated once
+
+use parser::{
+	extractor::ExtractedFunctions,
+	operators::{BinaryAssignmentOperator, BinaryOperator},
+	ASTNode, Chain, Expression, Span, VisitorsMut,
+};
+
+use super::{
+	assign_to, call, member, parenthesize, read_target, reference, reuse_target, take,
+	SharedTemporaries,
+};
+use crate::error_handling::TempDiagnostic;
+
+/// Passes for [crate::temp::Transform::LowerExponentiation]
+pub(crate) fn passes() -> Vec<VisitorsMut<Vec<TempDiagnostic>>> {
+	super::passes(|temporaries| VisitorsMut {
+		expression_visitors_mut: vec![Box::new(LowerExponentiation(temporaries.clone()))],
+		..Default::default()
+	})
+}
+
+struct LowerExponentiation(SharedTemporaries);
+
+impl parser::VisitorMut<Expression, Vec<TempDiagnostic>> for LowerExponentiation {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		_data: &mut Vec<TempDiagnostic>,
+		_functions: &mut ExtractedFunctions,
+		chain: &Chain,
+	) {
+		match item {
+			Expression::BinaryOperation { operator: BinaryOperator::Exponent, .. } => {
+				let position = item.get_position().into_owned();
+				let Expression::BinaryOperation { lhs, rhs, .. } = take(item, &position) else {
+					unreachable!()
+				};
+				*item = math_pow(*lhs, *rhs, &position);
+			}
+			Expression::BinaryAssignmentOperation {
+				operator: BinaryAssignmentOperator::ExponentAssign,
+				..
+			} => {
+				let position = item.get_position().into_owned();
+				let Expression::BinaryAssignmentOperation { lhs, rhs, .. } = take(item, &position)
+				else {
+					unreachable!()
+				};
+				// The target is evaluated before the value
+				let (stored, reused) = reuse_target(lhs, &mut self.0.borrow_mut(), chain);
+				let value = math_pow(read_target(reused), *rhs, &position);
+				*item = parenthesize(assign_to(stored, value), &position);
+			}
+			_ => {}
+		}
+	}
+}
+
+fn math_pow(base: Expression, exponent: Expression, position: &Span) -> Expression {
+	let function = member(reference("Math", position), "pow", position);
+	call(function, vec![base, exponent], position)
+}
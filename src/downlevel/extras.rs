@@ -0,0 +1,393 @@
+//! Lowers the non-standard syntax which the `extras` parse options enable
+//! - `a |> f` to `f(a)`
+//! - `f <@> g` (or `f ∘ g`) to `__compose(f, g)`, a function which calls `f` with the result of
+//!   `g`
+//! - `a /% b` (or `a ∣ b`), whether `a` is divisible by `b` (like `%%` in other languages), to
+//!   `a % b === 0`
+//! - `is (x) { T => ... }` to conditionals which test the type of `x` at runtime. Types which
+//!   cannot be tested (e.g. object literal types) do not match
+//! - `generator function` and `generator m() {}` to `function*` and `*m() {}`
+//!
+//! Operands are stored in temporaries where lowering would otherwise change the order they are
+//! evaluated in. `server` and `module` blocks are enabled by the same options, but the parser only
+//! lexes their keywords so there is nothing to lower
+
+use parser::{
+	expressions::{
+		arrow_function::{ArrowFunctionBase, ExpressionOrBlock},
+		ExpressionId, MultipleExpression,
+	},
+	extractor::{ExtractedFunctions, GetFunction},
+	is_expression::IsExpression,
+	operators::{BinaryOperator, UnaryOperator},
+	ASTNode, Block, BlockLikeMut, Chain, Expression, FunctionBase, FunctionId, FunctionParameters,
+	Keyword, NodeRef, NumberStructure, Quoted, Span, TypeReference, VisitorsMut,
+};
+
+use super::{
+	any_in_function, assign, call, is_literal, is_simple, lower_declared_functions,
+	lower_expression_functions, parenthesize, reference, sequence, take, unparenthesize, void_zero,
+	warning, LowerFunction, LowerableFunction, SharedTemporaries,
+};
+use crate::error_handling::TempDiagnostic;
+
+/// Passes for [crate::temp::Transform::LowerExtras]
+pub(crate) fn passes() -> Vec<VisitorsMut<Vec<TempDiagnostic>>> {
+	super::passes(|temporaries| VisitorsMut {
+		block_visitors_mut: vec![Box::new(LowerExtras(temporaries.clone()))],
+		expression_visitors_mut: vec![Box::new(LowerExtras(temporaries.clone()))],
+		..Default::default()
+	})
+}
+
+const COMPOSE: &str = "function (f, g) {
+	return function () {
+		return f(g.apply(this, arguments));
+	};
+}";
+
+struct LowerExtras(SharedTemporaries);
+
+impl<'a> parser::VisitorMut<BlockLikeMut<'a>, Vec<TempDiagnostic>> for LowerExtras {
+	fn visit_mut(
+		&mut self,
+		item: &mut BlockLikeMut<'a>,
+		data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		lower_declared_functions(item.items, functions, data, &mut GeneratorKeywords);
+	}
+}
+
+impl parser::VisitorMut<Expression, Vec<TempDiagnostic>> for LowerExtras {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		chain: &Chain,
+	) {
+		lower_expression_functions(item, functions, data, &mut GeneratorKeywords);
+		match item {
+			Expression::BinaryOperation {
+				operator: BinaryOperator::Pipe | BinaryOperator::Compose | BinaryOperator::Divides,
+				..
+			} => {
+				let position = item.get_position().into_owned();
+				let Expression::BinaryOperation { lhs, operator, rhs, .. } = take(item, &position)
+				else {
+					unreachable!()
+				};
+				*item = match operator {
+					BinaryOperator::Pipe => self.pipe(*lhs, *rhs, chain, &position),
+					BinaryOperator::Compose => {
+						let compose = self.0.borrow_mut().helper("__compose", COMPOSE);
+						call(reference(&compose, &position), vec![*lhs, *rhs], &position)
+					}
+					_ => {
+						let lhs = operand(*lhs, &position);
+						let remainder =
+							binary(lhs, BinaryOperator::Modulo, operand(*rhs, &position));
+						let zero = Expression::NumberLiteral(
+							NumberStructure::Number(0.),
+							position.clone(),
+							ExpressionId::new(),
+						);
+						parenthesize(
+							binary(remainder, BinaryOperator::StrictEqual, zero),
+							&position,
+						)
+					}
+				};
+			}
+			Expression::IsExpression(_) => {
+				let position = item.get_position().into_owned();
+				let Expression::IsExpression(is) = take(item, &position) else { unreachable!() };
+				*item = self.is_expression(is, data, functions, chain);
+			}
+			_ => {}
+		}
+	}
+}
+
+impl LowerExtras {
+	/// `f(a)`, or `(_a = a, f(_a))` when `a` has to be evaluated before `f`
+	fn pipe(
+		&self,
+		value: Expression,
+		function: Expression,
+		chain: &Chain,
+		position: &Span,
+	) -> Expression {
+		let function = operand(function, position);
+		// Arguments do not need brackets
+		let value = unparenthesize(value);
+		if is_pure(&value) || is_pure(&function) {
+			return call(function, vec![value], position);
+		}
+		let name = self.0.borrow_mut().temporary(chain);
+		let stored = assign(&name, value, position);
+		let call = call(function, vec![reference(&name, position)], position);
+		parenthesized_sequence([stored, call], position)
+	}
+
+	/// `(_a = x, test1 ? result1 : test2 ? result2 : void 0)`, where the tests are for the type
+	/// of each branch. Block branches become immediately invoked arrow functions
+	fn is_expression(
+		&self,
+		is: IsExpression,
+		data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		chain: &Chain,
+	) -> Expression {
+		let IsExpression { matcher, branches, position, .. } = is;
+		let matcher = match *matcher {
+			MultipleExpression { lhs: None, rhs } => rhs,
+			matcher => Expression::ParenthesizedExpression(
+				Box::new(matcher),
+				position.clone(),
+				ExpressionId::new(),
+			),
+		};
+		let (stored, value) = if is_simple(&matcher) || is_literal(&matcher) {
+			(None, matcher)
+		} else {
+			let name = self.0.borrow_mut().temporary(chain);
+			(Some(assign(&name, matcher, &position)), reference(&name, &position))
+		};
+
+		let mut result = void_zero(&position);
+		for (reference, body) in branches.into_iter().rev() {
+			let body = match body {
+				ExpressionOrBlock::Expression(expression) => *expression,
+				ExpressionOrBlock::Block(block) => invoke(block, functions, data),
+			};
+			result = match type_test(&reference, &value, data) {
+				Some(test) => Expression::TernaryExpression {
+					condition: Box::new(test),
+					truthy_result: Box::new(body),
+					falsy_result: Box::new(result),
+					id: ExpressionId::new(),
+				},
+				None => body,
+			};
+		}
+		match stored {
+			Some(stored) => parenthesized_sequence([stored, result], &position),
+			None => parenthesize(result, &position),
+		}
+	}
+}
+
+/// `generator function` to `function*` and `generator m() {}` to `*m() {}`
+struct GeneratorKeywords;
+
+impl LowerFunction for GeneratorKeywords {
+	fn lower<T: LowerableFunction>(
+		&mut self,
+		function: &mut FunctionBase<T>,
+		_functions: &mut ExtractedFunctions,
+		_data: &mut Vec<TempDiagnostic>,
+	) {
+		T::generator_star(function);
+	}
+}
+
+/// `(() => { ... })()`, or `await (async () => { ... })()` if the block awaits. `yield` cannot
+/// be used in the arrow function
+fn invoke(
+	block: Block,
+	functions: &mut ExtractedFunctions,
+	data: &mut Vec<TempDiagnostic>,
+) -> Expression {
+	let position = block.2.clone();
+	let contains = |operators: &[UnaryOperator]| {
+		any_in_function(NodeRef::Block(&block), functions, false, &|node| {
+			matches!(
+				node,
+				NodeRef::Expression(Expression::UnaryOperation { operator, .. })
+					if operators.contains(operator)
+			)
+		})
+	};
+	let is_async = contains(&[UnaryOperator::Await]);
+	if contains(&[UnaryOperator::Yield, UnaryOperator::DelegatedYield]) {
+		data.push(warning(
+			"'yield' in a block branch of an 'is' expression cannot be lowered".to_owned(),
+			position.clone(),
+		));
+	}
+	let function = FunctionBase::<ArrowFunctionBase> {
+		function_id: FunctionId::new(),
+		header: is_async.then(|| Keyword::new(position.clone())),
+		name: (),
+		type_parameters: None,
+		parameters: FunctionParameters {
+			parameters: Vec::new(),
+			optional_parameters: Vec::new(),
+			rest_parameter: None,
+			position: position.clone(),
+		},
+		return_type: None,
+		body: ExpressionOrBlock::Block(block),
+	};
+	let function = Expression::ExtractedArrowFunction(functions.new_extracted_function(function));
+	let call = call(parenthesize(function, &position), Vec::new(), &position);
+	if is_async {
+		Expression::UnaryOperation {
+			operator: UnaryOperator::Await,
+			operand: Box::new(call),
+			position,
+			id: ExpressionId::new(),
+		}
+	} else {
+		call
+	}
+}
+
+/// A condition for `value` being of type `reference`, or `None` if it always is
+fn type_test(
+	reference: &TypeReference,
+	value: &Expression,
+	data: &mut Vec<TempDiagnostic>,
+) -> Option<Expression> {
+	let position = reference.get_position().into_owned();
+	let is = |type_name: &str| {
+		let type_of = Expression::UnaryOperation {
+			operator: UnaryOperator::TypeOf,
+			operand: Box::new(value.clone()),
+			position: position.clone(),
+			id: ExpressionId::new(),
+		};
+		binary(type_of, BinaryOperator::StrictEqual, string(type_name, &position))
+	};
+	let equals = |other: Expression| binary(value.clone(), BinaryOperator::StrictEqual, other);
+	let instance_of = |class: Expression| binary(value.clone(), BinaryOperator::InstanceOf, class);
+	let is_array = || {
+		let is_array = super::member(self::reference("Array", &position), "isArray", &position);
+		call(is_array, vec![value.clone()], &position)
+	};
+	let test = match reference {
+		TypeReference::Name(name, _) | TypeReference::NameWithGenericArguments(name, ..) => {
+			match name.as_str() {
+				"number" | "string" | "boolean" | "bigint" | "symbol" | "undefined"
+				| "function" => is(name),
+				"object" => {
+					let not_null = Expression::Null(position.clone(), ExpressionId::new());
+					let not_null = binary(value.clone(), BinaryOperator::StrictNotEqual, not_null);
+					binary(is("object"), BinaryOperator::LogicalAnd, not_null)
+				}
+				"null" => equals(Expression::Null(position.clone(), ExpressionId::new())),
+				"void" => equals(void_zero(&position)),
+				"any" | "unknown" => return None,
+				"never" => Expression::BooleanLiteral(false, position, ExpressionId::new()),
+				"Array" | "ReadonlyArray" => is_array(),
+				name => instance_of(self::reference(name, &position)),
+			}
+		}
+		TypeReference::NamespacedName(namespace, name, _) => {
+			instance_of(super::member(self::reference(namespace, &position), name, &position))
+		}
+		TypeReference::StringLiteral(content, _) => equals(string(content, &position)),
+		TypeReference::NumberLiteral(number, _) => {
+			equals(Expression::NumberLiteral(*number, position, ExpressionId::new()))
+		}
+		TypeReference::BooleanLiteral(value, _) => {
+			equals(Expression::BooleanLiteral(*value, position, ExpressionId::new()))
+		}
+		TypeReference::ArrayLiteral(..) | TypeReference::TupleLiteral(..) => is_array(),
+		TypeReference::FunctionLiteral { .. } | TypeReference::ConstructorLiteral { .. } => {
+			is("function")
+		}
+		TypeReference::Union(members) => {
+			let mut tests = Vec::new();
+			for member in members {
+				tests.push(type_test(member, value, data)?);
+			}
+			tests.into_iter().reduce(|lhs, rhs| binary(lhs, BinaryOperator::LogicalOr, rhs))?
+		}
+		TypeReference::Intersection(members) => {
+			let tests = members.iter().filter_map(|member| type_test(member, value, data));
+			// `||` has a lower precedence than `&&`
+			let tests = tests.map(|test| match test {
+				Expression::BinaryOperation { operator: BinaryOperator::LogicalOr, .. } => {
+					parenthesize(test, &position)
+				}
+				test => test,
+			});
+			tests.reduce(|lhs, rhs| binary(lhs, BinaryOperator::LogicalAnd, rhs))?
+		}
+		TypeReference::ParenthesizedReference(inner, _)
+		| TypeReference::Readonly(inner, _)
+		| TypeReference::Decorated(_, inner, _) => return type_test(inner, value, data),
+		_ => {
+			data.push(warning(
+				"Type cannot be tested at runtime, so the branch never matches".to_owned(),
+				position.clone(),
+			));
+			Expression::BooleanLiteral(false, position, ExpressionId::new())
+		}
+	};
+	Some(test)
+}
+
+/// Whether evaluating `expression` before or after another expression does not change the result
+fn is_pure(expression: &Expression) -> bool {
+	is_simple(expression)
+		|| is_literal(expression)
+		|| matches!(
+			expression.get_non_parenthesized(),
+			Expression::ArrowFunction(..)
+				| Expression::ExpressionFunction(..)
+				| Expression::ExtractedArrowFunction(..)
+				| Expression::ExtractedExpressionFunction(..)
+		)
+}
+
+/// Parenthesizes `expression` unless it binds tighter than any operator
+fn operand(expression: Expression, position: &Span) -> Expression {
+	match expression {
+		Expression::VariableReference(..)
+		| Expression::ThisReference(..)
+		| Expression::NumberLiteral(..)
+		| Expression::StringLiteral(..)
+		| Expression::BooleanLiteral(..)
+		| Expression::Null(..)
+		| Expression::ParenthesizedExpression(..)
+		| Expression::PropertyAccess { .. }
+		| Expression::Index { .. }
+		| Expression::FunctionCall { .. } => expression,
+		expression => parenthesize(expression, position),
+	}
+}
+
+fn binary(lhs: Expression, operator: BinaryOperator, rhs: Expression) -> Expression {
+	Expression::BinaryOperation {
+		lhs: Box::new(lhs),
+		operator,
+		rhs: Box::new(rhs),
+		id: ExpressionId::new(),
+	}
+}
+
+fn string(content: &str, position: &Span) -> Expression {
+	Expression::StringLiteral(
+		content.to_owned(),
+		Quoted::Double,
+		position.clone(),
+		ExpressionId::new(),
+	)
+}
+
+/// `(a, b)`
+fn parenthesized_sequence(
+	expressions: impl IntoIterator<Item = Expression>,
+	position: &Span,
+) -> Expression {
+	Expression::ParenthesizedExpression(
+		Box::new(sequence(expressions)),
+		position.clone(),
+		ExpressionId::new(),
+	)
+}
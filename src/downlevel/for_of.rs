@@ -0,0 +1,164 @@
+//! `for ... of` loops to loops which call the iterator, `for (const x of xs) {}` to
+//! `for (var _a = __values(xs), _b = _a.next(); !_b.done; _b = _a.next()) { const x = _b.value; }`.
+//! `__values` calls `[Symbol.iterator]` where it exists and otherwise iterates over array-like
+//! values (arrays, strings, `arguments` etc), for environments without symbols. Loops over array
+//! literals are lowered to loops over indexes, `for (var _a = 0, _b = [...]; _a < _b.length; _a++)`.
+//!
+//! The iterator is not closed (`return()` is not called) when the loop is exited early. `for await`
+//! loops are lowered by [crate::temp::Transform::LowerAsyncIteration].
+//!
+//! The loop statement is kept, so labels, `break` and `continue` do not change
+
+use parser::{
+	declarations::VariableDeclarationItem,
+	expressions::{assignments::VariableOrPropertyAccess, ExpressionId, MultipleExpression},
+	extractor::ExtractedFunctions,
+	operators::{
+		BinaryOperator, IncrementOrDecrement, UnaryOperator, UnaryPostfixAssignmentOperator,
+	},
+	statements::{
+		ForLoopCondition, ForLoopStatement, ForLoopStatementInitializer, VarVariableStatement,
+	},
+	Block, BlockId, BlockOrSingleStatement, Chain, Expression, Keyword, NumberStructure, Span,
+	Statement, StatementOrDeclaration, VisitorsMut,
+};
+
+use super::{
+	assign, call, declaration_item, generators::VALUES, loop_variable, member, reference, shadows,
+	SharedTemporaries,
+};
+use crate::error_handling::TempDiagnostic;
+
+/// Passes for [crate::temp::Transform::LowerForOf]
+pub(crate) fn passes() -> Vec<VisitorsMut<Vec<TempDiagnostic>>> {
+	super::passes(|temporaries| VisitorsMut {
+		statement_visitors_mut: vec![Box::new(LowerForOf(temporaries.clone()))],
+		..Default::default()
+	})
+}
+
+struct LowerForOf(SharedTemporaries);
+
+impl parser::VisitorMut<Statement, Vec<TempDiagnostic>> for LowerForOf {
+	fn visit_mut(
+		&mut self,
+		item: &mut Statement,
+		_data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		let Statement::ForLoopStatement(ForLoopStatement { condition, inner, position }) = item
+		else {
+			return;
+		};
+		if !matches!(condition, ForLoopCondition::ForOf { is_await: false, .. }) {
+			return;
+		}
+		let position = position.clone();
+		let (first, second) = {
+			let mut temporaries = self.0.borrow_mut();
+			(temporaries.name(), temporaries.name())
+		};
+		let ForLoopCondition::ForOf { keyword, variable, of, .. } = std::mem::replace(
+			condition,
+			ForLoopCondition::Statements { initializer: None, condition: None, afterthought: None },
+		) else {
+			unreachable!()
+		};
+		let (declarations, test, afterthought, element) = if let Expression::ArrayLiteral(..) = of {
+			over_indexes(first, second, of, &position)
+		} else {
+			let values = self.0.borrow_mut().helper("__values", VALUES);
+			over_iterator(&values, first, second, of, &position)
+		};
+
+		let shadowed = shadows(inner, variable.get_ast(), functions);
+		let variable = loop_variable(keyword, variable, element, &position);
+		let body = std::mem::replace(
+			inner,
+			BlockOrSingleStatement::Braced(Block(Vec::new(), BlockId::new(), position.clone())),
+		);
+		let mut items = vec![variable];
+		match body {
+			BlockOrSingleStatement::Braced(block) if shadowed => {
+				items.push(StatementOrDeclaration::Statement(Statement::Block(block)))
+			}
+			BlockOrSingleStatement::Braced(block) => items.extend(block.0),
+			BlockOrSingleStatement::SingleStatement(statement) => {
+				items.push(StatementOrDeclaration::Statement(*statement))
+			}
+		}
+		*inner = BlockOrSingleStatement::Braced(Block(items, BlockId::new(), position.clone()));
+
+		let initializer =
+			VarVariableStatement { keyword: Keyword::new(position.clone()), declarations };
+		*condition = ForLoopCondition::Statements {
+			initializer: Some(ForLoopStatementInitializer::VarStatement(initializer)),
+			condition: Some(test),
+			afterthought: Some(afterthought),
+		};
+	}
+}
+
+/// The declarations, condition and afterthought of the loop and the element of each iteration
+type Loop = (Vec<VariableDeclarationItem<Option<Expression>>>, Expression, Expression, Expression);
+
+/// `var _a = 0, _b = xs; _a < _b.length; _a++` with `_b[_a]`
+fn over_indexes(index: String, array: String, of: Expression, position: &Span) -> Loop {
+	let zero = Expression::NumberLiteral(
+		NumberStructure::Number(0.0),
+		position.clone(),
+		ExpressionId::new(),
+	);
+	let declarations = vec![
+		declaration_item(index.clone(), position.clone(), Some(zero)),
+		declaration_item(array.clone(), position.clone(), Some(of)),
+	];
+	let in_bounds = Expression::BinaryOperation {
+		lhs: Box::new(reference(&index, position)),
+		operator: BinaryOperator::LessThan,
+		rhs: Box::new(member(reference(&array, position), "length", position)),
+		id: ExpressionId::new(),
+	};
+	let element = Expression::Index {
+		indexee: Box::new(reference(&array, position)),
+		indexer: Box::new(MultipleExpression::from(reference(&index, position))),
+		position: position.clone(),
+		expression_id: ExpressionId::new(),
+	};
+	let increment = Expression::UnaryPostfixAssignmentOperation {
+		operand: VariableOrPropertyAccess::Variable(index, position.clone(), ExpressionId::new()),
+		operator: UnaryPostfixAssignmentOperator(IncrementOrDecrement::Increment),
+		position: position.clone(),
+		id: ExpressionId::new(),
+	};
+	(declarations, in_bounds, increment, element)
+}
+
+/// `var _a = __values(xs), _b = _a.next(); !_b.done; _b = _a.next()` with `_b.value`
+fn over_iterator(
+	values: &str,
+	iterator: String,
+	result: String,
+	of: Expression,
+	position: &Span,
+) -> Loop {
+	let next =
+		|| call(member(reference(&iterator, position), "next", position), Vec::new(), position);
+	let declarations = vec![
+		declaration_item(
+			iterator.clone(),
+			position.clone(),
+			Some(call(reference(values, position), vec![of], position)),
+		),
+		declaration_item(result.clone(), position.clone(), Some(next())),
+	];
+	let not_done = Expression::UnaryOperation {
+		operator: UnaryOperator::LogicalNot,
+		operand: Box::new(member(reference(&result, position), "done", position)),
+		position: position.clone(),
+		id: ExpressionId::new(),
+	};
+	let advance = assign(&result, next(), position);
+	(declarations, not_done, advance, member(reference(&result, position), "value", position))
+}
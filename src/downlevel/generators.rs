@@ -0,0 +1,1720 @@
+//! Generators to state machines for ES5. `function* g() { ... }` becomes
+//! ```js
+//! function g() { var x; return __generator(this, function (_a) { switch (_a.label) { case 0: ... } }); }
+//! ```
+//! where the body is split into numbered cases at each `yield` (and at each place control flow
+//! can jump to). Each time the state function is called it runs from `_a.label` until it returns
+//! an instruction for `__generator`
+//! - `[0, value, label]` yields the value and continues from the label
+//! - `[1, value]` returns the value
+//! - `[2, label, exits]` jumps to the label, leaving `exits` `try` statements first (running their
+//!   `finally` blocks)
+//! - `[3, iterator, label]` yields each value of the iterator (`yield*`) and continues from the
+//!   label
+//! - `[4]` ends a `finally` block, continuing whatever entered it
+//!
+//! The value sent to the generator (the result of `yield`) is read from `_a.sent`, a `try`
+//! statement is entered with `_a.trys.push([catchLabel, finallyLabel])`. Parts of the body which
+//! do not contain `yield` are kept as they are, except for `return`, `var` and jumps out of them.
+//!
+//! Variables are declared in the outer function so that they keep their value between calls.
+//! Block scoped variables become function scoped, so a name declared in several blocks is shared.
+//! `arguments` of the generator is stored in a variable before the state function
+
+use std::{
+	cell::RefCell,
+	collections::{HashMap, HashSet},
+	rc::Rc,
+};
+
+use parser::{
+	declarations::{
+		classes::ClassDeclaration, variable::VariableDeclarationKeyword, VariableDeclaration,
+		VariableDeclarationItem,
+	},
+	expressions::{
+		assignments::{LHSOfAssignment, VariableOrPropertyAccess},
+		object_literal::{ObjectLiteral, ObjectLiteralMember},
+		template_literal::{TemplateLiteral, TemplateLiteralPart},
+		ExpressionFunctionBase, ExpressionId, MultipleExpression, SpreadExpression,
+	},
+	extractor::{ExtractedFunctions, GetFunction},
+	operators::{BinaryOperator, UnaryOperator},
+	statements::{
+		DoWhileStatement, ForLoopCondition, ForLoopStatement, ForLoopStatementInitializer,
+		IfStatement, SwitchBranch, SwitchStatement, TryCatchStatement, VarVariableStatement,
+		WhileStatement,
+	},
+	ASTNode, Block, BlockId, BlockLikeMut, BlockOrSingleStatement, Chain, Declaration, Decorated,
+	Expression, FunctionBase, FunctionHeader, FunctionId, FunctionParameters, Keyword, NodeRef,
+	NumberStructure, Parameter, Span, Statement, StatementOrDeclaration, VariableField,
+	VariableFieldInSourceCode, VariableId, VariableIdentifier, VisitorsMut, WithComment,
+};
+
+use super::{
+	any_in_function, assign, call, declaration_item, is_directive, is_literal, is_simple,
+	lower_declared_functions, lower_expression_functions, member, parenthesize, reference,
+	references_arguments, uses_super, void_zero, warning, LowerFunction, LowerableFunction,
+	SharedTemporaries, Temporaries,
+};
+use crate::error_handling::TempDiagnostic;
+
+/// Passes for [crate::temp::Transform::LowerGenerators]
+pub(crate) fn passes() -> Vec<VisitorsMut<Vec<TempDiagnostic>>> {
+	super::passes(|temporaries| {
+		let shared = Rc::new(RefCell::new(Generators {
+			temporaries: temporaries.clone(),
+			arguments: HashMap::new(),
+		}));
+		VisitorsMut {
+			block_visitors_mut: vec![Box::new(LowerGenerators(shared.clone()))],
+			expression_visitors_mut: vec![Box::new(LowerGenerators(shared))],
+			..Default::default()
+		}
+	})
+}
+
+const GENERATOR: &str = "function (thisArg, body) {
+	var state = { label: 0, sent: void 0, trys: [] }, started = false, running = false, done = false, delegate, instance = {};
+	instance.next = verb(0);
+	instance[\"throw\"] = verb(1);
+	instance[\"return\"] = verb(2);
+	if (typeof Symbol === \"function\" && Symbol.iterator) instance[Symbol.iterator] = function () { return this; };
+	return instance;
+	function verb(kind) { return function (value) { return step(kind, value); }; }
+	function step(kind, value) {
+		if (running) throw new TypeError(\"Generator is already executing\");
+		if (!started) { started = true; if (kind !== 0) done = true; }
+		if (done) {
+			if (kind === 1) throw value;
+			return { value: kind === 2 ? value : void 0, done: true };
+		}
+		running = true;
+		try {
+			if (delegate) return run([6, kind, value]);
+			if (kind === 0) state.sent = value;
+			return run(kind === 0 ? null : kind === 1 ? [5, value] : [1, value]);
+		} finally { running = false; }
+	}
+	function run(op) {
+		while (true) {
+			if (!op) {
+				try { op = body.call(thisArg, state); } catch (error) { op = [5, error]; }
+			}
+			switch (op[0]) {
+				case 0: state.label = op[2]; return { value: op[1], done: false };
+				case 3: delegate = op[1]; state.label = op[2]; op = [6, 0, void 0]; break;
+				case 4:
+					op = state.trys.pop()[3];
+					if (op[0] === 2) op = [2, op[1], op[2] - 1];
+					break;
+				case 6:
+					var method = op[1] === 0 ? delegate.next : op[1] === 1 ? delegate[\"throw\"] : delegate[\"return\"], result;
+					if (method === void 0) {
+						var iterator = delegate;
+						delegate = void 0;
+						if (op[1] === 1) {
+							if (iterator[\"return\"]) iterator[\"return\"]();
+							op = [5, new TypeError(\"The iterator does not provide a 'throw' method\")];
+						} else op = [1, op[2]];
+						break;
+					}
+					try { result = method.call(delegate, op[2]); } catch (error) { delegate = void 0; op = [5, error]; break; }
+					if (!result.done) return { value: result.value, done: false };
+					delegate = void 0;
+					if (op[1] === 2) op = [1, result.value];
+					else { state.sent = result.value; op = null; }
+					break;
+				default:
+					if (op[0] === 2 && !op[2]) { state.label = op[1]; op = null; break; }
+					var entry = state.trys[state.trys.length - 1];
+					if (!entry) {
+						done = true;
+						if (op[0] === 5) throw op[1];
+						return { value: op[1], done: true };
+					}
+					var phase = entry[2] || 0;
+					if (op[0] === 5 && phase === 0 && entry[0] !== void 0) { entry[2] = 1; state.sent = op[1]; state.label = entry[0]; op = null; }
+					else if (phase < 2 && entry[1] !== void 0) { entry[2] = 2; entry[3] = op; state.label = entry[1]; op = null; }
+					else { state.trys.pop(); if (op[0] === 2) op = [2, op[1], op[2] - 1]; }
+			}
+		}
+	}
+}";
+
+pub(crate) const VALUES: &str = "function (object) {
+	var method = typeof Symbol === \"function\" && Symbol.iterator && object[Symbol.iterator], index = 0;
+	if (method) return method.call(object);
+	if (object && typeof object.next === \"function\") return object;
+	if (object && typeof object.length === \"number\") return {
+		next: function () {
+			if (object && index >= object.length) object = void 0;
+			return { value: object && object[index++], done: !object };
+		}
+	};
+	throw new TypeError(object + \" is not iterable\");
+}";
+
+struct Generators {
+	temporaries: SharedTemporaries,
+	/// Names `arguments` is stored in, by the body of the state function which references it
+	arguments: HashMap<BlockId, String>,
+}
+
+impl LowerFunction for Generators {
+	fn lower<T: LowerableFunction>(
+		&mut self,
+		function: &mut FunctionBase<T>,
+		functions: &mut ExtractedFunctions,
+		data: &mut Vec<TempDiagnostic>,
+	) {
+		if !T::is_generator(function) || T::is_async(function) {
+			return;
+		}
+		if uses_super(T::body_ref(function), functions) {
+			data.push(warning(
+				"Generators which use 'super' are not lowered".to_owned(),
+				function.get_position().into_owned(),
+			));
+			return;
+		}
+		let uses_arguments = references_arguments(T::body_ref(function), functions);
+		T::remove_generator(function);
+		let Block(mut items, _, position) = T::take_body(function);
+		let body = items.split_off(items.iter().take_while(|item| is_directive(item)).count());
+
+		let mut temporaries = self.temporaries.borrow_mut();
+		let generator = temporaries.helper("__generator", GENERATOR);
+		let state = temporaries.name();
+		let mut machine =
+			StateMachine::new(&mut temporaries, functions, data, state.clone(), &position);
+		machine.items(body);
+		let (body, mut hoisted, hoisted_functions) = machine.finish();
+
+		let block = BlockId::new();
+		if uses_arguments {
+			let name = temporaries.unique("_arguments");
+			self.arguments.insert(block, name.clone());
+			hoisted.push((name, Some(reference("arguments", &position))));
+		}
+		if !hoisted.is_empty() {
+			let declarations = hoisted
+				.into_iter()
+				.map(|(name, value)| declaration_item(name, position.clone(), value))
+				.collect();
+			items.push(StatementOrDeclaration::Statement(Statement::VarVariable(
+				VarVariableStatement { keyword: Keyword::new(position.clone()), declarations },
+			)));
+		}
+		items.extend(hoisted_functions);
+
+		let state_function = FunctionBase::<ExpressionFunctionBase> {
+			function_id: FunctionId::new(),
+			header: FunctionHeader::VirginFunctionHeader {
+				async_keyword: None,
+				function_keyword: Keyword::new(position.clone()),
+				generator_star_token_pos: None,
+			},
+			name: None,
+			type_parameters: None,
+			parameters: FunctionParameters {
+				parameters: vec![Parameter {
+					name: WithComment::None(VariableField::Name(VariableIdentifier::Standard(
+						state,
+						VariableId::new(),
+						position.clone(),
+					))),
+					type_reference: None,
+					property: None,
+				}],
+				optional_parameters: Vec::new(),
+				rest_parameter: None,
+				position: position.clone(),
+			},
+			return_type: None,
+			body: Block(body, block, position.clone()),
+		};
+		let state_function = Expression::ExtractedExpressionFunction(
+			functions.new_extracted_function(state_function),
+		);
+		let this = Expression::ThisReference(position.clone(), ExpressionId::new());
+		let value = call(reference(&generator, &position), vec![this, state_function], &position);
+		T::set_body(function, items, value);
+	}
+}
+
+/// A statement which can be jumped to with `break` (or `continue`)
+struct JumpTarget {
+	labels: Vec<String>,
+	/// Whether `break` without a label exits it (loops and `switch`)
+	unlabelled: bool,
+	break_label: u32,
+	/// For loops
+	continue_label: Option<u32>,
+	try_depth: usize,
+}
+
+/// A statement in a part of the body which is kept as it is
+struct NativeTarget {
+	labels: Vec<String>,
+	is_loop: bool,
+	is_switch: bool,
+}
+
+/// Variables declared in the outer function, with their initial value
+type Hoisted = Vec<(String, Option<Expression>)>;
+
+/// Compiles the body of a generator into the cases of the state function
+struct StateMachine<'a> {
+	temporaries: &'a mut Temporaries,
+	functions: &'a ExtractedFunctions,
+	data: &'a mut Vec<TempDiagnostic>,
+	/// The parameter of the state function
+	state: String,
+	position: Span,
+	cases: Vec<(u32, Vec<Statement>)>,
+	next_label: u32,
+	hoisted: Hoisted,
+	hoisted_functions: Vec<StatementOrDeclaration>,
+	/// Temporaries created by the state machine, which hold values computed before a `yield`
+	own: HashSet<String>,
+	targets: Vec<JumpTarget>,
+	/// Number of compiled `try` statements (including their `catch` and `finally` blocks) the
+	/// current statement is in
+	try_depth: usize,
+}
+
+impl<'a> StateMachine<'a> {
+	fn new(
+		temporaries: &'a mut Temporaries,
+		functions: &'a ExtractedFunctions,
+		data: &'a mut Vec<TempDiagnostic>,
+		state: String,
+		position: &Span,
+	) -> Self {
+		Self {
+			temporaries,
+			functions,
+			data,
+			state,
+			position: position.clone(),
+			cases: vec![(0, Vec::new())],
+			next_label: 1,
+			hoisted: Vec::new(),
+			hoisted_functions: Vec::new(),
+			own: HashSet::new(),
+			targets: Vec::new(),
+			try_depth: 0,
+		}
+	}
+
+	/// The body of the state function, the variables to declare in the outer function and the
+	/// functions declared in the body
+	fn finish(mut self) -> (Vec<StatementOrDeclaration>, Hoisted, Vec<StatementOrDeclaration>) {
+		let ends = matches!(
+			self.cases.last().and_then(|(_, statements)| statements.last()),
+			Some(Statement::Return(..) | Statement::Throw(..))
+		);
+		if !ends {
+			let end = self.instruction(vec![self.number(1)]);
+			self.emit(end);
+		}
+		let body = if self.cases.len() == 1 {
+			let (_, statements) = self.cases.pop().unwrap();
+			statements.into_iter().map(StatementOrDeclaration::Statement).collect()
+		} else {
+			vec![StatementOrDeclaration::Statement(Statement::SwitchStatement(self.switch()))]
+		};
+		(body, self.hoisted, self.hoisted_functions)
+	}
+
+	/// `switch (_a.label) { case 0: ... }`
+	fn switch(&mut self) -> SwitchStatement {
+		let position = self.position.clone();
+		let branches = std::mem::take(&mut self.cases)
+			.into_iter()
+			.map(|(label, statements)| SwitchBranch::Case(self.number(label), statements))
+			.collect();
+		SwitchStatement {
+			case: member(reference(&self.state, &position), "label", &position),
+			branches,
+			position,
+		}
+	}
+
+	fn has_yield(&self, node: NodeRef) -> bool {
+		any_in_function(node, self.functions, false, &|node| {
+			matches!(
+				node,
+				NodeRef::Expression(Expression::UnaryOperation {
+					operator: UnaryOperator::Yield | UnaryOperator::DelegatedYield,
+					..
+				})
+			)
+		})
+	}
+
+	fn multiple_has_yield(&self, expression: &MultipleExpression) -> bool {
+		expression.lhs.as_ref().is_some_and(|lhs| self.multiple_has_yield(lhs))
+			|| self.has_yield(NodeRef::Expression(&expression.rhs))
+	}
+
+	// Building blocks
+
+	fn label(&mut self) -> u32 {
+		self.next_label += 1;
+		self.next_label - 1
+	}
+
+	/// Starts the case for a label, the previous case falls through to it
+	fn mark(&mut self, label: u32) {
+		self.cases.push((label, Vec::new()));
+	}
+
+	fn emit(&mut self, statement: Statement) {
+		self.cases.last_mut().unwrap().1.push(statement);
+	}
+
+	/// Emits an expression for its side effects
+	fn effect(&mut self, expression: Expression) {
+		if is_simple(&expression) || is_literal(&expression) || self.is_sent(&expression) {
+			return;
+		}
+		self.emit(Statement::Expression(expression.into()));
+	}
+
+	fn number(&self, value: u32) -> Expression {
+		Expression::NumberLiteral(
+			NumberStructure::Number(value.into()),
+			self.position.clone(),
+			ExpressionId::new(),
+		)
+	}
+
+	/// `return [...]`
+	fn instruction(&self, values: Vec<Expression>) -> Statement {
+		let array = Expression::ArrayLiteral(
+			values.into_iter().map(SpreadExpression::NonSpread).collect(),
+			self.position.clone(),
+			ExpressionId::new(),
+		);
+		Statement::Return(Keyword::new(self.position.clone()), Some(array.into()))
+	}
+
+	fn jump_instruction(&self, label: u32, exits: usize) -> Statement {
+		let mut values = vec![self.number(2), self.number(label)];
+		if exits > 0 {
+			values.push(self.number(exits as u32));
+		}
+		self.instruction(values)
+	}
+
+	fn jump(&mut self, label: u32) {
+		let jump = self.jump_instruction(label, 0);
+		self.emit(jump);
+	}
+
+	/// A jump out of the current `try` statement
+	fn jump_exiting(&mut self, label: u32) {
+		let jump = self.jump_instruction(label, 1);
+		self.emit(jump);
+	}
+
+	fn jump_if(&mut self, condition: Expression, label: u32) {
+		let jump = self.jump_instruction(label, 0);
+		self.emit(Statement::IfStatement(IfStatement {
+			condition: condition.into(),
+			inner: BlockOrSingleStatement::SingleStatement(Box::new(jump)),
+			else_conditions: Vec::new(),
+			trailing_else: None,
+			position: self.position.clone(),
+		}));
+	}
+
+	/// The value sent to the generator when it is resumed, the result of `yield`
+	fn sent(&self) -> Expression {
+		member(reference(&self.state, &self.position), "sent", &self.position)
+	}
+
+	fn is_sent(&self, expression: &Expression) -> bool {
+		matches!(
+			expression,
+			Expression::PropertyAccess { parent, property: parser::PropertyReference::Standard(property), .. }
+				if property == "sent" && matches!(&**parent, Expression::VariableReference(name, ..) if *name == self.state)
+		)
+	}
+
+	fn hoist(&mut self, name: String) {
+		if !self.hoisted.iter().any(|(existing, _)| *existing == name) {
+			self.hoisted.push((name, None));
+		}
+	}
+
+	fn temporary(&mut self) -> String {
+		let name = self.temporaries.name();
+		self.hoist(name.clone());
+		self.own.insert(name.clone());
+		name
+	}
+
+	/// Stores a value computed before a `yield` in a temporary, as the `yield` returns from the
+	/// state function
+	fn spill(&mut self, expression: Expression) -> Expression {
+		let is_stored = matches!(&expression, Expression::VariableReference(name, ..) if self.own.contains(name));
+		if is_stored
+			|| is_literal(&expression)
+			|| matches!(expression, Expression::ThisReference(..))
+		{
+			return expression;
+		}
+		let name = self.temporary();
+		let position = expression.get_position().into_owned();
+		self.emit(Statement::Expression(assign(&name, expression, &position).into()));
+		reference(&name, &position)
+	}
+
+	fn values(&mut self, iterable: Expression) -> Expression {
+		let values = self.temporaries.helper("__values", VALUES);
+		let position = iterable.get_position().into_owned();
+		call(reference(&values, &position), vec![iterable], &position)
+	}
+
+	// Declarations
+
+	fn items(&mut self, items: Vec<StatementOrDeclaration>) {
+		for item in items {
+			self.item(item);
+		}
+	}
+
+	fn item(&mut self, item: StatementOrDeclaration) {
+		match item {
+			StatementOrDeclaration::Statement(statement) => self.statement(statement, Vec::new()),
+			StatementOrDeclaration::Declaration(Declaration::Variable(declaration)) => {
+				self.variable_declaration(declaration);
+			}
+			StatementOrDeclaration::Declaration(
+				Declaration::Function(_) | Declaration::ExtractedFunction(_),
+			) => self.hoisted_functions.push(item),
+			StatementOrDeclaration::Declaration(Declaration::Class(Decorated {
+				decorators,
+				on: class,
+				..
+			})) => {
+				if !decorators.is_empty() {
+					self.data.push(warning(
+						"Decorators on classes in generators are not lowered".to_owned(),
+						class.position.clone(),
+					));
+				}
+				let name = match &class.name {
+					VariableIdentifier::Standard(name, ..) => name.clone(),
+					VariableIdentifier::Cursor(..) => unreachable!("cursor in build"),
+				};
+				let position = class.position.clone();
+				let class = ClassDeclaration {
+					class_keyword: class.class_keyword,
+					name: Some(class.name),
+					type_parameters: class.type_parameters,
+					extends: class.extends,
+					members: class.members,
+					type_id: class.type_id,
+					variable_id: class.variable_id,
+					position: class.position,
+				};
+				self.hoist(name.clone());
+				let value = Expression::ClassExpression(class, ExpressionId::new());
+				self.emit(Statement::Expression(assign(&name, value, &position).into()));
+			}
+			StatementOrDeclaration::Declaration(
+				Declaration::Interface(_)
+				| Declaration::TypeAlias(_)
+				| Declaration::DeclareVariable(_)
+				| Declaration::DeclareFunction(_)
+				| Declaration::DeclareInterface(_),
+			) => {}
+			StatementOrDeclaration::Declaration(declaration) => {
+				self.data.push(warning(
+					"Declaration in generator is not lowered".to_owned(),
+					declaration.get_position().into_owned(),
+				));
+			}
+		}
+	}
+
+	fn variable_declaration(&mut self, declaration: VariableDeclaration) {
+		match declaration {
+			VariableDeclaration::ConstDeclaration { declarations, .. } => {
+				for VariableDeclarationItem { name, expression, .. } in declarations {
+					self.declare(name.unwrap_ast(), Some(expression));
+				}
+			}
+			VariableDeclaration::LetDeclaration { declarations, .. } => {
+				for VariableDeclarationItem { name, expression, .. } in declarations {
+					let value = expression.unwrap_or_else(|| void_zero(&self.position));
+					self.declare(name.unwrap_ast(), Some(value));
+				}
+			}
+		}
+	}
+
+	/// Hoists the names of a variable (or destructuring pattern) and assigns the value
+	fn declare(
+		&mut self,
+		field: VariableField<VariableFieldInSourceCode>,
+		value: Option<Expression>,
+	) {
+		let mut names = Vec::new();
+		crate::bundle::variable_field_identifiers(&field, &mut names);
+		for (name, _) in names {
+			self.hoist(name);
+		}
+		if let Some(value) = value {
+			let value = self.expression(value);
+			let assignment = field_assignment(field, value);
+			self.emit(Statement::Expression(assignment.into()));
+		}
+	}
+
+	/// `x = value` for a loop or catch variable
+	fn assign_field(
+		&mut self,
+		field: VariableField<VariableFieldInSourceCode>,
+		value: Expression,
+		declares: bool,
+	) {
+		if declares {
+			self.declare(field, Some(value));
+		} else {
+			self.emit(Statement::Expression(field_assignment(field, value).into()));
+		}
+	}
+
+	// Statements
+
+	fn statement(&mut self, statement: Statement, mut labels: Vec<String>) {
+		if !self.has_yield(NodeRef::Statement(&statement)) {
+			if let Statement::VarVariable(VarVariableStatement { declarations, .. }) = statement {
+				for VariableDeclarationItem { name, expression, .. } in declarations {
+					self.declare(name.unwrap_ast(), expression);
+				}
+				return;
+			}
+			let mut statement = statement;
+			self.native(&mut statement, &mut Vec::new(), labels.clone());
+			for name in labels.into_iter().rev() {
+				let position = statement.get_position().into_owned();
+				statement = Statement::Labelled { position, name, statement: Box::new(statement) };
+			}
+			self.emit(statement);
+			return;
+		}
+		match statement {
+			Statement::Expression(expression) => {
+				let value = self.multiple(expression);
+				self.effect(value);
+			}
+			Statement::VarVariable(VarVariableStatement { declarations, .. }) => {
+				for VariableDeclarationItem { name, expression, .. } in declarations {
+					self.declare(name.unwrap_ast(), expression);
+				}
+			}
+			Statement::Return(_, value) => {
+				let mut values = vec![self.number(1)];
+				values.extend(value.map(|value| self.multiple(value)));
+				let instruction = self.instruction(values);
+				self.emit(instruction);
+			}
+			Statement::Throw(keyword, value) => {
+				let value = self.expression(*value);
+				self.emit(Statement::Throw(keyword, Box::new(value)));
+			}
+			Statement::Block(Block(items, ..)) => self.items(items),
+			Statement::IfStatement(statement) => self.if_statement(statement),
+			Statement::WhileStatement(statement) => self.while_statement(statement, labels),
+			Statement::DoWhileStatement(statement) => self.do_while_statement(statement, labels),
+			Statement::ForLoopStatement(statement) => self.for_statement(statement, labels),
+			Statement::SwitchStatement(statement) => self.switch_statement(statement, labels),
+			Statement::TryCatchStatement(statement) => self.try_statement(statement),
+			Statement::Labelled { name, statement, .. } => {
+				labels.push(name);
+				if matches!(
+					*statement,
+					Statement::WhileStatement(_)
+						| Statement::DoWhileStatement(_)
+						| Statement::ForLoopStatement(_)
+						| Statement::SwitchStatement(_)
+						| Statement::Labelled { .. }
+				) {
+					self.statement(*statement, labels);
+				} else {
+					let end = self.label();
+					self.push_target(labels, false, end, None);
+					self.statement(*statement, Vec::new());
+					self.targets.pop();
+					self.mark(end);
+				}
+			}
+			statement => self.emit(statement),
+		}
+	}
+
+	fn push_target(
+		&mut self,
+		labels: Vec<String>,
+		unlabelled: bool,
+		break_label: u32,
+		continue_label: Option<u32>,
+	) {
+		let try_depth = self.try_depth;
+		self.targets.push(JumpTarget {
+			labels,
+			unlabelled,
+			break_label,
+			continue_label,
+			try_depth,
+		});
+	}
+
+	fn body(&mut self, inner: BlockOrSingleStatement) {
+		match inner {
+			BlockOrSingleStatement::Braced(block) => {
+				self.statement(Statement::Block(block), Vec::new());
+			}
+			BlockOrSingleStatement::SingleStatement(statement) => {
+				self.statement(*statement, Vec::new());
+			}
+		}
+	}
+
+	fn if_statement(&mut self, statement: IfStatement) {
+		let IfStatement { condition, inner, else_conditions, trailing_else, .. } = statement;
+		let mut branches = vec![(Some(condition), inner)];
+		branches.extend(
+			else_conditions.into_iter().map(|branch| (Some(branch.condition), branch.inner)),
+		);
+		branches.extend(trailing_else.map(|branch| (None, branch.inner)));
+		let end = self.label();
+		let count = branches.len();
+		for (index, (condition, inner)) in branches.into_iter().enumerate() {
+			let next = if index + 1 < count { Some(self.label()) } else { None };
+			if let Some(condition) = condition {
+				let condition = self.multiple(condition);
+				self.jump_if(negate(condition), next.unwrap_or(end));
+			}
+			self.body(inner);
+			if let Some(next) = next {
+				self.jump(end);
+				self.mark(next);
+			}
+		}
+		self.mark(end);
+	}
+
+	fn while_statement(&mut self, statement: WhileStatement, labels: Vec<String>) {
+		let (start, end) = (self.label(), self.label());
+		self.mark(start);
+		if !matches!(statement.condition, Expression::BooleanLiteral(true, ..)) {
+			let condition = self.expression(statement.condition);
+			self.jump_if(negate(condition), end);
+		}
+		self.push_target(labels, true, end, Some(start));
+		self.body(statement.inner);
+		self.targets.pop();
+		self.jump(start);
+		self.mark(end);
+	}
+
+	fn do_while_statement(&mut self, statement: DoWhileStatement, labels: Vec<String>) {
+		let (start, test, end) = (self.label(), self.label(), self.label());
+		self.mark(start);
+		self.push_target(labels, true, end, Some(test));
+		self.body(statement.inner);
+		self.targets.pop();
+		self.mark(test);
+		let condition = self.expression(statement.condition);
+		self.jump_if(condition, start);
+		self.mark(end);
+	}
+
+	fn for_statement(&mut self, statement: ForLoopStatement, labels: Vec<String>) {
+		let ForLoopStatement { condition, inner, position } = statement;
+		match condition {
+			ForLoopCondition::Statements { initializer, condition, afterthought } => {
+				match initializer {
+					Some(ForLoopStatementInitializer::Statement(declaration)) => {
+						self.variable_declaration(declaration);
+					}
+					Some(ForLoopStatementInitializer::VarStatement(VarVariableStatement {
+						declarations,
+						..
+					})) => {
+						for VariableDeclarationItem { name, expression, .. } in declarations {
+							self.declare(name.unwrap_ast(), expression);
+						}
+					}
+					Some(ForLoopStatementInitializer::Expression(expression)) => {
+						let value = self.expression(expression);
+						self.effect(value);
+					}
+					None => {}
+				}
+				let (start, update, end) = (self.label(), self.label(), self.label());
+				self.mark(start);
+				if let Some(condition) = condition {
+					let condition = self.expression(condition);
+					self.jump_if(negate(condition), end);
+				}
+				self.push_target(labels, true, end, Some(update));
+				self.body(inner);
+				self.targets.pop();
+				self.mark(update);
+				if let Some(afterthought) = afterthought {
+					let value = self.expression(afterthought);
+					self.effect(value);
+				}
+				self.jump(start);
+				self.mark(end);
+			}
+			ForLoopCondition::ForOf { is_await: true, .. } => {
+				self.data.push(warning(
+					"'for await' is only lowered by 'lower-async-iteration'".to_owned(),
+					position,
+				));
+			}
+			ForLoopCondition::ForOf { keyword, variable, of, .. } => {
+				// The iterator is closed (with `return`) if the loop is exited early
+				let of = self.expression(of);
+				let values = self.values(of);
+				let (iterator, result) = (self.temporary(), self.temporary());
+				self.emit(Statement::Expression(assign(&iterator, values, &position).into()));
+				let (finally_label, end) = (self.label(), self.label());
+				self.emit(self.try_entry(None, Some(finally_label)));
+				self.try_depth += 1;
+
+				let (start, loop_end) = (self.label(), self.label());
+				self.mark(start);
+				let next = call(
+					member(reference(&iterator, &position), "next", &position),
+					Vec::new(),
+					&position,
+				);
+				self.emit(Statement::Expression(assign(&result, next, &position).into()));
+				self.jump_if(member(reference(&result, &position), "done", &position), loop_end);
+				let value = member(reference(&result, &position), "value", &position);
+				self.assign_field(variable.unwrap_ast(), value, keyword.is_some());
+				self.push_target(labels, true, loop_end, Some(start));
+				self.body(inner);
+				self.targets.pop();
+				self.jump(start);
+				self.mark(loop_end);
+				self.jump_exiting(end);
+
+				self.mark(finally_label);
+				let close = call(
+					member(reference(&iterator, &position), "return", &position),
+					Vec::new(),
+					&position,
+				);
+				let not = |expression| Expression::UnaryOperation {
+					operator: UnaryOperator::LogicalNot,
+					operand: Box::new(expression),
+					position: position.clone(),
+					id: ExpressionId::new(),
+				};
+				let and = |lhs, rhs| Expression::BinaryOperation {
+					lhs: Box::new(lhs),
+					operator: BinaryOperator::LogicalAnd,
+					rhs: Box::new(rhs),
+					id: ExpressionId::new(),
+				};
+				let condition = and(
+					and(
+						reference(&result, &position),
+						not(member(reference(&result, &position), "done", &position)),
+					),
+					member(reference(&iterator, &position), "return", &position),
+				);
+				self.emit(Statement::IfStatement(IfStatement {
+					condition: condition.into(),
+					inner: BlockOrSingleStatement::SingleStatement(Box::new(
+						Statement::Expression(close.into()),
+					)),
+					else_conditions: Vec::new(),
+					trailing_else: None,
+					position: position.clone(),
+				}));
+				let end_finally = self.instruction(vec![self.number(4)]);
+				self.emit(end_finally);
+				self.try_depth -= 1;
+				self.mark(end);
+			}
+			ForLoopCondition::ForIn { keyword, variable, r#in } => {
+				// The keys are collected first, keys which are deleted during the loop are skipped
+				let object = self.expression(r#in);
+				let (object_name, keys, index) =
+					(self.temporary(), self.temporary(), self.temporary());
+				self.emit(Statement::Expression(assign(&object_name, object, &position).into()));
+				let array =
+					Expression::ArrayLiteral(Vec::new(), position.clone(), ExpressionId::new());
+				self.emit(Statement::Expression(assign(&keys, array, &position).into()));
+				let push = call(
+					member(reference(&keys, &position), "push", &position),
+					vec![reference(&index, &position)],
+					&position,
+				);
+				self.emit(Statement::ForLoopStatement(ForLoopStatement {
+					condition: ForLoopCondition::ForIn {
+						keyword: None,
+						variable: WithComment::None(VariableField::Name(
+							VariableIdentifier::Standard(
+								index.clone(),
+								VariableId::new(),
+								position.clone(),
+							),
+						)),
+						r#in: reference(&object_name, &position),
+					},
+					inner: BlockOrSingleStatement::SingleStatement(Box::new(
+						Statement::Expression(push.into()),
+					)),
+					position: position.clone(),
+				}));
+				self.emit(Statement::Expression(assign(&index, self.number(0), &position).into()));
+
+				let (start, next, end) = (self.label(), self.label(), self.label());
+				self.mark(start);
+				let binary = |lhs, operator, rhs| Expression::BinaryOperation {
+					lhs: Box::new(lhs),
+					operator,
+					rhs: Box::new(rhs),
+					id: ExpressionId::new(),
+				};
+				let finished = binary(
+					reference(&index, &position),
+					BinaryOperator::GreaterThanEqual,
+					member(reference(&keys, &position), "length", &position),
+				);
+				self.jump_if(finished, end);
+				let key = || Expression::Index {
+					indexee: Box::new(reference(&keys, &position)),
+					indexer: Box::new(reference(&index, &position).into()),
+					position: position.clone(),
+					expression_id: ExpressionId::new(),
+				};
+				let exists = binary(key(), BinaryOperator::In, reference(&object_name, &position));
+				self.jump_if(negate(exists), next);
+				self.assign_field(variable.unwrap_ast(), key(), keyword.is_some());
+				self.push_target(labels, true, end, Some(next));
+				self.body(inner);
+				self.targets.pop();
+				self.mark(next);
+				let increment =
+					binary(reference(&index, &position), BinaryOperator::Add, self.number(1));
+				self.emit(Statement::Expression(assign(&index, increment, &position).into()));
+				self.jump(start);
+				self.mark(end);
+			}
+		}
+	}
+
+	fn switch_statement(&mut self, statement: SwitchStatement, labels: Vec<String>) {
+		let SwitchStatement { case, branches, position } = statement;
+		let case = self.expression(case);
+		let value = self.temporary();
+		self.emit(Statement::Expression(assign(&value, case, &position).into()));
+		let end = self.label();
+		let mut default = None;
+		let mut bodies = Vec::new();
+		for branch in branches {
+			let label = self.label();
+			match branch {
+				SwitchBranch::Case(test, statements) => {
+					let test = self.expression(test);
+					let matches = Expression::BinaryOperation {
+						lhs: Box::new(reference(&value, &position)),
+						operator: BinaryOperator::StrictEqual,
+						rhs: Box::new(test),
+						id: ExpressionId::new(),
+					};
+					self.jump_if(matches, label);
+					bodies.push((label, statements));
+				}
+				SwitchBranch::Default(statements) => {
+					default = Some(label);
+					bodies.push((label, statements));
+				}
+			}
+		}
+		self.jump(default.unwrap_or(end));
+		self.push_target(labels, true, end, None);
+		for (label, statements) in bodies {
+			self.mark(label);
+			for statement in statements {
+				self.statement(statement, Vec::new());
+			}
+		}
+		self.targets.pop();
+		self.mark(end);
+	}
+
+	fn try_statement(&mut self, statement: TryCatchStatement) {
+		let TryCatchStatement { try_inner, exception_var, catch_inner, finally_inner, .. } =
+			statement;
+		let catch_label = catch_inner.as_ref().map(|_| self.label());
+		let finally_label = finally_inner.as_ref().map(|_| self.label());
+		let end = self.label();
+		self.emit(self.try_entry(catch_label, finally_label));
+		self.try_depth += 1;
+		self.items(try_inner.0);
+		self.jump_exiting(end);
+		if let (Some(label), Some(Block(items, ..))) = (catch_label, catch_inner) {
+			self.mark(label);
+			if let Some(variable) = exception_var {
+				let error = self.sent();
+				self.assign_field(variable.unwrap_ast(), error, true);
+			}
+			self.items(items);
+			self.jump_exiting(end);
+		}
+		if let (Some(label), Some(Block(items, ..))) = (finally_label, finally_inner) {
+			self.mark(label);
+			self.items(items);
+			let end_finally = self.instruction(vec![self.number(4)]);
+			self.emit(end_finally);
+		}
+		self.try_depth -= 1;
+		self.mark(end);
+	}
+
+	/// `_a.trys.push([catchLabel, finallyLabel])`
+	fn try_entry(&self, catch_label: Option<u32>, finally_label: Option<u32>) -> Statement {
+		let position = &self.position;
+		let label = |label: Option<u32>| {
+			label.map_or_else(|| void_zero(position), |label| self.number(label))
+		};
+		let entry = Expression::ArrayLiteral(
+			vec![
+				SpreadExpression::NonSpread(label(catch_label)),
+				SpreadExpression::NonSpread(label(finally_label)),
+			],
+			position.clone(),
+			ExpressionId::new(),
+		);
+		let trys = member(reference(&self.state, position), "trys", position);
+		Statement::Expression(call(member(trys, "push", position), vec![entry], position).into())
+	}
+
+	/// The instruction for a `break` or `continue` out of the part of the body which is kept
+	fn jump_out(&self, label: Option<&String>, is_continue: bool) -> Option<Statement> {
+		let target = self.targets.iter().rev().find(|target| match label {
+			Some(label) => target.labels.contains(label),
+			None if is_continue => target.continue_label.is_some(),
+			None => target.unlabelled,
+		})?;
+		let label = if is_continue { target.continue_label? } else { target.break_label };
+		Some(self.jump_instruction(label, self.try_depth - target.try_depth))
+	}
+
+	/// Rewrites a statement without `yield` to run in the state function. `inner` are the
+	/// statements it is in which can be jumped to
+	fn native(
+		&mut self,
+		statement: &mut Statement,
+		inner: &mut Vec<NativeTarget>,
+		labels: Vec<String>,
+	) {
+		match statement {
+			Statement::Return(_, value) => {
+				let mut values = vec![self.number(1)];
+				values.extend(value.take().map(into_expression));
+				*statement = self.instruction(values);
+			}
+			Statement::Break(..) | Statement::Continue(..) => {
+				let is_continue = matches!(statement, Statement::Continue(..));
+				let (Statement::Break(label, _) | Statement::Continue(label, _)) = &*statement
+				else {
+					unreachable!()
+				};
+				let is_native = match label {
+					Some(label) => inner.iter().any(|target| target.labels.contains(label)),
+					None => inner
+						.iter()
+						.any(|target| target.is_loop || (!is_continue && target.is_switch)),
+				};
+				if !is_native {
+					if let Some(jump) = self.jump_out(label.as_ref(), is_continue) {
+						*statement = jump;
+					}
+				}
+			}
+			Statement::VarVariable(VarVariableStatement { declarations, .. }) => {
+				let assignments = self.hoist_declarations(std::mem::take(declarations));
+				*statement = if assignments.is_empty() {
+					Statement::Block(Block(Vec::new(), BlockId::new(), self.position.clone()))
+				} else {
+					Statement::Expression(super::sequence(assignments))
+				};
+			}
+			Statement::ForLoopStatement(ForLoopStatement { condition, inner: body, .. }) => {
+				// `var` in the head is hoisted like other `var` declarations
+				match condition {
+					ForLoopCondition::Statements { initializer, .. } => {
+						if let Some(ForLoopStatementInitializer::VarStatement(var_statement)) =
+							initializer
+						{
+							let declarations = std::mem::take(&mut var_statement.declarations);
+							let mut assignments = self.hoist_declarations(declarations);
+							let assignment = match assignments.len() {
+								0 => None,
+								1 => assignments.pop(),
+								_ => Some(Expression::ParenthesizedExpression(
+									Box::new(super::sequence(assignments)),
+									self.position.clone(),
+									ExpressionId::new(),
+								)),
+							};
+							*initializer = assignment.map(ForLoopStatementInitializer::Expression);
+						}
+					}
+					ForLoopCondition::ForOf { keyword, variable, .. }
+					| ForLoopCondition::ForIn { keyword, variable, .. } => {
+						if let Some(VariableDeclarationKeyword::Var(_)) = keyword {
+							let mut names = Vec::new();
+							crate::bundle::variable_field_identifiers(
+								variable.get_ast(),
+								&mut names,
+							);
+							for (name, _) in names {
+								self.hoist(name);
+							}
+							*keyword = None;
+						}
+					}
+				}
+				inner.push(NativeTarget { labels, is_loop: true, is_switch: false });
+				self.native_body(body, inner);
+				inner.pop();
+			}
+			Statement::Block(block) => self.native_items(&mut block.0, inner),
+			Statement::IfStatement(statement) => {
+				self.native_body(&mut statement.inner, inner);
+				for branch in &mut statement.else_conditions {
+					self.native_body(&mut branch.inner, inner);
+				}
+				if let Some(branch) = &mut statement.trailing_else {
+					self.native_body(&mut branch.inner, inner);
+				}
+			}
+			Statement::WhileStatement(WhileStatement { inner: body, .. })
+			| Statement::DoWhileStatement(DoWhileStatement { inner: body, .. }) => {
+				inner.push(NativeTarget { labels, is_loop: true, is_switch: false });
+				self.native_body(body, inner);
+				inner.pop();
+			}
+			Statement::SwitchStatement(statement) => {
+				inner.push(NativeTarget { labels, is_loop: false, is_switch: true });
+				for branch in &mut statement.branches {
+					let (SwitchBranch::Default(statements) | SwitchBranch::Case(_, statements)) =
+						branch;
+					for statement in statements {
+						self.native(statement, inner, Vec::new());
+					}
+				}
+				inner.pop();
+			}
+			Statement::TryCatchStatement(statement) => {
+				self.native_items(&mut statement.try_inner.0, inner);
+				if let Some(block) = &mut statement.catch_inner {
+					self.native_items(&mut block.0, inner);
+				}
+				if let Some(block) = &mut statement.finally_inner {
+					self.native_items(&mut block.0, inner);
+				}
+			}
+			Statement::Labelled { name, statement: labelled, .. } => {
+				let mut labels = labels;
+				labels.push(name.clone());
+				if matches!(
+					**labelled,
+					Statement::WhileStatement(_)
+						| Statement::DoWhileStatement(_)
+						| Statement::ForLoopStatement(_)
+						| Statement::SwitchStatement(_)
+						| Statement::Labelled { .. }
+				) {
+					self.native(labelled, inner, labels);
+				} else {
+					inner.push(NativeTarget { labels, is_loop: false, is_switch: false });
+					self.native(labelled, inner, Vec::new());
+					inner.pop();
+				}
+			}
+			_ => {}
+		}
+	}
+
+	/// Hoists the names of `var` declarations, returning the assignments of their values
+	fn hoist_declarations(
+		&mut self,
+		declarations: Vec<VariableDeclarationItem<Option<Expression>>>,
+	) -> Vec<Expression> {
+		let mut assignments = Vec::new();
+		for VariableDeclarationItem { name, expression, .. } in declarations {
+			let field = name.unwrap_ast();
+			let mut names = Vec::new();
+			crate::bundle::variable_field_identifiers(&field, &mut names);
+			for (name, _) in names {
+				self.hoist(name);
+			}
+			assignments.extend(expression.map(|value| field_assignment(field, value)));
+		}
+		assignments
+	}
+
+	fn native_items(
+		&mut self,
+		items: &mut Vec<StatementOrDeclaration>,
+		inner: &mut Vec<NativeTarget>,
+	) {
+		for item in items.iter_mut() {
+			if let StatementOrDeclaration::Statement(statement) = item {
+				self.native(statement, inner, Vec::new());
+			}
+		}
+		// `var` declarations without values
+		items.retain(|item| {
+			!matches!(item, StatementOrDeclaration::Statement(Statement::Block(Block(items, ..))) if items.is_empty())
+		});
+	}
+
+	fn native_body(&mut self, body: &mut BlockOrSingleStatement, inner: &mut Vec<NativeTarget>) {
+		match body {
+			BlockOrSingleStatement::Braced(block) => self.native_items(&mut block.0, inner),
+			BlockOrSingleStatement::SingleStatement(statement) => {
+				self.native(statement, inner, Vec::new());
+			}
+		}
+	}
+
+	// Expressions
+
+	fn multiple(&mut self, expression: MultipleExpression) -> Expression {
+		if !self.multiple_has_yield(&expression) {
+			return into_expression(expression);
+		}
+		let MultipleExpression { lhs, rhs } = expression;
+		if let Some(lhs) = lhs {
+			let value = self.multiple(*lhs);
+			self.effect(value);
+		}
+		self.expression(rhs)
+	}
+
+	/// Compiles an expression containing `yield`, emitting the parts before each `yield`
+	fn expression(&mut self, expression: Expression) -> Expression {
+		if !self.has_yield(NodeRef::Expression(&expression)) {
+			return expression;
+		}
+		match expression {
+			Expression::UnaryOperation { operator: UnaryOperator::Yield, operand, .. } => {
+				let value = self.expression(*operand);
+				let resume = self.label();
+				let instruction =
+					self.instruction(vec![self.number(0), value, self.number(resume)]);
+				self.emit(instruction);
+				self.mark(resume);
+				self.sent()
+			}
+			Expression::UnaryOperation {
+				operator: UnaryOperator::DelegatedYield, operand, ..
+			} => {
+				let iterable = self.expression(*operand);
+				let iterator = self.values(iterable);
+				let resume = self.label();
+				let instruction =
+					self.instruction(vec![self.number(3), iterator, self.number(resume)]);
+				self.emit(instruction);
+				self.mark(resume);
+				self.sent()
+			}
+			Expression::UnaryOperation { operator, operand, position, id } => {
+				let operand = Box::new(self.expression(*operand));
+				Expression::UnaryOperation { operator, operand, position, id }
+			}
+			Expression::BinaryOperation {
+				lhs,
+				operator:
+					operator @ (BinaryOperator::LogicalAnd
+					| BinaryOperator::LogicalOr
+					| BinaryOperator::NullCoalescing),
+				rhs,
+				..
+			} => {
+				let position = lhs.get_position().into_owned();
+				let result = self.temporary();
+				let lhs = self.expression(*lhs);
+				self.emit(Statement::Expression(assign(&result, lhs, &position).into()));
+				let value = reference(&result, &position);
+				let skip = match operator {
+					BinaryOperator::LogicalAnd => negate(value),
+					BinaryOperator::LogicalOr => value,
+					_ => {
+						let binary = |operator, rhs| Expression::BinaryOperation {
+							lhs: Box::new(reference(&result, &position)),
+							operator,
+							rhs: Box::new(rhs),
+							id: ExpressionId::new(),
+						};
+						Expression::BinaryOperation {
+							lhs: Box::new(binary(
+								BinaryOperator::StrictNotEqual,
+								Expression::Null(position.clone(), ExpressionId::new()),
+							)),
+							operator: BinaryOperator::LogicalAnd,
+							rhs: Box::new(binary(
+								BinaryOperator::StrictNotEqual,
+								void_zero(&position),
+							)),
+							id: ExpressionId::new(),
+						}
+					}
+				};
+				let end = self.label();
+				self.jump_if(skip, end);
+				let rhs = self.expression(*rhs);
+				self.emit(Statement::Expression(assign(&result, rhs, &position).into()));
+				self.mark(end);
+				reference(&result, &position)
+			}
+			Expression::BinaryOperation { lhs, operator, rhs, id } => {
+				let mut operands = self.operands(vec![*lhs, *rhs], false).into_iter();
+				let (lhs, rhs) = (operands.next().unwrap(), operands.next().unwrap());
+				Expression::BinaryOperation { lhs: Box::new(lhs), operator, rhs: Box::new(rhs), id }
+			}
+			Expression::TernaryExpression { condition, truthy_result, falsy_result, .. } => {
+				let position = condition.get_position().into_owned();
+				let result = self.temporary();
+				let condition = self.expression(*condition);
+				let (otherwise, end) = (self.label(), self.label());
+				self.jump_if(negate(condition), otherwise);
+				let truthy = self.expression(*truthy_result);
+				self.emit(Statement::Expression(assign(&result, truthy, &position).into()));
+				self.jump(end);
+				self.mark(otherwise);
+				let falsy = self.expression(*falsy_result);
+				self.emit(Statement::Expression(assign(&result, falsy, &position).into()));
+				self.mark(end);
+				reference(&result, &position)
+			}
+			Expression::ParenthesizedExpression(inner, position, id) => {
+				let value = self.multiple(*inner);
+				if is_simple(&value) || is_literal(&value) || self.is_sent(&value) {
+					value
+				} else {
+					Expression::ParenthesizedExpression(Box::new(value.into()), position, id)
+				}
+			}
+			Expression::Assignment {
+				lhs: LHSOfAssignment::VariableOrPropertyAccess(target),
+				rhs,
+				id,
+			} => {
+				let later_yield = self.has_yield(NodeRef::Expression(&rhs));
+				let target = self.target(target, later_yield);
+				let rhs = Box::new(self.expression(*rhs));
+				Expression::Assignment {
+					lhs: LHSOfAssignment::VariableOrPropertyAccess(target),
+					rhs,
+					id,
+				}
+			}
+			Expression::Assignment { lhs, rhs, id } => {
+				let rhs = Box::new(self.expression(*rhs));
+				Expression::Assignment { lhs, rhs, id }
+			}
+			Expression::BinaryAssignmentOperation { lhs, operator, rhs, id } => {
+				let later_yield = self.has_yield(NodeRef::Expression(&rhs));
+				let lhs = self.target(lhs, later_yield);
+				let rhs = Box::new(self.expression(*rhs));
+				Expression::BinaryAssignmentOperation { lhs, operator, rhs, id }
+			}
+			Expression::PropertyAccess {
+				parent,
+				property,
+				position,
+				expression_id,
+				is_optional,
+			} => {
+				let parent = Box::new(self.expression(*parent));
+				Expression::PropertyAccess {
+					parent,
+					property,
+					position,
+					expression_id,
+					is_optional,
+				}
+			}
+			Expression::Index { indexee, indexer, position, expression_id } => {
+				let mut operands =
+					self.operands(vec![*indexee, into_expression(*indexer)], false).into_iter();
+				let (indexee, indexer) = (operands.next().unwrap(), operands.next().unwrap());
+				Expression::Index {
+					indexee: Box::new(indexee),
+					indexer: Box::new(indexer.into()),
+					position,
+					expression_id,
+				}
+			}
+			Expression::FunctionCall {
+				function,
+				type_arguments,
+				arguments,
+				position,
+				expression_id,
+			} => {
+				let later_yield = arguments.iter().any(|argument| self.spread_has_yield(argument));
+				let function = Box::new(self.callee(*function, later_yield));
+				let arguments = self.spread_operands(arguments);
+				Expression::FunctionCall {
+					function,
+					type_arguments,
+					arguments,
+					position,
+					expression_id,
+				}
+			}
+			Expression::ConstructorCall {
+				constructor,
+				type_arguments,
+				arguments,
+				position,
+				expression_id,
+			} => {
+				let later_yield =
+					arguments.iter().flatten().any(|argument| self.spread_has_yield(argument));
+				let mut constructor = self.expression(*constructor);
+				if later_yield {
+					constructor = self.spill(constructor);
+				}
+				let arguments = arguments.map(|arguments| self.spread_operands(arguments));
+				Expression::ConstructorCall {
+					constructor: Box::new(constructor),
+					type_arguments,
+					arguments,
+					position,
+					expression_id,
+				}
+			}
+			Expression::ArrayLiteral(elements, position, id) => {
+				Expression::ArrayLiteral(self.spread_operands(elements), position, id)
+			}
+			Expression::ObjectLiteral(ObjectLiteral { mut members, position, expression_id }) => {
+				let values = members
+					.iter_mut()
+					.filter_map(|member| match member {
+						ObjectLiteralMember::Property(_, value, position)
+						| ObjectLiteralMember::SpreadExpression(value, position) => Some(std::mem::replace(
+							value,
+							Expression::Null(position.clone(), ExpressionId::new()),
+						)),
+						_ => None,
+					})
+					.collect();
+				let mut values = self.operands(values, false).into_iter();
+				for member in &mut members {
+					if let ObjectLiteralMember::Property(_, value, _)
+					| ObjectLiteralMember::SpreadExpression(value, _) = member
+					{
+						*value = values.next().unwrap();
+					}
+				}
+				Expression::ObjectLiteral(ObjectLiteral { members, position, expression_id })
+			}
+			Expression::TemplateLiteral(TemplateLiteral {
+				tag: None,
+				mut parts,
+				position,
+				expression_id,
+			}) => {
+				let values = parts
+					.iter_mut()
+					.filter_map(|part| match part {
+						TemplateLiteralPart::Dynamic(value) => Some(std::mem::replace(
+							&mut **value,
+							Expression::Null(position.clone(), ExpressionId::new()),
+						)),
+						TemplateLiteralPart::Static(_) => None,
+					})
+					.collect();
+				let mut values = self.operands(values, false).into_iter();
+				for part in &mut parts {
+					if let TemplateLiteralPart::Dynamic(value) = part {
+						**value = values.next().unwrap();
+					}
+				}
+				Expression::TemplateLiteral(TemplateLiteral {
+					tag: None,
+					parts,
+					position,
+					expression_id,
+				})
+			}
+			Expression::PrefixComment(comment, inner, position, id) => {
+				Expression::PrefixComment(comment, Box::new(self.expression(*inner)), position, id)
+			}
+			Expression::PostfixComment(inner, comment, position, id) => {
+				Expression::PostfixComment(Box::new(self.expression(*inner)), comment, position, id)
+			}
+			expression => {
+				self.data.push(warning(
+					"'yield' in this expression is not lowered".to_owned(),
+					expression.get_position().into_owned(),
+				));
+				expression
+			}
+		}
+	}
+
+	/// Compiles expressions which are evaluated in order. Values before the last one containing
+	/// `yield` (or all of them if `later_yield`) are stored in temporaries
+	fn operands(&mut self, expressions: Vec<Expression>, later_yield: bool) -> Vec<Expression> {
+		let last = if later_yield {
+			Some(expressions.len())
+		} else {
+			expressions
+				.iter()
+				.rposition(|expression| self.has_yield(NodeRef::Expression(expression)))
+		};
+		let mut compiled = Vec::with_capacity(expressions.len());
+		for (index, expression) in expressions.into_iter().enumerate() {
+			let value = self.expression(expression);
+			compiled.push(if last.is_some_and(|last| index < last) {
+				self.spill(value)
+			} else {
+				value
+			});
+		}
+		compiled
+	}
+
+	fn spread_has_yield(&self, expression: &SpreadExpression) -> bool {
+		match expression {
+			SpreadExpression::Spread(expression, _) | SpreadExpression::NonSpread(expression) => {
+				self.has_yield(NodeRef::Expression(expression))
+			}
+			SpreadExpression::Empty => false,
+		}
+	}
+
+	fn spread_operands(&mut self, expressions: Vec<SpreadExpression>) -> Vec<SpreadExpression> {
+		let mut kinds = Vec::with_capacity(expressions.len());
+		let mut values = Vec::new();
+		for expression in expressions {
+			match expression {
+				SpreadExpression::Spread(value, position) => {
+					kinds.push(Some(position));
+					values.push(value);
+				}
+				SpreadExpression::NonSpread(value) => {
+					kinds.push(None);
+					values.push(value);
+				}
+				SpreadExpression::Empty => {}
+			}
+		}
+		let mut values = self.operands(values, false).into_iter();
+		let mut compiled = Vec::with_capacity(kinds.len());
+		// Holes are only in array literals and do not contain `yield`
+		for kind in kinds {
+			compiled.push(match kind {
+				Some(position) => SpreadExpression::Spread(values.next().unwrap(), position),
+				None => SpreadExpression::NonSpread(values.next().unwrap()),
+			});
+		}
+		compiled
+	}
+
+	/// The function of a call. A method keeps its object (for `this`) but the object is stored
+	/// if an argument contains `yield`
+	fn callee(&mut self, function: Expression, later_yield: bool) -> Expression {
+		match function {
+			Expression::PropertyAccess {
+				parent,
+				property,
+				position,
+				expression_id,
+				is_optional,
+			} => {
+				let mut parent = self.expression(*parent);
+				if later_yield {
+					parent = self.spill(parent);
+				}
+				Expression::PropertyAccess {
+					parent: Box::new(parent),
+					property,
+					position,
+					expression_id,
+					is_optional,
+				}
+			}
+			Expression::Index { indexee, indexer, position, expression_id } => {
+				let mut operands = self
+					.operands(vec![*indexee, into_expression(*indexer)], later_yield)
+					.into_iter();
+				let (indexee, indexer) = (operands.next().unwrap(), operands.next().unwrap());
+				Expression::Index {
+					indexee: Box::new(indexee),
+					indexer: Box::new(indexer.into()),
+					position,
+					expression_id,
+				}
+			}
+			function => {
+				let function = self.expression(function);
+				if later_yield {
+					self.spill(function)
+				} else {
+					function
+				}
+			}
+		}
+	}
+
+	/// The target of an assignment, its object and key are stored if the value contains `yield`
+	fn target(
+		&mut self,
+		target: VariableOrPropertyAccess,
+		later_yield: bool,
+	) -> VariableOrPropertyAccess {
+		match target {
+			VariableOrPropertyAccess::PropertyAccess {
+				parent,
+				property,
+				position,
+				expression_id,
+			} => {
+				let mut parent = self.expression(*parent);
+				if later_yield {
+					parent = self.spill(parent);
+				}
+				VariableOrPropertyAccess::PropertyAccess {
+					parent: Box::new(parent),
+					property,
+					position,
+					expression_id,
+				}
+			}
+			VariableOrPropertyAccess::Index { indexee, indexer, position, expression_id } => {
+				let mut operands = self
+					.operands(vec![*indexee, into_expression(*indexer)], later_yield)
+					.into_iter();
+				let (indexee, indexer) = (operands.next().unwrap(), operands.next().unwrap());
+				VariableOrPropertyAccess::Index {
+					indexee: Box::new(indexee),
+					indexer: Box::new(indexer.into()),
+					position,
+					expression_id,
+				}
+			}
+			target => target,
+		}
+	}
+}
+
+fn into_expression(expression: MultipleExpression) -> Expression {
+	match expression {
+		MultipleExpression { lhs: None, rhs } => rhs,
+		expression => {
+			let position = expression.get_position().into_owned();
+			Expression::ParenthesizedExpression(Box::new(expression), position, ExpressionId::new())
+		}
+	}
+}
+
+/// `!expression`
+fn negate(expression: Expression) -> Expression {
+	let position = expression.get_position().into_owned();
+	let operand = match expression {
+		Expression::VariableReference(..)
+		| Expression::ThisReference(..)
+		| Expression::PropertyAccess { .. }
+		| Expression::Index { .. }
+		| Expression::FunctionCall { .. }
+		| Expression::ParenthesizedExpression(..)
+		| Expression::UnaryOperation { .. } => expression,
+		expression if is_literal(&expression) => expression,
+		expression => parenthesize(expression, &position),
+	};
+	Expression::UnaryOperation {
+		operator: UnaryOperator::LogicalNot,
+		operand: Box::new(operand),
+		position,
+		id: ExpressionId::new(),
+	}
+}
+
+/// `x = value`, `[a, b] = value` or `({ a, b } = value)`
+fn field_assignment(
+	field: VariableField<VariableFieldInSourceCode>,
+	value: Expression,
+) -> Expression {
+	match field {
+		VariableField::Name(VariableIdentifier::Standard(name, _, position)) => {
+			assign(&name, value, &position)
+		}
+		VariableField::Name(VariableIdentifier::Cursor(..)) => unreachable!("cursor in build"),
+		VariableField::Array(fields, position) => Expression::Assignment {
+			lhs: LHSOfAssignment::ArrayDestructuring(fields, position, ExpressionId::new()),
+			rhs: Box::new(value),
+			id: ExpressionId::new(),
+		},
+		VariableField::Object(fields, position) => parenthesize(
+			Expression::Assignment {
+				lhs: LHSOfAssignment::ObjectDestructuring(
+					fields,
+					position.clone(),
+					ExpressionId::new(),
+				),
+				rhs: Box::new(value),
+				id: ExpressionId::new(),
+			},
+			&position,
+		),
+	}
+}
+
+struct LowerGenerators(Rc<RefCell<Generators>>);
+
+impl<'a> parser::VisitorMut<BlockLikeMut<'a>, Vec<TempDiagnostic>> for LowerGenerators {
+	fn visit_mut(
+		&mut self,
+		item: &mut BlockLikeMut<'a>,
+		data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		lower_declared_functions(item.items, functions, data, &mut *self.0.borrow_mut());
+	}
+}
+
+impl parser::VisitorMut<Expression, Vec<TempDiagnostic>> for LowerGenerators {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		chain: &Chain,
+	) {
+		let mut state = self.0.borrow_mut();
+		lower_expression_functions(item, functions, data, &mut *state);
+		if let Expression::VariableReference(name, ..) = item {
+			if name == "arguments" {
+				let block = chain.function_block_id(false);
+				if let Some(stored) = block.and_then(|block| state.arguments.get(&block)) {
+					name.clone_from(stored);
+				}
+			}
+		}
+	}
+}
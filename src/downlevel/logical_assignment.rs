@@ -0,0 +1,53 @@
+//! `a ||= b` to `a || (a = b)`, and the same for `&&=` and `??=`. The object and key of
+//! property targets are stored in temporaries so that they are only evaluated once
+
+use parser::{
+	expressions::ExpressionId,
+	extractor::ExtractedFunctions,
+	operators::{BinaryAssignmentOperator, BinaryOperator},
+	ASTNode, Chain, Expression, VisitorsMut,
+};
+
+use super::{assign_to, parenthesize, read_target, reuse_target, take, SharedTemporaries};
+use crate::error_handling::TempDiagnostic;
+
+/// Passes for [crate::temp::Transform::LowerLogicalAssignment]
+pub(crate) fn passes() -> Vec<VisitorsMut<Vec<TempDiagnostic>>> {
+	super::passes(|temporaries| VisitorsMut {
+		expression_visitors_mut: vec![Box::new(LowerLogicalAssignment(temporaries.clone()))],
+		..Default::default()
+	})
+}
+
+struct LowerLogicalAssignment(SharedTemporaries);
+
+impl parser::VisitorMut<Expression, Vec<TempDiagnostic>> for LowerLogicalAssignment {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		_data: &mut Vec<TempDiagnostic>,
+		_functions: &mut ExtractedFunctions,
+		chain: &Chain,
+	) {
+		let Expression::BinaryAssignmentOperation { operator, .. } = item else { return };
+		let operator = match operator {
+			BinaryAssignmentOperator::LogicalOrAssign => BinaryOperator::LogicalOr,
+			BinaryAssignmentOperator::LogicalAndAssign => BinaryOperator::LogicalAnd,
+			BinaryAssignmentOperator::LogicalNullishAssignment => BinaryOperator::NullCoalescing,
+			_ => return,
+		};
+		let position = item.get_position().into_owned();
+		let Expression::BinaryAssignmentOperation { lhs, rhs, .. } = take(item, &position) else {
+			unreachable!()
+		};
+		let (stored, reused) = reuse_target(lhs, &mut self.0.borrow_mut(), chain);
+		let assignment = parenthesize(assign_to(reused, *rhs), &position);
+		let operation = Expression::BinaryOperation {
+			lhs: Box::new(read_target(stored)),
+			operator,
+			rhs: Box::new(assignment),
+			id: ExpressionId::new(),
+		};
+		*item = parenthesize(operation, &position);
+	}
+}
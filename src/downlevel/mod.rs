@@ -0,0 +1,1075 @@
+//! Lowers syntax to older versions of ECMAScript, for `--target`. Each kind of syntax is lowered by
+//! a separate [Transform] so that they can also be selected with `--transform`. A transform is made
+//! up of one or more passes, each of which is a standalone set of visitors.
+//!
+//! Values which are used more than once are stored in temporary variables (`_a`, `_b`, ...),
+//! which are declared with `var` at the start of the function they are used in. Only syntax is
+//! lowered, built-ins added after the target (e.g. `Object.assign`) need to be polyfilled.
+//! `let` and `const` variables captured in loops are kept where their loop body uses `arguments`,
+//! `super`, `yield` or `await`
+//!
+//! Generators, async functions and `for ... of` loops are lowered with runtime helpers
+//! (`__generator`, `__awaiter`, `__values` etc), which are declared at the start of each module that uses them
+
+pub(crate) mod arrow_functions;
+pub(crate) mod async_functions;
+pub(crate) mod async_iteration;
+pub(crate) mod block_scoping;
+pub(crate) mod classes;
+pub(crate) mod decorators;
+pub(crate) mod destructuring;
+pub(crate) mod exponentiation;
+pub(crate) mod extras;
+pub(crate) mod for_of;
+pub(crate) mod generators;
+pub(crate) mod logical_assignment;
+pub(crate) mod object_literals;
+pub(crate) mod object_spread;
+pub(crate) mod optional_chaining;
+pub(crate) mod parameters;
+pub(crate) mod spread;
+pub(crate) mod template_literals;
+
+use std::{
+	cell::RefCell,
+	collections::{HashMap, HashSet},
+	rc::Rc,
+};
+
+use parser::{
+	declarations::{
+		classes::{ClassFunctionBase, ClassMember},
+		export::Exportable,
+		variable::VariableDeclarationKeyword,
+		ExportDeclaration, VariableDeclaration, VariableDeclarationItem,
+	},
+	expressions::{
+		arrow_function::{ArrowFunctionBase, ExpressionOrBlock},
+		assignments::{LHSOfAssignment, VariableOrPropertyAccess},
+		object_literal::{ObjectLiteralMember, ObjectLiteralMethodBase},
+		ExpressionFunctionBase, ExpressionId, MultipleExpression, SpreadExpression,
+	},
+	extractor::{ExtractedFunctions, GetFunction},
+	functions::GeneralFunctionBase,
+	operators::UnaryOperator,
+	statements::VarVariableStatement,
+	tsx_keywords, ASTNode, Block, BlockId, BlockLikeMut, BlockOrSingleStatement, Chain,
+	Declaration, Decorated, Expression, ExpressionOrStatementPosition, FunctionBase, FunctionBased,
+	FunctionHeader, FunctionId, GetSetGeneratorOrNone, Keyword, NodeRef, NumberStructure,
+	ParseOutput, ParseSettings, PropertyKey, PropertyReference, Quoted, SourceId, Span, Statement,
+	StatementOrDeclaration, VariableField, VariableFieldInSourceCode, VariableId,
+	VariableIdentifier, VisitorsMut, WithComment,
+};
+
+use crate::{
+	bundle::ModuleScope,
+	error_handling::{DiagnosticCode, ErrorWarningInfo, TempDiagnostic},
+	temp::Transform,
+};
+
+/// Version of ECMAScript to output, from `--target` or `"target"` in the configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Target {
+	ES5,
+	ES2015,
+	ES2017,
+	ES2020,
+}
+
+impl Target {
+	pub const ALL: &'static [Target] =
+		&[Target::ES5, Target::ES2015, Target::ES2017, Target::ES2020];
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Target::ES5 => "es5",
+			Target::ES2015 => "es2015",
+			Target::ES2017 => "es2017",
+			Target::ES2020 => "es2020",
+		}
+	}
+
+	/// Transforms which lower the syntax the target does not support, in the order they run.
+	/// `for ... of` loops are lowered before destructuring, which lowers the patterns of the loop
+	/// variables. Classes are lowered before arrow functions, so that arrow functions in class fields
+	/// capture `this` of the constructor, and before spread arguments, so that spread arguments of
+	/// `super` calls are lowered. Async functions become generators, so generators are lowered after
+	/// them, and parameters after generators so that default values are evaluated on the call. `let` and `const` are lowered last, including those added by other transforms
+	pub fn transforms(self) -> Vec<Transform> {
+		// Each transform is needed by targets up to and including the one it is paired with
+		let transforms = [
+			(Target::ES2020, Transform::LowerExtras),
+			(Target::ES2020, Transform::LowerLogicalAssignment),
+			(Target::ES2017, Transform::LowerOptionalChaining),
+			(Target::ES2017, Transform::LowerAsyncIteration),
+			(Target::ES2015, Transform::LowerAsyncFunctions),
+			(Target::ES2015, Transform::LowerExponentiation),
+			(Target::ES2017, Transform::LowerObjectSpread),
+			(Target::ES5, Transform::LowerForOf),
+			(Target::ES5, Transform::LowerDestructuring),
+			(Target::ES5, Transform::LowerTemplateLiterals),
+			(Target::ES5, Transform::LowerObjectLiterals),
+			(Target::ES5, Transform::LowerClasses),
+			(Target::ES5, Transform::LowerSpread),
+			(Target::ES5, Transform::LowerArrowFunctions),
+			(Target::ES5, Transform::LowerGenerators),
+			(Target::ES5, Transform::LowerParameters),
+			(Target::ES5, Transform::LowerBlockScoping),
+		];
+		transforms
+			.into_iter()
+			.filter(|(newest, _)| self <= *newest)
+			.map(|(_, transform)| transform)
+			.collect()
+	}
+}
+
+impl std::str::FromStr for Target {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let lowercase = s.to_lowercase();
+		Target::ALL.iter().copied().find(|target| target.name() == lowercase).ok_or_else(|| {
+			let names = Target::ALL.iter().map(|target| target.name());
+			format!(
+				"Unknown target '{s}', expected one of {}",
+				names.collect::<Vec<_>>().join(", ")
+			)
+		})
+	}
+}
+
+/// Temporary variables of a transform, shared between its passes
+#[derive(Default)]
+pub(crate) struct Temporaries {
+	/// Names declared or referenced in the module, which temporaries must not shadow
+	used: HashSet<String>,
+	/// Number of names tried for [Temporaries::name]
+	next: usize,
+	/// Variables to declare at the start of each block, with their initial value
+	declarations: HashMap<BlockId, Vec<(String, Option<Expression>)>>,
+	/// Names `this` and `arguments` are captured as in each function
+	captured: HashMap<(BlockId, &'static str), String>,
+	/// The block of the module, which helpers are declared in
+	module: Option<BlockId>,
+	/// Helpers used by the lowered code, with their name and source
+	helpers: Vec<(&'static str, String, &'static str)>,
+}
+
+pub(crate) type SharedTemporaries = Rc<RefCell<Temporaries>>;
+
+impl Temporaries {
+	/// A name which is not used in the module. The caller is responsible for declaring it
+	pub(crate) fn name(&mut self) -> String {
+		loop {
+			let letter = char::from(b'a' + (self.next % 26) as u8);
+			let round = self.next / 26;
+			self.next += 1;
+			let name =
+				if round == 0 { format!("_{letter}") } else { format!("_{letter}{}", round + 1) };
+			if self.used.insert(name.clone()) {
+				return name;
+			}
+		}
+	}
+
+	/// `base`, or `base` with a number if it is used. The caller is responsible for declaring it
+	pub(crate) fn unique(&mut self, base: &str) -> String {
+		let name = (1..)
+			.map(|n| if n == 1 { base.to_owned() } else { format!("{base}{n}") })
+			.find(|name| !self.used.contains(name))
+			.unwrap();
+		self.used.insert(name.clone());
+		name
+	}
+
+	/// A new variable declared in the function (or arrow function with a block body) being visited
+	pub(crate) fn temporary(&mut self, chain: &Chain) -> String {
+		let name = self.name();
+		let block = chain.function_block_id(true).expect("no module block");
+		self.declarations.entry(block).or_default().push((name.clone(), None));
+		name
+	}
+
+	/// [Temporaries::temporary] with a value, which is evaluated at the start of the function
+	pub(crate) fn temporary_with_value(&mut self, chain: &Chain, value: Expression) -> String {
+		let name = self.name();
+		let block = chain.function_block_id(true).expect("no module block");
+		self.declarations.entry(block).or_default().push((name.clone(), Some(value)));
+		name
+	}
+
+	/// A variable holding `this` or `arguments` of the function being visited, for arrow
+	/// functions in it. Variables are reused for every arrow function in the same function
+	pub(crate) fn capture(&mut self, chain: &Chain, base: &'static str, position: &Span) -> String {
+		let block = chain.function_block_id(false).expect("no module block");
+		if let Some(name) = self.captured.get(&(block, base)) {
+			return name.clone();
+		}
+		let name = self.unique(&format!("_{base}"));
+		let value = match base {
+			"this" => Expression::ThisReference(position.clone(), ExpressionId::new()),
+			_ => reference(base, position),
+		};
+		self.declarations.entry(block).or_default().push((name.clone(), Some(value)));
+		self.captured.insert((block, base), name.clone());
+		name
+	}
+
+	/// The name of a runtime helper, `source` is a function expression which is declared (once)
+	/// at the start of the module. Helpers are written in ES5 as they are added after lowering
+	pub(crate) fn helper(&mut self, base: &'static str, source: &'static str) -> String {
+		if let Some((_, name, _)) = self.helpers.iter().find(|(existing, ..)| *existing == base) {
+			return name.clone();
+		}
+		let name = self.unique(base);
+		self.helpers.push((base, name.clone(), source));
+		name
+	}
+}
+
+/// Finds the names used in the module before anything is lowered. The module is the first block
+/// visited
+struct CollectNames {
+	temporaries: SharedTemporaries,
+	is_module: bool,
+}
+
+impl<'a> parser::VisitorMut<BlockLikeMut<'a>, Vec<TempDiagnostic>> for CollectNames {
+	fn visit_mut(
+		&mut self,
+		item: &mut BlockLikeMut<'a>,
+		_data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		if !std::mem::take(&mut self.is_module) {
+			return;
+		}
+		self.temporaries.borrow_mut().module = Some(item.block_id);
+		let imports = item
+			.items
+			.iter()
+			.filter_map(|item| match item {
+				StatementOrDeclaration::Declaration(Declaration::Import(import)) => Some(import),
+				_ => None,
+			})
+			.flat_map(|import| {
+				let parts = import.imports.iter().flatten().filter_map(crate::erasure::local_name);
+				import.default_import.clone().into_iter().chain(parts.map(str::to_owned))
+			});
+		let scope =
+			ModuleScope::new(item.items, imports.collect::<Vec<_>>().into_iter(), functions);
+		self.temporaries.borrow_mut().used.extend(scope.names());
+	}
+}
+
+/// Declares the temporaries of each block, after any directives (and for constructors, after
+/// the `super(...)` call as `this` cannot be captured before it). Helpers are declared before the
+/// temporaries of the module
+struct DeclareTemporaries(SharedTemporaries);
+
+impl<'a> parser::VisitorMut<BlockLikeMut<'a>, Vec<TempDiagnostic>> for DeclareTemporaries {
+	fn visit_mut(
+		&mut self,
+		item: &mut BlockLikeMut<'a>,
+		_data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		let mut temporaries = self.0.borrow_mut();
+		if temporaries.module == Some(item.block_id) {
+			let index = item.items.iter().take_while(|item| is_directive(item)).count();
+			let helpers =
+				std::mem::take(&mut temporaries.helpers).into_iter().map(|(_, name, source)| {
+					let ParseOutput(value, state) = Expression::from_string(
+						source.to_owned(),
+						ParseSettings::default(),
+						SourceId::NULL,
+						None,
+						Vec::new(),
+					)
+					.expect("Invalid helper");
+					functions.merge(state.function_extractor);
+					let declarations = vec![declaration_item(name, Span::NULL_SPAN, Some(value))];
+					let keyword = Keyword::new(Span::NULL_SPAN);
+					StatementOrDeclaration::Statement(Statement::VarVariable(
+						VarVariableStatement { keyword, declarations },
+					))
+				});
+			item.items.splice(index..index, helpers.collect::<Vec<_>>());
+		}
+		let Some(declarations) = temporaries.declarations.remove(&item.block_id) else {
+			return;
+		};
+		let declarations = declarations
+			.into_iter()
+			.map(|(name, value)| declaration_item(name, Span::NULL_SPAN, value))
+			.collect();
+		let statement =
+			StatementOrDeclaration::Statement(Statement::VarVariable(VarVariableStatement {
+				keyword: Keyword::new(Span::NULL_SPAN),
+				declarations,
+			}));
+		let index = match item.items.iter().position(is_super_call) {
+			Some(index) => index + 1,
+			None => item.items.iter().take_while(|item| is_directive(item)).count(),
+		};
+		item.items.insert(index, statement);
+	}
+}
+
+/// The passes of a transform which uses temporaries. `lowering` creates the visitors which do
+/// the lowering, the temporaries they create are declared in a second pass
+pub(crate) fn passes(
+	lowering: impl FnOnce(&SharedTemporaries) -> VisitorsMut<Vec<TempDiagnostic>>,
+) -> Vec<VisitorsMut<Vec<TempDiagnostic>>> {
+	let temporaries = SharedTemporaries::default();
+	let mut lowering = lowering(&temporaries);
+	lowering
+		.block_visitors_mut
+		.insert(0, Box::new(CollectNames { temporaries: temporaries.clone(), is_module: true }));
+	let declare = VisitorsMut {
+		block_visitors_mut: vec![Box::new(DeclareTemporaries(temporaries))],
+		..Default::default()
+	};
+	vec![lowering, declare]
+}
+
+/// Functions which can be `async` or generators
+pub(crate) trait LowerableFunction: FunctionBased + Sized + 'static {
+	fn is_async(function: &FunctionBase<Self>) -> bool;
+
+	fn is_generator(function: &FunctionBase<Self>) -> bool;
+
+	fn remove_async(function: &mut FunctionBase<Self>);
+
+	fn remove_generator(function: &mut FunctionBase<Self>);
+
+	/// Replaces a `generator` keyword with `*`
+	fn generator_star(function: &mut FunctionBase<Self>);
+
+	fn body_ref(function: &FunctionBase<Self>) -> NodeRef<'_>;
+
+	/// The statements of the body (an expression body becomes `return ...`), leaving it empty
+	fn take_body(function: &mut FunctionBase<Self>) -> Block;
+
+	/// Sets the body to `items` followed by `return value` (or for arrow functions without
+	/// `items`, an expression body)
+	fn set_body(
+		function: &mut FunctionBase<Self>,
+		items: Vec<StatementOrDeclaration>,
+		value: Expression,
+	);
+}
+
+impl<T: ExpressionOrStatementPosition + 'static> LowerableFunction for GeneralFunctionBase<T> {
+	fn is_async(function: &FunctionBase<Self>) -> bool {
+		function.header.is_async()
+	}
+
+	fn is_generator(function: &FunctionBase<Self>) -> bool {
+		function.header.is_generator()
+	}
+
+	fn remove_async(function: &mut FunctionBase<Self>) {
+		let generator = Self::is_generator(function).then(|| function.header.get_position());
+		let generator = generator.map(std::borrow::Cow::into_owned);
+		set_header(&mut function.header, None, generator);
+	}
+
+	fn remove_generator(function: &mut FunctionBase<Self>) {
+		let async_keyword = match &function.header {
+			FunctionHeader::VirginFunctionHeader { async_keyword, .. }
+			| FunctionHeader::ChadFunctionHeader { async_keyword, .. } => async_keyword.clone(),
+		};
+		set_header(&mut function.header, async_keyword, None);
+	}
+
+	fn generator_star(function: &mut FunctionBase<Self>) {
+		if let FunctionHeader::ChadFunctionHeader { async_keyword, generator_keyword, .. } =
+			&function.header
+		{
+			let async_keyword = async_keyword.clone();
+			let generator = generator_keyword.as_ref().map(|keyword| keyword.1.clone());
+			set_header(&mut function.header, async_keyword, generator);
+		}
+	}
+
+	fn body_ref(function: &FunctionBase<Self>) -> NodeRef<'_> {
+		NodeRef::Block(&function.body)
+	}
+
+	fn take_body(function: &mut FunctionBase<Self>) -> Block {
+		take_block(&mut function.body)
+	}
+
+	fn set_body(
+		function: &mut FunctionBase<Self>,
+		items: Vec<StatementOrDeclaration>,
+		value: Expression,
+	) {
+		set_block(&mut function.body, items, value)
+	}
+}
+
+/// Class methods and object literal methods have the same header
+macro_rules! lowerable_method {
+	($base:ty) => {
+		impl LowerableFunction for $base {
+			fn is_async(function: &FunctionBase<Self>) -> bool {
+				function.header.0.is_some()
+			}
+
+			fn is_generator(function: &FunctionBase<Self>) -> bool {
+				matches!(
+					function.header.1,
+					GetSetGeneratorOrNone::Generator(_) | GetSetGeneratorOrNone::GeneratorStar(_)
+				)
+			}
+
+			fn remove_async(function: &mut FunctionBase<Self>) {
+				function.header.0 = None;
+			}
+
+			fn remove_generator(function: &mut FunctionBase<Self>) {
+				function.header.1 = GetSetGeneratorOrNone::None;
+			}
+
+			fn generator_star(function: &mut FunctionBase<Self>) {
+				if let GetSetGeneratorOrNone::Generator(keyword) = &function.header.1 {
+					function.header.1 = GetSetGeneratorOrNone::GeneratorStar(keyword.1.clone());
+				}
+			}
+
+			fn body_ref(function: &FunctionBase<Self>) -> NodeRef<'_> {
+				NodeRef::Block(&function.body)
+			}
+
+			fn take_body(function: &mut FunctionBase<Self>) -> Block {
+				take_block(&mut function.body)
+			}
+
+			fn set_body(
+				function: &mut FunctionBase<Self>,
+				items: Vec<StatementOrDeclaration>,
+				value: Expression,
+			) {
+				set_block(&mut function.body, items, value)
+			}
+		}
+	};
+}
+
+lowerable_method!(ClassFunctionBase);
+lowerable_method!(ObjectLiteralMethodBase);
+
+impl LowerableFunction for ArrowFunctionBase {
+	fn is_async(function: &FunctionBase<Self>) -> bool {
+		function.header.is_some()
+	}
+
+	fn is_generator(_function: &FunctionBase<Self>) -> bool {
+		false
+	}
+
+	fn remove_async(function: &mut FunctionBase<Self>) {
+		function.header = None;
+	}
+
+	fn remove_generator(_function: &mut FunctionBase<Self>) {}
+
+	fn generator_star(_function: &mut FunctionBase<Self>) {}
+
+	fn body_ref(function: &FunctionBase<Self>) -> NodeRef<'_> {
+		match &function.body {
+			ExpressionOrBlock::Expression(expression) => NodeRef::Expression(expression),
+			ExpressionOrBlock::Block(block) => NodeRef::Block(block),
+		}
+	}
+
+	fn take_body(function: &mut FunctionBase<Self>) -> Block {
+		match &mut function.body {
+			ExpressionOrBlock::Expression(expression) => {
+				let position = expression.get_position().into_owned();
+				let expression = take(expression, &position);
+				let statement =
+					Statement::Return(Keyword::new(position.clone()), Some(expression.into()));
+				Block(vec![StatementOrDeclaration::Statement(statement)], BlockId::new(), position)
+			}
+			ExpressionOrBlock::Block(block) => take_block(block),
+		}
+	}
+
+	fn set_body(
+		function: &mut FunctionBase<Self>,
+		items: Vec<StatementOrDeclaration>,
+		value: Expression,
+	) {
+		match &mut function.body {
+			ExpressionOrBlock::Block(block) => set_block(block, items, value),
+			body if items.is_empty() => *body = ExpressionOrBlock::Expression(Box::new(value)),
+			body => {
+				let position = value.get_position().into_owned();
+				let mut block = Block(Vec::new(), BlockId::new(), position);
+				set_block(&mut block, items, value);
+				*body = ExpressionOrBlock::Block(block);
+			}
+		}
+	}
+}
+
+fn set_header(
+	header: &mut FunctionHeader,
+	async_keyword: Option<Keyword<parser::tsx_keywords::Async>>,
+	generator: Option<Span>,
+) {
+	let position = header.get_position().into_owned();
+	*header = FunctionHeader::VirginFunctionHeader {
+		async_keyword,
+		function_keyword: Keyword::new(position),
+		generator_star_token_pos: generator,
+	};
+}
+
+/// The emptied body keeps its [BlockId], as temporaries may be declared in it
+fn take_block(block: &mut Block) -> Block {
+	Block(std::mem::take(&mut block.0), BlockId::new(), block.2.clone())
+}
+
+fn set_block(block: &mut Block, mut items: Vec<StatementOrDeclaration>, value: Expression) {
+	let position = value.get_position().into_owned();
+	items.push(StatementOrDeclaration::Statement(Statement::Return(
+		Keyword::new(position),
+		Some(value.into()),
+	)));
+	block.0 = items;
+}
+
+/// Lowers functions (see [lower_declared_functions] and [lower_expression_functions])
+pub(crate) trait LowerFunction {
+	fn lower<T: LowerableFunction>(
+		&mut self,
+		function: &mut FunctionBase<T>,
+		functions: &mut ExtractedFunctions,
+		data: &mut Vec<TempDiagnostic>,
+	);
+}
+
+fn lower_extracted<T: LowerableFunction>(
+	id: FunctionId<T>,
+	functions: &mut ExtractedFunctions,
+	data: &mut Vec<TempDiagnostic>,
+	lowering: &mut impl LowerFunction,
+) where
+	ExtractedFunctions: GetFunction<T>,
+{
+	if GetFunction::<T>::get_function_ref(functions, id).is_none() {
+		return;
+	}
+	let mut function = GetFunction::<T>::get_function(functions, id);
+	lowering.lower(&mut function, functions, data);
+	functions.insert_function(function);
+}
+
+fn lower_class_methods(
+	members: &mut [Decorated<ClassMember>],
+	functions: &mut ExtractedFunctions,
+	data: &mut Vec<TempDiagnostic>,
+	lowering: &mut impl LowerFunction,
+) {
+	for member in members.iter() {
+		if let ClassMember::Function(_, method) = &member.on {
+			lower_extracted(method.0, functions, data, lowering);
+		}
+	}
+}
+
+/// Lowers the functions and class methods declared in a block, for block visitors. Functions are
+/// lowered before the visitor visits them, so their new bodies are visited
+pub(crate) fn lower_declared_functions(
+	items: &mut [StatementOrDeclaration],
+	functions: &mut ExtractedFunctions,
+	data: &mut Vec<TempDiagnostic>,
+	lowering: &mut impl LowerFunction,
+) {
+	for item in items.iter_mut() {
+		let StatementOrDeclaration::Declaration(declaration) = item else { continue };
+		match declaration {
+			Declaration::ExtractedFunction(function) => {
+				lower_extracted(function.0, functions, data, lowering)
+			}
+			Declaration::Function(Decorated { on: function, .. })
+			| Declaration::Export(Decorated {
+				on: ExportDeclaration::Variable { exported: Exportable::Function(function), .. },
+				..
+			}) => lowering.lower(function, functions, data),
+			Declaration::Class(Decorated { on: class, .. })
+			| Declaration::Export(Decorated {
+				on: ExportDeclaration::Variable { exported: Exportable::Class(class), .. },
+				..
+			}) => lower_class_methods(&mut class.members, functions, data, lowering),
+			_ => {}
+		}
+	}
+}
+
+/// Lowers function expressions, arrow functions, object literal methods and class expression
+/// methods, for expression visitors
+pub(crate) fn lower_expression_functions(
+	expression: &mut Expression,
+	functions: &mut ExtractedFunctions,
+	data: &mut Vec<TempDiagnostic>,
+	lowering: &mut impl LowerFunction,
+) {
+	match expression {
+		Expression::ExtractedExpressionFunction(function) => {
+			lower_extracted(function.0, functions, data, lowering)
+		}
+		Expression::ExtractedArrowFunction(function) => {
+			lower_extracted(function.0, functions, data, lowering)
+		}
+		Expression::ExpressionFunction(function) => lowering.lower(function, functions, data),
+		Expression::ArrowFunction(function) => lowering.lower(function, functions, data),
+		Expression::ObjectLiteral(object) => {
+			for member in object.members.iter() {
+				if let ObjectLiteralMember::Method(method) = member {
+					lower_extracted(method.0, functions, data, lowering);
+				}
+			}
+		}
+		Expression::ClassExpression(class, _) => {
+			lower_class_methods(&mut class.members, functions, data, lowering)
+		}
+		_ => {}
+	}
+}
+
+/// Whether `predicate` is true for `node` or a node under it. Nodes in nested functions are not
+/// included, except in arrow functions if `arrow_functions`
+pub(crate) fn any_in_function<'a>(
+	node: NodeRef<'a>,
+	functions: &'a ExtractedFunctions,
+	arrow_functions: bool,
+	predicate: &impl Fn(NodeRef<'a>) -> bool,
+) -> bool {
+	predicate(node)
+		|| node.children(functions).into_iter().any(|child| {
+			let is_arrow_function = matches!(
+				child,
+				NodeRef::Expression(
+					Expression::ArrowFunction(_) | Expression::ExtractedArrowFunction(_)
+				)
+			);
+			let skip = crate::bundle::is_function(child) && !(arrow_functions && is_arrow_function);
+			!skip && any_in_function(child, functions, arrow_functions, predicate)
+		})
+}
+
+/// Whether a function body references `arguments` (of itself, rather than of a nested function)
+pub(crate) fn references_arguments(body: NodeRef, functions: &ExtractedFunctions) -> bool {
+	any_in_function(
+		body,
+		functions,
+		true,
+		&|node| matches!(node, NodeRef::Expression(Expression::VariableReference(name, ..)) if name == "arguments"),
+	)
+}
+
+/// Whether a function body uses `super`, which cannot be moved into another function
+pub(crate) fn uses_super(body: NodeRef, functions: &ExtractedFunctions) -> bool {
+	any_in_function(body, functions, true, &|node| {
+		matches!(node, NodeRef::Expression(Expression::SuperExpression(..)))
+	})
+}
+
+pub(crate) fn is_super_call(item: &StatementOrDeclaration) -> bool {
+	matches!(
+		item,
+		StatementOrDeclaration::Statement(Statement::Expression(MultipleExpression {
+			lhs: None,
+			rhs: Expression::SuperExpression(parser::expressions::SuperReference::Call { .. }, ..)
+		}))
+	)
+}
+
+/// `"use strict"` and other directives
+pub(crate) fn is_directive(item: &StatementOrDeclaration) -> bool {
+	matches!(
+		item,
+		StatementOrDeclaration::Statement(Statement::Expression(MultipleExpression {
+			lhs: None,
+			rhs: Expression::StringLiteral(..)
+		}))
+	)
+}
+
+/// Whether an expression can be evaluated more than once without side effects (or a different
+/// result)
+pub(crate) fn is_simple(expression: &Expression) -> bool {
+	matches!(expression, Expression::VariableReference(..) | Expression::ThisReference(..))
+}
+
+pub(crate) fn warning(label: String, position: Span) -> TempDiagnostic {
+	TempDiagnostic {
+		label,
+		position,
+		kind: ErrorWarningInfo::Warning,
+		code: DiagnosticCode::UnsupportedTarget,
+		secondary_labels: Vec::new(),
+		notes: Vec::new(),
+	}
+}
+
+pub(crate) fn reference(name: &str, position: &Span) -> Expression {
+	Expression::VariableReference(name.to_owned(), position.clone(), ExpressionId::new())
+}
+
+pub(crate) fn parenthesize(expression: Expression, position: &Span) -> Expression {
+	Expression::ParenthesizedExpression(
+		Box::new(expression.into()),
+		position.clone(),
+		ExpressionId::new(),
+	)
+}
+
+/// `a, b, c`
+pub(crate) fn sequence(expressions: impl IntoIterator<Item = Expression>) -> MultipleExpression {
+	let mut expressions = expressions.into_iter();
+	let first = MultipleExpression::from(expressions.next().expect("empty sequence"));
+	expressions.fold(first, |lhs, rhs| MultipleExpression { lhs: Some(Box::new(lhs)), rhs })
+}
+
+/// `name = value`
+pub(crate) fn assign(name: &str, value: Expression, position: &Span) -> Expression {
+	assign_to(
+		VariableOrPropertyAccess::Variable(name.to_owned(), position.clone(), ExpressionId::new()),
+		value,
+	)
+}
+
+pub(crate) fn assign_to(target: VariableOrPropertyAccess, value: Expression) -> Expression {
+	Expression::Assignment {
+		lhs: LHSOfAssignment::VariableOrPropertyAccess(target),
+		rhs: Box::new(value),
+		id: ExpressionId::new(),
+	}
+}
+
+/// `object.property`
+pub(crate) fn member(object: Expression, property: &str, position: &Span) -> Expression {
+	Expression::PropertyAccess {
+		parent: Box::new(object),
+		property: PropertyReference::Standard(property.to_owned()),
+		position: position.clone(),
+		expression_id: ExpressionId::new(),
+		is_optional: false,
+	}
+}
+
+pub(crate) fn call(
+	function: Expression,
+	arguments: Vec<Expression>,
+	position: &Span,
+) -> Expression {
+	Expression::FunctionCall {
+		function: Box::new(function),
+		type_arguments: None,
+		arguments: arguments.into_iter().map(SpreadExpression::NonSpread).collect(),
+		position: position.clone(),
+		expression_id: ExpressionId::new(),
+	}
+}
+
+pub(crate) fn function_header(
+	async_keyword: Option<Keyword<tsx_keywords::Async>>,
+	generator: Option<Span>,
+	position: &Span,
+) -> FunctionHeader {
+	FunctionHeader::VirginFunctionHeader {
+		async_keyword,
+		function_keyword: Keyword::new(position.clone()),
+		generator_star_token_pos: generator,
+	}
+}
+
+/// A method as a function expression
+pub(crate) fn function_expression<T: FunctionBased<Body = Block>>(
+	function: FunctionBase<T>,
+	async_keyword: Option<Keyword<tsx_keywords::Async>>,
+	generator: Option<Span>,
+	position: &Span,
+	functions: &mut ExtractedFunctions,
+) -> Expression {
+	let function = FunctionBase::<ExpressionFunctionBase> {
+		function_id: FunctionId::new(),
+		header: function_header(async_keyword, generator, position),
+		name: None,
+		type_parameters: function.type_parameters,
+		parameters: function.parameters,
+		return_type: function.return_type,
+		body: function.body,
+	};
+	Expression::ExtractedExpressionFunction(functions.new_extracted_function(function))
+}
+
+/// `object.key` or `object[key]` as an assignment target
+pub(crate) fn property(object: Expression, key: PropertyKey) -> VariableOrPropertyAccess {
+	match key {
+		PropertyKey::Ident(name, _, position) => VariableOrPropertyAccess::PropertyAccess {
+			parent: Box::new(object),
+			property: PropertyReference::Standard(name),
+			position,
+			expression_id: ExpressionId::new(),
+		},
+		key => {
+			let position = key.get_position().into_owned();
+			VariableOrPropertyAccess::Index {
+				indexee: Box::new(object),
+				indexer: Box::new(key_expression(key).into()),
+				position,
+				expression_id: ExpressionId::new(),
+			}
+		}
+	}
+}
+
+/// The value of a key, for `object[key]`
+pub(crate) fn key_expression(key: PropertyKey) -> Expression {
+	match key {
+		PropertyKey::Ident(name, _, position) | PropertyKey::StringLiteral(name, _, position) => {
+			Expression::StringLiteral(name, Quoted::Double, position, ExpressionId::new())
+		}
+		PropertyKey::NumberLiteral(number, _, position) => {
+			Expression::NumberLiteral(number, position, ExpressionId::new())
+		}
+		PropertyKey::Computed(expression, ..) => *expression,
+	}
+}
+
+/// `void 0`, for `undefined` which can be shadowed
+pub(crate) fn void_zero(position: &Span) -> Expression {
+	Expression::UnaryOperation {
+		operator: UnaryOperator::Void,
+		operand: Box::new(Expression::NumberLiteral(
+			NumberStructure::Number(0.),
+			position.clone(),
+			ExpressionId::new(),
+		)),
+		position: position.clone(),
+		id: ExpressionId::new(),
+	}
+}
+
+/// `name = value` in a declaration
+pub(crate) fn declaration_item<T>(
+	name: String,
+	position: Span,
+	value: T,
+) -> VariableDeclarationItem<T>
+where
+	T: parser::declarations::variable::DeclarationExpression,
+{
+	VariableDeclarationItem {
+		name: WithComment::None(VariableField::Name(VariableIdentifier::Standard(
+			name,
+			VariableId::new(),
+			position,
+		))),
+		type_reference: None,
+		expression: value,
+	}
+}
+
+/// The expression to read an assignment target
+pub(crate) fn read_target(target: VariableOrPropertyAccess) -> Expression {
+	match target {
+		VariableOrPropertyAccess::Variable(name, position, id) => {
+			Expression::VariableReference(name, position, id)
+		}
+		VariableOrPropertyAccess::PropertyAccess { parent, property, position, expression_id } => {
+			Expression::PropertyAccess {
+				parent,
+				property,
+				position,
+				expression_id,
+				is_optional: false,
+			}
+		}
+		VariableOrPropertyAccess::Index { indexee, indexer, position, expression_id } => {
+			Expression::Index { indexee, indexer, position, expression_id }
+		}
+		VariableOrPropertyAccess::Cursor { .. } => unreachable!("cursor in build"),
+	}
+}
+
+/// Splits an assignment target which is both read and written (e.g. `a.b.c ||= d`) into one
+/// which stores its object and key in temporaries (`(_a = a.b).c`) and one which reuses them
+/// (`_a.c`), so that the object and key are only evaluated once
+pub(crate) fn reuse_target(
+	target: VariableOrPropertyAccess,
+	temporaries: &mut Temporaries,
+	chain: &Chain,
+) -> (VariableOrPropertyAccess, VariableOrPropertyAccess) {
+	// Stores `expression` in a temporary unless it is simple
+	let mut store = |expression: Expression, position: &Span| {
+		if is_simple(&expression) || is_literal(&expression) {
+			(expression.clone(), expression)
+		} else {
+			let name = temporaries.temporary(chain);
+			let stored = parenthesize(assign(&name, expression, position), position);
+			(stored, reference(&name, position))
+		}
+	};
+	match target {
+		VariableOrPropertyAccess::PropertyAccess { parent, property, position, .. } => {
+			let (stored, reused) = store(*parent, &position);
+			let first = VariableOrPropertyAccess::PropertyAccess {
+				parent: Box::new(stored),
+				property: property.clone(),
+				position: position.clone(),
+				expression_id: ExpressionId::new(),
+			};
+			let second = VariableOrPropertyAccess::PropertyAccess {
+				parent: Box::new(reused),
+				property,
+				position,
+				expression_id: ExpressionId::new(),
+			};
+			(first, second)
+		}
+		VariableOrPropertyAccess::Index { indexee, indexer, position, .. } => {
+			let (stored_object, reused_object) = store(*indexee, &position);
+			let key = match *indexer {
+				MultipleExpression { lhs: None, rhs } => rhs,
+				indexer => Expression::ParenthesizedExpression(
+					Box::new(indexer),
+					position.clone(),
+					ExpressionId::new(),
+				),
+			};
+			let (stored_key, reused_key) = store(key, &position);
+			let first = VariableOrPropertyAccess::Index {
+				indexee: Box::new(stored_object),
+				indexer: Box::new(unparenthesize(stored_key).into()),
+				position: position.clone(),
+				expression_id: ExpressionId::new(),
+			};
+			let second = VariableOrPropertyAccess::Index {
+				indexee: Box::new(reused_object),
+				indexer: Box::new(reused_key.into()),
+				position,
+				expression_id: ExpressionId::new(),
+			};
+			(first, second)
+		}
+		target => (target.clone(), target),
+	}
+}
+
+pub(crate) fn is_literal(expression: &Expression) -> bool {
+	matches!(
+		expression,
+		Expression::NumberLiteral(..)
+			| Expression::StringLiteral(..)
+			| Expression::BooleanLiteral(..)
+			| Expression::Null(..)
+	)
+}
+
+/// Moves an expression out of the tree, leaving `null`
+pub(crate) fn take(expression: &mut Expression, position: &Span) -> Expression {
+	std::mem::replace(expression, Expression::Null(position.clone(), ExpressionId::new()))
+}
+
+/// Brackets of an index already group the key
+pub(crate) fn unparenthesize(expression: Expression) -> Expression {
+	match expression {
+		Expression::ParenthesizedExpression(inner, ..) if inner.lhs.is_none() => inner.rhs,
+		expression => expression,
+	}
+}
+
+/// `const x = value` (or `let`, `var`), or `x = value` if the loop does not declare the variable
+pub(crate) fn loop_variable(
+	keyword: Option<VariableDeclarationKeyword>,
+	variable: WithComment<VariableField<VariableFieldInSourceCode>>,
+	value: Expression,
+	position: &Span,
+) -> StatementOrDeclaration {
+	let declaration = match keyword {
+		Some(VariableDeclarationKeyword::Const(keyword)) => {
+			let item =
+				VariableDeclarationItem { name: variable, type_reference: None, expression: value };
+			VariableDeclaration::ConstDeclaration { keyword, declarations: vec![item] }
+		}
+		Some(VariableDeclarationKeyword::Let(keyword)) => {
+			let item = VariableDeclarationItem {
+				name: variable,
+				type_reference: None,
+				expression: Some(value),
+			};
+			VariableDeclaration::LetDeclaration { keyword, declarations: vec![item] }
+		}
+		Some(VariableDeclarationKeyword::Var(keyword)) => {
+			let item = VariableDeclarationItem {
+				name: variable,
+				type_reference: None,
+				expression: Some(value),
+			};
+			let statement = VarVariableStatement { keyword, declarations: vec![item] };
+			return StatementOrDeclaration::Statement(Statement::VarVariable(statement));
+		}
+		None => {
+			let assignment = match variable.unwrap_ast() {
+				VariableField::Name(VariableIdentifier::Standard(name, ..)) => {
+					assign(&name, value, position)
+				}
+				VariableField::Name(VariableIdentifier::Cursor(..)) => {
+					unreachable!("cursor in build")
+				}
+				VariableField::Array(fields, position) => parenthesize(
+					Expression::Assignment {
+						lhs: LHSOfAssignment::ArrayDestructuring(
+							fields,
+							position.clone(),
+							ExpressionId::new(),
+						),
+						rhs: Box::new(value),
+						id: ExpressionId::new(),
+					},
+					&position,
+				),
+				VariableField::Object(fields, position) => parenthesize(
+					Expression::Assignment {
+						lhs: LHSOfAssignment::ObjectDestructuring(
+							fields,
+							position.clone(),
+							ExpressionId::new(),
+						),
+						rhs: Box::new(value),
+						id: ExpressionId::new(),
+					},
+					&position,
+				),
+			};
+			return StatementOrDeclaration::Statement(Statement::Expression(assignment.into()));
+		}
+	};
+	StatementOrDeclaration::Declaration(Declaration::Variable(declaration))
+}
+
+/// Whether the body of a loop declares a name of the loop variable, in which case the body is kept
+/// in its own block
+pub(crate) fn shadows(
+	body: &BlockOrSingleStatement,
+	variable: &VariableField<VariableFieldInSourceCode>,
+	functions: &ExtractedFunctions,
+) -> bool {
+	let BlockOrSingleStatement::Braced(body) = body else { return false };
+	let mut names = Vec::new();
+	crate::bundle::variable_field_identifiers(variable, &mut names);
+	let mut declared = Vec::new();
+	crate::bundle::lexical_declarations(&body.0, functions, &mut declared);
+	declared.iter().any(|(name, _)| names.iter().any(|(variable, _)| variable == name))
+}
@@ -0,0 +1,143 @@
+//! Shorthand properties and methods to properties, `{ a, b() {} }` to
+//! `{ a: a, b: function () {} }`. Members from the first computed key are assigned after the object
+//! is created, `{ a: 1, [b]: 2 }` to `(_a = { a: 1 }, _a[b] = 2, _a)`, so that they are evaluated
+//! in order.
+//!
+//! Getters and setters are kept as they are in ES5. Methods using `super` and objects with getters
+//! or setters after a computed key are kept, with a warning
+
+use parser::{
+	expressions::{
+		object_literal::{ObjectLiteral, ObjectLiteralMember, ObjectLiteralMethodBase},
+		ExpressionId,
+	},
+	extractor::{ExtractedFunctions, GetFunction},
+	Chain, Expression, GetSetGeneratorOrNone, NodeRef, PropertyId, PropertyKey, VisitorsMut,
+	WithComment,
+};
+
+use super::{
+	assign, assign_to, call, function_expression, member, property, reference, sequence,
+	uses_super, warning, SharedTemporaries,
+};
+use crate::error_handling::TempDiagnostic;
+
+/// Passes for [crate::temp::Transform::LowerObjectLiterals]
+pub(crate) fn passes() -> Vec<VisitorsMut<Vec<TempDiagnostic>>> {
+	super::passes(|temporaries| VisitorsMut {
+		expression_visitors_mut: vec![Box::new(LowerObjectLiterals(temporaries.clone()))],
+		..Default::default()
+	})
+}
+
+struct LowerObjectLiterals(SharedTemporaries);
+
+impl parser::VisitorMut<Expression, Vec<TempDiagnostic>> for LowerObjectLiterals {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		chain: &Chain,
+	) {
+		let Expression::ObjectLiteral(ObjectLiteral { members, position, .. }) = item else {
+			return;
+		};
+		let position = position.clone();
+		for member in members.iter_mut() {
+			lower_member(member, data, functions);
+		}
+
+		let Some(computed) = members.iter().position(|member| {
+			matches!(
+				member,
+				ObjectLiteralMember::Property(key, ..)
+					if matches!(key.get_ast(), PropertyKey::Computed(..))
+			)
+		}) else {
+			return;
+		};
+		// Only getters, setters and methods using `super` are still methods
+		if members[computed..].iter().any(|member| matches!(member, ObjectLiteralMember::Method(_)))
+		{
+			data.push(warning(
+				"Computed keys before getters and setters are not lowered".to_owned(),
+				position,
+			));
+			return;
+		}
+
+		let name = self.0.borrow_mut().temporary(chain);
+		let object = || reference(&name, &position);
+		let assignments = members
+			.drain(computed..)
+			.map(|lowered| match lowered {
+				ObjectLiteralMember::Property(key, value, _) => {
+					assign_to(property(object(), key.unwrap_ast()), value)
+				}
+				ObjectLiteralMember::SpreadExpression(value, position) => {
+					let assign = member(reference("Object", &position), "assign", &position);
+					call(assign, vec![object(), value], &position)
+				}
+				ObjectLiteralMember::Shorthand(..) | ObjectLiteralMember::Method(_) => {
+					unreachable!("not lowered")
+				}
+			})
+			.collect::<Vec<_>>();
+		let created = Expression::ObjectLiteral(ObjectLiteral {
+			members: std::mem::take(members),
+			position: position.clone(),
+			expression_id: ExpressionId::new(),
+		});
+		let expressions = std::iter::once(assign(&name, created, &position))
+			.chain(assignments)
+			.chain(std::iter::once(object()));
+		*item = Expression::ParenthesizedExpression(
+			Box::new(sequence(expressions)),
+			position,
+			ExpressionId::new(),
+		);
+	}
+}
+
+/// Shorthand properties and methods (other than getters and setters) to properties
+fn lower_member(
+	member: &mut ObjectLiteralMember,
+	data: &mut Vec<TempDiagnostic>,
+	functions: &mut ExtractedFunctions,
+) {
+	match member {
+		ObjectLiteralMember::Shorthand(name, position, ..) => {
+			let key = PropertyKey::Ident(name.clone(), PropertyId::new(), position.clone());
+			let value = reference(name, position);
+			*member =
+				ObjectLiteralMember::Property(WithComment::None(key), value, position.clone());
+		}
+		ObjectLiteralMember::Method(method) => {
+			let Some(function) =
+				GetFunction::<ObjectLiteralMethodBase>::get_function_ref(functions, method.0)
+			else {
+				return;
+			};
+			let (async_keyword, kind) = function.header.clone();
+			let generator = match kind {
+				GetSetGeneratorOrNone::Get(_) | GetSetGeneratorOrNone::Set(_) => return,
+				GetSetGeneratorOrNone::Generator(keyword) => Some(keyword.1),
+				GetSetGeneratorOrNone::GeneratorStar(position) => Some(position),
+				GetSetGeneratorOrNone::None => None,
+			};
+			let key = function.name.clone();
+			let position = key.get_ast().get_position().into_owned();
+			if uses_super(NodeRef::Block(&function.body), functions) {
+				data.push(warning("Methods using super are not lowered".to_owned(), position));
+				return;
+			}
+			let function =
+				GetFunction::<ObjectLiteralMethodBase>::get_function(functions, method.0);
+			let value =
+				function_expression(function, async_keyword, generator, &position, functions);
+			*member = ObjectLiteralMember::Property(key, value, position);
+		}
+		ObjectLiteralMember::Property(..) | ObjectLiteralMember::SpreadExpression(..) => {}
+	}
+}
@@ -0,0 +1,66 @@
+//! `{ a: 1, ...b, c }` to `Object.assign({ a: 1 }, b, { c })`. Members between spreads are
+//! grouped into object literals, so that properties are assigned in the original order
+
+use parser::{
+	expressions::object_literal::{ObjectLiteral, ObjectLiteralMember},
+	extractor::ExtractedFunctions,
+	Chain, Expression, VisitorsMut,
+};
+
+use super::{call, member, reference};
+use crate::error_handling::TempDiagnostic;
+
+/// Passes for [crate::temp::Transform::LowerObjectSpread]
+pub(crate) fn passes() -> Vec<VisitorsMut<Vec<TempDiagnostic>>> {
+	vec![VisitorsMut {
+		expression_visitors_mut: vec![Box::new(LowerObjectSpread)],
+		..Default::default()
+	}]
+}
+
+struct LowerObjectSpread;
+
+impl parser::VisitorMut<Expression, Vec<TempDiagnostic>> for LowerObjectSpread {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		_data: &mut Vec<TempDiagnostic>,
+		_functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		let Expression::ObjectLiteral(ObjectLiteral { members, position, .. }) = item else {
+			return;
+		};
+		if !members.iter().any(|member| matches!(member, ObjectLiteralMember::SpreadExpression(..)))
+		{
+			return;
+		}
+
+		let position = position.clone();
+		let object = |members| {
+			Expression::ObjectLiteral(ObjectLiteral {
+				members,
+				position: position.clone(),
+				expression_id: parser::expressions::ExpressionId::new(),
+			})
+		};
+		let mut arguments = Vec::new();
+		let mut group = Vec::new();
+		for member in std::mem::take(members) {
+			if let ObjectLiteralMember::SpreadExpression(value, _) = member {
+				// The first argument is the object which is created, so it cannot be a spread value
+				if !group.is_empty() || arguments.is_empty() {
+					arguments.push(object(std::mem::take(&mut group)));
+				}
+				arguments.push(value);
+			} else {
+				group.push(member);
+			}
+		}
+		if !group.is_empty() {
+			arguments.push(object(group));
+		}
+		let assign = member(reference("Object", &position), "assign", &position);
+		*item = call(assign, arguments, &position);
+	}
+}
@@ -0,0 +1,103 @@
+//! `a?.b` to `a == null ? void 0 : a.b` and `a ?? b` to `a != null ? a : b`. Values which are
+//! not simple are stored in temporaries so that they are only evaluated once
+
+use parser::{
+	expressions::ExpressionId, extractor::ExtractedFunctions, operators::BinaryOperator, ASTNode,
+	Chain, Expression, Span, VisitorsMut,
+};
+
+use super::{assign, is_simple, parenthesize, reference, take, void_zero, SharedTemporaries};
+use crate::error_handling::TempDiagnostic;
+
+/// Passes for [crate::temp::Transform::LowerOptionalChaining]
+pub(crate) fn passes() -> Vec<VisitorsMut<Vec<TempDiagnostic>>> {
+	super::passes(|temporaries| VisitorsMut {
+		expression_visitors_mut: vec![Box::new(LowerOptionalChaining(temporaries.clone()))],
+		..Default::default()
+	})
+}
+
+struct LowerOptionalChaining(SharedTemporaries);
+
+impl parser::VisitorMut<Expression, Vec<TempDiagnostic>> for LowerOptionalChaining {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		_data: &mut Vec<TempDiagnostic>,
+		_functions: &mut ExtractedFunctions,
+		chain: &Chain,
+	) {
+		if let Expression::BinaryOperation { operator: BinaryOperator::NullCoalescing, .. } = item {
+			let position = item.get_position().into_owned();
+			let Expression::BinaryOperation { lhs, rhs, .. } = take(item, &position) else {
+				unreachable!()
+			};
+			let (test, value) = self.store(*lhs, chain, &position);
+			let condition = Expression::TernaryExpression {
+				condition: Box::new(compare_to_null(test, BinaryOperator::NotEqual)),
+				truthy_result: Box::new(value),
+				falsy_result: rhs,
+				id: ExpressionId::new(),
+			};
+			*item = parenthesize(condition, &position);
+			return;
+		}
+
+		// Expressions are visited from the outside in, so this is the end of the chain. Nested
+		// chains in the object are lowered when it is visited
+		let Some(object) = outermost_optional_object(item) else { return };
+		let position = object.get_position().into_owned();
+		let (test, value) = self.store(take(object, &position), chain, &position);
+		*object = value;
+
+		let position = item.get_position().into_owned();
+		let condition = Expression::TernaryExpression {
+			condition: Box::new(compare_to_null(test, BinaryOperator::Equal)),
+			truthy_result: Box::new(void_zero(&position)),
+			falsy_result: Box::new(take(item, &position)),
+			id: ExpressionId::new(),
+		};
+		*item = parenthesize(condition, &position);
+	}
+}
+
+impl LowerOptionalChaining {
+	/// The expression to test (which may store the value) and the expression to use the value
+	fn store(&self, value: Expression, chain: &Chain, position: &Span) -> (Expression, Expression) {
+		if is_simple(&value) {
+			(value.clone(), value)
+		} else {
+			let name = self.0.borrow_mut().temporary(chain);
+			(parenthesize(assign(&name, value, position), position), reference(&name, position))
+		}
+	}
+}
+
+/// The object of the outermost `?.` in the chain of property accesses, indexes and calls ending
+/// at `expression`. The `?.` is replaced with `.`
+fn outermost_optional_object(expression: &mut Expression) -> Option<&mut Expression> {
+	match expression {
+		Expression::PropertyAccess { parent, is_optional, .. } => {
+			if *is_optional {
+				*is_optional = false;
+				Some(parent)
+			} else {
+				outermost_optional_object(parent)
+			}
+		}
+		Expression::Index { indexee: object, .. }
+		| Expression::FunctionCall { function: object, .. } => outermost_optional_object(object),
+		_ => None,
+	}
+}
+
+/// `value == null` or `value != null`, which also match `undefined`
+fn compare_to_null(value: Expression, operator: BinaryOperator) -> Expression {
+	let position = value.get_position().into_owned();
+	Expression::BinaryOperation {
+		lhs: Box::new(value),
+		operator,
+		rhs: Box::new(Expression::Null(position, ExpressionId::new())),
+		id: ExpressionId::new(),
+	}
+}
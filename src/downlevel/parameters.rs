@@ -0,0 +1,261 @@
+//! Default values of parameters and rest parameters, `function f(a = 1, ...b) {}` to
+//! `function f(a) { if (a === void 0) a = 1; var b = Array.prototype.slice.call(arguments, 1); }`.
+//!
+//! Parameters are lowered after generators and async functions, so that default values are still
+//! evaluated when the function is called. Destructured parameters (and the parameters after them)
+//! are kept, they are lowered by [crate::temp::Transform::LowerDestructuring]. Rest parameters of
+//! arrow functions are kept with a warning, as arrow functions do not have their own `arguments`
+
+use parser::{
+	declarations::{
+		classes::{ClassConstructorBase, ClassFunctionBase, ClassMember},
+		export::Exportable,
+		ClassDeclaration, ExportDeclaration, StatementFunctionBase,
+	},
+	expressions::{
+		arrow_function::{ArrowFunctionBase, ExpressionOrBlock},
+		object_literal::{ObjectLiteral, ObjectLiteralMember, ObjectLiteralMethodBase},
+		ExpressionFunctionBase, ExpressionId,
+	},
+	extractor::{ExtractedFunctions, GetFunction},
+	operators::BinaryOperator,
+	statements::{IfStatement, VarVariableStatement},
+	ASTNode, Block, BlockId, BlockLikeMut, BlockOrSingleStatement, Chain, Declaration, Decorated,
+	Expression, ExpressionOrStatementPosition, FunctionBase, FunctionBased, FunctionId,
+	FunctionParameters, Keyword, NumberStructure, OptionalOrWithDefaultValueParameter, Parameter,
+	Statement, StatementOrDeclaration, VariableField, VariableIdentifier, VisitorsMut, WithComment,
+};
+
+use super::{
+	assign, call, declaration_item, is_directive, member, reference, take, void_zero, warning,
+};
+use crate::error_handling::TempDiagnostic;
+
+/// Passes for [crate::temp::Transform::LowerParameters]
+pub(crate) fn passes() -> Vec<VisitorsMut<Vec<TempDiagnostic>>> {
+	vec![VisitorsMut {
+		block_visitors_mut: vec![Box::new(LowerParameters)],
+		expression_visitors_mut: vec![Box::new(LowerParameters)],
+		..Default::default()
+	}]
+}
+
+struct LowerParameters;
+
+/// Functions and classes
+impl<'a> parser::VisitorMut<BlockLikeMut<'a>, Vec<TempDiagnostic>> for LowerParameters {
+	fn visit_mut(
+		&mut self,
+		item: &mut BlockLikeMut<'a>,
+		_data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		for item in item.items.iter_mut() {
+			let StatementOrDeclaration::Declaration(declaration) = item else { continue };
+			match declaration {
+				Declaration::ExtractedFunction(function) => {
+					lower_extracted::<StatementFunctionBase>(function.0, functions)
+				}
+				Declaration::Function(Decorated { on: function, .. })
+				| Declaration::Export(Decorated {
+					on: ExportDeclaration::Variable { exported: Exportable::Function(function), .. },
+					..
+				}) => lower_function(function),
+				Declaration::Class(Decorated { on: class, .. })
+				| Declaration::Export(Decorated {
+					on: ExportDeclaration::Variable { exported: Exportable::Class(class), .. },
+					..
+				}) => lower_class(class, functions),
+				_ => {}
+			}
+		}
+	}
+}
+
+/// Function expressions, arrow functions, object literal methods and class expressions
+impl parser::VisitorMut<Expression, Vec<TempDiagnostic>> for LowerParameters {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		match item {
+			Expression::ExtractedExpressionFunction(function) => {
+				lower_extracted::<ExpressionFunctionBase>(function.0, functions)
+			}
+			Expression::ExpressionFunction(function) => lower_function(function),
+			Expression::ExtractedArrowFunction(function) => {
+				let mut function =
+					GetFunction::<ArrowFunctionBase>::get_function(functions, function.0);
+				lower_arrow_function(&mut function, data);
+				functions.insert_function(function);
+			}
+			Expression::ArrowFunction(function) => lower_arrow_function(function, data),
+			Expression::ClassExpression(class, _) => lower_class(class, functions),
+			Expression::ObjectLiteral(ObjectLiteral { members, .. }) => {
+				for member in members.iter() {
+					if let ObjectLiteralMember::Method(method) = member {
+						lower_extracted::<ObjectLiteralMethodBase>(method.0, functions);
+					}
+				}
+			}
+			_ => {}
+		}
+	}
+}
+
+/// Replaces default values with parameters and removes the rest parameter (unless `keep_rest`),
+/// returning the statements which assign them for the start of the body
+fn lower_parameters(
+	parameters: &mut FunctionParameters,
+	keep_rest: bool,
+) -> Vec<StatementOrDeclaration> {
+	let mut statements = Vec::new();
+	let mut optional_parameters = std::mem::take(&mut parameters.optional_parameters).into_iter();
+	for parameter in optional_parameters.by_ref() {
+		match parameter {
+			OptionalOrWithDefaultValueParameter::Optional { name, type_reference, property } => {
+				let name = WithComment::None(VariableField::Name(name));
+				parameters.parameters.push(Parameter { name, type_reference, property });
+			}
+			OptionalOrWithDefaultValueParameter::WithDefaultValue {
+				name,
+				type_reference,
+				value,
+				property,
+			} => {
+				let VariableField::Name(VariableIdentifier::Standard(identifier, _, position)) =
+					name.get_ast()
+				else {
+					parameters.optional_parameters.push(
+						OptionalOrWithDefaultValueParameter::WithDefaultValue {
+							name,
+							type_reference,
+							value,
+							property,
+						},
+					);
+					break;
+				};
+				// `if (a === void 0) a = 1`
+				let condition = Expression::BinaryOperation {
+					lhs: Box::new(reference(identifier, position)),
+					operator: BinaryOperator::StrictEqual,
+					rhs: Box::new(void_zero(position)),
+					id: ExpressionId::new(),
+				};
+				let assignment = assign(identifier, *value, position);
+				statements.push(StatementOrDeclaration::Statement(Statement::IfStatement(
+					IfStatement {
+						condition: condition.into(),
+						inner: BlockOrSingleStatement::SingleStatement(Box::new(
+							Statement::Expression(assignment.into()),
+						)),
+						else_conditions: Vec::new(),
+						trailing_else: None,
+						position: position.clone(),
+					},
+				)));
+				parameters.parameters.push(Parameter { name, type_reference, property });
+			}
+		}
+	}
+	parameters.optional_parameters.extend(optional_parameters);
+
+	if keep_rest {
+		return statements;
+	}
+	let Some(rest) = parameters.rest_parameter.take() else {
+		return statements;
+	};
+	let VariableIdentifier::Standard(name, _, position) = rest.name else {
+		unreachable!("cursor in build")
+	};
+	// `var b = Array.prototype.slice.call(arguments, 1)`
+	let index = parameters.parameters.len() + parameters.optional_parameters.len();
+	let prototype = member(reference("Array", &position), "prototype", &position);
+	let slice = member(member(prototype, "slice", &position), "call", &position);
+	let index = Expression::NumberLiteral(
+		NumberStructure::Number(index as f64),
+		position.clone(),
+		ExpressionId::new(),
+	);
+	let value = call(slice, vec![reference("arguments", &position), index], &position);
+	let declarations = vec![declaration_item(name, position.clone(), Some(value))];
+	let statement = VarVariableStatement { keyword: Keyword::new(position), declarations };
+	statements.push(StatementOrDeclaration::Statement(Statement::VarVariable(statement)));
+	statements
+}
+
+/// Inserts statements after the directives of a body
+fn prepend(body: &mut Block, statements: Vec<StatementOrDeclaration>) {
+	let index = body.0.iter().take_while(|item| is_directive(item)).count();
+	body.0.splice(index..index, statements);
+}
+
+fn lower_function<T: FunctionBased<Body = Block>>(function: &mut FunctionBase<T>) {
+	let statements = lower_parameters(&mut function.parameters, false);
+	prepend(&mut function.body, statements);
+}
+
+fn lower_extracted<T: FunctionBased<Body = Block> + 'static>(
+	id: FunctionId<T>,
+	functions: &mut ExtractedFunctions,
+) where
+	ExtractedFunctions: GetFunction<T>,
+{
+	if GetFunction::<T>::get_function_ref(functions, id).is_none() {
+		return;
+	}
+	let mut function = functions.get_function(id);
+	lower_function(&mut function);
+	functions.insert_function(function);
+}
+
+fn lower_arrow_function(
+	function: &mut FunctionBase<ArrowFunctionBase>,
+	data: &mut Vec<TempDiagnostic>,
+) {
+	if let Some(rest) = &function.parameters.rest_parameter {
+		data.push(warning(
+			"Rest parameters of arrow functions are not lowered".to_owned(),
+			rest.name.get_position().into_owned(),
+		));
+	}
+	let statements = lower_parameters(&mut function.parameters, true);
+	if statements.is_empty() {
+		return;
+	}
+	match &mut function.body {
+		ExpressionOrBlock::Block(block) => prepend(block, statements),
+		ExpressionOrBlock::Expression(expression) => {
+			let position = expression.get_position().into_owned();
+			let expression = take(expression, &position);
+			let statement =
+				Statement::Return(Keyword::new(position.clone()), Some(expression.into()));
+			let mut items = statements;
+			items.push(StatementOrDeclaration::Statement(statement));
+			function.body = ExpressionOrBlock::Block(Block(items, BlockId::new(), position));
+		}
+	}
+}
+
+fn lower_class<T: ExpressionOrStatementPosition>(
+	class: &ClassDeclaration<T>,
+	functions: &mut ExtractedFunctions,
+) {
+	for member in class.members.iter() {
+		match &member.on {
+			ClassMember::Constructor(constructor) => {
+				lower_extracted::<ClassConstructorBase>(constructor.0, functions)
+			}
+			ClassMember::Function(_, function) => {
+				lower_extracted::<ClassFunctionBase>(function.0, functions)
+			}
+			ClassMember::Property(..) => {}
+		}
+	}
+}
@@ -0,0 +1,205 @@
+//! Spread arguments and elements, `f(a, ...b)` to `f.apply(void 0, __spreadArray([a], b))` and
+//! `[a, ...b, c]` to `__spreadArray(__spreadArray([a], b), [c])`. `__spreadArray` iterates over
+//! values with `[Symbol.iterator]` and copies array-like values where symbols are not available.
+//!
+//! Method calls pass the object as `this`, storing it in a temporary if it is not a variable.
+//! `f.call(x, ...a)` (which classes are lowered to) becomes `f.apply(x, __spreadArray([], a))` and
+//! `new F(...a)` becomes `new (Function.prototype.bind.apply(F, __spreadArray([void 0], a)))()`
+
+use parser::{
+	expressions::{ExpressionId, SpreadExpression},
+	extractor::ExtractedFunctions,
+	ASTNode, Chain, Expression, PropertyReference, Span, VisitorsMut,
+};
+
+use super::{
+	assign, call, is_simple, member, parenthesize, reference, void_zero, SharedTemporaries,
+};
+use crate::error_handling::TempDiagnostic;
+
+/// Passes for [crate::temp::Transform::LowerSpread]
+pub(crate) fn passes() -> Vec<VisitorsMut<Vec<TempDiagnostic>>> {
+	super::passes(|temporaries| VisitorsMut {
+		expression_visitors_mut: vec![Box::new(LowerSpread(temporaries.clone()))],
+		..Default::default()
+	})
+}
+
+/// Appends the values of an iterable (or array-like value) to an array
+const SPREAD_ARRAY: &str = r#"function (to, from) {
+	var method = typeof Symbol === "function" && Symbol.iterator && from[Symbol.iterator];
+	if (!method) return to.concat(Array.prototype.slice.call(from));
+	for (var iterator = method.call(from), result; !(result = iterator.next()).done;) to.push(result.value);
+	return to;
+}"#;
+
+struct LowerSpread(SharedTemporaries);
+
+impl parser::VisitorMut<Expression, Vec<TempDiagnostic>> for LowerSpread {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		_data: &mut Vec<TempDiagnostic>,
+		_functions: &mut ExtractedFunctions,
+		chain: &Chain,
+	) {
+		match item {
+			Expression::ArrayLiteral(elements, position, _) if has_spread(elements) => {
+				let position = position.clone();
+				*item = self.spread_array(std::mem::take(elements), Vec::new(), &position);
+			}
+			Expression::FunctionCall { function, arguments, position, .. }
+				if has_spread(arguments) =>
+			{
+				let position = position.clone();
+				let arguments = std::mem::take(arguments);
+				let function = std::mem::replace(&mut **function, void_zero(&position));
+				*item = self.spread_call(function, arguments, &position, chain);
+			}
+			Expression::ConstructorCall {
+				constructor,
+				arguments: Some(arguments),
+				position,
+				..
+			} if has_spread(arguments) => {
+				let position = position.clone();
+				let arguments = self.spread_array(
+					std::mem::take(arguments),
+					vec![SpreadExpression::NonSpread(void_zero(&position))],
+					&position,
+				);
+				let constructor = std::mem::replace(&mut **constructor, void_zero(&position));
+				let bind = ["prototype", "bind", "apply"]
+					.into_iter()
+					.fold(reference("Function", &position), |object, property| {
+						member(object, property, &position)
+					});
+				let bound = call(bind, vec![constructor, arguments], &position);
+				*item = Expression::ConstructorCall {
+					constructor: Box::new(parenthesize(bound, &position)),
+					type_arguments: None,
+					arguments: Some(Vec::new()),
+					position,
+					expression_id: ExpressionId::new(),
+				};
+			}
+			_ => {}
+		}
+	}
+}
+
+impl LowerSpread {
+	/// `[a, ...b, c]` as `__spreadArray(__spreadArray([a], b), [c])`. `leading` elements are put
+	/// before the first spread
+	fn spread_array(
+		&self,
+		elements: Vec<SpreadExpression>,
+		leading: Vec<SpreadExpression>,
+		position: &Span,
+	) -> Expression {
+		let helper = self.0.borrow_mut().helper("__spreadArray", SPREAD_ARRAY);
+		let array =
+			|elements| Expression::ArrayLiteral(elements, position.clone(), ExpressionId::new());
+		let mut result = None;
+		let mut group = leading;
+		for element in elements {
+			match element {
+				SpreadExpression::Spread(value, _) => {
+					let to = match result.take() {
+						Some(result) if group.is_empty() => result,
+						Some(result) => {
+							let group = array(std::mem::take(&mut group));
+							call(reference(&helper, position), vec![result, group], position)
+						}
+						None => array(std::mem::take(&mut group)),
+					};
+					result = Some(call(reference(&helper, position), vec![to, value], position));
+				}
+				element => group.push(element),
+			}
+		}
+		let result = result.expect("no spread elements");
+		if group.is_empty() {
+			result
+		} else {
+			call(reference(&helper, position), vec![result, array(group)], position)
+		}
+	}
+
+	/// `f(...a)` as `f.apply(this, ...)`, where `this` is the object of method calls
+	fn spread_call(
+		&self,
+		function: Expression,
+		mut arguments: Vec<SpreadExpression>,
+		position: &Span,
+		chain: &Chain,
+	) -> Expression {
+		let (function, this) = match function {
+			// `f.call(x, ...a)` to `f.apply(x, ...)`
+			Expression::PropertyAccess {
+				parent,
+				property: PropertyReference::Standard(name),
+				..
+			} if name == "call"
+				&& matches!(arguments.first(), Some(SpreadExpression::NonSpread(..))) =>
+			{
+				let SpreadExpression::NonSpread(this) = arguments.remove(0) else { unreachable!() };
+				(*parent, this)
+			}
+			Expression::PropertyAccess { parent, property, position: access, .. }
+				if !matches!(*parent, Expression::SuperExpression(..)) =>
+			{
+				let (parent, this) = self.reuse(*parent, chain);
+				let method = Expression::PropertyAccess {
+					parent: Box::new(parent),
+					property,
+					position: access,
+					expression_id: ExpressionId::new(),
+					is_optional: false,
+				};
+				(method, this)
+			}
+			Expression::Index { indexee, indexer, position: access, .. }
+				if !matches!(*indexee, Expression::SuperExpression(..)) =>
+			{
+				let (indexee, this) = self.reuse(*indexee, chain);
+				let method = Expression::Index {
+					indexee: Box::new(indexee),
+					indexer,
+					position: access,
+					expression_id: ExpressionId::new(),
+				};
+				(method, this)
+			}
+			function => (function, void_zero(position)),
+		};
+		let arguments = self.spread_array(arguments, Vec::new(), position);
+		let function = match function {
+			function @ (Expression::VariableReference(..)
+			| Expression::PropertyAccess { .. }
+			| Expression::Index { .. }
+			| Expression::FunctionCall { .. }
+			| Expression::ParenthesizedExpression(..)) => function,
+			function => {
+				let position = function.get_position().into_owned();
+				parenthesize(function, &position)
+			}
+		};
+		call(member(function, "apply", position), vec![this, arguments], position)
+	}
+
+	/// The object of a method call and a reference to it, which stores it in a temporary if it is
+	/// not simple
+	fn reuse(&self, object: Expression, chain: &Chain) -> (Expression, Expression) {
+		if is_simple(&object) {
+			return (object.clone(), object);
+		}
+		let position = object.get_position().into_owned();
+		let name = self.0.borrow_mut().temporary(chain);
+		(parenthesize(assign(&name, object, &position), &position), reference(&name, &position))
+	}
+}
+
+fn has_spread(elements: &[SpreadExpression]) -> bool {
+	elements.iter().any(|element| matches!(element, SpreadExpression::Spread(..)))
+}
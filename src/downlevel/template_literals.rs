@@ -0,0 +1,96 @@
+//! `` `a${b}c` `` to `"a".concat(b, "c")`. `concat` (rather than `+`) converts values to strings
+//! in the same way as template literals. Tagged templates are kept, with a warning
+
+use parser::{
+	expressions::{
+		template_literal::{TemplateLiteral, TemplateLiteralPart},
+		ExpressionId,
+	},
+	extractor::ExtractedFunctions,
+	Chain, Expression, Quoted, Span, VisitorsMut,
+};
+
+use super::{call, member, warning};
+use crate::error_handling::TempDiagnostic;
+
+/// Passes for [crate::temp::Transform::LowerTemplateLiterals]
+pub(crate) fn passes() -> Vec<VisitorsMut<Vec<TempDiagnostic>>> {
+	vec![VisitorsMut {
+		expression_visitors_mut: vec![Box::new(LowerTemplateLiterals)],
+		..Default::default()
+	}]
+}
+
+struct LowerTemplateLiterals;
+
+impl parser::VisitorMut<Expression, Vec<TempDiagnostic>> for LowerTemplateLiterals {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		data: &mut Vec<TempDiagnostic>,
+		_functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		let Expression::TemplateLiteral(TemplateLiteral { tag, parts, position, .. }) = item else {
+			return;
+		};
+		if tag.is_some() {
+			data.push(warning(
+				"Tagged template literals are not lowered".to_owned(),
+				position.clone(),
+			));
+			return;
+		}
+
+		let position = position.clone();
+		let mut parts = std::mem::take(parts).into_iter().peekable();
+		let first = match parts.next_if(|part| matches!(part, TemplateLiteralPart::Static(_))) {
+			Some(TemplateLiteralPart::Static(content)) => string(&content, &position),
+			_ => string("", &position),
+		};
+		let arguments = parts
+			.map(|part| match part {
+				TemplateLiteralPart::Static(content) => string(&content, &position),
+				TemplateLiteralPart::Dynamic(expression) => *expression,
+			})
+			.collect::<Vec<_>>();
+		*item = if arguments.is_empty() {
+			first
+		} else {
+			call(member(first, "concat", &position), arguments, &position)
+		};
+	}
+}
+
+/// A string literal from the source of part of a template literal. Escape sequences are the same
+/// in both, but quotes and line breaks need escaping
+fn string(raw: &str, position: &Span) -> Expression {
+	let mut content = String::with_capacity(raw.len());
+	let mut chars = raw.chars().peekable();
+	while let Some(chr) = chars.next() {
+		match chr {
+			'\\' => match chars.next() {
+				// Line continuations are not part of the value
+				Some('\r') => {
+					chars.next_if_eq(&'\n');
+				}
+				Some('\n' | '\u{2028}' | '\u{2029}') => {}
+				escaped => {
+					content.push(chr);
+					content.extend(escaped);
+				}
+			},
+			'"' => content.push_str("\\\""),
+			'\n' => content.push_str("\\n"),
+			// Template literals normalise `\r\n` and `\r` to `\n`
+			'\r' => {
+				chars.next_if_eq(&'\n');
+				content.push_str("\\n");
+			}
+			'\u{2028}' => content.push_str("\\u2028"),
+			'\u{2029}' => content.push_str("\\u2029"),
+			chr => content.push(chr),
+		}
+	}
+	Expression::StringLiteral(content, Quoted::Double, position.clone(), ExpressionId::new())
+}
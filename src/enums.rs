@@ -0,0 +1,313 @@
+//! Replaces members of enums and `as const` objects with their values, keeping the name in a
+//! comment: `Direction.Up` becomes `1 /* Direction.Up */`. Members are evaluated like
+//! [crate::erasure] evaluates `const enum`s, so initializers can use arithmetic, strings and
+//! earlier members, and members without an initializer are auto-incremented.
+//!
+//! Only declarations at the top level of a module are inlined. Members of enums imported from
+//! other modules in the build are inlined using what those modules [exported]. Declarations are
+//! kept, [crate::erasure] removes `const enum`s and imports which are no longer used
+
+use std::{
+	cell::RefCell,
+	collections::{HashMap, HashSet},
+	path::PathBuf,
+	rc::Rc,
+};
+
+use parser::{
+	declarations::{export::Exportable, ExportDeclaration, ImportPart, VariableDeclaration},
+	expressions::{
+		object_literal::ObjectLiteralMember, ExpressionId, MultipleExpression, SpecialOperators,
+	},
+	extractor::ExtractedFunctions,
+	types::enum_declaration::{EnumDeclaration, EnumMember},
+	ASTNode, BlockLikeMut, Chain, Declaration, Decorated, Expression, Module, PropertyKey,
+	PropertyReference, StatementOrDeclaration, TypeReference, VariableField, VariableIdentifier,
+	VisitorsMut,
+};
+
+use crate::{
+	downlevel::parenthesize,
+	erasure::{evaluate, evaluate_members, ConstantEnums, EnumValue},
+	error_handling::TempDiagnostic,
+	minify::scope::Scopes,
+};
+
+/// Enums imported from other modules by local name, with their exported name and members
+pub(crate) type ImportedEnums = HashMap<String, (String, HashMap<String, EnumValue>)>;
+
+/// Visitors for [crate::temp::Transform::InlineEnums]
+pub(crate) fn visitors(imported: ImportedEnums) -> VisitorsMut<Vec<TempDiagnostic>> {
+	let mut inlined =
+		Inlined { enums: ConstantEnums::new(), names: HashMap::new(), shadowed: HashSet::new() };
+	for (local, (name, members)) in imported {
+		if local != name {
+			inlined.names.insert(local.clone(), name);
+		}
+		inlined.enums.insert(local, members);
+	}
+	let inlined = Rc::new(RefCell::new(inlined));
+	VisitorsMut {
+		block_visitors_mut: vec![Box::new(FindDeclarations {
+			inlined: inlined.clone(),
+			is_module: true,
+		})],
+		expression_visitors_mut: vec![Box::new(InlineMembers(inlined))],
+		..Default::default()
+	}
+}
+
+/// Members of the enums and `as const` objects a module exports, by exported name
+pub(crate) fn exported(module: &Module) -> ConstantEnums {
+	let mut enums = ConstantEnums::new();
+	let exported = declarations(&module.items, &mut enums);
+	enums.retain(|name, _| exported.contains(name));
+	enums
+}
+
+/// The enums `items` import from modules in `resolved`
+pub(crate) fn imported(
+	items: &[StatementOrDeclaration],
+	resolved: &HashMap<String, PathBuf>,
+	exported: &HashMap<PathBuf, ConstantEnums>,
+) -> ImportedEnums {
+	let mut enums = ImportedEnums::new();
+	for item in items.iter() {
+		let StatementOrDeclaration::Declaration(Declaration::Import(import)) = item else {
+			continue;
+		};
+		let Some(exported) = resolved.get(&import.from).and_then(|path| exported.get(path)) else {
+			continue;
+		};
+		for part in import.imports.iter().flatten() {
+			let (name, local) = match part {
+				ImportPart::Name(VariableIdentifier::Standard(name, ..)) => (name, name),
+				ImportPart::NameWithAlias { name, alias, .. } => (name, alias),
+				_ => continue,
+			};
+			if let Some(members) = exported.get(name) {
+				enums.insert(local.clone(), (name.clone(), members.clone()));
+			}
+		}
+	}
+	enums
+}
+
+struct Inlined {
+	/// By local name
+	enums: ConstantEnums,
+	/// Names of imported enums which are imported with an alias, for comments
+	names: HashMap<String, String>,
+	/// Start of references to the names in [Inlined::enums] which are to other bindings
+	shadowed: HashSet<u32>,
+}
+
+impl Inlined {
+	/// The value of `E.A` or `E["A"]`
+	fn value(&self, expression: &Expression) -> Option<Expression> {
+		let (object, object_position, member, position) = match expression {
+			Expression::PropertyAccess {
+				parent,
+				property: PropertyReference::Standard(member),
+				position,
+				..
+			} => match parent.as_ref() {
+				Expression::VariableReference(object, object_position, _) => {
+					(object, object_position, member, position)
+				}
+				_ => return None,
+			},
+			Expression::Index { indexee, indexer, position, .. } => {
+				match (indexee.as_ref(), indexer.as_ref()) {
+					(
+						Expression::VariableReference(object, object_position, _),
+						MultipleExpression {
+							lhs: None,
+							rhs: Expression::StringLiteral(member, ..),
+						},
+					) => (object, object_position, member, position),
+					_ => return None,
+				}
+			}
+			_ => return None,
+		};
+		if self.shadowed.contains(&object_position.start) {
+			return None;
+		}
+		let value = self.enums.get(object)?.get(member)?.clone();
+		let name = self.names.get(object).unwrap_or(object);
+		Some(Expression::PostfixComment(
+			Box::new(value.into_expression(position.clone())),
+			format!(" {name}.{member} "),
+			position.clone(),
+			ExpressionId::new(),
+		))
+	}
+}
+
+/// Evaluates the declarations in the module (the first block visited) and finds references which
+/// are shadowed by other declarations
+struct FindDeclarations {
+	inlined: Rc<RefCell<Inlined>>,
+	is_module: bool,
+}
+
+impl<'a> parser::VisitorMut<BlockLikeMut<'a>, Vec<TempDiagnostic>> for FindDeclarations {
+	fn visit_mut(
+		&mut self,
+		item: &mut BlockLikeMut<'a>,
+		_data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		if !std::mem::take(&mut self.is_module) {
+			return;
+		}
+		let inlined = &mut *self.inlined.borrow_mut();
+		declarations(item.items, &mut inlined.enums);
+		if inlined.enums.is_empty() {
+			return;
+		}
+		let scopes = Scopes::new(item.items, functions);
+		inlined.shadowed = scopes
+			.bindings
+			.iter()
+			.filter(|binding| binding.scope != 0 && inlined.enums.contains_key(&binding.name))
+			.flat_map(|binding| binding.references.iter().map(|position| position.start))
+			.collect();
+	}
+}
+
+struct InlineMembers(Rc<RefCell<Inlined>>);
+
+impl parser::VisitorMut<Expression, Vec<TempDiagnostic>> for InlineMembers {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		_data: &mut Vec<TempDiagnostic>,
+		_functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		let inlined = self.0.borrow();
+		if let Some(value) = inlined.value(item) {
+			*item = value;
+			return;
+		}
+		// Parents are visited first. `1.toString()` is not valid, so numbers are parenthesized
+		if let Expression::PropertyAccess { parent, .. } = item {
+			if let Some(value) = inlined.value(parent) {
+				let position = value.get_position().into_owned();
+				**parent = match value {
+					Expression::PostfixComment(ref number, ..)
+						if matches!(**number, Expression::NumberLiteral(..)) =>
+					{
+						parenthesize(value, &position)
+					}
+					value => value,
+				};
+			}
+		}
+	}
+}
+
+/// Evaluates the enums and `as const` objects in `items`, adding their members to `enums`.
+/// Returns the names of those which are exported
+fn declarations(items: &[StatementOrDeclaration], enums: &mut ConstantEnums) -> Vec<String> {
+	let mut exported = Vec::new();
+	for item in items.iter() {
+		let StatementOrDeclaration::Declaration(declaration) = item else { continue };
+		match declaration {
+			Declaration::Enum(Decorated { on: enum_declaration, .. }) => {
+				evaluate_enum(enum_declaration, enums);
+			}
+			Declaration::Export(Decorated {
+				on: ExportDeclaration::Variable { exported: Exportable::Enum(enum_declaration), .. },
+				..
+			}) => {
+				evaluate_enum(enum_declaration, enums);
+				exported.push(enum_declaration.name.clone());
+			}
+			Declaration::Variable(declaration) => {
+				constant_objects(declaration, enums);
+			}
+			Declaration::Export(Decorated {
+				on: ExportDeclaration::Variable { exported: Exportable::Variable(declaration), .. },
+				..
+			}) => {
+				exported.extend(constant_objects(declaration, enums));
+			}
+			_ => {}
+		}
+	}
+	exported
+}
+
+/// Adds the members of an enum which can be evaluated to `enums`
+fn evaluate_enum(enum_declaration: &EnumDeclaration, enums: &mut ConstantEnums) {
+	let EnumDeclaration { name, members, .. } = enum_declaration;
+	let values = evaluate_members(name, members, enums);
+	let members = members
+		.iter()
+		.zip(values)
+		.filter_map(|(EnumMember::Variant { name, .. }, value)| Some((name.clone(), value?)))
+		.collect();
+	enums.insert(name.clone(), members);
+}
+
+/// Adds the members of `const Name = { ... } as const` to `enums`, returning the names. Objects
+/// with spreads or methods are skipped, as which keys they override is not known
+fn constant_objects(declaration: &VariableDeclaration, enums: &mut ConstantEnums) -> Vec<String> {
+	let VariableDeclaration::ConstDeclaration { declarations, .. } = declaration else {
+		return Vec::new();
+	};
+	let mut names = Vec::new();
+	for declaration in declarations.iter() {
+		let VariableField::Name(VariableIdentifier::Standard(name, ..)) =
+			declaration.name.get_ast()
+		else {
+			continue;
+		};
+		let Expression::SpecialOperators(
+			SpecialOperators::AsExpression { value, type_annotation, .. },
+			..,
+		) = &declaration.expression
+		else {
+			continue;
+		};
+		let (Expression::ObjectLiteral(object), TypeReference::Name(annotation, _)) =
+			(value.as_ref(), type_annotation.as_ref())
+		else {
+			continue;
+		};
+		if annotation != "const" {
+			continue;
+		}
+		let mut members = HashMap::new();
+		let mut is_known = true;
+		for member in object.members.iter() {
+			match member {
+				ObjectLiteralMember::Property(key, value, _) => {
+					let key = match key.get_ast() {
+						PropertyKey::Ident(key, ..) | PropertyKey::StringLiteral(key, ..) => key,
+						_ => continue,
+					};
+					match evaluate(value, name, &HashMap::new(), enums) {
+						Some(value) => members.insert(key.clone(), value),
+						None => members.remove(key),
+					};
+				}
+				ObjectLiteralMember::Shorthand(key, ..) => {
+					members.remove(key);
+				}
+				ObjectLiteralMember::Method(_) | ObjectLiteralMember::SpreadExpression(..) => {
+					is_known = false;
+				}
+			}
+		}
+		if is_known {
+			enums.insert(name.clone(), members);
+			names.push(name.clone());
+		}
+	}
+	names
+}
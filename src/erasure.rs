@@ -0,0 +1,705 @@
+//! Lowers TypeScript syntax which has an effect at runtime into JavaScript, for output without
+//! types. Enums become objects built by an IIFE (or are inlined for `const enum`s), parameter
+//! properties become assignments in the constructor, imports only used as types are removed and
+//! ambient (`declare`) and type declarations are dropped.
+//!
+//! Generated nodes use the positions of the nodes they are lowered from, so that source maps
+//! point back to the TypeScript source
+
+use std::{
+	cell::RefCell,
+	collections::{HashMap, HashSet},
+	rc::Rc,
+};
+
+use parser::{
+	declarations::{
+		classes::{ClassConstructorBase, ClassMember},
+		export::Exportable,
+		ClassDeclaration, ExportDeclaration, ImportDeclaration, ImportPart,
+		VariableDeclarationItem,
+	},
+	expressions::{
+		assignments::{LHSOfAssignment, VariableOrPropertyAccess},
+		object_literal::ObjectLiteral,
+		ExpressionFunctionBase, ExpressionId, MultipleExpression, SpreadExpression, SuperReference,
+	},
+	extractor::{ExtractedFunctions, GetFunction},
+	operators::{BinaryOperator, UnaryOperator},
+	statements::VarVariableStatement,
+	types::enum_declaration::{EnumDeclaration, EnumMember},
+	Block, BlockId, BlockLikeMut, Chain, Declaration, Decorated, Expression,
+	ExpressionOrStatementPosition, FunctionBase, FunctionHeader, FunctionId, FunctionParameters,
+	Keyword, NumberStructure, OptionalOrWithDefaultValueParameter, Parameter, PropertyReference,
+	Quoted, Span, Statement, StatementOrDeclaration, VariableField, VariableId, VariableIdentifier,
+	VisitorsMut, WithComment,
+};
+
+use crate::{
+	bundle::ModuleScope,
+	error_handling::{DiagnosticCode, ErrorWarningInfo, TempDiagnostic},
+};
+
+/// Values of the members of enums, by enum name then member name
+pub(crate) type ConstantEnums = HashMap<String, HashMap<String, EnumValue>>;
+
+/// Visitors for [crate::temp::Transform::EraseTypes]
+pub(crate) fn visitors() -> VisitorsMut<Vec<TempDiagnostic>> {
+	let constant_enums = Rc::new(RefCell::new(ConstantEnums::new()));
+	VisitorsMut {
+		block_visitors_mut: vec![Box::new(EraseDeclarations {
+			is_module: true,
+			constant_enums: constant_enums.clone(),
+		})],
+		expression_visitors_mut: vec![Box::new(EraseExpressions(constant_enums))],
+		..Default::default()
+	}
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum EnumValue {
+	Number(f64),
+	String(String),
+}
+
+impl EnumValue {
+	/// Negative numbers are parenthesized, as `-E.A` and `E.A ** 2` would otherwise print
+	/// `--1` and `-1 ** 2`
+	pub(crate) fn into_expression(self, position: Span) -> Expression {
+		match self {
+			EnumValue::Number(value) => {
+				let number = Expression::NumberLiteral(
+					NumberStructure::from(value),
+					position.clone(),
+					ExpressionId::new(),
+				);
+				if value < 0.0 {
+					parenthesize(number, position)
+				} else {
+					number
+				}
+			}
+			EnumValue::String(value) => {
+				Expression::StringLiteral(value, Quoted::Double, position, ExpressionId::new())
+			}
+		}
+	}
+}
+
+/// Lowers declarations in each block. Blocks are visited before the items in them, so the values
+/// of `const enum`s are known before references to them are visited
+struct EraseDeclarations {
+	/// The module is the first block visited
+	is_module: bool,
+	constant_enums: Rc<RefCell<ConstantEnums>>,
+}
+
+impl<'a> parser::VisitorMut<BlockLikeMut<'a>, Vec<TempDiagnostic>> for EraseDeclarations {
+	fn visit_mut(
+		&mut self,
+		item: &mut BlockLikeMut<'a>,
+		data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		if std::mem::take(&mut self.is_module) {
+			elide_imports(item.items, functions);
+		}
+		let items = std::mem::take(item.items);
+		for statement in items {
+			self.erase(statement, item.items, data, functions);
+		}
+	}
+}
+
+impl EraseDeclarations {
+	fn erase(
+		&self,
+		item: StatementOrDeclaration,
+		out: &mut Vec<StatementOrDeclaration>,
+		data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+	) {
+		match item {
+			StatementOrDeclaration::Declaration(
+				Declaration::Interface(_)
+				| Declaration::TypeAlias(_)
+				| Declaration::DeclareVariable(_)
+				| Declaration::DeclareFunction(_)
+				| Declaration::DeclareInterface(_)
+				| Declaration::Export(Decorated {
+					on:
+						ExportDeclaration::Variable {
+							exported: Exportable::Interface(_) | Exportable::TypeAlias(_),
+							..
+						},
+					..
+				}),
+			) => {}
+			StatementOrDeclaration::Declaration(Declaration::Enum(Decorated {
+				on: enum_declaration,
+				..
+			})) => self.lower_enum(enum_declaration, false, out, data, functions),
+			StatementOrDeclaration::Declaration(Declaration::Export(Decorated {
+				on: ExportDeclaration::Variable { exported: Exportable::Enum(enum_declaration), .. },
+				..
+			})) => self.lower_enum(enum_declaration, true, out, data, functions),
+			mut item => {
+				match &mut item {
+					StatementOrDeclaration::Declaration(Declaration::Class(Decorated {
+						on: class,
+						..
+					}))
+					| StatementOrDeclaration::Declaration(Declaration::Export(Decorated {
+						on: ExportDeclaration::Variable { exported: Exportable::Class(class), .. },
+						..
+					})) => lower_parameter_properties(class, functions),
+					_ => {}
+				}
+				out.push(item);
+			}
+		}
+	}
+
+	/// `const enum`s are removed and references to their members inlined. Otherwise (or if a
+	/// member of a `const enum` cannot be evaluated) `enum E { A }` becomes
+	/// `var E; (function (E) { E[E["A"] = 0] = "A"; })(E || (E = {}));`. `const enum`s which are
+	/// exported are also kept for other modules
+	fn lower_enum(
+		&self,
+		enum_declaration: EnumDeclaration,
+		exported: bool,
+		out: &mut Vec<StatementOrDeclaration>,
+		data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+	) {
+		let EnumDeclaration { is_constant, name, mut members, position } = enum_declaration;
+
+		let evaluated = evaluate_members(&name, &members, &self.constant_enums.borrow());
+		let mut values = HashMap::new();
+		for (EnumMember::Variant { name: member, value, position }, result) in
+			members.iter().zip(evaluated.iter())
+		{
+			if let Some(result) = result {
+				values.insert(member.clone(), result.clone());
+			} else if value.is_none() {
+				data.push(enum_diagnostic(
+					format!("Enum member '{member}' must have an initializer"),
+					position.clone(),
+				));
+			}
+		}
+
+		if is_constant {
+			let non_constant =
+				members.iter().zip(evaluated.iter()).find(|(_, value)| value.is_none());
+			if let Some((EnumMember::Variant { name: member, position, .. }, _)) = non_constant {
+				data.push(enum_diagnostic(
+					format!(
+						"Value of '{name}.{member}' is not constant, so '{name}' is not inlined"
+					),
+					position.clone(),
+				));
+			} else {
+				self.constant_enums.borrow_mut().insert(name.clone(), values);
+				if !exported {
+					return;
+				}
+			}
+		}
+
+		let parameter = Span::NULL_SPAN;
+		let statements = members
+			.iter_mut()
+			.zip(evaluated)
+			.map(|(EnumMember::Variant { name: member, value, position }, evaluated)| {
+				let is_string = matches!(evaluated, Some(EnumValue::String(_)));
+				let value = match (evaluated, value.take()) {
+					(Some(evaluated), _) => evaluated.into_expression(position.clone()),
+					(None, Some(value)) => value,
+					(None, None) => Expression::UnaryOperation {
+						operator: UnaryOperator::Void,
+						operand: Box::new(Expression::NumberLiteral(
+							NumberStructure::Number(0.),
+							position.clone(),
+							ExpressionId::new(),
+						)),
+						position: position.clone(),
+						id: ExpressionId::new(),
+					},
+				};
+				let key = Expression::StringLiteral(
+					member.clone(),
+					Quoted::Double,
+					position.clone(),
+					ExpressionId::new(),
+				);
+				// `E["A"] = value`
+				let assignment = index_assignment(&name, &parameter, key, value, position.clone());
+				let expression = if is_string {
+					assignment
+				} else {
+					// Also map the value back to the name, `E[E["A"] = 0] = "A"`
+					let reverse = Expression::StringLiteral(
+						member.clone(),
+						Quoted::Double,
+						position.clone(),
+						ExpressionId::new(),
+					);
+					index_assignment(&name, &parameter, assignment, reverse, position.clone())
+				};
+				StatementOrDeclaration::Statement(Statement::Expression(expression.into()))
+			})
+			.collect();
+
+		let declaration = VariableDeclarationItem {
+			name: WithComment::None(VariableField::Name(VariableIdentifier::Standard(
+				name.clone(),
+				VariableId::new(),
+				position.clone(),
+			))),
+			type_reference: None,
+			expression: None,
+		};
+		let statement = VarVariableStatement {
+			keyword: Keyword::new(position.clone()),
+			declarations: vec![declaration],
+		};
+		out.push(if exported {
+			StatementOrDeclaration::Declaration(Declaration::Export(Decorated {
+				decorators: Vec::new(),
+				on: ExportDeclaration::Variable {
+					exported: Exportable::VarVariable(statement),
+					position: position.clone(),
+				},
+			}))
+		} else {
+			StatementOrDeclaration::Statement(Statement::VarVariable(statement))
+		});
+
+		let function = FunctionBase::<ExpressionFunctionBase> {
+			function_id: FunctionId::new(),
+			header: FunctionHeader::VirginFunctionHeader {
+				async_keyword: None,
+				function_keyword: Keyword::new(position.clone()),
+				generator_star_token_pos: None,
+			},
+			name: None,
+			type_parameters: None,
+			parameters: FunctionParameters {
+				parameters: vec![Parameter {
+					name: WithComment::None(VariableField::Name(VariableIdentifier::Standard(
+						name.clone(),
+						VariableId::new(),
+						parameter,
+					))),
+					type_reference: None,
+					property: None,
+				}],
+				optional_parameters: Vec::new(),
+				rest_parameter: None,
+				position: Span::NULL_SPAN,
+			},
+			return_type: None,
+			body: Block(statements, BlockId::new(), position.clone()),
+		};
+		let function =
+			Expression::ExtractedExpressionFunction(functions.new_extracted_function(function));
+		// `E || (E = {})`
+		let argument = Expression::BinaryOperation {
+			lhs: Box::new(Expression::VariableReference(
+				name.clone(),
+				position.clone(),
+				ExpressionId::new(),
+			)),
+			operator: BinaryOperator::LogicalOr,
+			rhs: Box::new(parenthesize(
+				Expression::Assignment {
+					lhs: LHSOfAssignment::VariableOrPropertyAccess(
+						VariableOrPropertyAccess::Variable(
+							name.clone(),
+							position.clone(),
+							ExpressionId::new(),
+						),
+					),
+					rhs: Box::new(Expression::ObjectLiteral(ObjectLiteral {
+						members: Vec::new(),
+						position: position.clone(),
+						expression_id: ExpressionId::new(),
+					})),
+					id: ExpressionId::new(),
+				},
+				position.clone(),
+			)),
+			id: ExpressionId::new(),
+		};
+		let call = Expression::FunctionCall {
+			function: Box::new(parenthesize(function, position.clone())),
+			type_arguments: None,
+			arguments: vec![SpreadExpression::NonSpread(argument)],
+			position: position.clone(),
+			expression_id: ExpressionId::new(),
+		};
+		out.push(StatementOrDeclaration::Statement(Statement::Expression(call.into())));
+	}
+}
+
+/// Inlines members of `const enum`s and lowers parameter properties of class expressions
+struct EraseExpressions(Rc<RefCell<ConstantEnums>>);
+
+impl parser::VisitorMut<Expression, Vec<TempDiagnostic>> for EraseExpressions {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		_data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		if let Expression::ClassExpression(class, _) = item {
+			lower_parameter_properties(class, functions);
+			return;
+		}
+		let (object, member, position) = match &*item {
+			Expression::PropertyAccess {
+				parent,
+				property: PropertyReference::Standard(member),
+				position,
+				..
+			} => match parent.as_ref() {
+				Expression::VariableReference(object, ..) => (object, member, position),
+				_ => return,
+			},
+			Expression::Index { indexee, indexer, position, .. } => {
+				match (indexee.as_ref(), indexer.as_ref()) {
+					(
+						Expression::VariableReference(object, ..),
+						MultipleExpression {
+							lhs: None,
+							rhs: Expression::StringLiteral(member, ..),
+						},
+					) => (object, member, position),
+					_ => return,
+				}
+			}
+			_ => return,
+		};
+		let value = self.0.borrow().get(object).and_then(|members| members.get(member)).cloned();
+		if let Some(value) = value {
+			*item = value.into_expression(position.clone());
+		}
+	}
+}
+
+/// Removes imports which are only used as types: `import type`, `type` specifiers and names
+/// which are not referenced as values. Imports without any names (for side effects) are kept
+fn elide_imports(items: &mut Vec<StatementOrDeclaration>, functions: &ExtractedFunctions) {
+	let used = used_imports(items, functions);
+	items.retain_mut(|item| {
+		let StatementOrDeclaration::Declaration(Declaration::Import(import)) = item else {
+			return true;
+		};
+		if is_elided(import, &used) {
+			return false;
+		}
+		if !has_names(import) {
+			return true;
+		}
+		if !import.default_import.as_ref().is_some_and(|name| used.contains(name)) {
+			import.default_import = None;
+		}
+		if let Some(parts) = &mut import.imports {
+			parts.retain(|part| local_name(part).is_some_and(|name| used.contains(name)));
+			if parts.is_empty() {
+				import.imports = None;
+			}
+		}
+		true
+	});
+}
+
+/// Imported names which are referenced as values
+pub(crate) fn used_imports(
+	items: &[StatementOrDeclaration],
+	functions: &ExtractedFunctions,
+) -> HashSet<String> {
+	let mut imported = Vec::new();
+	for item in items.iter() {
+		let StatementOrDeclaration::Declaration(Declaration::Import(import)) = item else {
+			continue;
+		};
+		imported.extend(import.default_import.clone());
+		imported.extend(import.imports.iter().flatten().filter_map(local_name).map(str::to_owned));
+	}
+	if imported.is_empty() {
+		return HashSet::new();
+	}
+
+	let scope = ModuleScope::new(items, imported.iter().cloned(), functions);
+	imported
+		.into_iter()
+		// Elements reference the factory of the classic JSX runtime
+		.filter(|name| {
+			(scope.jsx && name == "React") || scope.occurrences.iter().any(|(used, _)| used == name)
+		})
+		.collect()
+}
+
+/// Whether [elide_imports] removes all of `import`, given the names from [used_imports]
+pub(crate) fn is_elided(import: &ImportDeclaration, used: &HashSet<String>) -> bool {
+	import.only_type
+		|| (has_names(import)
+			&& !import.default_import.as_ref().is_some_and(|name| used.contains(name))
+			&& !import
+				.imports
+				.iter()
+				.flatten()
+				.any(|part| local_name(part).is_some_and(|name| used.contains(name))))
+}
+
+fn has_names(import: &ImportDeclaration) -> bool {
+	import.default_import.is_some()
+		|| import.imports.as_ref().is_some_and(|parts| !parts.is_empty())
+}
+
+/// The name an import part declares in the module. `None` for `type` specifiers
+pub(crate) fn local_name(part: &ImportPart) -> Option<&str> {
+	match part {
+		ImportPart::Name(VariableIdentifier::Standard(name, ..))
+		| ImportPart::NameWithAlias { alias: name, .. } => Some(name),
+		ImportPart::Name(VariableIdentifier::Cursor(_)) | ImportPart::TypeOnly(..) => None,
+	}
+}
+
+/// `constructor(private x)` to `constructor(x) { this.x = x; }`. The assignments are after the
+/// `super(...)` call in classes which extend another
+fn lower_parameter_properties<T: ExpressionOrStatementPosition>(
+	class: &mut ClassDeclaration<T>,
+	functions: &mut ExtractedFunctions,
+) {
+	for member in class.members.iter() {
+		let ClassMember::Constructor(constructor) = &member.on else { continue };
+		let mut constructor =
+			GetFunction::<ClassConstructorBase>::get_function(functions, constructor.0);
+		let FunctionParameters { parameters, optional_parameters, .. } =
+			&mut constructor.parameters;
+
+		let mut assignments = Vec::new();
+		let mut assign = |property: bool, name: &VariableIdentifier| {
+			if let (true, VariableIdentifier::Standard(name, _, position)) = (property, name) {
+				assignments.push(this_assignment(name, position));
+			}
+		};
+		for parameter in parameters.iter_mut() {
+			if let VariableField::Name(name) = parameter.name.get_ast() {
+				assign(parameter.property.take().is_some(), name);
+			}
+		}
+		for parameter in optional_parameters.iter_mut() {
+			match parameter {
+				OptionalOrWithDefaultValueParameter::Optional { name, property, .. } => {
+					assign(property.take().is_some(), name);
+				}
+				OptionalOrWithDefaultValueParameter::WithDefaultValue {
+					name, property, ..
+				} => {
+					if let VariableField::Name(name) = name.get_ast() {
+						assign(property.take().is_some(), name);
+					}
+				}
+			}
+		}
+
+		if !assignments.is_empty() {
+			let body = &mut constructor.body.0;
+			let index = if class.extends.is_some() {
+				body.iter().position(is_super_call).map_or(0, |index| index + 1)
+			} else {
+				0
+			};
+			body.splice(index..index, assignments);
+		}
+		functions.insert_function(constructor);
+	}
+}
+
+fn is_super_call(item: &StatementOrDeclaration) -> bool {
+	matches!(
+		item,
+		StatementOrDeclaration::Statement(Statement::Expression(MultipleExpression {
+			lhs: None,
+			rhs: Expression::SuperExpression(SuperReference::Call { .. }, ..)
+		}))
+	)
+}
+
+/// `this.name = name;`
+fn this_assignment(name: &str, position: &Span) -> StatementOrDeclaration {
+	let lhs = VariableOrPropertyAccess::PropertyAccess {
+		parent: Box::new(Expression::ThisReference(position.clone(), ExpressionId::new())),
+		property: PropertyReference::Standard(name.to_owned()),
+		position: position.clone(),
+		expression_id: ExpressionId::new(),
+	};
+	let assignment = Expression::Assignment {
+		lhs: LHSOfAssignment::VariableOrPropertyAccess(lhs),
+		rhs: Box::new(Expression::VariableReference(
+			name.to_owned(),
+			position.clone(),
+			ExpressionId::new(),
+		)),
+		id: ExpressionId::new(),
+	};
+	StatementOrDeclaration::Statement(Statement::Expression(assignment.into()))
+}
+
+/// `object[key] = value`
+fn index_assignment(
+	object: &str,
+	object_position: &Span,
+	key: Expression,
+	value: Expression,
+	position: Span,
+) -> Expression {
+	let lhs = VariableOrPropertyAccess::Index {
+		indexee: Box::new(Expression::VariableReference(
+			object.to_owned(),
+			object_position.clone(),
+			ExpressionId::new(),
+		)),
+		indexer: Box::new(key.into()),
+		position,
+		expression_id: ExpressionId::new(),
+	};
+	Expression::Assignment {
+		lhs: LHSOfAssignment::VariableOrPropertyAccess(lhs),
+		rhs: Box::new(value),
+		id: ExpressionId::new(),
+	}
+}
+
+fn parenthesize(expression: Expression, position: Span) -> Expression {
+	Expression::ParenthesizedExpression(Box::new(expression.into()), position, ExpressionId::new())
+}
+
+fn enum_diagnostic(label: String, position: Span) -> TempDiagnostic {
+	TempDiagnostic {
+		label,
+		position,
+		kind: ErrorWarningInfo::Warning,
+		code: DiagnosticCode::NonConstantEnumMember,
+		secondary_labels: Vec::new(),
+		notes: Vec::new(),
+	}
+}
+
+/// Evaluates the members of an enum in order. Members without an initializer are one more than
+/// the previous member (or zero if first). `None` for members which cannot be evaluated
+pub(crate) fn evaluate_members(
+	enum_name: &str,
+	members: &[EnumMember],
+	constant_enums: &ConstantEnums,
+) -> Vec<Option<EnumValue>> {
+	let mut values = HashMap::new();
+	let mut next = Some(0.);
+	members
+		.iter()
+		.map(|EnumMember::Variant { name: member, value, .. }| {
+			let result = match value {
+				Some(value) => evaluate(value, enum_name, &values, constant_enums),
+				None => next.map(EnumValue::Number),
+			};
+			next = match result {
+				Some(EnumValue::Number(value)) => Some(value + 1.),
+				_ => None,
+			};
+			if let Some(result) = &result {
+				values.insert(member.clone(), result.clone());
+			}
+			result
+		})
+		.collect()
+}
+
+/// Evaluates an enum member initializer. Supports literals, arithmetic and bitwise operators,
+/// string concatenation and references to earlier members and members of other `const enum`s
+pub(crate) fn evaluate(
+	expression: &Expression,
+	enum_name: &str,
+	members: &HashMap<String, EnumValue>,
+	constant_enums: &ConstantEnums,
+) -> Option<EnumValue> {
+	let evaluate = |expression| evaluate(expression, enum_name, members, constant_enums);
+	match expression {
+		Expression::NumberLiteral(value, ..) => Some(EnumValue::Number(f64::from(*value))),
+		Expression::StringLiteral(value, ..) => Some(EnumValue::String(value.clone())),
+		Expression::ParenthesizedExpression(inner, ..) if inner.lhs.is_none() => {
+			evaluate(&inner.rhs)
+		}
+		Expression::VariableReference(name, ..) => members.get(name).cloned(),
+		Expression::PropertyAccess {
+			parent,
+			property: PropertyReference::Standard(member),
+			..
+		} => match parent.as_ref() {
+			Expression::VariableReference(name, ..) if name == enum_name => {
+				members.get(member).cloned()
+			}
+			Expression::VariableReference(name, ..) => {
+				constant_enums.get(name).and_then(|members| members.get(member)).cloned()
+			}
+			_ => None,
+		},
+		Expression::UnaryOperation { operator, operand, .. } => {
+			let EnumValue::Number(value) = evaluate(operand)? else { return None };
+			let value = match operator {
+				UnaryOperator::Plus => value,
+				UnaryOperator::Negation => -value,
+				UnaryOperator::BitwiseNot => f64::from(!to_int32(value)),
+				_ => return None,
+			};
+			Some(EnumValue::Number(value))
+		}
+		Expression::BinaryOperation { lhs, operator, rhs, .. } => {
+			match (evaluate(lhs)?, evaluate(rhs)?) {
+				(EnumValue::String(lhs), EnumValue::String(rhs))
+					if *operator == BinaryOperator::Add =>
+				{
+					Some(EnumValue::String(lhs + &rhs))
+				}
+				(EnumValue::Number(lhs), EnumValue::Number(rhs)) => {
+					// Shifts only use the lower five bits of the right hand side
+					let shift = to_int32(rhs) as u32 & 0x1f;
+					let value = match operator {
+						BinaryOperator::Add => lhs + rhs,
+						BinaryOperator::Subtract => lhs - rhs,
+						BinaryOperator::Multiply => lhs * rhs,
+						BinaryOperator::Divide => lhs / rhs,
+						BinaryOperator::Modulo => lhs % rhs,
+						BinaryOperator::Exponent => lhs.powf(rhs),
+						BinaryOperator::BitwiseShiftLeft => f64::from(to_int32(lhs) << shift),
+						BinaryOperator::BitwiseShiftRight => f64::from(to_int32(lhs) >> shift),
+						BinaryOperator::BitwiseShiftRightUnsigned => {
+							f64::from(to_int32(lhs) as u32 >> shift)
+						}
+						BinaryOperator::BitwiseAnd => f64::from(to_int32(lhs) & to_int32(rhs)),
+						BinaryOperator::BitwiseXOr => f64::from(to_int32(lhs) ^ to_int32(rhs)),
+						BinaryOperator::BitwiseOr => f64::from(to_int32(lhs) | to_int32(rhs)),
+						_ => return None,
+					};
+					Some(EnumValue::Number(value))
+				}
+				_ => None,
+			}
+		}
+		_ => None,
+	}
+}
+
+/// [ToInt32](https://tc39.es/ecma262/#sec-toint32)
+fn to_int32(value: f64) -> i32 {
+	if value.is_finite() {
+		value.trunc().rem_euclid(4294967296.) as u32 as i32
+	} else {
+		0
+	}
+}
@@ -6,12 +6,108 @@ use codespan_reporting::{
 };
 use parser::{source_map::FileSystem, Span};
 
+use crate::{json::JSONValue, utilities::print_to_cli};
+
 /// This actually exists in the checker
 #[cfg_attr(target_family = "wasm", derive(serde::Serialize))]
 pub struct TempDiagnostic {
 	pub label: String,
 	pub position: Span,
 	pub kind: ErrorWarningInfo,
+	pub code: DiagnosticCode,
+	/// Other positions relevant to the diagnostic, with a message
+	pub secondary_labels: Vec<(Span, String)>,
+	/// Help and other information
+	pub notes: Vec<String>,
+}
+
+impl TempDiagnostic {
+	pub(crate) fn from_parse_error(error: parser::ParseError, kind: ErrorWarningInfo) -> Self {
+		TempDiagnostic {
+			code: DiagnosticCode::Parse(error.code()),
+			notes: error.help().into_iter().map(|help| format!("help: {help}")).collect(),
+			label: error.reason,
+			position: error.position,
+			kind,
+			secondary_labels: error.labels,
+		}
+	}
+}
+
+/// Stable identifiers for diagnostics, so that tools can refer to them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(target_family = "wasm", derive(serde::Serialize))]
+pub enum DiagnosticCode {
+	/// From [parser::ParseErrorKind::code]
+	Parse(&'static str),
+	CannotResolve,
+	MissingExport,
+	/// Imports which cannot be included in the chosen bundle format
+	UnsupportedBundleImport,
+	TernarySwapped,
+	/// Enum member which needs an initializer or a `const enum` member which cannot be evaluated
+	NonConstantEnumMember,
+	/// Syntax which is kept as it cannot be lowered to the `--target`
+	UnsupportedTarget,
+	/// Decorator on something other than a class or class method, accessor or field
+	InvalidDecorator,
+	/// Rewrite which overlaps an earlier rewrite in the same file, so is skipped
+	OverlappingRewrite,
+	/// `let`, `const`, `class` or `function` redeclared in the same block
+	DuplicateDeclaration,
+	ReturnOutsideFunction,
+	/// `break` outside of a loop or switch, or `continue` outside of a loop
+	InvalidBreakOrContinue,
+	UnknownLabel,
+	/// A panic while checking a file
+	InternalError,
+	Lint(crate::check::Lint),
+	/// From a pass registered with [crate::pipeline::Pipeline::register], with its own code
+	Pass(&'static str),
+}
+
+impl DiagnosticCode {
+	pub fn code(self) -> &'static str {
+		match self {
+			DiagnosticCode::Parse(code) => code,
+			DiagnosticCode::CannotResolve => "E2001",
+			DiagnosticCode::MissingExport => "E2002",
+			DiagnosticCode::UnsupportedBundleImport => "E2003",
+			DiagnosticCode::TernarySwapped => "I0001",
+			DiagnosticCode::NonConstantEnumMember => "W0001",
+			DiagnosticCode::UnsupportedTarget => "W0002",
+			DiagnosticCode::InvalidDecorator => "W0003",
+			DiagnosticCode::OverlappingRewrite => "W0004",
+			DiagnosticCode::DuplicateDeclaration => "E3001",
+			DiagnosticCode::ReturnOutsideFunction => "E3002",
+			DiagnosticCode::InvalidBreakOrContinue => "E3003",
+			DiagnosticCode::UnknownLabel => "E3004",
+			DiagnosticCode::InternalError => "E9001",
+			DiagnosticCode::Lint(lint) => lint.code(),
+			DiagnosticCode::Pass(code) => code,
+		}
+	}
+
+	pub fn description(self) -> &'static str {
+		match self {
+			DiagnosticCode::Parse(_) => "Syntax error",
+			DiagnosticCode::CannotResolve => "Module could not be resolved",
+			DiagnosticCode::MissingExport => "Imported name is not exported",
+			DiagnosticCode::UnsupportedBundleImport => "Import is not supported in bundle format",
+			DiagnosticCode::TernarySwapped => "Ternary branches swapped",
+			DiagnosticCode::NonConstantEnumMember => "Enum member value is not constant",
+			DiagnosticCode::UnsupportedTarget => "Syntax cannot be lowered to the target",
+			DiagnosticCode::InvalidDecorator => "Decorator cannot be applied here",
+			DiagnosticCode::OverlappingRewrite => "Rewrite overlaps an earlier rewrite",
+			DiagnosticCode::DuplicateDeclaration => "Name is already declared in this block",
+			DiagnosticCode::ReturnOutsideFunction => "Return statement outside of a function",
+			DiagnosticCode::InvalidBreakOrContinue => "Break or continue outside of a loop",
+			DiagnosticCode::UnknownLabel => "Label is not defined",
+			DiagnosticCode::InternalError => "Internal error",
+			DiagnosticCode::Lint(lint) => lint.description(),
+			DiagnosticCode::Pass(_) => "Reported by a registered pass",
+		}
+	}
 }
 
 #[allow(unused)]
@@ -29,6 +125,10 @@ pub(crate) fn emit_ezno_diagnostic(
 	let reason = error.label;
 	let id = error.position.source_id;
 	let range = std::ops::Range::from(error.position);
+	let mut labels = vec![Label::primary(id, range).with_message(reason)];
+	labels.extend(error.secondary_labels.into_iter().map(|(position, message)| {
+		Label::secondary(position.source_id, std::ops::Range::from(position)).with_message(message)
+	}));
 
 	let diagnostic = Diagnostic {
 		severity: match error.kind {
@@ -36,10 +136,10 @@ pub(crate) fn emit_ezno_diagnostic(
 			ErrorWarningInfo::Warning => Severity::Warning,
 			ErrorWarningInfo::Info => Severity::Note,
 		},
-		code: None,
+		code: Some(error.code.code().to_owned()),
 		message: Default::default(),
-		labels: vec![Label::primary(id, range).with_message(reason)],
-		notes: Vec::default(),
+		labels,
+		notes: error.notes,
 	};
 
 	emit_diagnostic(&fs.into_code_span_store(), &diagnostic)
@@ -52,17 +152,16 @@ pub(crate) fn emit_parser_error(
 	use codespan_reporting::files::SimpleFile;
 
 	let simple_file = SimpleFile::new("INPUT", source);
-	let label =
-		Label::primary((), std::ops::Range::from(error.position)).with_message(error.reason);
+	let code = Some(error.code().to_owned());
+	let notes = error.help().into_iter().map(|help| format!("help: {help}")).collect();
+	let mut labels =
+		vec![Label::primary((), std::ops::Range::from(error.position)).with_message(error.reason)];
+	labels.extend(error.labels.into_iter().map(|(position, message)| {
+		Label::secondary((), std::ops::Range::from(position)).with_message(message)
+	}));
 	emit_diagnostic(
 		&simple_file,
-		&Diagnostic {
-			severity: Severity::Error,
-			code: None,
-			message: Default::default(),
-			labels: vec![label],
-			notes: Vec::default(),
-		},
+		&Diagnostic { severity: Severity::Error, code, message: Default::default(), labels, notes },
 	)
 }
 
@@ -96,3 +195,272 @@ fn emit_diagnostic<'files, F: Files<'files>>(
 	let mut lock = writer.lock();
 	emit(&mut lock, &config, files, diagnostic)
 }
+
+/// How diagnostics are printed
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DiagnosticsFormat {
+	/// Human readable with source excerpts
+	#[default]
+	Text,
+	/// An array of diagnostics
+	Json,
+	/// [SARIF 2.1.0](https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html) log
+	Sarif,
+	/// GitHub Actions workflow annotations
+	Github,
+}
+
+impl std::str::FromStr for DiagnosticsFormat {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"text" => Ok(DiagnosticsFormat::Text),
+			"json" => Ok(DiagnosticsFormat::Json),
+			"sarif" => Ok(DiagnosticsFormat::Sarif),
+			"github" => Ok(DiagnosticsFormat::Github),
+			s => Err(format!(
+				"Unknown diagnostics format '{s}', expected 'text', 'json', 'sarif' or 'github'"
+			)),
+		}
+	}
+}
+
+/// One based line and columns of a position in a file
+struct LineColumn {
+	line: usize,
+	/// In UTF-8 bytes
+	column: usize,
+	/// In UTF-16 code units (as used by JavaScript and LSP)
+	utf16_column: usize,
+}
+
+impl LineColumn {
+	fn new(source: &str, offset: usize) -> Self {
+		let offset = offset.min(source.len());
+		let before = &source[..offset];
+		let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+		// Offsets should be on a character boundary but in case they are not
+		let line = before.get(line_start..).unwrap_or_default();
+		LineColumn {
+			line: before.matches('\n').count() + 1,
+			column: offset - line_start + 1,
+			utf16_column: line.encode_utf16().count() + 1,
+		}
+	}
+}
+
+/// A diagnostic with the information other tools need
+struct LocatedDiagnostic {
+	diagnostic: TempDiagnostic,
+	path: String,
+	start: LineColumn,
+	end: LineColumn,
+}
+
+impl LocatedDiagnostic {
+	fn new(fs: &impl FileSystem, diagnostic: TempDiagnostic) -> Self {
+		let (path, content) = fs.get_file_path_and_content(diagnostic.position.source_id);
+		let start = LineColumn::new(&content, diagnostic.position.start as usize);
+		let end = LineColumn::new(&content, diagnostic.position.end as usize);
+		let path = path.display().to_string().replace('\\', "/");
+		Self { diagnostic, path, start, end }
+	}
+
+	fn severity(&self) -> &'static str {
+		match self.diagnostic.kind {
+			ErrorWarningInfo::Error => "error",
+			ErrorWarningInfo::Warning => "warning",
+			ErrorWarningInfo::Info => "info",
+		}
+	}
+
+	fn to_json(&self) -> JSONValue {
+		fn position(position: &LineColumn) -> JSONValue {
+			JSONValue::Object(vec![
+				("line".to_owned(), JSONValue::Number(position.line as f64)),
+				("column".to_owned(), JSONValue::Number(position.column as f64)),
+				("utf16Column".to_owned(), JSONValue::Number(position.utf16_column as f64)),
+			])
+		}
+
+		let Span { start, end, .. } = self.diagnostic.position;
+		JSONValue::Object(vec![
+			("severity".to_owned(), JSONValue::String(self.severity().to_owned())),
+			("code".to_owned(), JSONValue::String(self.diagnostic.code.code().to_owned())),
+			("message".to_owned(), JSONValue::String(self.diagnostic.label.clone())),
+			("file".to_owned(), JSONValue::String(self.path.clone())),
+			(
+				"range".to_owned(),
+				JSONValue::Object(vec![
+					("start".to_owned(), JSONValue::Number(start.into())),
+					("end".to_owned(), JSONValue::Number(end.into())),
+				]),
+			),
+			("start".to_owned(), position(&self.start)),
+			("end".to_owned(), position(&self.end)),
+			(
+				"labels".to_owned(),
+				JSONValue::Array(
+					self.diagnostic
+						.secondary_labels
+						.iter()
+						.map(|(position, message)| {
+							JSONValue::Object(vec![
+								("message".to_owned(), JSONValue::String(message.clone())),
+								("start".to_owned(), JSONValue::Number(position.start.into())),
+								("end".to_owned(), JSONValue::Number(position.end.into())),
+							])
+						})
+						.collect(),
+				),
+			),
+			(
+				"notes".to_owned(),
+				JSONValue::Array(
+					self.diagnostic.notes.iter().cloned().map(JSONValue::String).collect(),
+				),
+			),
+		])
+	}
+
+	fn to_sarif_result(&self) -> JSONValue {
+		let level = match self.diagnostic.kind {
+			ErrorWarningInfo::Error => "error",
+			ErrorWarningInfo::Warning => "warning",
+			ErrorWarningInfo::Info => "note",
+		};
+		let Span { start, end, .. } = self.diagnostic.position;
+		// SARIF columns are in UTF-16 code units by default
+		let region = JSONValue::Object(vec![
+			("startLine".to_owned(), JSONValue::Number(self.start.line as f64)),
+			("startColumn".to_owned(), JSONValue::Number(self.start.utf16_column as f64)),
+			("endLine".to_owned(), JSONValue::Number(self.end.line as f64)),
+			("endColumn".to_owned(), JSONValue::Number(self.end.utf16_column as f64)),
+			("byteOffset".to_owned(), JSONValue::Number(start.into())),
+			("byteLength".to_owned(), JSONValue::Number(end.saturating_sub(start).into())),
+		]);
+		let physical_location = JSONValue::Object(vec![
+			(
+				"artifactLocation".to_owned(),
+				JSONValue::Object(vec![("uri".to_owned(), JSONValue::String(self.path.clone()))]),
+			),
+			("region".to_owned(), region),
+		]);
+		JSONValue::Object(vec![
+			("ruleId".to_owned(), JSONValue::String(self.diagnostic.code.code().to_owned())),
+			("level".to_owned(), JSONValue::String(level.to_owned())),
+			(
+				"message".to_owned(),
+				JSONValue::Object(vec![(
+					"text".to_owned(),
+					JSONValue::String(self.diagnostic.label.clone()),
+				)]),
+			),
+			(
+				"locations".to_owned(),
+				JSONValue::Array(vec![JSONValue::Object(vec![(
+					"physicalLocation".to_owned(),
+					physical_location,
+				)])]),
+			),
+		])
+	}
+
+	/// <https://docs.github.com/en/actions/using-workflows/workflow-commands-for-github-actions>
+	fn to_github_annotation(&self) -> String {
+		fn escape_data(value: &str) -> String {
+			value.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+		}
+		fn escape_property(value: &str) -> String {
+			escape_data(value).replace(':', "%3A").replace(',', "%2C")
+		}
+
+		let command = match self.diagnostic.kind {
+			ErrorWarningInfo::Error => "error",
+			ErrorWarningInfo::Warning => "warning",
+			ErrorWarningInfo::Info => "notice",
+		};
+		format!(
+			"::{command} file={},line={},col={},endLine={},endColumn={},title={}::{}",
+			escape_property(&self.path),
+			self.start.line,
+			self.start.utf16_column,
+			self.end.line,
+			self.end.utf16_column,
+			escape_property(self.diagnostic.code.code()),
+			escape_data(&self.diagnostic.label)
+		)
+	}
+}
+
+/// Prints diagnostics in `format`. Formats other than [DiagnosticsFormat::Text] are printed to
+/// stdout so they can be piped into other tools
+pub(crate) fn emit_diagnostics(
+	fs: &impl FileSystem,
+	diagnostics: Vec<TempDiagnostic>,
+	format: DiagnosticsFormat,
+) {
+	let located = |diagnostics: Vec<TempDiagnostic>| {
+		diagnostics.into_iter().map(|diagnostic| LocatedDiagnostic::new(fs, diagnostic))
+	};
+	match format {
+		DiagnosticsFormat::Text => {
+			for diagnostic in diagnostics {
+				emit_ezno_diagnostic(fs, diagnostic).unwrap();
+			}
+		}
+		DiagnosticsFormat::Json => {
+			let diagnostics = located(diagnostics).map(|diagnostic| diagnostic.to_json());
+			print_to_cli(format_args!("{}", JSONValue::Array(diagnostics.collect())));
+		}
+		DiagnosticsFormat::Sarif => {
+			let located = located(diagnostics).collect::<Vec<_>>();
+			let mut codes = Vec::new();
+			for diagnostic in located.iter() {
+				if !codes.contains(&diagnostic.diagnostic.code) {
+					codes.push(diagnostic.diagnostic.code);
+				}
+			}
+			let rules = codes
+				.iter()
+				.map(|code| {
+					JSONValue::Object(vec![
+						("id".to_owned(), JSONValue::String(code.code().to_owned())),
+						(
+							"shortDescription".to_owned(),
+							JSONValue::Object(vec![(
+								"text".to_owned(),
+								JSONValue::String(code.description().to_owned()),
+							)]),
+						),
+					])
+				})
+				.collect();
+			let driver = JSONValue::Object(vec![
+				("name".to_owned(), JSONValue::String(env!("CARGO_PKG_NAME").to_owned())),
+				("version".to_owned(), JSONValue::String(env!("CARGO_PKG_VERSION").to_owned())),
+				("rules".to_owned(), JSONValue::Array(rules)),
+			]);
+			let results = located.iter().map(LocatedDiagnostic::to_sarif_result);
+			let run = JSONValue::Object(vec![
+				("tool".to_owned(), JSONValue::Object(vec![("driver".to_owned(), driver)])),
+				("results".to_owned(), JSONValue::Array(results.collect())),
+			]);
+			let log = JSONValue::Object(vec![
+				("version".to_owned(), JSONValue::String("2.1.0".to_owned())),
+				(
+					"$schema".to_owned(),
+					JSONValue::String("https://json.schemastore.org/sarif-2.1.0.json".to_owned()),
+				),
+				("runs".to_owned(), JSONValue::Array(vec![run])),
+			]);
+			print_to_cli(format_args!("{log}"));
+		}
+		DiagnosticsFormat::Github => {
+			for diagnostic in located(diagnostics) {
+				print_to_cli(format_args!("{}", diagnostic.to_github_annotation()));
+			}
+		}
+	}
+}
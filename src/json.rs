@@ -0,0 +1,251 @@
+//! A small JSON parser for configuration files (`package.json`, `tsconfig.json` etc).
+//!
+//! Comments and trailing commas are allowed as `tsconfig.json` uses them. Object keys keep their
+//! order as it is significant for `exports` conditions in `package.json`.
+
+use std::{
+	fmt::{self, Write},
+	iter::Peekable,
+	str::CharIndices,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JSONValue {
+	Null,
+	Boolean(bool),
+	Number(f64),
+	String(String),
+	Array(Vec<JSONValue>),
+	Object(Vec<(String, JSONValue)>),
+}
+
+impl JSONValue {
+	/// Value of `key` if this is an object with it
+	pub fn get(&self, key: &str) -> Option<&JSONValue> {
+		match self {
+			JSONValue::Object(entries) => {
+				entries.iter().find_map(|(k, value)| (k == key).then_some(value))
+			}
+			_ => None,
+		}
+	}
+
+	pub fn as_str(&self) -> Option<&str> {
+		match self {
+			JSONValue::String(value) => Some(value),
+			_ => None,
+		}
+	}
+}
+
+impl fmt::Display for JSONValue {
+	/// Compact JSON
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			JSONValue::Null => f.write_str("null"),
+			JSONValue::Boolean(value) => write!(f, "{value}"),
+			JSONValue::Number(value) if value.is_finite() => write!(f, "{value}"),
+			JSONValue::Number(_) => f.write_str("null"),
+			JSONValue::String(value) => write_string(f, value),
+			JSONValue::Array(items) => {
+				f.write_char('[')?;
+				for (index, item) in items.iter().enumerate() {
+					if index > 0 {
+						f.write_char(',')?;
+					}
+					write!(f, "{item}")?;
+				}
+				f.write_char(']')
+			}
+			JSONValue::Object(entries) => {
+				f.write_char('{')?;
+				for (index, (key, value)) in entries.iter().enumerate() {
+					if index > 0 {
+						f.write_char(',')?;
+					}
+					write_string(f, key)?;
+					write!(f, ":{value}")?;
+				}
+				f.write_char('}')
+			}
+		}
+	}
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+	f.write_char('"')?;
+	for chr in value.chars() {
+		match chr {
+			'"' => f.write_str("\\\"")?,
+			'\\' => f.write_str("\\\\")?,
+			'\n' => f.write_str("\\n")?,
+			'\r' => f.write_str("\\r")?,
+			'\t' => f.write_str("\\t")?,
+			chr if chr.is_control() => write!(f, "\\u{:04x}", chr as u32)?,
+			chr => f.write_char(chr)?,
+		}
+	}
+	f.write_char('"')
+}
+
+/// Parses JSON with comments. The error contains the byte offset of where parsing failed
+pub(crate) fn parse(source: &str) -> Result<JSONValue, (String, usize)> {
+	let mut parser = Parser { source, chars: source.char_indices().peekable() };
+	let value = parser.value()?;
+	parser.skip_whitespace_and_comments();
+	match parser.chars.next() {
+		None => Ok(value),
+		Some((offset, chr)) => Err((format!("Unexpected '{chr}' after value"), offset)),
+	}
+}
+
+struct Parser<'a> {
+	source: &'a str,
+	chars: Peekable<CharIndices<'a>>,
+}
+
+impl<'a> Parser<'a> {
+	fn offset(&mut self) -> usize {
+		self.chars.peek().map_or(self.source.len(), |(offset, _)| *offset)
+	}
+
+	fn skip_whitespace_and_comments(&mut self) {
+		while let Some((_, chr)) = self.chars.peek().copied() {
+			if chr.is_whitespace() {
+				self.chars.next();
+			} else if chr == '/' {
+				let rest = &self.source[self.offset()..];
+				if rest.starts_with("//") {
+					while self.chars.next_if(|(_, chr)| *chr != '\n').is_some() {}
+				} else if rest.starts_with("/*") {
+					let end = rest.find("*/").map_or(rest.len(), |end| end + 2);
+					let end = self.offset() + end;
+					while self.chars.next_if(|(offset, _)| *offset < end).is_some() {}
+				} else {
+					return;
+				}
+			} else {
+				return;
+			}
+		}
+	}
+
+	fn expect(&mut self, expected: char) -> Result<(), (String, usize)> {
+		self.skip_whitespace_and_comments();
+		match self.chars.next() {
+			Some((_, chr)) if chr == expected => Ok(()),
+			Some((offset, chr)) => Err((format!("Expected '{expected}' found '{chr}'"), offset)),
+			None => Err((format!("Expected '{expected}' found end of input"), self.source.len())),
+		}
+	}
+
+	fn value(&mut self) -> Result<JSONValue, (String, usize)> {
+		self.skip_whitespace_and_comments();
+		let offset = self.offset();
+		match self.chars.peek().map(|(_, chr)| *chr) {
+			Some('{') => {
+				self.chars.next();
+				let mut entries = Vec::new();
+				loop {
+					self.skip_whitespace_and_comments();
+					if self.chars.next_if(|(_, chr)| *chr == '}').is_some() {
+						break;
+					}
+					self.skip_whitespace_and_comments();
+					let key = self.string()?;
+					self.expect(':')?;
+					let value = self.value()?;
+					entries.push((key, value));
+					self.skip_whitespace_and_comments();
+					if self.chars.next_if(|(_, chr)| *chr == ',').is_none() {
+						self.expect('}')?;
+						break;
+					}
+				}
+				Ok(JSONValue::Object(entries))
+			}
+			Some('[') => {
+				self.chars.next();
+				let mut items = Vec::new();
+				loop {
+					self.skip_whitespace_and_comments();
+					if self.chars.next_if(|(_, chr)| *chr == ']').is_some() {
+						break;
+					}
+					items.push(self.value()?);
+					self.skip_whitespace_and_comments();
+					if self.chars.next_if(|(_, chr)| *chr == ',').is_none() {
+						self.expect(']')?;
+						break;
+					}
+				}
+				Ok(JSONValue::Array(items))
+			}
+			Some('"') => self.string().map(JSONValue::String),
+			Some(chr) if chr == '-' || chr.is_ascii_digit() => {
+				let mut end = offset;
+				while let Some((_, chr)) = self.chars.next_if(|(_, chr)| {
+					chr.is_ascii_digit() || matches!(chr, '-' | '+' | '.' | 'e' | 'E')
+				}) {
+					end += chr.len_utf8();
+				}
+				self.source[offset..end].parse().map(JSONValue::Number).map_err(|_| {
+					(format!("Invalid number '{}'", &self.source[offset..end]), offset)
+				})
+			}
+			Some(_) => {
+				let rest = &self.source[offset..];
+				let (value, length) = if rest.starts_with("true") {
+					(JSONValue::Boolean(true), 4)
+				} else if rest.starts_with("false") {
+					(JSONValue::Boolean(false), 5)
+				} else if rest.starts_with("null") {
+					(JSONValue::Null, 4)
+				} else {
+					let chr = rest.chars().next().unwrap();
+					return Err((format!("Unexpected '{chr}'"), offset));
+				};
+				for _ in 0..length {
+					self.chars.next();
+				}
+				Ok(value)
+			}
+			None => Err(("Expected value found end of input".to_owned(), offset)),
+		}
+	}
+
+	fn string(&mut self) -> Result<String, (String, usize)> {
+		let start = self.offset();
+		match self.chars.next() {
+			Some((_, '"')) => {}
+			Some((offset, chr)) => return Err((format!("Expected string found '{chr}'"), offset)),
+			None => return Err(("Expected string found end of input".to_owned(), start)),
+		}
+		let mut value = String::new();
+		loop {
+			match self.chars.next() {
+				Some((_, '"')) => return Ok(value),
+				Some((offset, '\\')) => match self.chars.next().map(|(_, chr)| chr) {
+					Some('n') => value.push('\n'),
+					Some('t') => value.push('\t'),
+					Some('r') => value.push('\r'),
+					Some('b') => value.push('\u{8}'),
+					Some('f') => value.push('\u{c}'),
+					Some('u') => {
+						let hex = (0..4)
+							.filter_map(|_| self.chars.next().map(|(_, chr)| chr))
+							.collect::<String>();
+						let code = u32::from_str_radix(&hex, 16)
+							.map_err(|_| ("Invalid unicode escape".to_owned(), offset))?;
+						// Surrogate pairs are not combined
+						value.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+					}
+					Some(chr) => value.push(chr),
+					None => return Err(("Unterminated string".to_owned(), start)),
+				},
+				Some((_, chr)) => value.push(chr),
+				None => return Err(("Unterminated string".to_owned(), start)),
+			}
+		}
+	}
+}
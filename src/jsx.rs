@@ -0,0 +1,612 @@
+//! Lowers JSX to function calls, for output which does not expect JSX.
+//!
+//! In the classic runtime `<a href="/">Home</a>` becomes
+//! `React.createElement("a", { href: "/" }, "Home")` with a configurable pragma. In the automatic
+//! runtime it becomes `_jsx("a", { href: "/", children: "Home" })` with `jsx`, `jsxs` and
+//! `Fragment` imported (under `_` prefixed names, so they do not clash with the module's own
+//! names) from `<importSource>/jsx-runtime`.
+//!
+//! Text is cleaned up in the same way as React: lines are trimmed (other than the start of the
+//! first line and end of the last), empty lines are removed and the rest are joined with a space
+
+use std::{
+	borrow::Cow,
+	cell::RefCell,
+	collections::{BTreeMap, BTreeSet},
+	rc::Rc,
+};
+
+use parser::{
+	expressions::{
+		object_literal::{ObjectLiteral, ObjectLiteralMember},
+		ExpressionId, SpreadExpression,
+	},
+	extractor::ExtractedFunctions,
+	ASTNode, BlockLikeMut, Chain, Expression, JSXAttribute, JSXElement, JSXElementChildren,
+	JSXFragment, JSXNode, JSXRoot, NodeRef, ParseOutput, ParseSettings, PropertyId, PropertyKey,
+	PropertyReference, Quoted, SourceId, Span, StatementOrDeclaration, VisitorsMut, WithComment,
+};
+
+use crate::error_handling::TempDiagnostic;
+
+/// How JSX is lowered by [crate::temp::Transform::LowerJSX]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JSXRuntime {
+	/// `pragma(type, props, ...children)`. Fragments use `pragma_frag` as the type
+	Classic { pragma: String, pragma_frag: String },
+	/// `jsx(type, props, key)` from `<import_source>/jsx-runtime`, with children in the props
+	Automatic { import_source: String },
+}
+
+impl JSXRuntime {
+	pub fn classic() -> Self {
+		JSXRuntime::Classic {
+			pragma: "React.createElement".to_owned(),
+			pragma_frag: "React.Fragment".to_owned(),
+		}
+	}
+
+	pub fn automatic() -> Self {
+		JSXRuntime::Automatic { import_source: "react".to_owned() }
+	}
+
+	/// Overrides the pragmas of the classic runtime or the import source of the automatic runtime
+	pub fn with_options(
+		mut self,
+		pragma: Option<String>,
+		pragma_frag: Option<String>,
+		import_source: Option<String>,
+	) -> Result<Self, String> {
+		match &mut self {
+			JSXRuntime::Classic { pragma: current_pragma, pragma_frag: current_pragma_frag } => {
+				if import_source.is_some() {
+					return Err(
+						"An import source only applies to the automatic JSX runtime".to_owned()
+					);
+				}
+				if let Some(pragma) = pragma {
+					*current_pragma = pragma;
+				}
+				if let Some(pragma_frag) = pragma_frag {
+					*current_pragma_frag = pragma_frag;
+				}
+			}
+			JSXRuntime::Automatic { import_source: current_import_source } => {
+				if pragma.is_some() || pragma_frag.is_some() {
+					return Err("Pragmas only apply to the classic JSX runtime".to_owned());
+				}
+				if let Some(import_source) = import_source {
+					*current_import_source = import_source;
+				}
+			}
+		}
+		Ok(self)
+	}
+}
+
+impl Default for JSXRuntime {
+	fn default() -> Self {
+		Self::classic()
+	}
+}
+
+impl std::str::FromStr for JSXRuntime {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"classic" => Ok(Self::classic()),
+			"automatic" => Ok(Self::automatic()),
+			_ => Err(format!("Unknown JSX runtime '{s}', expected 'classic' or 'automatic'")),
+		}
+	}
+}
+
+/// Functions imported for the automatic runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Helper {
+	/// For elements with less than two children
+	Jsx,
+	/// For elements with two or more children (which are passed as an array)
+	Jsxs,
+	Fragment,
+	/// For elements with a `key` after a spread attribute, as `jsx` would lose the order
+	CreateElement,
+}
+
+impl Helper {
+	fn imported_name(self) -> &'static str {
+		match self {
+			Helper::Jsx => "jsx",
+			Helper::Jsxs => "jsxs",
+			Helper::Fragment => "Fragment",
+			Helper::CreateElement => "createElement",
+		}
+	}
+
+	fn local_name(self) -> &'static str {
+		match self {
+			Helper::Jsx => "_jsx",
+			Helper::Jsxs => "_jsxs",
+			Helper::Fragment => "_Fragment",
+			Helper::CreateElement => "_createElement",
+		}
+	}
+
+	fn specifier(self, import_source: &str) -> String {
+		match self {
+			Helper::CreateElement => import_source.to_owned(),
+			_ => format!("{import_source}/jsx-runtime"),
+		}
+	}
+}
+
+/// Helpers used by the automatic runtime. Bundles collect these for every module so that they are
+/// only imported once
+#[derive(Default)]
+pub(crate) struct RuntimeImports {
+	helpers: BTreeSet<Helper>,
+	/// Of the first JSX which needs an import
+	pub(crate) position: Option<Span>,
+}
+
+impl RuntimeImports {
+	fn collect(&mut self, items: &[StatementOrDeclaration], functions: &ExtractedFunctions) {
+		for item in items {
+			NodeRef::from(item).walk(functions, &mut |node, _| {
+				let position = node.get_position().into_owned();
+				let helpers = match node {
+					NodeRef::Expression(Expression::JSXRoot(JSXRoot::Element(element)))
+					| NodeRef::JSXElement(element) => vec![element_helper(element)],
+					NodeRef::Expression(Expression::JSXRoot(JSXRoot::Fragment(fragment)))
+					| NodeRef::JSXNode(JSXNode::Fragment(fragment)) => {
+						vec![Helper::Fragment, children_helper(&fragment.children)]
+					}
+					_ => return,
+				};
+				self.helpers.extend(helpers);
+				self.position.get_or_insert(position);
+			});
+		}
+	}
+
+	/// `import` statements for the helpers, grouped by specifier
+	pub(crate) fn to_imports(&self, import_source: &str) -> Vec<String> {
+		let mut specifiers = BTreeMap::<String, Vec<String>>::new();
+		for helper in self.helpers.iter().copied() {
+			specifiers.entry(helper.specifier(import_source)).or_default().push(format!(
+				"{} as {}",
+				helper.imported_name(),
+				helper.local_name()
+			));
+		}
+		specifiers
+			.into_iter()
+			.map(|(specifier, names)| {
+				format!("import {{ {} }} from \"{specifier}\"", names.join(", "))
+			})
+			.collect()
+	}
+}
+
+/// Visitors for [crate::temp::Transform::LowerJSX]. If `runtime_imports` is passed, imports for
+/// the automatic runtime are added to it rather than to the module
+pub(crate) fn visitors(
+	runtime: &JSXRuntime,
+	runtime_imports: Option<Rc<RefCell<RuntimeImports>>>,
+) -> VisitorsMut<Vec<TempDiagnostic>> {
+	let mut visitors = VisitorsMut {
+		expression_visitors_mut: vec![Box::new(LowerJSX(runtime.clone()))],
+		..Default::default()
+	};
+	if let JSXRuntime::Automatic { import_source } = runtime {
+		visitors.block_visitors_mut.push(Box::new(ImportRuntime {
+			is_module: true,
+			import_source: import_source.clone(),
+			runtime_imports,
+		}));
+	}
+	visitors
+}
+
+/// Adds imports for the automatic runtime to the module (which is the first block visited)
+struct ImportRuntime {
+	is_module: bool,
+	import_source: String,
+	runtime_imports: Option<Rc<RefCell<RuntimeImports>>>,
+}
+
+impl<'a> parser::VisitorMut<BlockLikeMut<'a>, Vec<TempDiagnostic>> for ImportRuntime {
+	fn visit_mut(
+		&mut self,
+		item: &mut BlockLikeMut<'a>,
+		_data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		if !std::mem::take(&mut self.is_module) {
+			return;
+		}
+		if let Some(runtime_imports) = &self.runtime_imports {
+			runtime_imports.borrow_mut().collect(item.items, functions);
+			return;
+		}
+		let mut runtime_imports = RuntimeImports::default();
+		runtime_imports.collect(item.items, functions);
+		let imports = runtime_imports.to_imports(&self.import_source).into_iter().map(|import| {
+			let ParseOutput(import, _) = StatementOrDeclaration::from_string(
+				import,
+				ParseSettings::default(),
+				SourceId::NULL,
+				None,
+				Vec::new(),
+			)
+			.expect("Invalid runtime import");
+			import
+		});
+		item.items.splice(0..0, imports);
+	}
+}
+
+struct LowerJSX(JSXRuntime);
+
+impl parser::VisitorMut<Expression, Vec<TempDiagnostic>> for LowerJSX {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		_data: &mut Vec<TempDiagnostic>,
+		_functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		if !matches!(item, Expression::JSXRoot(_)) {
+			return;
+		}
+		let position = item.get_position().into_owned();
+		let placeholder = Expression::Null(position, ExpressionId::new());
+		let Expression::JSXRoot(root) = std::mem::replace(item, placeholder) else {
+			unreachable!()
+		};
+		// Expressions inside are visited after this, which lowers any JSX in them
+		*item = match root {
+			JSXRoot::Element(element) => self.lower_element(element),
+			JSXRoot::Fragment(fragment) => self.lower_fragment(fragment),
+		};
+	}
+}
+
+impl LowerJSX {
+	fn lower_fragment(&self, fragment: JSXFragment) -> Expression {
+		let tag = match &self.0 {
+			JSXRuntime::Classic { pragma_frag, .. } => reference(pragma_frag, &fragment.position),
+			JSXRuntime::Automatic { .. } => {
+				reference(Helper::Fragment.local_name(), &fragment.position)
+			}
+		};
+		let children = self.lower_children(fragment.children);
+		self.call(tag, Vec::new(), children, fragment.position)
+	}
+
+	fn lower_element(&self, element: JSXElement) -> Expression {
+		let JSXElement { tag_name, attributes, children, position, .. } = element;
+		let tag = if tag_name.contains('.') {
+			reference(&tag_name, &position)
+		} else if is_intrinsic(&tag_name) {
+			string(&tag_name, position.clone())
+		} else {
+			Expression::VariableReference(tag_name, position.clone(), ExpressionId::new())
+		};
+		let children = match children {
+			JSXElementChildren::Children(children) => self.lower_children(children),
+			// Including void elements without a `/>` (see `html_tag_is_self_closing`)
+			JSXElementChildren::SelfClosing => Vec::new(),
+		};
+		self.call(tag, attributes, children, position)
+	}
+
+	fn lower_children(&self, children: Vec<JSXNode>) -> Vec<Expression> {
+		let mut lowered = Vec::new();
+		let mut text = String::new();
+		let mut text_position: Option<Span> = None;
+		for child in children {
+			match child {
+				JSXNode::TextNode(content, position) => {
+					text.push_str(&content);
+					text_position = Some(match text_position {
+						Some(text_position) => text_position.union(&position),
+						None => position,
+					});
+					continue;
+				}
+				JSXNode::LineBreak => {
+					text.push('\n');
+					continue;
+				}
+				_ => {}
+			}
+			lowered.extend(text_child(&std::mem::take(&mut text), text_position.take()));
+			match child {
+				JSXNode::InterpolatedExpression(expression, _) => {
+					if !matches!(*expression, Expression::Comment(..)) {
+						lowered.push(*expression);
+					}
+				}
+				JSXNode::Element(element) => lowered.push(self.lower_element(element)),
+				JSXNode::Fragment(fragment) => lowered.push(self.lower_fragment(fragment)),
+				JSXNode::TextNode(..) | JSXNode::LineBreak => unreachable!(),
+			}
+		}
+		lowered.extend(text_child(&text, text_position));
+		lowered
+	}
+
+	fn call(
+		&self,
+		tag: Expression,
+		attributes: Vec<JSXAttribute>,
+		children: Vec<Expression>,
+		position: Span,
+	) -> Expression {
+		let (function, arguments) = match &self.0 {
+			JSXRuntime::Classic { pragma, .. } => {
+				(reference(pragma, &position), create_element_arguments(tag, attributes, children))
+			}
+			JSXRuntime::Automatic { .. } if key_after_spread(&attributes) => (
+				reference(Helper::CreateElement.local_name(), &position),
+				create_element_arguments(tag, attributes, children),
+			),
+			JSXRuntime::Automatic { .. } => {
+				let helper = if children.len() > 1 { Helper::Jsxs } else { Helper::Jsx };
+				let mut key = None;
+				let mut members = Vec::new();
+				for attribute in attributes {
+					match attribute {
+						JSXAttribute::Static(name, value, position) if name == "key" => {
+							key = Some(string(&decode_entities(&value), position));
+						}
+						JSXAttribute::Dynamic(name, value, _) if name == "key" => {
+							key = Some(*value);
+						}
+						attribute => members.push(attribute_member(attribute)),
+					}
+				}
+				let mut children = children.into_iter();
+				let children = match (children.next(), children.next()) {
+					(None, _) => None,
+					(Some(child), None) => Some(child),
+					(Some(first), Some(second)) => {
+						let children = [first, second].into_iter().chain(children);
+						let elements = children.map(SpreadExpression::NonSpread).collect();
+						Some(Expression::ArrayLiteral(
+							elements,
+							position.clone(),
+							ExpressionId::new(),
+						))
+					}
+				};
+				if let Some(children) = children {
+					members.push(property("children", children, position.clone()));
+				}
+				let props = Expression::ObjectLiteral(ObjectLiteral {
+					members,
+					position: position.clone(),
+					expression_id: ExpressionId::new(),
+				});
+				let mut arguments = vec![tag, props];
+				arguments.extend(key);
+				(reference(helper.local_name(), &position), arguments)
+			}
+		};
+		Expression::FunctionCall {
+			function: Box::new(function),
+			type_arguments: None,
+			arguments: arguments.into_iter().map(SpreadExpression::NonSpread).collect(),
+			position,
+			expression_id: ExpressionId::new(),
+		}
+	}
+}
+
+/// `type, props, ...children` where props is `null` if there are no attributes
+fn create_element_arguments(
+	tag: Expression,
+	attributes: Vec<JSXAttribute>,
+	children: Vec<Expression>,
+) -> Vec<Expression> {
+	let props = if attributes.is_empty() {
+		Expression::Null(tag.get_position().into_owned(), ExpressionId::new())
+	} else {
+		let position = tag.get_position().into_owned();
+		Expression::ObjectLiteral(ObjectLiteral {
+			members: attributes.into_iter().map(attribute_member).collect(),
+			position,
+			expression_id: ExpressionId::new(),
+		})
+	};
+	let mut arguments = vec![tag, props];
+	arguments.extend(children);
+	arguments
+}
+
+fn attribute_member(attribute: JSXAttribute) -> ObjectLiteralMember {
+	match attribute {
+		JSXAttribute::Static(name, value, position) => {
+			property(&name, string(&decode_entities(&value), position.clone()), position)
+		}
+		JSXAttribute::Dynamic(name, value, position) => property(&name, *value, position),
+		JSXAttribute::BooleanAttribute(name, position) => {
+			let value = Expression::BooleanLiteral(true, position.clone(), ExpressionId::new());
+			property(&name, value, position)
+		}
+		JSXAttribute::Spread(expression, position) => {
+			ObjectLiteralMember::SpreadExpression(expression, position)
+		}
+		// `<div {x}>` is `<div x={x}>`
+		JSXAttribute::Shorthand(Expression::VariableReference(name, position, id)) => {
+			let value = Expression::VariableReference(name.clone(), position.clone(), id);
+			property(&name, value, position)
+		}
+		JSXAttribute::Shorthand(expression) => {
+			let position = expression.get_position().into_owned();
+			ObjectLiteralMember::SpreadExpression(expression, position)
+		}
+	}
+}
+
+fn property(name: &str, value: Expression, position: Span) -> ObjectLiteralMember {
+	let is_identifier = !name.contains(['-', ':']);
+	let key = if is_identifier {
+		PropertyKey::Ident(name.to_owned(), PropertyId::new(), position.clone())
+	} else {
+		PropertyKey::StringLiteral(name.to_owned(), PropertyId::new(), position.clone())
+	};
+	ObjectLiteralMember::Property(WithComment::None(key), value, position)
+}
+
+/// Whether the element is a HTML (or custom) element rather than a component
+fn is_intrinsic(tag_name: &str) -> bool {
+	tag_name.starts_with(|chr: char| chr.is_ascii_lowercase()) || tag_name.contains(['-', ':'])
+}
+
+/// `a.b.c` as a variable followed by property accesses
+fn reference(path: &str, position: &Span) -> Expression {
+	let mut parts = path.split('.');
+	let root = parts.next().unwrap_or_default().to_owned();
+	let root = Expression::VariableReference(root, position.clone(), ExpressionId::new());
+	parts.fold(root, |parent, property| Expression::PropertyAccess {
+		parent: Box::new(parent),
+		property: PropertyReference::Standard(property.to_owned()),
+		position: position.clone(),
+		expression_id: ExpressionId::new(),
+		is_optional: false,
+	})
+}
+
+fn string(value: &str, position: Span) -> Expression {
+	let mut escaped = String::with_capacity(value.len());
+	for chr in value.chars() {
+		match chr {
+			'"' => escaped.push_str("\\\""),
+			'\\' => escaped.push_str("\\\\"),
+			'\n' => escaped.push_str("\\n"),
+			'\r' => escaped.push_str("\\r"),
+			chr => escaped.push(chr),
+		}
+	}
+	Expression::StringLiteral(escaped, Quoted::Double, position, ExpressionId::new())
+}
+
+fn key_after_spread(attributes: &[JSXAttribute]) -> bool {
+	let is_key = |attribute: &JSXAttribute| matches!(attribute, JSXAttribute::Static(name, ..) | JSXAttribute::Dynamic(name, ..) if name == "key");
+	attributes
+		.iter()
+		.skip_while(|attribute| !matches!(attribute, JSXAttribute::Spread(..)))
+		.any(is_key)
+}
+
+fn element_helper(element: &JSXElement) -> Helper {
+	if key_after_spread(&element.attributes) {
+		return Helper::CreateElement;
+	}
+	match &element.children {
+		JSXElementChildren::Children(children) => children_helper(children),
+		JSXElementChildren::SelfClosing => Helper::Jsx,
+	}
+}
+
+/// Must match the number of children [LowerJSX::lower_children] produces
+fn children_helper(children: &[JSXNode]) -> Helper {
+	let mut count = 0;
+	let mut text = String::new();
+	for child in children {
+		match child {
+			JSXNode::TextNode(content, _) => text.push_str(content),
+			JSXNode::LineBreak => text.push('\n'),
+			JSXNode::InterpolatedExpression(expression, _) => {
+				count += usize::from(!clean_text(&std::mem::take(&mut text)).is_empty());
+				count += usize::from(!matches!(&**expression, Expression::Comment(..)));
+			}
+			JSXNode::Element(_) | JSXNode::Fragment(_) => {
+				count += usize::from(!clean_text(&std::mem::take(&mut text)).is_empty());
+				count += 1;
+			}
+		}
+	}
+	count += usize::from(!clean_text(&text).is_empty());
+	if count > 1 {
+		Helper::Jsxs
+	} else {
+		Helper::Jsx
+	}
+}
+
+fn text_child(text: &str, position: Option<Span>) -> Option<Expression> {
+	let text = clean_text(text);
+	(!text.is_empty()).then(|| string(&decode_entities(&text), position.unwrap_or(Span::NULL_SPAN)))
+}
+
+/// Removes whitespace around line breaks, joining lines with a single space
+fn clean_text(text: &str) -> String {
+	let lines = text.split('\n').map(|line| line.trim_end_matches('\r')).collect::<Vec<_>>();
+	let last_non_empty =
+		lines.iter().rposition(|line| line.contains(|chr| chr != ' ' && chr != '\t')).unwrap_or(0);
+	let mut cleaned = String::new();
+	for (index, line) in lines.iter().enumerate() {
+		let line = line.replace('\t', " ");
+		let mut line = line.as_str();
+		if index != 0 {
+			line = line.trim_start_matches(' ');
+		}
+		if index != lines.len() - 1 {
+			line = line.trim_end_matches(' ');
+		}
+		if !line.is_empty() {
+			cleaned.push_str(line);
+			if index != last_non_empty {
+				cleaned.push(' ');
+			}
+		}
+	}
+	cleaned
+}
+
+/// Replaces HTML character references (`&amp;`, `&#123;`, `&#x7B;`) in text and attribute values
+fn decode_entities(text: &str) -> Cow<'_, str> {
+	if !text.contains('&') {
+		return Cow::Borrowed(text);
+	}
+	let mut decoded = String::with_capacity(text.len());
+	let mut rest = text;
+	while let Some(start) = rest.find('&') {
+		decoded.push_str(&rest[..start]);
+		rest = &rest[start..];
+		let end = rest.find(';').filter(|end| *end <= 10);
+		let character = end.and_then(|end| match &rest[1..end] {
+			"amp" => Some('&'),
+			"lt" => Some('<'),
+			"gt" => Some('>'),
+			"quot" => Some('"'),
+			"apos" => Some('\''),
+			"nbsp" => Some('\u{a0}'),
+			reference => {
+				let number = reference.strip_prefix('#')?;
+				let value = match number.strip_prefix(['x', 'X']) {
+					Some(hex) => u32::from_str_radix(hex, 16),
+					None => number.parse(),
+				};
+				value.ok().and_then(char::from_u32)
+			}
+		});
+		match (character, end) {
+			(Some(character), Some(end)) => {
+				decoded.push(character);
+				rest = &rest[end + 1..];
+			}
+			_ => {
+				decoded.push('&');
+				rest = &rest[1..];
+			}
+		}
+	}
+	decoded.push_str(rest);
+	Cow::Owned(decoded)
+}
@@ -1,7 +1,23 @@
 mod ast_explorer;
+mod bundle;
+mod check;
+pub mod config;
+mod downlevel;
+mod enums;
+mod erasure;
 mod error_handling;
+mod json;
+mod jsx;
+mod minify;
+pub mod pipeline;
 // mod repl;
+pub mod resolver;
+mod rewrite;
+mod search;
+mod source_maps;
+pub mod statistics;
 mod temp;
+mod tree_shaking;
 pub(crate) mod utilities;
 
 pub mod cli;
@@ -1,5 +1,9 @@
 use ezno_lib::cli::run_cli;
 
+#[cfg(all(feature = "memory-statistics", not(target_family = "wasm")))]
+#[global_allocator]
+static ALLOCATOR: ezno_lib::statistics::CountingAllocator = ezno_lib::statistics::CountingAllocator;
+
 fn main() {
 	run_cli()
 }
@@ -0,0 +1,369 @@
+//! Rewrites `if` statements as expressions when that is shorter. Branches which only contain
+//! expression statements become `&&`, `||` and `?:` (with several statements joined by commas),
+//! `if (a) b()` becomes `a && b()`, `if (!a) b()` becomes `a || b()` and
+//! `if (a) b(); else c()` becomes `a ? b() : c()`. Branches which all return a value become a
+//! single `return a ? b : c`
+//!
+//! Operands are grouped where their precedence requires it, and the statement is only rewritten
+//! when the grouping costs fewer bytes than the `if` and `else` keywords save
+
+use parser::{
+	expressions::{ExpressionId, MultipleExpression},
+	extractor::ExtractedFunctions,
+	operators::{BinaryOperator, Operator, UnaryOperator},
+	statements::IfStatement,
+	ASTNode, BlockOrSingleStatement, Chain, Expression, Span, Statement, StatementOrDeclaration,
+	VisitorsMut,
+};
+
+use super::constants::starts_with_declaration_token;
+use crate::{downlevel::sequence, error_handling::TempDiagnostic};
+
+/// Passes for [crate::temp::Transform::CollapseConditionals]
+pub(crate) fn passes() -> Vec<VisitorsMut<Vec<TempDiagnostic>>> {
+	vec![VisitorsMut {
+		statement_visitors_mut: vec![Box::new(CollapseConditionals)],
+		..Default::default()
+	}]
+}
+
+struct CollapseConditionals;
+
+impl parser::VisitorMut<Statement, Vec<TempDiagnostic>> for CollapseConditionals {
+	fn visit_mut(
+		&mut self,
+		item: &mut Statement,
+		_data: &mut Vec<TempDiagnostic>,
+		_functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		collapse(item);
+	}
+}
+
+/// Bytes of `if(){}`, `else if(){}` and `else{}`, as branches are always braced when minified
+const IF_LENGTH: usize = 6;
+const ELSE_IF_LENGTH: usize = 11;
+const ELSE_LENGTH: usize = 6;
+
+fn collapse(statement: &mut Statement) {
+	let Statement::IfStatement(if_statement) = statement else { return };
+	// Statements are visited before the statements in them, but `if (a) { if (b) c() }` can only
+	// become `a && b && c()` once the inner statement is an expression
+	for inner in branches(if_statement) {
+		match inner {
+			BlockOrSingleStatement::Braced(block) => {
+				for item in block.0.iter_mut() {
+					if let StatementOrDeclaration::Statement(statement) = item {
+						collapse(statement);
+					}
+				}
+			}
+			BlockOrSingleStatement::SingleStatement(statement) => collapse(statement),
+		}
+	}
+
+	let position = if_statement.position.clone();
+	if expressions_cost(if_statement).is_some_and(|(before, after)| after < before) {
+		let Statement::IfStatement(if_statement) =
+			std::mem::replace(statement, Statement::Debugger(position.clone()))
+		else {
+			unreachable!()
+		};
+		*statement = Statement::Expression(to_expression(if_statement, &position).into());
+	} else if is_return_chain(if_statement) {
+		let Statement::IfStatement(if_statement) =
+			std::mem::replace(statement, Statement::Debugger(position.clone()))
+		else {
+			unreachable!()
+		};
+		*statement = to_return(if_statement, &position);
+	}
+}
+
+fn branches(if_statement: &mut IfStatement) -> impl Iterator<Item = &mut BlockOrSingleStatement> {
+	std::iter::once(&mut if_statement.inner)
+		.chain(if_statement.else_conditions.iter_mut().map(|branch| &mut branch.inner))
+		.chain(if_statement.trailing_else.iter_mut().map(|branch| &mut branch.inner))
+}
+
+fn conditions(if_statement: &IfStatement) -> impl Iterator<Item = &MultipleExpression> {
+	std::iter::once(&if_statement.condition)
+		.chain(if_statement.else_conditions.iter().map(|branch| &branch.condition))
+}
+
+/// The expression statements of a branch, if it only contains expression statements
+fn branch_expressions(inner: &BlockOrSingleStatement) -> Option<Vec<&MultipleExpression>> {
+	match inner {
+		BlockOrSingleStatement::Braced(block) => block
+			.0
+			.iter()
+			.map(|item| match item {
+				StatementOrDeclaration::Statement(Statement::Expression(expression)) => {
+					Some(expression)
+				}
+				_ => None,
+			})
+			.collect::<Option<Vec<_>>>()
+			.filter(|expressions| !expressions.is_empty()),
+		BlockOrSingleStatement::SingleStatement(statement) => match statement.as_ref() {
+			Statement::Expression(expression) => Some(vec![expression]),
+			_ => None,
+		},
+	}
+}
+
+/// The bytes used by the `if` statement and the expression, excluding the conditions and
+/// branches, if its branches only contain expression statements
+fn expressions_cost(if_statement: &IfStatement) -> Option<(usize, usize)> {
+	let mut before = IF_LENGTH + ELSE_IF_LENGTH * if_statement.else_conditions.len();
+	let mut after = 0;
+	let has_else = if_statement.trailing_else.is_some();
+	let is_negated = !has_else
+		&& if_statement.else_conditions.is_empty()
+		&& matches!(
+			if_statement.condition,
+			MultipleExpression {
+				lhs: None,
+				rhs: Expression::UnaryOperation { operator: UnaryOperator::LogicalNot, .. }
+			}
+		);
+
+	let count = if_statement.else_conditions.len() + 1;
+	for (index, (condition, inner)) in conditions(if_statement)
+		.zip(
+			std::iter::once(&if_statement.inner)
+				.chain(if_statement.else_conditions.iter().map(|branch| &branch.inner)),
+		)
+		.enumerate()
+	{
+		let expressions = branch_expressions(inner)?;
+		// The last condition without an `else` becomes `&&`, the others `?:`
+		let is_and = index + 1 == count && !has_else;
+		after += 2;
+		let grouped_condition = if is_negated {
+			false
+		} else if is_and {
+			group_multiple(condition, needs_grouping_in_and)
+		} else {
+			group_multiple(condition, needs_grouping_in_condition)
+		};
+		let grouped_value = match expressions[..] {
+			[expression] if is_negated => group_multiple(expression, needs_grouping_in_or),
+			[expression] if is_and => group_multiple(expression, needs_grouping_in_and),
+			[expression] => expression.lhs.is_some(),
+			_ => true,
+		};
+		after += 2 * (usize::from(grouped_condition) + usize::from(grouped_value));
+	}
+	if let Some(trailing_else) = &if_statement.trailing_else {
+		let expressions = branch_expressions(&trailing_else.inner)?;
+		before += ELSE_LENGTH;
+		if expressions.len() > 1 || expressions[0].lhs.is_some() {
+			after += 2;
+		}
+	}
+	if let Some(first) = conditions(if_statement).next() {
+		if first.lhs.is_none() && starts_with_declaration_token(&first.rhs) {
+			after += 2;
+		}
+	}
+	Some((before, after))
+}
+
+/// Whether every branch is `return ...` with a value, including an `else`. Each `return` and `else`
+/// saves more than grouping the condition costs
+fn is_return_chain(if_statement: &IfStatement) -> bool {
+	let returns_value = |inner: &BlockOrSingleStatement| {
+		matches!(branch_statement(inner), Some(Statement::Return(_, Some(_))))
+	};
+	if_statement.trailing_else.as_ref().is_some_and(|branch| returns_value(&branch.inner))
+		&& returns_value(&if_statement.inner)
+		&& if_statement.else_conditions.iter().all(|branch| returns_value(&branch.inner))
+}
+
+/// The only statement of a branch
+fn branch_statement(inner: &BlockOrSingleStatement) -> Option<&Statement> {
+	match inner {
+		BlockOrSingleStatement::Braced(block) => match &block.0[..] {
+			[StatementOrDeclaration::Statement(statement)] => Some(statement),
+			_ => None,
+		},
+		BlockOrSingleStatement::SingleStatement(statement) => Some(statement),
+	}
+}
+
+fn to_expression(if_statement: IfStatement, position: &Span) -> Expression {
+	let IfStatement { condition, inner, else_conditions, trailing_else, .. } = if_statement;
+	let mut branches = std::iter::once((condition, inner))
+		.chain(else_conditions.into_iter().map(|branch| (branch.condition, branch.inner)))
+		.map(|(condition, inner)| (condition, take_expressions(inner, position)))
+		.collect::<Vec<_>>();
+
+	let mut result = match trailing_else {
+		Some(branch) => take_expressions(branch.inner, position),
+		None => {
+			let (condition, value) = branches.pop().unwrap();
+			match condition {
+				MultipleExpression {
+					lhs: None,
+					rhs:
+						Expression::UnaryOperation {
+							operator: UnaryOperator::LogicalNot, operand, ..
+						},
+				} if branches.is_empty() => {
+					logical(*operand, BinaryOperator::LogicalOr, group(value, needs_grouping_in_or))
+				}
+				condition => logical(
+					group(grouped(condition, position), needs_grouping_in_and),
+					BinaryOperator::LogicalAnd,
+					group(value, needs_grouping_in_and),
+				),
+			}
+		}
+	};
+	for (condition, value) in branches.into_iter().rev() {
+		result = Expression::TernaryExpression {
+			condition: Box::new(group(grouped(condition, position), needs_grouping_in_condition)),
+			truthy_result: Box::new(value),
+			falsy_result: Box::new(result),
+			id: ExpressionId::new(),
+		};
+	}
+	if starts_with_declaration_token(&result) {
+		result = parenthesize_expression(result);
+	}
+	result
+}
+
+fn to_return(if_statement: IfStatement, position: &Span) -> Statement {
+	let IfStatement { condition, inner, else_conditions, trailing_else, .. } = if_statement;
+	let mut keyword = None;
+	let mut take_return = |inner: BlockOrSingleStatement| {
+		let statement = match inner {
+			BlockOrSingleStatement::Braced(mut block) => match block.0.pop() {
+				Some(StatementOrDeclaration::Statement(statement)) => statement,
+				_ => unreachable!(),
+			},
+			BlockOrSingleStatement::SingleStatement(statement) => *statement,
+		};
+		let Statement::Return(return_keyword, Some(value)) = statement else { unreachable!() };
+		keyword.get_or_insert(return_keyword);
+		grouped(value, position)
+	};
+	let branches = std::iter::once((condition, inner))
+		.chain(else_conditions.into_iter().map(|branch| (branch.condition, branch.inner)))
+		.map(|(condition, inner)| (condition, take_return(inner)))
+		.collect::<Vec<_>>();
+	let mut result = take_return(trailing_else.unwrap().inner);
+	for (condition, value) in branches.into_iter().rev() {
+		result = Expression::TernaryExpression {
+			condition: Box::new(group(grouped(condition, position), needs_grouping_in_condition)),
+			truthy_result: Box::new(value),
+			falsy_result: Box::new(result),
+			id: ExpressionId::new(),
+		};
+	}
+	Statement::Return(keyword.unwrap(), Some(result.into()))
+}
+
+/// The expressions of a branch as one expression, grouped if there is more than one
+fn take_expressions(inner: BlockOrSingleStatement, position: &Span) -> Expression {
+	let statements = match inner {
+		BlockOrSingleStatement::Braced(block) => block
+			.0
+			.into_iter()
+			.map(|item| match item {
+				StatementOrDeclaration::Statement(statement) => statement,
+				StatementOrDeclaration::Declaration(_) => unreachable!(),
+			})
+			.collect(),
+		BlockOrSingleStatement::SingleStatement(statement) => vec![*statement],
+	};
+	let mut expressions = Vec::new();
+	for statement in statements {
+		let Statement::Expression(expression) = statement else { unreachable!() };
+		flatten(expression, &mut expressions);
+	}
+	grouped(sequence(expressions), position)
+}
+
+/// The expressions of a comma sequence, in order
+fn flatten(expression: MultipleExpression, out: &mut Vec<Expression>) {
+	if let Some(lhs) = expression.lhs {
+		flatten(*lhs, out);
+	}
+	out.push(expression.rhs);
+}
+
+/// A sequence as an expression, in parentheses if it has more than one expression
+fn grouped(expression: MultipleExpression, position: &Span) -> Expression {
+	match expression {
+		MultipleExpression { lhs: None, rhs } => rhs,
+		expression => Expression::ParenthesizedExpression(
+			Box::new(expression),
+			position.clone(),
+			ExpressionId::new(),
+		),
+	}
+}
+
+fn group(expression: Expression, needs_grouping: fn(&Expression) -> bool) -> Expression {
+	if needs_grouping(&expression) {
+		parenthesize_expression(expression)
+	} else {
+		expression
+	}
+}
+
+fn group_multiple(
+	expression: &MultipleExpression,
+	needs_grouping: fn(&Expression) -> bool,
+) -> bool {
+	expression.lhs.is_some() || needs_grouping(&expression.rhs)
+}
+
+fn parenthesize_expression(expression: Expression) -> Expression {
+	let position = expression.get_position().into_owned();
+	crate::downlevel::parenthesize(expression, &position)
+}
+
+fn logical(lhs: Expression, operator: BinaryOperator, rhs: Expression) -> Expression {
+	Expression::BinaryOperation {
+		lhs: Box::new(lhs),
+		operator,
+		rhs: Box::new(rhs),
+		id: ExpressionId::new(),
+	}
+}
+
+fn is_arrow_function(expression: &Expression) -> bool {
+	matches!(expression, Expression::ArrowFunction(..) | Expression::ExtractedArrowFunction(..))
+}
+
+/// Operands of `&&`. `??` cannot be mixed with it without parentheses
+fn needs_grouping_in_and(expression: &Expression) -> bool {
+	expression.get_precedence() < BinaryOperator::LogicalAnd.precedence()
+		|| is_arrow_function(expression)
+}
+
+/// Operands of `||`. `??` cannot be mixed with it without parentheses
+fn needs_grouping_in_or(expression: &Expression) -> bool {
+	match expression {
+		Expression::BinaryOperation {
+			operator: BinaryOperator::LogicalAnd | BinaryOperator::LogicalOr,
+			..
+		} => false,
+		expression => {
+			expression.get_precedence() <= BinaryOperator::LogicalOr.precedence()
+				|| is_arrow_function(expression)
+		}
+	}
+}
+
+/// The condition of `?:`
+fn needs_grouping_in_condition(expression: &Expression) -> bool {
+	matches!(expression, Expression::TernaryExpression { .. })
+		|| expression.get_precedence() < BinaryOperator::LogicalOr.precedence()
+		|| is_arrow_function(expression)
+}
@@ -0,0 +1,397 @@
+//! Evaluates operations on literals, e.g. `60 * 60` to `3600` and `"a" + "b"` to `"ab"`, and
+//! selects the branch of conditional expressions (`?:`, `&&`, `||` and `??`) with a literal
+//! condition. Results are only written when they are at most as long as the expression, so
+//! `!0` is kept rather than becoming `true`
+//!
+//! Numbers are only folded when the result has the same decimal form in JavaScript (so not for
+//! `NaN`, infinities and very small or large numbers) and strings only when they do not contain
+//! escapes or quotes. Replacements keep the position of the expression, for source maps
+
+use parser::{
+	expressions::ExpressionId, extractor::ExtractedFunctions, operators::BinaryOperator,
+	operators::UnaryOperator, ASTNode, Chain, Expression, NumberStructure, Quoted, Span,
+	ToStringSettings, ToStringSettingsAndData, VisitorsMut,
+};
+
+use crate::{
+	downlevel::{parenthesize, take},
+	error_handling::TempDiagnostic,
+};
+
+/// Passes for [crate::temp::Transform::FoldConstants]
+pub(crate) fn passes() -> Vec<VisitorsMut<Vec<TempDiagnostic>>> {
+	vec![VisitorsMut {
+		expression_visitors_mut: vec![Box::new(FoldConstants)],
+		..Default::default()
+	}]
+}
+
+struct FoldConstants;
+
+impl parser::VisitorMut<Expression, Vec<TempDiagnostic>> for FoldConstants {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		_data: &mut Vec<TempDiagnostic>,
+		_functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		// Parents are visited before their operands, so operands are folded here first
+		fold(item);
+	}
+}
+
+/// The value of a literal
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Constant {
+	Number(f64),
+	/// Content, without escapes or quotes
+	String(String),
+	Boolean(bool),
+	Null,
+	Undefined,
+}
+
+impl Constant {
+	pub(crate) fn is_truthy(&self) -> bool {
+		match self {
+			Constant::Number(value) => *value != 0. && !value.is_nan(),
+			Constant::String(value) => !value.is_empty(),
+			Constant::Boolean(value) => *value,
+			Constant::Null | Constant::Undefined => false,
+		}
+	}
+
+	fn to_number(&self) -> Option<f64> {
+		match self {
+			Constant::Number(value) => Some(*value),
+			Constant::Boolean(value) => Some(if *value { 1. } else { 0. }),
+			Constant::Null => Some(0.),
+			Constant::Undefined => Some(f64::NAN),
+			// Parsing is not implemented
+			Constant::String(_) => None,
+		}
+	}
+
+	fn to_js_string(&self) -> Option<String> {
+		match self {
+			Constant::Number(value) => number_to_string(*value),
+			Constant::String(value) => Some(value.clone()),
+			Constant::Boolean(value) => Some(value.to_string()),
+			Constant::Null => Some("null".to_owned()),
+			Constant::Undefined => Some("undefined".to_owned()),
+		}
+	}
+
+	/// `typeof` of the value
+	fn type_name(&self) -> &'static str {
+		match self {
+			Constant::Number(_) => "number",
+			Constant::String(_) => "string",
+			Constant::Boolean(_) => "boolean",
+			Constant::Null => "object",
+			Constant::Undefined => "undefined",
+		}
+	}
+
+	/// The literal for the value, if it can be written as one
+	fn to_expression(&self, position: &Span) -> Option<Expression> {
+		let expression = match self {
+			Constant::Number(value) => {
+				// `-0` would be printed as `0`
+				if *value == 0. && value.is_sign_negative() {
+					return None;
+				}
+				number_to_string(*value)?;
+				Expression::NumberLiteral(
+					NumberStructure::Number(*value),
+					position.clone(),
+					ExpressionId::new(),
+				)
+			}
+			Constant::String(value) => Expression::StringLiteral(
+				value.clone(),
+				Quoted::Double,
+				position.clone(),
+				ExpressionId::new(),
+			),
+			Constant::Boolean(value) => {
+				Expression::BooleanLiteral(*value, position.clone(), ExpressionId::new())
+			}
+			Constant::Null => Expression::Null(position.clone(), ExpressionId::new()),
+			Constant::Undefined => return None,
+		};
+		Some(expression)
+	}
+}
+
+/// The value of an expression made up of literals (which is not changed by [fold])
+pub(crate) fn constant(expression: &Expression) -> Option<Constant> {
+	match expression {
+		Expression::NumberLiteral(NumberStructure::Number(value), ..) => {
+			Some(Constant::Number(*value))
+		}
+		Expression::StringLiteral(content, ..) if is_plain(content) => {
+			Some(Constant::String(content.clone()))
+		}
+		Expression::BooleanLiteral(value, ..) => Some(Constant::Boolean(*value)),
+		Expression::Null(..) => Some(Constant::Null),
+		Expression::UnaryOperation { operator: UnaryOperator::Void, operand, .. } => {
+			constant(operand).map(|_| Constant::Undefined)
+		}
+		Expression::UnaryOperation { operator: UnaryOperator::Negation, operand, .. } => {
+			match constant(operand)? {
+				Constant::Number(value) => Some(Constant::Number(-value)),
+				_ => None,
+			}
+		}
+		Expression::ParenthesizedExpression(inner, ..) if inner.lhs.is_none() => {
+			constant(&inner.rhs)
+		}
+		_ => None,
+	}
+}
+
+/// Whether a condition is always truthy or always falsy
+pub(crate) fn truthiness(expression: &Expression) -> Option<bool> {
+	match expression {
+		Expression::UnaryOperation { operator: UnaryOperator::LogicalNot, operand, .. } => {
+			truthiness(operand).map(|truthy| !truthy)
+		}
+		expression => constant(expression).map(|value| value.is_truthy()),
+	}
+}
+
+/// Strings which can be compared and written as they are
+fn is_plain(content: &str) -> bool {
+	content.is_ascii() && !content.contains(['\\', '"', '\'', '\n', '\r'])
+}
+
+/// The number as JavaScript would print it, when Rust prints it the same way
+fn number_to_string(value: f64) -> Option<String> {
+	if value == 0. {
+		Some("0".to_owned())
+	} else if value.is_finite()
+		&& (value.abs() >= 1e-6 || value.fract() == 0.)
+		&& value.abs() < 1e21
+	{
+		Some(value.to_string())
+	} else {
+		None
+	}
+}
+
+/// Folds the operands of `expression` then `expression`
+pub(crate) fn fold(expression: &mut Expression) {
+	match expression {
+		Expression::BinaryOperation { lhs, rhs, .. } => {
+			fold(lhs);
+			fold(rhs);
+		}
+		Expression::UnaryOperation { operand, .. } => fold(operand),
+		Expression::ParenthesizedExpression(inner, ..) if inner.lhs.is_none() => {
+			fold(&mut inner.rhs)
+		}
+		Expression::TernaryExpression { condition, truthy_result, falsy_result, .. } => {
+			fold(condition);
+			fold(truthy_result);
+			fold(falsy_result);
+		}
+		_ => return,
+	}
+
+	let position = expression.get_position().into_owned();
+	match expression {
+		Expression::TernaryExpression { condition, .. } => {
+			if let Some(truthy) = truthiness(condition) {
+				let Expression::TernaryExpression { truthy_result, falsy_result, .. } =
+					take(expression, &position)
+				else {
+					unreachable!()
+				};
+				*expression = branch(*if truthy { truthy_result } else { falsy_result }, &position);
+			}
+		}
+		Expression::BinaryOperation {
+			lhs,
+			operator:
+				operator @ (BinaryOperator::LogicalAnd
+				| BinaryOperator::LogicalOr
+				| BinaryOperator::NullCoalescing),
+			..
+		} => {
+			let Some(value) = constant(lhs) else { return };
+			let keep_lhs = match operator {
+				BinaryOperator::LogicalAnd => !value.is_truthy(),
+				BinaryOperator::LogicalOr => value.is_truthy(),
+				_ => !matches!(value, Constant::Null | Constant::Undefined),
+			};
+			let Expression::BinaryOperation { lhs, rhs, .. } = take(expression, &position) else {
+				unreachable!()
+			};
+			*expression = branch(*if keep_lhs { lhs } else { rhs }, &position);
+		}
+		Expression::ParenthesizedExpression(inner, ..) => {
+			// Strings and booleans do not need to be grouped, numbers do for `(1).toString()`
+			if inner.lhs.is_none()
+				&& matches!(
+					inner.rhs,
+					Expression::StringLiteral(..) | Expression::BooleanLiteral(..)
+				) {
+				let Expression::ParenthesizedExpression(inner, ..) = take(expression, &position)
+				else {
+					unreachable!()
+				};
+				*expression = inner.rhs;
+			}
+		}
+		expression => {
+			let value = match expression {
+				Expression::BinaryOperation { lhs, operator, rhs, .. } => {
+					binary_operation(*operator, &constant(lhs), &constant(rhs))
+				}
+				Expression::UnaryOperation { operator, operand, .. } => {
+					unary_operation(*operator, constant(operand))
+				}
+				_ => None,
+			};
+			if let Some(folded) = value.and_then(|value| value.to_expression(&position)) {
+				if printed_length(&folded) <= printed_length(expression) {
+					*expression = folded;
+				}
+			}
+		}
+	}
+}
+
+/// A branch which replaces its conditional expression. Branches starting with `{`, `function` or
+/// `class` are grouped as the conditional may be an expression statement
+fn branch(expression: Expression, position: &Span) -> Expression {
+	if starts_with_declaration_token(&expression) {
+		parenthesize(expression, position)
+	} else {
+		expression
+	}
+}
+
+pub(crate) fn starts_with_declaration_token(expression: &Expression) -> bool {
+	match expression {
+		Expression::ObjectLiteral(..)
+		| Expression::ExpressionFunction(..)
+		| Expression::ExtractedExpressionFunction(..)
+		| Expression::ClassExpression(..) => true,
+		Expression::PropertyAccess { parent: leftmost, .. }
+		| Expression::Index { indexee: leftmost, .. }
+		| Expression::FunctionCall { function: leftmost, .. }
+		| Expression::BinaryOperation { lhs: leftmost, .. }
+		| Expression::TernaryExpression { condition: leftmost, .. } => {
+			starts_with_declaration_token(leftmost)
+		}
+		_ => false,
+	}
+}
+
+fn printed_length(expression: &Expression) -> usize {
+	let settings =
+		ToStringSettingsAndData(ToStringSettings::minified(), ExtractedFunctions::default());
+	expression.to_string(&settings).len()
+}
+
+fn unary_operation(operator: UnaryOperator, operand: Option<Constant>) -> Option<Constant> {
+	let operand = operand?;
+	match operator {
+		UnaryOperator::LogicalNot => Some(Constant::Boolean(!operand.is_truthy())),
+		UnaryOperator::Negation => operand.to_number().map(|value| Constant::Number(-value)),
+		UnaryOperator::Plus => operand.to_number().map(Constant::Number),
+		UnaryOperator::BitwiseNot => {
+			operand.to_number().map(|value| Constant::Number(!to_int32(value) as f64))
+		}
+		UnaryOperator::TypeOf => Some(Constant::String(operand.type_name().to_owned())),
+		UnaryOperator::Void => Some(Constant::Undefined),
+		_ => None,
+	}
+}
+
+fn binary_operation(
+	operator: BinaryOperator,
+	lhs: &Option<Constant>,
+	rhs: &Option<Constant>,
+) -> Option<Constant> {
+	let (Some(lhs), Some(rhs)) = (lhs, rhs) else { return None };
+	let numbers = || Some((lhs.to_number()?, rhs.to_number()?));
+	let integers = || numbers().map(|(lhs, rhs)| (to_int32(lhs), to_int32(rhs)));
+	let value = match operator {
+		BinaryOperator::Add => match (lhs, rhs) {
+			(Constant::String(_), _) | (_, Constant::String(_)) => {
+				return Some(Constant::String(lhs.to_js_string()? + &rhs.to_js_string()?));
+			}
+			_ => numbers().map(|(lhs, rhs)| lhs + rhs),
+		},
+		BinaryOperator::Subtract => numbers().map(|(lhs, rhs)| lhs - rhs),
+		BinaryOperator::Multiply => numbers().map(|(lhs, rhs)| lhs * rhs),
+		BinaryOperator::Divide => numbers().map(|(lhs, rhs)| lhs / rhs),
+		// Same sign as the dividend, like JavaScript
+		BinaryOperator::Modulo => numbers().map(|(lhs, rhs)| lhs % rhs),
+		BinaryOperator::Exponent => {
+			// `powf` differs for these
+			numbers()
+				.filter(|(lhs, rhs)| !(rhs.is_nan() || lhs.abs() == 1. && rhs.is_infinite()))
+				.map(|(lhs, rhs)| lhs.powf(rhs))
+		}
+		BinaryOperator::BitwiseAnd => integers().map(|(lhs, rhs)| (lhs & rhs) as f64),
+		BinaryOperator::BitwiseOr => integers().map(|(lhs, rhs)| (lhs | rhs) as f64),
+		BinaryOperator::BitwiseXOr => integers().map(|(lhs, rhs)| (lhs ^ rhs) as f64),
+		BinaryOperator::BitwiseShiftLeft => {
+			integers().map(|(lhs, rhs)| lhs.wrapping_shl(rhs as u32 & 31) as f64)
+		}
+		BinaryOperator::BitwiseShiftRight => {
+			integers().map(|(lhs, rhs)| lhs.wrapping_shr(rhs as u32 & 31) as f64)
+		}
+		BinaryOperator::BitwiseShiftRightUnsigned => {
+			integers().map(|(lhs, rhs)| (lhs as u32).wrapping_shr(rhs as u32 & 31) as f64)
+		}
+		BinaryOperator::StrictEqual => return Some(Constant::Boolean(lhs == rhs)),
+		BinaryOperator::StrictNotEqual => return Some(Constant::Boolean(lhs != rhs)),
+		BinaryOperator::Equal | BinaryOperator::NotEqual => {
+			let nullish = |value: &Constant| matches!(value, Constant::Null | Constant::Undefined);
+			let equal = if nullish(lhs) || nullish(rhs) {
+				nullish(lhs) && nullish(rhs)
+			} else if std::mem::discriminant(lhs) == std::mem::discriminant(rhs) {
+				lhs == rhs
+			} else {
+				// Conversions between other types are not implemented
+				return None;
+			};
+			return Some(Constant::Boolean(equal == matches!(operator, BinaryOperator::Equal)));
+		}
+		BinaryOperator::LessThan
+		| BinaryOperator::LessThanEqual
+		| BinaryOperator::GreaterThan
+		| BinaryOperator::GreaterThanEqual => {
+			let ordering = match (lhs, rhs) {
+				(Constant::String(lhs), Constant::String(rhs)) => Some(lhs.cmp(rhs)),
+				(Constant::Number(lhs), Constant::Number(rhs)) => lhs.partial_cmp(rhs),
+				_ => return None,
+			};
+			// Comparisons with `NaN` are always false
+			let result = ordering.is_some_and(|ordering| match operator {
+				BinaryOperator::LessThan => ordering.is_lt(),
+				BinaryOperator::LessThanEqual => ordering.is_le(),
+				BinaryOperator::GreaterThan => ordering.is_gt(),
+				_ => ordering.is_ge(),
+			});
+			return Some(Constant::Boolean(result));
+		}
+		_ => None,
+	};
+	value.map(Constant::Number)
+}
+
+/// [ToInt32](https://tc39.es/ecma262/#sec-toint32)
+fn to_int32(value: f64) -> i32 {
+	if value.is_finite() {
+		value.trunc().rem_euclid(4294967296.) as u32 as i32
+	} else {
+		0
+	}
+}
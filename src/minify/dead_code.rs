@@ -0,0 +1,189 @@
+//! Removes code which never runs: statements after `return`, `throw`, `break` and `continue`, and
+//! branches of `if` statements whose condition is a literal (see [super::constants::truthiness]).
+//! Function declarations are hoisted, so they are kept, as are the names of `var` declarations
+//! (as `var a;`)
+
+use parser::{
+	expressions::MultipleExpression,
+	extractor::ExtractedFunctions,
+	statements::{
+		ConditionalElseStatement, IfStatement, UnconditionalElseStatement, VarVariableStatement,
+	},
+	BlockLikeMut, BlockOrSingleStatement, Chain, Declaration, Keyword, NodeRef, Span, Statement,
+	StatementOrDeclaration, VisitorsMut,
+};
+
+use super::constants::truthiness;
+use crate::{
+	bundle::{lexical_declarations, var_declarations},
+	downlevel::declaration_item,
+	error_handling::TempDiagnostic,
+};
+
+/// Passes for [crate::temp::Transform::RemoveDeadCode]
+pub(crate) fn passes() -> Vec<VisitorsMut<Vec<TempDiagnostic>>> {
+	vec![VisitorsMut { block_visitors_mut: vec![Box::new(RemoveDeadCode)], ..Default::default() }]
+}
+
+struct RemoveDeadCode;
+
+impl<'a> parser::VisitorMut<BlockLikeMut<'a>, Vec<TempDiagnostic>> for RemoveDeadCode {
+	fn visit_mut(
+		&mut self,
+		item: &mut BlockLikeMut<'a>,
+		_data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		if item.items.iter().any(|item| matches!(item, StatementOrDeclaration::Statement(Statement::IfStatement(statement)) if has_literal_condition(statement)))
+		{
+			let items = std::mem::take(item.items);
+			for statement in items {
+				match statement {
+					StatementOrDeclaration::Statement(Statement::IfStatement(statement))
+						if has_literal_condition(&statement) =>
+					{
+						item.items.extend(remove_branches(statement, functions));
+					}
+					statement => item.items.push(statement),
+				}
+			}
+		}
+
+		let Some(index) = item.items.iter().position(is_exit) else { return };
+		let mut removed = item.items.split_off(index + 1);
+		if removed.is_empty() {
+			return;
+		}
+		let mut declarations = Vec::new();
+		for item in removed.iter() {
+			var_declarations(NodeRef::from(item), functions, &mut declarations);
+		}
+		removed.retain(|item| {
+			matches!(
+				item,
+				StatementOrDeclaration::Declaration(
+					Declaration::Function(_) | Declaration::ExtractedFunction(_)
+				)
+			)
+		});
+		item.items.extend(removed);
+		item.items.extend(declare_vars(declarations));
+	}
+}
+
+/// Statements after which the rest of the block is not run
+fn is_exit(item: &StatementOrDeclaration) -> bool {
+	matches!(
+		item,
+		StatementOrDeclaration::Statement(
+			Statement::Return(..)
+				| Statement::Throw(..)
+				| Statement::Break(..)
+				| Statement::Continue(..)
+		)
+	)
+}
+
+fn has_literal_condition(statement: &IfStatement) -> bool {
+	std::iter::once(&statement.condition)
+		.chain(statement.else_conditions.iter().map(|branch| &branch.condition))
+		.any(|condition| condition.lhs.is_none() && truthiness(&condition.rhs).is_some())
+}
+
+/// The statements which replace an `if` statement with branches with literal conditions. Branches
+/// with a falsy condition are removed and a branch with a truthy condition becomes the `else`
+fn remove_branches(
+	statement: IfStatement,
+	functions: &ExtractedFunctions,
+) -> Vec<StatementOrDeclaration> {
+	let IfStatement { condition, inner, else_conditions, trailing_else, position } = statement;
+	let branches = std::iter::once((Some(condition), inner))
+		.chain(else_conditions.into_iter().map(|branch| (Some(branch.condition), branch.inner)))
+		.chain(trailing_else.map(|branch| (None, branch.inner)));
+
+	let mut kept = Vec::<(MultipleExpression, BlockOrSingleStatement)>::new();
+	let mut last = None;
+	let mut declarations = Vec::new();
+	let mut taken = false;
+	for (condition, inner) in branches {
+		let truthy = match &condition {
+			Some(condition) if condition.lhs.is_none() => truthiness(&condition.rhs),
+			Some(_) => None,
+			None => Some(true),
+		};
+		match truthy {
+			_ if taken => var_declarations(branch_ref(&inner), functions, &mut declarations),
+			Some(true) => {
+				last = Some(inner);
+				taken = true;
+			}
+			Some(false) => var_declarations(branch_ref(&inner), functions, &mut declarations),
+			None => kept.push((condition.unwrap(), inner)),
+		}
+	}
+
+	let mut items = Vec::new();
+	let mut kept = kept.into_iter();
+	match kept.next() {
+		Some((condition, inner)) => {
+			let else_conditions = kept
+				.map(|(condition, inner)| ConditionalElseStatement {
+					condition,
+					inner,
+					position: position.clone(),
+				})
+				.collect();
+			let trailing_else =
+				last.map(|inner| UnconditionalElseStatement { inner, position: position.clone() });
+			items.push(StatementOrDeclaration::Statement(Statement::IfStatement(IfStatement {
+				condition,
+				inner,
+				else_conditions,
+				trailing_else,
+				position,
+			})));
+		}
+		None => match last {
+			Some(BlockOrSingleStatement::Braced(block)) => {
+				// Blocks with `let`, `const` etc are kept so that the names are not in scope after
+				let mut lexical = Vec::new();
+				lexical_declarations(&block.0, functions, &mut lexical);
+				if lexical.is_empty() {
+					items.extend(block.0);
+				} else {
+					items.push(StatementOrDeclaration::Statement(Statement::Block(block)));
+				}
+			}
+			Some(BlockOrSingleStatement::SingleStatement(statement)) => {
+				items.push(StatementOrDeclaration::Statement(*statement));
+			}
+			None => {}
+		},
+	}
+	items.extend(declare_vars(declarations));
+	items
+}
+
+fn branch_ref(inner: &BlockOrSingleStatement) -> NodeRef<'_> {
+	match inner {
+		BlockOrSingleStatement::Braced(block) => NodeRef::Block(block),
+		BlockOrSingleStatement::SingleStatement(statement) => NodeRef::Statement(statement),
+	}
+}
+
+/// `var a, b` for the names of removed `var` declarations. Identifiers keep their position, so
+/// that they are renamed with the rest of the binding
+fn declare_vars(mut declarations: Vec<(String, Span)>) -> Option<StatementOrDeclaration> {
+	let mut seen = std::collections::HashSet::new();
+	declarations.retain(|(name, _)| seen.insert(name.clone()));
+	let position = declarations.first()?.1.clone();
+	let declarations = declarations
+		.into_iter()
+		.map(|(name, position)| declaration_item(name, position, None))
+		.collect();
+	Some(StatementOrDeclaration::Statement(Statement::VarVariable(VarVariableStatement {
+		keyword: Keyword::new(position),
+		declarations,
+	})))
+}
@@ -0,0 +1,205 @@
+//! Replaces `const` bindings which are referenced once with their value, when the value is a
+//! literal, and removes their declaration. `const a = 2; f(a)` becomes `f(2)`. Only bindings which
+//! [super::mangle] would rename are inlined, and only when the reference is after the declaration
+
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use parser::{
+	declarations::VariableDeclaration,
+	expressions::{object_literal::ObjectLiteralMember, ExpressionId},
+	extractor::ExtractedFunctions,
+	operators::BinaryOperator,
+	BlockLikeMut, Chain, Declaration, Expression, PropertyId, PropertyKey, Span,
+	StatementOrDeclaration, VariableField, VariableIdentifier, VisitorsMut, WithComment,
+};
+
+use super::{
+	constants::{constant, fold},
+	scope::Scopes,
+};
+use crate::{
+	downlevel::{is_literal, parenthesize},
+	error_handling::TempDiagnostic,
+};
+
+/// Passes for [crate::temp::Transform::InlineConstants]
+pub(crate) fn passes() -> Vec<VisitorsMut<Vec<TempDiagnostic>>> {
+	let inlined = Rc::new(RefCell::new(Inlined::default()));
+	let remove = VisitorsMut {
+		block_visitors_mut: vec![Box::new(RemoveDeclarations {
+			inlined: inlined.clone(),
+			is_module: true,
+		})],
+		..Default::default()
+	};
+	let replace = VisitorsMut {
+		expression_visitors_mut: vec![Box::new(ReplaceReferences(inlined))],
+		..Default::default()
+	};
+	vec![remove, replace]
+}
+
+#[derive(Default)]
+struct Inlined {
+	/// The position of the reference of each binding to inline, by the position of its declaration
+	references: HashMap<(u32, String), u32>,
+	/// Values of removed declarations, by the position of their reference
+	values: HashMap<(u32, String), Expression>,
+}
+
+impl Inlined {
+	fn take(&mut self, name: &str, position: &Span) -> Option<Expression> {
+		self.values.remove(&(position.start, name.to_owned()))
+	}
+}
+
+/// Finds the bindings to inline (from the module, the first block visited) and removes their
+/// declarations
+struct RemoveDeclarations {
+	inlined: Rc<RefCell<Inlined>>,
+	is_module: bool,
+}
+
+impl<'a> parser::VisitorMut<BlockLikeMut<'a>, Vec<TempDiagnostic>> for RemoveDeclarations {
+	fn visit_mut(
+		&mut self,
+		item: &mut BlockLikeMut<'a>,
+		_data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		let inlined = &mut *self.inlined.borrow_mut();
+		if std::mem::take(&mut self.is_module) {
+			inlined.references = single_use_constants(&Scopes::new(item.items, functions));
+		}
+		if inlined.references.is_empty() {
+			return;
+		}
+
+		item.items.retain_mut(|item| {
+			let StatementOrDeclaration::Declaration(Declaration::Variable(
+				VariableDeclaration::ConstDeclaration { declarations, .. },
+			)) = item
+			else {
+				return true;
+			};
+			declarations.retain_mut(|declaration| {
+				let VariableField::Name(VariableIdentifier::Standard(name, _, position)) =
+					declaration.name.get_ast()
+				else {
+					return true;
+				};
+				let key = (position.start, name.clone());
+				let Some(&reference) = inlined.references.get(&key) else { return true };
+				// Values are folded here as inlining runs before folding, e.g. for `60 * 60`
+				fold(&mut declaration.expression);
+				if !is_literal(&declaration.expression)
+					&& constant(&declaration.expression).is_none()
+				{
+					return true;
+				}
+				let value = std::mem::replace(
+					&mut declaration.expression,
+					Expression::Null(position.clone(), ExpressionId::new()),
+				);
+				inlined.values.insert((reference, key.1), value);
+				false
+			});
+			!declarations.is_empty()
+		});
+	}
+}
+
+/// Bindings which can be inlined, with the position of their only reference
+fn single_use_constants(scopes: &Scopes) -> HashMap<(u32, String), u32> {
+	// Identifiers of different bindings with the same name and position cannot be told apart
+	let mut counts = HashMap::<_, usize>::new();
+	for binding in scopes.bindings.iter() {
+		for position in binding.occurrences() {
+			*counts.entry((position.start, binding.name.as_str())).or_default() += 1;
+		}
+	}
+
+	let mut constants = HashMap::new();
+	for binding in scopes.bindings.iter() {
+		let ([declaration], [reference]) = (&binding.declarations[..], &binding.references[..])
+		else {
+			continue;
+		};
+		let inlinable = binding.constant
+			&& !binding.written
+			&& binding.scope != 0
+			&& !scopes.scopes[binding.scope].has_eval
+			&& !declaration.is_null()
+			&& !reference.is_null()
+			&& reference.start > declaration.start
+			&& binding
+				.occurrences()
+				.all(|position| counts[&(position.start, binding.name.as_str())] == 1);
+		if inlinable {
+			constants.insert((declaration.start, binding.name.clone()), reference.start);
+		}
+	}
+	constants
+}
+
+struct ReplaceReferences(Rc<RefCell<Inlined>>);
+
+impl parser::VisitorMut<Expression, Vec<TempDiagnostic>> for ReplaceReferences {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		_data: &mut Vec<TempDiagnostic>,
+		_functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		let mut inlined = self.0.borrow_mut();
+		if inlined.values.is_empty() {
+			return;
+		}
+		match item {
+			// Numbers are grouped for `(1).toString()` and `(-1) ** 2`
+			Expression::PropertyAccess { parent: target, .. }
+			| Expression::Index { indexee: target, .. }
+			| Expression::FunctionCall { function: target, .. }
+			| Expression::BinaryOperation {
+				lhs: target, operator: BinaryOperator::Exponent, ..
+			} => {
+				if let Expression::VariableReference(name, position, _) = target.as_mut() {
+					if let Some(value) = inlined.take(name, position) {
+						let position = position.clone();
+						**target = match value {
+							value @ (Expression::NumberLiteral(..)
+							| Expression::UnaryOperation { .. }) => parenthesize(value, &position),
+							value => value,
+						};
+					}
+				}
+			}
+			Expression::VariableReference(name, position, _) => {
+				if let Some(value) = inlined.take(name, position) {
+					*item = value;
+				}
+			}
+			Expression::ObjectLiteral(object) => {
+				for member in object.members.iter_mut() {
+					let ObjectLiteralMember::Shorthand(name, position, ..) = member else {
+						continue;
+					};
+					let Some(value) = inlined.take(name, position) else { continue };
+					// `{ x }` becomes `{ x: 2 }`
+					*member = ObjectLiteralMember::Property(
+						WithComment::None(PropertyKey::Ident(
+							name.clone(),
+							PropertyId::new(),
+							position.clone(),
+						)),
+						value,
+						position.clone(),
+					);
+				}
+			}
+			_ => {}
+		}
+	}
+}
@@ -0,0 +1,222 @@
+//! Renames bindings to the shortest names which are free, using [Renamer]. Bindings used most are
+//! named first so that they get the shortest names. Names are reused across sibling scopes but a
+//! binding never shadows one from an enclosing scope, a global it references or a binding which is
+//! not renamed.
+//!
+//! Top level bindings are kept as they may be exported or used by other scripts, as are bindings
+//! in scopes with a direct `eval` (and the scopes it is in) as the evaluated code can reference
+//! them. Bindings whose identifiers cannot be found again by position (e.g. added by lowering) are
+//! also kept
+
+use std::{
+	cell::RefCell,
+	collections::{HashMap, HashSet},
+	rc::Rc,
+};
+
+use parser::{extractor::ExtractedFunctions, BlockLikeMut, Chain, Span, VisitorsMut};
+
+use super::scope::Scopes;
+use crate::{
+	bundle::{Renamer, Renames},
+	error_handling::TempDiagnostic,
+};
+
+/// Characters names can start with, in the order they are used
+const FIRST: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ_$";
+
+/// Characters after the first
+const REST: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ_$0123456789";
+
+/// Names which cannot be declared (or which would be confusing to)
+const RESERVED: &[&str] = &[
+	"as",
+	"do",
+	"if",
+	"in",
+	"of",
+	"for",
+	"get",
+	"let",
+	"new",
+	"set",
+	"try",
+	"var",
+	"case",
+	"else",
+	"enum",
+	"eval",
+	"from",
+	"null",
+	"this",
+	"true",
+	"void",
+	"with",
+	"async",
+	"await",
+	"break",
+	"catch",
+	"class",
+	"const",
+	"false",
+	"super",
+	"throw",
+	"while",
+	"yield",
+	"delete",
+	"export",
+	"import",
+	"public",
+	"return",
+	"static",
+	"switch",
+	"typeof",
+	"default",
+	"extends",
+	"finally",
+	"package",
+	"private",
+	"continue",
+	"debugger",
+	"function",
+	"arguments",
+	"interface",
+	"protected",
+	"implements",
+	"instanceof",
+	"undefined",
+	"NaN",
+	"Infinity",
+];
+
+/// Passes for [crate::temp::Transform::MangleNames]
+pub(crate) fn passes() -> Vec<VisitorsMut<Vec<TempDiagnostic>>> {
+	let renamer = Renamer(Rc::new(RefCell::new(Renames::default())));
+	let choose = VisitorsMut {
+		block_visitors_mut: vec![Box::new(ChooseNames {
+			renames: renamer.0.clone(),
+			is_module: true,
+		})],
+		..Default::default()
+	};
+	let rename = VisitorsMut {
+		expression_visitors_mut: vec![Box::new(renamer.clone())],
+		statement_visitors_mut: vec![Box::new(renamer.clone())],
+		jsx_element_visitors_mut: vec![Box::new(renamer.clone())],
+		block_visitors_mut: vec![Box::new(renamer)],
+		..Default::default()
+	};
+	vec![choose, rename]
+}
+
+/// Analyses the module, which is the first block visited
+struct ChooseNames {
+	renames: Rc<RefCell<Renames>>,
+	is_module: bool,
+}
+
+impl<'a> parser::VisitorMut<BlockLikeMut<'a>, Vec<TempDiagnostic>> for ChooseNames {
+	fn visit_mut(
+		&mut self,
+		item: &mut BlockLikeMut<'a>,
+		_data: &mut Vec<TempDiagnostic>,
+		functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		if std::mem::take(&mut self.is_module) {
+			let scopes = Scopes::new(item.items, functions);
+			self.renames.borrow_mut().names = choose_names(&scopes);
+		}
+	}
+}
+
+/// The new name of each identifier of a renamed binding
+fn choose_names(scopes: &Scopes) -> HashMap<(u32, String), String> {
+	let Scopes { scopes, bindings } = scopes;
+
+	let mut kept = bindings
+		.iter()
+		.map(|binding| {
+			binding.scope == 0
+				|| scopes[binding.scope].has_eval
+				|| binding.occurrences().any(Span::is_null)
+		})
+		.collect::<Vec<_>>();
+	// Identifiers of different bindings with the same name and position cannot be told apart
+	let mut identifiers = HashMap::new();
+	for (index, binding) in bindings.iter().enumerate() {
+		for position in binding.occurrences() {
+			let key = (position.start, binding.name.as_str());
+			if let Some(other) = identifiers.insert(key, index) {
+				if other != index {
+					kept[index] = true;
+					kept[other] = true;
+				}
+			}
+		}
+	}
+
+	// Names which bindings of each scope cannot be renamed to, because they are referenced or
+	// declared (and kept) in the scope or the scopes inside of it
+	let mut reserved = scopes.iter().map(|scope| scope.free.clone()).collect::<Vec<_>>();
+	for (binding, _) in bindings.iter().zip(&kept).filter(|(_, kept)| **kept) {
+		let mut scope = Some(binding.scope);
+		while let Some(index) = scope {
+			reserved[index].insert(binding.name.clone());
+			scope = scopes[index].parent;
+		}
+	}
+
+	let mut names = bindings.iter().map(|binding| binding.name.clone()).collect::<Vec<_>>();
+	// Parents are before their children, so the bindings they are in are already named
+	for (index, scope) in scopes.iter().enumerate() {
+		let mut unavailable = reserved[index].iter().map(String::as_str).collect::<HashSet<_>>();
+		let mut parent = scope.parent;
+		while let Some(index) = parent {
+			unavailable
+				.extend(scopes[index].bindings.iter().map(|binding| names[*binding].as_str()));
+			parent = scopes[index].parent;
+		}
+
+		let mut order =
+			scope.bindings.iter().copied().filter(|binding| !kept[*binding]).collect::<Vec<_>>();
+		order.sort_by_key(|binding| std::cmp::Reverse(bindings[*binding].occurrences().count()));
+		let mut chosen = Vec::new();
+		let mut next = 0;
+		for binding in order {
+			let name = loop {
+				let name = short_name(next);
+				next += 1;
+				if !unavailable.contains(name.as_str()) && !RESERVED.contains(&name.as_str()) {
+					break name;
+				}
+			};
+			chosen.push((binding, name));
+		}
+		for (binding, name) in chosen {
+			names[binding] = name;
+		}
+	}
+
+	let mut renames = HashMap::new();
+	for (binding, name) in bindings.iter().zip(names) {
+		if binding.name != name {
+			for position in binding.occurrences() {
+				renames.insert((position.start, binding.name.clone()), name.clone());
+			}
+		}
+	}
+	renames
+}
+
+/// The name for `index`, `a` to `$` then `aa`, `ba` etc
+fn short_name(mut index: usize) -> String {
+	let mut name = String::from(FIRST[index % FIRST.len()] as char);
+	index /= FIRST.len();
+	while index > 0 {
+		index -= 1;
+		name.push(REST[index % REST.len()] as char);
+		index /= REST.len();
+	}
+	name
+}
@@ -0,0 +1,26 @@
+//! Minification for `--minify`, in addition to printing without whitespace and comments. Each
+//! step is a separate [Transform] so that they can also be selected with `--transform`. They run
+//! after every other transform, so that lowered code is minified as well
+//!
+//! Replacement nodes keep the position of the code they replace, so source maps still point to
+//! the original code
+
+pub(crate) mod conditionals;
+pub(crate) mod constants;
+pub(crate) mod dead_code;
+pub(crate) mod inline;
+pub(crate) mod mangle;
+pub(crate) mod scope;
+
+use crate::temp::Transform;
+
+/// Transforms run by `--minify`, in order. Inlining creates expressions to fold, which creates
+/// conditions for removing dead code. Names are mangled last as the other transforms remove
+/// bindings and references
+pub(crate) const TRANSFORMS: &[Transform] = &[
+	Transform::InlineConstants,
+	Transform::FoldConstants,
+	Transform::RemoveDeadCode,
+	Transform::CollapseConditionals,
+	Transform::MangleNames,
+];
@@ -0,0 +1,445 @@
+//! Resolves the identifiers of a module to the bindings they refer to, for renaming and inlining.
+//! Unlike [crate::bundle::ModuleScope] every scope is kept, not just the top level
+
+use std::collections::HashSet;
+
+use parser::{
+	declarations::{
+		export::Exportable, variable::VariableDeclarationKeyword, ExportDeclaration,
+		VariableDeclaration,
+	},
+	expressions::{
+		assignments::{LHSOfAssignment, VariableOrPropertyAccess},
+		object_literal::ObjectLiteralMember,
+	},
+	extractor::ExtractedFunctions,
+	statements::{ForLoopCondition, ForLoopStatementInitializer},
+	Declaration, Decorated, Expression, NodeRef, OptionalOrWithDefaultValueParameter, Span,
+	Statement, StatementOrDeclaration, TypeReference,
+};
+
+use crate::bundle::{
+	array_fields_identifiers, function_expression_name, identifier, is_component_name, is_function,
+	lexical_declarations, object_fields_identifiers, var_declarations,
+	variable_declaration_identifiers, variable_field_identifiers,
+};
+
+/// The scopes of a module, the first being its top level
+pub(crate) struct Scopes {
+	pub(crate) scopes: Vec<Scope>,
+	pub(crate) bindings: Vec<Binding>,
+}
+
+pub(crate) struct Scope {
+	/// Always before this scope in [Scopes::scopes]
+	pub(crate) parent: Option<usize>,
+	/// Bindings declared in the scope, in order of declaration
+	pub(crate) bindings: Vec<usize>,
+	/// Names referenced in this scope (or scopes inside of it) which are not declared in the
+	/// module, e.g. globals
+	pub(crate) free: HashSet<String>,
+	/// Whether there is a direct `eval` in this scope (or scopes inside of it), which can reference
+	/// any name in scope
+	pub(crate) has_eval: bool,
+	/// The scope of a function (or the module), which `var` declarations are hoisted to
+	pub(crate) function: bool,
+	/// Whether the scope is in a loop, not counting loops outside of the function it is in
+	pub(crate) in_loop: bool,
+}
+
+pub(crate) struct Binding {
+	pub(crate) name: String,
+	pub(crate) scope: usize,
+	/// Declared once with `const`
+	pub(crate) constant: bool,
+	/// Declared with `let` or `const`
+	pub(crate) lexical: bool,
+	/// Positions of the identifiers which declare the binding
+	pub(crate) declarations: Vec<Span>,
+	/// Positions of the identifiers which reference the binding
+	pub(crate) references: Vec<Span>,
+	/// Assigned to after being declared or used as a JSX tag, so references cannot be replaced
+	/// with a value
+	pub(crate) written: bool,
+	/// Referenced from a function inside of the scope it is declared in
+	pub(crate) captured: bool,
+}
+
+impl Binding {
+	pub(crate) fn occurrences(&self) -> impl Iterator<Item = &Span> {
+		self.declarations.iter().chain(self.references.iter())
+	}
+}
+
+impl Scopes {
+	pub(crate) fn new(items: &[StatementOrDeclaration], functions: &ExtractedFunctions) -> Self {
+		let mut analysis = ScopeAnalysis {
+			functions,
+			scopes: Scopes { scopes: Vec::new(), bindings: Vec::new() },
+			current: 0,
+			loops: 0,
+		};
+		analysis.enter();
+		analysis.scopes.scopes[0].function = true;
+		let mut declarations = Vec::new();
+		for item in items.iter() {
+			if let StatementOrDeclaration::Declaration(Declaration::Import(import)) = item {
+				// Only names are needed, top level bindings are never renamed or inlined
+				let parts = import.imports.iter().flatten().filter_map(crate::erasure::local_name);
+				let names = import.default_import.iter().map(String::as_str).chain(parts);
+				declarations.extend(names.map(|name| (name.to_owned(), Span::NULL_SPAN)));
+			}
+			var_declarations(NodeRef::from(item), functions, &mut declarations);
+		}
+		analysis.declare_all(declarations);
+		analysis.declare_lexical(items);
+		for item in items.iter() {
+			analysis.walk(NodeRef::from(item));
+		}
+		analysis.scopes
+	}
+}
+
+struct ScopeAnalysis<'a> {
+	functions: &'a ExtractedFunctions,
+	scopes: Scopes,
+	current: usize,
+	/// Loops around the node being walked, in the current function
+	loops: usize,
+}
+
+impl<'a> ScopeAnalysis<'a> {
+	fn enter(&mut self) {
+		let parent = (!self.scopes.scopes.is_empty()).then_some(self.current);
+		self.current = self.scopes.scopes.len();
+		self.scopes.scopes.push(Scope {
+			parent,
+			bindings: Vec::new(),
+			free: HashSet::new(),
+			has_eval: false,
+			function: false,
+			in_loop: self.loops > 0,
+		});
+	}
+
+	fn exit(&mut self) {
+		self.current = self.scopes.scopes[self.current].parent.expect("exited top level");
+	}
+
+	fn declare(&mut self, name: String, position: Span) {
+		let scope = &mut self.scopes.scopes[self.current];
+		let bindings = &mut self.scopes.bindings;
+		match scope.bindings.iter().find(|binding| bindings[**binding].name == name) {
+			Some(binding) => bindings[*binding].declarations.push(position),
+			None => {
+				scope.bindings.push(bindings.len());
+				bindings.push(Binding {
+					name,
+					scope: self.current,
+					constant: false,
+					lexical: false,
+					declarations: vec![position],
+					references: Vec::new(),
+					written: false,
+					captured: false,
+				});
+			}
+		}
+	}
+
+	fn declare_all(&mut self, declarations: Vec<(String, Span)>) {
+		for (name, position) in declarations {
+			self.declare(name, position);
+		}
+	}
+
+	/// Declares the `let`, `const`, `function` and `class` declarations of a block (exported or
+	/// not)
+	fn declare_lexical(&mut self, items: &[StatementOrDeclaration]) {
+		let mut declarations = Vec::new();
+		lexical_declarations(items, self.functions, &mut declarations);
+		let mut constants = Vec::new();
+		let mut lexical = Vec::new();
+		for item in items.iter() {
+			let StatementOrDeclaration::Declaration(declaration) = item else { continue };
+			let declaration = match declaration {
+				Declaration::Variable(declaration) => declaration,
+				Declaration::Export(Decorated {
+					on: ExportDeclaration::Variable { exported, .. },
+					..
+				}) => match exported {
+					Exportable::Variable(declaration) => {
+						variable_declaration_identifiers(declaration, &mut declarations);
+						declaration
+					}
+					Exportable::Function(function) => {
+						identifier(&function.name, &mut declarations);
+						continue;
+					}
+					Exportable::Class(class) => {
+						identifier(&class.name, &mut declarations);
+						continue;
+					}
+					_ => continue,
+				},
+				_ => continue,
+			};
+			match declaration {
+				VariableDeclaration::ConstDeclaration { .. } => {
+					variable_declaration_identifiers(declaration, &mut constants)
+				}
+				VariableDeclaration::LetDeclaration { .. } => {
+					variable_declaration_identifiers(declaration, &mut lexical)
+				}
+			}
+		}
+		self.declare_all(declarations);
+
+		for (name, _) in constants.iter() {
+			let binding = self.resolve(name).expect("constant not declared");
+			let binding = &mut self.scopes.bindings[binding];
+			binding.constant = binding.declarations.len() == 1;
+		}
+		self.mark_lexical(constants.into_iter().chain(lexical));
+	}
+
+	fn mark_lexical(&mut self, declarations: impl IntoIterator<Item = (String, Span)>) {
+		for (name, _) in declarations {
+			let binding = self.resolve(&name).expect("lexical binding not declared");
+			self.scopes.bindings[binding].lexical = true;
+		}
+	}
+
+	fn resolve(&self, name: &str) -> Option<usize> {
+		let mut scope = Some(self.current);
+		while let Some(index) = scope {
+			let current = &self.scopes.scopes[index];
+			let mut bindings = current.bindings.iter().copied();
+			if let Some(binding) =
+				bindings.find(|binding| self.scopes.bindings[*binding].name == name)
+			{
+				return Some(binding);
+			}
+			scope = current.parent;
+		}
+		None
+	}
+
+	/// Marks this scope and the scopes it is in
+	fn mark(&mut self, mut mark: impl FnMut(&mut Scope)) {
+		let mut scope = Some(self.current);
+		while let Some(index) = scope {
+			mark(&mut self.scopes.scopes[index]);
+			scope = self.scopes.scopes[index].parent;
+		}
+	}
+
+	fn reference(&mut self, name: &str, position: &Span, written: bool) {
+		match self.resolve(name) {
+			Some(binding) => {
+				let captured = self.crosses_function(self.scopes.bindings[binding].scope);
+				let binding = &mut self.scopes.bindings[binding];
+				binding.references.push(position.clone());
+				binding.written |= written;
+				binding.captured |= captured;
+			}
+			None => self.mark(|scope| {
+				scope.free.insert(name.to_owned());
+			}),
+		}
+	}
+
+	/// Whether there is a function between the current scope and the scope (which contains it)
+	fn crosses_function(&self, scope: usize) -> bool {
+		let mut current = self.current;
+		while current != scope {
+			let inner = &self.scopes.scopes[current];
+			if inner.function {
+				return true;
+			}
+			current = inner.parent.expect("scope not around current scope");
+		}
+		false
+	}
+
+	fn reference_extends(&mut self, extends: &Option<TypeReference>) {
+		if let Some(TypeReference::Name(name, position)) = extends {
+			self.reference(name, position, false);
+		}
+	}
+
+	/// Names assigned to by a destructuring assignment or `for ... of` without a declaration
+	fn write_all(&mut self, identifiers: Vec<(String, Span)>) {
+		for (name, position) in identifiers {
+			self.reference(&name, &position, true);
+		}
+	}
+
+	fn walk(&mut self, node: NodeRef<'a>) {
+		match node {
+			NodeRef::Expression(Expression::VariableReference(name, position, _))
+			| NodeRef::ObjectLiteralMember(ObjectLiteralMember::Shorthand(name, position, ..)) => {
+				self.reference(name, position, false)
+			}
+			NodeRef::VariableOrPropertyAccess(VariableOrPropertyAccess::Variable(
+				name,
+				position,
+				_,
+			)) => self.reference(name, position, true),
+			NodeRef::Expression(Expression::Assignment { lhs, .. }) => {
+				let mut identifiers = Vec::new();
+				match lhs {
+					LHSOfAssignment::ObjectDestructuring(fields, ..) => {
+						object_fields_identifiers(fields, &mut identifiers)
+					}
+					LHSOfAssignment::ArrayDestructuring(fields, ..) => {
+						array_fields_identifiers(fields, &mut identifiers)
+					}
+					LHSOfAssignment::VariableOrPropertyAccess(_) => {}
+				}
+				self.write_all(identifiers);
+			}
+			NodeRef::Expression(Expression::FunctionCall { function, .. }) => {
+				if let Expression::VariableReference(name, ..) = function.as_ref() {
+					if name == "eval" && self.resolve(name).is_none() {
+						self.mark(|scope| scope.has_eval = true);
+					}
+				}
+			}
+			NodeRef::Expression(Expression::ClassExpression(class, _)) => {
+				self.reference_extends(&class.extends);
+				if let Some(name) = &class.name {
+					// The name is only in scope inside of the class
+					let mut declarations = Vec::new();
+					identifier(name, &mut declarations);
+					self.enter();
+					self.declare_all(declarations);
+					for child in node.children(self.functions) {
+						self.walk(child);
+					}
+					self.exit();
+					return;
+				}
+			}
+			NodeRef::Declaration(Declaration::Class(class)) => {
+				self.reference_extends(&class.on.extends)
+			}
+			NodeRef::ClassDeclaration(class) => self.reference_extends(&class.extends),
+			NodeRef::JSXElement(element) if is_component_name(&element.tag_name) => {
+				self.reference(&element.tag_name, &element.position, true)
+			}
+			NodeRef::Statement(Statement::ForLoopStatement(for_statement)) => {
+				if let ForLoopCondition::ForOf { keyword: None, variable, .. }
+				| ForLoopCondition::ForIn { keyword: None, variable, .. } = &for_statement.condition
+				{
+					let mut identifiers = Vec::new();
+					variable_field_identifiers(variable.get_ast(), &mut identifiers);
+					self.write_all(identifiers);
+				}
+			}
+			NodeRef::Statement(Statement::TryCatchStatement(try_catch_statement)) => {
+				self.walk(NodeRef::Block(&try_catch_statement.try_inner));
+				// The exception variable is only in scope in the catch block
+				self.enter();
+				if let Some(exception_var) = &try_catch_statement.exception_var {
+					let mut declarations = Vec::new();
+					variable_field_identifiers(exception_var.get_ast(), &mut declarations);
+					self.declare_all(declarations);
+					self.walk(NodeRef::VariableField(exception_var.get_ast()));
+				}
+				if let Some(catch_inner) = &try_catch_statement.catch_inner {
+					self.walk(NodeRef::Block(catch_inner));
+				}
+				self.exit();
+				if let Some(finally_inner) = &try_catch_statement.finally_inner {
+					self.walk(NodeRef::Block(finally_inner));
+				}
+				return;
+			}
+			_ => {}
+		}
+
+		let children = node.children(self.functions);
+		let is_loop = matches!(
+			node,
+			NodeRef::Statement(
+				Statement::ForLoopStatement(_)
+					| Statement::WhileStatement(_)
+					| Statement::DoWhileStatement(_)
+			)
+		);
+		if is_loop {
+			self.loops += 1;
+		}
+		// Loops outside of a function do not repeat the declarations in it
+		let mut outer_loops = None;
+		let has_scope = match node {
+			NodeRef::Block(block) | NodeRef::Statement(Statement::Block(block)) => {
+				self.enter();
+				self.declare_lexical(&block.0);
+				true
+			}
+			NodeRef::Statement(Statement::ForLoopStatement(for_statement)) => {
+				let mut declarations = Vec::new();
+				match &for_statement.condition {
+					ForLoopCondition::ForOf { keyword: Some(keyword), variable, .. }
+					| ForLoopCondition::ForIn { keyword: Some(keyword), variable, .. }
+						if !matches!(keyword, VariableDeclarationKeyword::Var(_)) =>
+					{
+						variable_field_identifiers(variable.get_ast(), &mut declarations)
+					}
+					ForLoopCondition::Statements {
+						initializer: Some(ForLoopStatementInitializer::Statement(declaration)),
+						..
+					} => variable_declaration_identifiers(declaration, &mut declarations),
+					_ => {}
+				}
+				self.enter();
+				self.declare_all(declarations.clone());
+				self.mark_lexical(declarations);
+				true
+			}
+			node if is_function(node) => {
+				let mut declarations = Vec::new();
+				if let Some(name) = function_expression_name(node, self.functions) {
+					identifier(name, &mut declarations);
+				}
+				for child in children.iter().copied() {
+					match child {
+						NodeRef::Parameter(parameter) => {
+							variable_field_identifiers(parameter.name.get_ast(), &mut declarations)
+						}
+						NodeRef::OptionalParameter(
+							OptionalOrWithDefaultValueParameter::Optional { name, .. },
+						)
+						| NodeRef::SpreadParameter(parser::SpreadParameter { name, .. }) => {
+							identifier(name, &mut declarations)
+						}
+						NodeRef::OptionalParameter(
+							OptionalOrWithDefaultValueParameter::WithDefaultValue { name, .. },
+						) => variable_field_identifiers(name.get_ast(), &mut declarations),
+						child => var_declarations(child, self.functions, &mut declarations),
+					}
+				}
+				outer_loops = Some(std::mem::take(&mut self.loops));
+				self.enter();
+				self.scopes.scopes[self.current].function = true;
+				self.declare_all(declarations);
+				true
+			}
+			_ => false,
+		};
+		for child in children {
+			self.walk(child);
+		}
+		if has_scope {
+			self.exit();
+		}
+		if let Some(outer_loops) = outer_loops {
+			self.loops = outer_loops;
+		}
+		if is_loop {
+			self.loops -= 1;
+		}
+	}
+}
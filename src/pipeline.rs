@@ -0,0 +1,188 @@
+//! A [Pipeline] builds modules by running an ordered list of named passes over each of them.
+//!
+//! The built-in [Transform]s are registered in every pipeline and more passes can be registered
+//! from Rust with [Pipeline::register]. Passes are selected by name in [BuildSettings::transforms],
+//! which the CLI fills from `--transform` or `"transforms"` in the configuration (where
+//! `["name", { ... }]` passes options to a registered pass). Hooks run on each module before and
+//! after its passes and, like passes, can add diagnostics to the build
+
+use std::path::{Path, PathBuf};
+
+use parser::{extractor::ExtractedFunctions, source_map::MapFileStore, Module, VisitorsMut};
+
+pub use crate::{
+	error_handling::{DiagnosticCode, ErrorWarningInfo, TempDiagnostic},
+	json::JSONValue,
+	temp::{BuildOutput, BuildSettings, Output, Transform},
+};
+use crate::{resolver::Resolver, temp::IncrementalBuild};
+
+/// Visitors which traverse a module once. Diagnostics are added to the data
+pub type Visitors = VisitorsMut<Vec<TempDiagnostic>>;
+
+/// What passes are created with, for each module
+pub struct PassContext<'a> {
+	pub settings: &'a BuildSettings,
+	/// From `["name", options]` in `"transforms"` of the configuration
+	pub options: Option<&'a JSONValue>,
+	/// The module the pass runs on
+	pub path: &'a Path,
+}
+
+type CreateVisitors = Box<dyn Fn(&PassContext) -> Vec<Visitors>>;
+
+/// A named transform, which creates visitors for each module
+pub struct Pass {
+	name: &'static str,
+	create: CreateVisitors,
+}
+
+impl Pass {
+	pub fn new(name: &'static str, create: impl Fn(&PassContext) -> Visitors + 'static) -> Self {
+		Self { name, create: Box::new(move |context| vec![create(context)]) }
+	}
+
+	/// A pass which traverses each module more than once, with each [Visitors] in order. For when
+	/// a later traversal depends on everything the earlier one did
+	pub fn with_traversals(
+		name: &'static str,
+		create: impl Fn(&PassContext) -> Vec<Visitors> + 'static,
+	) -> Self {
+		Self { name, create: Box::new(create) }
+	}
+
+	pub fn name(&self) -> &'static str {
+		self.name
+	}
+}
+
+/// A module being built, passed to hooks
+pub struct File<'a> {
+	pub path: &'a Path,
+	pub module: &'a mut Module,
+	pub functions: &'a mut ExtractedFunctions,
+	/// Diagnostics of the build so far
+	pub diagnostics: &'a mut Vec<TempDiagnostic>,
+}
+
+type Hook = Box<dyn FnMut(&mut File)>;
+
+/// Builds modules with [BuildSettings], the registered passes and hooks
+pub struct Pipeline {
+	pub(crate) settings: BuildSettings,
+	/// Built-in transforms followed by registered passes
+	passes: Vec<Pass>,
+	before_each_file: Vec<Hook>,
+	after_each_file: Vec<Hook>,
+}
+
+impl Pipeline {
+	pub fn new(settings: BuildSettings) -> Self {
+		let passes = Transform::ALL
+			.iter()
+			.map(|transform| {
+				Pass::with_traversals(transform.name(), |context| {
+					transform.passes(context.settings)
+				})
+			})
+			.collect();
+		Self { settings, passes, before_each_file: Vec::new(), after_each_file: Vec::new() }
+	}
+
+	/// Adds a pass which can be selected by its name. A pass with the same name is replaced
+	pub fn register(mut self, pass: Pass) -> Self {
+		self.passes.retain(|existing| existing.name != pass.name);
+		self.passes.push(pass);
+		self
+	}
+
+	/// Runs `hook` on each module before its passes
+	pub fn before_each_file(mut self, hook: impl FnMut(&mut File) + 'static) -> Self {
+		self.before_each_file.push(Box::new(hook));
+		self
+	}
+
+	/// Runs `hook` on each module after its passes, before it is printed
+	pub fn after_each_file(mut self, hook: impl FnMut(&mut File) + 'static) -> Self {
+		self.after_each_file.push(Box::new(hook));
+		self
+	}
+
+	pub fn settings(&self) -> &BuildSettings {
+		&self.settings
+	}
+
+	/// Names of the passes which can be selected
+	pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+		self.passes.iter().map(Pass::name)
+	}
+
+	/// Checks that every selected transform has been registered
+	pub fn validate(&self) -> Result<(), String> {
+		match self.settings.transforms.iter().find(|transform| self.pass(transform).is_none()) {
+			Some(transform) => Err(format!(
+				"Unknown transform '{}', expected one of {}",
+				transform.name(),
+				self.names().collect::<Vec<_>>().join(", ")
+			)),
+			None => Ok(()),
+		}
+	}
+
+	/// Builds the entry point and every module in the project reachable from it through `import`s
+	/// and `import()`s. `read_from_path` returns the content of other modules (or `None` if they
+	/// do not exist).
+	///
+	/// If only one module is built and `output_path` is not a directory, it is written to
+	/// `output_path`. Otherwise the output mirrors the source tree under `output_path`
+	///
+	/// # Panics
+	/// If a selected transform is not registered, see [Pipeline::validate]
+	pub fn build(
+		&mut self,
+		entry_path: PathBuf,
+		entry_content: String,
+		resolver: &Resolver,
+		read_from_path: &impl Fn(&Path) -> Option<String>,
+		output_path: PathBuf,
+	) -> (MapFileStore, Result<BuildOutput, Vec<TempDiagnostic>>) {
+		IncrementalBuild::new(output_path).rebuild(
+			self,
+			vec![(entry_path, entry_content)],
+			resolver,
+			read_from_path,
+		)
+	}
+
+	fn pass(&self, transform: &Transform) -> Option<&Pass> {
+		self.passes.iter().find(|pass| pass.name == transform.name())
+	}
+
+	/// The name and visitors of a selected transform for the module at `path`
+	pub(crate) fn passes(
+		&self,
+		transform: &Transform,
+		path: &Path,
+	) -> (&'static str, Vec<Visitors>) {
+		let pass =
+			self.pass(transform).unwrap_or_else(|| panic!("{}", self.validate().unwrap_err()));
+		let options = match transform {
+			Transform::Pass { options, .. } => options.as_ref(),
+			_ => None,
+		};
+		let context = PassContext { settings: &self.settings, options, path };
+		(pass.name, (pass.create)(&context))
+	}
+
+	pub(crate) fn before_file(&mut self, file: &mut File) {
+		for hook in self.before_each_file.iter_mut() {
+			hook(file);
+		}
+	}
+
+	pub(crate) fn after_file(&mut self, file: &mut File) {
+		for hook in self.after_each_file.iter_mut() {
+			hook(file);
+		}
+	}
+}
@@ -0,0 +1,563 @@
+//! Resolves import specifiers to files, following Node's algorithm with TypeScript's additions.
+//!
+//! Files are only accessed through a `read_from_path` function (which returns `None` for paths which
+//! are not files) so that resolution works the same on disk, in tests and in WASM.
+
+use std::{
+	ffi::OsStr,
+	fmt,
+	path::{Component, Path, PathBuf},
+};
+
+use crate::{
+	json::{self, JSONValue},
+	utilities::{glob_matches, normalize_path},
+};
+
+/// Extensions tried (in order) for paths which are not files as is
+pub const DEFAULT_EXTENSIONS: &[&str] =
+	&["ts", "tsx", "d.ts", "js", "jsx", "mts", "cts", "mjs", "cjs"];
+
+/// Limit on `extends` chains in `tsconfig.json`, so that cycles terminate
+const MAX_EXTENDS_DEPTH: u8 = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolveError {
+	NotFound {
+		specifier: String,
+	},
+	/// The package has `exports` but the subpath is not in it
+	NotExported {
+		package: String,
+		subpath: String,
+	},
+	/// A `package.json` or `tsconfig.json` could not be read
+	InvalidConfig {
+		path: PathBuf,
+		reason: String,
+	},
+}
+
+impl fmt::Display for ResolveError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ResolveError::NotFound { specifier } => {
+				write!(f, "Could not find module '{specifier}'")
+			}
+			ResolveError::NotExported { package, subpath } => {
+				write!(f, "'{subpath}' is not exported from package '{package}'")
+			}
+			ResolveError::InvalidConfig { path, reason } => {
+				write!(f, "Invalid {}: {reason}", path.display())
+			}
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedModule {
+	pub path: PathBuf,
+	pub content: String,
+}
+
+/// `compilerOptions.baseUrl` and `compilerOptions.paths` from a `tsconfig.json`
+#[derive(Debug, Clone, Default)]
+pub struct PathMappings {
+	/// Directory non-relative specifiers are also looked up in
+	pub base_url: Option<PathBuf>,
+	/// Patterns (with at most one `*`) and the paths they map to
+	pub paths: Vec<(String, Vec<String>)>,
+	/// Directory of the `tsconfig.json` which declared `paths`. They are relative to this if
+	/// there is no `base_url`
+	pub paths_directory: PathBuf,
+}
+
+#[derive(Debug, Clone)]
+pub struct Resolver {
+	/// Conditions matched against `exports` and `imports` in `package.json`. `default` always
+	/// matches. If it contains `types`, `types` is preferred over `main`
+	pub conditions: Vec<String>,
+	/// Extensions tried, in order, for paths which are not files
+	pub extensions: Vec<String>,
+	pub path_mappings: PathMappings,
+}
+
+impl Default for Resolver {
+	fn default() -> Self {
+		Self {
+			conditions: vec!["import".to_owned()],
+			extensions: DEFAULT_EXTENSIONS
+				.iter()
+				.map(|extension| (*extension).to_owned())
+				.collect(),
+			path_mappings: PathMappings::default(),
+		}
+	}
+}
+
+impl Resolver {
+	/// Path of the closest `tsconfig.json` in `directory` or its ancestors
+	pub fn find_ts_config(
+		directory: &Path,
+		read_from_path: &impl Fn(&Path) -> Option<String>,
+	) -> Option<PathBuf> {
+		ancestors(directory)
+			.into_iter()
+			.map(|directory| directory.join("tsconfig.json"))
+			.find(|path| read_from_path(path).is_some())
+	}
+
+	/// Uses the path mappings of a `tsconfig.json` (and the configurations it `extends`)
+	pub fn with_ts_config(
+		mut self,
+		path: &Path,
+		read_from_path: &impl Fn(&Path) -> Option<String>,
+	) -> Result<Self, ResolveError> {
+		self.path_mappings = read_ts_config(path, read_from_path, 0)?;
+		Ok(self)
+	}
+
+	/// Resolves `specifier` imported from the file at `importer`
+	pub fn resolve(
+		&self,
+		specifier: &str,
+		importer: &Path,
+		read_from_path: &impl Fn(&Path) -> Option<String>,
+	) -> Result<ResolvedModule, ResolveError> {
+		let directory = importer.parent().unwrap_or(Path::new(""));
+		let not_found = || ResolveError::NotFound { specifier: specifier.to_owned() };
+
+		if specifier.starts_with('#') {
+			return self
+				.resolve_package_import(specifier, directory, read_from_path)?
+				.ok_or_else(not_found);
+		}
+
+		if is_relative(specifier) || Path::new(specifier).is_absolute() {
+			let path = normalize_path(&directory.join(specifier));
+			return self.resolve_path(&path, read_from_path)?.ok_or_else(not_found);
+		}
+
+		if let Some(resolved) = self.resolve_mapped(specifier, read_from_path)? {
+			return Ok(resolved);
+		}
+
+		self.resolve_package(specifier, directory, read_from_path)?.ok_or_else(not_found)
+	}
+
+	/// A file or a directory
+	fn resolve_path(
+		&self,
+		path: &Path,
+		read_from_path: &impl Fn(&Path) -> Option<String>,
+	) -> Result<Option<ResolvedModule>, ResolveError> {
+		if let Some(resolved) = self.resolve_file(path, read_from_path) {
+			return Ok(Some(resolved));
+		}
+		self.resolve_directory(path, read_from_path)
+	}
+
+	fn resolve_file(
+		&self,
+		path: &Path,
+		read_from_path: &impl Fn(&Path) -> Option<String>,
+	) -> Option<ResolvedModule> {
+		let read =
+			|path: PathBuf| read_from_path(&path).map(|content| ResolvedModule { path, content });
+
+		if let Some(resolved) = read(path.to_owned()) {
+			return Some(resolved);
+		}
+
+		// TypeScript sources are imported with the extension they are compiled to
+		let source_extensions: &[&str] = match path.extension().and_then(OsStr::to_str) {
+			Some("js") => &["ts", "tsx", "d.ts"],
+			Some("jsx") => &["tsx"],
+			Some("mjs") => &["mts", "d.mts"],
+			Some("cjs") => &["cts", "d.cts"],
+			_ => &[],
+		};
+		let without_extension = path.with_extension("");
+		for extension in source_extensions {
+			if let Some(resolved) = read(with_added_extension(&without_extension, extension)) {
+				return Some(resolved);
+			}
+		}
+
+		self.extensions.iter().find_map(|extension| read(with_added_extension(path, extension)))
+	}
+
+	/// The entry point in `package.json` or an index file
+	fn resolve_directory(
+		&self,
+		path: &Path,
+		read_from_path: &impl Fn(&Path) -> Option<String>,
+	) -> Result<Option<ResolvedModule>, ResolveError> {
+		if let Some(package) = read_package(path, read_from_path)? {
+			for entry in self.package_entries(&package) {
+				let entry = normalize_path(&path.join(entry));
+				if let Some(resolved) = self.resolve_file(&entry, read_from_path) {
+					return Ok(Some(resolved));
+				}
+				if let Some(resolved) = self.resolve_index(&entry, read_from_path) {
+					return Ok(Some(resolved));
+				}
+			}
+		}
+		Ok(self.resolve_index(path, read_from_path))
+	}
+
+	fn resolve_index(
+		&self,
+		directory: &Path,
+		read_from_path: &impl Fn(&Path) -> Option<String>,
+	) -> Option<ResolvedModule> {
+		self.extensions.iter().find_map(|extension| {
+			let path = directory.join(format!("index.{extension}"));
+			read_from_path(&path).map(|content| ResolvedModule { path, content })
+		})
+	}
+
+	/// `types` (if looking for types) and `main` fields of a `package.json`
+	fn package_entries<'a>(&self, package: &'a JSONValue) -> Vec<&'a str> {
+		let fields: &[&str] = if self.conditions.iter().any(|condition| condition == "types") {
+			&["types", "typings", "main"]
+		} else {
+			&["main"]
+		};
+		fields.iter().filter_map(|field| package.get(field)?.as_str()).collect()
+	}
+
+	/// `paths` and `baseUrl` from `tsconfig.json`
+	fn resolve_mapped(
+		&self,
+		specifier: &str,
+		read_from_path: &impl Fn(&Path) -> Option<String>,
+	) -> Result<Option<ResolvedModule>, ResolveError> {
+		let PathMappings { base_url, paths, paths_directory } = &self.path_mappings;
+		let paths_directory = base_url.as_ref().unwrap_or(paths_directory);
+
+		if let Some((targets, star)) =
+			match_pattern(paths.iter().map(|(pattern, targets)| (pattern, targets)), specifier)
+		{
+			for target in targets {
+				let target = match star {
+					Some(star) => target.replacen('*', star, 1),
+					None => target.clone(),
+				};
+				let path = normalize_path(&paths_directory.join(target));
+				if let Some(resolved) = self.resolve_path(&path, read_from_path)? {
+					return Ok(Some(resolved));
+				}
+			}
+		}
+
+		if let Some(base_url) = base_url {
+			return self.resolve_path(&normalize_path(&base_url.join(specifier)), read_from_path);
+		}
+		Ok(None)
+	}
+
+	/// A package in a `node_modules` folder of `directory` or its ancestors
+	fn resolve_package(
+		&self,
+		specifier: &str,
+		directory: &Path,
+		read_from_path: &impl Fn(&Path) -> Option<String>,
+	) -> Result<Option<ResolvedModule>, ResolveError> {
+		let (name, subpath) = split_package_specifier(specifier);
+		let looking_for_types = self.conditions.iter().any(|condition| condition == "types");
+
+		for directory in ancestors(directory) {
+			let node_modules = directory.join("node_modules");
+			let mut package_directories = vec![node_modules.join(name)];
+			if looking_for_types {
+				// `@scope/name` is published as `@types/scope__name`
+				let types_name = name.trim_start_matches('@').replace('/', "__");
+				package_directories.push(node_modules.join("@types").join(types_name));
+			}
+
+			for package_directory in package_directories {
+				let Some(package) = read_package(&package_directory, read_from_path)? else {
+					// Packages without a `package.json`
+					let path = normalize_path(&package_directory.join(&subpath));
+					if let Some(resolved) = self.resolve_path(&path, read_from_path)? {
+						return Ok(Some(resolved));
+					}
+					continue;
+				};
+
+				if let Some(exports) = package.get("exports") {
+					let resolved =
+						self.resolve_exports(&package_directory, exports, &subpath, read_from_path);
+					return match resolved {
+						Some(resolved) => Ok(Some(resolved)),
+						None => {
+							Err(ResolveError::NotExported { package: name.to_owned(), subpath })
+						}
+					};
+				}
+
+				if subpath == "." {
+					return self.resolve_directory(&package_directory, read_from_path);
+				}
+				let path = normalize_path(&package_directory.join(&subpath));
+				return self.resolve_path(&path, read_from_path);
+			}
+		}
+		Ok(None)
+	}
+
+	fn resolve_exports(
+		&self,
+		package_directory: &Path,
+		exports: &JSONValue,
+		subpath: &str,
+		read_from_path: &impl Fn(&Path) -> Option<String>,
+	) -> Option<ResolvedModule> {
+		let is_subpath_map = matches!(
+			exports,
+			JSONValue::Object(entries) if entries.iter().any(|(key, _)| key.starts_with('.'))
+		);
+		if let (true, JSONValue::Object(entries)) = (is_subpath_map, exports) {
+			let (target, star) =
+				match_pattern(entries.iter().map(|(key, value)| (key, value)), subpath)?;
+			self.resolve_target(package_directory, target, star, read_from_path)
+		} else if subpath == "." {
+			self.resolve_target(package_directory, exports, None, read_from_path)
+		} else {
+			None
+		}
+	}
+
+	/// `#name` specifiers, which are mapped in the `imports` of the closest `package.json`
+	fn resolve_package_import(
+		&self,
+		specifier: &str,
+		directory: &Path,
+		read_from_path: &impl Fn(&Path) -> Option<String>,
+	) -> Result<Option<ResolvedModule>, ResolveError> {
+		for directory in ancestors(directory) {
+			let Some(package) = read_package(&directory, read_from_path)? else { continue };
+			let Some(JSONValue::Object(imports)) = package.get("imports") else {
+				return Ok(None);
+			};
+			let Some((target, star)) =
+				match_pattern(imports.iter().map(|(key, value)| (key, value)), specifier)
+			else {
+				return Ok(None);
+			};
+			return Ok(self.resolve_target(&directory, target, star, read_from_path));
+		}
+		Ok(None)
+	}
+
+	/// A value in `exports` or `imports`. Either a path, a list of alternatives or conditions
+	fn resolve_target(
+		&self,
+		package_directory: &Path,
+		target: &JSONValue,
+		star: Option<&str>,
+		read_from_path: &impl Fn(&Path) -> Option<String>,
+	) -> Option<ResolvedModule> {
+		match target {
+			JSONValue::String(target) => {
+				let target = match star {
+					Some(star) => target.replace('*', star),
+					None => target.clone(),
+				};
+				if let Some(relative) = target.strip_prefix("./") {
+					self.resolve_file(
+						&normalize_path(&package_directory.join(relative)),
+						read_from_path,
+					)
+				} else {
+					// `imports` can map to other packages
+					self.resolve_package(&target, package_directory, read_from_path).ok().flatten()
+				}
+			}
+			JSONValue::Array(targets) => targets.iter().find_map(|target| {
+				self.resolve_target(package_directory, target, star, read_from_path)
+			}),
+			JSONValue::Object(conditions) => conditions.iter().find_map(|(condition, target)| {
+				let matches = condition == "default"
+					|| self.conditions.iter().any(|active| active == condition);
+				if matches {
+					self.resolve_target(package_directory, target, star, read_from_path)
+				} else {
+					None
+				}
+			}),
+			// `null` excludes the subpath
+			_ => None,
+		}
+	}
+}
+
+fn read_ts_config(
+	path: &Path,
+	read_from_path: &impl Fn(&Path) -> Option<String>,
+	depth: u8,
+) -> Result<PathMappings, ResolveError> {
+	let invalid = |reason: String| ResolveError::InvalidConfig { path: path.to_owned(), reason };
+	let content = read_from_path(path).ok_or_else(|| invalid("Could not read file".to_owned()))?;
+	let config = json::parse(&content).map_err(|(reason, _)| invalid(reason))?;
+	let directory = path.parent().unwrap_or(Path::new(""));
+
+	let mut mappings = match config.get("extends").and_then(JSONValue::as_str) {
+		Some(extends) if depth < MAX_EXTENDS_DEPTH => {
+			let candidates = if is_relative(extends) {
+				vec![normalize_path(&directory.join(extends))]
+			} else {
+				ancestors(directory)
+					.into_iter()
+					.map(|directory| directory.join("node_modules").join(extends))
+					.collect()
+			};
+			let extends_path = candidates
+				.into_iter()
+				.flat_map(|path| [with_added_extension(&path, "json"), path])
+				.find(|path| {
+					path.extension() == Some(OsStr::new("json")) && read_from_path(path).is_some()
+				})
+				.ok_or_else(|| invalid(format!("Could not find '{extends}' to extend")))?;
+			read_ts_config(&extends_path, read_from_path, depth + 1)?
+		}
+		_ => PathMappings::default(),
+	};
+
+	let options = config.get("compilerOptions");
+	if let Some(base_url) = options.and_then(|options| options.get("baseUrl")?.as_str()) {
+		mappings.base_url = Some(normalize_path(&directory.join(base_url)));
+	}
+	if let Some(JSONValue::Object(paths)) = options.and_then(|options| options.get("paths")) {
+		mappings.paths = paths
+			.iter()
+			.map(|(pattern, targets)| {
+				let targets = match targets {
+					JSONValue::Array(targets) => targets
+						.iter()
+						.filter_map(JSONValue::as_str)
+						.map(ToOwned::to_owned)
+						.collect(),
+					_ => Vec::new(),
+				};
+				(pattern.clone(), targets)
+			})
+			.collect();
+		mappings.paths_directory = directory.to_owned();
+	}
+	Ok(mappings)
+}
+
+fn read_package(
+	directory: &Path,
+	read_from_path: &impl Fn(&Path) -> Option<String>,
+) -> Result<Option<JSONValue>, ResolveError> {
+	let path = directory.join("package.json");
+	let Some(content) = read_from_path(&path) else { return Ok(None) };
+	json::parse(&content)
+		.map(Some)
+		.map_err(|(reason, _)| ResolveError::InvalidConfig { path, reason })
+}
+
+/// Finds the value for `key` in a map where keys can contain a `*` wildcard. Exact matches are
+/// preferred, then the pattern with the longest prefix. Also returns what the `*` matched
+fn match_pattern<'a, 'b, T: 'a>(
+	entries: impl Iterator<Item = (&'a String, &'a T)>,
+	key: &'b str,
+) -> Option<(&'a T, Option<&'b str>)> {
+	let mut best: Option<(usize, &T, &str)> = None;
+	for (pattern, value) in entries {
+		match pattern.split_once('*') {
+			None if pattern == key => return Some((value, None)),
+			None => {}
+			Some((prefix, suffix)) => {
+				let matches = key.len() >= prefix.len() + suffix.len()
+					&& key.starts_with(prefix)
+					&& key.ends_with(suffix);
+				if matches && best.is_none_or(|(length, ..)| prefix.len() > length) {
+					best =
+						Some((prefix.len(), value, &key[prefix.len()..key.len() - suffix.len()]));
+				}
+			}
+		}
+	}
+	best.map(|(_, value, star)| (value, Some(star)))
+}
+
+/// `@scope/name/sub/path` to `("@scope/name", "./sub/path")` and `name` to `("name", ".")`
+fn split_package_specifier(specifier: &str) -> (&str, String) {
+	let name_length = if specifier.starts_with('@') {
+		specifier.match_indices('/').nth(1).map_or(specifier.len(), |(index, _)| index)
+	} else {
+		specifier.find('/').unwrap_or(specifier.len())
+	};
+	let (name, rest) = specifier.split_at(name_length);
+	(name, format!(".{rest}"))
+}
+
+/// Whether a module can have side effects when it is imported, from the `sideEffects` field of the
+/// closest `package.json`. It is either a boolean or globs of the files which have side effects
+/// (globs without a `/` match the file name in any directory). Modules have side effects if the
+/// field is missing or invalid
+pub fn module_has_side_effects(
+	path: &Path,
+	read_from_path: &impl Fn(&Path) -> Option<String>,
+) -> bool {
+	let directory = path.parent().unwrap_or(Path::new(""));
+	for directory in ancestors(directory) {
+		let package = match read_package(&directory, read_from_path) {
+			Ok(Some(package)) => package,
+			Ok(None) => continue,
+			Err(_) => return true,
+		};
+		let relative = path.strip_prefix(&directory).unwrap_or(path).to_string_lossy();
+		return match package.get("sideEffects") {
+			Some(JSONValue::Boolean(side_effects)) => *side_effects,
+			Some(JSONValue::Array(globs)) => {
+				globs.iter().filter_map(JSONValue::as_str).any(|glob| {
+					let glob = glob.strip_prefix("./").unwrap_or(glob);
+					if glob.contains('/') {
+						glob_matches(glob, &relative)
+					} else {
+						glob_matches(&format!("**/{glob}"), &relative)
+					}
+				})
+			}
+			_ => true,
+		};
+	}
+	true
+}
+
+/// Whether a specifier is a path (rather than a package or mapped name)
+pub fn is_relative(specifier: &str) -> bool {
+	specifier.starts_with("./")
+		|| specifier.starts_with("../")
+		|| specifier.starts_with('/')
+		|| matches!(specifier, "." | "..")
+}
+
+/// `directory` and its ancestors. Relative directories continue from the current directory
+fn ancestors(directory: &Path) -> Vec<PathBuf> {
+	let current_directory = std::env::current_dir().unwrap_or_default();
+	let directory = if directory.components().any(|component| component == Component::ParentDir) {
+		normalize_path(&current_directory.join(directory))
+	} else {
+		directory.to_owned()
+	};
+	let mut ancestors = directory.ancestors().map(Path::to_owned).collect::<Vec<_>>();
+	if directory.is_relative() {
+		ancestors.extend(current_directory.ancestors().skip(1).map(Path::to_owned));
+	}
+	ancestors
+}
+
+/// `with_extension` replaces the existing extension, this appends `.extension`
+fn with_added_extension(path: &Path, extension: &str) -> PathBuf {
+	let mut path = path.as_os_str().to_owned();
+	path.push(".");
+	path.push(extension);
+	path.into()
+}
@@ -0,0 +1,179 @@
+use std::{fs, path::PathBuf};
+
+use argh::FromArgs;
+use parser::{
+	rewrite::{apply_edits, edit_to_string, Edit, RewriteRule, Rewriter},
+	source_map::MapFileStore,
+	ASTNode, Module, ParseOutput, ParseSettings, SourceId, ToStringSettings,
+	ToStringSettingsAndData, VisitSettings, VisitorsMut,
+};
+
+use crate::{
+	error_handling::{emit_ezno_diagnostic, DiagnosticCode, ErrorWarningInfo, TempDiagnostic},
+	search::collect_source_files,
+	utilities::print_to_cli,
+};
+
+/// Rewrite source files using rules of the form `pattern => replacement`
+#[derive(FromArgs, Debug)]
+#[argh(subcommand, name = "rewrite")]
+pub(crate) struct RewriteArguments {
+	/// rule, e.g. `$a.indexOf($b) !== -1 => $a.includes($b)`
+	#[argh(positional)]
+	rule: String,
+	/// files or directories to rewrite
+	#[argh(positional)]
+	paths: Vec<PathBuf>,
+	/// print a diff of changes rather than writing them
+	#[argh(switch)]
+	dry_run: bool,
+}
+
+impl RewriteArguments {
+	pub(crate) fn run(&self) {
+		let rule = match RewriteRule::from_string(&self.rule, ParseSettings::default()) {
+			Ok(rule) => rule,
+			Err(err) => {
+				print_to_cli(format_args!("{err}"));
+				return;
+			}
+		};
+
+		let mut files = Vec::new();
+		let paths =
+			if self.paths.is_empty() { vec![PathBuf::from(".")] } else { self.paths.clone() };
+		for path in paths {
+			collect_source_files(&path, &mut files);
+		}
+
+		let mut visitors = VisitorsMut {
+			expression_visitors_mut: vec![Box::new(Rewriter::new(vec![rule]))],
+			..Default::default()
+		};
+
+		let mut fs = MapFileStore::default();
+		let (mut files_changed, mut total_edits) = (0, 0);
+		for path in files {
+			let Ok(content) = fs::read_to_string(&path) else {
+				continue;
+			};
+			let source_id = SourceId::new(&mut fs, path.clone(), content.clone());
+			let result = Module::from_string(
+				content.clone(),
+				ParseSettings::default(),
+				source_id,
+				None,
+				Vec::new(),
+			);
+			let ParseOutput(mut module, state) = match result {
+				Ok(output) => output,
+				Err(err) => {
+					let diagnostic =
+						TempDiagnostic::from_parse_error(err, ErrorWarningInfo::Warning);
+					emit_ezno_diagnostic(&fs, diagnostic).unwrap();
+					continue;
+				}
+			};
+
+			let mut functions = state.function_extractor;
+			let mut edits = Vec::new();
+			module.visit_mut(&mut visitors, &mut edits, &mut functions, &VisitSettings::default());
+			let edits = without_overlapping(edits, &fs);
+			if edits.is_empty() {
+				continue;
+			}
+
+			files_changed += 1;
+			total_edits += edits.len();
+			let settings = ToStringSettingsAndData(ToStringSettings::default(), functions);
+			if self.dry_run {
+				print_diff(&path.display().to_string(), &content, &edits, &settings);
+			} else {
+				let output = apply_edits(&content, source_id, &edits, &settings);
+				if let Err(err) = fs::write(&path, output.as_ref()) {
+					print_to_cli(format_args!("Could not write {}: {err}", path.display()));
+				}
+			}
+		}
+
+		print_to_cli(format_args!(
+			"{} {total_edits} expression{} in {files_changed} file{}",
+			if self.dry_run { "Would rewrite" } else { "Rewrote" },
+			if total_edits == 1 { "" } else { "s" },
+			if files_changed == 1 { "" } else { "s" }
+		));
+	}
+}
+
+/// Sorts edits by position and skips, with a warning, those which overlap an earlier edit. [apply_edits]
+/// would skip them anyway, so this keeps `--dry-run` and the count of edits in line with what is written
+fn without_overlapping(mut edits: Vec<Edit>, fs: &MapFileStore) -> Vec<Edit> {
+	edits.sort_by_key(|edit| edit.position.start);
+	let mut kept: Vec<Edit> = Vec::with_capacity(edits.len());
+	for edit in edits {
+		match kept.last() {
+			Some(last) if edit.position.start < last.position.end => {
+				let diagnostic = TempDiagnostic {
+					label: "Skipped as it overlaps an earlier rewrite".to_owned(),
+					position: edit.position.clone(),
+					kind: ErrorWarningInfo::Warning,
+					code: DiagnosticCode::OverlappingRewrite,
+					secondary_labels: vec![(last.position.clone(), "Earlier rewrite".to_owned())],
+					notes: Vec::new(),
+				};
+				emit_ezno_diagnostic(fs, diagnostic).unwrap();
+			}
+			_ => kept.push(edit),
+		}
+	}
+	kept
+}
+
+/// Prints a unified diff. Each hunk covers the whole lines that edits touch. Edits must be sorted and
+/// not overlap
+fn print_diff(path: &str, content: &str, edits: &[Edit], settings: &ToStringSettingsAndData) {
+	print_to_cli(format_args!("--- {path}\n+++ {path}"));
+
+	let line_start = |idx: usize| content[..idx].rfind('\n').map_or(0, |idx| idx + 1);
+	let line_end = |idx: usize| content[idx..].find('\n').map_or(content.len(), |end| idx + end);
+
+	// (start, end, edits) in byte offsets of whole lines
+	let mut hunks: Vec<(usize, usize, Vec<&Edit>)> = Vec::new();
+	for edit in edits {
+		let start = line_start(edit.position.start as usize);
+		let end = line_end(edit.position.end as usize);
+		match hunks.last_mut() {
+			Some((_, last_end, last_edits)) if start <= *last_end => {
+				*last_end = end.max(*last_end);
+				last_edits.push(edit);
+			}
+			_ => hunks.push((start, end, vec![edit])),
+		}
+	}
+
+	let mut line_difference: isize = 0;
+	for (start, end, edits) in hunks {
+		let mut new = String::new();
+		let mut last = start;
+		for edit in edits {
+			new.push_str(&content[last..edit.position.start as usize]);
+			new.push_str(&edit_to_string(edit, settings));
+			last = edit.position.end as usize;
+		}
+		new.push_str(&content[last..end]);
+
+		let old = &content[start..end];
+		let old_line = content[..start].matches('\n').count() + 1;
+		let (old_count, new_count) = (old.lines().count(), new.lines().count());
+		let new_line = old_line as isize + line_difference;
+		line_difference += new_count as isize - old_count as isize;
+
+		print_to_cli(format_args!("@@ -{old_line},{old_count} +{new_line},{new_count} @@"));
+		for line in old.lines() {
+			print_to_cli(format_args!("-{line}"));
+		}
+		for line in new.lines() {
+			print_to_cli(format_args!("+{line}"));
+		}
+	}
+}
@@ -0,0 +1,147 @@
+use std::{
+	fs,
+	path::{Path, PathBuf},
+};
+
+use argh::FromArgs;
+use parser::{
+	query::{Capture, Pattern},
+	source_map::MapFileStore,
+	ASTNode, Module, NodeRef, ParseOutput, ParseSettings, SourceId, Span,
+};
+
+use crate::{
+	error_handling::{emit_ezno_diagnostic, emit_parser_error, ErrorWarningInfo, TempDiagnostic},
+	utilities::print_to_cli,
+};
+
+const EXTENSIONS: &[&str] = &["js", "mjs", "cjs", "jsx", "ts", "mts", "tsx"];
+
+/// Search source files for a pattern. Use `$name` to match any expression and `$$$name` for the
+/// remaining arguments
+#[derive(FromArgs, Debug)]
+#[argh(subcommand, name = "search")]
+pub(crate) struct SearchArguments {
+	/// pattern to search for, e.g. `$a.indexOf($b) !== -1`
+	#[argh(positional)]
+	pattern: String,
+	/// files or directories to search
+	#[argh(positional)]
+	paths: Vec<PathBuf>,
+	/// print what each metavariable matched
+	#[argh(switch)]
+	captures: bool,
+}
+
+impl SearchArguments {
+	pub(crate) fn run(&self) {
+		let pattern = match Pattern::from_string(&self.pattern, ParseSettings::default()) {
+			Ok(pattern) => pattern,
+			Err(err) => {
+				emit_parser_error(self.pattern.clone(), err).unwrap();
+				return;
+			}
+		};
+
+		let mut files = Vec::new();
+		let paths =
+			if self.paths.is_empty() { vec![PathBuf::from(".")] } else { self.paths.clone() };
+		for path in paths {
+			collect_source_files(&path, &mut files);
+		}
+
+		let mut fs = MapFileStore::default();
+		let mut count = 0;
+		for path in files {
+			let Ok(content) = fs::read_to_string(&path) else {
+				continue;
+			};
+			let source_id = SourceId::new(&mut fs, path.clone(), content.clone());
+			let result = Module::from_string(
+				content.clone(),
+				ParseSettings::default(),
+				source_id,
+				None,
+				Vec::new(),
+			);
+			let ParseOutput(module, state) = match result {
+				Ok(output) => output,
+				Err(err) => {
+					let diagnostic =
+						TempDiagnostic::from_parse_error(err, ErrorWarningInfo::Warning);
+					emit_ezno_diagnostic(&fs, diagnostic).unwrap();
+					continue;
+				}
+			};
+
+			for found in pattern.find_all(NodeRef::Module(&module), &state.function_extractor) {
+				count += 1;
+				let position = found.get_position();
+				let (line, column) = line_column(&content, position.start as usize);
+				let text = slice(&content, &position);
+				let first_line = text.lines().next().unwrap_or_default();
+				print_to_cli(format_args!("{}:{line}:{column}: {first_line}", path.display()));
+
+				if self.captures {
+					let mut captures = found.captures.iter().collect::<Vec<_>>();
+					captures.sort_by_key(|(name, _)| *name);
+					for (name, capture) in captures {
+						let value = match capture {
+							Capture::Node(node) => slice(&content, &node.get_position()).to_owned(),
+							Capture::Property(property) => (*property).to_owned(),
+							Capture::Items(items) => match (items.first(), items.last()) {
+								(Some(first), Some(last)) => slice(
+									&content,
+									&first.get_position().union(&last.get_position()),
+								)
+								.to_owned(),
+								_ => String::new(),
+							},
+						};
+						print_to_cli(format_args!("\t${name} = {value}"));
+					}
+				}
+			}
+		}
+
+		print_to_cli(format_args!("Found {count} match{}", if count == 1 { "" } else { "es" }));
+	}
+}
+
+pub(crate) fn collect_source_files(path: &Path, files: &mut Vec<PathBuf>) {
+	if path.is_dir() {
+		let Ok(entries) = fs::read_dir(path) else {
+			return;
+		};
+		let mut entries =
+			entries.filter_map(Result::ok).map(|entry| entry.path()).collect::<Vec<_>>();
+		entries.sort();
+		for entry in entries {
+			let skip = entry
+				.file_name()
+				.and_then(|name| name.to_str())
+				.is_some_and(|name| name == "node_modules" || name.starts_with('.'));
+			if !skip {
+				collect_source_files(&entry, files);
+			}
+		}
+	} else if path
+		.extension()
+		.and_then(|extension| extension.to_str())
+		.is_some_and(|extension| EXTENSIONS.contains(&extension))
+	{
+		files.push(path.to_owned());
+	}
+}
+
+fn slice<'a>(content: &'a str, span: &Span) -> &'a str {
+	content.get(span.start as usize..span.end as usize).unwrap_or_default()
+}
+
+/// One based line and column
+fn line_column(content: &str, idx: usize) -> (usize, usize) {
+	let before = &content[..idx];
+	let line = before.matches('\n').count() + 1;
+	let column = before.rsplit('\n').next().map_or(0, |line| line.chars().count()) + 1;
+	(line, column)
+}
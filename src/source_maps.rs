@@ -0,0 +1,81 @@
+use std::{
+	fmt::Write,
+	path::{Path, PathBuf},
+};
+
+use parser::source_map::{FileSystem, SourceMap};
+
+use crate::utilities::normalize_path;
+
+/// Serializes a source map to [version 3 JSON](https://sourcemaps.info/spec.html). Paths of sources are
+/// made relative to the directory `output_path` is in
+pub(crate) fn source_map_to_json(
+	source_map: &SourceMap,
+	fs: &impl FileSystem,
+	output_path: &Path,
+) -> String {
+	let output_directory = output_path.parent().unwrap_or(Path::new(""));
+	let (mut sources, mut sources_content) = (Vec::new(), Vec::new());
+	for source_id in source_map.sources.iter() {
+		let (path, content) = fs.get_file_path_and_content(*source_id);
+		let path = relative_path(output_directory, &path);
+		sources.push(json_string(&path.display().to_string().replace('\\', "/")));
+		sources_content.push(json_string(&content));
+	}
+
+	let file = output_path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+	format!(
+		r#"{{"version":3,"file":{},"sources":[{}],"sourcesContent":[{}],"names":[],"mappings":{}}}"#,
+		json_string(&file),
+		sources.join(","),
+		sources_content.join(","),
+		json_string(&source_map.mappings)
+	)
+}
+
+/// The comment which links output to its source map
+pub(crate) fn source_mapping_url_comment(url: &str) -> String {
+	format!("//# sourceMappingURL={url}")
+}
+
+/// A data URL for embedding a source map in the output
+pub(crate) fn inline_source_map_url(json: &str) -> String {
+	format!("data:application/json;charset=utf-8;base64,{}", base64::encode(json))
+}
+
+fn json_string(value: &str) -> String {
+	let mut buf = String::with_capacity(value.len() + 2);
+	buf.push('"');
+	for chr in value.chars() {
+		match chr {
+			'"' => buf.push_str("\\\""),
+			'\\' => buf.push_str("\\\\"),
+			'\n' => buf.push_str("\\n"),
+			'\r' => buf.push_str("\\r"),
+			'\t' => buf.push_str("\\t"),
+			chr if chr.is_control() => write!(buf, "\\u{:04x}", chr as u32).unwrap(),
+			chr => buf.push(chr),
+		}
+	}
+	buf.push('"');
+	buf
+}
+
+/// Path of `to` relative to `from_directory`. Relative paths are treated as relative to the current
+/// working directory
+pub(crate) fn relative_path(from_directory: &Path, to: &Path) -> PathBuf {
+	let current_directory = std::env::current_dir().unwrap_or_default();
+	let (from, to) = (current_directory.join(from_directory), current_directory.join(to));
+	let (from, to) = (normalize_path(&from), normalize_path(&to));
+	let (from, to) = (from.components().collect::<Vec<_>>(), to.components().collect::<Vec<_>>());
+
+	let common = from.iter().zip(to.iter()).take_while(|(a, b)| a == b).count();
+	let mut relative = PathBuf::new();
+	for _ in common..from.len() {
+		relative.push("..");
+	}
+	for component in &to[common..] {
+		relative.push(component);
+	}
+	relative
+}
@@ -0,0 +1,442 @@
+//! Time spent in each phase of a build for each module, with AST node counts and memory usage.
+//! Collected when [crate::temp::BuildSettings::statistics] is set (`--timings` in the CLI).
+//!
+//! Memory usage is only known when [CountingAllocator] is the global allocator, as it is in the
+//! `ezno` binary built with the `memory-statistics` feature
+
+use std::{
+	alloc::{GlobalAlloc, Layout, System},
+	fmt::Write,
+	path::PathBuf,
+	sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+	time::{Duration, Instant},
+};
+
+use parser::{
+	extractor::ExtractedFunctions, BlockLike, Chain, Expression, ImmutableVariableOrPropertyPart,
+	JSXElement, Module, Statement, StatementOrDeclaration,
+};
+
+use crate::json::JSONValue;
+
+/// Wraps the system allocator, keeping track of the number of bytes allocated
+pub struct CountingAllocator;
+
+static IN_USE: AtomicBool = AtomicBool::new(false);
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+/// Peak since the start of the current phase
+static PHASE_PEAK: AtomicUsize = AtomicUsize::new(0);
+
+fn allocated(size: usize) {
+	let allocated = ALLOCATED.fetch_add(size, Ordering::Relaxed) + size;
+	PEAK.fetch_max(allocated, Ordering::Relaxed);
+	PHASE_PEAK.fetch_max(allocated, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		let pointer = System.alloc(layout);
+		if !pointer.is_null() {
+			allocated(layout.size());
+		}
+		pointer
+	}
+
+	unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+		let pointer = System.alloc_zeroed(layout);
+		if !pointer.is_null() {
+			allocated(layout.size());
+		}
+		pointer
+	}
+
+	unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout) {
+		System.dealloc(pointer, layout);
+		ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+	}
+
+	unsafe fn realloc(&self, pointer: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+		let new_pointer = System.realloc(pointer, layout, new_size);
+		if !new_pointer.is_null() {
+			ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+			allocated(new_size);
+		}
+		new_pointer
+	}
+}
+
+impl CountingAllocator {
+	/// Whether this is the global allocator
+	fn in_use() -> bool {
+		// Anything using the allocator before this sets it
+		IN_USE.load(Ordering::Relaxed) || {
+			let in_use = ALLOCATED.load(Ordering::Relaxed) > 0;
+			IN_USE.store(in_use, Ordering::Relaxed);
+			in_use
+		}
+	}
+
+	/// Most bytes allocated at once since the program started
+	pub(crate) fn peak() -> Option<usize> {
+		Self::in_use().then(|| PEAK.load(Ordering::Relaxed))
+	}
+}
+
+/// AST nodes in a module, counted after parsing
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct NodeCounts {
+	pub statements: usize,
+	pub declarations: usize,
+	pub expressions: usize,
+	pub jsx_elements: usize,
+	/// Variable names, destructuring members, property keys etc
+	pub variables: usize,
+}
+
+impl NodeCounts {
+	pub fn total(&self) -> usize {
+		self.statements + self.declarations + self.expressions + self.jsx_elements + self.variables
+	}
+
+	pub(crate) fn count(module: &Module, functions: &mut ExtractedFunctions) -> Self {
+		let mut visitors = parser::Visitors {
+			expression_visitors: vec![Box::new(CountNodes)],
+			statement_visitors: vec![Box::new(CountNodes)],
+			jsx_element_visitors: vec![Box::new(CountNodes)],
+			variable_visitors: vec![Box::new(CountNodes)],
+			block_visitors: vec![Box::new(CountNodes)],
+		};
+		let mut counts = NodeCounts::default();
+		module.visit(&mut visitors, &mut counts, functions, &parser::VisitSettings::default());
+		counts
+	}
+}
+
+struct CountNodes;
+
+impl parser::Visitor<Expression, NodeCounts> for CountNodes {
+	fn visit(
+		&mut self,
+		_: &Expression,
+		counts: &mut NodeCounts,
+		_: &mut ExtractedFunctions,
+		_: &Chain,
+	) {
+		counts.expressions += 1;
+	}
+}
+
+impl parser::Visitor<Statement, NodeCounts> for CountNodes {
+	fn visit(
+		&mut self,
+		_: &Statement,
+		counts: &mut NodeCounts,
+		_: &mut ExtractedFunctions,
+		_: &Chain,
+	) {
+		counts.statements += 1;
+	}
+}
+
+impl parser::Visitor<JSXElement, NodeCounts> for CountNodes {
+	fn visit(
+		&mut self,
+		_: &JSXElement,
+		counts: &mut NodeCounts,
+		_: &mut ExtractedFunctions,
+		_: &Chain,
+	) {
+		counts.jsx_elements += 1;
+	}
+}
+
+impl<'a> parser::Visitor<ImmutableVariableOrPropertyPart<'a>, NodeCounts> for CountNodes {
+	fn visit(
+		&mut self,
+		_: &ImmutableVariableOrPropertyPart<'a>,
+		counts: &mut NodeCounts,
+		_: &mut ExtractedFunctions,
+		_: &Chain,
+	) {
+		counts.variables += 1;
+	}
+}
+
+/// Declarations are not visited themselves, so they are counted from the blocks they are in
+impl<'a> parser::Visitor<BlockLike<'a>, NodeCounts> for CountNodes {
+	fn visit(
+		&mut self,
+		block: &BlockLike<'a>,
+		counts: &mut NodeCounts,
+		_: &mut ExtractedFunctions,
+		_: &Chain,
+	) {
+		counts.declarations += block
+			.items
+			.iter()
+			.filter(|item| matches!(item, StatementOrDeclaration::Declaration(_)))
+			.count();
+	}
+}
+
+/// Some part of building a module, e.g. parsing or a transform
+#[derive(Debug, Clone)]
+pub struct Phase {
+	pub name: &'static str,
+	pub start: Instant,
+	pub duration: Duration,
+	/// Most bytes allocated at once during the phase
+	pub peak_memory: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModuleStatistics {
+	pub path: PathBuf,
+	/// Bytes of source, or of output for a bundle
+	pub size: usize,
+	pub nodes: NodeCounts,
+	pub phases: Vec<Phase>,
+}
+
+impl ModuleStatistics {
+	pub(crate) fn new(path: PathBuf, size: usize) -> Self {
+		Self { path, size, nodes: NodeCounts::default(), phases: Vec::new() }
+	}
+}
+
+/// Runs `phase`, recording how long it took if statistics are being collected
+pub(crate) fn time_phase<T>(
+	statistics: &mut Option<ModuleStatistics>,
+	name: &'static str,
+	phase: impl FnOnce() -> T,
+) -> T {
+	let Some(statistics) = statistics else {
+		return phase();
+	};
+	PHASE_PEAK.store(ALLOCATED.load(Ordering::Relaxed), Ordering::Relaxed);
+	let start = Instant::now();
+	let result = phase();
+	let duration = start.elapsed();
+	let peak_memory = CountingAllocator::in_use().then(|| PHASE_PEAK.load(Ordering::Relaxed));
+	statistics.phases.push(Phase { name, start, duration, peak_memory });
+	result
+}
+
+/// How [BuildStatistics] are printed
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TimingsFormat {
+	#[default]
+	Table,
+	Json,
+	/// For `chrome://tracing` and <https://ui.perfetto.dev>
+	ChromeTrace,
+}
+
+impl std::str::FromStr for TimingsFormat {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"table" => Ok(TimingsFormat::Table),
+			"json" => Ok(TimingsFormat::Json),
+			"chrome-trace" => Ok(TimingsFormat::ChromeTrace),
+			_ => Err(format!(
+				"Unknown timings format '{s}', expected 'table', 'json' or 'chrome-trace'"
+			)),
+		}
+	}
+}
+
+pub struct BuildStatistics {
+	pub start: Instant,
+	pub total: Duration,
+	pub modules: Vec<ModuleStatistics>,
+	pub peak_memory: Option<usize>,
+}
+
+impl BuildStatistics {
+	/// Finishes statistics for a build which started at `start`
+	pub(crate) fn new(start: Instant, modules: Vec<ModuleStatistics>) -> Self {
+		Self { start, total: start.elapsed(), modules, peak_memory: CountingAllocator::peak() }
+	}
+
+	pub fn format(&self, format: TimingsFormat) -> String {
+		match format {
+			TimingsFormat::Table => self.table(),
+			TimingsFormat::Json => self.to_json().to_string(),
+			TimingsFormat::ChromeTrace => self.to_chrome_trace().to_string(),
+		}
+	}
+
+	/// Phase names in the order they first appear
+	fn phase_names(&self) -> Vec<&'static str> {
+		let mut names = Vec::new();
+		for phase in self.modules.iter().flat_map(|module| module.phases.iter()) {
+			if !names.contains(&phase.name) {
+				names.push(phase.name);
+			}
+		}
+		names
+	}
+
+	/// A row for each module and a column for each phase, followed by the totals
+	pub fn table(&self) -> String {
+		let phase_names = self.phase_names();
+		let mut header = vec!["module".to_owned(), "bytes".to_owned(), "nodes".to_owned()];
+		header.extend(phase_names.iter().map(|name| (*name).to_owned()));
+		let mut rows = vec![header];
+
+		let mut totals = vec![Duration::ZERO; phase_names.len()];
+		for module in self.modules.iter() {
+			let mut row = vec![
+				module.path.display().to_string(),
+				module.size.to_string(),
+				module.nodes.total().to_string(),
+			];
+			for (name, total) in phase_names.iter().zip(totals.iter_mut()) {
+				let duration = module
+					.phases
+					.iter()
+					.filter(|phase| phase.name == *name)
+					.map(|phase| phase.duration)
+					.sum::<Duration>();
+				*total += duration;
+				row.push(format_duration(duration));
+			}
+			rows.push(row);
+		}
+		let mut total_row = vec![
+			"total".to_owned(),
+			self.modules.iter().map(|module| module.size).sum::<usize>().to_string(),
+			self.modules.iter().map(|module| module.nodes.total()).sum::<usize>().to_string(),
+		];
+		total_row.extend(totals.into_iter().map(format_duration));
+		rows.push(total_row);
+
+		let widths = (0..rows[0].len())
+			.map(|column| rows.iter().map(|row| row[column].chars().count()).max().unwrap_or(0))
+			.collect::<Vec<_>>();
+		let mut table = String::new();
+		for row in rows {
+			for (column, (cell, width)) in row.iter().zip(widths.iter()).enumerate() {
+				if column == 0 {
+					let _ = write!(table, "{cell:<width$}");
+				} else {
+					let _ = write!(table, "  {cell:>width$}");
+				}
+			}
+			table.push('\n');
+		}
+		let _ = write!(table, "Built in {}", format_duration(self.total));
+		if let Some(peak_memory) = self.peak_memory {
+			let _ = write!(table, ", peak memory {}", format_bytes(peak_memory));
+		}
+		table
+	}
+
+	fn milliseconds(duration: Duration) -> JSONValue {
+		JSONValue::Number(duration.as_secs_f64() * 1000.)
+	}
+
+	fn memory(peak_memory: Option<usize>) -> JSONValue {
+		peak_memory.map_or(JSONValue::Null, |bytes| JSONValue::Number(bytes as f64))
+	}
+
+	/// Durations are in milliseconds and phase starts are relative to the start of the build
+	pub(crate) fn to_json(&self) -> JSONValue {
+		let modules = self.modules.iter().map(|module| {
+			let NodeCounts { statements, declarations, expressions, jsx_elements, variables } =
+				module.nodes;
+			let nodes = [
+				("statements", statements),
+				("declarations", declarations),
+				("expressions", expressions),
+				("jsxElements", jsx_elements),
+				("variables", variables),
+				("total", module.nodes.total()),
+			]
+			.into_iter()
+			.map(|(name, count)| (name.to_owned(), JSONValue::Number(count as f64)));
+			let phases = module.phases.iter().map(|phase| {
+				JSONValue::Object(vec![
+					("name".to_owned(), JSONValue::String(phase.name.to_owned())),
+					("start".to_owned(), Self::milliseconds(phase.start - self.start)),
+					("duration".to_owned(), Self::milliseconds(phase.duration)),
+					("peakMemory".to_owned(), Self::memory(phase.peak_memory)),
+				])
+			});
+			JSONValue::Object(vec![
+				("path".to_owned(), JSONValue::String(module.path.display().to_string())),
+				("size".to_owned(), JSONValue::Number(module.size as f64)),
+				("nodes".to_owned(), JSONValue::Object(nodes.collect())),
+				("phases".to_owned(), JSONValue::Array(phases.collect())),
+			])
+		});
+		JSONValue::Object(vec![
+			("total".to_owned(), Self::milliseconds(self.total)),
+			("peakMemory".to_owned(), Self::memory(self.peak_memory)),
+			("modules".to_owned(), JSONValue::Array(modules.collect())),
+		])
+	}
+
+	/// [Trace Event Format](https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU)
+	/// complete events, with timestamps in microseconds
+	pub(crate) fn to_chrome_trace(&self) -> JSONValue {
+		let microseconds = |duration: Duration| JSONValue::Number(duration.as_micros() as f64);
+		let event = |name: String, start: Duration, duration: Duration, args: JSONValue| {
+			JSONValue::Object(vec![
+				("name".to_owned(), JSONValue::String(name)),
+				("cat".to_owned(), JSONValue::String("build".to_owned())),
+				("ph".to_owned(), JSONValue::String("X".to_owned())),
+				("ts".to_owned(), microseconds(start)),
+				("dur".to_owned(), microseconds(duration)),
+				("pid".to_owned(), JSONValue::Number(1.)),
+				("tid".to_owned(), JSONValue::Number(1.)),
+				("args".to_owned(), args),
+			])
+		};
+		let build = event(
+			"build".to_owned(),
+			Duration::ZERO,
+			self.total,
+			JSONValue::Object(vec![("peakMemory".to_owned(), Self::memory(self.peak_memory))]),
+		);
+		let phases = self.modules.iter().flat_map(|module| {
+			module.phases.iter().map(|phase| {
+				let path = module.path.display().to_string();
+				event(
+					format!("{} {path}", phase.name),
+					phase.start - self.start,
+					phase.duration,
+					JSONValue::Object(vec![
+						("module".to_owned(), JSONValue::String(path)),
+						("peakMemory".to_owned(), Self::memory(phase.peak_memory)),
+					]),
+				)
+			})
+		});
+		JSONValue::Object(vec![
+			(
+				"traceEvents".to_owned(),
+				JSONValue::Array(std::iter::once(build).chain(phases).collect()),
+			),
+			("displayTimeUnit".to_owned(), JSONValue::String("ms".to_owned())),
+		])
+	}
+}
+
+fn format_duration(duration: Duration) -> String {
+	format!("{:.2}ms", duration.as_secs_f64() * 1000.)
+}
+
+fn format_bytes(bytes: usize) -> String {
+	const UNITS: &[&str] = &["B", "KB", "MB", "GB"];
+	let mut value = bytes as f64;
+	let mut unit = 0;
+	while value >= 1024. && unit < UNITS.len() - 1 {
+		value /= 1024.;
+		unit += 1;
+	}
+	format!("{value:.1} {}", UNITS[unit])
+}
@@ -1,78 +1,822 @@
-use std::path::PathBuf;
+use std::{
+	collections::{HashMap, HashSet, VecDeque},
+	path::{Path, PathBuf},
+};
 
 use parser::{
-	expressions::ExpressionId, source_map::MapFileStore, ASTNode, ParseSettings, SourceId,
-	ToStringSettings,
+	expressions::ExpressionId, extractor::ExtractedFunctions, source_map::MapFileStore, ASTNode,
+	Chain, Declaration, Expression, LexedSource, Module, ParseOutput, ParseSettings, SourceId,
+	Span, StatementOrDeclaration, ToStringSettings,
 };
 
-use crate::error_handling::{self, TempDiagnostic};
+use crate::{
+	downlevel::decorators::DecoratorSemantics,
+	enums::ImportedEnums,
+	erasure::ConstantEnums,
+	error_handling::{self, DiagnosticCode, ErrorWarningInfo, TempDiagnostic},
+	json::JSONValue,
+	jsx::JSXRuntime,
+	pipeline::{File, Pipeline},
+	resolver::{is_relative, ResolveError, Resolver},
+	source_maps::source_map_to_json,
+	statistics::{time_phase, ModuleStatistics, NodeCounts},
+	utilities::normalize_path,
+};
 
 #[cfg_attr(target_family = "wasm", derive(serde::Serialize))]
 pub struct Output {
 	pub output_path: String,
 	pub content: String,
 	pub mappings: String,
+	/// Source map as JSON, with sources relative to the output path
+	pub source_map: String,
 }
 
 #[cfg_attr(target_family = "wasm", derive(serde::Serialize))]
 pub struct BuildOutput {
 	pub outputs: Vec<Output>,
 	pub temp_warnings_and_infos: Vec<TempDiagnostic>,
+	/// Empty unless [BuildSettings::statistics] is set
+	#[cfg_attr(target_family = "wasm", serde(skip))]
+	pub statistics: Vec<ModuleStatistics>,
+}
+
+pub(crate) struct ParsedModule {
+	pub(crate) path: PathBuf,
+	pub(crate) module: Module,
+	pub(crate) functions: ExtractedFunctions,
+	/// Relative specifiers in the module and the path they resolved to
+	pub(crate) resolved: HashMap<String, PathBuf>,
+	pub(crate) statistics: Option<ModuleStatistics>,
+}
+
+/// Options for [Pipeline::build] and [crate::bundle::bundle]
+#[derive(Clone)]
+pub struct BuildSettings {
+	pub parse_settings: ParseSettings,
+	pub output_settings: ToStringSettings,
+	/// Run on every module in order. See [Pipeline]
+	pub transforms: Vec<Transform>,
+	/// Used by [Transform::LowerJSX]
+	pub jsx: JSXRuntime,
+	/// Used by [Transform::LowerDecorators]
+	pub decorators: DecoratorSemantics,
+	/// Record how long each phase takes for each module. See [crate::statistics]
+	pub statistics: bool,
+}
+
+impl Default for BuildSettings {
+	fn default() -> Self {
+		Self {
+			parse_settings: ParseSettings::default(),
+			output_settings: ToStringSettings::default(),
+			transforms: vec![Transform::EraseTypes, Transform::InvertTernaryBranches],
+			jsx: JSXRuntime::default(),
+			decorators: DecoratorSemantics::default(),
+			statistics: false,
+		}
+	}
+}
+
+/// Transforms which can be selected in the configuration or with `--transform`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Transform {
+	/// Lowers enums, parameter properties and type only imports and removes type declarations.
+	/// Always run when the output does not include types. See [crate::erasure]
+	EraseTypes,
+	/// Replaces members of enums and `as const` objects with their values, including those
+	/// imported from other modules. Runs before [Transform::EraseTypes]. See [crate::enums]
+	InlineEnums,
+	/// Swaps the branches of `!a ? b : c` to remove the negation
+	InvertTernaryBranches,
+	/// Lowers JSX to function calls using [BuildSettings::jsx]. See [crate::jsx]
+	LowerJSX,
+	/// Decorators to function calls using [BuildSettings::decorators]. Runs before
+	/// [Transform::EraseTypes] for metadata. See [crate::downlevel::decorators]
+	LowerDecorators,
+	/// Pipes, compositions, `is` expressions and `generator` keywords, the syntax the `extras`
+	/// parse options enable. See [crate::downlevel::extras]
+	LowerExtras,
+	/// `a ||= b`, `a &&= b` and `a ??= b` to `a || (a = b)` etc. See [crate::downlevel]
+	LowerLogicalAssignment,
+	/// `a?.b` and `a ?? b` to conditional expressions
+	LowerOptionalChaining,
+	/// Async generators and `for await` to generators driven by `__asyncGenerator` and loops over
+	/// `__asyncValues`
+	LowerAsyncIteration,
+	/// Async functions to generators driven by `__awaiter`
+	LowerAsyncFunctions,
+	/// `a ** b` to `Math.pow(a, b)`
+	LowerExponentiation,
+	/// `{ ...a }` to `Object.assign({}, a)`
+	LowerObjectSpread,
+	/// `for ... of` loops to loops which call the iterator, through the `__values` helper
+	LowerForOf,
+	/// Destructuring declarations, assignments and parameters to temporaries and property accesses
+	LowerDestructuring,
+	/// Untagged template literals to string concatenation
+	LowerTemplateLiterals,
+	/// Shorthand properties, methods and computed keys of object literals to properties and
+	/// assignments
+	LowerObjectLiterals,
+	/// Classes to constructor functions and prototypes
+	LowerClasses,
+	/// Spread arguments and array elements to `apply` and `__spreadArray`
+	LowerSpread,
+	/// Arrow functions to function expressions, capturing `this` and `arguments`
+	LowerArrowFunctions,
+	/// Generators to state machines driven by `__generator`
+	LowerGenerators,
+	/// Default values of parameters to assignments and rest parameters to slices of `arguments`
+	LowerParameters,
+	/// `let` and `const` to `var`, renaming variables which would clash and moving the bodies of
+	/// loops with captured variables to functions
+	LowerBlockScoping,
+	/// Replaces `const` variables used once with their literal value. See [crate::minify]
+	InlineConstants,
+	/// Evaluates operations on literals and conditionals with literal conditions
+	FoldConstants,
+	/// Removes statements after `return` etc and branches of `if`s with literal conditions
+	RemoveDeadCode,
+	/// `if` statements to `&&`, `||` and `?:` expressions where shorter
+	CollapseConditionals,
+	/// Renames local variables to short names
+	MangleNames,
+	/// A pass registered with [Pipeline::register]
+	Pass { name: String, options: Option<JSONValue> },
+}
+
+impl Transform {
+	/// The built-in transforms
+	pub const ALL: &'static [Transform] = &[
+		Transform::EraseTypes,
+		Transform::InlineEnums,
+		Transform::InvertTernaryBranches,
+		Transform::LowerJSX,
+		Transform::LowerDecorators,
+		Transform::LowerExtras,
+		Transform::LowerLogicalAssignment,
+		Transform::LowerOptionalChaining,
+		Transform::LowerAsyncIteration,
+		Transform::LowerAsyncFunctions,
+		Transform::LowerExponentiation,
+		Transform::LowerObjectSpread,
+		Transform::LowerForOf,
+		Transform::LowerDestructuring,
+		Transform::LowerTemplateLiterals,
+		Transform::LowerObjectLiterals,
+		Transform::LowerClasses,
+		Transform::LowerSpread,
+		Transform::LowerArrowFunctions,
+		Transform::LowerGenerators,
+		Transform::LowerParameters,
+		Transform::LowerBlockScoping,
+		Transform::InlineConstants,
+		Transform::FoldConstants,
+		Transform::RemoveDeadCode,
+		Transform::CollapseConditionals,
+		Transform::MangleNames,
+	];
+
+	pub fn name(&self) -> &str {
+		match self {
+			Transform::EraseTypes => "erase-types",
+			Transform::InlineEnums => "inline-enums",
+			Transform::InvertTernaryBranches => "invert-ternary-branches",
+			Transform::LowerJSX => "lower-jsx",
+			Transform::LowerDecorators => "lower-decorators",
+			Transform::LowerExtras => "lower-extras",
+			Transform::LowerLogicalAssignment => "lower-logical-assignment",
+			Transform::LowerOptionalChaining => "lower-optional-chaining",
+			Transform::LowerAsyncIteration => "lower-async-iteration",
+			Transform::LowerAsyncFunctions => "lower-async-functions",
+			Transform::LowerExponentiation => "lower-exponentiation",
+			Transform::LowerObjectSpread => "lower-object-spread",
+			Transform::LowerForOf => "lower-for-of",
+			Transform::LowerDestructuring => "lower-destructuring",
+			Transform::LowerTemplateLiterals => "lower-template-literals",
+			Transform::LowerObjectLiterals => "lower-object-literals",
+			Transform::LowerClasses => "lower-classes",
+			Transform::LowerSpread => "lower-spread",
+			Transform::LowerArrowFunctions => "lower-arrow-functions",
+			Transform::LowerGenerators => "lower-generators",
+			Transform::LowerParameters => "lower-parameters",
+			Transform::LowerBlockScoping => "lower-block-scoping",
+			Transform::InlineConstants => "inline-constants",
+			Transform::FoldConstants => "fold-constants",
+			Transform::RemoveDeadCode => "remove-dead-code",
+			Transform::CollapseConditionals => "collapse-conditionals",
+			Transform::MangleNames => "mangle-names",
+			Transform::Pass { name, .. } => name,
+		}
+	}
+
+	/// The passes of a built-in transform, which are run in order over the whole module
+	pub(crate) fn passes(
+		&self,
+		settings: &BuildSettings,
+	) -> Vec<parser::VisitorsMut<Vec<TempDiagnostic>>> {
+		use crate::{downlevel, minify};
+
+		match self {
+			Transform::EraseTypes => vec![crate::erasure::visitors()],
+			// Without the enums of other modules, which are added by the build
+			Transform::InlineEnums => vec![crate::enums::visitors(ImportedEnums::new())],
+			Transform::InvertTernaryBranches => vec![parser::VisitorsMut {
+				expression_visitors_mut: vec![Box::new(InvertTernaryBranches)],
+				..Default::default()
+			}],
+			Transform::LowerJSX => vec![crate::jsx::visitors(&settings.jsx, None)],
+			Transform::LowerDecorators => downlevel::decorators::passes(settings.decorators),
+			Transform::LowerExtras => downlevel::extras::passes(),
+			Transform::LowerLogicalAssignment => downlevel::logical_assignment::passes(),
+			Transform::LowerOptionalChaining => downlevel::optional_chaining::passes(),
+			Transform::LowerAsyncIteration => downlevel::async_iteration::passes(),
+			Transform::LowerAsyncFunctions => downlevel::async_functions::passes(),
+			Transform::LowerExponentiation => downlevel::exponentiation::passes(),
+			Transform::LowerObjectSpread => downlevel::object_spread::passes(),
+			Transform::LowerForOf => downlevel::for_of::passes(),
+			Transform::LowerDestructuring => downlevel::destructuring::passes(),
+			Transform::LowerTemplateLiterals => downlevel::template_literals::passes(),
+			Transform::LowerObjectLiterals => downlevel::object_literals::passes(),
+			Transform::LowerClasses => downlevel::classes::passes(),
+			Transform::LowerSpread => downlevel::spread::passes(),
+			Transform::LowerArrowFunctions => downlevel::arrow_functions::passes(),
+			Transform::LowerGenerators => downlevel::generators::passes(),
+			Transform::LowerParameters => downlevel::parameters::passes(),
+			Transform::LowerBlockScoping => downlevel::block_scoping::passes(),
+			Transform::InlineConstants => minify::inline::passes(),
+			Transform::FoldConstants => minify::constants::passes(),
+			Transform::RemoveDeadCode => minify::dead_code::passes(),
+			Transform::CollapseConditionals => minify::conditionals::passes(),
+			Transform::MangleNames => minify::mangle::passes(),
+			Transform::Pass { .. } => unreachable!("registered passes are created by the pipeline"),
+		}
+	}
+
+	/// Options for a registered pass, from `["name", options]` in the configuration
+	pub fn with_options(self, options: JSONValue) -> Result<Self, String> {
+		match self {
+			Transform::Pass { name, .. } => Ok(Transform::Pass { name, options: Some(options) }),
+			transform => Err(format!("Transform '{}' does not take options", transform.name())),
+		}
+	}
+}
+
+impl std::str::FromStr for Transform {
+	type Err = String;
+
+	/// Names which are not built-in are passes registered with the [Pipeline], which checks they
+	/// exist in [Pipeline::validate]
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let transform = Transform::ALL.iter().find(|transform| transform.name() == s).cloned();
+		Ok(transform.unwrap_or_else(|| Transform::Pass { name: s.to_owned(), options: None }))
+	}
+}
+
+/// A [Pipeline::build] which remembers the import graph of the previous build, so that later
+/// builds only parse and generate the modules which changed and the modules which (transitively)
+/// import them
+pub(crate) struct IncrementalBuild {
+	output_path: PathBuf,
+	/// Modules from the last successful build and the paths their imports resolved to
+	modules: HashMap<PathBuf, HashMap<String, PathBuf>>,
+	/// Source root and whether the output was a single file, as output paths depend on it
+	layout: Option<(PathBuf, bool)>,
+	/// Absolute paths of files changed since the last successful build
+	changed: HashSet<PathBuf>,
+	failed: bool,
+	/// Members of the enums each module exports, for [Transform::InlineEnums] in modules which
+	/// import them but are rebuilt without them
+	enums: HashMap<PathBuf, ConstantEnums>,
+}
+
+impl IncrementalBuild {
+	pub(crate) fn new(output_path: PathBuf) -> Self {
+		Self {
+			output_path,
+			modules: HashMap::new(),
+			layout: None,
+			changed: HashSet::new(),
+			failed: false,
+			enums: HashMap::new(),
+		}
+	}
+
+	/// Number of modules in the last successful build
+	pub(crate) fn module_count(&self) -> usize {
+		self.modules.len()
+	}
+
+	/// Marks a file as changed, returning whether it affects the next build. Every file affects
+	/// it while the last build failed as the module graph is not known
+	pub(crate) fn mark_changed(&mut self, path: &Path) -> bool {
+		let path = absolute(path);
+		let affects = self.failed || self.modules.keys().any(|module| absolute(module) == path);
+		if affects {
+			self.changed.insert(path);
+		}
+		affects
+	}
+
+	/// Rebuilds everything next time. For when files are added or removed, which may change how
+	/// imports are resolved
+	pub(crate) fn mark_all_changed(&mut self) {
+		self.modules.clear();
+		self.layout = None;
+	}
+
+	/// Builds modules which changed since the last successful build. The outputs only include the
+	/// rebuilt modules. If there are errors, the changes are kept for the next build.
+	/// `entries` are the paths and content of the entry points
+	pub(crate) fn rebuild(
+		&mut self,
+		pipeline: &mut Pipeline,
+		entries: Vec<(PathBuf, String)>,
+		resolver: &Resolver,
+		read_from_path: &impl Fn(&Path) -> Option<String>,
+	) -> (MapFileStore, Result<BuildOutput, Vec<TempDiagnostic>>) {
+		let mut rebuild = self
+			.modules
+			.keys()
+			.filter(|path| self.changed.contains(&absolute(path)))
+			.cloned()
+			.collect::<HashSet<_>>();
+		// Add dependants until there are no more
+		loop {
+			let dependants = self
+				.modules
+				.iter()
+				.filter(|(path, resolved)| {
+					!rebuild.contains(*path)
+						&& resolved.values().any(|imported| rebuild.contains(imported))
+				})
+				.map(|(path, _)| path.clone())
+				.collect::<Vec<_>>();
+			if dependants.is_empty() {
+				break;
+			}
+			rebuild.extend(dependants);
+		}
+		let unchanged = self
+			.modules
+			.iter()
+			.filter(|(path, _)| !rebuild.contains(*path))
+			.map(|(path, resolved)| (path.clone(), resolved.clone()))
+			.collect::<HashMap<_, _>>();
+
+		let mut fs = MapFileStore::default();
+		let mut diagnostics = Vec::new();
+		let (modules, reached_unchanged) = load_changed_modules(
+			entries.clone(),
+			resolver,
+			read_from_path,
+			&pipeline.settings,
+			&unchanged,
+			&mut fs,
+			&mut diagnostics,
+		);
+
+		if diagnostics.iter().any(|diagnostic| matches!(diagnostic.kind, ErrorWarningInfo::Error)) {
+			self.failed = true;
+			return (fs, Err(diagnostics));
+		}
+
+		let root = common_directory(
+			modules
+				.iter()
+				.map(|module| module.path.as_path())
+				.chain(reached_unchanged.iter().map(PathBuf::as_path)),
+		);
+		let single_output_file =
+			modules.len() + reached_unchanged.len() == 1 && !is_directory(&self.output_path);
+		let layout = Some((root.clone(), single_output_file));
+		if self.layout != layout && !unchanged.is_empty() {
+			// Output paths of unchanged modules would be different
+			self.mark_all_changed();
+			return self.rebuild(pipeline, entries, resolver, read_from_path);
+		}
+
+		// Before any module is transformed
+		if pipeline.settings.transforms.contains(&Transform::InlineEnums) {
+			for module in modules.iter() {
+				self.enums.insert(module.path.clone(), crate::enums::exported(&module.module));
+			}
+		}
+
+		let mut built_modules = reached_unchanged
+			.into_iter()
+			.map(|path| {
+				let resolved = unchanged[&path].clone();
+				(path, resolved)
+			})
+			.collect::<HashMap<_, _>>();
+		let mut outputs = Vec::new();
+		let mut statistics = Vec::new();
+		for module in modules {
+			built_modules.insert(module.path.clone(), module.resolved.clone());
+			let output_path = if single_output_file {
+				self.output_path.clone()
+			} else {
+				let relative = absolute(&module.path)
+					.strip_prefix(&root)
+					.map(ToOwned::to_owned)
+					.unwrap_or(module.path.clone());
+				self.output_path.join(&relative).with_extension(output_extension(&relative))
+			};
+			let (output, module_statistics) =
+				generate_module(module, output_path, pipeline, &self.enums, &fs, &mut diagnostics);
+			outputs.push(output);
+			statistics.extend(module_statistics);
+		}
+
+		self.modules = built_modules;
+		self.layout = layout;
+		self.changed.clear();
+		self.failed = false;
+		(fs, Ok(BuildOutput { outputs, temp_warnings_and_infos: diagnostics, statistics }))
+	}
 }
 
-// Just builds one file temporarily
-pub fn build(
-	content: String,
-	input_path: String,
-	output_path: String,
-) -> (MapFileStore, Result<BuildOutput, Vec<TempDiagnostic>>) {
-	let mut fs = MapFileStore::default();
-	let source_id = SourceId::new(&mut fs, PathBuf::from(input_path), content.clone());
-	let module_result = parser::Module::from_string(
-		content,
-		ParseSettings::default(),
-		source_id,
-		None,
-		Default::default(),
+/// Rewrites imports and runs transforms on a module, then prints it. Each transform is a separate
+/// pass so that they can be timed
+fn generate_module(
+	ParsedModule { path, mut module, mut functions, resolved, mut statistics }: ParsedModule,
+	output_path: PathBuf,
+	pipeline: &mut Pipeline,
+	enums: &HashMap<PathBuf, ConstantEnums>,
+	fs: &MapFileStore,
+	diagnostics: &mut Vec<TempDiagnostic>,
+) -> (Output, Option<ModuleStatistics>) {
+	let imported = crate::enums::imported(&module.items, &resolved, enums);
+	let directory = path.parent().unwrap_or(Path::new(""));
+	let specifier_rewrites = resolved
+		.into_iter()
+		.filter_map(|(specifier, imported_path)| {
+			let rewritten = rewrite_specifier(&specifier, directory, &imported_path)?;
+			Some((specifier, rewritten))
+		})
+		.collect::<HashMap<_, _>>();
+	run_transforms(
+		pipeline,
+		&path,
+		&mut module,
+		&mut functions,
+		imported,
+		&mut statistics,
+		diagnostics,
 	);
+	time_phase(&mut statistics, "rewriting imports", || {
+		rewrite_imports(&mut module, &specifier_rewrites);
+		let visitors = parser::VisitorsMut {
+			expression_visitors_mut: vec![Box::new(RewriteDynamicImports(specifier_rewrites))],
+			..Default::default()
+		};
+		run_pass(&mut module, visitors, diagnostics, &mut functions);
+	});
 
-	let mut output = match module_result {
-		Ok(t) => t,
-		Err(parse_err) => {
-			let diag = TempDiagnostic {
-				label: parse_err.reason,
-				position: parse_err.position,
-				kind: error_handling::ErrorWarningInfo::Error,
+	let output_path = output_path.into_os_string().into_string().expect("Invalid path");
+	let (content, source_map) = time_phase(&mut statistics, "printing", || {
+		module.to_string_with_source_map(
+			&parser::ToStringSettingsAndData(pipeline.settings.output_settings.clone(), functions),
+			fs,
+		)
+	});
+	let source_map_json = time_phase(&mut statistics, "source map", || {
+		source_map_to_json(&source_map, fs, Path::new(&output_path))
+	});
+	let output =
+		Output { output_path, content, mappings: source_map.mappings, source_map: source_map_json };
+	(output, statistics)
+}
+
+/// Runs the selected transforms of the pipeline on a module, between its hooks
+fn run_transforms(
+	pipeline: &mut Pipeline,
+	path: &Path,
+	module: &mut Module,
+	functions: &mut ExtractedFunctions,
+	imported_enums: ImportedEnums,
+	statistics: &mut Option<ModuleStatistics>,
+	diagnostics: &mut Vec<TempDiagnostic>,
+) {
+	pipeline.before_file(&mut File { path, module, functions, diagnostics });
+	for transform in pipeline.settings.transforms.iter() {
+		let (name, mut passes) = pipeline.passes(transform, path);
+		// Members of enums from other modules are only known to the build
+		if let Transform::InlineEnums = transform {
+			passes = vec![crate::enums::visitors(imported_enums.clone())];
+		}
+		time_phase(statistics, name, || {
+			for visitors in passes {
+				run_pass(module, visitors, diagnostics, functions);
+			}
+		});
+	}
+	pipeline.after_file(&mut File { path, module, functions, diagnostics });
+}
+
+pub(crate) fn run_pass(
+	module: &mut Module,
+	mut visitors: parser::VisitorsMut<Vec<TempDiagnostic>>,
+	diagnostics: &mut Vec<TempDiagnostic>,
+	functions: &mut ExtractedFunctions,
+) {
+	module.visit_mut(&mut visitors, diagnostics, functions, &parser::VisitSettings::default());
+}
+
+/// Parses the entry point and every module reachable from it through `import`s and `import()`s,
+/// in breadth first order (so the entry is first). Packages and declaration files are not followed
+pub(crate) fn load_modules(
+	entry_path: PathBuf,
+	entry_content: String,
+	resolver: &Resolver,
+	read_from_path: &impl Fn(&Path) -> Option<String>,
+	settings: &BuildSettings,
+	fs: &mut MapFileStore,
+	diagnostics: &mut Vec<TempDiagnostic>,
+) -> Vec<ParsedModule> {
+	let (modules, _) = load_changed_modules(
+		vec![(entry_path, entry_content)],
+		resolver,
+		read_from_path,
+		settings,
+		&HashMap::new(),
+		fs,
+		diagnostics,
+	);
+	modules
+}
+
+/// [load_modules] but modules in `unchanged` are not parsed, their previously resolved imports
+/// are followed instead. Returns the parsed modules and the paths of reached unchanged modules
+fn load_changed_modules(
+	entries: Vec<(PathBuf, String)>,
+	resolver: &Resolver,
+	read_from_path: &impl Fn(&Path) -> Option<String>,
+	settings: &BuildSettings,
+	unchanged: &HashMap<PathBuf, HashMap<String, PathBuf>>,
+	fs: &mut MapFileStore,
+	diagnostics: &mut Vec<TempDiagnostic>,
+) -> (Vec<ParsedModule>, Vec<PathBuf>) {
+	let mut modules = Vec::new();
+	let mut reached_unchanged = Vec::new();
+	let mut seen = HashSet::new();
+	let mut queue = VecDeque::new();
+	for (path, content) in entries {
+		let path = normalize_path(&path);
+		if seen.insert(path.clone()) {
+			queue.push_back((path, Some(content)));
+		}
+	}
+
+	while let Some((path, content)) = queue.pop_front() {
+		if let Some(resolved) = unchanged.get(&path) {
+			for imported in resolved.values() {
+				if seen.insert(imported.clone()) {
+					queue.push_back((imported.clone(), None));
+				}
+			}
+			reached_unchanged.push(path);
+			continue;
+		}
+
+		// Imports of unchanged modules are read here rather than when resolved
+		let Some(content) = content.or_else(|| read_from_path(&path)) else {
+			continue;
+		};
+		let mut statistics =
+			settings.statistics.then(|| ModuleStatistics::new(path.clone(), content.len()));
+		let source_id = SourceId::new(fs, path.clone(), content.clone());
+		let parse_settings = &settings.parse_settings;
+		let module_result = if statistics.is_some() {
+			// Rather than in parallel, lex then parse so that each can be timed
+			time_phase(&mut statistics, "lexing", || {
+				LexedSource::new(&content, parse_settings, source_id)
+			})
+			.and_then(|lexed| {
+				time_phase(&mut statistics, "parsing", || lexed.parse::<Module>(parse_settings))
+			})
+		} else {
+			Module::from_string(content, parse_settings.clone(), source_id, None, Vec::new())
+		};
+
+		let ParseOutput(mut module, state) = match module_result {
+			Ok(output) => output,
+			Err(parse_err) => {
+				diagnostics
+					.push(TempDiagnostic::from_parse_error(parse_err, ErrorWarningInfo::Error));
+				continue;
+			}
+		};
+
+		let mut functions = state.function_extractor;
+		if let Some(statistics) = &mut statistics {
+			statistics.nodes = NodeCounts::count(&module, &mut functions);
+		}
+		// Modules imported only for types may only contain types (or not exist), so imports which
+		// erasure removes are not followed. Names used in types are values in decorator metadata
+		// and JavaScript modules are followed for their side effects
+		let elide_imports = settings.transforms.contains(&Transform::EraseTypes)
+			&& settings.decorators != DecoratorSemantics::Legacy { metadata: true }
+			&& is_typescript(&path);
+		let mut resolved = HashMap::new();
+		let imports = time_phase(&mut statistics, "resolving", || {
+			get_imports(&mut module, &mut functions, elide_imports)
+				.into_iter()
+				.map(|(specifier, position)| {
+					let imported = resolver.resolve(&specifier, &path, read_from_path);
+					(specifier, position, imported)
+				})
+				.collect::<Vec<_>>()
+		});
+		for (specifier, position, imported) in imports {
+			let imported = match imported {
+				Ok(imported) => imported,
+				// Packages which cannot be found may be provided by the runtime
+				Err(ResolveError::NotFound { .. }) if !is_relative(&specifier) => continue,
+				Err(err) => {
+					diagnostics.push(TempDiagnostic {
+						label: err.to_string(),
+						position,
+						kind: ErrorWarningInfo::Error,
+						code: DiagnosticCode::CannotResolve,
+						secondary_labels: Vec::new(),
+						notes: Vec::new(),
+					});
+					continue;
+				}
 			};
-			return (fs, Err(vec![diag]));
+			if !is_source(&imported.path) {
+				continue;
+			}
+			if seen.insert(imported.path.clone()) {
+				queue.push_back((imported.path.clone(), Some(imported.content)));
+			}
+			resolved.insert(specifier, imported.path);
 		}
-	};
 
-	let mut visitors_mut = parser::VisitorsMut {
-		expression_visitors_mut: vec![Box::new(InvertTernaryBranches)],
+		modules.push(ParsedModule { path, module, functions, resolved, statistics });
+	}
+	(modules, reached_unchanged)
+}
+
+/// Specifiers (and their position) of `import` declarations and `import()` with a string literal.
+/// Type only imports (and with `elide_imports`, imports erasure removes) are skipped as they are
+/// not needed at runtime
+fn get_imports(
+	module: &mut Module,
+	functions: &mut ExtractedFunctions,
+	elide_imports: bool,
+) -> Vec<(String, Span)> {
+	let used = elide_imports.then(|| crate::erasure::used_imports(&module.items, functions));
+	let mut imports = module
+		.items
+		.iter()
+		.filter_map(|item| match item {
+			StatementOrDeclaration::Declaration(Declaration::Import(import))
+				if !used
+					.as_ref()
+					.map_or(import.only_type, |used| crate::erasure::is_elided(import, used)) =>
+			{
+				Some((import.from.clone(), import.position.clone()))
+			}
+			_ => None,
+		})
+		.collect::<Vec<_>>();
+
+	let mut visitors = parser::Visitors {
+		expression_visitors: vec![Box::new(CollectDynamicImports)],
 		..Default::default()
 	};
+	module.visit(&mut visitors, &mut imports, functions, &parser::VisitSettings::default());
+	imports
+}
 
-	let mut temp_errors = Vec::new();
+fn is_typescript(path: &Path) -> bool {
+	path.extension()
+		.and_then(|extension| extension.to_str())
+		.is_some_and(|extension| matches!(extension, "ts" | "tsx" | "mts" | "cts"))
+}
 
-	output.0.visit_mut(
-		&mut visitors_mut,
-		&mut temp_errors,
-		&mut output.1.function_extractor,
-		&parser::VisitSettings::default(),
-	);
+/// Whether a resolved module is part of the project rather than a package or declaration file
+fn is_source(path: &Path) -> bool {
+	let is_declaration_file =
+		path.file_name().and_then(|name| name.to_str()).is_some_and(|name| name.contains(".d."));
+	!is_declaration_file
+		&& path.components().all(|component| component.as_os_str() != "node_modules")
+}
 
-	let (content, source_map) = output.0.to_string_with_source_map(
-		&parser::ToStringSettingsAndData(ToStringSettings::minified(), output.1.function_extractor),
-		&fs,
-	);
+/// Output modules are JavaScript so specifiers which include a TypeScript extension need updating.
+/// Specifiers which leave out the extension or the `index` file of a directory are completed, as
+/// they are not resolved in ES modules
+fn rewrite_specifier(specifier: &str, directory: &Path, imported_path: &Path) -> Option<String> {
+	if !is_relative(specifier) || !is_source(imported_path) {
+		return None;
+	}
+	let joined = normalize_path(&directory.join(specifier));
+	let imported = imported_path.to_str()?;
+	let (stem, rest) = if *imported_path == joined {
+		let extension = Path::new(specifier).extension()?.to_str()?;
+		if !matches!(extension, "ts" | "tsx" | "mts" | "cts" | "jsx") {
+			return None;
+		}
+		(&specifier[..specifier.len() - extension.len() - 1], String::new())
+	} else {
+		// The extension or `/index` with the extension
+		let added = imported.strip_prefix(joined.to_str()?)?.replace('\\', "/");
+		if !added.starts_with(['.', '/']) {
+			return None;
+		}
+		let added = added.strip_suffix(imported_path.extension()?.to_str()?)?.to_owned();
+		(specifier.trim_end_matches('/'), added.trim_end_matches('.').to_owned())
+	};
+	Some(format!("{stem}{rest}.{}", output_extension(imported_path)))
+}
 
-	let output = Output { output_path, content, mappings: source_map.mappings };
+fn output_extension(path: &Path) -> &'static str {
+	match path.extension().and_then(|extension| extension.to_str()) {
+		Some("mts" | "mjs") => "mjs",
+		Some("cts" | "cjs") => "cjs",
+		_ => "js",
+	}
+}
+
+/// If `output` is a directory, outputs are placed under it
+pub(crate) fn is_directory(output: &Path) -> bool {
+	output.is_dir() || output.as_os_str().to_str().is_some_and(|path| path.ends_with(['/', '\\']))
+}
+
+pub(crate) fn absolute(path: &Path) -> PathBuf {
+	normalize_path(&std::env::current_dir().unwrap_or_default().join(path))
+}
+
+/// The deepest directory that contains all of `paths`
+fn common_directory<'a>(mut paths: impl Iterator<Item = &'a Path>) -> PathBuf {
+	let Some(first) = paths.next() else {
+		return PathBuf::new();
+	};
+	let mut common = absolute(first).parent().map(ToOwned::to_owned).unwrap_or_default();
+	for path in paths {
+		let path = absolute(path);
+		while !path.starts_with(&common) {
+			if !common.pop() {
+				break;
+			}
+		}
+	}
+	common
+}
+
+fn rewrite_imports(module: &mut Module, specifier_rewrites: &HashMap<String, String>) {
+	for item in module.items.iter_mut() {
+		if let StatementOrDeclaration::Declaration(Declaration::Import(import)) = item {
+			if let Some(rewritten) = specifier_rewrites.get(&import.from) {
+				import.from = rewritten.clone();
+			}
+		}
+	}
+}
+
+struct CollectDynamicImports;
+
+impl parser::Visitor<Expression, Vec<(String, Span)>> for CollectDynamicImports {
+	fn visit(
+		&mut self,
+		item: &Expression,
+		data: &mut Vec<(String, Span)>,
+		_functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		if let Expression::DynamicImport { path, position, .. } = item {
+			if let Expression::StringLiteral(specifier, ..) = path.as_ref() {
+				data.push((specifier.clone(), position.clone()));
+			}
+		}
+	}
+}
+
+struct RewriteDynamicImports(HashMap<String, String>);
 
-	(fs, Ok(BuildOutput { outputs: vec![output], temp_warnings_and_infos: temp_errors }))
+impl parser::VisitorMut<Expression, Vec<TempDiagnostic>> for RewriteDynamicImports {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		_data: &mut Vec<TempDiagnostic>,
+		_functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		if let Expression::DynamicImport { path, .. } = item {
+			if let Expression::StringLiteral(specifier, ..) = path.as_mut() {
+				if let Some(rewritten) = self.0.get(specifier) {
+					*specifier = rewritten.clone();
+				}
+			}
+		}
+	}
 }
 
-struct InvertTernaryBranches;
+pub(crate) struct InvertTernaryBranches;
 
 impl parser::VisitorMut<parser::Expression, Vec<TempDiagnostic>> for InvertTernaryBranches {
 	fn visit_mut(
@@ -103,6 +847,9 @@ impl parser::VisitorMut<parser::Expression, Vec<TempDiagnostic>> for InvertTerna
 					label: String::from("Ternary swapped"),
 					position,
 					kind: error_handling::ErrorWarningInfo::Info,
+					code: DiagnosticCode::TernarySwapped,
+					secondary_labels: Vec::new(),
+					notes: Vec::new(),
 				});
 
 				let temp_swap =
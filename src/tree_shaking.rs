@@ -0,0 +1,242 @@
+//! Which top level items of a module can be removed from a bundle when nothing uses the names they
+//! declare. Declarations are removable when evaluating them cannot have side effects. Calls are
+//! assumed to have side effects unless marked with a `/*#__PURE__*/` annotation, as are reads of
+//! globals (which may not exist) and properties (which may be getters).
+//!
+//! Whether a module can have side effects at all comes from the `sideEffects` field of its
+//! `package.json`, see [crate::resolver::module_has_side_effects]
+
+use parser::{
+	declarations::{
+		classes::{ClassFunctionBase, ClassMember},
+		ClassDeclaration, VariableDeclaration,
+	},
+	expressions::{
+		object_literal::ObjectLiteralMember, MultipleExpression, SpecialOperators,
+		SpreadExpression, TemplateLiteralPart,
+	},
+	extractor::{ExtractedFunctions, GetFunction},
+	is_purity_annotation,
+	operators::{BinaryOperator, UnaryOperator},
+	statements::VarVariableStatement,
+	types::enum_declaration::EnumMember,
+	Declaration, Decorated, Expression, ExpressionOrStatementPosition, PropertyKey, Statement,
+	StatementOrDeclaration, TypeReference, VariableField, VariableFieldTypes, VariableIdentifier,
+	WithComment,
+};
+
+use crate::minify::constants::constant;
+
+/// Whether `item` can be removed if the names it declares are not used. `is_bound` is whether a
+/// name refers to a declaration in the bundle (rather than a global)
+pub(crate) fn is_removable(
+	item: &StatementOrDeclaration,
+	functions: &ExtractedFunctions,
+	is_bound: &impl Fn(&str) -> bool,
+) -> bool {
+	let purity = Purity { functions, is_bound };
+	match item {
+		StatementOrDeclaration::Declaration(declaration) => match declaration {
+			Declaration::Variable(VariableDeclaration::ConstDeclaration {
+				declarations, ..
+			}) => declarations.iter().all(|declaration| {
+				is_identifier(&declaration.name) && purity.expression(&declaration.expression)
+			}),
+			Declaration::Variable(VariableDeclaration::LetDeclaration { declarations, .. }) => {
+				declarations.iter().all(|declaration| {
+					is_identifier(&declaration.name)
+						&& declaration
+							.expression
+							.as_ref()
+							.is_none_or(|expression| purity.expression(expression))
+				})
+			}
+			Declaration::Function(Decorated { decorators, .. }) => decorators.is_empty(),
+			Declaration::ExtractedFunction(_) => true,
+			Declaration::Class(Decorated { decorators, on }) => {
+				decorators.is_empty() && purity.class(on)
+			}
+			Declaration::Enum(Decorated { decorators, on }) => {
+				decorators.is_empty()
+					&& on.members.iter().all(|member| {
+						let EnumMember::Variant { value, .. } = member;
+						value.as_ref().is_none_or(|value| purity.expression(value))
+					})
+			}
+			// Types are removed by erasure (or kept in the output)
+			_ => false,
+		},
+		StatementOrDeclaration::Statement(Statement::VarVariable(VarVariableStatement {
+			declarations,
+			..
+		})) => declarations.iter().all(|declaration| {
+			is_identifier(&declaration.name)
+				&& declaration
+					.expression
+					.as_ref()
+					.is_none_or(|expression| purity.expression(expression))
+		}),
+		StatementOrDeclaration::Statement(Statement::Expression(expression)) => {
+			purity.multiple(expression)
+		}
+		StatementOrDeclaration::Statement(_) => false,
+	}
+}
+
+/// Destructuring can run getters and iterators
+fn is_identifier<T: VariableFieldTypes>(name: &WithComment<VariableField<T>>) -> bool {
+	matches!(name.get_ast(), VariableField::Name(VariableIdentifier::Standard(..)))
+}
+
+struct Purity<'a, F> {
+	functions: &'a ExtractedFunctions,
+	is_bound: &'a F,
+}
+
+impl<'a, F: Fn(&str) -> bool> Purity<'a, F> {
+	/// Whether evaluating `expression` cannot have side effects
+	fn expression(&self, expression: &Expression) -> bool {
+		match expression {
+			Expression::NumberLiteral(..)
+			| Expression::StringLiteral(..)
+			| Expression::BooleanLiteral(..)
+			| Expression::RegexLiteral { .. }
+			| Expression::Null(..)
+			| Expression::ThisReference(..)
+			| Expression::ArrowFunction(..)
+			| Expression::ExpressionFunction(..)
+			| Expression::ExtractedArrowFunction(..)
+			| Expression::ExtractedExpressionFunction(..)
+			| Expression::Comment(..) => true,
+			Expression::VariableReference(name, ..) => {
+				(self.is_bound)(name) || matches!(name.as_str(), "undefined" | "NaN" | "Infinity")
+			}
+			Expression::ArrayLiteral(elements, ..) => {
+				elements.iter().all(|element| match element {
+					SpreadExpression::NonSpread(element) => self.expression(element),
+					SpreadExpression::Empty => true,
+					// Runs the iterator
+					SpreadExpression::Spread(..) => false,
+				})
+			}
+			Expression::ObjectLiteral(object) => object.members.iter().all(|member| match member {
+				ObjectLiteralMember::Shorthand(name, ..) => (self.is_bound)(name),
+				ObjectLiteralMember::Property(key, value, _) => {
+					self.key(key.get_ast()) && self.expression(value)
+				}
+				ObjectLiteralMember::Method(method) => {
+					GetFunction::get_function_ref(self.functions, method.0)
+						.is_some_and(|method| self.key(method.name.get_ast()))
+				}
+				// Runs getters
+				ObjectLiteralMember::SpreadExpression(..) => false,
+			}),
+			Expression::TemplateLiteral(template) => {
+				template.tag.is_none()
+					&& template.parts.iter().all(|part| match part {
+						TemplateLiteralPart::Static(_) => true,
+						// Converting to a string can call `toString`
+						TemplateLiteralPart::Dynamic(expression) => constant(expression).is_some(),
+					})
+			}
+			Expression::ParenthesizedExpression(inner, ..) => self.multiple(inner),
+			Expression::BinaryOperation { lhs, operator, rhs, .. } => match operator {
+				BinaryOperator::StrictEqual
+				| BinaryOperator::StrictNotEqual
+				| BinaryOperator::LogicalAnd
+				| BinaryOperator::LogicalOr
+				| BinaryOperator::NullCoalescing => self.expression(lhs) && self.expression(rhs),
+				// Others can convert objects to primitives
+				_ => constant(lhs).is_some() && constant(rhs).is_some(),
+			},
+			Expression::UnaryOperation { operator, operand, .. } => match operator {
+				// Does not throw for globals which do not exist
+				UnaryOperator::TypeOf if matches!(**operand, Expression::VariableReference(..)) => {
+					true
+				}
+				UnaryOperator::LogicalNot | UnaryOperator::TypeOf | UnaryOperator::Void => {
+					self.expression(operand)
+				}
+				UnaryOperator::Plus | UnaryOperator::Negation | UnaryOperator::BitwiseNot => {
+					constant(operand).is_some()
+				}
+				_ => false,
+			},
+			Expression::SpecialOperators(
+				SpecialOperators::AsExpression { value, .. }
+				| SpecialOperators::SatisfiesExpression { value, .. },
+				..,
+			) => self.expression(value),
+			Expression::TernaryExpression { condition, truthy_result, falsy_result, .. } => {
+				self.expression(condition)
+					&& self.expression(truthy_result)
+					&& self.expression(falsy_result)
+			}
+			Expression::ClassExpression(class, _) => self.class(class),
+			Expression::PrefixComment(comment, expression, ..) if is_purity_annotation(comment) => {
+				match expression.get_non_parenthesized() {
+					Expression::FunctionCall { arguments, .. } => self.arguments(arguments),
+					Expression::ConstructorCall { arguments, .. } => {
+						arguments.as_deref().is_none_or(|arguments| self.arguments(arguments))
+					}
+					expression => self.expression(expression),
+				}
+			}
+			Expression::PrefixComment(_, expression, ..)
+			| Expression::PostfixComment(expression, ..) => self.expression(expression),
+			_ => false,
+		}
+	}
+
+	fn multiple(&self, expression: &MultipleExpression) -> bool {
+		expression.lhs.as_ref().is_none_or(|lhs| self.multiple(lhs))
+			&& self.expression(&expression.rhs)
+	}
+
+	fn arguments(&self, arguments: &[SpreadExpression]) -> bool {
+		arguments.iter().all(|argument| match argument {
+			SpreadExpression::NonSpread(argument) => self.expression(argument),
+			SpreadExpression::Empty => true,
+			SpreadExpression::Spread(..) => false,
+		})
+	}
+
+	/// Computed keys are converted to strings
+	fn key(&self, key: &PropertyKey) -> bool {
+		match key {
+			PropertyKey::Computed(key, ..) => constant(key).is_some(),
+			_ => true,
+		}
+	}
+
+	/// Classes run static property initializers and computed keys when they are declared
+	fn class<T: ExpressionOrStatementPosition>(&self, class: &ClassDeclaration<T>) -> bool {
+		let extends = match &class.extends {
+			None => true,
+			Some(TypeReference::Name(name, _)) => (self.is_bound)(name),
+			Some(_) => false,
+		};
+		extends
+			&& class.members.iter().all(|Decorated { decorators, on: member }| {
+				decorators.is_empty()
+					&& match member {
+						ClassMember::Constructor(_) => true,
+						ClassMember::Function(_, function) => {
+							GetFunction::<ClassFunctionBase>::get_function_ref(
+								self.functions,
+								function.0,
+							)
+							.is_some_and(|function| self.key(function.name.get_ast()))
+						}
+						ClassMember::Property(is_static, property) => {
+							self.key(property.key.get_ast())
+								&& (is_static.is_none()
+									|| property
+										.value
+										.as_ref()
+										.is_none_or(|value| self.expression(value)))
+						}
+					}
+			})
+	}
+}
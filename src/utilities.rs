@@ -1,6 +1,6 @@
 use std::fmt::Arguments;
 use std::io;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 
 pub(crate) fn print_info() {
 	print_to_cli(format_args!("{}@{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")));
@@ -46,7 +46,9 @@ pub(crate) fn read_fs_path_to_string(path: impl AsRef<Path>) -> io::Result<Strin
 // TODO pass on IO error...
 #[cfg(target_family = "wasm")]
 pub(crate) fn read_fs_path_to_string(path: impl AsRef<Path>) -> io::Result<String> {
-	Ok(super::wasm_bindings::read_from_path(path.as_ref().to_str().expect("Invalid path")))
+	let path = path.as_ref().to_str().expect("Invalid path");
+	super::wasm_bindings::read_from_path(path)
+		.ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{path} not found")))
 }
 
 #[cfg(target_family = "wasm")]
@@ -63,3 +65,90 @@ pub(crate) fn get_cli_args() -> Vec<String> {
 pub(crate) fn get_cli_args() -> Vec<String> {
 	std::env::args().skip(1).collect()
 }
+
+/// Resolves `.` and `..` without touching the file system (paths may not exist yet)
+pub(crate) fn normalize_path(path: &Path) -> PathBuf {
+	let mut normalized = PathBuf::new();
+	for component in path.components() {
+		match component {
+			Component::CurDir => {}
+			Component::ParentDir => {
+				if matches!(normalized.components().next_back(), Some(Component::Normal(_))) {
+					normalized.pop();
+				} else {
+					normalized.push(component);
+				}
+			}
+			component => normalized.push(component),
+		}
+	}
+	normalized
+}
+
+/// Whether a `/` separated path matches a glob. `*` and `?` match within a path segment, `**`
+/// matches any number of segments and `{a,b}` matches either alternative. Directories match
+/// every path under them
+pub(crate) fn glob_matches(pattern: &str, path: &str) -> bool {
+	fn segments(path: &str) -> Vec<&str> {
+		path.split(['/', '\\']).filter(|segment| !segment.is_empty() && *segment != ".").collect()
+	}
+
+	fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+		match pattern.split_first() {
+			None => path.is_empty(),
+			Some((&"**", rest)) => (0..=path.len()).any(|skip| match_segments(rest, &path[skip..])),
+			Some((segment, rest)) => path.split_first().is_some_and(|(name, path)| {
+				match_segment(segment.as_bytes(), name.as_bytes()) && match_segments(rest, path)
+			}),
+		}
+	}
+
+	fn match_segment(pattern: &[u8], name: &[u8]) -> bool {
+		match pattern.split_first() {
+			None => name.is_empty(),
+			Some((b'*', rest)) => (0..=name.len()).any(|skip| match_segment(rest, &name[skip..])),
+			Some((b'?', rest)) => !name.is_empty() && match_segment(rest, &name[1..]),
+			Some((chr, rest)) => name.first() == Some(chr) && match_segment(rest, &name[1..]),
+		}
+	}
+
+	let path = segments(path);
+	expand_braces(pattern).iter().any(|pattern| {
+		let mut pattern = segments(pattern);
+		pattern.push("**");
+		match_segments(&pattern, &path)
+	})
+}
+
+/// `a/{b,c}.ts` -> [`a/b.ts`, `a/c.ts`]
+fn expand_braces(pattern: &str) -> Vec<String> {
+	let Some(start) = pattern.find('{') else {
+		return vec![pattern.to_owned()];
+	};
+	let mut depth = 0;
+	let mut alternatives = Vec::new();
+	let mut alternative_start = start + 1;
+	for (index, chr) in pattern.char_indices().skip_while(|(index, _)| *index <= start) {
+		match chr {
+			'{' => depth += 1,
+			'}' if depth > 0 => depth -= 1,
+			',' if depth == 0 => {
+				alternatives.push(&pattern[alternative_start..index]);
+				alternative_start = index + 1;
+			}
+			'}' => {
+				alternatives.push(&pattern[alternative_start..index]);
+				let (prefix, suffix) = (&pattern[..start], &pattern[index + 1..]);
+				return alternatives
+					.into_iter()
+					.flat_map(|alternative| {
+						expand_braces(&format!("{prefix}{alternative}{suffix}"))
+					})
+					.collect();
+			}
+			_ => {}
+		}
+	}
+	// Unclosed, treated as a literal
+	vec![pattern.to_owned()]
+}
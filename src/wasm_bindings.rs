@@ -11,9 +11,9 @@ extern "C" {
 	#[wasm_bindgen]
 	pub(crate) fn read_from_cli() -> String;
 
-	// TODO error
+	/// `None` when the JS side returns `undefined` or `null`, e.g. as the file does not exist
 	#[wasm_bindgen]
-	pub(crate) fn read_from_path(path: &str) -> String;
+	pub(crate) fn read_from_path(path: &str) -> Option<String>;
 
 	#[wasm_bindgen(raw_module = "./bindings")]
 	pub(crate) fn get_cli_args() -> String;
@@ -21,6 +21,16 @@ extern "C" {
 
 #[wasm_bindgen]
 pub fn build_wasm(content: String, path: String) -> JsValue {
-	let (_fs, result) = crate::temp::build(content, path, "OUTPUT".to_owned());
+	let mut pipeline = crate::pipeline::Pipeline::new(crate::temp::BuildSettings {
+		output_settings: parser::ToStringSettings::minified(),
+		..Default::default()
+	});
+	let (_fs, result) = pipeline.build(
+		path.into(),
+		content,
+		&crate::resolver::Resolver::default(),
+		&|path: &std::path::Path| read_from_path(path.to_str()?),
+		"OUTPUT".into(),
+	);
 	serde_wasm_bindgen::to_value(&result).unwrap()
 }
@@ -0,0 +1,64 @@
+use std::{path::Path, process::Command};
+
+/// Runs `ezno check` in the check fixture, returning the exit code and stdout after stderr
+fn check(arguments: &[&str]) -> (i32, String) {
+	let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/check");
+	let output = Command::new(env!("CARGO_BIN_EXE_ezno"))
+		.current_dir(fixture)
+		.arg("check")
+		.args(arguments)
+		.output()
+		.unwrap();
+	let output_text = [output.stderr, output.stdout].concat();
+	(output.status.code().unwrap(), String::from_utf8(output_text).unwrap())
+}
+
+#[test]
+fn summary() {
+	let (code, output) = check(&["src/ok.ts", "src/lint.js"]);
+	assert_eq!(code, 0);
+	let lines = output.lines().skip_while(|line| !line.starts_with("ok")).collect::<Vec<_>>();
+	assert_eq!(lines[..2], ["ok     src/ok.ts", "warn   src/lint.js: 0 errors, 1 warning"]);
+	assert!(lines[2].starts_with("Checked 2 files in "));
+	assert!(lines[2].ends_with(": 0 errors, 1 warning"));
+}
+
+#[test]
+fn lints_from_configuration() {
+	let (code, output) = check(&["--diagnostics-format", "github"]);
+	assert_eq!(code, 1);
+	assert_eq!(
+		output.trim(),
+		[
+			"::error file=src/errors.ts,line=3,col=7,endLine=3,endColumn=8,title=E3001::'a' has already been declared",
+			"::error file=src/errors.ts,line=12,col=1,endLine=12,endColumn=7,title=E3002::Return statement is only valid in a function",
+			"::error file=src/errors.ts,line=13,col=1,endLine=13,endColumn=6,title=E3003::Break statement is only valid in a loop or switch",
+			"::error file=src/errors.ts,line=15,col=23,endLine=15,endColumn=31,title=E3003::Continue statement is only valid in a loop",
+			"::error file=src/errors.ts,line=17,col=24,endLine=17,endColumn=29,title=E3004::Label 'inner' is not defined",
+			"::warning file=src/lint.js,line=1,col=1,endLine=1,endColumn=10,title=L0002::Unexpected var declaration",
+		]
+		.join("\n")
+	);
+}
+
+#[test]
+fn globs_and_lints() {
+	let (code, output) =
+		check(&["src/**/*.ts", "--lint", "no-debugger", "--lint", "no-loose-equality"]);
+	assert_eq!(code, 1);
+	assert!(output.contains("L0003"));
+	assert!(output.contains("L0001"));
+	assert!(!output.contains("lint.js"));
+	assert!(output.contains("error  src/errors.ts: 5 errors, 2 warnings"));
+	assert!(output.contains("ok     src/ok.ts"));
+	assert!(output.contains("Checked 2 files"));
+}
+
+#[test]
+fn panics_are_reported_for_the_file() {
+	let (code, output) = check(&["panics/optional.ts", "src/ok.ts", "--jobs", "2"]);
+	assert_eq!(code, 1);
+	assert!(output.contains("Could not check this file"), "{output}");
+	assert!(output.contains("error  panics/optional.ts: 1 error, 0 warnings"), "{output}");
+	assert!(output.contains("ok     src/ok.ts"), "{output}");
+}
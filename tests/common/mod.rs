@@ -0,0 +1,30 @@
+//! Helpers for tests which run `ezno build` on a fixture. Not every test uses all of them
+#![allow(dead_code)]
+
+use std::{
+	path::{Path, PathBuf},
+	process::{Command, Output},
+};
+
+/// The directory `tests/fixtures/{name}`
+pub fn fixture(name: &str) -> PathBuf {
+	Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+}
+
+/// Runs `ezno build` with `arguments` in `directory`
+pub fn ezno(directory: &Path, arguments: &[&str]) -> Output {
+	Command::new(env!("CARGO_BIN_EXE_ezno"))
+		.current_dir(directory)
+		.arg("build")
+		.args(arguments)
+		.output()
+		.unwrap()
+}
+
+/// Builds a file in the fixture, returning the output (printed as there is no output path) and
+/// stderr
+pub fn build(fixture_name: &str, file: &str, arguments: &[&str]) -> (String, String) {
+	let output = ezno(&fixture(fixture_name), &[&[file], arguments].concat());
+	assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+	(String::from_utf8(output.stdout).unwrap(), String::from_utf8(output.stderr).unwrap())
+}
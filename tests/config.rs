@@ -0,0 +1,100 @@
+use std::path::{Path, PathBuf};
+
+use ezno_lib::config::{Configuration, ConfigurationError, SourceMaps};
+
+fn fixture() -> PathBuf {
+	Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/config")
+}
+
+fn read(path: &Path) -> Option<String> {
+	std::fs::read_to_string(path).ok()
+}
+
+#[test]
+fn find_configuration() {
+	let configuration =
+		Configuration::find(&fixture().join("src/components"), &read).unwrap().unwrap();
+
+	assert_eq!(configuration.directory, fixture());
+	assert_eq!(configuration.entry_points, vec![fixture().join("src/index.ts")]);
+	assert_eq!(configuration.out_dir, Some(fixture().join("dist")));
+	assert_eq!(configuration.jsx, Some(false));
+	assert_eq!(configuration.decorators, None);
+	assert_eq!(configuration.minify, Some(true));
+	assert_eq!(configuration.comments, None);
+	assert_eq!(configuration.source_maps, Some(Some(SourceMaps::Inline)));
+	assert_eq!(configuration.transforms, Some(Vec::new()));
+	assert_eq!(configuration.bundle, Some(true));
+	assert_eq!(configuration.global_name.as_deref(), Some("Library"));
+	assert_eq!(configuration.tree_shaking, Some(false));
+
+	let parse_settings = configuration.parse_settings();
+	assert!(!parse_settings.jsx);
+	assert!(parse_settings.decorators);
+	assert!(parse_settings.server_blocks && parse_settings.module_blocks);
+
+	assert_eq!(Configuration::find(Path::new("/"), &read), Ok(None));
+}
+
+#[test]
+fn include_and_exclude() {
+	let configuration = Configuration::find(&fixture(), &read).unwrap().unwrap();
+	let files = configuration
+		.files()
+		.into_iter()
+		.map(|path| path.strip_prefix(fixture()).unwrap().display().to_string().replace('\\', "/"))
+		.collect::<Vec<_>>();
+
+	assert_eq!(files, ["src/index.ts", "src/components/button.tsx", "src/util.ts"]);
+}
+
+#[test]
+fn errors() {
+	let error = |source: &str| Configuration::from_json(source, PathBuf::new()).unwrap_err();
+
+	assert_eq!(error(r#"{ "entri": "a.ts" }"#), "Unknown option 'entri'");
+	assert_eq!(error(r#"{ "parse": { "jsx": "yes" } }"#), "Expected 'jsx' to be a boolean");
+	assert_eq!(
+		error(r#"{ "entry": 2 }"#),
+		"Expected 'entry' to be a string or an array of strings"
+	);
+	assert_eq!(
+		error(r#"{ "bundle": { "format": "cjs" } }"#),
+		"Unknown bundle format 'cjs', expected 'esm' or 'iife'"
+	);
+	assert_eq!(
+		error(r#"{ "transforms": [["lower-jsx", {}]] }"#),
+		"Transform 'lower-jsx' does not take options"
+	);
+	assert_eq!(
+		error(r#"{ "transforms": [["my-pass"]] }"#),
+		"Expected a transform with options to be a name and a value"
+	);
+	assert_eq!(
+		error(r#"{ "jsx": { "runtime": "automatic", "pragma": "h" } }"#),
+		"Pragmas only apply to the classic JSX runtime"
+	);
+	assert_eq!(
+		error(r#"{ "jsx": { "runtime": "preserve" } }"#),
+		"Unknown JSX runtime 'preserve', expected 'classic' or 'automatic'"
+	);
+	assert_eq!(
+		error(r#"{ "decorators": { "version": "2022-03" } }"#),
+		"Unknown decorators version '2022-03', expected '2023-05' or 'legacy'"
+	);
+	assert_eq!(
+		error(r#"{ "decorators": { "metadata": true } }"#),
+		"Metadata only applies to legacy decorators"
+	);
+	assert_eq!(
+		error("{\n\t\"entry\": \"a.ts\"\n\t\"outDir\": \"b\"\n}"),
+		"Expected '}' found '\"' at 3:2"
+	);
+
+	let path = fixture().join("ezno.json");
+	let invalid = |_: &Path| Some("[]".to_owned());
+	assert_eq!(
+		Configuration::find(&fixture(), &invalid),
+		Err(ConfigurationError { path, reason: "Expected an object".to_owned() })
+	);
+}
@@ -0,0 +1,199 @@
+mod common;
+
+use common::{ezno, fixture};
+
+fn build(file: &str, arguments: &[&str]) -> (String, String) {
+	common::build("decorators", file, arguments)
+}
+
+#[test]
+fn standard_semantics() {
+	let (output, stderr) = build("standard.js", &["--decorators", "2023-05"]);
+	assert!(stderr.is_empty(), "{stderr}");
+	assert!(output.starts_with("var __esDecorate = function ("), "{output}");
+	assert!(output.contains("var __runInitializers = function ("), "{output}");
+	for expected in [
+		"let Counter = (() => {",
+		"_classDecorators = [logged], _classExtraInitializers = [], _classDescriptor, _count_decorators = [logged], _count_initializers = [], _limit_decorators = [logged], _limit_initializers = [], _increment_decorators = [logged, bound], _double_decorators = [logged], _a, _member_decorators = [logged], _staticExtraInitializers = [], _instanceExtraInitializers = [];",
+		// The class is anonymous so that references in it are to the decorated class
+		"var Counter = class {",
+		// Instance initializers run before the first field is initialized
+		"count = (__runInitializers(this, _instanceExtraInitializers), __runInitializers(this, _count_initializers, 2))",
+		// Members are decorated when the first static field is initialized
+		"static limit = (__esDecorate(this, null, _increment_decorators, { kind: \"method\", name: \"increment\", static: false, private: false, access: { has: obj => \"increment\" in obj, get: obj => obj.increment }, metadata: _metadata }, null, _instanceExtraInitializers), __esDecorate(this, null, _double_decorators, { kind: \"getter\", name: \"double\"",
+		"__esDecorate(this, null, _member_decorators, { kind: \"method\", name: _a, static: false, private: false, access: { has: obj => _a in obj, get: obj => obj[_a] }, metadata: _metadata }, null, _instanceExtraInitializers), __esDecorate(null, null, _limit_decorators, { kind: \"field\", name: \"limit\", static: true",
+		"}, metadata: _metadata }, _limit_initializers, _staticExtraInitializers), __esDecorate(null, null, _count_decorators,",
+		"__runInitializers(this, _staticExtraInitializers), __runInitializers(this, _limit_initializers, 10))",
+		"[_a = \"computed\"]() {",
+		"__esDecorate(null, _classDescriptor = { value: Counter }, _classDecorators, { kind: \"class\", name: \"Counter\", metadata: _metadata }, null, _classExtraInitializers);
+    Counter = _classDescriptor.value;
+    _metadata && Object.defineProperty(Counter, Symbol.metadata, { enumerable: true, configurable: true, writable: true, value: _metadata });
+    __runInitializers(Counter, _classExtraInitializers);
+    return Counter
+})();",
+	] {
+		assert!(output.contains(expected), "{expected} in {output}");
+	}
+}
+
+#[test]
+fn standard_class_expressions() {
+	let (output, _) = build("expressions.js", &["--decorators", "2023-05"]);
+	for expected in [
+		// Metadata is inherited and a constructor is added to run initializers
+		"Object.create(Base[Symbol.metadata] || null)",
+		"var Worker = class Worker extends Base {
+        constructor(...args) {
+            super(...args);
+            __runInitializers(this, _instanceExtraInitializers2)
+        }",
+		// Without static fields members are decorated after the class
+		"    };
+    __esDecorate(Worker, null, _start_decorators, { kind: \"method\", name: \"start\"",
+		"let _default = (() => {",
+		"var _class = class {",
+		"{ kind: \"class\", name: \"default\", metadata: _metadata }",
+		"export default _default;",
+	] {
+		assert!(output.contains(expected), "{expected} in {output}");
+	}
+}
+
+#[test]
+fn legacy_semantics() {
+	let (output, _) = build("legacy.ts", &["--decorators", "legacy"]);
+	assert!(output.starts_with("var __decorate = function ("), "{output}");
+	assert!(!output.contains("__metadata"), "{output}");
+	// Instance members are decorated before static members, then the class is reassigned
+	assert!(
+		output.contains(
+			r#"export let Greeter = class Greeter {
+    greeting
+    level
+    static count = 0
+"#
+		),
+		"{output}"
+	);
+	assert!(
+		output.trim_end().ends_with(
+			r#"};
+__decorate([field], Greeter.prototype, "greeting", void 0);
+__decorate([field], Greeter.prototype, "level", void 0);
+__decorate([enumerable(false)], Greeter.prototype, "greet", null);
+__decorate([enumerable(true)], Greeter.prototype, "loud", null);
+__decorate([enumerable(true)], Greeter.prototype, "fetch", null);
+__decorate([field], Greeter, "count", void 0);
+Greeter = __decorate([sealed], Greeter);
+console.log(Object.keys(Greeter.prototype).join(","), Object.isSealed(Greeter));
+console.log(log.join("\n"))"#
+		),
+		"{output}"
+	);
+
+	let (output, _) = build("expressions.js", &["--decorators", "legacy"]);
+	assert!(output.starts_with("var _a;\n"), "{output}");
+	assert!(
+		output.contains(
+			r#"}, __decorate([tracked], _a.prototype, "start", null), _a);
+new Service(1);
+let _default = class {"#
+		),
+		"{output}"
+	);
+	assert!(
+		output.contains(
+			r#"__decorate([tracked], _default.prototype, "stop", null);
+_default = __decorate([register], _default);
+export default _default;"#
+		),
+		"{output}"
+	);
+}
+
+#[test]
+fn legacy_metadata() {
+	let (output, _) = build("legacy.ts", &["--decorators", "legacy", "--decorator-metadata"]);
+	// Interfaces and type only imports are `Object` and enums are the type of their values.
+	// Metadata references `Level` (as a value) so it is kept
+	assert!(
+		output.contains(
+			r#"__decorate([field, __metadata("design:type", String)], Greeter.prototype, "greeting", void 0);
+__decorate([field, __metadata("design:type", Number)], Greeter.prototype, "level", void 0);
+__decorate([enumerable(false), __metadata("design:type", Function), __metadata("design:paramtypes", [Object, Number]), __metadata("design:returntype", String)], Greeter.prototype, "greet", null);
+__decorate([enumerable(true), __metadata("design:type", String), __metadata("design:paramtypes", [])], Greeter.prototype, "loud", null);
+__decorate([enumerable(true), __metadata("design:type", Function), __metadata("design:paramtypes", [String]), __metadata("design:returntype", Promise)], Greeter.prototype, "fetch", null);
+__decorate([field, __metadata("design:type", Number)], Greeter, "count", void 0);
+Greeter = __decorate([sealed, __metadata("design:paramtypes", [String, Object])], Greeter);"#
+		),
+		"{output}"
+	);
+
+	let output = ezno(&fixture("decorators"), &["standard.js", "--decorator-metadata"]);
+	assert_eq!(
+		String::from_utf8(output.stdout).unwrap(),
+		"Metadata only applies to legacy decorators\n"
+	);
+}
+
+#[test]
+fn configured() {
+	let output = ezno(&fixture("decorators").join("configured"), &["service.ts"]);
+	let output = String::from_utf8(output.stdout).unwrap();
+	assert!(
+		output.trim_end().ends_with(
+			r#"export class Service {
+    name
+}
+__decorate([inject, __metadata("design:type", String)], Service.prototype, "name", void 0)"#
+		),
+		"{output}"
+	);
+}
+
+#[test]
+fn invalid_decorators() {
+	let (output, stderr) = build("invalid.ts", &["--decorators", "legacy"]);
+	assert_eq!(stderr.matches("W0003").count(), 2, "{stderr}");
+	assert!(!output.contains('@'), "{output}");
+}
+
+#[test]
+fn auto_accessors() {
+	let (output, stderr) = build("accessors.js", &["--decorators", "2023-05"]);
+	assert!(stderr.is_empty(), "{stderr}");
+	for expected in [
+		"_size_storage = Symbol(), _count_storage = Symbol(), _key, _storage = Symbol(),",
+		"[_size_storage] = (__runInitializers(this, _instanceExtraInitializers), __runInitializers(this, _size_initializers, 5))
+        get size() {
+            return this[_size_storage]
+        }
+        set size(value) {
+            this[_size_storage] = value
+        }",
+		// Decorated with the methods, on the class rather than the instance
+		"__esDecorate(this, null, _size_decorators, { kind: \"accessor\", name: \"size\", static: false, private: false, access: { has: obj => \"size\" in obj, get: obj => obj.size, set: (obj, value) => {",
+		"}, metadata: _metadata }, _size_initializers, _instanceExtraInitializers)",
+		// Computed keys are evaluated once
+		"[_storage] = 3
+        get [_key = key]() {
+            return this[_storage]
+        }
+        set [_key](value) {",
+	] {
+		assert!(output.contains(expected), "{expected} in {output}");
+	}
+
+	let (output, _) = build("accessors.js", &["--decorators", "legacy"]);
+	assert!(
+		output.starts_with("var _a = Symbol(), _b = Symbol(), _c, _d = Symbol();\n"),
+		"{output}"
+	);
+	assert!(
+		output.contains(
+			r#"__decorate([tracked], Store.prototype, "size", null);
+__decorate([tracked], Store, "count", null);"#
+		),
+		"{output}"
+	);
+}
@@ -0,0 +1,72 @@
+use std::{path::Path, process::Command};
+
+/// Builds a file in the diagnostics fixture and returns stdout
+fn build(file: &str, diagnostics_format: &str) -> String {
+	let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/diagnostics");
+	let output_path =
+		std::env::temp_dir().join(format!("ezno-diagnostics-{diagnostics_format}.js"));
+	let output = Command::new(env!("CARGO_BIN_EXE_ezno"))
+		.current_dir(fixture)
+		.args(["build", file])
+		.arg(output_path)
+		.args(["--diagnostics-format", diagnostics_format])
+		.output()
+		.unwrap();
+	String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn exit_status() {
+	let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/diagnostics");
+	let status = |file: &str| {
+		let output = Command::new(env!("CARGO_BIN_EXE_ezno"))
+			.current_dir(&fixture)
+			.args(["build", file])
+			.output()
+			.unwrap();
+		output.status
+	};
+	assert_eq!(status("error.ts").code(), Some(1));
+	assert!(status("info.ts").success());
+}
+
+// `error.ts` has a syntax error after an emoji, which is 4 bytes in UTF-8 and 2 code units in
+// UTF-16
+
+#[test]
+fn json() {
+	assert_eq!(
+		build("error.ts", "json").trim(),
+		r#"[{"severity":"error","code":"E0009","message":"Expected identifier at variable reference, found SemiColon","file":"error.ts","range":{"start":28,"end":29},"start":{"line":1,"column":29,"utf16Column":27},"end":{"line":1,"column":30,"utf16Column":28},"labels":[],"notes":[]}]"#
+	);
+}
+
+#[test]
+fn labels() {
+	assert_eq!(
+		build("unclosed.ts", "json").trim(),
+		r#"[{"severity":"error","code":"E0006","message":"Unmatched brackets","file":"unclosed.ts","range":{"start":15,"end":15},"start":{"line":1,"column":16,"utf16Column":16},"end":{"line":1,"column":16,"utf16Column":16},"labels":[{"message":"Unclosed bracket","start":10,"end":11}],"notes":[]}]"#
+	);
+}
+
+#[test]
+fn github() {
+	assert_eq!(
+		build("error.ts", "github").trim(),
+		"::error file=error.ts,line=1,col=27,endLine=1,endColumn=28,title=E0009::Expected identifier at variable reference, found SemiColon"
+	);
+	assert_eq!(
+		build("info.ts", "github").trim(),
+		"::notice file=info.ts,line=2,col=18,endLine=2,endColumn=28,title=I0001::Ternary swapped"
+	);
+}
+
+#[test]
+fn sarif() {
+	let log = build("error.ts", "sarif");
+	assert!(log.starts_with(r#"{"version":"2.1.0","#));
+	assert!(log.contains(r#"{"id":"E0009","shortDescription":{"text":"Syntax error"}}"#));
+	assert!(log.contains(
+		r#""region":{"startLine":1,"startColumn":27,"endLine":1,"endColumn":28,"byteOffset":28,"byteLength":1}"#
+	));
+}
@@ -0,0 +1,323 @@
+mod common;
+
+use common::{ezno, fixture};
+
+fn build(file: &str, arguments: &[&str]) -> (String, String) {
+	common::build("downlevel", file, arguments)
+}
+
+#[test]
+fn operators() {
+	let (output, stderr) = build("operators.js", &["--target", "es5"]);
+	assert_eq!(
+		output.trim_end(),
+		r#"export function operators(options, cache, key) {
+    var _e;
+    var _b, _c, _d;
+    var _a;
+    (((_d = options.retries) != null ? _d : (options.retries = 3)));
+    (cache[_a = key()] || (cache[_a] = new Map()));
+    (options.verbose && (options.verbose = options.level > 1));
+    var area = Math.pow(options.size, 2);
+    (options.scale = Math.pow(options.scale, 0.5));
+    var name = ((_b = ((_c = options.user) == null ? void 0 : _c.profile.name)) != null ? _b : "anonymous");
+    return Object.assign({  }, options, (_e = { area: area, name: name }, _e[options.key] = area, _e.describe = function () {
+        return name
+    }, _e))
+}"#
+	);
+	assert!(stderr.is_empty());
+}
+
+#[test]
+fn newer_targets_keep_supported_syntax() {
+	// Logical assignment is from ES2021, optional chaining ES2020, object spread ES2018 and
+	// exponentiation ES2016
+	let (es2020, _) = build("operators.js", &["--target", "es2020"]);
+	assert!(es2020.contains("(options.verbose && (options.verbose = options.level > 1))"));
+	assert!(es2020.contains(r#"options.user?.profile.name ?? "anonymous""#));
+
+	let (es2017, _) = build("operators.js", &["--target", "ES2017"]);
+	assert!(es2017
+		.contains("Object.assign({  }, options, { area, name, [options.key]: area, describe() {"));
+	assert!(es2017.contains("options.size ** 2"));
+
+	let (es2015, _) = build("operators.js", &["--target", "es2015"]);
+	assert!(es2015.contains("Math.pow(options.size, 2)"));
+}
+
+#[test]
+fn functions() {
+	let (output, stderr) = build("functions.js", &["--target", "es5"]);
+	let (helpers, output) = output.split_at(output.find("export function").unwrap());
+	assert!(helpers.starts_with("var __values = function (object) {"), "{helpers}");
+	assert_eq!(
+		output.trim_end(),
+		r#"export function greet(_c) {
+    var rest = Array.prototype.slice.call(arguments, 1);
+    var _this = this, _arguments = arguments;
+    var name = _c.name, _d = _c.title, title = (_d === void 0 ? "friend" : _d);
+    var first = rest[0], _f = rest[2], third = (_f === void 0 ? 0 : _f), others = rest.slice(3);
+    var a = first.a, _g = first.b, c = _g.c, _h = Object.assign({  }, first), remaining = (delete _h["a"], delete _h["b"], _h);
+    var handler = function (event) {
+        return _this.handle(event, _arguments[0])
+    };
+    return "Hello ".concat(title, " ", name, ", ", others.length + third)
+}
+for (var _a = __values(entries), _b = _a.next(); !_b.done; _b = _a.next()) {
+    var _e = _b.value, key = _e[0], value = _e[1];
+    console.log("".concat(key, ": \"", value, "\""))
+}"#
+	);
+	assert!(stderr.is_empty());
+
+	let (es2015, _) = build("functions.js", &["--target", "es2015"]);
+	assert!(es2015.contains("for (const [key, value] of entries) {"));
+}
+
+#[test]
+fn classes() {
+	let (output, stderr) = build("classes.js", &["--target", "es5"]);
+	let (helpers, output) = output.split_at(output.find("var Animal").unwrap());
+	assert!(helpers.starts_with("var __extends = function (child, parent) {"), "{helpers}");
+	assert_eq!(
+		output.trim_end(),
+		r#"var Animal = (function () {
+    function Animal(name) {
+        this.legs = 4;
+        this.name = name
+    }
+    Animal.prototype.speak = function () {
+        return "".concat(this.name, " makes a sound")
+    };
+    Object.defineProperty(Animal.prototype, "description", { get: function () {
+            return this.name
+        }, set: function (value) {
+            this.name = value
+        }, configurable: true });
+    Animal.count = 0;
+    Animal.next = (function () {
+        return this.count + 1
+    }).call(Animal);
+    return Animal
+})();
+export var Dog = (function (_super) {
+    function Dog(name) {
+        var _this = this;
+        _super.call(this, name);
+        this.bark = function () {
+            return _this.speak()
+        }
+    }
+    __extends(Dog, _super);
+    Dog.prototype.speak = function () {
+        return _super.prototype.speak.call(this) + " (woof)"
+    };
+    Dog.create = function () {
+        return _super.create.call(this)
+    };
+    return Dog
+})(Animal);
+export var Puppy = (function (_super) {
+    function Puppy() {
+        _super.apply(this, arguments);
+        this.tricks = this.legs / 2
+    }
+    __extends(Puppy, _super);
+    return Puppy
+})(Dog)"#
+	);
+	assert!(stderr.is_empty());
+}
+
+#[test]
+fn spread() {
+	let (output, stderr) = build("spread.js", &["--target", "es5"]);
+	let (helpers, output) = output.split_at(output.find("export function").unwrap());
+	assert!(helpers.starts_with("var __spreadArray = function (to, from) {"), "{helpers}");
+	assert_eq!(
+		output.trim_end(),
+		r#"export function spread(values, options) {
+    var _a;
+    console.log.apply(console, __spreadArray(__spreadArray([], values), ["done"]));
+    var items = __spreadArray(__spreadArray(__spreadArray([0], values), new Set(values)), [4]);
+    options.handler.apply(options, __spreadArray([], items));
+    (_a = getOptions()).handler.apply(_a, __spreadArray([], items));
+    return new (Function.prototype.bind.apply(Date, __spreadArray([void 0], values)))()
+}"#
+	);
+	assert!(stderr.is_empty());
+
+	let (es2015, _) = build("spread.js", &["--target", "es2015"]);
+	assert!(es2015.contains("const items = [0, ...values, ...new Set(values), 4];"));
+}
+
+#[test]
+fn block_scoping() {
+	let (output, stderr) = build("block_scoping.js", &["--target", "es5"]);
+	let (helpers, output) = output.split_at(output.find("export var limit").unwrap());
+	assert!(helpers.starts_with("var __values = function (object) {"), "{helpers}");
+	assert_eq!(
+		output.trim_end(),
+		r#"export var limit = 3;
+var total = 0;
+{
+    var total_1 = limit;
+    console.log(total_1)
+}
+export function count(items) {
+    var callbacks = [];
+    var _loop = function (i) {
+        var last;
+        if (items[i]) last = i;
+        if (last > limit) return "break";
+        console.log(last);
+        callbacks.push(function () {
+            return i
+        })
+    };
+    for (var i = 0; i < items.length; i++) {
+        var _state = _loop(i);
+        if (_state === "break") break;
+    }
+    for (var _a = __values(items), _b = _a.next(); !_b.done; _b = _a.next()) {
+        var item = _b.value;
+        var name = String(item);
+        total += name.length
+    }
+    return callbacks
+}
+export function bind() {
+    var bound = [];
+    for (var i = 0; i < arguments.length; i++) {
+        const value = arguments[i];
+        bound.push(function () {
+            return value
+        })
+    }
+    return bound
+}"#
+	);
+	// `arguments` would refer to the function the body is moved to
+	assert!(
+		stderr.contains("Block scoped variables captured in loops are not lowered"),
+		"{stderr}"
+	);
+
+	let (es2015, _) = build("block_scoping.js", &["--target", "es2015"]);
+	assert!(es2015.contains("let total = limit;"));
+}
+
+#[test]
+fn generators() {
+	let (output, stderr) = build("generators.js", &["--target", "es5"]);
+	assert!(output.starts_with("var __generator = function (thisArg, body) {"));
+	assert!(output.contains("var __values = function (object) {"));
+	let (_, range) = output.split_once("export function range").unwrap();
+	assert_eq!(
+		range.trim_end(),
+		r#"(start, end) {
+    if (end === void 0) end = start + 10;
+    var i;
+    return __generator(this, function (_a) {
+        switch (_a.label) {
+            case 0:
+                i = start;
+            case 1:
+                if (!(i < end)) return [2, 3];
+
+                _a.trys.push([void 0, 4]);
+
+                return [0, i, 7];
+            case 7:
+                if (!_a.sent) return [2, 6];
+
+                return [2, 3, 1];
+            case 6:
+                return [2, 5, 1];
+            case 4:
+                console.log("step", i);
+
+                return [4];
+            case 5:
+            case 2:
+                i++;
+
+                return [2, 1];
+            case 3:
+                return [3, __values([end]), 8];
+            case 8:
+                return [1, _a.sent];
+        }
+    })
+}"#
+	);
+	assert!(stderr.is_empty());
+
+	let (es2015, _) = build("generators.js", &["--target", "es2015"]);
+	assert!(es2015.starts_with("export function* range(start, end = start + 10) {"));
+	assert!(es2015.contains("return yield* [end]"));
+}
+
+#[test]
+fn async_functions() {
+	// ES2017 has async functions but not async iteration
+	let (es2017, stderr) = build("async.js", &["--target", "es2017"]);
+	assert!(es2017.contains("export async function load(urls) {"));
+	assert!(es2017.contains(
+		"export function lines(stream) {\n    return __asyncGenerator(this, void 0, function* () {"
+	));
+	assert!(es2017.contains("_b = (yield [0, _a.next()]);"));
+	assert!(stderr.is_empty());
+
+	let (es2015, _) = build("async.js", &["--target", "es2015"]);
+	assert!(es2015.contains(
+		"export function load(urls) {\n    return __awaiter(this, void 0, function* () {"
+	));
+	assert!(es2015.contains("results.push((yield fetch(url)))"));
+
+	let (es5, _) = build("async.js", &["--target", "es5"]);
+	assert!(es5.contains("return __awaiter(this, void 0, function () {"));
+	assert!(es5.contains("_c = __values(urls);"));
+	assert!(es5.contains("if (!!_d.done) return [2, 3];"));
+	assert!(es5.contains("return [0, fetch(url), 4];"));
+	assert!(es5.contains("return [0, [1, chunk.trim()], 6];"));
+	assert!(!es5.contains("yield") && !es5.contains("async function"));
+}
+
+#[test]
+fn passes_can_be_selected() {
+	let (output, _) = build("functions.js", &["--transform", "lower-template-literals"]);
+	assert!(
+		output.contains(r#"return "Hello ".concat(title, " ", name, ", ", others.length + third)"#)
+	);
+	assert!(output.contains("const handler = event => this.handle(event, arguments[0])"));
+	assert!(output.contains("export function greet({ name, title = \"friend\" }, ...rest)"));
+}
+
+#[test]
+fn unsupported_syntax_is_kept_with_a_warning() {
+	let directory = std::env::temp_dir().join("ezno-downlevel-tagged");
+	std::fs::create_dir_all(&directory).unwrap();
+	std::fs::write(directory.join("index.js"), "html`<p>${text}</p>`;\n").unwrap();
+	let output = ezno(&directory, &["index.js", "--target", "es5"]);
+	assert!(output.status.success());
+	assert_eq!(String::from_utf8(output.stdout).unwrap().trim_end(), "html`<p>${text}</p>`");
+	let stderr = String::from_utf8(output.stderr).unwrap();
+	assert!(stderr.contains("W0002"), "{stderr}");
+	assert!(stderr.contains("Tagged template literals are not lowered"), "{stderr}");
+}
+
+#[test]
+fn target_from_configuration() {
+	let output = ezno(&fixture("downlevel").join("configured"), &["index.js"]);
+	assert_eq!(
+		String::from_utf8(output.stdout).unwrap().trim_end(),
+		"var _a;\nexport const value = ((_a = (a == null ? void 0 : a.b)) != null ? _a : c);\nlet d = 2 ** 3"
+	);
+
+	let output = ezno(&fixture("downlevel"), &["operators.js", "--target", "es3"]);
+	assert!(String::from_utf8(output.stdout)
+		.unwrap()
+		.contains("Unknown target 'es3', expected one of es5, es2015, es2017, es2020"));
+}
@@ -0,0 +1,71 @@
+use std::{
+	path::{Path, PathBuf},
+	process::Command,
+};
+
+fn fixture() -> PathBuf {
+	Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/enums")
+}
+
+/// Builds `main.ts` in the fixture (and the module it imports) into `output`, returning stderr
+fn build(output: &Path, arguments: &[&str]) -> String {
+	let output = Command::new(env!("CARGO_BIN_EXE_ezno"))
+		.current_dir(fixture())
+		.args(["build", "main.ts"])
+		.arg(output)
+		.args(arguments)
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+	String::from_utf8(output.stderr).unwrap()
+}
+
+#[test]
+fn inlined() {
+	let directory = std::env::temp_dir().join("ezno-enums");
+	std::fs::create_dir_all(&directory).unwrap();
+	build(&directory, &["--transform", "inline-enums"]);
+	let output = std::fs::read_to_string(directory.join("main.js")).unwrap();
+
+	assert_eq!(
+		output,
+		r#"import {Dynamic} from "./direction.js";
+const Offsets = { Back: -5 };
+const Sizes = { Small: 1, Large: 10, ...{ Huge: 100 } };
+function move(Direction) {
+    return Direction.length
+}
+console.log(1 /* Direction.Up */, 8 /* Direction.Left */, "right" /* Direction.Right */, 3 /* Flags.ReadWrite */);
+console.log("red" /* Colors.Red */, 3 /* Colors.Size */, "ab" /* Local.B */, "a" /* Local.A */.length, (2 /* Direction.Down */).toString());
+console.log(-(-1) /* Signs.Negative */, 2 ** (-1) /* Signs.Negative */, (-5) /* Offsets.Back */ ** 2);
+console.log(Dynamic.Now, Dynamic.Later, Sizes.Small, move("left"))"#
+	);
+
+	// Declarations are kept for other modules
+	let exporter = std::fs::read_to_string(directory.join("direction.js")).unwrap();
+	assert!(exporter.contains(r#"Flags[Flags["ReadWrite"] = 3] = "ReadWrite""#), "{exporter}");
+	assert!(exporter.contains(r#"export const Colors = { Red: "red", Size: 2 + 1 };"#));
+}
+
+#[test]
+fn bundled() {
+	let output = std::env::temp_dir().join("ezno-enums-bundle.js");
+	build(&output, &["--transform", "inline-enums", "--bundle"]);
+	let output = std::fs::read_to_string(output).unwrap();
+
+	assert!(
+		output.contains(r#"console.log(1 /* Direction.Up */, 8 /* Direction.Left */, "right" /* Direction.Right */, 3 /* Flags.ReadWrite */);"#),
+		"{output}"
+	);
+}
+
+#[test]
+fn minified() {
+	let output = std::env::temp_dir().join("ezno-enums-minified.js");
+	build(&output, &["--transform", "inline-enums", "--bundle", "--minify"]);
+	let output = std::fs::read_to_string(output).unwrap();
+
+	assert!(output.contains(r#"console.log("red",3,"ab","a".length,(2).toString())"#), "{output}");
+	// Negative values are parenthesized
+	assert!(output.contains("console.log(-(-1),2**(-1),(-5)**2)"), "{output}");
+}
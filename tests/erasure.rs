@@ -0,0 +1,184 @@
+use std::{
+	path::{Path, PathBuf},
+	process::Command,
+};
+
+/// Builds a file in the erasure fixture into a temporary directory, returning the output for that
+/// file and stderr
+fn build(file: &str, arguments: &[&str]) -> (String, String) {
+	let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/erasure");
+	let directory = output_directory(file, arguments);
+	std::fs::create_dir_all(&directory).unwrap();
+	let output = Command::new(env!("CARGO_BIN_EXE_ezno"))
+		.current_dir(fixture)
+		.args(["build", file])
+		.arg(&directory)
+		.args(arguments)
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+	let content = std::fs::read_to_string(directory.join(file).with_extension("js")).unwrap();
+	(content, String::from_utf8(output.stderr).unwrap())
+}
+
+/// Separate for each test so they can run in parallel
+fn output_directory(file: &str, arguments: &[&str]) -> PathBuf {
+	std::env::temp_dir().join(format!("ezno-erasure-{file}{}", arguments.concat()))
+}
+
+#[test]
+fn enums() {
+	let (output, stderr) = build("enums.ts", &[]);
+	assert_eq!(
+		output,
+		r#"var Direction;
+(function (Direction) {
+    Direction[Direction["Up"] = 1] = "Up";
+    Direction[Direction["Down"] = 2] = "Down";
+    Direction[Direction["Left"] = 8] = "Left";
+    Direction["Right"] = "right"
+})(Direction || (Direction = {  }));
+var Dynamic;
+(function (Dynamic) {
+    Dynamic[Dynamic["Now"] = Date.now()] = "Now"
+})(Dynamic || (Dynamic = {  }));
+export var Exported;
+(function (Exported) {
+    Exported["A"] = "a"
+})(Exported || (Exported = {  }));
+console.log(Direction.Up, 3, 2, Dynamic.Now, "a")"#
+	);
+	assert!(stderr.contains("Value of 'Dynamic.Now' is not constant, so 'Dynamic' is not inlined"));
+}
+
+#[test]
+fn parameter_properties() {
+	let (output, _) = build("classes.ts", &[]);
+	assert_eq!(
+		output,
+		"class Point {
+    constructor(x, y, z = 0) {
+        this.x = x;
+        this.y = y;
+        this.z = z
+    }
+}
+class Named extends Point {
+    constructor(name) {
+        super(1);
+        this.name = name;
+        console.log(name)
+    }
+}
+const Anonymous = class {
+    constructor(value) {
+        this.value = value
+    }
+}"
+	);
+}
+
+#[test]
+fn type_only_imports() {
+	let (output, _) = build("imports.ts", &[]);
+	assert_eq!(
+		output,
+		"import {area} from \"./shapes.js\";
+import \"./setup.js\";
+const square = { size: 2 };
+console.log(area(square))"
+	);
+}
+
+#[test]
+fn imports_only_used_as_types_are_not_followed() {
+	let (output, stderr) = build("annotated.ts", &[]);
+	assert_eq!(
+		output,
+		"const options = { verbose: true };\nconst generated = undefined;\nconsole.log(options, generated)"
+	);
+	// `./generated` does not exist and `./options` only has types
+	assert!(stderr.is_empty(), "{stderr}");
+	assert!(!output_directory("annotated.ts", &[]).join("options.js").exists());
+}
+
+#[test]
+fn namespaces_are_rejected() {
+	let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/erasure");
+	let output = Command::new(env!("CARGO_BIN_EXE_ezno"))
+		.current_dir(fixture)
+		.args(["build", "namespaces.ts"])
+		.output()
+		.unwrap();
+	assert!(!output.status.success());
+	let stderr = String::from_utf8(output.stderr).unwrap();
+	assert!(stderr.contains("Namespaces are only supported in type definition files"), "{stderr}");
+}
+
+#[test]
+fn ambient_and_type_declarations() {
+	let (output, _) = build("declarations.ts", &[]);
+	assert_eq!(output, "export function run(options, name, ...rest) {\n    log(VERSION)\n}");
+}
+
+#[test]
+fn erased_when_transforms_are_replaced() {
+	let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/erasure");
+	let output = Command::new(env!("CARGO_BIN_EXE_ezno"))
+		.current_dir(fixture)
+		.args(["build", "classes.ts", "--transform", "invert-ternary-branches"])
+		.output()
+		.unwrap();
+	// Types are not included by default, so erasure still runs
+	assert!(String::from_utf8(output.stdout).unwrap().contains("this.x = x"));
+}
+
+/// Source line of the first mapping on each line of the output
+fn mapped_lines(mappings: &str) -> Vec<Option<i64>> {
+	let mut source_line = 0;
+	mappings
+		.split(';')
+		.map(|line| {
+			let mut first = None;
+			for segment in line.split(',').filter(|segment| !segment.is_empty()) {
+				let fields = decode_vlq(segment);
+				if let Some(delta) = fields.get(2) {
+					source_line += delta;
+					first.get_or_insert(source_line);
+				}
+			}
+			first
+		})
+		.collect()
+}
+
+fn decode_vlq(segment: &str) -> Vec<i64> {
+	const ALPHABET: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+	let mut fields = Vec::new();
+	let (mut value, mut shift) = (0, 0);
+	for character in segment.chars() {
+		let digit = ALPHABET.find(character).unwrap() as i64;
+		value += (digit & 0b11111) << shift;
+		if digit & 0b100000 == 0 {
+			fields.push(if value & 1 == 1 { -(value >> 1) } else { value >> 1 });
+			(value, shift) = (0, 0);
+		} else {
+			shift += 5;
+		}
+	}
+	fields
+}
+
+#[test]
+fn source_maps_point_to_enum() {
+	let arguments = ["--source-maps"];
+	build("enums.ts", &arguments);
+	let source_map =
+		std::fs::read_to_string(output_directory("enums.ts", &arguments).join("enums.js.map"))
+			.unwrap();
+	let mappings = source_map.split("\"mappings\":\"").nth(1).unwrap();
+	let mappings = &mappings[..mappings.find('"').unwrap()];
+	let lines = mapped_lines(mappings);
+	// `var Direction;` and the IIFE map to the declaration, members to their lines
+	assert_eq!(&lines[..7], [Some(0), Some(0), Some(1), Some(2), Some(3), Some(4), Some(0)]);
+}
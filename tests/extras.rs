@@ -0,0 +1,105 @@
+mod common;
+
+use common::{ezno, fixture};
+
+fn lower(file: &str) -> (String, String) {
+	common::build("extras", file, &["--transform", "lower-extras"])
+}
+
+#[test]
+fn pipes() {
+	let (output, stderr) = lower("operators.js");
+	assert!(stderr.is_empty(), "{stderr}");
+	for expected in [
+		"console.log(increment(double(4)));",
+		// The value is evaluated before the function
+		"console.log((_a = record(1), record(double)(_a)));",
+		"console.log((value => value * 10)(3 + 1));",
+	] {
+		assert!(output.contains(expected), "{expected} in {output}");
+	}
+	assert!(output.starts_with("var _a;\n"), "{output}");
+}
+
+#[test]
+fn compositions() {
+	let (output, _) = lower("operators.js");
+	assert!(output.contains("var __compose = function (f, g) {"), "{output}");
+	for expected in [
+		"const doubleThenIncrement = __compose(increment, double);",
+		"(__compose(double, increment))(5)",
+	] {
+		assert!(output.contains(expected), "{expected} in {output}");
+	}
+}
+
+#[test]
+fn divides() {
+	let (output, _) = lower("operators.js");
+	assert!(
+		output.contains("console.log((9 % 3 === 0), (10 % 3 === 0), ((2 * 6) % 4 === 0));"),
+		"{output}"
+	);
+}
+
+#[test]
+fn is_expressions() {
+	let (output, stderr) = lower("is.js");
+	for expected in [
+		r#"return (typeof value === "number" ? "number" : typeof value === "string" || typeof value === "boolean" ? (() => {
+        const text = String(value);
+        return `primitive ${text}`
+    })() : value === null ? "null" : value === "yes" ? "never matched" : Array.isArray(value) ? "array" : value instanceof Point ? "point" : value instanceof shapes.Circle ? "circle" : "other")"#,
+		// The matched value is only evaluated once
+		r#"console.log((_a = next(), Array.isArray(_a) ? "numbers" : void 0), calls);"#,
+		// Branches which await are awaited
+		r#"return (typeof value === "string" ? await (async () => {
+        const name = await Promise.resolve(value);
+        return name.toUpperCase()
+    })() : false ? value.name : void 0)"#,
+	] {
+		assert!(output.contains(expected), "{expected} in {output}");
+	}
+	// Object literal types cannot be tested
+	assert_eq!(stderr.matches("W0002").count(), 1, "{stderr}");
+	assert!(stderr.contains("Type cannot be tested at runtime"), "{stderr}");
+
+	let (_, stderr) = lower("invalid.js");
+	assert!(stderr.contains("'yield' in a block branch of an 'is' expression"), "{stderr}");
+}
+
+#[test]
+fn generator_keywords() {
+	let (output, _) = lower("generators.js");
+	for expected in [
+		"function* numbers() {",
+		"const letters = async function* () {",
+		"const object = { *values() {",
+		"class Range {
+    *steps() {",
+	] {
+		assert!(output.contains(expected), "{expected} in {output}");
+	}
+	assert!(!output.contains("generator"), "{output}");
+}
+
+#[test]
+fn lowered_for_target() {
+	let output = ezno(&fixture("extras"), &["operators.js", "--target", "es2020"]);
+	let output = String::from_utf8(output.stdout).unwrap();
+	assert!(output.contains("console.log(increment(double(4)));"), "{output}");
+	assert!(!output.contains("|>"), "{output}");
+}
+
+#[test]
+fn configured() {
+	let output = ezno(&fixture("extras").join("configured"), &["main.js"]);
+	let output = String::from_utf8(output.stdout).unwrap();
+	assert_eq!(
+		output.trim_end(),
+		"function* ids() {
+    yield 1
+}
+export const first = String(ids().next().value)"
+	);
+}
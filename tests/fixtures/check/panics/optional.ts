@@ -0,0 +1,2 @@
+// The parser does not support assigning to optional chains yet
+a?.b = 1;
@@ -0,0 +1,19 @@
+const a = 1;
+let b = 2;
+const a = 3;
+if (a == b) {
+    debugger;
+}
+for (const x of [1]) {
+    if (x) { continue; }
+    outer: while (true) { break outer; }
+}
+function f() { return 2 }
+return 4;
+break;
+for (const z of [2]) {
+    const g = () => { continue; };
+    inner: while (z) {
+        function h() { break inner; }
+    }
+}
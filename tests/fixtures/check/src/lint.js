@@ -0,0 +1 @@
+var y = 2;
@@ -0,0 +1,4 @@
+const ok = 1;
+function f(a) {
+    if (a) { return a; }
+}
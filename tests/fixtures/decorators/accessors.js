@@ -0,0 +1,20 @@
+function tracked(value, context) {
+	return {
+		get() {
+			return value.get.call(this);
+		},
+		init(initial) {
+			return initial * 10;
+		},
+	};
+}
+
+const key = "computed";
+
+class Store {
+	@tracked accessor size = 5;
+	@tracked static accessor count = 2;
+	accessor [key] = 3;
+}
+
+console.log(new Store().size, Store.count, new Store().computed);
@@ -0,0 +1,5 @@
+function inject(target: any, key: string) {}
+
+export class Service {
+	@inject name: string;
+}
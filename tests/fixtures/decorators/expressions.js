@@ -0,0 +1,30 @@
+const log = [];
+
+function tracked(value, context) {
+	context.addInitializer(function () {
+		log.push(`${context.name} on ${this.constructor.name}`);
+	});
+}
+
+function register(target) {
+	log.push(`register ${target.name || "anonymous"}`);
+}
+
+class Base {
+	constructor(value) {
+		this.value = value;
+	}
+}
+
+const Service = class Worker extends Base {
+	@tracked start() {}
+};
+
+new Service(1);
+
+@register
+export default class {
+	@tracked stop() {}
+};
+
+console.log(log.join("\n"));
@@ -0,0 +1,11 @@
+function register(target) {}
+
+@register
+interface Shape {
+	sides: number;
+}
+
+@register
+enum Direction {
+	Up,
+}
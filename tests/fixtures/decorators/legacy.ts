@@ -0,0 +1,59 @@
+import "reflect-metadata";
+import type { Options } from "./options";
+
+interface Shape {
+	sides: number;
+}
+
+enum Level {
+	Low,
+	High,
+}
+
+const log: Array<string> = [];
+
+function sealed(constructor: Function) {
+	log.push(`sealed ${constructor.name}`);
+	Object.seal(constructor);
+	Object.seal(constructor.prototype);
+}
+
+function enumerable(value: boolean) {
+	return function (target: any, key: string, descriptor: PropertyDescriptor) {
+		log.push(`enumerable ${key}`);
+		descriptor.enumerable = value;
+	};
+}
+
+function field(target: any, key: string) {
+	log.push(`field ${key}${typeof target === "function" ? " static" : ""}`);
+}
+
+@sealed
+export class Greeter {
+	@field greeting: string;
+	@field level: Level;
+	@field static count: number = 0;
+
+	constructor(message: string, options?: Options) {
+		this.greeting = message;
+	}
+
+	@enumerable(false)
+	greet(shape: Shape, times: number): string {
+		return "Hello, " + this.greeting;
+	}
+
+	@enumerable(true)
+	get loud(): string {
+		return this.greeting.toUpperCase();
+	}
+
+	@enumerable(true)
+	async fetch(url: string | null) {
+		return url;
+	}
+}
+
+console.log(Object.keys(Greeter.prototype).join(","), Object.isSealed(Greeter));
+console.log(log.join("\n"));
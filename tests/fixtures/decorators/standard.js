@@ -0,0 +1,52 @@
+const log = [];
+
+function logged(value, context) {
+	log.push(`${context.kind} ${String(context.name)}${context.static ? " static" : ""}`);
+	if (context.kind === "method") {
+		return function (...args) {
+			log.push(`call ${context.name}`);
+			return value.call(this, ...args);
+		};
+	}
+	if (context.kind === "field") {
+		return (initial) => initial * 2;
+	}
+	if (context.kind === "class") {
+		context.addInitializer(function () {
+			log.push(`initialized ${this.name}`);
+		});
+	}
+}
+
+function bound(value, context) {
+	context.addInitializer(function () {
+		this[context.name] = this[context.name].bind(this);
+	});
+}
+
+@logged
+class Counter {
+	@logged count = 2;
+	@logged static limit = 10;
+
+	@logged
+	@bound
+	increment() {
+		this.count += 1;
+		return this.count;
+	}
+
+	@logged get double() {
+		return this.count * 2;
+	}
+
+	@logged ["computed"]() {
+		return Counter.limit;
+	}
+}
+
+const counter = new Counter();
+const { increment } = counter;
+increment();
+console.log(counter.count, counter.double, counter.computed(), Counter.limit);
+console.log(log.join("\n"));
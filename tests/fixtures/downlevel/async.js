@@ -0,0 +1,12 @@
+export async function load(urls) {
+    const results = [];
+    for (const url of urls) {
+        results.push(await fetch(url));
+    }
+    return results;
+}
+export async function* lines(stream) {
+    for await (const chunk of stream) {
+        yield chunk.trim();
+    }
+}
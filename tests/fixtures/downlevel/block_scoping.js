@@ -0,0 +1,29 @@
+export const limit = 3;
+let total = 0;
+{
+	let total = limit;
+	console.log(total);
+}
+export function count(items) {
+	const callbacks = [];
+	for (let i = 0; i < items.length; i++) {
+		let last;
+		if (items[i]) last = i;
+		if (last > limit) break;
+		console.log(last);
+		callbacks.push(() => i);
+	}
+	for (const item of items) {
+		const name = String(item);
+		total += name.length;
+	}
+	return callbacks;
+}
+export function bind() {
+	const bound = [];
+	for (let i = 0; i < arguments.length; i++) {
+		const value = arguments[i];
+		bound.push(() => value);
+	}
+	return bound;
+}
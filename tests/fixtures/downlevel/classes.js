@@ -0,0 +1,32 @@
+class Animal {
+    legs = 4;
+    static count = 0;
+    static next = this.count + 1;
+    constructor(name) {
+        this.name = name;
+    }
+    speak() {
+        return `${this.name} makes a sound`;
+    }
+    get description() {
+        return this.name;
+    }
+    set description(value) {
+        this.name = value;
+    }
+}
+export class Dog extends Animal {
+    bark = () => this.speak();
+    constructor(name) {
+        super(name);
+    }
+    speak() {
+        return super.speak() + " (woof)";
+    }
+    static create() {
+        return super.create();
+    }
+}
+export class Puppy extends Dog {
+    tricks = this.legs / 2;
+}
@@ -0,0 +1,2 @@
+export const value = a?.b ?? c;
+let d = 2 ** 3;
@@ -0,0 +1,9 @@
+export function greet({ name, title = "friend" }, ...rest) {
+    const [first, , third = 0, ...others] = rest;
+    const { a, b: { c }, ...remaining } = first;
+    const handler = (event) => this.handle(event, arguments[0]);
+    return `Hello ${title} ${name}, ${others.length + third}`;
+}
+for (const [key, value] of entries) {
+    console.log(`${key}: "${value}"`);
+}
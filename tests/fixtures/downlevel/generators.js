@@ -0,0 +1,10 @@
+export function* range(start, end = start + 10) {
+    for (let i = start; i < end; i++) {
+        try {
+            if (yield i) break;
+        } finally {
+            console.log("step", i);
+        }
+    }
+    return yield* [end];
+}
@@ -0,0 +1,9 @@
+export function operators(options, cache, key) {
+    options.retries ??= 3;
+    cache[key()] ||= new Map();
+    options.verbose &&= options.level > 1;
+    const area = options.size ** 2;
+    options.scale **= 0.5;
+    const name = options.user?.profile.name ?? "anonymous";
+    return { ...options, area, name, [options.key]: area, describe() { return name; } };
+}
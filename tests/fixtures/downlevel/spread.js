@@ -0,0 +1,7 @@
+export function spread(values, options) {
+	console.log(...values, "done");
+	const items = [0, ...values, ...new Set(values), 4];
+	options.handler(...items);
+	getOptions().handler(...items);
+	return new Date(...values);
+}
@@ -0,0 +1,20 @@
+export enum Direction {
+	Up = 1,
+	Down,
+	Left = Down * 4,
+	Right = "right",
+}
+
+export const enum Flags {
+	None = 0,
+	Read = 1 << 0,
+	Write = 1 << 1,
+	ReadWrite = Read | Write,
+}
+
+export const Colors = { Red: "red", Size: 2 + 1 } as const;
+
+export enum Dynamic {
+	Now = Date.now(),
+	Later,
+}
@@ -0,0 +1,24 @@
+import { Direction, Flags as Permissions, Colors, Dynamic } from "./direction";
+
+const enum Local {
+	A = "a",
+	B = A + "b",
+}
+
+const enum Signs {
+	Negative = -1,
+}
+
+const Offsets = { Back: -5 } as const;
+
+const Sizes = { Small: 1, Large: 10, ...{ Huge: 100 } } as const;
+
+function move(Direction: string) {
+	// A parameter, not the enum
+	return Direction.length;
+}
+
+console.log(Direction.Up, Direction["Left"], Direction.Right, Permissions.ReadWrite);
+console.log(Colors.Red, Colors.Size, Local.B, Local.A.length, Direction.Down.toString());
+console.log(-Signs.Negative, 2 ** Signs.Negative, Offsets.Back ** 2);
+console.log(Dynamic.Now, Dynamic.Later, Sizes.Small, move("left"));
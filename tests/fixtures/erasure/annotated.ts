@@ -0,0 +1,6 @@
+import { Options } from "./options";
+import { Generated } from "./generated";
+
+const options: Options = { verbose: true };
+const generated: Generated | undefined = undefined;
+console.log(options, generated);
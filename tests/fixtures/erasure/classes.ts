@@ -0,0 +1,14 @@
+class Point {
+	constructor(public x: number, private readonly y?: number, protected z = 0) {}
+}
+
+class Named extends Point {
+	constructor(public name: string) {
+		super(1);
+		console.log(name);
+	}
+}
+
+const Anonymous = class {
+	constructor(private value: string) {}
+};
@@ -0,0 +1,16 @@
+declare var VERSION: string;
+declare function log(message: string): void;
+
+interface Options {
+	verbose: boolean;
+}
+
+type Name = string;
+
+export type Other = number;
+
+export interface Exported {}
+
+export function run(options: Options, name: Name, ...rest: Name[]) {
+	log(VERSION);
+}
@@ -0,0 +1,23 @@
+enum Direction {
+	Up = 1,
+	Down,
+	Left = Down << 2,
+	Right = "right",
+}
+
+const enum Flags {
+	None,
+	Read = 1 << 0,
+	Write = 1 << 1,
+	ReadWrite = Read | Write,
+}
+
+const enum Dynamic {
+	Now = Date.now(),
+}
+
+export const enum Exported {
+	A = "a",
+}
+
+console.log(Direction.Up, Flags.ReadWrite, Flags["Write"], Dynamic.Now, Exported.A);
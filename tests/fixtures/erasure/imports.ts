@@ -0,0 +1,6 @@
+import type { Shape } from "./shapes";
+import { type Size, area, unit } from "./shapes";
+import "./setup";
+
+const square: Shape = { size: 2 as Size };
+console.log(area(square));
@@ -0,0 +1,3 @@
+namespace Shapes {
+	export const unit = 1;
+}
@@ -0,0 +1,11 @@
+export interface Shape {
+	size: number;
+}
+
+export type Size = number;
+
+export function area(shape: Shape): number {
+	return shape.size ** 2;
+}
+
+export const unit = 1;
@@ -0,0 +1,5 @@
+generator function ids() {
+	yield 1;
+}
+
+export const first = ids().next().value |> String;
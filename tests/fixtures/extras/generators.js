@@ -0,0 +1,23 @@
+generator function numbers() {
+	yield 1;
+	yield 2;
+}
+
+const letters = async generator function () {
+	yield "a";
+};
+
+const object = {
+	generator values() {
+		yield* numbers();
+	},
+};
+
+class Range {
+	generator steps() {
+		yield 3;
+	}
+}
+
+console.log([...numbers(), ...object.values(), ...new Range().steps()].join(","));
+letters().next().then(result => console.log(result.value));
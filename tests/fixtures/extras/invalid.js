@@ -0,0 +1,8 @@
+generator function parse(value) {
+	return is (value) {
+		string => {
+			const next = yield value;
+			return next;
+		}
+	};
+}
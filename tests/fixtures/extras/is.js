@@ -0,0 +1,38 @@
+class Point {}
+const shapes = { Circle: class Circle {} };
+
+function describe(value) {
+	return is (value) {
+		number => "number",
+		string | boolean => {
+			const text = String(value);
+			return `primitive ${text}`;
+		},
+		null => "null",
+		"yes" => "never matched",
+		Array => "array",
+		Point => "point",
+		shapes.Circle => "circle",
+		unknown => "other"
+	};
+}
+
+let calls = 0;
+function next() {
+	calls++;
+	return [1];
+}
+
+async function fetchName(value) {
+	return is (value) {
+		string => {
+			const name = await Promise.resolve(value);
+			return name.toUpperCase();
+		},
+		{ name: string } => value.name
+	};
+}
+
+console.log([4, "a", true, null, [], new Point(), new shapes.Circle(), {}].map(describe).join(","));
+console.log(is (next()) { number[] => "numbers" }, calls);
+fetchName("ezno").then(console.log);
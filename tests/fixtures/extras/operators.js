@@ -0,0 +1,17 @@
+const double = value => value * 2;
+const increment = value => value + 1;
+const log = [];
+function record(value) {
+	log.push(value);
+	return value;
+}
+
+console.log(4 |> double |> increment);
+console.log(record(1) |> record(double));
+console.log((3 + 1) |> (value => value * 10));
+
+const doubleThenIncrement = increment <@> double;
+console.log(doubleThenIncrement(5), (double ∘ increment)(5));
+
+console.log(9 /% 3, 10 /% 3, 2 * 6 ∣ 4);
+console.log(log.join(","));
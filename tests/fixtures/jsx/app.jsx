@@ -0,0 +1,20 @@
+import { Header } from "./header";
+
+export function App({ items, user, ...props }) {
+    return <main {...props} id="app" data-user={user.id} hidden>
+        <Header title="Shop &amp; more" />
+        <ul>
+            {items.map(item => <li key={item.id}>{item.name} ({item.count})</li>)}
+        </ul>
+        <p>
+            Hello   {user.name}, welcome
+            back!
+        </p>
+        <>
+            <img src="logo.png" alt="Logo">
+            <br>
+        </>
+        {/* comment */}
+        <ui.Button {...props} key="spread">Press</ui.Button>
+    </main>;
+}
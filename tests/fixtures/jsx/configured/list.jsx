@@ -0,0 +1,4 @@
+export const list = <>
+    <li>One</li>
+    <li>Two</li>
+</>;
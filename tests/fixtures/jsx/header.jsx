@@ -0,0 +1,3 @@
+export function Header({ title }) {
+    return <h1 class="header">{title}</h1>;
+}
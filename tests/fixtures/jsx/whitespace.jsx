@@ -0,0 +1,10 @@
+const inline = <p>{first} {last}</p>;
+const lines = <p>
+    Multiple
+    lines   joined
+    <b>bold</b> text &lt;escaped&gt; &#x2764; "quoted"
+</p>;
+const empty = <div>
+    {/* only a comment */}
+</div>;
+const leading = <span>  padded  </span>;
@@ -0,0 +1,18 @@
+export function collapse(a, b) {
+	if (a) b();
+	if (!a) b();
+	if (a) {
+		b(1);
+		b(2);
+	} else if (b) {
+		a();
+	} else {
+		b(3);
+	}
+	if (a = b) a();
+	if (a) {
+		return 1;
+	} else {
+		return 2;
+	}
+}
@@ -0,0 +1,16 @@
+export function unreachable(x) {
+	if (x) {
+		return 1;
+		console.log("never");
+		var hoisted = 2;
+	}
+	if (false) {
+		console.log("removed");
+	} else {
+		console.log("kept");
+	}
+	return helper();
+	function helper() {
+		return hoisted;
+	}
+}
@@ -0,0 +1,8 @@
+export function constants(x) {
+	const seconds = 60 * 60 * 24;
+	const greeting = "Hello, " + "world";
+	const flag = true ? "yes" : "no";
+	const limit = (2 ** 10) - 1;
+	const fallback = null ?? x;
+	return [seconds, greeting, flag, limit, fallback, x - -1, !0];
+}
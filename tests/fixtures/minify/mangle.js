@@ -0,0 +1,20 @@
+export function total(items, discount) {
+	let sum = 0;
+	for (const item of items) {
+		const price = item.price * item.quantity;
+		sum += price;
+	}
+	function applyDiscount(value) {
+		return value * (1 - discount);
+	}
+	return applyDiscount(sum);
+}
+
+export function debug(source) {
+	const local = 1;
+	return eval(source);
+}
+
+export function global(message) {
+	console.log(message, window.location);
+}
@@ -0,0 +1,6 @@
+export function check(value, Type, key) {
+	const isInstance = value instanceof Type;
+	const hasKey = "key" in value;
+	const hasItem = key in [1, 2];
+	return [isInstance, hasKey, hasItem, (value) instanceof Type];
+}
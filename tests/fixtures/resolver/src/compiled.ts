@@ -0,0 +1 @@
+export const compiled = 1;
@@ -0,0 +1 @@
+export const folder = 1;
@@ -0,0 +1 @@
+export const fallback = 1;
@@ -0,0 +1 @@
+export const node = 1;
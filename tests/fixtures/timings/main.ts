@@ -0,0 +1,3 @@
+import { double } from "./utilities";
+
+export const values = [1, 2, 3].map(double);
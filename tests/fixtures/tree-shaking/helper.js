@@ -0,0 +1,5 @@
+export function helper(value) {
+	return value + 1;
+}
+
+export const NAME = "helper";
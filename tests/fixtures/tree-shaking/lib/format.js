@@ -0,0 +1,9 @@
+export function format(value, shape) {
+	return `${value} ${shape}`;
+}
+
+export function pad(value) {
+	return String(value).padStart(4);
+}
+
+console.log("format loaded");
@@ -0,0 +1,5 @@
+export function unused() {
+	return 1;
+}
+
+console.log("never loaded");
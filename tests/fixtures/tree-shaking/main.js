@@ -0,0 +1,8 @@
+import { used, Shape } from "./utilities";
+import { format } from "./lib/format";
+import { unused } from "./lib/unused";
+import "./lib/polyfill";
+
+export function run(value) {
+	return format(used(value), new Shape());
+}
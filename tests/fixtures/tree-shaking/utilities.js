@@ -0,0 +1,25 @@
+import { helper } from "./helper";
+
+export function used(value) {
+	return helper(value) * 2;
+}
+
+export function unused() {
+	return helper(0);
+}
+
+export const table = /*#__PURE__*/ createTable(16);
+
+export const registered = register("utilities");
+
+export class Shape {
+	static sides = 0;
+}
+
+export class Unused extends Shape {
+	static instance = /*#__PURE__*/ new Unused();
+}
+
+const cache = /* @__PURE__ */ new Map();
+
+console.log("utilities loaded");
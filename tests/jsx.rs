@@ -0,0 +1,106 @@
+mod common;
+
+use common::{ezno, fixture};
+
+/// Builds `app.jsx` (and `header.jsx`) into a temporary directory, returning the output for `file`
+fn build(name: &str, file: &str, arguments: &[&str]) -> String {
+	let directory = std::env::temp_dir().join(format!("ezno-jsx-{name}"));
+	std::fs::create_dir_all(&directory).unwrap();
+	let output =
+		ezno(&fixture("jsx"), &[&["app.jsx", directory.to_str().unwrap()], arguments].concat());
+	assert!(output.stderr.is_empty(), "{}", String::from_utf8_lossy(&output.stderr));
+	std::fs::read_to_string(directory.join(file)).unwrap()
+}
+
+#[test]
+fn classic_runtime() {
+	let output = build("classic", "app.js", &["--jsx", "classic"]);
+	assert_eq!(
+		output,
+		r#"import {Header} from "./header.js";
+export function App({ items, user, ...props }) {
+    return React.createElement("main", { ...props, id: "app", "data-user": user.id, hidden: true }, React.createElement(Header, { title: "Shop & more" }), React.createElement("ul", null, items.map(item => React.createElement("li", { key: item.id }, item.name, " (", item.count, ")"))), React.createElement("p", null, "Hello   ", user.name, ", welcome back!"), React.createElement(React.Fragment, null, React.createElement("img", { src: "logo.png", alt: "Logo" }), React.createElement("br", null)), React.createElement(ui.Button, { ...props, key: "spread" }, "Press"))
+}"#
+	);
+}
+
+#[test]
+fn automatic_runtime() {
+	let output = build("automatic", "app.js", &["--jsx", "automatic"]);
+	// `key` is passed separately unless it is after a spread, which falls back to `createElement`
+	assert_eq!(
+		output,
+		r#"import {createElement as _createElement} from "react";
+import {jsx as _jsx,jsxs as _jsxs,Fragment as _Fragment} from "react/jsx-runtime";
+import {Header} from "./header.js";
+export function App({ items, user, ...props }) {
+    return _jsxs("main", { ...props, id: "app", "data-user": user.id, hidden: true, children: [_jsx(Header, { title: "Shop & more" }), _jsx("ul", { children: items.map(item => _jsxs("li", { children: [item.name, " (", item.count, ")"] }, item.id)) }), _jsxs("p", { children: ["Hello   ", user.name, ", welcome back!"] }), _jsxs(_Fragment, { children: [_jsx("img", { src: "logo.png", alt: "Logo" }), _jsx("br", {  })] }), _createElement(ui.Button, { ...props, key: "spread" }, "Press")] })
+}"#
+	);
+
+	let header = build(
+		"import-source",
+		"header.js",
+		&["--jsx", "automatic", "--jsx-import-source", "preact"],
+	);
+	assert_eq!(
+		header,
+		r#"import {jsx as _jsx} from "preact/jsx-runtime";
+export function Header({ title }) {
+    return _jsx("h1", { class: "header", children: title })
+}"#
+	);
+}
+
+#[test]
+fn whitespace_and_pragma() {
+	let output = ezno(&fixture("jsx"), &["whitespace.jsx", "--jsx-pragma", "h"]);
+	assert_eq!(
+		String::from_utf8(output.stdout).unwrap(),
+		r#"const inline = h("p", null, first, " ", last);
+const lines = h("p", null, "Multiple lines   joined", h("b", null, "bold"), " text <escaped> ❤ \"quoted\"");
+const empty = h("div", null);
+const leading = h("span", null, "  padded  ")
+"#
+	);
+}
+
+#[test]
+fn preserved_without_runtime() {
+	let output = ezno(&fixture("jsx"), &["whitespace.jsx"]);
+	let output = String::from_utf8(output.stdout).unwrap();
+	assert!(output.starts_with("const inline = <p>{first} {last}</p>;"));
+}
+
+#[test]
+fn runtime_from_configuration() {
+	let output = ezno(&fixture("jsx").join("configured"), &["list.jsx"]);
+	assert_eq!(
+		String::from_utf8(output.stdout).unwrap(),
+		"export const list = h(Fragment, null, h(\"li\", null, \"One\"), h(\"li\", null, \"Two\"))\n"
+	);
+
+	let output =
+		ezno(&fixture("jsx").join("configured"), &["list.jsx", "--jsx-import-source", "preact"]);
+	assert_eq!(
+		String::from_utf8(output.stdout).unwrap(),
+		"An import source only applies to the automatic JSX runtime\n"
+	);
+}
+
+#[test]
+fn bundles_import_runtime_once() {
+	let output = ezno(&fixture("jsx"), &["app.jsx", "--bundle", "--jsx", "automatic"]);
+	let output = String::from_utf8(output.stdout).unwrap();
+	assert!(output.starts_with(
+		"import { createElement as _createElement } from \"react\";\nimport { jsx as _jsx, jsxs as \
+		 _jsxs, Fragment as _Fragment } from \"react/jsx-runtime\";\n"
+	));
+	assert_eq!(output.matches("react/jsx-runtime").count(), 1);
+
+	let output =
+		ezno(&fixture("jsx"), &["app.jsx", "--bundle", "--format", "iife", "--jsx", "automatic"]);
+	assert!(String::from_utf8(output.stderr)
+		.unwrap()
+		.contains("Cannot import the 'react' JSX runtime in an IIFE bundle"));
+}
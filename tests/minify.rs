@@ -0,0 +1,159 @@
+use std::{
+	path::{Path, PathBuf},
+	process::Command,
+};
+
+fn fixture() -> PathBuf {
+	Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/minify")
+}
+
+/// Builds a file in the fixture with `--minify`, returning the output (printed as there is no
+/// output path)
+fn minify(file: &str) -> String {
+	let output = Command::new(env!("CARGO_BIN_EXE_ezno"))
+		.current_dir(fixture())
+		.args(["build", file, "--minify"])
+		.output()
+		.unwrap();
+	assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+	String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn mangles_local_names() {
+	let output = minify("mangle.js");
+	let mut functions = output.trim_end().split("export ");
+	assert_eq!(functions.next(), Some(""));
+	// Bindings used most get the shortest names and names are reused in sibling scopes, but
+	// `e` in `d` does not shadow `b`
+	assert_eq!(
+		functions.next(),
+		Some("function total(a,b){let c=0;for(const e of a){const f=e.price*e.quantity;c+=f}function d(e){return e*(1-b)}return d(c)}")
+	);
+	// `eval` can reference any name in scope
+	assert_eq!(functions.next(), Some("function debug(source){const local=1;return eval(source)}"));
+	// Globals are not renamed
+	assert_eq!(functions.next(), Some("function global(a){console.log(a,window.location)}"));
+}
+
+#[test]
+fn folds_and_inlines_constants() {
+	let output = minify("fold.js");
+	assert_eq!(
+		output.trim_end(),
+		r#"export function constants(a){const b=a;return [86400,"Hello, world","yes",1023,b,a- -1,!0]}"#
+	);
+}
+
+#[test]
+fn removes_dead_code() {
+	let output = minify("dead.js");
+	// `hoisted` is still declared and `helper` is still hoisted
+	assert_eq!(
+		output.trim_end(),
+		r#"export function unreachable(a){if(a){return 1;var b}console.log("kept");return c();function c(){return b}}"#
+	);
+}
+
+#[test]
+fn collapses_conditionals() {
+	let output = minify("conditionals.js");
+	assert_eq!(
+		output.trim_end(),
+		"export function collapse(a,b){a&&b();a||b();a?(b(1),b(2)):b?a():b(3);(a=b)&&a();return a?1:2}"
+	);
+}
+
+#[test]
+fn keyword_operators() {
+	let output = minify("operators.js");
+	// Spaces are only left out next to punctuation
+	assert_eq!(
+		output.trim_end(),
+		r#"export function check(a,b,c){const d=a instanceof b;const e="key"in a;const f=c in[1,2];return [d,e,f,(a)instanceof b]}"#
+	);
+}
+
+#[test]
+fn transforms_can_be_selected() {
+	let output = Command::new(env!("CARGO_BIN_EXE_ezno"))
+		.current_dir(fixture())
+		.args(["build", "fold.js", "--transform", "fold-constants"])
+		.output()
+		.unwrap();
+	let output = String::from_utf8(output.stdout).unwrap();
+	assert!(output.contains("const seconds = 86400;"), "{output}");
+	assert!(output.contains("const limit = 1023;"), "{output}");
+}
+
+#[test]
+fn source_maps_point_to_constants() {
+	let directory = std::env::temp_dir().join("ezno-minify-source-maps");
+	std::fs::create_dir_all(&directory).unwrap();
+	let output = Command::new(env!("CARGO_BIN_EXE_ezno"))
+		.current_dir(fixture())
+		.args(["build", "fold.js"])
+		.arg(&directory)
+		.args(["--minify", "--source-maps"])
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+
+	let content = std::fs::read_to_string(directory.join("fold.js")).unwrap();
+	let source_map = std::fs::read_to_string(directory.join("fold.js.map")).unwrap();
+	let mappings = source_map.split("\"mappings\":\"").nth(1).unwrap();
+	let mappings = &mappings[..mappings.find('"').unwrap()];
+	let segments = segments(mappings.split(';').next().unwrap());
+
+	// Inlined values map to the lines of the declarations they came from
+	for (value, line) in [("86400", 1), ("\"Hello, world\"", 2), ("\"yes\"", 3), ("1023", 4)] {
+		let column = content.find(value).unwrap() as i64;
+		assert!(segments.contains(&(column, line)), "{value} at {column}: {segments:?}");
+	}
+}
+
+#[test]
+fn printed_source_maps_are_inline() {
+	let output = Command::new(env!("CARGO_BIN_EXE_ezno"))
+		.current_dir(fixture())
+		.args(["build", "fold.js", "--minify", "--source-maps"])
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+	let output = String::from_utf8(output.stdout).unwrap();
+	assert!(
+		output.contains("//# sourceMappingURL=data:application/json;charset=utf-8;base64,"),
+		"{output}"
+	);
+}
+
+/// The generated column and source line of each segment on a line
+fn segments(line: &str) -> Vec<(i64, i64)> {
+	let (mut column, mut source_line) = (0, 0);
+	line.split(',')
+		.filter(|segment| !segment.is_empty())
+		.filter_map(|segment| {
+			let fields = decode_vlq(segment);
+			column += fields[0];
+			source_line += fields.get(2)?;
+			Some((column, source_line))
+		})
+		.collect()
+}
+
+fn decode_vlq(segment: &str) -> Vec<i64> {
+	const ALPHABET: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+	let mut fields = Vec::new();
+	let (mut value, mut shift) = (0, 0);
+	for character in segment.chars() {
+		let digit = ALPHABET.find(character).unwrap() as i64;
+		value += (digit & 0b11111) << shift;
+		if digit & 0b100000 == 0 {
+			fields.push(if value & 1 == 1 { -(value >> 1) } else { value >> 1 });
+			(value, shift) = (0, 0);
+		} else {
+			shift += 5;
+		}
+	}
+	fields
+}
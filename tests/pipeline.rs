@@ -0,0 +1,209 @@
+use std::{
+	cell::RefCell,
+	path::{Path, PathBuf},
+	process::Command,
+	rc::Rc,
+};
+
+use ezno_lib::{
+	config::Configuration,
+	pipeline::{
+		BuildOutput, BuildSettings, DiagnosticCode, ErrorWarningInfo, JSONValue, Pass, PassContext,
+		Pipeline, TempDiagnostic, Transform, Visitors,
+	},
+	resolver::Resolver,
+};
+use parser::{expressions::ExpressionId, extractor::ExtractedFunctions, Chain, Expression};
+
+/// Replaces references to the names in its options with their boolean values
+struct Define(Vec<(String, bool)>);
+
+impl parser::VisitorMut<Expression, Vec<TempDiagnostic>> for Define {
+	fn visit_mut(
+		&mut self,
+		item: &mut Expression,
+		data: &mut Vec<TempDiagnostic>,
+		_functions: &mut ExtractedFunctions,
+		_chain: &Chain,
+	) {
+		if let Expression::VariableReference(name, position, _) = item {
+			if let Some((_, value)) = self.0.iter().find(|(defined, _)| defined == name) {
+				data.push(TempDiagnostic {
+					label: format!("Replaced '{name}'"),
+					position: position.clone(),
+					kind: ErrorWarningInfo::Info,
+					code: DiagnosticCode::Pass("D0001"),
+					secondary_labels: Vec::new(),
+					notes: Vec::new(),
+				});
+				*item = Expression::BooleanLiteral(*value, position.clone(), ExpressionId::NULL);
+			}
+		}
+	}
+}
+
+fn define(context: &PassContext) -> Visitors {
+	let defined = match context.options {
+		Some(JSONValue::Object(entries)) => entries
+			.iter()
+			.filter_map(|(name, value)| match value {
+				JSONValue::Boolean(value) => Some((name.clone(), *value)),
+				_ => None,
+			})
+			.collect(),
+		_ => Vec::new(),
+	};
+	Visitors { expression_visitors_mut: vec![Box::new(Define(defined))], ..Default::default() }
+}
+
+const MAIN: &str = "import { log } from \"./log\";\nif (DEBUG) { log(\"debug\") }";
+const LOG: &str = "export function log(message) { console.log(message) }";
+
+fn build(pipeline: &mut Pipeline) -> BuildOutput {
+	let read = |path: &Path| (path == Path::new("/project/log.js")).then(|| LOG.to_owned());
+	let (_, output) = pipeline.build(
+		PathBuf::from("/project/main.js"),
+		MAIN.to_owned(),
+		&Resolver::default(),
+		&read,
+		PathBuf::from("/project/dist"),
+	);
+	output.unwrap_or_else(|_| panic!("build failed"))
+}
+
+/// Settings with the transforms from a configuration
+fn settings(configuration: &str) -> BuildSettings {
+	let configuration = Configuration::from_json(configuration, PathBuf::new()).unwrap();
+	BuildSettings { transforms: configuration.transforms.unwrap(), ..Default::default() }
+}
+
+#[test]
+fn registered_passes() {
+	let settings = settings(r#"{ "transforms": [["define", { "DEBUG": false }]] }"#);
+	let mut pipeline = Pipeline::new(settings).register(Pass::new("define", define));
+	assert!(pipeline.names().any(|name| name == "define"));
+	assert_eq!(pipeline.validate(), Ok(()));
+
+	let output = build(&mut pipeline);
+	let main = &output.outputs[0];
+	assert_eq!(main.output_path, "/project/dist/main.js");
+	assert!(main.content.contains("if (false) {"), "{}", main.content);
+
+	let [diagnostic] = output.temp_warnings_and_infos.as_slice() else {
+		panic!("expected one diagnostic");
+	};
+	assert_eq!(diagnostic.label, "Replaced 'DEBUG'");
+	assert_eq!(diagnostic.code.code(), "D0001");
+}
+
+#[test]
+fn hooks() {
+	let files = Rc::new(RefCell::new(Vec::new()));
+	let before = files.clone();
+	let after = files.clone();
+	let mut pipeline = Pipeline::new(BuildSettings::default())
+		.before_each_file(move |file| {
+			let name = file.path.file_name().unwrap().to_string_lossy();
+			before.borrow_mut().push(format!("before {name}"));
+		})
+		.after_each_file(move |file| {
+			let name = file.path.file_name().unwrap().to_string_lossy();
+			after.borrow_mut().push(format!("after {name} {}", file.module.items.len()));
+			file.diagnostics.push(TempDiagnostic {
+				label: format!("Built {name}"),
+				position: parser::Span::NULL_SPAN,
+				kind: ErrorWarningInfo::Info,
+				code: DiagnosticCode::Pass("H0001"),
+				secondary_labels: Vec::new(),
+				notes: Vec::new(),
+			});
+		});
+
+	let output = build(&mut pipeline);
+	assert_eq!(
+		*files.borrow(),
+		["before main.js", "after main.js 2", "before log.js", "after log.js 1"]
+	);
+	let labels = output
+		.temp_warnings_and_infos
+		.iter()
+		.map(|diagnostic| &diagnostic.label)
+		.collect::<Vec<_>>();
+	assert_eq!(labels, ["Built main.js", "Built log.js"]);
+}
+
+#[test]
+fn replacing_passes() {
+	let settings =
+		BuildSettings { transforms: vec![Transform::InvertTernaryBranches], ..Default::default() };
+	let mut pipeline = Pipeline::new(settings)
+		.register(Pass::new("invert-ternary-branches", |_| Visitors::default()));
+	assert_eq!(pipeline.names().filter(|name| *name == "invert-ternary-branches").count(), 1);
+
+	let read = |_: &Path| None;
+	let (_, output) = pipeline.build(
+		PathBuf::from("main.js"),
+		"if (x) {\n    console.log(!a ? b : c)\n}".to_owned(),
+		&Resolver::default(),
+		&read,
+		PathBuf::from("out.js"),
+	);
+	let output = output.unwrap_or_else(|_| panic!("build failed"));
+	assert!(output.outputs[0].content.contains("!a ? b : c"), "{}", output.outputs[0].content);
+	assert!(output.temp_warnings_and_infos.is_empty());
+}
+
+#[test]
+fn unknown_transforms() {
+	let pipeline = Pipeline::new(settings(r#"{ "transforms": ["minify"] }"#));
+	assert_eq!(
+		pipeline.validate(),
+		Err("Unknown transform 'minify', expected one of erase-types, inline-enums, invert-ternary-branches, lower-jsx, lower-decorators, lower-extras, lower-logical-assignment, lower-optional-chaining, lower-async-iteration, lower-async-functions, lower-exponentiation, lower-object-spread, lower-for-of, lower-destructuring, lower-template-literals, lower-object-literals, lower-classes, lower-spread, lower-arrow-functions, lower-generators, lower-parameters, lower-block-scoping, inline-constants, fold-constants, remove-dead-code, collapse-conditionals, mangle-names".to_owned())
+	);
+
+	let output = Command::new(env!("CARGO_BIN_EXE_ezno"))
+		.current_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/extras"))
+		.args(["build", "operators.js", "--transform", "define"])
+		.output()
+		.unwrap();
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	assert!(
+		stdout.starts_with("Unknown transform 'define', expected one of erase-types"),
+		"{stdout}"
+	);
+}
+
+#[test]
+fn rewritten_specifiers() {
+	let main = "import { a } from \"./a.ts\";\nimport { b } from \"./b\";\nimport c from \"./lib\";\nconsole.log(a, b, c, await import(\"./b\"));";
+	let read = |path: &Path| match path.to_str()? {
+		"/project/a.ts" => Some("export const a = 1;".to_owned()),
+		"/project/b.mts" => Some("export const b = 2;".to_owned()),
+		"/project/lib/index.ts" => Some("export default 3;".to_owned()),
+		_ => None,
+	};
+	let mut pipeline = Pipeline::new(BuildSettings::default());
+	let (_, output) = pipeline.build(
+		PathBuf::from("/project/main.ts"),
+		main.to_owned(),
+		&Resolver::default(),
+		&read,
+		PathBuf::from("/project/dist"),
+	);
+	let output = output.unwrap_or_else(|_| panic!("build failed"));
+	let paths = output.outputs.iter().map(|output| output.output_path.as_str()).collect::<Vec<_>>();
+	assert_eq!(
+		paths,
+		[
+			"/project/dist/main.js",
+			"/project/dist/a.js",
+			"/project/dist/b.mjs",
+			"/project/dist/lib/index.js"
+		]
+	);
+	let content = &output.outputs[0].content;
+	assert!(content.contains("from \"./a.js\";"), "{content}");
+	assert!(content.contains("from \"./b.mjs\";"), "{content}");
+	assert!(content.contains("from \"./lib/index.js\";"), "{content}");
+	assert!(content.contains("import(\"./b.mjs\")"), "{content}");
+}
@@ -0,0 +1,151 @@
+use std::path::{Path, PathBuf};
+
+use ezno_lib::resolver::{ResolveError, Resolver};
+
+fn fixture() -> PathBuf {
+	Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/resolver")
+}
+
+fn read(path: &Path) -> Option<String> {
+	std::fs::read_to_string(path).ok()
+}
+
+/// Resolves from `src/index.ts` in the fixture, returning the path relative to the fixture
+fn resolve(resolver: &Resolver, specifier: &str) -> Result<String, ResolveError> {
+	let importer = fixture().join("src/index.ts");
+	resolver.resolve(specifier, &importer, &read).map(|resolved| {
+		resolved.path.strip_prefix(fixture()).unwrap().display().to_string().replace('\\', "/")
+	})
+}
+
+fn project_resolver() -> Resolver {
+	Resolver::default().with_ts_config(&fixture().join("tsconfig.json"), &read).unwrap()
+}
+
+fn not_found(specifier: &str) -> Result<String, ResolveError> {
+	Err(ResolveError::NotFound { specifier: specifier.to_owned() })
+}
+
+#[test]
+fn relative_paths() {
+	let resolver = Resolver::default();
+
+	assert_eq!(resolve(&resolver, "./a"), Ok("src/a.ts".to_owned()));
+	assert_eq!(resolve(&resolver, "./a.ts"), Ok("src/a.ts".to_owned()));
+	assert_eq!(resolve(&resolver, "./b"), Ok("src/b.tsx".to_owned()));
+	assert_eq!(resolve(&resolver, "./c"), Ok("src/c.js".to_owned()));
+	assert_eq!(resolve(&resolver, "./types"), Ok("src/types.d.ts".to_owned()));
+	assert_eq!(resolve(&resolver, "./compiled.js"), Ok("src/compiled.ts".to_owned()));
+	assert_eq!(resolve(&resolver, "./folder"), Ok("src/folder/index.ts".to_owned()));
+	assert_eq!(resolve(&resolver, "./legacy"), Ok("src/legacy/main.ts".to_owned()));
+	assert_eq!(resolve(&resolver, "../src/utils/format"), Ok("src/utils/format.ts".to_owned()));
+	assert_eq!(resolve(&resolver, "./missing"), not_found("./missing"));
+}
+
+#[test]
+fn node_modules() {
+	let resolver = Resolver::default();
+
+	assert_eq!(resolve(&resolver, "plain"), Ok("node_modules/plain/lib/main.js".to_owned()));
+	assert_eq!(
+		resolve(&resolver, "plain/lib/extra"),
+		Ok("node_modules/plain/lib/extra.js".to_owned())
+	);
+	assert_eq!(resolve(&resolver, "typed"), Ok("node_modules/typed/index.js".to_owned()));
+	assert_eq!(resolve(&resolver, "loose"), Ok("node_modules/loose/index.js".to_owned()));
+	assert_eq!(resolve(&resolver, "untyped"), not_found("untyped"));
+	assert_eq!(resolve(&resolver, "missing"), not_found("missing"));
+}
+
+#[test]
+fn package_exports() {
+	let resolver = Resolver::default();
+
+	assert_eq!(
+		resolve(&resolver, "@scope/pkg"),
+		Ok("node_modules/@scope/pkg/dist/index.mjs".to_owned())
+	);
+	assert_eq!(
+		resolve(&resolver, "@scope/pkg/feature/x"),
+		Ok("node_modules/@scope/pkg/dist/feature/x.mjs".to_owned())
+	);
+	assert_eq!(
+		resolve(&resolver, "@scope/pkg/feature/private/y"),
+		Err(ResolveError::NotExported {
+			package: "@scope/pkg".to_owned(),
+			subpath: "./feature/private/y".to_owned()
+		})
+	);
+	assert_eq!(
+		resolve(&resolver, "@scope/pkg/dist/index.mjs"),
+		Err(ResolveError::NotExported {
+			package: "@scope/pkg".to_owned(),
+			subpath: "./dist/index.mjs".to_owned()
+		})
+	);
+
+	let require_resolver =
+		Resolver { conditions: vec!["require".to_owned()], ..Default::default() };
+	assert_eq!(
+		resolve(&require_resolver, "@scope/pkg"),
+		Ok("node_modules/@scope/pkg/dist/index.cjs".to_owned())
+	);
+}
+
+#[test]
+fn types_condition() {
+	let resolver = Resolver {
+		conditions: vec!["types".to_owned(), "import".to_owned()],
+		..Default::default()
+	};
+
+	assert_eq!(resolve(&resolver, "typed"), Ok("node_modules/typed/index.d.ts".to_owned()));
+	assert_eq!(
+		resolve(&resolver, "@scope/pkg"),
+		Ok("node_modules/@scope/pkg/dist/index.d.ts".to_owned())
+	);
+	assert_eq!(
+		resolve(&resolver, "untyped"),
+		Ok("node_modules/@types/untyped/index.d.ts".to_owned())
+	);
+}
+
+#[test]
+fn package_imports() {
+	let resolver = Resolver::default();
+
+	assert_eq!(resolve(&resolver, "#internal/secret"), Ok("src/internal/secret.ts".to_owned()));
+	assert_eq!(resolve(&resolver, "#fallback"), Ok("src/internal/fallback.ts".to_owned()));
+	assert_eq!(resolve(&resolver, "#plain"), Ok("node_modules/plain/lib/main.js".to_owned()));
+	assert_eq!(resolve(&resolver, "#missing"), not_found("#missing"));
+
+	let node_resolver = Resolver { conditions: vec!["node".to_owned()], ..Default::default() };
+	assert_eq!(resolve(&node_resolver, "#fallback"), Ok("src/internal/node.ts".to_owned()));
+}
+
+#[test]
+fn ts_config_paths() {
+	let resolver = project_resolver();
+
+	assert_eq!(resolve(&resolver, "@utils/format"), Ok("src/utils/format.ts".to_owned()));
+	assert_eq!(resolve(&resolver, "~config"), Ok("src/config/index.ts".to_owned()));
+	// `baseUrl` from the extended configuration
+	assert_eq!(resolve(&resolver, "shared"), Ok("src/shared.ts".to_owned()));
+	assert_eq!(resolve(&resolver, "plain"), Ok("node_modules/plain/lib/main.js".to_owned()));
+
+	assert_eq!(resolve(&Resolver::default(), "@utils/format"), not_found("@utils/format"));
+}
+
+#[test]
+fn find_ts_config() {
+	assert_eq!(
+		Resolver::find_ts_config(&fixture().join("src/folder"), &read),
+		Some(fixture().join("tsconfig.json"))
+	);
+
+	let missing = fixture().join("missing.json");
+	assert!(matches!(
+		Resolver::default().with_ts_config(&missing, &read),
+		Err(ResolveError::InvalidConfig { path, .. }) if path == missing
+	));
+}
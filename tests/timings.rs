@@ -0,0 +1,94 @@
+use std::{path::Path, process::Command};
+
+/// Runs `ezno build` on the timings fixture, returning stderr
+fn build(arguments: &[&str]) -> String {
+	let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/timings");
+	let output = Command::new(env!("CARGO_BIN_EXE_ezno"))
+		.current_dir(fixture)
+		.args(["build", "main.ts"])
+		.args(arguments)
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+	String::from_utf8(output.stderr).unwrap()
+}
+
+#[test]
+fn table() {
+	let output = build(&["--timings"]);
+	let lines = output.lines().collect::<Vec<_>>();
+	let columns = lines[0].split_whitespace().collect::<Vec<_>>();
+	assert_eq!(
+		columns,
+		[
+			"module",
+			"bytes",
+			"nodes",
+			"lexing",
+			"parsing",
+			"resolving",
+			"erase-types",
+			"invert-ternary-branches",
+			"rewriting",
+			"imports",
+			"printing",
+			"source",
+			"map"
+		]
+	);
+	assert!(lines[1].starts_with("main.ts "));
+	assert!(lines[2].starts_with("utilities.ts "));
+	assert!(lines[3].starts_with("total "));
+	assert!(lines[4].starts_with("Built in "));
+	assert_eq!(lines[4].contains("peak memory"), cfg!(feature = "memory-statistics"));
+}
+
+#[test]
+fn json() {
+	let output = build(&["--timings-format", "json"]);
+	assert!(output.starts_with("{\"total\":"));
+	assert!(output.contains(
+		"\"path\":\"utilities.ts\",\"size\":69,\"nodes\":{\"statements\":1,\"declarations\":1,\"expressions\":3,\"jsxElements\":0,\"variables\":1,\"total\":6}"
+	));
+	let phases = output
+		.split("\"name\":\"")
+		.skip(1)
+		.map(|rest| &rest[..rest.find('"').unwrap()])
+		.collect::<Vec<_>>();
+	let expected = [
+		"lexing",
+		"parsing",
+		"resolving",
+		"erase-types",
+		"invert-ternary-branches",
+		"rewriting imports",
+		"printing",
+		"source map",
+	];
+	assert_eq!(phases, [expected, expected].concat());
+}
+
+#[test]
+fn chrome_trace_to_file() {
+	let directory = std::env::temp_dir().join("ezno-timings");
+	std::fs::create_dir_all(&directory).unwrap();
+	let trace = directory.join("trace.json");
+	let output = build(&[
+		directory.join("bundle.js").to_str().unwrap(),
+		"--bundle",
+		"--timings-format",
+		"chrome-trace",
+		"--timings-output",
+		trace.to_str().unwrap(),
+	]);
+	assert!(output.is_empty());
+	let trace = std::fs::read_to_string(trace).unwrap();
+	assert!(
+		trace.starts_with("{\"traceEvents\":[{\"name\":\"build\",\"cat\":\"build\",\"ph\":\"X\"")
+	);
+	assert!(trace.contains("\"name\":\"parsing utilities.ts\""));
+	assert!(trace.contains("\"name\":\"renaming main.ts\""));
+	assert!(trace.contains("\"name\":\"linking "));
+	assert!(trace.contains("bundle.js\",\"peakMemory\""));
+	assert!(trace.ends_with("\"displayTimeUnit\":\"ms\"}"));
+}
@@ -0,0 +1,78 @@
+use std::{path::Path, process::Command};
+
+/// Bundles `main.js` in the fixture, returning the output (printed as there is no output path)
+fn bundle(arguments: &[&str]) -> String {
+	let output = Command::new(env!("CARGO_BIN_EXE_ezno"))
+		.current_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tree-shaking"))
+		.args(["build", "main.js", "--bundle"])
+		.args(arguments)
+		.output()
+		.unwrap();
+	assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+	String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn removes_unused_declarations() {
+	let output = bundle(&[]);
+	assert_eq!(
+		output.trim_end(),
+		r#"// helper.js
+function helper(value) {
+    return value + 1
+}
+// utilities.js
+function used(value) {
+    return helper(value) * 2
+}
+const registered = register("utilities");
+class Shape {
+    static sides = 0
+}
+console.log("utilities loaded");
+// lib/format.js
+function format(value, shape) {
+    return `${value} ${shape}`
+}
+console.log("format loaded");
+// lib/polyfill.js
+globalThis.polyfilled = true;
+// main.js
+function run(value) {
+    return format(used(value), new Shape())
+}
+export { run };"#
+	);
+}
+
+#[test]
+fn side_effects_in_package() {
+	let output = bundle(&[]);
+	// `lib/unused.js` is imported but nothing from it is used and `lib/package.json` does not list
+	// it in `sideEffects`. `lib/format.js` is used so all of its statements run
+	assert!(!output.contains("never loaded"));
+	assert!(output.contains("console.log(\"format loaded\")"));
+	assert!(output.contains("globalThis.polyfilled = true"));
+}
+
+#[test]
+fn kept_without_tree_shaking() {
+	let output = bundle(&["--no-tree-shaking"]);
+	for kept in [
+		"const NAME = \"helper\"",
+		"function unused() {",
+		"const table = /*#__PURE__*/ createTable(16);",
+		"static instance = /*#__PURE__*/ new Unused()",
+		"function pad(value) {",
+		"console.log(\"never loaded\")",
+	] {
+		assert!(output.contains(kept), "{kept} in {output}");
+	}
+}
+
+#[test]
+fn purity_annotations_are_kept_when_minified() {
+	let output = bundle(&["--no-tree-shaking", "--minify"]);
+	assert!(output.contains("=/*#__PURE__*/createTable(16);"), "{output}");
+	assert!(output.contains("=/* @__PURE__ */new Map();"), "{output}");
+}